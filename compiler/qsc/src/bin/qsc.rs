@@ -3,19 +3,25 @@
 
 allocator::assign_global!();
 
-use clap::{crate_version, ArgGroup, Parser, ValueEnum};
+use clap::{crate_version, ArgGroup, Args, Parser, ValueEnum};
 use log::info;
 use miette::{Context, IntoDiagnostic, Report};
-use qsc::compile::compile;
-use qsc_codegen::qir_base;
+use qsc::{compile::compile, target::Profile};
+use qsc_codegen::{
+    qasm3::{self, fir_to_qasm3},
+    qir::{fir_to_rir, rir_to_qir, rir_to_qir_bitcode, Program},
+    qir_base,
+};
 use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
 use qsc_frontend::{
     compile::{PackageStore, SourceContents, SourceMap, SourceName},
     error::WithSource,
 };
 use qsc_hir::hir::{Package, PackageId};
-use qsc_passes::PackageType;
+use qsc_partial_eval::ProgramEntry;
+use qsc_passes::{PackageType, PassContext};
 use qsc_project::{FileSystem, Manifest, StdFs};
+use qsc_rca::{Analyzer, PackageStoreComputeProperties};
 use std::{
     concat, fs,
     io::{self, Read},
@@ -59,12 +65,34 @@ struct Cli {
     /// Language features to compile with
     #[arg(short, long)]
     features: Vec<String>,
+
+    /// Target profile to compile for: `base`, `quantinuum` or `unrestricted`. Defaults to `base`
+    /// when emitting QIR and to `quantinuum` when emitting OpenQASM.
+    #[arg(long, value_parser = parse_profile)]
+    profile: Option<Profile>,
+
+    #[command(flatten)]
+    codegen: CodegenArgs,
+}
+
+/// Passes applied to the partially evaluated program when emitting QIR.
+#[derive(Debug, Args)]
+struct CodegenArgs {
+    /// Optimize away adjacent gates that cancel out or can be merged when emitting QIR.
+    #[arg(long)]
+    optimize_gates: bool,
+
+    /// Map qubits with lifetimes that do not overlap onto the same qubit when emitting QIR.
+    #[arg(long)]
+    reuse_qubits: bool,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum Emit {
     Hir,
     Qir,
+    QirBc,
+    Qasm3,
 }
 
 fn main() -> miette::Result<ExitCode> {
@@ -73,11 +101,23 @@ fn main() -> miette::Result<ExitCode> {
     let mut store = PackageStore::new(qsc::compile::core());
     let mut dependencies = Vec::new();
 
-    let (package_type, capabilities) = if cli.emit.contains(&Emit::Qir) {
-        (PackageType::Exe, TargetCapabilityFlags::empty())
-    } else {
-        (PackageType::Lib, TargetCapabilityFlags::all())
-    };
+    let (package_type, profile) =
+        if cli.emit.contains(&Emit::Qir) || cli.emit.contains(&Emit::QirBc) {
+            (PackageType::Exe, cli.profile.unwrap_or(Profile::Base))
+        } else if cli.emit.contains(&Emit::Qasm3) {
+            (PackageType::Exe, cli.profile.unwrap_or(Profile::Quantinuum))
+        } else {
+            (
+                PackageType::Lib,
+                cli.profile.unwrap_or(Profile::Unrestricted),
+            )
+        };
+    if package_type == PackageType::Exe && profile == Profile::Unrestricted {
+        return Err(miette::miette!(
+            "code generation does not support the unrestricted profile"
+        ));
+    }
+    let capabilities = profile.into();
 
     if !cli.nostdlib {
         dependencies.push(store.insert(qsc::compile::std(&store, capabilities)));
@@ -96,6 +136,12 @@ fn main() -> miette::Result<ExitCode> {
         let manifest = Manifest::load(cli.qsharp_json)?;
         if let Some(manifest) = manifest {
             let project = fs.load_project(&manifest)?;
+            if !project.errors.is_empty() {
+                for error in project.errors {
+                    eprintln!("error: {error:?}");
+                }
+                return Ok(ExitCode::FAILURE);
+            }
             let mut project_sources = project.sources;
 
             sources.append(&mut project_sources);
@@ -125,7 +171,31 @@ fn main() -> miette::Result<ExitCode> {
             Emit::Hir => emit_hir(&unit.package, out_dir)?,
             Emit::Qir => {
                 if errors.is_empty() {
-                    emit_qir(out_dir, &store, package_id)?;
+                    emit_qir(
+                        out_dir,
+                        &store,
+                        package_id,
+                        capabilities,
+                        &cli.codegen,
+                        cli.verbose,
+                    )?;
+                }
+            }
+            Emit::QirBc => {
+                if errors.is_empty() {
+                    emit_qir_bitcode(
+                        out_dir,
+                        &store,
+                        package_id,
+                        capabilities,
+                        &cli.codegen,
+                        cli.verbose,
+                    )?;
+                }
+            }
+            Emit::Qasm3 => {
+                if errors.is_empty() {
+                    emit_qasm3(out_dir, &store, package_id, capabilities)?;
                 }
             }
         }
@@ -142,6 +212,11 @@ fn main() -> miette::Result<ExitCode> {
     }
 }
 
+fn parse_profile(s: &str) -> Result<Profile, String> {
+    s.parse()
+        .map_err(|()| format!("invalid target profile `{s}`"))
+}
+
 fn read_source(path: impl AsRef<Path>) -> miette::Result<(SourceName, SourceContents)> {
     let path = path.as_ref();
     if path.as_os_str() == "-" {
@@ -172,22 +247,159 @@ fn emit_hir(package: &Package, dir: impl AsRef<Path>) -> miette::Result<()> {
         .with_context(|| format!("could not emit HIR file `{}`", path.display()))
 }
 
-fn emit_qir(out_dir: &Path, store: &PackageStore, package_id: PackageId) -> Result<(), Report> {
+fn emit_qir(
+    out_dir: &Path,
+    store: &PackageStore,
+    package_id: PackageId,
+    capabilities: TargetCapabilityFlags,
+    codegen: &CodegenArgs,
+    verbose: bool,
+) -> Result<(), Report> {
     let path = out_dir.join("qir.ll");
-    let result = qir_base::generate_qir(store, package_id);
-    match result {
-        Ok(qir) => {
+    // Gate optimization and qubit reuse run on the partially evaluated program, so the base
+    // profile generator is only used when neither is enabled.
+    let qir = if capabilities == TargetCapabilityFlags::empty()
+        && !codegen.optimize_gates
+        && !codegen.reuse_qubits
+    {
+        qir_base::generate_qir(store, package_id).map_err(|(error, _)| {
+            let unit = store.get(package_id).expect("package should be in store");
+            Report::new(WithSource::from_map(&unit.sources, error))
+        })?
+    } else {
+        let program = generate_rir(store, package_id, capabilities, codegen, verbose)?;
+        rir_to_qir(&program)
+    };
+    info!(
+        "Writing QIR output file to: {}",
+        path.to_str().unwrap_or_default()
+    );
+    fs::write(&path, qir)
+        .into_diagnostic()
+        .with_context(|| format!("could not emit QIR file `{}`", path.display()))
+}
+
+fn emit_qir_bitcode(
+    out_dir: &Path,
+    store: &PackageStore,
+    package_id: PackageId,
+    capabilities: TargetCapabilityFlags,
+    codegen: &CodegenArgs,
+    verbose: bool,
+) -> Result<(), Report> {
+    let path = out_dir.join("qir.bc");
+    let program = generate_rir(store, package_id, capabilities, codegen, verbose)?;
+    let bitcode = rir_to_qir_bitcode(&program);
+    info!(
+        "Writing QIR bitcode output file to: {}",
+        path.to_str().unwrap_or_default()
+    );
+    fs::write(&path, bitcode)
+        .into_diagnostic()
+        .with_context(|| format!("could not emit QIR bitcode file `{}`", path.display()))
+}
+
+/// Partially evaluates the entry expression of the package into RIR, applying the requested passes.
+fn generate_rir(
+    store: &PackageStore,
+    package_id: PackageId,
+    capabilities: TargetCapabilityFlags,
+    codegen: &CodegenArgs,
+    verbose: bool,
+) -> Result<Program, Report> {
+    let (fir_store, compute_properties, entry) =
+        lower_for_codegen(store, package_id, capabilities)?;
+    match fir_to_rir(
+        &fir_store,
+        capabilities,
+        codegen.optimize_gates,
+        codegen.reuse_qubits,
+        Some(compute_properties),
+        &entry,
+    ) {
+        Ok((program, reuse_report)) => {
+            if let Some(report) = reuse_report.filter(|_| verbose) {
+                eprintln!("note: qubit reuse: {report}");
+            }
+            Ok(program)
+        }
+        Err(error) => {
+            // The error may originate in a library package, so report it against that package's sources.
+            let unit = store
+                .get(error.span().package)
+                .expect("package should be in store");
+            Err(Report::new(WithSource::from_map(&unit.sources, error)))
+        }
+    }
+}
+
+fn emit_qasm3(
+    out_dir: &Path,
+    store: &PackageStore,
+    package_id: PackageId,
+    capabilities: TargetCapabilityFlags,
+) -> Result<(), Report> {
+    let path = out_dir.join("qasm3.qasm");
+    let (fir_store, compute_properties, entry) =
+        lower_for_codegen(store, package_id, capabilities)?;
+    match fir_to_qasm3(&fir_store, capabilities, Some(compute_properties), &entry) {
+        Ok(qasm) => {
             info!(
-                "Writing QIR output file to: {}",
+                "Writing OpenQASM output file to: {}",
                 path.to_str().unwrap_or_default()
             );
-            fs::write(&path, qir)
+            fs::write(&path, qasm)
                 .into_diagnostic()
-                .with_context(|| format!("could not emit QIR file `{}`", path.display()))
+                .with_context(|| format!("could not emit OpenQASM file `{}`", path.display()))
         }
-        Err((error, _)) => {
-            let unit = store.get(package_id).expect("package should be in store");
+        Err(qasm3::Error::PartialEvaluation(error)) => {
+            // The error may originate in a library package, so report it against that package's sources.
+            let unit = store
+                .get(error.span().package)
+                .expect("package should be in store");
             Err(Report::new(WithSource::from_map(&unit.sources, error)))
         }
+        Err(error) => Err(Report::new(error)),
     }
 }
+
+/// Lowers the package to FIR and analyzes it for the given capabilities, returning everything
+/// needed to partially evaluate its entry expression.
+fn lower_for_codegen(
+    store: &PackageStore,
+    package_id: PackageId,
+    capabilities: TargetCapabilityFlags,
+) -> Result<
+    (
+        qsc_fir::fir::PackageStore,
+        PackageStoreComputeProperties,
+        ProgramEntry,
+    ),
+    Report,
+> {
+    let unit = store.get(package_id).expect("package should be in store");
+    let (fir_store, fir_package_id) = qsc_passes::lower_hir_to_fir(store, package_id);
+    let compute_properties = if capabilities == TargetCapabilityFlags::empty() {
+        // The base profile check already ran during compilation, so only the analysis is needed.
+        Analyzer::init(&fir_store).analyze_all()
+    } else {
+        PassContext::run_fir_passes_on_fir(&fir_store, fir_package_id, capabilities).map_err(
+            |errors| {
+                let error = errors.into_iter().next().expect("there should be an error");
+                Report::new(WithSource::from_map(&unit.sources, error))
+            },
+        )?
+    };
+    let package = fir_store.get(fir_package_id);
+    let entry = ProgramEntry {
+        exec_graph: package.entry_exec_graph.clone(),
+        expr: (
+            fir_package_id,
+            package
+                .entry
+                .expect("package must have an entry expression"),
+        )
+            .into(),
+    };
+    Ok((fir_store, compute_properties, entry))
+}
@@ -92,6 +92,12 @@ fn main() -> miette::Result<ExitCode> {
         let manifest = Manifest::load(cli.qsharp_json)?;
         if let Some(manifest) = manifest {
             let project = fs.load_project(&manifest)?;
+            if !project.errors.is_empty() {
+                for error in project.errors {
+                    eprintln!("error: {error:?}");
+                }
+                return Ok(ExitCode::FAILURE);
+            }
             let mut project_sources = project.sources;
 
             sources.append(&mut project_sources);
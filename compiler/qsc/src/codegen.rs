@@ -4,20 +4,113 @@
 #[cfg(test)]
 mod tests;
 
-use qsc_codegen::qir::fir_to_qir;
+pub use qsc_codegen::qir::QubitReuseReport;
+use qsc_codegen::{
+    qasm3::{self, fir_to_qasm3},
+    qir::{fir_to_qir, fir_to_qir_bitcode},
+};
 use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
 use qsc_frontend::compile::{PackageStore, SourceMap};
 use qsc_partial_eval::ProgramEntry;
 use qsc_passes::{PackageType, PassContext};
-use qsc_rca::Analyzer;
+use qsc_rca::{Analyzer, PackageStoreComputeProperties};
 
-use crate::compile;
+use crate::{
+    compile,
+    error::{from_partial_eval, WithSource},
+    interpret::Error,
+};
 
+/// Compiles the given sources and generates QIR for their entry point.
+/// When `optimize_gates` is set, adjacent gates that cancel out or can be merged are optimized away.
+/// When `reuse_qubits` is set, qubits with lifetimes that do not overlap are mapped onto the same qubit ID.
 pub fn get_qir(
     sources: SourceMap,
     language_features: LanguageFeatures,
     capabilities: TargetCapabilityFlags,
-) -> Result<String, String> {
+    optimize_gates: bool,
+    reuse_qubits: bool,
+) -> Result<String, Vec<Error>> {
+    let (package_store, fir_store, compute_properties, entry) =
+        compile_for_codegen(sources, language_features, capabilities)?;
+    fir_to_qir(
+        &fir_store,
+        capabilities,
+        optimize_gates,
+        reuse_qubits,
+        Some(compute_properties),
+        &entry,
+    )
+    .map_err(|e| {
+        vec![Error::PartialEvaluation(from_partial_eval(
+            e,
+            &package_store,
+        ))]
+    })
+}
+
+/// Compiles the given sources and generates QIR bitcode for their entry point.
+/// When `optimize_gates` is set, adjacent gates that cancel out or can be merged are optimized away.
+/// When `reuse_qubits` is set, qubits with lifetimes that do not overlap are mapped onto the same qubit ID.
+pub fn get_qir_bitcode(
+    sources: SourceMap,
+    language_features: LanguageFeatures,
+    capabilities: TargetCapabilityFlags,
+    optimize_gates: bool,
+    reuse_qubits: bool,
+) -> Result<Vec<u8>, Vec<Error>> {
+    let (package_store, fir_store, compute_properties, entry) =
+        compile_for_codegen(sources, language_features, capabilities)?;
+    fir_to_qir_bitcode(
+        &fir_store,
+        capabilities,
+        optimize_gates,
+        reuse_qubits,
+        Some(compute_properties),
+        &entry,
+    )
+    .map_err(|e| {
+        vec![Error::PartialEvaluation(from_partial_eval(
+            e,
+            &package_store,
+        ))]
+    })
+}
+
+/// Compiles the given sources and generates an OpenQASM 3 program for their entry point.
+pub fn get_qasm3(
+    sources: SourceMap,
+    language_features: LanguageFeatures,
+    capabilities: TargetCapabilityFlags,
+) -> Result<String, Vec<Error>> {
+    let (package_store, fir_store, compute_properties, entry) =
+        compile_for_codegen(sources, language_features, capabilities)?;
+    fir_to_qasm3(&fir_store, capabilities, Some(compute_properties), &entry).map_err(|e| match e {
+        qasm3::Error::PartialEvaluation(e) => {
+            vec![Error::PartialEvaluation(from_partial_eval(
+                e,
+                &package_store,
+            ))]
+        }
+        e => vec![Error::Qasm3(e)],
+    })
+}
+
+/// Compiles the given sources as an executable and lowers them to FIR, returning everything
+/// needed to partially evaluate the entry point and to report errors against the sources.
+fn compile_for_codegen(
+    sources: SourceMap,
+    language_features: LanguageFeatures,
+    capabilities: TargetCapabilityFlags,
+) -> Result<
+    (
+        PackageStore,
+        qsc_fir::fir::PackageStore,
+        PackageStoreComputeProperties,
+        ProgramEntry,
+    ),
+    Vec<Error>,
+> {
     let core = compile::core();
     let mut package_store = PackageStore::new(core);
     let std = compile::std(&package_store, capabilities);
@@ -34,15 +127,9 @@ pub fn get_qir(
 
     // Ensure it compiles before trying to add it to the store.
     if !errors.is_empty() {
-        // This will happen when QIR generation is attempted on a program that has errors.
+        // This will happen when code generation is attempted on a program that has errors.
         // This can happen in the playground.
-        let mut error_message =
-            String::from("Failed to generate QIR. Could not compile sources.:\n");
-        for error in errors {
-            error_message.push_str(&format!("{error}\n"));
-        }
-
-        return Err(error_message);
+        return Err(errors.into_iter().map(Error::Compile).collect());
     }
 
     let package_id = package_store.insert(unit);
@@ -67,12 +154,16 @@ pub fn get_qir(
         PassContext::run_fir_passes_on_fir(&fir_store, fir_package_id, capabilities)
     };
 
-    let Ok(compute_properties) = compute_properties else {
-        // This should never happen, as the program should be checked for errors before trying to
-        // generate code for it. But just in case, simply report the failure.
-        return Err("Failed to generate QIR. Could not generate compute properties.".to_string());
-    };
+    let compute_properties = compute_properties.map_err(|errors| {
+        let sources = &package_store
+            .get(package_id)
+            .expect("package should be in store")
+            .sources;
+        errors
+            .into_iter()
+            .map(|error| Error::Pass(WithSource::from_map(sources, error)))
+            .collect::<Vec<_>>()
+    })?;
 
-    fir_to_qir(&fir_store, capabilities, Some(compute_properties), &entry)
-        .map_err(|e| e.to_string())
+    Ok((package_store, fir_store, compute_properties, entry))
 }
@@ -5,7 +5,39 @@ use expect_test::expect;
 use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
 use qsc_frontend::compile::SourceMap;
 
-use crate::codegen::get_qir;
+use crate::{
+    codegen::{get_qasm3, get_qir, get_qir_bitcode},
+    interpret::Error,
+};
+use miette::Diagnostic;
+use std::{fmt::Write, iter, str::from_utf8};
+
+/// Renders each error with its chain of causes and the source text each of its labels points to.
+fn render_errors(errors: &[Error]) -> String {
+    let mut rendered = String::new();
+    for error in errors {
+        write!(rendered, "{error}").expect("writing should succeed");
+        for source in iter::successors(std::error::Error::source(error), |&s| s.source()) {
+            write!(rendered, ": {source}").expect("writing should succeed");
+        }
+        for label in error.labels().into_iter().flatten() {
+            let span = error
+                .source_code()
+                .expect("expected valid source code")
+                .read_span(label.inner(), 0, 0)
+                .expect("expected to be able to read span");
+            write!(
+                rendered,
+                "\n  [{}] [{}]",
+                span.name().expect("expected source file name"),
+                from_utf8(span.data()).expect("expected valid utf-8 string"),
+            )
+            .expect("writing should succeed");
+        }
+        writeln!(rendered).expect("writing should succeed");
+    }
+    rendered
+}
 
 #[test]
 fn code_with_errors_returns_errors() {
@@ -20,12 +52,695 @@ fn code_with_errors_returns_errors() {
     let language_features = LanguageFeatures::default();
     let capabilities = TargetCapabilityFlags::empty();
 
+    let errors = get_qir(sources, language_features, capabilities, false, false)
+        .expect_err("QIR generation should fail");
+    expect![[r"
+        syntax error: expected `;`, found keyword `let`
+          [test.qs] [let]
+    "]]
+    .assert_eq(&render_errors(&errors));
+}
+
+#[test]
+fn qasm3_for_adaptive_program() {
+    let source = "namespace Test {
+            @EntryPoint()
+            operation Main() : Result[] {
+                use (q0, q1) = (Qubit(), Qubit());
+                H(q0);
+                CNOT(q0, q1);
+                Rzz(1.5, q0, q1);
+                [MResetZ(q0), MResetZ(q1)]
+            }
+        }";
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let language_features = LanguageFeatures::default();
+    let capabilities = TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::QubitReset;
+
+    let qasm = get_qasm3(sources, language_features, capabilities)
+        .expect("OpenQASM generation should succeed");
     expect![[r#"
-        Err(
-            "Failed to generate QIR. Could not compile sources.:\nsyntax error\n",
-        )
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        gate rzz(theta) a, b { cx a, b; rz(theta) b; cx a, b; }
+        output bit output_0;
+        output bit output_1;
+        qubit[2] q;
+        bit[2] c;
+        h q[0];
+        cx q[0], q[1];
+        rzz(1.5) q[0], q[1];
+        c[0] = measure q[0];
+        reset q[0];
+        c[1] = measure q[1];
+        reset q[1];
+        output_0 = c[0];
+        output_1 = c[1];
     "#]]
-    .assert_debug_eq(&get_qir(sources, language_features, capabilities));
+    .assert_eq(&qasm);
+}
+
+#[test]
+fn qasm3_for_program_with_unsupported_intrinsic_returns_error() {
+    let source = "namespace Test {
+            operation Custom(q : Qubit) : Unit {
+                body intrinsic;
+            }
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                Custom(q);
+                MResetZ(q)
+            }
+        }";
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let language_features = LanguageFeatures::default();
+    let capabilities = TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::QubitReset;
+
+    let errors = get_qasm3(sources, language_features, capabilities)
+        .expect_err("OpenQASM generation should fail");
+    expect![[r"
+        intrinsic `Custom` is not supported in OpenQASM 3
+    "]]
+    .assert_eq(&render_errors(&errors));
+}
+
+#[test]
+fn qir_bitcode_for_adaptive_program() {
+    let source = "namespace Test {
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                H(q);
+                MResetZ(q)
+            }
+        }";
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let language_features = LanguageFeatures::default();
+    let capabilities = TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::QubitReset;
+
+    let bitcode = get_qir_bitcode(sources, language_features, capabilities, false, false)
+        .expect("QIR bitcode generation should succeed");
+    assert!(bitcode.starts_with(b"BC\xC0\xDE"));
+    assert_eq!(bitcode.len() % 4, 0, "bitcode should be word aligned");
+}
+
+#[test]
+fn qasm3_for_loop_with_measurement_dependent_condition() {
+    let source = "namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                use q = Qubit();
+                mutable attempts = 0;
+                mutable done = false;
+                while not done {
+                    H(q);
+                    set attempts += 1;
+                    set done = MResetZ(q) == One;
+                }
+                attempts
+            }
+        }";
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let language_features = LanguageFeatures::default();
+    let capabilities = TargetCapabilityFlags::Adaptive
+        | TargetCapabilityFlags::QubitReset
+        | TargetCapabilityFlags::IntegerComputations
+        | TargetCapabilityFlags::BackwardsBranching;
+
+    let qasm = get_qasm3(sources, language_features, capabilities)
+        .expect("OpenQASM generation should succeed");
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        output int[64] output_0;
+        qubit[1] q;
+        bit[1] c;
+        bool var_2;
+        int[64] var_3;
+        bool var_4;
+        bool var_5;
+        bool var_6;
+        int[64] var_7;
+        var_6 = false;
+        var_7 = 0;
+        while (true) {
+            var_2 = !var_6;
+            if (!var_2) {
+                break;
+            }
+            h q[0];
+            var_3 = var_7 + 1;
+            c[0] = measure q[0];
+            reset q[0];
+            var_4 = bool(c[0]);
+            var_5 = var_4 == true;
+            var_6 = var_5;
+            var_7 = var_3;
+        }
+        output_0 = var_7;
+    "#]]
+    .assert_eq(&qasm);
+}
+
+#[test]
+fn qir_for_loop_with_measurement_dependent_condition() {
+    let source = "namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                use q = Qubit();
+                mutable attempts = 0;
+                mutable done = false;
+                while not done {
+                    H(q);
+                    set attempts += 1;
+                    set done = MResetZ(q) == One;
+                }
+                attempts
+            }
+        }";
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let language_features = LanguageFeatures::default();
+    let capabilities = TargetCapabilityFlags::Adaptive
+        | TargetCapabilityFlags::QubitReset
+        | TargetCapabilityFlags::IntegerComputations
+        | TargetCapabilityFlags::BackwardsBranching;
+
+    let qir = get_qir(sources, language_features, capabilities, false, false)
+        .expect("Failed to generate QIR");
+    expect![[r#"
+        %Result = type opaque
+        %Qubit = type opaque
+
+        define void @ENTRYPOINT__main() #0 {
+        block_0:
+          br label %block_1
+        block_1:
+          %var_6 = phi i1 [false, %block_0], [%var_5, %block_2]
+          %var_7 = phi i64 [0, %block_0], [%var_3, %block_2]
+          %var_2 = xor i1 %var_6, true
+          br i1 %var_2, label %block_2, label %block_3
+        block_2:
+          call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          %var_3 = add i64 %var_7, 1
+          call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+          %var_4 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+          %var_5 = icmp eq i1 %var_4, true
+          br label %block_1
+        block_3:
+          call void @__quantum__rt__integer_record_output(i64 %var_7, i8* null)
+          ret void
+        }
+
+        declare void @__quantum__qis__h__body(%Qubit*)
+
+        declare void @__quantum__qis__mresetz__body(%Qubit*, %Result*) #1
+
+        declare i1 @__quantum__qis__read_result__body(%Result*)
+
+        declare void @__quantum__rt__integer_record_output(i64, i8*)
+
+        attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="1" "required_num_results"="1" }
+        attributes #1 = { "irreversible" }
+
+        ; module flags
+
+        !llvm.module.flags = !{!0, !1, !2, !3, !4, !5, !6, !7, !8, !9, !10}
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 7, !"qir_minor_version", i32 0}
+        !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+        !3 = !{i32 1, !"dynamic_result_management", i1 false}
+        !4 = !{i32 1, !"classical_ints", i1 true}
+        !5 = !{i32 1, !"backwards_branching", i1 true}
+        !6 = !{i32 1, !"qubit_resetting", i1 true}
+        !7 = !{i32 1, !"classical_floats", i1 false}
+        !8 = !{i32 1, !"classical_fixed_points", i1 false}
+        !9 = !{i32 1, !"user_functions", i1 false}
+        !10 = !{i32 1, !"multiple_target_branching", i1 false}
+    "#]].assert_eq(&qir);
+}
+
+#[test]
+fn qir_for_rotation_angle_computed_from_measurement() {
+    let source = "namespace Test {
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                H(q);
+                let theta = if MResetZ(q) == One { 1.0 } else { 0.5 };
+                Rx(2.0 * theta, q);
+                MResetZ(q)
+            }
+        }";
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let language_features = LanguageFeatures::default();
+    let capabilities = TargetCapabilityFlags::Adaptive
+        | TargetCapabilityFlags::QubitReset
+        | TargetCapabilityFlags::IntegerComputations
+        | TargetCapabilityFlags::FloatingPointComputations;
+
+    let qir = get_qir(sources, language_features, capabilities, false, false)
+        .expect("Failed to generate QIR");
+    expect![[r#"
+        %Result = type opaque
+        %Qubit = type opaque
+
+        define void @ENTRYPOINT__main() #0 {
+        block_0:
+          call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+          %var_0 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+          %var_1 = icmp eq i1 %var_0, true
+          br i1 %var_1, label %block_1, label %block_2
+        block_1:
+          br label %block_3
+        block_2:
+          br label %block_3
+        block_3:
+          %var_4 = phi double [1.0, %block_1], [0.5, %block_2]
+          %var_3 = fmul double 2.0, %var_4
+          call void @__quantum__qis__rx__body(double %var_3, %Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 1 to %Result*))
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* null)
+          ret void
+        }
+
+        declare void @__quantum__qis__h__body(%Qubit*)
+
+        declare void @__quantum__qis__mresetz__body(%Qubit*, %Result*) #1
+
+        declare i1 @__quantum__qis__read_result__body(%Result*)
+
+        declare void @__quantum__qis__rx__body(double, %Qubit*)
+
+        declare void @__quantum__rt__result_record_output(%Result*, i8*)
+
+        attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="1" "required_num_results"="2" }
+        attributes #1 = { "irreversible" }
+
+        ; module flags
+
+        !llvm.module.flags = !{!0, !1, !2, !3, !4, !5, !6, !7, !8, !9, !10}
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 7, !"qir_minor_version", i32 0}
+        !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+        !3 = !{i32 1, !"dynamic_result_management", i1 false}
+        !4 = !{i32 1, !"classical_ints", i1 true}
+        !5 = !{i32 1, !"classical_floats", i1 true}
+        !6 = !{i32 1, !"qubit_resetting", i1 true}
+        !7 = !{i32 1, !"backwards_branching", i1 false}
+        !8 = !{i32 1, !"classical_fixed_points", i1 false}
+        !9 = !{i32 1, !"user_functions", i1 false}
+        !10 = !{i32 1, !"multiple_target_branching", i1 false}
+    "#]].assert_eq(&qir);
+}
+
+#[test]
+fn qir_for_double_used_after_later_measurement_branch() {
+    let source = "namespace Test {
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                H(q);
+                let theta = if MResetZ(q) == One { 1.0 } else { 0.5 };
+                H(q);
+                if MResetZ(q) == One {
+                    X(q);
+                }
+                Rx(theta, q);
+                MResetZ(q)
+            }
+        }";
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let language_features = LanguageFeatures::default();
+    let capabilities = TargetCapabilityFlags::Adaptive
+        | TargetCapabilityFlags::QubitReset
+        | TargetCapabilityFlags::IntegerComputations
+        | TargetCapabilityFlags::FloatingPointComputations;
+
+    let qir = get_qir(sources, language_features, capabilities, false, false)
+        .expect("Failed to generate QIR");
+    expect![[r#"
+        %Result = type opaque
+        %Qubit = type opaque
+
+        define void @ENTRYPOINT__main() #0 {
+        block_0:
+          call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+          %var_0 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+          %var_1 = icmp eq i1 %var_0, true
+          br i1 %var_1, label %block_1, label %block_2
+        block_1:
+          br label %block_3
+        block_2:
+          br label %block_3
+        block_3:
+          %var_5 = phi double [1.0, %block_1], [0.5, %block_2]
+          call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 1 to %Result*))
+          %var_3 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 1 to %Result*))
+          %var_4 = icmp eq i1 %var_3, true
+          br i1 %var_4, label %block_4, label %block_5
+        block_4:
+          call void @__quantum__qis__x__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          br label %block_5
+        block_5:
+          call void @__quantum__qis__rx__body(double %var_5, %Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 2 to %Result*))
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 2 to %Result*), i8* null)
+          ret void
+        }
+
+        declare void @__quantum__qis__h__body(%Qubit*)
+
+        declare void @__quantum__qis__mresetz__body(%Qubit*, %Result*) #1
+
+        declare i1 @__quantum__qis__read_result__body(%Result*)
+
+        declare void @__quantum__qis__x__body(%Qubit*)
+
+        declare void @__quantum__qis__rx__body(double, %Qubit*)
+
+        declare void @__quantum__rt__result_record_output(%Result*, i8*)
+
+        attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="1" "required_num_results"="3" }
+        attributes #1 = { "irreversible" }
+
+        ; module flags
+
+        !llvm.module.flags = !{!0, !1, !2, !3, !4, !5, !6, !7, !8, !9, !10}
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 7, !"qir_minor_version", i32 0}
+        !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+        !3 = !{i32 1, !"dynamic_result_management", i1 false}
+        !4 = !{i32 1, !"classical_ints", i1 true}
+        !5 = !{i32 1, !"classical_floats", i1 true}
+        !6 = !{i32 1, !"qubit_resetting", i1 true}
+        !7 = !{i32 1, !"backwards_branching", i1 false}
+        !8 = !{i32 1, !"classical_fixed_points", i1 false}
+        !9 = !{i32 1, !"user_functions", i1 false}
+        !10 = !{i32 1, !"multiple_target_branching", i1 false}
+    "#]].assert_eq(&qir);
+}
+
+#[test]
+fn qir_for_integer_used_after_later_measurement_branch() {
+    let source = "namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                use q = Qubit();
+                H(q);
+                let n = if MResetZ(q) == One { 3 } else { 2 };
+                H(q);
+                if MResetZ(q) == One {
+                    X(q);
+                }
+                n
+            }
+        }";
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let language_features = LanguageFeatures::default();
+    let capabilities = TargetCapabilityFlags::Adaptive
+        | TargetCapabilityFlags::QubitReset
+        | TargetCapabilityFlags::IntegerComputations;
+
+    let qir = get_qir(sources, language_features, capabilities, false, false)
+        .expect("Failed to generate QIR");
+    expect![[r#"
+        %Result = type opaque
+        %Qubit = type opaque
+
+        define void @ENTRYPOINT__main() #0 {
+        block_0:
+          call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+          %var_0 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+          %var_1 = icmp eq i1 %var_0, true
+          br i1 %var_1, label %block_1, label %block_2
+        block_1:
+          br label %block_3
+        block_2:
+          br label %block_3
+        block_3:
+          %var_5 = phi i64 [3, %block_1], [2, %block_2]
+          call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 1 to %Result*))
+          %var_3 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 1 to %Result*))
+          %var_4 = icmp eq i1 %var_3, true
+          br i1 %var_4, label %block_4, label %block_5
+        block_4:
+          call void @__quantum__qis__x__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          br label %block_5
+        block_5:
+          call void @__quantum__rt__integer_record_output(i64 %var_5, i8* null)
+          ret void
+        }
+
+        declare void @__quantum__qis__h__body(%Qubit*)
+
+        declare void @__quantum__qis__mresetz__body(%Qubit*, %Result*) #1
+
+        declare i1 @__quantum__qis__read_result__body(%Result*)
+
+        declare void @__quantum__qis__x__body(%Qubit*)
+
+        declare void @__quantum__rt__integer_record_output(i64, i8*)
+
+        attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="1" "required_num_results"="2" }
+        attributes #1 = { "irreversible" }
+
+        ; module flags
+
+        !llvm.module.flags = !{!0, !1, !2, !3, !4, !5, !6, !7, !8, !9, !10}
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 7, !"qir_minor_version", i32 0}
+        !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+        !3 = !{i32 1, !"dynamic_result_management", i1 false}
+        !4 = !{i32 1, !"classical_ints", i1 true}
+        !5 = !{i32 1, !"qubit_resetting", i1 true}
+        !6 = !{i32 1, !"classical_floats", i1 false}
+        !7 = !{i32 1, !"backwards_branching", i1 false}
+        !8 = !{i32 1, !"classical_fixed_points", i1 false}
+        !9 = !{i32 1, !"user_functions", i1 false}
+        !10 = !{i32 1, !"multiple_target_branching", i1 false}
+    "#]].assert_eq(&qir);
+}
+
+#[test]
+fn qir_for_branch_on_integer_computed_from_measurement() {
+    let source = "namespace Test {
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                H(q);
+                let x = if MResetZ(q) == One { 3 } else { 2 };
+                if x * 2 > 5 {
+                    X(q);
+                }
+                MResetZ(q)
+            }
+        }";
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let language_features = LanguageFeatures::default();
+    let capabilities = TargetCapabilityFlags::Adaptive
+        | TargetCapabilityFlags::QubitReset
+        | TargetCapabilityFlags::IntegerComputations;
+
+    let qir = get_qir(sources, language_features, capabilities, false, false)
+        .expect("Failed to generate QIR");
+    expect![[r#"
+        %Result = type opaque
+        %Qubit = type opaque
+
+        define void @ENTRYPOINT__main() #0 {
+        block_0:
+          call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+          %var_0 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+          %var_1 = icmp eq i1 %var_0, true
+          br i1 %var_1, label %block_1, label %block_2
+        block_1:
+          br label %block_3
+        block_2:
+          br label %block_3
+        block_3:
+          %var_5 = phi i64 [3, %block_1], [2, %block_2]
+          %var_3 = mul i64 %var_5, 2
+          %var_4 = icmp sgt i64 %var_3, 5
+          br i1 %var_4, label %block_4, label %block_5
+        block_4:
+          call void @__quantum__qis__x__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          br label %block_5
+        block_5:
+          call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 1 to %Result*))
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* null)
+          ret void
+        }
+
+        declare void @__quantum__qis__h__body(%Qubit*)
+
+        declare void @__quantum__qis__mresetz__body(%Qubit*, %Result*) #1
+
+        declare i1 @__quantum__qis__read_result__body(%Result*)
+
+        declare void @__quantum__qis__x__body(%Qubit*)
+
+        declare void @__quantum__rt__result_record_output(%Result*, i8*)
+
+        attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="1" "required_num_results"="2" }
+        attributes #1 = { "irreversible" }
+
+        ; module flags
+
+        !llvm.module.flags = !{!0, !1, !2, !3, !4, !5, !6, !7, !8, !9, !10}
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 7, !"qir_minor_version", i32 0}
+        !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+        !3 = !{i32 1, !"dynamic_result_management", i1 false}
+        !4 = !{i32 1, !"classical_ints", i1 true}
+        !5 = !{i32 1, !"qubit_resetting", i1 true}
+        !6 = !{i32 1, !"classical_floats", i1 false}
+        !7 = !{i32 1, !"backwards_branching", i1 false}
+        !8 = !{i32 1, !"classical_fixed_points", i1 false}
+        !9 = !{i32 1, !"user_functions", i1 false}
+        !10 = !{i32 1, !"multiple_target_branching", i1 false}
+    "#]].assert_eq(&qir);
+}
+
+#[test]
+fn qir_for_value_known_on_every_branch_is_folded() {
+    let source = "namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                use q = Qubit();
+                mutable value = 0;
+                if MResetZ(q) == One {
+                    set value = 2;
+                } else {
+                    set value = 2;
+                }
+                value * 3 + 1
+            }
+        }";
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let language_features = LanguageFeatures::default();
+    let capabilities = TargetCapabilityFlags::Adaptive
+        | TargetCapabilityFlags::QubitReset
+        | TargetCapabilityFlags::IntegerComputations;
+
+    let qir = get_qir(sources, language_features, capabilities, false, false)
+        .expect("Failed to generate QIR");
+    expect![[r#"
+        %Result = type opaque
+        %Qubit = type opaque
+
+        define void @ENTRYPOINT__main() #0 {
+        block_0:
+          call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+          %var_0 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+          call void @__quantum__rt__integer_record_output(i64 7, i8* null)
+          ret void
+        }
+
+        declare void @__quantum__qis__mresetz__body(%Qubit*, %Result*) #1
+
+        declare i1 @__quantum__qis__read_result__body(%Result*)
+
+        declare void @__quantum__rt__integer_record_output(i64, i8*)
+
+        attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="1" "required_num_results"="1" }
+        attributes #1 = { "irreversible" }
+
+        ; module flags
+
+        !llvm.module.flags = !{!0, !1, !2, !3, !4, !5, !6, !7, !8, !9, !10}
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 7, !"qir_minor_version", i32 0}
+        !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+        !3 = !{i32 1, !"dynamic_result_management", i1 false}
+        !4 = !{i32 1, !"classical_ints", i1 true}
+        !5 = !{i32 1, !"qubit_resetting", i1 true}
+        !6 = !{i32 1, !"classical_floats", i1 false}
+        !7 = !{i32 1, !"backwards_branching", i1 false}
+        !8 = !{i32 1, !"classical_fixed_points", i1 false}
+        !9 = !{i32 1, !"user_functions", i1 false}
+        !10 = !{i32 1, !"multiple_target_branching", i1 false}
+    "#]].assert_eq(&qir);
+}
+
+#[test]
+fn qir_with_gate_optimization_cancels_and_merges_gates() {
+    let source = "namespace Test {
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                within {
+                    H(q);
+                } apply {
+                    H(q);
+                }
+                S(q);
+                Adjoint S(q);
+                Rx(0.5, q);
+                Rx(0.25, q);
+                MResetZ(q)
+            }
+        }";
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let language_features = LanguageFeatures::default();
+    let capabilities = TargetCapabilityFlags::Adaptive
+        | TargetCapabilityFlags::QubitReset
+        | TargetCapabilityFlags::IntegerComputations;
+
+    let qir = get_qir(sources, language_features, capabilities, true, false)
+        .expect("Failed to generate QIR");
+    expect![[r#"
+        %Result = type opaque
+        %Qubit = type opaque
+
+        define void @ENTRYPOINT__main() #0 {
+        block_0:
+          call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__rx__body(double 0.75, %Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+          ret void
+        }
+
+        declare void @__quantum__qis__h__body(%Qubit*)
+
+        declare void @__quantum__qis__rx__body(double, %Qubit*)
+
+        declare void @__quantum__qis__mresetz__body(%Qubit*, %Result*) #1
+
+        declare void @__quantum__rt__result_record_output(%Result*, i8*)
+
+        attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="1" "required_num_results"="1" }
+        attributes #1 = { "irreversible" }
+
+        ; module flags
+
+        !llvm.module.flags = !{!0, !1, !2, !3, !4, !5, !6, !7, !8, !9, !10}
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 7, !"qir_minor_version", i32 0}
+        !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+        !3 = !{i32 1, !"dynamic_result_management", i1 false}
+        !4 = !{i32 1, !"classical_ints", i1 true}
+        !5 = !{i32 1, !"qubit_resetting", i1 true}
+        !6 = !{i32 1, !"classical_floats", i1 false}
+        !7 = !{i32 1, !"backwards_branching", i1 false}
+        !8 = !{i32 1, !"classical_fixed_points", i1 false}
+        !9 = !{i32 1, !"user_functions", i1 false}
+        !10 = !{i32 1, !"multiple_target_branching", i1 false}
+    "#]].assert_eq(&qir);
 }
 
 mod base_profile {
@@ -56,8 +771,8 @@ mod base_profile {
         let language_features = LanguageFeatures::default();
         let capabilities = TargetCapabilityFlags::empty();
 
-        let qir =
-            get_qir(sources, language_features, capabilities).expect("Failed to generate QIR");
+        let qir = get_qir(sources, language_features, capabilities, false, false)
+            .expect("Failed to generate QIR");
         expect![[r#"
             %Result = type opaque
             %Qubit = type opaque
@@ -93,6 +808,60 @@ mod base_profile {
         .assert_eq(&qir);
     }
 
+    #[test]
+    fn measurement_entangles_auxiliary_qubit() {
+        let source = "namespace Test {
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                H(q);
+                M(q)
+            }
+        }";
+        let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+        let language_features = LanguageFeatures::default();
+        let capabilities = TargetCapabilityFlags::empty();
+
+        let qir = get_qir(sources, language_features, capabilities, false, false)
+            .expect("Failed to generate QIR");
+        expect![[r#"
+            %Result = type opaque
+            %Qubit = type opaque
+
+            define void @ENTRYPOINT__main() #0 {
+            block_0:
+              call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+              call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 1 to %Qubit*))
+              call void @__quantum__qis__cz__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Qubit* inttoptr (i64 0 to %Qubit*))
+              call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 1 to %Qubit*))
+              call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+              call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+              ret void
+            }
+
+            declare void @__quantum__qis__h__body(%Qubit*)
+
+            declare void @__quantum__qis__cz__body(%Qubit*, %Qubit*)
+
+            declare void @__quantum__rt__result_record_output(%Result*, i8*)
+
+            declare void @__quantum__qis__mz__body(%Qubit*, %Result*) #1
+
+            attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="base_profile" "required_num_qubits"="2" "required_num_results"="1" }
+            attributes #1 = { "irreversible" }
+
+            ; module flags
+
+            !llvm.module.flags = !{!0, !1, !2, !3}
+
+            !0 = !{i32 1, !"qir_major_version", i32 1}
+            !1 = !{i32 7, !"qir_minor_version", i32 0}
+            !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+            !3 = !{i32 1, !"dynamic_result_management", i1 false}
+        "#]]
+        .assert_eq(&qir);
+    }
+
     #[test]
     fn qubit_reuse_triggers_reindexing() {
         let source = "namespace Test {
@@ -106,8 +875,8 @@ mod base_profile {
         let language_features = LanguageFeatures::default();
         let capabilities = TargetCapabilityFlags::empty();
 
-        let qir =
-            get_qir(sources, language_features, capabilities).expect("Failed to generate QIR");
+        let qir = get_qir(sources, language_features, capabilities, false, false)
+            .expect("Failed to generate QIR");
         expect![[r#"
             %Result = type opaque
             %Qubit = type opaque
@@ -159,8 +928,8 @@ mod base_profile {
         let language_features = LanguageFeatures::default();
         let capabilities = TargetCapabilityFlags::empty();
 
-        let qir =
-            get_qir(sources, language_features, capabilities).expect("Failed to generate QIR");
+        let qir = get_qir(sources, language_features, capabilities, false, false)
+            .expect("Failed to generate QIR");
         expect![[r#"
             %Result = type opaque
             %Qubit = type opaque
@@ -228,8 +997,8 @@ mod adaptive_profile {
         let language_features = LanguageFeatures::default();
         let capabilities = TargetCapabilityFlags::Adaptive;
 
-        let qir =
-            get_qir(sources, language_features, capabilities).expect("Failed to generate QIR");
+        let qir = get_qir(sources, language_features, capabilities, false, false)
+            .expect("Failed to generate QIR");
         expect![[r#"
             %Result = type opaque
             %Qubit = type opaque
@@ -272,6 +1041,80 @@ mod adaptive_profile {
         .assert_eq(&qir);
     }
 
+    #[test]
+    fn controlled_specializations() {
+        let source = "namespace Test {
+            @EntryPoint()
+            operation Main() : Result {
+                use (q0, q1, q2) = (Qubit(), Qubit(), Qubit());
+                Controlled X([q0], q1);
+                Controlled Z([q0, q1], q2);
+                Controlled Adjoint S([q2], q0);
+                MResetZ(q0)
+            }
+        }";
+        let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+        let language_features = LanguageFeatures::default();
+        let capabilities = TargetCapabilityFlags::Adaptive;
+
+        let qir = get_qir(sources, language_features, capabilities, false, false)
+            .expect("Failed to generate QIR");
+        expect![[r#"
+            %Result = type opaque
+            %Qubit = type opaque
+
+            define void @ENTRYPOINT__main() #0 {
+            block_0:
+              call void @__quantum__qis__cx__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Qubit* inttoptr (i64 1 to %Qubit*))
+              call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 2 to %Qubit*))
+              call void @__quantum__qis__ccx__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Qubit* inttoptr (i64 1 to %Qubit*), %Qubit* inttoptr (i64 2 to %Qubit*))
+              call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 2 to %Qubit*))
+              call void @__quantum__qis__cx__body(%Qubit* inttoptr (i64 2 to %Qubit*), %Qubit* inttoptr (i64 0 to %Qubit*))
+              call void @__quantum__qis__t__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+              call void @__quantum__qis__cx__body(%Qubit* inttoptr (i64 2 to %Qubit*), %Qubit* inttoptr (i64 0 to %Qubit*))
+              call void @__quantum__qis__t__adj(%Qubit* inttoptr (i64 0 to %Qubit*))
+              call void @__quantum__qis__t__adj(%Qubit* inttoptr (i64 2 to %Qubit*))
+              call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+              call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+              ret void
+            }
+
+            declare void @__quantum__qis__cx__body(%Qubit*, %Qubit*)
+
+            declare void @__quantum__qis__h__body(%Qubit*)
+
+            declare void @__quantum__qis__ccx__body(%Qubit*, %Qubit*, %Qubit*)
+
+            declare void @__quantum__qis__t__body(%Qubit*)
+
+            declare void @__quantum__qis__t__adj(%Qubit*)
+
+            declare void @__quantum__rt__result_record_output(%Result*, i8*)
+
+            declare void @__quantum__qis__mz__body(%Qubit*, %Result*) #1
+
+            attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="3" "required_num_results"="1" }
+            attributes #1 = { "irreversible" }
+
+            ; module flags
+
+            !llvm.module.flags = !{!0, !1, !2, !3, !4, !5, !6, !7, !8, !9, !10}
+
+            !0 = !{i32 1, !"qir_major_version", i32 1}
+            !1 = !{i32 7, !"qir_minor_version", i32 0}
+            !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+            !3 = !{i32 1, !"dynamic_result_management", i1 false}
+            !4 = !{i32 1, !"classical_ints", i1 false}
+            !5 = !{i32 1, !"classical_floats", i1 false}
+            !6 = !{i32 1, !"backwards_branching", i1 false}
+            !7 = !{i32 1, !"qubit_resetting", i1 false}
+            !8 = !{i32 1, !"classical_fixed_points", i1 false}
+            !9 = !{i32 1, !"user_functions", i1 false}
+            !10 = !{i32 1, !"multiple_target_branching", i1 false}
+        "#]]
+        .assert_eq(&qir);
+    }
+
     #[test]
     fn qubit_reuse_triggers_reindexing() {
         let source = "namespace Test {
@@ -285,8 +1128,8 @@ mod adaptive_profile {
         let language_features = LanguageFeatures::default();
         let capabilities = TargetCapabilityFlags::Adaptive;
 
-        let qir =
-            get_qir(sources, language_features, capabilities).expect("Failed to generate QIR");
+        let qir = get_qir(sources, language_features, capabilities, false, false)
+            .expect("Failed to generate QIR");
         expect![[r#"
             %Result = type opaque
             %Qubit = type opaque
@@ -345,8 +1188,8 @@ mod adaptive_profile {
         let language_features = LanguageFeatures::default();
         let capabilities = TargetCapabilityFlags::Adaptive;
 
-        let qir =
-            get_qir(sources, language_features, capabilities).expect("Failed to generate QIR");
+        let qir = get_qir(sources, language_features, capabilities, false, false)
+            .expect("Failed to generate QIR");
         expect![[r#"
             %Result = type opaque
             %Qubit = type opaque
@@ -393,6 +1236,31 @@ mod adaptive_profile {
     }
 }
 
+#[test]
+fn qir_for_unsupported_construct_returns_error_with_span() {
+    let source = "namespace Test {
+            operation Op(q : Qubit) : Int {
+                body intrinsic;
+            }
+            @EntryPoint()
+            operation Main() : Unit {
+                use q = Qubit();
+                let _ = Op(q);
+            }
+        }";
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let language_features = LanguageFeatures::default();
+    let capabilities = TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::IntegerComputations;
+
+    let errors = get_qir(sources, language_features, capabilities, false, false)
+        .expect_err("QIR generation should fail");
+    expect![[r"
+        partial evaluation error: unsupported intrinsic `Op`
+          [test.qs] [Op]
+    "]]
+    .assert_eq(&render_errors(&errors));
+}
+
 mod quantinuum_profile {
     use expect_test::expect;
     use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
@@ -423,8 +1291,8 @@ mod quantinuum_profile {
             | TargetCapabilityFlags::QubitReset
             | TargetCapabilityFlags::IntegerComputations;
 
-        let qir =
-            get_qir(sources, language_features, capabilities).expect("Failed to generate QIR");
+        let qir = get_qir(sources, language_features, capabilities, false, false)
+            .expect("Failed to generate QIR");
         expect![[r#"
             %Result = type opaque
             %Qubit = type opaque
@@ -482,8 +1350,8 @@ mod quantinuum_profile {
             | TargetCapabilityFlags::QubitReset
             | TargetCapabilityFlags::IntegerComputations;
 
-        let qir =
-            get_qir(sources, language_features, capabilities).expect("Failed to generate QIR");
+        let qir = get_qir(sources, language_features, capabilities, false, false)
+            .expect("Failed to generate QIR");
         expect![[r#"
             %Result = type opaque
             %Qubit = type opaque
@@ -544,8 +1412,8 @@ mod quantinuum_profile {
             | TargetCapabilityFlags::QubitReset
             | TargetCapabilityFlags::IntegerComputations;
 
-        let qir =
-            get_qir(sources, language_features, capabilities).expect("Failed to generate QIR");
+        let qir = get_qir(sources, language_features, capabilities, false, false)
+            .expect("Failed to generate QIR");
         expect![[r#"
             %Result = type opaque
             %Qubit = type opaque
@@ -607,8 +1475,8 @@ mod quantinuum_profile {
             | TargetCapabilityFlags::QubitReset
             | TargetCapabilityFlags::IntegerComputations;
 
-        let qir =
-            get_qir(sources, language_features, capabilities).expect("Failed to generate QIR");
+        let qir = get_qir(sources, language_features, capabilities, false, false)
+            .expect("Failed to generate QIR");
         expect![[r#"
             %Result = type opaque
             %Qubit = type opaque
@@ -83,3 +83,19 @@ pub(super) fn from_eval(
 
     WithStack::new(WithSource::from_map(sources, error), stack_trace)
 }
+
+/// Attaches the sources of the package in which the error occurred, which may be a
+/// library package rather than the user's package.
+pub(super) fn from_partial_eval(
+    error: qsc_partial_eval::Error,
+    store: &PackageStore,
+) -> WithSource<qsc_partial_eval::Error> {
+    let span = error.span();
+
+    let sources = &store
+        .get(span.package)
+        .expect("expected to find package id in store")
+        .sources;
+
+    WithSource::from_map(sources, error)
+}
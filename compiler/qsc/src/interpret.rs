@@ -2,6 +2,8 @@
 // Licensed under the MIT License.
 
 mod debug;
+#[cfg(feature = "reverse-stepping")]
+mod history;
 
 #[cfg(test)]
 mod tests;
@@ -32,15 +34,21 @@ use crate::{
     incremental::Compiler,
     location::Location,
 };
-use debug::format_call_stack;
+use debug::{format_call_stack, frame_locals, watch_lambda_source, FrameLocal};
+#[cfg(feature = "reverse-stepping")]
+use history::{History, Snapshot};
 use miette::Diagnostic;
 use num_bigint::BigUint;
 use num_complex::Complex;
 use qsc_circuit::{
-    operations::entry_expr_for_qubit_operation, Builder as CircuitBuilder, Circuit,
+    operations::entry_expr_for_qubit_operation, rir_to_circuit, Builder as CircuitBuilder, Circuit,
     Config as CircuitConfig,
 };
-use qsc_codegen::{qir::fir_to_qir, qir_base::BaseProfSim};
+use qsc_codegen::{
+    qasm3::{self, fir_to_qasm3},
+    qir::{fir_to_rir, rir_to_qir, QubitReuseReport},
+    qir_base::BaseProfSim,
+};
 use qsc_data_structures::{
     functors::FunctorApp,
     language_features::LanguageFeatures,
@@ -51,6 +59,7 @@ use qsc_data_structures::{
 use qsc_eval::{
     backend::{Backend, Chain as BackendChain, SparseSim},
     output::Receiver,
+    state::{get_qubit_state, QubitState},
     val, Env, State, VariableInfo,
 };
 use qsc_fir::fir::{self, ExecGraphNode, Global, PackageStoreLookup};
@@ -64,7 +73,7 @@ use qsc_frontend::{
     incremental::Increment,
 };
 use qsc_passes::{PackageType, PassContext};
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 use thiserror::Error;
 
 impl Error {
@@ -104,6 +113,28 @@ pub enum Error {
     #[error("partial evaluation error")]
     #[diagnostic(transparent)]
     PartialEvaluation(#[from] WithSource<qsc_partial_eval::Error>),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Qasm3(qasm3::Error),
+    #[error("stack frame {0} not found")]
+    #[diagnostic(code("Qsc.Interpret.FrameNotFound"))]
+    FrameNotFound(usize),
+    #[error("watch expression cannot perform quantum operations")]
+    #[diagnostic(code("Qsc.Interpret.QuantumWatchExpr"))]
+    #[diagnostic(help(
+        "watch expressions are evaluated without side effects, so they cannot call operations or allocate qubits"
+    ))]
+    QuantumWatchExpr,
+    #[error("variable `{0}` not found")]
+    #[diagnostic(code("Qsc.Interpret.VariableNotFound"))]
+    VariableNotFound(String),
+    #[error("variable `{0}` is immutable")]
+    #[diagnostic(code("Qsc.Interpret.ImmutableVariable"))]
+    #[diagnostic(help("only variables declared with `mutable` can be modified"))]
+    ImmutableVariable(String),
+    #[error("variable `{0}` has a type that cannot be modified")]
+    #[diagnostic(code("Qsc.Interpret.UnsupportedVariableType"))]
+    UnsupportedVariableType(String),
 }
 
 /// A Q# interpreter.
@@ -137,10 +168,24 @@ pub struct Interpreter {
     classical_seed: Option<u64>,
     /// The evaluator environment.
     env: Env,
+    /// The maximum depth of nested operation calls grouped in generated circuits.
+    circuit_operation_depth: usize,
+    /// Whether adjacent gates that cancel out or can be merged are optimized away in generated QIR.
+    optimize_gates: bool,
+    /// Whether qubits with lifetimes that do not overlap are mapped onto the same qubit in generated QIR.
+    reuse_qubits: bool,
 }
 
 pub type InterpretResult = std::result::Result<Value, Vec<Error>>;
 
+/// The package and execution graph of an entry expression, along with the compute properties for
+/// partial evaluation if they were computed.
+type EntryExecGraph = (
+    PackageId,
+    Rc<[ExecGraphNode]>,
+    Option<PackageStoreComputeProperties>,
+);
+
 impl Interpreter {
     /// Creates a new incremental compiler, compiling the passed in sources.
     /// # Errors
@@ -212,9 +257,12 @@ impl Interpreter {
             fir_store,
             lowerer: qsc_lowerer::Lowerer::new().with_debug(dbg),
             env: Env::default(),
-            sim: sim_circuit_backend(),
+            sim: sim_circuit_backend(0),
             quantum_seed: None,
             classical_seed: None,
+            circuit_operation_depth: 0,
+            optimize_gates: false,
+            reuse_qubits: false,
             package: map_hir_package_to_fir(package_id),
             source_package: map_hir_package_to_fir(source_package_id),
         })
@@ -248,9 +296,12 @@ impl Interpreter {
             fir_store,
             lowerer: qsc_lowerer::Lowerer::new(),
             env: Env::default(),
-            sim: sim_circuit_backend(),
+            sim: sim_circuit_backend(0),
             quantum_seed: None,
             classical_seed: None,
+            circuit_operation_depth: 0,
+            optimize_gates: false,
+            reuse_qubits: false,
             package: map_hir_package_to_fir(package_id),
             source_package: map_hir_package_to_fir(source_package_id),
         })
@@ -264,6 +315,27 @@ impl Interpreter {
     pub fn set_classical_seed(&mut self, seed: Option<u64>) {
         self.classical_seed = seed;
     }
+
+    /// Sets the maximum depth of nested operation calls that are grouped into
+    /// parent operations in generated circuits. A depth of `0` produces a flat list of gates.
+    /// Circuits generated from the partially evaluated program are always flat.
+    pub fn set_circuit_operation_depth(&mut self, depth: usize) {
+        self.circuit_operation_depth = depth;
+        self.sim.chained.set_max_operation_depth(depth);
+    }
+
+    /// Sets whether adjacent gates that cancel out or can be merged, such as `H; H` or consecutive
+    /// rotations on the same qubit, are optimized away in QIR generated by `qirgen`.
+    pub fn set_optimize_gates(&mut self, enabled: bool) {
+        self.optimize_gates = enabled;
+    }
+
+    /// Sets whether qubits with lifetimes that do not overlap are mapped onto the same qubit in QIR
+    /// generated by `qirgen`, reducing the number of qubits the program requires.
+    pub fn set_reuse_qubits(&mut self, enabled: bool) {
+        self.reuse_qubits = enabled;
+    }
+
     /// Executes the entry expression until the end of execution.
     /// # Errors
     /// Returns a vector of errors if evaluating the entry point fails.
@@ -406,17 +478,32 @@ impl Interpreter {
     /// Performs QIR codegen using the given entry expression on a new instance of the environment
     /// and simulator but using the current compilation.
     pub fn qirgen(&mut self, expr: &str) -> std::result::Result<String, Vec<Error>> {
+        self.qirgen_with_qubit_reuse_report(expr)
+            .map(|(qir, _)| qir)
+    }
+
+    /// Performs QIR codegen like `qirgen`, also returning the report of the qubits saved by qubit
+    /// reuse if it is enabled and the target supports it.
+    pub fn qirgen_with_qubit_reuse_report(
+        &mut self,
+        expr: &str,
+    ) -> std::result::Result<(String, Option<QubitReuseReport>), Vec<Error>> {
         if self.capabilities == TargetCapabilityFlags::all() {
             return Err(vec![Error::UnsupportedRuntimeCapabilities]);
         }
-        if self.capabilities == TargetCapabilityFlags::empty() {
+        // Gate optimization and qubit reuse run on the partially evaluated program, so the base
+        // profile simulation is only used when neither is enabled.
+        if self.capabilities == TargetCapabilityFlags::empty()
+            && !self.optimize_gates
+            && !self.reuse_qubits
+        {
             let mut sim = BaseProfSim::new();
             let mut stdout = std::io::sink();
             let mut out = GenericReceiver::new(&mut stdout);
 
             let val = self.run_with_sim(&mut sim, &mut out, expr)??;
 
-            Ok(sim.finish(&val))
+            Ok((sim.finish(&val), None))
         } else {
             // Compile the expression. This operation will set the expression as
             // the entry-point in the FIR store.
@@ -429,38 +516,70 @@ impl Interpreter {
                     "internal error: compute properties not set after lowering entry expression"
                 );
             };
-            let package = self.fir_store.get(self.package);
-            let entry = ProgramEntry {
-                exec_graph: graph.into(),
-                expr: (
-                    self.package,
-                    package
-                        .entry
-                        .expect("package must have an entry expression"),
-                )
-                    .into(),
-            };
+            let entry = self.program_entry(self.package, graph.into());
             // Generate QIR
-            fir_to_qir(
+            fir_to_rir(
                 &self.fir_store,
                 self.capabilities,
+                self.optimize_gates,
+                self.reuse_qubits,
                 Some(compute_properties),
                 &entry,
             )
-            .map_err(|e| {
-                let source_package = self
-                    .compiler
-                    .package_store()
-                    .get(map_fir_package_to_hir(self.package))
-                    .expect("package should exist in the package store");
-                vec![Error::PartialEvaluation(WithSource::from_map(
-                    &source_package.sources,
-                    e,
-                ))]
-            })
+            .map(|(program, reuse_report)| (rir_to_qir(&program), reuse_report))
+            .map_err(|e| self.partial_evaluation_errors(e))
         }
     }
 
+    /// Generates an OpenQASM 3 program from the given entry expression by partially evaluating it.
+    pub fn qasm(&mut self, expr: &str) -> std::result::Result<String, Vec<Error>> {
+        if self.capabilities == TargetCapabilityFlags::all() {
+            return Err(vec![Error::UnsupportedRuntimeCapabilities]);
+        }
+
+        let (graph, compute_properties) = self.compile_entry_expr(expr)?;
+        let Some(compute_properties) = compute_properties else {
+            panic!("internal error: compute properties not set after lowering entry expression");
+        };
+        let entry = self.program_entry(self.package, graph.into());
+        fir_to_qasm3(
+            &self.fir_store,
+            self.capabilities,
+            Some(compute_properties),
+            &entry,
+        )
+        .map_err(|e| match e {
+            qasm3::Error::PartialEvaluation(e) => self.partial_evaluation_errors(e),
+            e => vec![Error::Qasm3(e)],
+        })
+    }
+
+    /// Creates the entry for partial evaluation from the entry expression of the given package.
+    fn program_entry(
+        &self,
+        package_id: PackageId,
+        exec_graph: Rc<[ExecGraphNode]>,
+    ) -> ProgramEntry {
+        let package = self.fir_store.get(package_id);
+        ProgramEntry {
+            exec_graph,
+            expr: (
+                package_id,
+                package
+                    .entry
+                    .expect("package must have an entry expression"),
+            )
+                .into(),
+        }
+    }
+
+    fn partial_evaluation_errors(&self, error: qsc_partial_eval::Error) -> Vec<Error> {
+        vec![Error::PartialEvaluation(error::from_partial_eval(
+            error,
+            self.compiler.package_store(),
+        ))]
+    }
+
     /// Generates a circuit representation for the program.
     ///
     /// `entry` can be the current entrypoint, an entry expression, or any operation
@@ -473,6 +592,10 @@ impl Interpreter {
     /// circuit is returned (a.k.a. trace mode). Otherwise, the circuit is generated without
     /// simulation. In this case circuit generation may fail if the program contains dynamic
     /// behavior (quantum operations that are dependent on measurement results).
+    ///
+    /// For targets that support partial evaluation, a circuit generated without simulation
+    /// shows operations that depend on measurement results as classically controlled groups.
+    /// Such a circuit is not grouped by operation calls, since partial evaluation inlines them.
     pub fn circuit(
         &mut self,
         entry: CircuitEntryPoint,
@@ -489,18 +612,27 @@ impl Interpreter {
             CircuitEntryPoint::EntryPoint => None,
         };
 
+        // The entry expression is compiled once and shared by all the ways of
+        // generating the circuit below.
+        let (package_id, graph, compute_properties) = self.entry_exec_graph(entry_expr)?;
+
         let circuit = if simulate {
-            let mut sim = sim_circuit_backend();
+            let mut sim = sim_circuit_backend(self.circuit_operation_depth);
 
-            self.run_with_sim_no_output(entry_expr, &mut sim)?;
+            self.run_with_sim_no_output(package_id, graph, &mut sim)?;
 
             sim.chained.finish()
+        } else if let Some(circuit) =
+            self.circuit_from_rir(package_id, graph.clone(), compute_properties)
+        {
+            circuit
         } else {
             let mut sim = CircuitBuilder::new(CircuitConfig {
                 base_profile: self.capabilities.is_empty(),
+                max_operation_depth: self.circuit_operation_depth,
             });
 
-            self.run_with_sim_no_output(entry_expr, &mut sim)?;
+            self.run_with_sim_no_output(package_id, graph, &mut sim)?;
 
             sim.finish()
         };
@@ -508,6 +640,37 @@ impl Interpreter {
         Ok(circuit)
     }
 
+    /// Generates a circuit from the partially evaluated program, so that operations
+    /// that depend on measurement results are shown as classically controlled groups.
+    ///
+    /// Returns `None` if the target does not support partial evaluation, if the
+    /// program could not be partially evaluated, or if the partially evaluated program
+    /// cannot be shown as a circuit, such as one with loops or branches on conditions other
+    /// than a measurement result, in which case the circuit is generated without it.
+    fn circuit_from_rir(
+        &self,
+        package_id: PackageId,
+        graph: Rc<[ExecGraphNode]>,
+        compute_properties: Option<PackageStoreComputeProperties>,
+    ) -> Option<Circuit> {
+        if self.capabilities == TargetCapabilityFlags::empty()
+            || self.capabilities == TargetCapabilityFlags::all()
+        {
+            return None;
+        }
+        let entry = self.program_entry(package_id, graph);
+        let (program, _) = fir_to_rir(
+            &self.fir_store,
+            self.capabilities,
+            false,
+            false,
+            compute_properties,
+            &entry,
+        )
+        .ok()?;
+        rir_to_circuit(&program).ok()
+    }
+
     /// Runs the given entry expression on the given simulator with a new instance of the environment
     /// but using the current compilation.
     pub fn run_with_sim(
@@ -534,22 +697,32 @@ impl Interpreter {
         ))
     }
 
-    fn run_with_sim_no_output(
+    /// Returns the package and execution graph of the given entry expression, compiling it, or of
+    /// the entrypoint if there is no entry expression, along with the compute properties for
+    /// partial evaluation if they were computed.
+    fn entry_exec_graph(
         &mut self,
         entry_expr: Option<String>,
+    ) -> std::result::Result<EntryExecGraph, Vec<Error>> {
+        if let Some(entry_expr) = entry_expr {
+            // entry expression is provided
+            let (graph, compute_properties) = self.compile_entry_expr(&entry_expr)?;
+            Ok((self.package, graph.into(), compute_properties))
+        } else {
+            // no entry expression, use the entrypoint in the package
+            Ok((self.source_package, self.get_entry_exec_graph()?, None))
+        }
+    }
+
+    fn run_with_sim_no_output(
+        &mut self,
+        package_id: PackageId,
+        graph: Rc<[ExecGraphNode]>,
         sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
     ) -> InterpretResult {
         let mut sink = std::io::sink();
         let mut out = GenericReceiver::new(&mut sink);
 
-        let (package_id, graph) = if let Some(entry_expr) = entry_expr {
-            // entry expression is provided
-            (self.package, self.compile_entry_expr(&entry_expr)?.0.into())
-        } else {
-            // no entry expression, use the entrypoint in the package
-            (self.source_package, self.get_entry_exec_graph()?)
-        };
-
         if self.quantum_seed.is_some() {
             sim.set_seed(self.quantum_seed);
         }
@@ -679,7 +852,7 @@ impl Interpreter {
     }
 }
 
-fn sim_circuit_backend() -> BackendChain<SparseSim, CircuitBuilder> {
+fn sim_circuit_backend(max_operation_depth: usize) -> BackendChain<SparseSim, CircuitBuilder> {
     BackendChain::new(
         SparseSim::new(),
         CircuitBuilder::new(CircuitConfig {
@@ -691,6 +864,7 @@ fn sim_circuit_backend() -> BackendChain<SparseSim, CircuitBuilder> {
             // will still respect the selected profile. This also
             // matches the behavior of the simulator.
             base_profile: false,
+            max_operation_depth,
         }),
     )
 }
@@ -716,6 +890,18 @@ pub struct Debugger {
     position_encoding: Encoding,
     /// The current state of the evaluator.
     state: State,
+    /// The snapshots recorded for reverse stepping, if enabled.
+    #[cfg(feature = "reverse-stepping")]
+    history: Option<History>,
+    /// The result of the most recent step, if the debugger is paused.
+    #[cfg(feature = "reverse-stepping")]
+    last_result: Option<StepResult>,
+    /// Whether runtime errors pause the debugger instead of ending evaluation.
+    break_on_exceptions: bool,
+    /// The errors for the exception the debugger is paused on, if any.
+    exception: Option<Vec<Error>>,
+    /// The callables compiled for watch expressions, keyed by the source of their lambda.
+    watch_callables: FxHashMap<String, Value>,
 }
 
 impl Debugger {
@@ -739,6 +925,13 @@ impl Debugger {
             interpreter,
             position_encoding,
             state: State::new(source_package_id, entry_exec_graph, None),
+            #[cfg(feature = "reverse-stepping")]
+            history: None,
+            #[cfg(feature = "reverse-stepping")]
+            last_result: None,
+            break_on_exceptions: false,
+            exception: None,
+            watch_callables: FxHashMap::default(),
         })
     }
 
@@ -751,23 +944,57 @@ impl Debugger {
         breakpoints: &[StmtId],
         step: StepAction,
     ) -> std::result::Result<StepResult, Vec<Error>> {
-        self.state
-            .eval(
-                &self.interpreter.fir_store,
-                &mut self.interpreter.env,
-                &mut self.interpreter.sim,
-                receiver,
-                breakpoints,
-                step,
-            )
-            .map_err(|(error, call_stack)| {
-                eval_error(
+        if let Some(errors) = self.exception.take() {
+            // Evaluation cannot resume past a failure, so report it now.
+            #[cfg(feature = "reverse-stepping")]
+            {
+                self.last_result = None;
+            }
+            return Err(errors);
+        }
+        #[cfg(feature = "reverse-stepping")]
+        self.record_snapshot();
+        let result = match self.state.eval(
+            &self.interpreter.fir_store,
+            &mut self.interpreter.env,
+            &mut self.interpreter.sim,
+            receiver,
+            breakpoints,
+            step,
+        ) {
+            Ok(result) => result,
+            Err((error, call_stack)) => {
+                let errors = eval_error(
                     self.interpreter.compiler.package_store(),
                     &self.interpreter.fir_store,
                     call_stack,
-                    error,
-                )
-            })
+                    error.clone(),
+                );
+                if !self.break_on_exceptions {
+                    return Err(errors);
+                }
+                self.exception = Some(errors);
+                StepResult::Exception(error)
+            }
+        };
+        #[cfg(feature = "reverse-stepping")]
+        {
+            self.last_result = Some(result.clone());
+        }
+        Ok(result)
+    }
+
+    /// Sets whether runtime errors, including `fail`, pause the debugger at the failing
+    /// statement with `StepResult::Exception` instead of ending evaluation with an error.
+    /// Stepping again after such a pause returns the error.
+    pub fn set_break_on_exceptions(&mut self, enabled: bool) {
+        self.break_on_exceptions = enabled;
+    }
+
+    /// Returns the errors for the exception the debugger is paused on, if any.
+    #[must_use]
+    pub fn exception(&self) -> Option<&[Error]> {
+        self.exception.as_deref()
     }
 
     #[must_use]
@@ -807,10 +1034,31 @@ impl Debugger {
         self.interpreter.sim.capture_quantum_state()
     }
 
+    /// Returns the id and reduced single-qubit state of each qubit held by `value`,
+    /// which may be a `Qubit` or an array of qubits. Other values hold no qubits.
+    pub fn qubit_states(&mut self, value: &Value) -> Vec<(usize, QubitState)> {
+        let mut qubits = Vec::new();
+        collect_qubits(value, &mut qubits);
+        if qubits.is_empty() {
+            return Vec::new();
+        }
+        let (state, qubit_count) = self.capture_quantum_state();
+        qubits
+            .into_iter()
+            .map(|id| (id, get_qubit_state(&state, qubit_count, id)))
+            .collect()
+    }
+
     pub fn circuit(&self) -> Circuit {
         self.interpreter.get_circuit()
     }
 
+    /// Sets the maximum depth of nested operation calls that are grouped into
+    /// parent operations in the circuit returned by `circuit`.
+    pub fn set_circuit_operation_depth(&mut self, depth: usize) {
+        self.interpreter.set_circuit_operation_depth(depth);
+    }
+
     #[must_use]
     pub fn get_breakpoints(&self, path: &str) -> Vec<BreakpointSpan> {
         let unit = self.source_package();
@@ -847,6 +1095,176 @@ impl Debugger {
             .collect()
     }
 
+    /// Evaluates `expr` in the scope of the stack frame at index `frame_id`
+    /// of the list returned by `get_stack_frames`.
+    ///
+    /// The locals of the frame are visible to the expression by name, except for locals whose
+    /// type cannot be named from source, such as values of a generic type.
+    /// The expression is compiled as a function, so expressions that call operations
+    /// or allocate qubits are rejected with `Error::QuantumWatchExpr`. It is evaluated in an
+    /// environment of its own, so it cannot change the locals or the quantum state of the
+    /// paused program.
+    ///
+    /// The compiled function is added to the session package. It is reused when the same
+    /// expression is evaluated again over locals of the same types, such as when a watch is
+    /// refreshed after each step, so the package only grows with each distinct watch.
+    /// # Errors
+    /// Returns a vector of errors if the frame does not exist, or if compiling or
+    /// evaluating the expression fails.
+    pub fn eval_watch(
+        &mut self,
+        receiver: &mut impl Receiver,
+        frame_id: usize,
+        expr: &str,
+    ) -> InterpretResult {
+        let locals = self.frame_locals(frame_id)?;
+        self.eval_with_locals(receiver, locals, expr)
+    }
+
+    /// Rebinds the mutable local `name` in the stack frame at index `frame_id`
+    /// of the list returned by `get_stack_frames` to the result of `value_expr`,
+    /// and returns the new value.
+    ///
+    /// The value expression is type-checked against the declared type of the local,
+    /// and can refer to other locals of the frame in the same way as a watch expression.
+    /// # Errors
+    /// Returns a vector of errors if the frame or local does not exist, if the local
+    /// is immutable, or if compiling or evaluating the value expression fails.
+    pub fn set_variable(
+        &mut self,
+        frame_id: usize,
+        name: &str,
+        value_expr: &str,
+    ) -> InterpretResult {
+        let locals = self.frame_locals(frame_id)?;
+        let Some(local) = locals.iter().find(|local| *local.name == *name) else {
+            return Err(vec![Error::VariableNotFound(name.to_string())]);
+        };
+        if !local.mutable {
+            return Err(vec![Error::ImmutableVariable(name.to_string())]);
+        }
+        let Some(ty) = &local.ty else {
+            return Err(vec![Error::UnsupportedVariableType(name.to_string())]);
+        };
+        let id = local.id;
+        let expr = format!("{{\nlet __new_value : {ty} =\n{value_expr}\n;\n__new_value\n}}");
+
+        let mut sink = std::io::sink();
+        let mut out = GenericReceiver::new(&mut sink);
+        let value = self.eval_with_locals(&mut out, locals, &expr)?;
+        let updated =
+            self.interpreter
+                .env
+                .update_variable_in_frame(frame_id + 1, id, value.clone());
+        assert!(updated, "local should be bound in the frame");
+        Ok(value)
+    }
+
+    fn frame_locals(&self, frame_id: usize) -> std::result::Result<Vec<FrameLocal>, Vec<Error>> {
+        let frames = self.state.get_stack_frames();
+        let frame = frames
+            .get(frame_id)
+            .ok_or(vec![Error::FrameNotFound(frame_id)])?;
+        // Scopes are keyed by call stack depth, so the outermost frame has an id of 1.
+        let variables = self.interpreter.env.get_variables_in_frame(frame_id + 1);
+        Ok(frame_locals(
+            self.interpreter.compiler.package_store(),
+            self.interpreter.fir_store.get(frame.id.package),
+            frame.id,
+            variables,
+        ))
+    }
+
+    fn eval_with_locals(
+        &mut self,
+        receiver: &mut impl Receiver,
+        locals: Vec<FrameLocal>,
+        expr: &str,
+    ) -> InterpretResult {
+        let (names, args): (Vec<_>, Vec<_>) = locals
+            .into_iter()
+            .filter_map(|local| Some(((local.name, local.ty?), local.value)))
+            .unzip();
+        let args = match <[Value; 1]>::try_from(args) {
+            Ok([arg]) => arg,
+            Err(args) => Value::Tuple(args.into()),
+        };
+
+        let source = watch_lambda_source(&names, expr);
+        let callable = if let Some(callable) = self.watch_callables.get(&source) {
+            callable.clone()
+        } else {
+            let callable = self.compile_watch_callable(receiver, &source)?;
+            self.watch_callables.insert(source, callable.clone());
+            callable
+        };
+
+        qsc_eval::invoke(
+            self.interpreter.package,
+            self.interpreter.classical_seed,
+            &self.interpreter.fir_store,
+            &mut Env::default(),
+            &mut self.interpreter.sim,
+            receiver,
+            callable,
+            args,
+        )
+        .map_err(|(error, call_stack)| {
+            eval_error(
+                self.interpreter.compiler.package_store(),
+                &self.interpreter.fir_store,
+                call_stack,
+                error,
+            )
+        })
+    }
+
+    /// Compiles the lambda `source` of a watch expression into the session package
+    /// and evaluates it to a callable value.
+    fn compile_watch_callable(
+        &mut self,
+        receiver: &mut impl Receiver,
+        source: &str,
+    ) -> InterpretResult {
+        let label = self.interpreter.next_line_label();
+        let increment = self
+            .interpreter
+            .compiler
+            .compile_fragments_fail_fast(&label, source)
+            .map_err(|errors| {
+                if errors.iter().any(|error| {
+                    matches!(
+                        error.error(),
+                        crate::compile::ErrorKind::Pass(qsc_passes::Error::CallableLimits(_))
+                    )
+                }) {
+                    vec![Error::QuantumWatchExpr]
+                } else {
+                    into_errors(errors)
+                }
+            })?;
+
+        // The lambda is lowered without running capability analysis, since watch expressions are
+        // only ever simulated and should not be restricted by the target profile.
+        let fir_package = self.interpreter.fir_store.get_mut(self.interpreter.package);
+        self.interpreter
+            .lowerer
+            .lower_and_update_package(fir_package, &increment.hir);
+        let graph = self.interpreter.lowerer.take_exec_graph();
+        self.interpreter.compiler.update(increment);
+
+        eval(
+            self.interpreter.package,
+            self.interpreter.classical_seed,
+            graph.into(),
+            self.interpreter.compiler.package_store(),
+            &self.interpreter.fir_store,
+            &mut Env::default(),
+            &mut self.interpreter.sim,
+            receiver,
+        )
+    }
+
     fn source_package(&self) -> &CompileUnit {
         self.interpreter
             .compiler
@@ -856,6 +1274,69 @@ impl Debugger {
     }
 }
 
+#[cfg(feature = "reverse-stepping")]
+impl Debugger {
+    /// Starts recording a snapshot of the evaluator, environment and simulator before each step,
+    /// so that execution can be reversed with `step_back` and `reverse_continue`.
+    /// Once the estimated size of the snapshots exceeds `memory_cap` bytes,
+    /// the oldest snapshots are discarded.
+    pub fn enable_reverse_stepping(&mut self, memory_cap: usize) {
+        self.history = Some(History::new(memory_cap));
+    }
+
+    /// Stops recording snapshots and discards the recorded ones.
+    pub fn disable_reverse_stepping(&mut self) {
+        self.history = None;
+    }
+
+    #[must_use]
+    pub fn can_step_back(&self) -> bool {
+        self.history
+            .as_ref()
+            .is_some_and(|history| !history.is_empty())
+    }
+
+    /// Restores the debugger to where it was paused before the most recent step.
+    /// Returns the result of the step that originally paused it there,
+    /// or `None` if there is no snapshot to restore.
+    pub fn step_back(&mut self) -> Option<StepResult> {
+        let snapshot = self.history.as_mut()?.pop()?;
+        Some(self.restore(snapshot))
+    }
+
+    /// Restores the debugger to the most recent point where it was paused on one of the
+    /// given breakpoints, or to the oldest recorded snapshot if there is no such point.
+    /// Returns the result of the step that originally paused it there,
+    /// or `None` if there is no snapshot to restore.
+    pub fn reverse_continue(&mut self, breakpoints: &[StmtId]) -> Option<StepResult> {
+        let snapshot = self.history.as_mut()?.pop_to_breakpoint(breakpoints)?;
+        Some(self.restore(snapshot))
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) -> StepResult {
+        self.state = snapshot.state;
+        self.interpreter.env = snapshot.env;
+        self.interpreter.sim = snapshot.sim;
+        self.exception = None;
+        self.last_result = Some(snapshot.result.clone());
+        snapshot.result
+    }
+
+    /// Records a snapshot of where the debugger is paused before it steps again.
+    fn record_snapshot(&mut self) {
+        if let (Some(history), Some(result)) = (&mut self.history, self.last_result.take()) {
+            if !matches!(result, StepResult::Return(_)) {
+                history.push(Snapshot::new(
+                    &self.state,
+                    &self.interpreter.env,
+                    &self.interpreter.sim,
+                    result,
+                ));
+            }
+        }
+    }
+}
+
 /// Wrapper function for `qsc_eval::eval` that handles error conversion.
 #[allow(clippy::too_many_arguments)]
 fn eval(
@@ -880,6 +1361,15 @@ fn eval(
     .map_err(|(error, call_stack)| eval_error(package_store, fir_store, call_stack, error))
 }
 
+/// Collects the IDs of the qubits held by a value, including those in nested arrays.
+fn collect_qubits(value: &Value, qubits: &mut Vec<usize>) {
+    match value {
+        Value::Qubit(q) => qubits.push(q.0),
+        Value::Array(arr) => arr.iter().for_each(|v| collect_qubits(v, qubits)),
+        _ => {}
+    }
+}
+
 /// Represents a stack frame for debugging.
 pub struct StackFrame {
     /// The name of the callable.
@@ -11,6 +11,7 @@ use qsc_data_structures::language_features::LanguageFeatures;
 use qsc_eval::output::GenericReceiver;
 use qsc_frontend::compile::SourceMap;
 use qsc_passes::PackageType;
+use std::fmt::Write;
 
 fn interpreter(code: &str, profile: Profile) -> Interpreter {
     let sources = SourceMap::new([("test.qs".into(), code.into())], None);
@@ -764,6 +765,289 @@ fn operation_with_non_qubit_args() {
     .assert_debug_eq(&circ_err);
 }
 
+static GROUPED_SOURCE: &str = r"
+    namespace Test {
+        operation Bell(q0 : Qubit, q1 : Qubit) : Unit {
+            H(q0);
+            Entangle(q0, q1);
+        }
+        operation Entangle(q0 : Qubit, q1 : Qubit) : Unit {
+            CNOT(q0, q1);
+        }
+        operation Rotate(theta : Double, q : Qubit) : Unit is Adj {
+            Rx(theta, q);
+        }
+        @EntryPoint()
+        operation Main() : Result[] {
+            use qs = Qubit[3];
+            Bell(qs[0], qs[1]);
+            Adjoint Rotate(1.0, qs[2]);
+            MResetEachZ(qs)
+        }
+    }
+";
+
+fn fmt_registers(registers: &[crate::circuit::Register]) -> String {
+    registers
+        .iter()
+        .map(|r| match r.c_id {
+            Some(c) => format!("c{}.{c}", r.q_id),
+            None => format!("q{}", r.q_id),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Formats the operation tree of a circuit, one operation per line,
+/// with children indented under their parent.
+fn fmt_operations(operations: &[crate::circuit::Operation], indent: usize) -> String {
+    let mut s = String::new();
+    for op in operations {
+        let targets = fmt_registers(&op.targets);
+        let controls = fmt_registers(&op.controls);
+        let _ = writeln!(
+            s,
+            "{:indent$}{}{}{}{} [{targets}]{}",
+            "",
+            op.conditional_render
+                .map_or(String::new(), |render| format!("{render:?}: ")),
+            op.gate,
+            if op.is_adjoint { "'" } else { "" },
+            op.display_args
+                .as_ref()
+                .map_or(String::new(), |a| format!("({a})")),
+            if op.is_conditional {
+                format!(" when [{controls}]")
+            } else {
+                String::new()
+            },
+        );
+        s.push_str(&fmt_operations(&op.children, indent + 4));
+    }
+    s
+}
+
+fn grouped_circuit(depth: usize) -> String {
+    let mut interpreter = interpreter(GROUPED_SOURCE, Profile::Unrestricted);
+    interpreter.set_circuit_operation_depth(depth);
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .expect("circuit generation should succeed");
+    fmt_operations(&circ.operations, 0)
+}
+
+#[test]
+fn operation_depth_zero_is_flat() {
+    expect![[r"
+        H [q0]
+        X [q1]
+        rx(-1.0000) [q2]
+        Measure [c0.0]
+        |0〉 [q0]
+        Measure [c1.0]
+        |0〉 [q1]
+        Measure [c2.0]
+        |0〉 [q2]
+    "]]
+    .assert_eq(&grouped_circuit(0));
+}
+
+#[test]
+fn operation_depth_groups_user_operations() {
+    expect![[r"
+        Main [q0, q1, q2]
+            Bell [q0, q1]
+                H [q0]
+                    H [q0]
+                Entangle [q0, q1]
+                    CNOT [q0, q1]
+                        X [q1]
+            Rotate'(1.0) [q2]
+                Rx'(1.0) [q2]
+                    Rx(-1.0) [q2]
+                        rx(-1.0000) [q2]
+            MResetEachZ [q0, q1, q2]
+                MResetZ [q0]
+                    Measure [c0.0]
+                    |0〉 [q0]
+                MResetZ [q1]
+                    Measure [c1.0]
+                    |0〉 [q1]
+                MResetZ [q2]
+                    Measure [c2.0]
+                    |0〉 [q2]
+    "]]
+    .assert_eq(&grouped_circuit(usize::MAX));
+}
+
+#[test]
+fn operation_depth_limits_nesting() {
+    expect![[r"
+        Main [q0, q1, q2]
+            Bell [q0, q1]
+                H [q0]
+                X [q1]
+            Rotate'(1.0) [q2]
+                rx(-1.0000) [q2]
+            MResetEachZ [q0, q1, q2]
+                Measure [c0.0]
+                |0〉 [q0]
+                Measure [c1.0]
+                |0〉 [q1]
+                Measure [c2.0]
+                |0〉 [q2]
+    "]]
+    .assert_eq(&grouped_circuit(2));
+}
+
+#[test]
+fn adaptive_profile_result_comparison_is_conditional() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result[] {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    H(q0);
+                    let r = M(q0);
+                    if r == One {
+                        X(q1);
+                    } else {
+                        Z(q1);
+                    }
+                    [r, M(q1)]
+                }
+            }
+        ",
+        Profile::Quantinuum,
+    );
+
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .expect("circuit generation should succeed");
+
+    expect![[r"
+        q_0    ── H ──── M ────────────────
+                         ╘═════ ● ═════════
+        q_1    ─────────────── if ──── M ──
+                                       ╘═══
+    "]]
+    .assert_eq(&circ.to_string());
+    expect![[r"
+        H [q0]
+        Measure [c0.0]
+        if [q1] when [c0.0]
+            OnOne: X [q1]
+            OnZero: Z [q1]
+        Measure [c1.0]
+    "]]
+    .assert_eq(&fmt_operations(&circ.operations, 0));
+}
+
+#[test]
+fn adaptive_profile_nested_result_comparisons_are_nested_groups() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use (q0, q1, q2) = (Qubit(), Qubit(), Qubit());
+                    H(q0);
+                    H(q1);
+                    if MResetZ(q0) != One {
+                        if MResetZ(q1) == One {
+                            X(q2);
+                        }
+                    }
+                }
+            }
+        ",
+        Profile::Quantinuum,
+    );
+
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .expect("circuit generation should succeed");
+
+    expect![[r"
+        H [q0]
+        H [q1]
+        Measure [c0.0]
+        |0〉 [q0]
+        if [q1, q2] when [c0.0]
+            OnZero: Measure [c1.0]
+            OnZero: |0〉 [q1]
+            OnZero: if [q2] when [c1.0]
+                OnOne: X [q2]
+    "]]
+    .assert_eq(&fmt_operations(&circ.operations, 0));
+}
+
+#[test]
+fn adaptive_profile_result_equality_falls_back_to_circuit_builder() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result[] {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    H(q0);
+                    H(q1);
+                    let r0 = M(q0);
+                    let r1 = M(q1);
+                    if r0 == r1 {
+                        X(q0);
+                    }
+                    [r0, r1]
+                }
+            }
+        ",
+        Profile::Quantinuum,
+    );
+
+    // The branch is not conditioned on a single measurement result, so the circuit is
+    // generated without partial evaluation, which reports the result comparison.
+    let circuit_err = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .expect_err("circuit should return error")
+        .pop()
+        .expect("error should exist");
+
+    expect!["Qsc.Eval.ResultComparisonUnsupported"].assert_eq(
+        &circuit_err
+            .code()
+            .expect("error code should exist")
+            .to_string(),
+    );
+}
+
+#[test]
+fn adaptive_profile_operation() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                operation Test(q0 : Qubit, q1 : Qubit) : Unit {
+                    H(q0);
+                    CNOT(q0, q1);
+                }
+                @EntryPoint()
+                operation Main() : Unit {}
+            }
+        ",
+        Profile::Quantinuum,
+    );
+
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::Operation("Test.Test".into()), false)
+        .expect("circuit generation should succeed");
+
+    expect![[r"
+        q_0    ── H ──── ● ──
+        q_1    ───────── X ──
+    "]]
+    .assert_eq(&circ.to_string());
+}
+
 /// Tests that invoke circuit generation throught the debugger.
 mod debugger_stepping {
     use super::Debugger;
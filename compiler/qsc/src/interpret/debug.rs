@@ -4,12 +4,20 @@
 #[cfg(test)]
 mod tests;
 
-use qsc_eval::debug::Frame;
-use qsc_fir::fir::{Global, PackageStoreLookup, StoreItemId};
+use std::fmt::Write;
+
+use qsc_eval::{debug::Frame, val::Value, VariableInfo};
+use qsc_fir::fir::{
+    Block, BlockId, CallableKind, Expr, ExprId, Global, LocalVarId, Mutability, Package, PackageId,
+    PackageStoreLookup, Pat, PatId, PatKind, Res, Stmt, StmtId, StmtKind, StoreItemId,
+};
+use qsc_fir::ty::{FunctorSet, FunctorSetValue, Prim, Ty};
+use qsc_fir::visit::{self, Visitor};
 use qsc_frontend::compile::PackageStore;
 use qsc_hir::hir;
 use qsc_hir::hir::{Item, ItemKind};
 use qsc_lowerer::map_fir_package_to_hir;
+use rustc_hash::FxHashMap;
 
 #[must_use]
 pub(crate) fn format_call_stack(
@@ -89,3 +97,218 @@ fn get_ns_name(item: &Item) -> Option<String> {
         None
     }
 }
+
+/// A local variable of a stack frame, as seen by watch expressions.
+pub(crate) struct FrameLocal {
+    pub(crate) id: LocalVarId,
+    pub(crate) name: String,
+    /// The declared type of the local as Q# source, if it can be named.
+    pub(crate) ty: Option<String>,
+    pub(crate) value: Value,
+    pub(crate) mutable: bool,
+}
+
+/// Resolves the declared types and mutability of the given frame variables from the declaration
+/// of the frame's callable `item`. Compiler-generated variables are skipped, and when a name is
+/// bound more than once only the innermost binding is kept.
+#[must_use]
+pub(crate) fn frame_locals(
+    store: &PackageStore,
+    package: &Package,
+    item: StoreItemId,
+    variables: Vec<VariableInfo>,
+) -> Vec<FrameLocal> {
+    let mut collector = LocalDeclCollector {
+        package,
+        mutability: Mutability::Immutable,
+        locals: FxHashMap::default(),
+    };
+    collector.visit_item(
+        package
+            .items
+            .get(item.item)
+            .expect("frame callable should exist"),
+    );
+
+    let mut locals: Vec<FrameLocal> = Vec::new();
+    for var in variables {
+        if var.name.starts_with('@') {
+            continue;
+        }
+        let decl = collector.locals.get(&var.id);
+        // Inner scopes come last, so later bindings shadow earlier ones.
+        locals.retain(|local| *local.name != *var.name);
+        locals.push(FrameLocal {
+            id: var.id,
+            name: var.name.to_string(),
+            ty: decl.and_then(|decl| ty_to_source(store, item.package, decl.ty)),
+            value: var.value,
+            mutable: decl.is_some_and(|decl| decl.mutability == Mutability::Mutable),
+        });
+    }
+    locals
+}
+
+/// The declared type and mutability of a local.
+struct LocalDecl<'a> {
+    ty: &'a Ty,
+    mutability: Mutability,
+}
+
+/// Collects the declarations of the locals bound in a callable, keyed by their local id,
+/// which is unique within a callable.
+struct LocalDeclCollector<'a> {
+    package: &'a Package,
+    /// The mutability of the bindings of the statement being visited.
+    mutability: Mutability,
+    locals: FxHashMap<LocalVarId, LocalDecl<'a>>,
+}
+
+impl<'a> Visitor<'a> for LocalDeclCollector<'a> {
+    fn visit_stmt(&mut self, stmt: StmtId) {
+        if let StmtKind::Local(mutability, pat, value) = self.get_stmt(stmt).kind {
+            self.mutability = mutability;
+            self.visit_pat(pat);
+            self.mutability = Mutability::Immutable;
+            self.visit_expr(value);
+        } else {
+            visit::walk_stmt(self, stmt);
+        }
+    }
+
+    fn visit_pat(&mut self, pat: PatId) {
+        let pat_ref = self.get_pat(pat);
+        if let PatKind::Bind(ident) = &pat_ref.kind {
+            self.locals.insert(
+                ident.id,
+                LocalDecl {
+                    ty: &pat_ref.ty,
+                    mutability: self.mutability,
+                },
+            );
+        }
+        visit::walk_pat(self, pat);
+    }
+
+    fn get_block(&self, id: BlockId) -> &'a Block {
+        self.package.blocks.get(id).expect("block should exist")
+    }
+
+    fn get_expr(&self, id: ExprId) -> &'a Expr {
+        self.package.exprs.get(id).expect("expr should exist")
+    }
+
+    fn get_pat(&self, id: PatId) -> &'a Pat {
+        self.package.pats.get(id).expect("pat should exist")
+    }
+
+    fn get_stmt(&self, id: StmtId) -> &'a Stmt {
+        self.package.stmts.get(id).expect("stmt should exist")
+    }
+}
+
+/// The name of the parameter through which the locals of a stack frame
+/// are passed to a watch expression.
+const WATCH_ARGS: &str = "__watch_args";
+
+/// Builds the source of a function lambda that takes the given locals as a single argument,
+/// binds them by name with their declared types, and then evaluates `expr`.
+///
+/// Because the lambda is a function, any attempt by `expr` to call an operation or allocate
+/// qubits is rejected by the compiler.
+#[must_use]
+pub(crate) fn watch_lambda_source(locals: &[(String, String)], expr: &str) -> String {
+    let mut source = String::new();
+    if locals.is_empty() {
+        source.push_str("() -> {\n");
+    } else {
+        let _ = writeln!(source, "({WATCH_ARGS}) -> {{");
+        let bindings = locals
+            .iter()
+            .map(|(name, ty)| format!("{name} : {ty}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if locals.len() == 1 {
+            let _ = writeln!(source, "let {bindings} = {WATCH_ARGS};");
+        } else {
+            let _ = writeln!(source, "let ({bindings}) = {WATCH_ARGS};");
+        }
+    }
+    source.push_str(expr);
+    source.push_str("\n}");
+    source
+}
+
+/// Renders a FIR type from the given package as Q# source. User-defined types are
+/// fully qualified so that the result can be compiled from any package.
+/// Returns `None` if the type cannot be written in source, such as a type parameter.
+#[must_use]
+fn ty_to_source(store: &PackageStore, package: PackageId, ty: &Ty) -> Option<String> {
+    match ty {
+        Ty::Array(item) => Some(format!("{}[]", ty_to_source(store, package, item)?)),
+        Ty::Arrow(arrow) => {
+            let arrow_str = match arrow.kind {
+                CallableKind::Function => "->",
+                CallableKind::Operation => "=>",
+            };
+            let functors = match arrow.functors {
+                FunctorSet::Value(FunctorSetValue::Empty) => String::new(),
+                FunctorSet::Value(functors) => format!(" is {functors}"),
+                FunctorSet::Param(_) | FunctorSet::Infer(_) => return None,
+            };
+            Some(format!(
+                "({} {arrow_str} {}{functors})",
+                ty_to_source(store, package, &arrow.input)?,
+                ty_to_source(store, package, &arrow.output)?,
+            ))
+        }
+        Ty::Prim(
+            prim @ (Prim::BigInt
+            | Prim::Bool
+            | Prim::Double
+            | Prim::Int
+            | Prim::Pauli
+            | Prim::Qubit
+            | Prim::Range
+            | Prim::Result
+            | Prim::String),
+        ) => Some(format!("{prim:?}")),
+        Ty::Tuple(items) => match items.as_slice() {
+            [] => Some("Unit".to_string()),
+            [item] => Some(format!("({},)", ty_to_source(store, package, item)?)),
+            items => {
+                let items = items
+                    .iter()
+                    .map(|item| ty_to_source(store, package, item))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(format!("({})", items.join(", ")))
+            }
+        },
+        Ty::Udt(Res::Item(item)) => {
+            let id = StoreItemId {
+                package: item.package.unwrap_or(package),
+                item: item.item,
+            };
+            get_udt_name(store, id)
+        }
+        Ty::Prim(Prim::RangeTo | Prim::RangeFrom | Prim::RangeFull)
+        | Ty::Udt(_)
+        | Ty::Infer(_)
+        | Ty::Param(_)
+        | Ty::Err => None,
+    }
+}
+
+#[must_use]
+fn get_udt_name(store: &PackageStore, id: StoreItemId) -> Option<String> {
+    let package = map_fir_package_to_hir(id.package);
+    let item = hir::LocalItemId::from(usize::from(id.item));
+    let unit = store.get(package)?;
+    let ItemKind::Ty(name, _) = &unit.package.items.get(item)?.kind else {
+        return None;
+    };
+    match get_item_parent(store, id).as_ref().and_then(get_ns_name) {
+        Some(ns) => Some(format!("{ns}.{}", name.name)),
+        None => Some(name.name.to_string()),
+    }
+}
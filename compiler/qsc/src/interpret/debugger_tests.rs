@@ -220,4 +220,624 @@ mod given_debugger {
             Ok(())
         }
     }
+
+    static WATCH_SOURCE: &str = r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Result[] {
+                use qs = Qubit[2];
+                let results = [One, Zero];
+                let angle = 0.5;
+                Foo(qs, angle);
+                ResetAll(qs);
+                results
+            }
+            operation Foo(qs : Qubit[], theta : Double) : Unit {
+                let angle = theta * 2.0;
+                Rx(angle, qs[0]);
+            }
+        }"#;
+
+    #[cfg(test)]
+    mod watch {
+        use qsc_data_structures::target::TargetCapabilityFlags;
+
+        use super::*;
+
+        fn watch_debugger() -> Debugger {
+            let sources = SourceMap::new([("test".into(), WATCH_SOURCE.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                TargetCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )
+            .expect("debugger should be created");
+            // Break on the call to `Foo`.
+            let bp = debugger
+                .get_breakpoints("test")
+                .into_iter()
+                .find(|bp| bp.range.start.line == 7)
+                .expect("breakpoint should exist");
+            let ids = [bp.id.into()];
+            expect_bp(&mut debugger, &ids, ids[0]);
+            debugger
+        }
+
+        fn watch(debugger: &mut Debugger, frame_id: usize, expr: &str) -> String {
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            match debugger.eval_watch(&mut receiver, frame_id, expr) {
+                Ok(value) => value.to_string(),
+                Err(errors) => format!("{:?}", errors[0]),
+            }
+        }
+
+        #[test]
+        fn locals_are_visible() {
+            let mut debugger = watch_debugger();
+            assert_eq!(watch(&mut debugger, 0, "Length(qs)"), "2");
+            assert_eq!(watch(&mut debugger, 0, "results[0] == One"), "true");
+            assert_eq!(watch(&mut debugger, 0, "angle * 2.0"), "1.0");
+        }
+
+        #[test]
+        fn expression_does_not_affect_program() {
+            let mut debugger = watch_debugger();
+            assert_eq!(
+                watch(&mut debugger, 0, "{ mutable x = angle; set x += 1.0; x }"),
+                "1.5"
+            );
+            assert_eq!(watch(&mut debugger, 0, "angle"), "0.5");
+            match step(&mut debugger, &[], StepAction::Continue).0 {
+                Ok(StepResult::Return(value)) => assert_eq!(value.to_string(), "[One, Zero]"),
+                Ok(v) => panic!("Expected Return, got {v:?}"),
+                Err(e) => panic!("Expected Return, got {e:?}"),
+            }
+        }
+
+        #[test]
+        fn repeated_expression_is_compiled_once() {
+            let mut debugger = watch_debugger();
+            let num_items = |debugger: &Debugger| {
+                debugger
+                    .interpreter
+                    .fir_store
+                    .get(debugger.interpreter.package)
+                    .items
+                    .iter()
+                    .count()
+            };
+            assert_eq!(watch(&mut debugger, 0, "angle * 2.0"), "1.0");
+            let items_after_first_watch = num_items(&debugger);
+            assert_eq!(watch(&mut debugger, 0, "angle * 2.0"), "1.0");
+            assert_eq!(num_items(&debugger), items_after_first_watch);
+        }
+
+        #[test]
+        fn quantum_operations_are_rejected() {
+            let mut debugger = watch_debugger();
+            assert_eq!(watch(&mut debugger, 0, "H(qs[0])"), "QuantumWatchExpr");
+            assert_eq!(
+                watch(&mut debugger, 0, "{ use q = Qubit(); 0 }"),
+                "QuantumWatchExpr"
+            );
+        }
+
+        #[test]
+        fn runtime_errors_are_reported() {
+            let mut debugger = watch_debugger();
+            let result = watch(&mut debugger, 0, "results[3]");
+            assert!(result.contains("IndexOutOfRange"), "{result}");
+        }
+
+        #[test]
+        fn unknown_frame_is_reported() {
+            let mut debugger = watch_debugger();
+            assert_eq!(watch(&mut debugger, 1, "angle"), "FrameNotFound(1)");
+        }
+
+        #[test]
+        fn frames_have_separate_scopes() {
+            let mut debugger = watch_debugger();
+            expect_in(&mut debugger);
+            expect_next(&mut debugger);
+            assert_eq!(watch(&mut debugger, 1, "angle"), "1.0");
+            assert_eq!(watch(&mut debugger, 1, "theta"), "0.5");
+            assert_eq!(watch(&mut debugger, 0, "angle"), "0.5");
+        }
+    }
+
+    static SET_VARIABLE_SOURCE: &str = r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : (Double, Int) {
+                mutable angle = 0.5;
+                let count = 3;
+                set angle = angle * 2.0;
+                (angle, count)
+            }
+        }"#;
+
+    static SHADOWED_SOURCE: &str = r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : (Int, Int) {
+                mutable x = 1;
+                mutable y = 0;
+                {
+                    mutable x = 2;
+                    set y = x;
+                }
+                (x, y)
+            }
+        }"#;
+
+    #[cfg(test)]
+    mod set_variable {
+        use qsc_data_structures::target::TargetCapabilityFlags;
+
+        use super::*;
+
+        fn set_variable_debugger() -> Debugger {
+            // Break on the update of `angle`.
+            debugger_at(SET_VARIABLE_SOURCE, 6)
+        }
+
+        fn debugger_at(source: &str, line: u32) -> Debugger {
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                TargetCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )
+            .expect("debugger should be created");
+            let bp = debugger
+                .get_breakpoints("test")
+                .into_iter()
+                .find(|bp| bp.range.start.line == line)
+                .expect("breakpoint should exist");
+            let ids = [bp.id.into()];
+            expect_bp(&mut debugger, &ids, ids[0]);
+            debugger
+        }
+
+        fn set(debugger: &mut Debugger, name: &str, value_expr: &str) -> String {
+            match debugger.set_variable(0, name, value_expr) {
+                Ok(value) => value.to_string(),
+                Err(errors) => format!("{:?}", errors[0]),
+            }
+        }
+
+        fn expect_continue_return(debugger: &mut Debugger, expected: &str) {
+            match step(debugger, &[], StepAction::Continue).0 {
+                Ok(StepResult::Return(value)) => assert_eq!(value.to_string(), expected),
+                Ok(v) => panic!("Expected Return, got {v:?}"),
+                Err(e) => panic!("Expected Return, got {e:?}"),
+            }
+        }
+
+        #[test]
+        fn mutable_local_is_rebound() {
+            let mut debugger = set_variable_debugger();
+            assert_eq!(set(&mut debugger, "angle", "0.1"), "0.1");
+            assert_eq!(
+                debugger
+                    .get_locals()
+                    .iter()
+                    .find(|v| &*v.name == "angle")
+                    .expect("local should exist")
+                    .value
+                    .to_string(),
+                "0.1"
+            );
+            expect_continue_return(&mut debugger, "(0.2, 3)");
+        }
+
+        #[test]
+        fn value_can_refer_to_other_locals() {
+            let mut debugger = set_variable_debugger();
+            assert_eq!(
+                set(
+                    &mut debugger,
+                    "angle",
+                    "angle + Microsoft.Quantum.Convert.IntAsDouble(count)"
+                ),
+                "3.5"
+            );
+            expect_continue_return(&mut debugger, "(7.0, 3)");
+        }
+
+        #[test]
+        fn value_must_match_declared_type() {
+            let mut debugger = set_variable_debugger();
+            let result = set(&mut debugger, "angle", "count");
+            assert!(result.starts_with("Compile("), "{result}");
+            expect_continue_return(&mut debugger, "(1.0, 3)");
+        }
+
+        #[test]
+        fn immutable_local_is_rejected() {
+            let mut debugger = set_variable_debugger();
+            assert_eq!(
+                set(&mut debugger, "count", "4"),
+                "ImmutableVariable(\"count\")"
+            );
+        }
+
+        #[test]
+        fn shadowing_local_is_rebound() {
+            // Break on the update of `y`, where the inner `x` shadows the outer one.
+            let mut debugger = debugger_at(SHADOWED_SOURCE, 8);
+            assert_eq!(set(&mut debugger, "x", "5"), "5");
+            expect_continue_return(&mut debugger, "(1, 5)");
+        }
+
+        #[test]
+        fn unknown_local_is_rejected() {
+            let mut debugger = set_variable_debugger();
+            assert_eq!(
+                set(&mut debugger, "missing", "1.0"),
+                "VariableNotFound(\"missing\")"
+            );
+        }
+    }
+
+    static QUBIT_STATES_SOURCE: &str = r#"
+        namespace Test {
+            open Microsoft.Quantum.Measurement;
+            @EntryPoint()
+            operation Main() : Result[] {
+                use single = Qubit();
+                use pair = Qubit[2];
+                H(pair[0]);
+                CNOT(pair[0], pair[1]);
+                X(single);
+                MResetEachZ([single] + pair)
+            }
+        }"#;
+
+    #[cfg(test)]
+    mod qubit_states {
+        use qsc_data_structures::target::TargetCapabilityFlags;
+
+        use super::*;
+        use crate::interpret::Value;
+
+        fn qubit_states_debugger() -> Debugger {
+            let sources = SourceMap::new([("test".into(), QUBIT_STATES_SOURCE.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                TargetCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )
+            .expect("debugger should be created");
+            // Break before the measurement.
+            let bp = debugger
+                .get_breakpoints("test")
+                .into_iter()
+                .find(|bp| bp.range.start.line == 10)
+                .expect("breakpoint should exist");
+            let ids = [bp.id.into()];
+            expect_bp(&mut debugger, &ids, ids[0]);
+            debugger
+        }
+
+        fn local(debugger: &Debugger, name: &str) -> Value {
+            debugger
+                .get_locals()
+                .into_iter()
+                .find(|v| &*v.name == name)
+                .expect("local should exist")
+                .value
+        }
+
+        #[test]
+        fn single_qubit_is_separable() {
+            let mut debugger = qubit_states_debugger();
+            let single = local(&debugger, "single");
+            let states = debugger.qubit_states(&single);
+            assert_eq!(states.len(), 1);
+            let (id, state) = states[0];
+            assert_eq!(id, 0);
+            assert!((state.bloch.2 + 1.0).abs() < 1e-9);
+            assert!((state.probabilities.1 - 1.0).abs() < 1e-9);
+            assert!(!state.entangled);
+        }
+
+        #[test]
+        fn qubit_array_reports_each_entangled_qubit() {
+            let mut debugger = qubit_states_debugger();
+            let pair = local(&debugger, "pair");
+            let states = debugger.qubit_states(&pair);
+            assert_eq!(
+                states.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+                vec![1, 2]
+            );
+            for (_, state) in states {
+                assert!(state.bloch.2.abs() < 1e-9);
+                assert!((state.probabilities.0 - 0.5).abs() < 1e-9);
+                assert!(state.entangled);
+            }
+        }
+
+        #[test]
+        fn classical_value_has_no_qubits() {
+            let mut debugger = qubit_states_debugger();
+            assert!(debugger.qubit_states(&Value::Int(3)).is_empty());
+        }
+    }
+
+    static REVERSE_SOURCE: &str = r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                use q = Qubit();
+                mutable count = 0;
+                for i in 1..3 {
+                    H(q);
+                    set count += i;
+                }
+                Reset(q);
+                count
+            }
+        }"#;
+
+    #[cfg(test)]
+    mod reverse_stepping {
+        use qsc_data_structures::target::TargetCapabilityFlags;
+
+        use super::*;
+
+        fn reverse_debugger(memory_cap: usize) -> (Debugger, Vec<StmtId>) {
+            let sources = SourceMap::new([("test".into(), REVERSE_SOURCE.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                TargetCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )
+            .expect("debugger should be created");
+            debugger.enable_reverse_stepping(memory_cap);
+            // Break on `H(q)` in the loop.
+            let bp = debugger
+                .get_breakpoints("test")
+                .into_iter()
+                .find(|bp| bp.range.start.line == 7)
+                .expect("breakpoint should exist");
+            let ids = vec![bp.id.into()];
+            expect_bp(&mut debugger, &ids, ids[0]);
+            (debugger, ids)
+        }
+
+        fn local(debugger: &Debugger, name: &str) -> String {
+            debugger
+                .get_locals()
+                .into_iter()
+                .find(|v| &*v.name == name)
+                .expect("local should exist")
+                .value
+                .to_string()
+        }
+
+        fn expect_continue_bp(debugger: &mut Debugger, ids: &[StmtId]) {
+            match step(debugger, ids, StepAction::Continue).0 {
+                Ok(StepResult::BreakpointHit(id)) => assert_eq!(id, ids[0]),
+                Ok(v) => panic!("Expected BP, got {v:?}"),
+                Err(e) => panic!("Expected BP, got {e:?}"),
+            }
+        }
+
+        fn expect_continue_return(debugger: &mut Debugger, ids: &[StmtId], expected: &str) {
+            match step(debugger, ids, StepAction::Continue).0 {
+                Ok(StepResult::Return(value)) => assert_eq!(value.to_string(), expected),
+                Ok(v) => panic!("Expected Return, got {v:?}"),
+                Err(e) => panic!("Expected Return, got {e:?}"),
+            }
+        }
+
+        #[test]
+        fn step_back_restores_locals_and_quantum_state() {
+            let (mut debugger, ids) = reverse_debugger(usize::MAX);
+            assert_eq!(debugger.capture_quantum_state().0.len(), 1);
+            expect_next(&mut debugger);
+            assert_eq!(debugger.capture_quantum_state().0.len(), 2);
+            expect_next(&mut debugger);
+            assert_eq!(local(&debugger, "count"), "1");
+
+            assert!(matches!(debugger.step_back(), Some(StepResult::Next)));
+            assert_eq!(local(&debugger, "count"), "0");
+            assert_eq!(debugger.capture_quantum_state().0.len(), 2);
+            assert!(matches!(
+                debugger.step_back(),
+                Some(StepResult::BreakpointHit(id)) if id == ids[0]
+            ));
+            assert_eq!(debugger.capture_quantum_state().0.len(), 1);
+            assert!(!debugger.can_step_back());
+            assert!(debugger.step_back().is_none());
+
+            expect_continue_bp(&mut debugger, &ids);
+            expect_continue_bp(&mut debugger, &ids);
+            expect_continue_return(&mut debugger, &ids, "6");
+        }
+
+        #[test]
+        fn reverse_continue_stops_at_previous_breakpoint() {
+            let (mut debugger, ids) = reverse_debugger(usize::MAX);
+            expect_continue_bp(&mut debugger, &ids);
+            expect_continue_bp(&mut debugger, &ids);
+            assert_eq!(local(&debugger, "i"), "3");
+            expect_next(&mut debugger);
+
+            assert!(matches!(
+                debugger.reverse_continue(&ids),
+                Some(StepResult::BreakpointHit(_))
+            ));
+            assert_eq!(local(&debugger, "i"), "3");
+            assert!(matches!(
+                debugger.reverse_continue(&ids),
+                Some(StepResult::BreakpointHit(_))
+            ));
+            assert_eq!(local(&debugger, "i"), "2");
+            expect_continue_bp(&mut debugger, &ids);
+            expect_continue_return(&mut debugger, &ids, "6");
+        }
+
+        #[test]
+        fn reverse_continue_without_breakpoints_stops_at_oldest_snapshot() {
+            let (mut debugger, ids) = reverse_debugger(usize::MAX);
+            expect_continue_bp(&mut debugger, &ids);
+            expect_continue_bp(&mut debugger, &ids);
+            assert!(matches!(
+                debugger.reverse_continue(&[]),
+                Some(StepResult::BreakpointHit(_))
+            ));
+            assert_eq!(local(&debugger, "i"), "1");
+            assert!(!debugger.can_step_back());
+        }
+
+        #[test]
+        fn step_back_from_return() {
+            let (mut debugger, ids) = reverse_debugger(usize::MAX);
+            expect_continue_bp(&mut debugger, &ids);
+            expect_continue_bp(&mut debugger, &ids);
+            expect_continue_return(&mut debugger, &ids, "6");
+            assert!(matches!(
+                debugger.step_back(),
+                Some(StepResult::BreakpointHit(_))
+            ));
+            assert_eq!(local(&debugger, "i"), "3");
+            expect_continue_return(&mut debugger, &ids, "6");
+        }
+
+        #[test]
+        fn snapshots_over_memory_cap_are_discarded() {
+            let (mut debugger, ids) = reverse_debugger(0);
+            expect_continue_bp(&mut debugger, &ids);
+            assert!(!debugger.can_step_back());
+            assert!(debugger.step_back().is_none());
+        }
+
+        #[test]
+        fn step_back_is_unavailable_when_disabled() {
+            let (mut debugger, ids) = reverse_debugger(usize::MAX);
+            debugger.disable_reverse_stepping();
+            expect_continue_bp(&mut debugger, &ids);
+            assert!(debugger.step_back().is_none());
+        }
+    }
+
+    static EXCEPTION_SOURCE: &str = r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Unit {
+                let items = [1, 2, 3];
+                Check(items, 3);
+            }
+            operation Check(items : Int[], index : Int) : Unit {
+                let limit = Length(items);
+                if index >= limit {
+                    fail "index too large";
+                }
+            }
+            operation OutOfRange() : Int {
+                let items = [1];
+                let index = 2;
+                items[index]
+            }
+            operation NotReleasedInZero() : Unit {
+                use q = Qubit();
+                X(q);
+            }
+        }"#;
+
+    #[cfg(test)]
+    mod exceptions {
+        use qsc_data_structures::target::TargetCapabilityFlags;
+        use qsc_eval::Error;
+
+        use super::*;
+
+        fn exception_debugger(entry: Option<&str>) -> Debugger {
+            let sources = SourceMap::new(
+                [("test".into(), EXCEPTION_SOURCE.into())],
+                entry.map(Into::into),
+            );
+            let mut debugger = Debugger::new(
+                sources,
+                TargetCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )
+            .expect("debugger should be created");
+            debugger.set_break_on_exceptions(true);
+            debugger
+        }
+
+        fn continue_to_exception(debugger: &mut Debugger) -> Error {
+            match step(debugger, &[], StepAction::Continue).0 {
+                Ok(StepResult::Exception(error)) => error,
+                Ok(v) => panic!("Expected Exception, got {v:?}"),
+                Err(e) => panic!("Expected Exception, got {e:?}"),
+            }
+        }
+
+        #[test]
+        fn fail_pauses_with_stack_and_locals() {
+            let mut debugger = exception_debugger(None);
+            let error = continue_to_exception(&mut debugger);
+            assert!(
+                matches!(&error, Error::UserFail(message, _) if message == "index too large"),
+                "{error:?}"
+            );
+            assert!(debugger.exception().is_some());
+
+            let frames = debugger.get_stack_frames();
+            assert_eq!(frames.len(), 2);
+            assert_eq!(frames[1].name, "Check");
+            let locals = debugger
+                .get_locals()
+                .into_iter()
+                .map(|v| format!("{} = {}", v.name, v.value))
+                .collect::<Vec<_>>();
+            assert_eq!(locals, ["items = [1, 2, 3]", "index = 3", "limit = 3"]);
+
+            // Resuming after the exception reports the error.
+            let result = step(&mut debugger, &[], StepAction::Continue).0;
+            let errors = result.expect_err("resuming should fail");
+            assert!(format!("{errors:?}").contains("index too large"));
+        }
+
+        #[test]
+        fn runtime_error_pauses_at_failing_statement() {
+            let mut debugger = exception_debugger(Some("Test.OutOfRange()"));
+            let error = continue_to_exception(&mut debugger);
+            assert!(matches!(error, Error::IndexOutOfRange(2, _)), "{error:?}");
+            let frames = debugger.get_stack_frames();
+            assert_eq!(frames.len(), 1);
+            assert_eq!(frames[0].location.range.start.line, 16);
+        }
+
+        #[test]
+        fn qubit_release_error_pauses() {
+            let mut debugger = exception_debugger(Some("Test.NotReleasedInZero()"));
+            let error = continue_to_exception(&mut debugger);
+            assert!(
+                matches!(error, Error::ReleasedQubitNotZero(..)),
+                "{error:?}"
+            );
+        }
+
+        #[test]
+        fn errors_end_evaluation_when_disabled() {
+            let mut debugger = exception_debugger(None);
+            debugger.set_break_on_exceptions(false);
+            let result = step(&mut debugger, &[], StepAction::Continue).0;
+            let errors = result.expect_err("evaluation should fail");
+            assert!(format!("{errors:?}").contains("index too large"));
+            assert!(debugger.exception().is_none());
+        }
+    }
 }
@@ -0,0 +1,114 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use std::collections::VecDeque;
+
+use num_bigint::BigUint;
+use num_complex::Complex64;
+use qsc_circuit::Builder as CircuitBuilder;
+use qsc_eval::{
+    backend::{Backend, Chain as BackendChain, SparseSim},
+    val::Value,
+    Env, State, StepResult,
+};
+use qsc_fir::fir::StmtId;
+
+/// A copy of the evaluator, environment and simulator taken while the debugger was paused.
+pub(crate) struct Snapshot {
+    pub(crate) state: State,
+    pub(crate) env: Env,
+    pub(crate) sim: BackendChain<SparseSim, CircuitBuilder>,
+    /// The result of the step that paused the debugger where the snapshot was taken.
+    pub(crate) result: StepResult,
+    /// The estimated memory used by the snapshot, in bytes.
+    size: usize,
+}
+
+impl Snapshot {
+    pub(crate) fn new(
+        state: &State,
+        env: &Env,
+        sim: &BackendChain<SparseSim, CircuitBuilder>,
+        result: StepResult,
+    ) -> Self {
+        let mut sim = sim.clone();
+        let size = estimate_size(env, &mut sim);
+        Self {
+            state: state.clone(),
+            env: env.clone(),
+            sim,
+            result,
+            size,
+        }
+    }
+}
+
+/// Estimates the memory used by a snapshot, which is dominated by the quantum state.
+fn estimate_size(env: &Env, sim: &mut BackendChain<SparseSim, CircuitBuilder>) -> usize {
+    let (state, qubit_count) = sim.capture_quantum_state();
+    let entry_size = std::mem::size_of::<(BigUint, Complex64)>() + qubit_count.div_ceil(8);
+    state.len() * entry_size + env.variable_count() * std::mem::size_of::<Value>()
+}
+
+/// The snapshots recorded before each debugger step, most recent last.
+/// The oldest snapshots are discarded once their total estimated size exceeds the memory cap.
+pub(crate) struct History {
+    snapshots: VecDeque<Snapshot>,
+    memory_cap: usize,
+    memory_used: usize,
+}
+
+impl History {
+    pub(crate) fn new(memory_cap: usize) -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            memory_cap,
+            memory_used: 0,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub(crate) fn push(&mut self, snapshot: Snapshot) {
+        if snapshot.size > self.memory_cap {
+            // A snapshot that can never fit makes older snapshots unreachable by stepping back.
+            self.snapshots.clear();
+            self.memory_used = 0;
+            return;
+        }
+        self.memory_used += snapshot.size;
+        self.snapshots.push_back(snapshot);
+        while self.memory_used > self.memory_cap {
+            let oldest = self
+                .snapshots
+                .pop_front()
+                .expect("snapshots should not be empty while memory is used");
+            self.memory_used -= oldest.size;
+        }
+    }
+
+    /// Removes and returns the most recent snapshot.
+    pub(crate) fn pop(&mut self) -> Option<Snapshot> {
+        let snapshot = self.snapshots.pop_back()?;
+        self.memory_used -= snapshot.size;
+        Some(snapshot)
+    }
+
+    /// Removes and returns the most recent snapshot paused on one of the given breakpoints,
+    /// along with every snapshot after it. If no snapshot is paused on a breakpoint,
+    /// all snapshots are removed and the oldest one is returned.
+    pub(crate) fn pop_to_breakpoint(&mut self, breakpoints: &[StmtId]) -> Option<Snapshot> {
+        while let Some(snapshot) = self.pop() {
+            let on_breakpoint = matches!(
+                snapshot.result,
+                StepResult::BreakpointHit(id) if breakpoints.contains(&id)
+            );
+            if on_breakpoint || self.snapshots.is_empty() {
+                return Some(snapshot);
+            }
+        }
+        None
+    }
+}
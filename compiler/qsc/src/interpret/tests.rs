@@ -873,6 +873,64 @@ mod given_interpreter {
             .assert_eq(&res);
         }
 
+        #[test]
+        fn adaptive_qasm() {
+            let mut interpreter = Interpreter::new(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::QubitReset,
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {r#"
+                namespace Test {
+                    @EntryPoint()
+                    operation Main() : (Result, Bool) {
+                        use (q0, q1) = (Qubit(), Qubit());
+                        H(q0);
+                        let r = MResetZ(q0);
+                        if r == One {
+                            X(q1);
+                        }
+                        (MResetZ(q1), r == Zero)
+                    }
+                }"#
+                },
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let res = interpreter.qasm("Test.Main()").expect("expected success");
+            expect![[r#"
+                OPENQASM 3.0;
+                include "stdgates.inc";
+                output bit output_0;
+                output bool output_1;
+                qubit[2] q;
+                bit[2] c;
+                bool var_0;
+                bool var_1;
+                bool var_2;
+                bool var_3;
+                h q[0];
+                c[0] = measure q[0];
+                reset q[0];
+                var_0 = bool(c[0]);
+                var_1 = var_0 == true;
+                if (var_1) {
+                    x q[1];
+                }
+                c[1] = measure q[1];
+                reset q[1];
+                var_2 = bool(c[0]);
+                var_3 = var_2 == false;
+                output_0 = c[1];
+                output_1 = var_3;
+            "#]]
+            .assert_eq(&res);
+        }
+
         #[test]
         fn adaptive_qirgen_fails_when_entry_expr_does_not_match_profile() {
             let mut interpreter = Interpreter::new(
@@ -904,6 +962,53 @@ mod given_interpreter {
             );
         }
 
+        #[test]
+        fn adaptive_qirgen_with_qubit_reuse_reports_qubits_saved() {
+            let mut interpreter = Interpreter::new(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::QubitReset,
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            interpreter.set_reuse_qubits(true);
+            let (_, report) = interpreter
+                .qirgen_with_qubit_reuse_report(indoc! {"{
+                    use a = Qubit();
+                    let r1 = Microsoft.Quantum.Measurement.MResetZ(a);
+                    use b = Qubit();
+                    let r2 = Microsoft.Quantum.Measurement.MResetZ(b);
+                    (r1, r2)
+                }"})
+                .expect("expected success");
+            let report = report.expect("qubit reuse should have run");
+            assert_eq!(report.qubits_before, 2);
+            assert_eq!(report.qubits_after, 1);
+            assert_eq!(report.qubits_saved(), 1);
+        }
+
+        #[test]
+        fn base_qirgen_with_gate_optimization_removes_cancelling_gates() {
+            let mut interpreter = Interpreter::new(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                TargetCapabilityFlags::empty(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let (result, output) = line(
+                &mut interpreter,
+                "operation Foo() : Result { use q = Qubit(); H(q); H(q); X(q); Microsoft.Quantum.Measurement.MResetZ(q) }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            interpreter.set_optimize_gates(true);
+            let res = interpreter.qirgen("Foo()").expect("expected success");
+            assert!(!res.contains("call void @__quantum__qis__h__body"));
+            assert!(res.contains("call void @__quantum__qis__x__body"));
+        }
+
         #[test]
         fn qirgen_entry_expr_in_block() {
             let mut interpreter = Interpreter::new(
@@ -1253,6 +1358,28 @@ mod given_interpreter {
             );
         }
 
+        #[test]
+        fn qirgen_unsupported_output_type_reports_entry_expr() {
+            let mut interpreter = Interpreter::new(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::IntegerComputations,
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let res = interpreter
+                .qirgen("{ use q = Qubit(); let r = M(q); 1.0 }")
+                .expect_err("expected qirgen to fail");
+            is_error(
+                &res,
+                &expect![[r#"
+                partial evaluation error: unsupported type `Double` in output
+                   [<entry>] [{ use q = Qubit(); let r = M(q); 1.0 }]
+            "#]],
+            );
+        }
+
         #[test]
         fn run_with_shots() {
             let mut interpreter = get_interpreter();
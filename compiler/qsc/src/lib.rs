@@ -7,6 +7,7 @@ pub mod error;
 pub mod incremental;
 pub mod interpret;
 pub mod location;
+pub mod qasm;
 pub mod target;
 
 pub use qsc_formatter::formatter;
@@ -34,7 +35,7 @@ pub mod project {
 }
 
 pub use qsc_data_structures::{
-    language_features::LanguageFeatures, span::Span, target::TargetCapabilityFlags,
+    functors, language_features::LanguageFeatures, span::Span, target::TargetCapabilityFlags,
 };
 
 pub use qsc_passes::{lower_hir_to_fir, PackageType, PassContext};
@@ -45,7 +46,9 @@ pub mod line_column {
 
 pub use qsc_eval::{
     backend::{Backend, SparseSim},
-    state::{fmt_basis_state_label, fmt_complex, format_state_id, get_latex, get_phase},
+    state::{
+        fmt_basis_state_label, fmt_complex, format_state_id, get_latex, get_phase, QubitState,
+    },
 };
 
 pub mod linter {
@@ -55,5 +58,5 @@ pub mod linter {
 pub use qsc_doc_gen::{display, generate_docs};
 
 pub mod circuit {
-    pub use qsc_circuit::{operations::*, Circuit, Operation};
+    pub use qsc_circuit::{operations::*, Circuit, ConditionalRender, Operation, Register};
 }
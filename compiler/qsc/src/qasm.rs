@@ -0,0 +1,7 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+pub use qsc_qasm::{is_identifier, qasm_to_qsharp, Error};
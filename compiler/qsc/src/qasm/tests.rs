@@ -0,0 +1,138 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::unicode_not_nfc)]
+
+use super::qasm_to_qsharp;
+use crate::interpret::{CircuitEntryPoint, Interpreter};
+use expect_test::expect;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_eval::output::GenericReceiver;
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+
+fn interpreter(qasm: &str) -> Interpreter {
+    let qsharp = qasm_to_qsharp(qasm, "Qasm", "Circuit").expect("import should succeed");
+    let sources = SourceMap::new([("circuit.qasm".into(), qsharp.into())], None);
+    Interpreter::new(
+        true,
+        sources,
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter creation should succeed")
+}
+
+const GHZ: &str = r#"
+    OPENQASM 2.0;
+    include "qelib1.inc";
+    gate prep a, b, c { x a; cx a, b; ccx a, b, c; }
+    qreg q[3];
+    creg c[3];
+    prep q[0], q[1], q[2];
+    measure q -> c;
+"#;
+
+#[test]
+fn imported_circuit_runs() {
+    let mut interpreter = interpreter(GHZ);
+    let mut out = Vec::new();
+    let mut receiver = GenericReceiver::new(&mut out);
+    let result = interpreter
+        .run(&mut receiver, "Qasm.Circuit()")
+        .expect("run should succeed")
+        .expect("program should succeed");
+
+    expect!["[One, One, One]"].assert_eq(&result.to_string());
+}
+
+#[test]
+fn imported_circuit_can_be_drawn() {
+    let mut interpreter = interpreter(GHZ);
+    let circuit = interpreter
+        .circuit(
+            CircuitEntryPoint::EntryExpr("Qasm.Circuit()".to_string()),
+            false,
+        )
+        .expect("circuit generation should succeed");
+
+    expect![[r"
+        q_0    ── X ──── ● ──── ● ──── M ─── |0〉 ─
+                         │      │      ╘══════════
+        q_1    ───────── X ──── ● ──── M ─── |0〉 ─
+                                │      ╘══════════
+        q_2    ─────────────── CX ──── M ─── |0〉 ─
+                                       ╘══════════
+    "]]
+    .assert_eq(&circuit.to_string());
+}
+
+#[test]
+fn imported_gate_is_controllable_from_qsharp() {
+    let mut interpreter = interpreter("OPENQASM 3; include \"stdgates.inc\"; gate flip a { x a; }");
+    let mut out = Vec::new();
+    let mut receiver = GenericReceiver::new(&mut out);
+    let result = interpreter
+        .run(
+            &mut receiver,
+            "{ use (c, t) = (Qubit(), Qubit()); X(c); Controlled Qasm.flip([c], t); let r = MResetZ(t); Reset(c); r }",
+        )
+        .expect("run should succeed")
+        .expect("program should succeed");
+
+    expect!["One"].assert_eq(&result.to_string());
+}
+
+#[test]
+fn imported_controlled_u_has_no_relative_phase() {
+    // `cu3(pi, 0, pi)` is exactly a controlled X, so applying it twice leaves the control in |+〉.
+    // If `U` only matched up to a global phase, the control would pick up a relative phase.
+    let mut interpreter = interpreter(
+        r#"
+        OPENQASM 2.0;
+        include "qelib1.inc";
+        qreg q[2];
+        creg c[1];
+        h q[0];
+        cu3(pi, 0, pi) q[0], q[1];
+        cu3(pi, 0, pi) q[0], q[1];
+        h q[0];
+        measure q[0] -> c[0];
+        "#,
+    );
+    let mut out = Vec::new();
+    let mut receiver = GenericReceiver::new(&mut out);
+    let result = interpreter
+        .run(&mut receiver, "Qasm.Circuit()")
+        .expect("run should succeed")
+        .expect("program should succeed");
+
+    expect!["[Zero]"].assert_eq(&result.to_string());
+}
+
+#[test]
+fn imported_square_root_of_x_runs() {
+    let mut interpreter = interpreter(
+        r#"
+        OPENQASM 3;
+        include "stdgates.inc";
+        qubit[2] q;
+        bit[2] c;
+        sx q[0];
+        sx q[0];
+        x q[1];
+        csx q[1], q[0];
+        csx q[1], q[0];
+        c = measure q;
+        "#,
+    );
+    let mut out = Vec::new();
+    let mut receiver = GenericReceiver::new(&mut out);
+    let result = interpreter
+        .run(&mut receiver, "Qasm.Circuit()")
+        .expect("run should succeed")
+        .expect("program should succeed");
+
+    expect!["[Zero, One]"].assert_eq(&result.to_string());
+}
@@ -8,15 +8,20 @@ use crate::{
 use num_bigint::BigUint;
 use num_complex::Complex;
 use qsc_codegen::remapper::{HardwareId, Remapper};
-use qsc_data_structures::index_map::IndexMap;
+use qsc_data_structures::{functors::FunctorApp, index_map::IndexMap};
 use qsc_eval::{backend::Backend, val::Value};
 use std::{fmt::Write, mem::take, rc::Rc};
 
 /// Backend implementation that builds a circuit representation.
+#[derive(Clone)]
 pub struct Builder {
     circuit: Circuit,
     config: Config,
     remapper: Remapper,
+    /// For each callable entered and not yet left, whether it opened a group.
+    call_stack: Vec<bool>,
+    /// The groups for the operation calls currently being evaluated, outermost first.
+    groups: Vec<Operation>,
 }
 
 impl Backend for Builder {
@@ -214,6 +219,34 @@ impl Backend for Builder {
             _ => Some(Ok(Value::unit())),
         }
     }
+
+    fn enter_callable(&mut self, name: &str, functor: FunctorApp, arg: &Value) {
+        let opens_group = self.call_stack.len() < self.config.max_operation_depth;
+        self.call_stack.push(opens_group);
+        if opens_group {
+            let (_, classical_args) = self.split_qubit_args(arg.clone());
+            let mut group = custom_gate(
+                name,
+                &[],
+                if classical_args.is_empty() {
+                    None
+                } else {
+                    Some(classical_args)
+                },
+            );
+            group.is_adjoint = functor.adjoint;
+            self.groups.push(group);
+        }
+    }
+
+    fn leave_callable(&mut self) {
+        if self.call_stack.pop() == Some(true) {
+            let group = self.groups.pop().expect("group should be open");
+            if let Some(group) = finish_group(group) {
+                self.push_gate(group);
+            }
+        }
+    }
 }
 
 impl Builder {
@@ -223,18 +256,28 @@ impl Builder {
             circuit: Circuit::default(),
             config,
             remapper: Remapper::default(),
+            call_stack: Vec::new(),
+            groups: Vec::new(),
         }
     }
 
+    /// Sets the maximum depth of nested operation calls that are grouped into parent operations.
+    /// Operation calls that are already being evaluated are not affected.
+    pub fn set_max_operation_depth(&mut self, depth: usize) {
+        self.config.max_operation_depth = depth;
+    }
+
     #[must_use]
     pub fn snapshot(&self) -> Circuit {
-        let circuit = self.circuit.clone();
+        let mut circuit = self.circuit.clone();
+        close_groups(&mut circuit, self.groups.clone());
         self.finish_circuit(circuit)
     }
 
     #[must_use]
     pub fn finish(mut self) -> Circuit {
-        let circuit = take(&mut self.circuit);
+        let mut circuit = take(&mut self.circuit);
+        close_groups(&mut circuit, take(&mut self.groups));
         self.finish_circuit(circuit)
     }
 
@@ -243,7 +286,10 @@ impl Builder {
     }
 
     fn push_gate(&mut self, gate: Operation) {
-        self.circuit.operations.push(gate);
+        match self.groups.last_mut() {
+            Some(group) => group.children.push(gate),
+            None => self.circuit.operations.push(gate),
+        }
     }
 
     fn num_measurements_by_qubit(&self) -> IndexMap<usize, usize> {
@@ -370,36 +416,72 @@ impl Builder {
     }
 }
 
+/// Closes the groups for operation calls that have not returned yet, innermost first,
+/// and adds them to the circuit.
+fn close_groups(circuit: &mut Circuit, mut groups: Vec<Operation>) {
+    while let Some(group) = groups.pop() {
+        if let Some(group) = finish_group(group) {
+            match groups.last_mut() {
+                Some(parent) => parent.children.push(group),
+                None => circuit.operations.push(group),
+            }
+        }
+    }
+}
+
+/// Completes a group by targeting every qubit its children act on.
+/// Groups without any children are dropped.
+pub(crate) fn finish_group(mut group: Operation) -> Option<Operation> {
+    if group.children.is_empty() {
+        return None;
+    }
+    let mut targets = group
+        .children
+        .iter()
+        .flat_map(|child| child.controls.iter().chain(child.targets.iter()))
+        .filter(|reg| reg.c_id.is_none())
+        .map(|reg| reg.q_id)
+        .collect::<Vec<_>>();
+    targets.sort_unstable();
+    targets.dedup();
+    group.targets = targets.into_iter().map(Register::quantum).collect();
+    Some(group)
+}
+
 #[allow(clippy::unicode_not_nfc)]
-static KET_ZERO: &str = "|0〉";
+pub(crate) static KET_ZERO: &str = "|0〉";
 
-fn gate<const N: usize>(name: &str, targets: [HardwareId; N]) -> Operation {
+pub(crate) fn gate<const N: usize>(name: &str, targets: [HardwareId; N]) -> Operation {
     Operation {
         gate: name.into(),
         display_args: None,
         is_controlled: false,
         is_adjoint: false,
         is_measurement: false,
+        is_conditional: false,
+        conditional_render: None,
         controls: vec![],
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
     }
 }
 
-fn adjoint_gate<const N: usize>(name: &str, targets: [HardwareId; N]) -> Operation {
+pub(crate) fn adjoint_gate<const N: usize>(name: &str, targets: [HardwareId; N]) -> Operation {
     Operation {
         gate: name.into(),
         display_args: None,
         is_controlled: false,
         is_adjoint: true,
         is_measurement: false,
+        is_conditional: false,
+        conditional_render: None,
         controls: vec![],
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
     }
 }
 
-fn controlled_gate<const M: usize, const N: usize>(
+pub(crate) fn controlled_gate<const M: usize, const N: usize>(
     name: &str,
     controls: [HardwareId; M],
     targets: [HardwareId; N],
@@ -410,45 +492,61 @@ fn controlled_gate<const M: usize, const N: usize>(
         is_controlled: true,
         is_adjoint: false,
         is_measurement: false,
+        is_conditional: false,
+        conditional_render: None,
         controls: controls.iter().map(|q| Register::quantum(q.0)).collect(),
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
     }
 }
 
-fn measurement_gate(qubit: usize, result: usize) -> Operation {
+pub(crate) fn measurement_gate(qubit: usize, result: usize) -> Operation {
     Operation {
         gate: "Measure".into(),
         display_args: None,
         is_controlled: false,
         is_adjoint: false,
         is_measurement: true,
+        is_conditional: false,
+        conditional_render: None,
         controls: vec![Register::quantum(qubit)],
         targets: vec![Register::classical(qubit, result)],
         children: vec![],
     }
 }
 
-fn rotation_gate<const N: usize>(name: &str, theta: f64, targets: [HardwareId; N]) -> Operation {
+pub(crate) fn rotation_gate<const N: usize>(
+    name: &str,
+    theta: f64,
+    targets: [HardwareId; N],
+) -> Operation {
     Operation {
         gate: name.into(),
         display_args: Some(format!("{theta:.4}")),
         is_controlled: false,
         is_adjoint: false,
         is_measurement: false,
+        is_conditional: false,
+        conditional_render: None,
         controls: vec![],
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
     }
 }
 
-fn custom_gate(name: &str, targets: &[HardwareId], display_args: Option<String>) -> Operation {
+pub(crate) fn custom_gate(
+    name: &str,
+    targets: &[HardwareId],
+    display_args: Option<String>,
+) -> Operation {
     Operation {
         gate: name.into(),
         display_args,
         is_controlled: false,
         is_adjoint: false,
         is_measurement: false,
+        is_conditional: false,
+        conditional_render: None,
         controls: vec![],
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
@@ -32,6 +32,15 @@ pub struct Operation {
     #[serde(rename = "isMeasurement")]
     #[serde(skip_serializing_if = "Not::not")]
     pub is_measurement: bool,
+    /// Whether the operation is controlled by the classical registers in `controls`.
+    #[serde(rename = "isConditional")]
+    #[serde(skip_serializing_if = "Not::not")]
+    pub is_conditional: bool,
+    /// For the children of a conditional operation, which outcome of
+    /// the classical control they are applied on.
+    #[serde(rename = "conditionalRender")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conditional_render: Option<ConditionalRender>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub controls: Vec<Register>,
     pub targets: Vec<Register>,
@@ -39,6 +48,22 @@ pub struct Operation {
     pub children: Vec<Operation>,
 }
 
+/// How an operation inside a classically controlled group is rendered.
+/// Serialized as the integer values of the `ConditionalRender` enum in quantum-viz.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConditionalRender {
+    Always = 0,
+    OnZero = 1,
+    OnOne = 2,
+    AsGroup = 3,
+}
+
+impl Serialize for ConditionalRender {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
 const QUANTUM_REGISTER: usize = 0;
 const CLASSICAL_REGISTER: usize = 1;
 
@@ -81,6 +106,9 @@ pub struct Qubit {
 pub struct Config {
     /// Perform Base Profile decompositions
     pub base_profile: bool,
+    /// Maximum depth of nested operation calls that are grouped into parent operations.
+    /// `0` produces a flat list of gates.
+    pub max_operation_depth: usize,
 }
 
 type ObjectsByColumn = FxHashMap<usize, String>;
@@ -303,7 +331,7 @@ impl Display for Circuit {
                 };
             }
 
-            if o.is_controlled || o.is_measurement {
+            if o.is_controlled || o.is_measurement || o.is_conditional {
                 for i in controls {
                     let row = &mut rows[i];
                     if matches!(row.wire, Wire::Qubit { .. }) && o.is_measurement {
@@ -47,6 +47,8 @@ fn bell() {
                 is_controlled: false,
                 is_adjoint: false,
                 is_measurement: false,
+                is_conditional: false,
+                conditional_render: None,
                 controls: vec![],
                 targets: vec![Register::quantum(0)],
                 children: vec![],
@@ -57,6 +59,8 @@ fn bell() {
                 is_controlled: true,
                 is_adjoint: false,
                 is_measurement: false,
+                is_conditional: false,
+                conditional_render: None,
                 controls: vec![Register::quantum(0)],
                 targets: vec![Register::quantum(1)],
                 children: vec![],
@@ -67,6 +71,8 @@ fn bell() {
                 is_controlled: false,
                 is_adjoint: false,
                 is_measurement: true,
+                is_conditional: false,
+                conditional_render: None,
                 controls: vec![Register::quantum(0)],
                 targets: vec![Register::classical(0, 0)],
                 children: vec![],
@@ -77,6 +83,8 @@ fn bell() {
                 is_controlled: false,
                 is_adjoint: false,
                 is_measurement: true,
+                is_conditional: false,
+                conditional_render: None,
                 controls: vec![Register::quantum(1)],
                 targets: vec![Register::classical(1, 0)],
                 children: vec![],
@@ -113,6 +121,8 @@ fn control_classical() {
                 is_controlled: false,
                 is_adjoint: false,
                 is_measurement: true,
+                is_conditional: false,
+                conditional_render: None,
                 controls: vec![Register::quantum(0)],
                 targets: vec![Register::classical(0, 0)],
                 children: vec![],
@@ -123,6 +133,8 @@ fn control_classical() {
                 is_controlled: true,
                 is_adjoint: false,
                 is_measurement: false,
+                is_conditional: false,
+                conditional_render: None,
                 controls: vec![Register::classical(0, 0)],
                 targets: vec![Register::quantum(2)],
                 children: vec![],
@@ -133,6 +145,8 @@ fn control_classical() {
                 is_controlled: true,
                 is_adjoint: false,
                 is_measurement: false,
+                is_conditional: false,
+                conditional_render: None,
                 controls: vec![Register::quantum(0)],
                 targets: vec![Register::quantum(2)],
                 children: vec![],
@@ -173,6 +187,8 @@ fn two_measurements() {
                 is_controlled: false,
                 is_adjoint: false,
                 is_measurement: true,
+                is_conditional: false,
+                conditional_render: None,
                 controls: vec![Register::quantum(0)],
                 targets: vec![Register::classical(0, 0)],
                 children: vec![],
@@ -183,6 +199,8 @@ fn two_measurements() {
                 is_controlled: false,
                 is_adjoint: false,
                 is_measurement: true,
+                is_conditional: false,
+                conditional_render: None,
                 controls: vec![Register::quantum(0)],
                 targets: vec![Register::classical(0, 1)],
                 children: vec![],
@@ -211,6 +229,8 @@ fn with_args() {
             is_controlled: false,
             is_adjoint: false,
             is_measurement: false,
+            is_conditional: false,
+            conditional_render: None,
             controls: vec![],
             targets: vec![Register::quantum(0)],
             children: vec![],
@@ -238,6 +258,8 @@ fn two_targets() {
             is_controlled: false,
             is_adjoint: false,
             is_measurement: false,
+            is_conditional: false,
+            conditional_render: None,
             controls: vec![],
             targets: vec![Register::quantum(0), Register::quantum(2)],
             children: vec![],
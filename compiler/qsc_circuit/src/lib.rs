@@ -4,7 +4,9 @@
 mod builder;
 mod circuit;
 pub mod operations;
+mod rir_to_circuit;
 
 pub use builder::Builder;
-pub use circuit::{Circuit, Config, Operation};
+pub use circuit::{Circuit, ConditionalRender, Config, Operation, Register};
 pub use operations::Error;
+pub use rir_to_circuit::rir_to_circuit;
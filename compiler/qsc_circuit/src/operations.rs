@@ -25,6 +25,20 @@ pub enum Error {
         "controlled invocations are not currently supported. consider wrapping the invocation in a lambda expression"
     ))]
     ControlledUnsupported,
+    #[error(
+        "cannot generate circuit for a branch that is not conditioned on a measurement result"
+    )]
+    #[diagnostic(code("Qsc.Circuit.UnsupportedCondition"))]
+    #[diagnostic(help(
+        "only branches that compare a single measurement result to `One` or `Zero` can be shown as classically controlled operations"
+    ))]
+    UnsupportedCondition,
+    #[error("cannot generate circuit for a program that contains loops")]
+    #[diagnostic(code("Qsc.Circuit.LoopUnsupported"))]
+    #[diagnostic(help(
+        "loops that depend on measurement results cannot be shown in a circuit. consider generating the circuit by simulating the program"
+    ))]
+    LoopUnsupported,
 }
 
 /// If the item is a callable, returns the information that would
@@ -0,0 +1,298 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    builder::{
+        adjoint_gate, controlled_gate, custom_gate, finish_group, gate, measurement_gate,
+        rotation_gate, KET_ZERO,
+    },
+    circuit::{Circuit, ConditionalRender, Operation, Qubit, Register},
+    Error,
+};
+use qsc_codegen::remapper::HardwareId;
+use qsc_rir::{
+    rir::{
+        BlockId, CallableType, ConditionCode, Instruction, Literal, Operand, Program, VariableId,
+    },
+    utils::{get_all_block_successors, get_merge_block},
+};
+use rustc_hash::FxHashMap;
+
+/// Generates a circuit for a program that has been partially evaluated into RIR.
+///
+/// Branches on measurement results become classically controlled groups,
+/// with the operations of each branch rendered on the matching outcome.
+/// The circuit is not grouped by operation calls, since partial evaluation
+/// inlines them, so [`crate::Config::max_operation_depth`] does not apply.
+pub fn rir_to_circuit(program: &Program) -> Result<Circuit, Error> {
+    let entry = program.get_callable(program.entry);
+    let body = entry.body.expect("entry point should have a body");
+    // A block reachable from itself is part of a loop, which a circuit cannot represent.
+    if get_all_block_successors(body, program)
+        .iter()
+        .any(|block_id| get_all_block_successors(*block_id, program).contains(block_id))
+    {
+        return Err(Error::LoopUnsupported);
+    }
+    let mut builder = RirBuilder {
+        program,
+        results: FxHashMap::default(),
+        conditions: FxHashMap::default(),
+        measurements_per_qubit: vec![0; program.num_qubits as usize],
+    };
+    let operations = builder.operations(body, None)?;
+    let qubits = builder
+        .measurements_per_qubit
+        .iter()
+        .enumerate()
+        .map(|(id, num_children)| Qubit {
+            id,
+            num_children: *num_children,
+        })
+        .collect();
+    Ok(Circuit { operations, qubits })
+}
+
+/// A boolean variable whose value depends on a measurement result.
+#[derive(Clone)]
+struct Condition {
+    /// The classical register holding the measurement result.
+    register: Register,
+    /// Whether the variable is true when the result is `One`, rather than `Zero`.
+    on_one: bool,
+}
+
+struct RirBuilder<'a> {
+    program: &'a Program,
+    /// The classical register for each measured result.
+    results: FxHashMap<u32, Register>,
+    /// The variables that can be used as classical controls.
+    conditions: FxHashMap<VariableId, Condition>,
+    measurements_per_qubit: Vec<usize>,
+}
+
+impl RirBuilder<'_> {
+    /// Collects the operations from the blocks starting at `start`, following jumps
+    /// and branches until `stop` or the end of the callable is reached.
+    fn operations(
+        &mut self,
+        start: BlockId,
+        stop: Option<BlockId>,
+    ) -> Result<Vec<Operation>, Error> {
+        let mut operations = Vec::new();
+        let mut block_id = start;
+        'blocks: while Some(block_id) != stop {
+            for instruction in &self.program.get_block(block_id).0 {
+                match instruction {
+                    Instruction::Call(callable_id, args, var) => {
+                        let callable = self.program.get_callable(*callable_id);
+                        match callable.call_type {
+                            CallableType::Measurement => {
+                                operations.extend(self.measurement(&callable.name, args));
+                            }
+                            CallableType::Reset => {
+                                operations.push(custom_gate(KET_ZERO, &qubit_args(args), None));
+                            }
+                            CallableType::Readout => {
+                                if let (Some(var), Some(Operand::Literal(Literal::Result(r)))) =
+                                    (var, args.first())
+                                {
+                                    if let Some(register) = self.results.get(r) {
+                                        let condition = Condition {
+                                            register: register.clone(),
+                                            on_one: true,
+                                        };
+                                        self.conditions.insert(var.variable_id, condition);
+                                    }
+                                }
+                            }
+                            CallableType::OutputRecording => {}
+                            CallableType::Regular => {
+                                operations.push(call_gate(&callable.name, args));
+                            }
+                        }
+                    }
+                    Instruction::Icmp(code, lhs, rhs, var) => {
+                        let ((Operand::Variable(cond), Operand::Literal(Literal::Bool(value)))
+                        | (Operand::Literal(Literal::Bool(value)), Operand::Variable(cond))) =
+                            (lhs, rhs)
+                        else {
+                            continue;
+                        };
+                        let keep = match code {
+                            ConditionCode::Eq => *value,
+                            ConditionCode::Ne => !*value,
+                            _ => continue,
+                        };
+                        self.derive_condition(cond.variable_id, var.variable_id, !keep);
+                    }
+                    Instruction::LogicalNot(Operand::Variable(cond), var) => {
+                        self.derive_condition(cond.variable_id, var.variable_id, true);
+                    }
+                    Instruction::Store(Operand::Variable(cond), var) => {
+                        self.derive_condition(cond.variable_id, var.variable_id, false);
+                    }
+                    Instruction::Branch(cond, if_true, if_false) => {
+                        let condition = self
+                            .conditions
+                            .get(&cond.variable_id)
+                            .cloned()
+                            .ok_or(Error::UnsupportedCondition)?;
+                        let merge = get_merge_block(*if_true, *if_false, self.program);
+                        let true_ops = self.operations(*if_true, merge)?;
+                        let false_ops = self.operations(*if_false, merge)?;
+                        operations.extend(conditional_group(condition, true_ops, false_ops));
+                        match merge {
+                            Some(merge) => {
+                                block_id = merge;
+                                continue 'blocks;
+                            }
+                            None => break 'blocks,
+                        }
+                    }
+                    Instruction::Jump(target) => {
+                        block_id = *target;
+                        continue 'blocks;
+                    }
+                    Instruction::Return => break 'blocks,
+                    _ => {}
+                }
+            }
+            break;
+        }
+        Ok(operations)
+    }
+
+    /// Records that `var` is a condition on the same measurement result as `cond`.
+    fn derive_condition(&mut self, cond: VariableId, var: VariableId, negate: bool) {
+        if let Some(condition) = self.conditions.get(&cond) {
+            let condition = Condition {
+                register: condition.register.clone(),
+                on_one: condition.on_one != negate,
+            };
+            self.conditions.insert(var, condition);
+        }
+    }
+
+    fn measurement(&mut self, name: &str, args: &[Operand]) -> Vec<Operation> {
+        let (Some(Operand::Literal(Literal::Qubit(q))), Some(Operand::Literal(Literal::Result(r)))) =
+            (args.first(), args.get(1))
+        else {
+            return vec![custom_gate(name, &qubit_args(args), None)];
+        };
+        let q = *q as usize;
+        // In the Circuit schema, result id is per-qubit
+        let res_id = self.measurements_per_qubit[q];
+        self.measurements_per_qubit[q] += 1;
+        self.results.insert(*r, Register::classical(q, res_id));
+
+        let mut operations = vec![measurement_gate(q, res_id)];
+        if name == "__quantum__qis__mresetz__body" {
+            // Decompose MResetZ into a measurement and a reset gate, as the circuit builder does.
+            operations.push(gate(KET_ZERO, [HardwareId(q)]));
+        }
+        operations
+    }
+}
+
+/// Creates a classically controlled group for a branch on `condition`.
+/// Groups without any operations in either branch are dropped.
+fn conditional_group(
+    condition: Condition,
+    if_true: Vec<Operation>,
+    if_false: Vec<Operation>,
+) -> Option<Operation> {
+    let (on_one, on_zero) = if condition.on_one {
+        (if_true, if_false)
+    } else {
+        (if_false, if_true)
+    };
+    let mut group = custom_gate("if", &[], None);
+    group.is_conditional = true;
+    group.controls = vec![condition.register];
+    group.children = on_one
+        .into_iter()
+        .map(|op| (op, ConditionalRender::OnOne))
+        .chain(
+            on_zero
+                .into_iter()
+                .map(|op| (op, ConditionalRender::OnZero)),
+        )
+        .map(|(mut op, render)| {
+            op.conditional_render = Some(render);
+            op
+        })
+        .collect();
+    finish_group(group)
+}
+
+fn qubit_args(args: &[Operand]) -> Vec<HardwareId> {
+    args.iter()
+        .filter_map(|arg| match arg {
+            Operand::Literal(Literal::Qubit(q)) => Some(HardwareId(*q as usize)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Creates the gate for a call to a quantum intrinsic.
+fn call_gate(name: &str, args: &[Operand]) -> Operation {
+    let qubits = qubit_args(args);
+    let angle = args.iter().find_map(|arg| match arg {
+        Operand::Literal(Literal::Double(d)) => Some(*d),
+        _ => None,
+    });
+    match (name, qubits.as_slice(), angle) {
+        ("__quantum__qis__ccx__body", &[ctl0, ctl1, q], _) => {
+            controlled_gate("CX", [ctl0, ctl1], [q])
+        }
+        ("__quantum__qis__cx__body", &[ctl, q], _) => controlled_gate("X", [ctl], [q]),
+        ("__quantum__qis__cy__body", &[ctl, q], _) => controlled_gate("Y", [ctl], [q]),
+        ("__quantum__qis__cz__body", &[ctl, q], _) => controlled_gate("Z", [ctl], [q]),
+        ("__quantum__qis__h__body", &[q], _) => gate("H", [q]),
+        ("__quantum__qis__s__body", &[q], _) => gate("S", [q]),
+        ("__quantum__qis__s__adj", &[q], _) => adjoint_gate("S", [q]),
+        ("__quantum__qis__t__body", &[q], _) => gate("T", [q]),
+        ("__quantum__qis__t__adj", &[q], _) => adjoint_gate("T", [q]),
+        ("__quantum__qis__x__body", &[q], _) => gate("X", [q]),
+        ("__quantum__qis__y__body", &[q], _) => gate("Y", [q]),
+        ("__quantum__qis__z__body", &[q], _) => gate("Z", [q]),
+        ("__quantum__qis__swap__body", &[q0, q1], _) => gate("SWAP", [q0, q1]),
+        ("__quantum__qis__rx__body", &[q], Some(theta)) => rotation_gate("rx", theta, [q]),
+        ("__quantum__qis__ry__body", &[q], Some(theta)) => rotation_gate("ry", theta, [q]),
+        ("__quantum__qis__rz__body", &[q], Some(theta)) => rotation_gate("rz", theta, [q]),
+        ("__quantum__qis__rxx__body", &[q0, q1], Some(theta)) => {
+            rotation_gate("rxx", theta, [q0, q1])
+        }
+        ("__quantum__qis__ryy__body", &[q0, q1], Some(theta)) => {
+            rotation_gate("ryy", theta, [q0, q1])
+        }
+        ("__quantum__qis__rzz__body", &[q0, q1], Some(theta)) => {
+            rotation_gate("rzz", theta, [q0, q1])
+        }
+        _ => {
+            // Any classical literal arguments are shown as part of the gate label.
+            let classical_args = args
+                .iter()
+                .filter_map(|arg| match arg {
+                    Operand::Literal(Literal::Bool(b)) => Some(b.to_string()),
+                    Operand::Literal(Literal::Integer(i)) => Some(i.to_string()),
+                    Operand::Literal(Literal::Double(d)) => Some(d.to_string()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            custom_gate(
+                name,
+                &qubits,
+                if classical_args.is_empty() {
+                    None
+                } else {
+                    Some(classical_args.join(", "))
+                },
+            )
+        }
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::rir_to_circuit;
+use expect_test::expect;
+use qsc_rir::{
+    builder::{
+        bell_program, mresetz_decl, new_program, read_result_decl, teleport_program, x_decl, z_decl,
+    },
+    rir::{
+        Block, BlockId, CallableId, ConditionCode, Instruction, Literal, Operand, Program, Ty,
+        Variable, VariableId,
+    },
+};
+
+fn bool_var(id: u32) -> Variable {
+    Variable {
+        variable_id: VariableId(id),
+        ty: Ty::Boolean,
+    }
+}
+
+fn qubit(q: u32) -> Operand {
+    Operand::Literal(Literal::Qubit(q))
+}
+
+/// A program that measures qubit 0, then applies either `X` or `Z` to qubit 1
+/// depending on the result. `condition` computes variable 1 from the result in variable 0.
+fn if_else_program(condition: Instruction) -> Program {
+    let mut program = new_program();
+    program.callables.insert(CallableId(1), mresetz_decl());
+    program.callables.insert(CallableId(2), read_result_decl());
+    program.callables.insert(CallableId(3), x_decl());
+    program.callables.insert(CallableId(4), z_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(
+                CallableId(1),
+                vec![qubit(0), Operand::Literal(Literal::Result(0))],
+                None,
+            ),
+            Instruction::Call(
+                CallableId(2),
+                vec![Operand::Literal(Literal::Result(0))],
+                Some(bool_var(0)),
+            ),
+            condition,
+            Instruction::Branch(bool_var(1), BlockId(1), BlockId(2)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Call(CallableId(3), vec![qubit(1)], None),
+            Instruction::Jump(BlockId(3)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(2),
+        Block(vec![
+            Instruction::Call(CallableId(4), vec![qubit(1)], None),
+            Instruction::Jump(BlockId(3)),
+        ]),
+    );
+    program
+        .blocks
+        .insert(BlockId(3), Block(vec![Instruction::Return]));
+    program.num_qubits = 2;
+    program.num_results = 1;
+    program
+}
+
+#[test]
+fn straight_line_program() {
+    let circuit = rir_to_circuit(&bell_program()).expect("circuit generation should succeed");
+
+    expect![[r#"
+        q_0    ── H ──── ● ──── M ──
+                         │      ╘═══
+        q_1    ───────── X ──── M ──
+                                ╘═══
+    "#]]
+    .assert_eq(&circuit.to_string());
+}
+
+#[test]
+fn branches_on_results_are_conditional_groups() {
+    let circuit = rir_to_circuit(&teleport_program()).expect("circuit generation should succeed");
+
+    expect![[r#"
+        q_0    ── X ─────────── ● ──── H ──── M ─── |0〉 ──────────────────────
+                                │             ╘═════ ● ═══════════════════════
+        q_1    ───────── X ─────┼────────────────── if ─── if ──── M ─── |0〉 ─
+                         │      │                           │      ╘══════════
+        q_2    ── H ──── ● ──── X ──── M ─── |0〉 ───────────┼─────────────────
+                                       ╘═══════════════════ ● ════════════════
+    "#]]
+    .assert_eq(&circuit.to_string());
+
+    let group = &circuit.operations[7];
+    assert!(group.is_conditional);
+    expect![[r#"
+        {
+          "gate": "if",
+          "isConditional": true,
+          "controls": [
+            {
+              "qId": 0,
+              "type": 1,
+              "cId": 0
+            }
+          ],
+          "targets": [
+            {
+              "qId": 1,
+              "type": 0
+            }
+          ],
+          "children": [
+            {
+              "gate": "Z",
+              "conditionalRender": 2,
+              "targets": [
+                {
+                  "qId": 1,
+                  "type": 0
+                }
+              ]
+            }
+          ]
+        }"#]]
+    .assert_eq(&serde_json::to_string_pretty(group).expect("serialization should succeed"));
+}
+
+#[test]
+fn else_branch_renders_on_zero() {
+    let circuit = rir_to_circuit(&if_else_program(Instruction::Icmp(
+        ConditionCode::Eq,
+        Operand::Variable(bool_var(0)),
+        Operand::Literal(Literal::Bool(true)),
+        bool_var(1),
+    )))
+    .expect("circuit generation should succeed");
+
+    let children = &circuit.operations[2].children;
+    expect![[r#"
+        [
+            (
+                "X",
+                Some(
+                    OnOne,
+                ),
+            ),
+            (
+                "Z",
+                Some(
+                    OnZero,
+                ),
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(
+        &children
+            .iter()
+            .map(|op| (op.gate.as_str(), op.conditional_render))
+            .collect::<Vec<_>>(),
+    );
+}
+
+#[test]
+fn negated_condition_swaps_branches() {
+    let circuit = rir_to_circuit(&if_else_program(Instruction::LogicalNot(
+        Operand::Variable(bool_var(0)),
+        bool_var(1),
+    )))
+    .expect("circuit generation should succeed");
+
+    let children = &circuit.operations[2].children;
+    expect![[r#"
+        [
+            (
+                "Z",
+                Some(
+                    OnOne,
+                ),
+            ),
+            (
+                "X",
+                Some(
+                    OnZero,
+                ),
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(
+        &children
+            .iter()
+            .map(|op| (op.gate.as_str(), op.conditional_render))
+            .collect::<Vec<_>>(),
+    );
+}
+
+#[test]
+fn branch_on_classical_value_fails() {
+    let error = rir_to_circuit(&if_else_program(Instruction::Store(
+        Operand::Literal(Literal::Bool(true)),
+        bool_var(1),
+    )))
+    .expect_err("circuit generation should fail");
+
+    expect![[r#"
+        UnsupportedCondition
+    "#]]
+    .assert_debug_eq(&error);
+}
+
+#[test]
+fn loop_fails() {
+    let mut program = new_program();
+    program.callables.insert(CallableId(1), x_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(CallableId(1), vec![qubit(0)], None),
+            Instruction::Jump(BlockId(0)),
+        ]),
+    );
+    program.num_qubits = 1;
+
+    let error = rir_to_circuit(&program).expect_err("circuit generation should fail");
+
+    expect![[r#"
+        LoopUnsupported
+    "#]]
+    .assert_debug_eq(&error);
+}
+
+#[test]
+fn loop_on_measurement_result_fails() {
+    let mut program = new_program();
+    program.callables.insert(CallableId(1), mresetz_decl());
+    program.callables.insert(CallableId(2), read_result_decl());
+    program.callables.insert(CallableId(3), x_decl());
+    program
+        .blocks
+        .insert(BlockId(0), Block(vec![Instruction::Jump(BlockId(1))]));
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Call(
+                CallableId(1),
+                vec![qubit(0), Operand::Literal(Literal::Result(0))],
+                None,
+            ),
+            Instruction::Call(
+                CallableId(2),
+                vec![Operand::Literal(Literal::Result(0))],
+                Some(bool_var(0)),
+            ),
+            Instruction::Branch(bool_var(0), BlockId(2), BlockId(3)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(2),
+        Block(vec![
+            Instruction::Call(CallableId(3), vec![qubit(0)], None),
+            Instruction::Jump(BlockId(1)),
+        ]),
+    );
+    program
+        .blocks
+        .insert(BlockId(3), Block(vec![Instruction::Return]));
+    program.num_qubits = 1;
+    program.num_results = 1;
+
+    let error = rir_to_circuit(&program).expect_err("circuit generation should fail");
+
+    expect![[r#"
+        LoopUnsupported
+    "#]]
+    .assert_debug_eq(&error);
+}
@@ -0,0 +1,561 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::qir::fir_to_rir;
+use miette::Diagnostic;
+use qsc_data_structures::target::TargetCapabilityFlags;
+use qsc_partial_eval::ProgramEntry;
+use qsc_rca::PackageStoreComputeProperties;
+use qsc_rir::{
+    rir::{
+        BlockId, CallableType, ConditionCode, FcmpConditionCode, Instruction, Literal, Operand,
+        Program, Ty, Variable, VariableId,
+    },
+    utils::{get_all_block_successors, get_block_successors, get_instruction_operands},
+};
+use rustc_hash::FxHashSet;
+use std::{collections::BTreeMap, fmt::Write};
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    PartialEvaluation(#[from] qsc_partial_eval::Error),
+
+    #[error("intrinsic `{0}` is not supported in OpenQASM 3")]
+    #[diagnostic(code("Qsc.Qasm3.UnsupportedIntrinsic"))]
+    UnsupportedIntrinsic(String),
+
+    #[error("value `{0}` cannot be represented in OpenQASM 3")]
+    #[diagnostic(code("Qsc.Qasm3.NonFiniteLiteral"))]
+    #[diagnostic(help("OpenQASM 3 has no literals for infinite or NaN values"))]
+    NonFiniteLiteral(f64),
+}
+
+/// Converts the given entry to OpenQASM 3 by way of partial evaluation.
+pub fn fir_to_qasm3(
+    fir_store: &qsc_fir::fir::PackageStore,
+    capabilities: TargetCapabilityFlags,
+    compute_properties: Option<PackageStoreComputeProperties>,
+    entry: &ProgramEntry,
+) -> Result<String, Error> {
+    let (program, _) = fir_to_rir(
+        fir_store,
+        capabilities,
+        false,
+        false,
+        compute_properties,
+        entry,
+    )?;
+    rir_to_qasm3(&program)
+}
+
+/// Converts a RIR program in SSA form to an OpenQASM 3 program.
+///
+/// Qubits and results are declared as the registers `q` and `c`, and RIR variables are declared
+/// up front as `var_<id>`. Branches become `if`/`else` statements that rejoin at the block
+/// where control flow merges, and each `Phi` becomes an assignment at the end of
+/// its predecessors. Loops become `while (true)` statements that `break` on the branch
+/// leaving the loop. Recorded outputs become `output` variables, while the tuple
+/// and array structure of the output is not represented.
+///
+/// Calls to intrinsics that have no OpenQASM 3 gate and double literals that are infinite or NaN
+/// are reported as errors.
+pub fn rir_to_qasm3(program: &Program) -> Result<String, Error> {
+    check_program(program)?;
+    let entry = program.get_callable(program.entry);
+    let body = entry.body.expect("entry point should have a body");
+    // Block IDs are in reverse postorder, so the targets of back edges are exactly the successors
+    // with IDs lower than or equal to their predecessors.
+    let mut loop_headers = FxHashSet::default();
+    for block_id in get_all_block_successors(body, program)
+        .into_iter()
+        .chain(std::iter::once(body))
+    {
+        for successor in get_block_successors(program.get_block(block_id)) {
+            if successor <= block_id {
+                loop_headers.insert(successor);
+            }
+        }
+    }
+    let mut generator = Qasm3Generator {
+        program,
+        loop_headers,
+        loops: Vec::new(),
+        variables: BTreeMap::new(),
+        outputs: Vec::new(),
+        gate_defs: Vec::new(),
+        lines: Vec::new(),
+        indent: 0,
+    };
+    generator.blocks(None, body, None);
+
+    let mut qasm = String::new();
+    qasm.push_str("OPENQASM 3.0;\ninclude \"stdgates.inc\";\n");
+    for gate_def in &generator.gate_defs {
+        qasm.push_str(gate_def);
+    }
+    for (name, ty) in &generator.outputs {
+        let _ = writeln!(qasm, "output {ty} {name};");
+    }
+    if program.num_qubits > 0 {
+        let _ = writeln!(qasm, "qubit[{}] q;", program.num_qubits);
+    }
+    if program.num_results > 0 {
+        let _ = writeln!(qasm, "bit[{}] c;", program.num_results);
+    }
+    for (variable_id, ty) in &generator.variables {
+        let _ = writeln!(qasm, "{} var_{};", ty_to_qasm3(*ty), variable_id.0);
+    }
+    for line in &generator.lines {
+        qasm.push_str(line);
+        qasm.push('\n');
+    }
+    Ok(qasm)
+}
+
+/// Checks that every instruction in the program can be represented in OpenQASM 3.
+fn check_program(program: &Program) -> Result<(), Error> {
+    for (_, block) in program.blocks.iter() {
+        for instruction in &block.0 {
+            if let Instruction::Call(callable_id, ..) = instruction {
+                let callable = program.get_callable(*callable_id);
+                if callable.call_type == CallableType::Regular && gate(&callable.name).is_none() {
+                    return Err(Error::UnsupportedIntrinsic(callable.name.clone()));
+                }
+            }
+            for operand in get_instruction_operands(instruction) {
+                if let Operand::Literal(Literal::Double(d)) = operand {
+                    if !d.is_finite() {
+                        return Err(Error::NonFiniteLiteral(d));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+struct Qasm3Generator<'a> {
+    program: &'a Program,
+    /// The blocks that are the target of a back edge.
+    loop_headers: FxHashSet<BlockId>,
+    /// The loops being emitted, innermost last.
+    loops: Vec<Loop>,
+    /// The type of each variable assigned in the program.
+    variables: BTreeMap<VariableId, Ty>,
+    /// The name and type of each recorded output, in order.
+    outputs: Vec<(String, &'static str)>,
+    /// Definitions of the gates that are not part of `stdgates.inc`.
+    gate_defs: Vec<&'static str>,
+    lines: Vec<String>,
+    indent: usize,
+}
+
+/// A loop being emitted, with the block control flow continues at once it is left.
+struct Loop {
+    header: BlockId,
+    exit: Option<BlockId>,
+}
+
+impl Qasm3Generator<'_> {
+    fn line(&mut self, line: impl AsRef<str>) {
+        self.lines.push(format!(
+            "{:width$}{}",
+            "",
+            line.as_ref(),
+            width = self.indent * 4
+        ));
+    }
+
+    /// Emits the blocks starting at `block_id`, following jumps and branches until `stop`,
+    /// the header of the innermost loop being emitted, or the end of the callable is reached.
+    /// `pred` is the block that transferred control to `block_id`, if its `Phi` assignments
+    /// have not been emitted yet.
+    fn blocks(&mut self, mut pred: Option<BlockId>, mut block_id: BlockId, stop: Option<BlockId>) {
+        loop {
+            if let Some(pred) = pred {
+                self.phi_assignments(pred, block_id);
+            }
+            if Some(block_id) == stop
+                || self.loops.last().map(|current| current.header) == Some(block_id)
+            {
+                return;
+            }
+            let next = if self.loop_headers.contains(&block_id) {
+                self.while_loop(block_id)
+            } else {
+                self.block(block_id)
+            };
+            match next {
+                Some((next_pred, next_block_id)) => {
+                    pred = next_pred;
+                    block_id = next_block_id;
+                }
+                None => return,
+            }
+        }
+    }
+
+    /// Emits the loop with the given header, returning where control flow continues after it.
+    fn while_loop(&mut self, header: BlockId) -> Option<(Option<BlockId>, BlockId)> {
+        self.line("while (true) {");
+        self.indent += 1;
+        self.loops.push(Loop { header, exit: None });
+        if let Some((pred, block_id)) = self.block(header) {
+            self.blocks(pred, block_id, None);
+        }
+        let current = self.loops.pop().expect("loop should be on the stack");
+        self.indent -= 1;
+        self.line("}");
+        // The `Phi` assignments of the exit block are emitted before breaking out of the loop.
+        current.exit.map(|exit| (None, exit))
+    }
+
+    /// Emits the instructions of a single block, returning the predecessor and the block
+    /// control flow continues at, if any.
+    fn block(&mut self, block_id: BlockId) -> Option<(Option<BlockId>, BlockId)> {
+        for instruction in &self.program.get_block(block_id).0 {
+            match instruction {
+                Instruction::Branch(cond, if_true, if_false) => {
+                    if let Some((exit, inside)) = self.loop_exit(*if_true, *if_false) {
+                        let cond = variable_to_qasm3(*cond);
+                        if exit == *if_true {
+                            self.line(format!("if ({cond}) {{"));
+                        } else {
+                            self.line(format!("if (!{cond}) {{"));
+                        }
+                        self.indent += 1;
+                        self.phi_assignments(block_id, exit);
+                        self.line("break;");
+                        self.indent -= 1;
+                        self.line("}");
+                        let current = self.loops.last_mut().expect("loop should be on the stack");
+                        current.exit.get_or_insert(exit);
+                        return Some((Some(block_id), inside));
+                    }
+                    let merge = self.merge_block(*if_true, *if_false);
+                    self.line(format!("if ({}) {{", variable_to_qasm3(*cond)));
+                    self.indent += 1;
+                    self.blocks(Some(block_id), *if_true, merge);
+                    self.indent -= 1;
+                    self.line("} else {");
+                    let else_start = self.lines.len();
+                    self.indent += 1;
+                    self.blocks(Some(block_id), *if_false, merge);
+                    self.indent -= 1;
+                    if self.lines.len() == else_start {
+                        self.lines.pop();
+                    }
+                    self.line("}");
+                    return merge.map(|merge| (None, merge));
+                }
+                Instruction::Jump(target) => return Some((Some(block_id), *target)),
+                Instruction::Return => return None,
+                instruction => self.instruction(instruction),
+            }
+        }
+        None
+    }
+
+    /// If exactly one target of a branch leaves the innermost loop being emitted, returns that
+    /// target followed by the one that stays in the loop.
+    fn loop_exit(&self, if_true: BlockId, if_false: BlockId) -> Option<(BlockId, BlockId)> {
+        let header = self.loops.last()?.header;
+        let in_loop = |block_id: BlockId| {
+            block_id == header || get_all_block_successors(block_id, self.program).contains(&header)
+        };
+        match (in_loop(if_true), in_loop(if_false)) {
+            (true, false) => Some((if_false, if_true)),
+            (false, true) => Some((if_true, if_false)),
+            _ => None,
+        }
+    }
+
+    /// Returns the block where control flow from both targets of a branch joins again, if any,
+    /// ignoring back edges so that branches in a loop body rejoin before the loop repeats.
+    fn merge_block(&self, if_true: BlockId, if_false: BlockId) -> Option<BlockId> {
+        let from_if_false = self.forward_reachable(if_false);
+        self.forward_reachable(if_true)
+            .into_iter()
+            .filter(|block_id| from_if_false.contains(block_id))
+            .max_by_key(|block_id| (self.forward_reachable(*block_id).len(), *block_id))
+    }
+
+    /// Returns the blocks reachable from the given block, including itself, without following back edges.
+    fn forward_reachable(&self, block_id: BlockId) -> FxHashSet<BlockId> {
+        let mut reachable = FxHashSet::default();
+        let mut to_visit = vec![block_id];
+        while let Some(block_id) = to_visit.pop() {
+            if reachable.insert(block_id) {
+                to_visit.extend(
+                    get_block_successors(self.program.get_block(block_id))
+                        .into_iter()
+                        .filter(|successor| *successor > block_id),
+                );
+            }
+        }
+        reachable
+    }
+
+    /// Emits the assignments for the `Phi` instructions of `block_id` that take their value from `pred`.
+    fn phi_assignments(&mut self, pred: BlockId, block_id: BlockId) {
+        for instruction in &self.program.get_block(block_id).0 {
+            if let Instruction::Phi(args, variable) = instruction {
+                let (value, _) = args
+                    .iter()
+                    .find(|(_, block_id)| *block_id == pred)
+                    .expect("phi should have an argument for each predecessor");
+                self.assign(*variable, operand_to_qasm3(value));
+            }
+        }
+    }
+
+    fn assign(&mut self, variable: Variable, value: impl AsRef<str>) {
+        self.variables.insert(variable.variable_id, variable.ty);
+        self.line(format!(
+            "{} = {};",
+            variable_to_qasm3(variable),
+            value.as_ref()
+        ));
+    }
+
+    fn binop(&mut self, op: &str, lhs: &Operand, rhs: &Operand, variable: Variable) {
+        self.assign(
+            variable,
+            format!("{} {op} {}", operand_to_qasm3(lhs), operand_to_qasm3(rhs)),
+        );
+    }
+
+    fn fcmp(&mut self, op: FcmpConditionCode, lhs: &Operand, rhs: &Operand, variable: Variable) {
+        let (lhs, rhs) = (operand_to_qasm3(lhs), operand_to_qasm3(rhs));
+        // OpenQASM has no unordered comparisons, so NaN checks are spelled out with self-equality.
+        let value = match op {
+            FcmpConditionCode::False => "false".to_string(),
+            FcmpConditionCode::True => "true".to_string(),
+            FcmpConditionCode::Ordered => format!("{lhs} == {lhs} && {rhs} == {rhs}"),
+            FcmpConditionCode::Unordered => format!("{lhs} != {lhs} || {rhs} != {rhs}"),
+            FcmpConditionCode::OrderedAndEqual | FcmpConditionCode::UnorderedOrEqual => {
+                format!("{lhs} == {rhs}")
+            }
+            FcmpConditionCode::OrderedAndNotEqual | FcmpConditionCode::UnorderedOrNotEqual => {
+                format!("{lhs} != {rhs}")
+            }
+            FcmpConditionCode::OrderedAndLessThan | FcmpConditionCode::UnorderedOrLessThan => {
+                format!("{lhs} < {rhs}")
+            }
+            FcmpConditionCode::OrderedAndLessThanOrEqual
+            | FcmpConditionCode::UnorderedOrLessThanOrEqual => format!("{lhs} <= {rhs}"),
+            FcmpConditionCode::OrderedAndGreaterThan
+            | FcmpConditionCode::UnorderedOrGreaterThan => format!("{lhs} > {rhs}"),
+            FcmpConditionCode::OrderedAndGreaterThanOrEqual
+            | FcmpConditionCode::UnorderedOrGreaterThanOrEqual => format!("{lhs} >= {rhs}"),
+        };
+        self.assign(variable, value);
+    }
+
+    fn instruction(&mut self, instruction: &Instruction) {
+        match instruction {
+            Instruction::Call(callable_id, args, variable) => {
+                self.call(*callable_id, args, *variable);
+            }
+            Instruction::Store(value, variable) => self.assign(*variable, operand_to_qasm3(value)),
+            Instruction::Add(lhs, rhs, variable) | Instruction::Fadd(lhs, rhs, variable) => {
+                self.binop("+", lhs, rhs, *variable);
+            }
+            Instruction::Sub(lhs, rhs, variable) | Instruction::Fsub(lhs, rhs, variable) => {
+                self.binop("-", lhs, rhs, *variable);
+            }
+            Instruction::Mul(lhs, rhs, variable) | Instruction::Fmul(lhs, rhs, variable) => {
+                self.binop("*", lhs, rhs, *variable);
+            }
+            Instruction::Sdiv(lhs, rhs, variable) | Instruction::Fdiv(lhs, rhs, variable) => {
+                self.binop("/", lhs, rhs, *variable);
+            }
+            Instruction::Srem(lhs, rhs, variable) => self.binop("%", lhs, rhs, *variable),
+            Instruction::Shl(lhs, rhs, variable) => self.binop("<<", lhs, rhs, *variable),
+            Instruction::Ashr(lhs, rhs, variable) => self.binop(">>", lhs, rhs, *variable),
+            Instruction::Icmp(op, lhs, rhs, variable) => {
+                self.binop(condition_code_to_qasm3(*op), lhs, rhs, *variable);
+            }
+            Instruction::Fcmp(op, lhs, rhs, variable) => self.fcmp(*op, lhs, rhs, *variable),
+            Instruction::LogicalNot(value, variable) => {
+                self.assign(*variable, format!("!{}", operand_to_qasm3(value)));
+            }
+            Instruction::LogicalAnd(lhs, rhs, variable) => self.binop("&&", lhs, rhs, *variable),
+            Instruction::LogicalOr(lhs, rhs, variable) => self.binop("||", lhs, rhs, *variable),
+            Instruction::BitwiseNot(value, variable) => {
+                self.assign(*variable, format!("~{}", operand_to_qasm3(value)));
+            }
+            Instruction::BitwiseAnd(lhs, rhs, variable) => self.binop("&", lhs, rhs, *variable),
+            Instruction::BitwiseOr(lhs, rhs, variable) => self.binop("|", lhs, rhs, *variable),
+            Instruction::BitwiseXor(lhs, rhs, variable) => self.binop("^", lhs, rhs, *variable),
+            // Phi values are assigned at the end of each predecessor.
+            Instruction::Phi(_, variable) => {
+                self.variables.insert(variable.variable_id, variable.ty);
+            }
+            Instruction::Jump(..) | Instruction::Branch(..) | Instruction::Return => {
+                unreachable!("terminators should be handled by the caller")
+            }
+        }
+    }
+
+    fn call(
+        &mut self,
+        callable_id: qsc_rir::rir::CallableId,
+        args: &[Operand],
+        variable: Option<Variable>,
+    ) {
+        let callable = self.program.get_callable(callable_id);
+        match callable.call_type {
+            CallableType::Measurement => {
+                let (Some(qubit), Some(result)) = (args.first(), args.get(1)) else {
+                    panic!("measurement should have a qubit and a result argument");
+                };
+                let (qubit, result) = (operand_to_qasm3(qubit), operand_to_qasm3(result));
+                self.line(format!("{result} = measure {qubit};"));
+                if callable.name == "__quantum__qis__mresetz__body" {
+                    self.line(format!("reset {qubit};"));
+                }
+            }
+            CallableType::Reset => {
+                for arg in args {
+                    self.line(format!("reset {};", operand_to_qasm3(arg)));
+                }
+            }
+            CallableType::Readout => {
+                let variable = variable.expect("readout should have an output variable");
+                let result = args.first().expect("readout should have a result argument");
+                self.assign(variable, format!("bool({})", operand_to_qasm3(result)));
+            }
+            CallableType::OutputRecording => {
+                // Tuple and array records only describe the structure of the output.
+                if matches!(
+                    callable.name.as_str(),
+                    "__quantum__rt__tuple_record_output" | "__quantum__rt__array_record_output"
+                ) {
+                    return;
+                }
+                let value = args.first().expect("output recording should have a value");
+                let ty = ty_to_qasm3(value.get_type());
+                let name = format!("output_{}", self.outputs.len());
+                self.line(format!("{name} = {};", operand_to_qasm3(value)));
+                self.outputs.push((name, ty));
+            }
+            CallableType::Regular => {
+                let (name, gate_defs) =
+                    gate(&callable.name).expect("intrinsic should have been checked");
+                self.define_gates(gate_defs);
+                let (qubits, classical): (Vec<_>, Vec<_>) =
+                    args.iter().partition(|arg| arg.get_type() == Ty::Qubit);
+                let qubits = qubits
+                    .into_iter()
+                    .map(operand_to_qasm3)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if classical.is_empty() {
+                    self.line(format!("{name} {qubits};"));
+                } else {
+                    let classical = classical
+                        .into_iter()
+                        .map(operand_to_qasm3)
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    self.line(format!("{name}({classical}) {qubits};"));
+                }
+            }
+        }
+    }
+
+    fn define_gates(&mut self, gate_defs: &[&'static str]) {
+        for gate_def in gate_defs {
+            if !self.gate_defs.contains(gate_def) {
+                self.gate_defs.push(gate_def);
+            }
+        }
+    }
+}
+
+/// Returns the OpenQASM gate for a quantum intrinsic, along with the definitions it needs
+/// for gates that are not part of `stdgates.inc`.
+fn gate(name: &str) -> Option<(&'static str, &'static [&'static str])> {
+    let gate = match name {
+        "__quantum__qis__ccx__body" => ("ccx", &[][..]),
+        "__quantum__qis__cx__body" => ("cx", &[][..]),
+        "__quantum__qis__cy__body" => ("cy", &[][..]),
+        "__quantum__qis__cz__body" => ("cz", &[][..]),
+        "__quantum__qis__h__body" => ("h", &[][..]),
+        "__quantum__qis__s__body" => ("s", &[][..]),
+        "__quantum__qis__s__adj" => ("sdg", &[][..]),
+        "__quantum__qis__t__body" => ("t", &[][..]),
+        "__quantum__qis__t__adj" => ("tdg", &[][..]),
+        "__quantum__qis__x__body" => ("x", &[][..]),
+        "__quantum__qis__y__body" => ("y", &[][..]),
+        "__quantum__qis__z__body" => ("z", &[][..]),
+        "__quantum__qis__swap__body" => ("swap", &[][..]),
+        "__quantum__qis__rx__body" => ("rx", &[][..]),
+        "__quantum__qis__ry__body" => ("ry", &[][..]),
+        "__quantum__qis__rz__body" => ("rz", &[][..]),
+        "__quantum__qis__rxx__body" => ("rxx", &[RZZ_DEF, RXX_DEF][..]),
+        "__quantum__qis__ryy__body" => ("ryy", &[RZZ_DEF, RYY_DEF][..]),
+        "__quantum__qis__rzz__body" => ("rzz", &[RZZ_DEF][..]),
+        _ => return None,
+    };
+    Some(gate)
+}
+
+const RZZ_DEF: &str = "gate rzz(theta) a, b { cx a, b; rz(theta) b; cx a, b; }\n";
+const RXX_DEF: &str = "gate rxx(theta) a, b { h a; h b; rzz(theta) a, b; h a; h b; }\n";
+const RYY_DEF: &str =
+    "gate ryy(theta) a, b { rx(-pi/2) a; rx(-pi/2) b; rzz(theta) a, b; rx(pi/2) a; rx(pi/2) b; }\n";
+
+fn ty_to_qasm3(ty: Ty) -> &'static str {
+    match ty {
+        Ty::Boolean => "bool",
+        Ty::Integer => "int[64]",
+        Ty::Double => "float[64]",
+        Ty::Result => "bit",
+        Ty::Qubit | Ty::Pointer => panic!("unsupported variable type {ty}"),
+    }
+}
+
+fn condition_code_to_qasm3(op: ConditionCode) -> &'static str {
+    match op {
+        ConditionCode::Eq => "==",
+        ConditionCode::Ne => "!=",
+        ConditionCode::Slt => "<",
+        ConditionCode::Sle => "<=",
+        ConditionCode::Sgt => ">",
+        ConditionCode::Sge => ">=",
+    }
+}
+
+fn variable_to_qasm3(variable: Variable) -> String {
+    format!("var_{}", variable.variable_id.0)
+}
+
+fn operand_to_qasm3(operand: &Operand) -> String {
+    match operand {
+        Operand::Literal(literal) => match literal {
+            Literal::Bool(b) => format!("{b}"),
+            Literal::Double(d) => {
+                if (d.floor() - d.ceil()).abs() < f64::EPSILON {
+                    // The value is a whole number, which requires at least one decimal point
+                    // to differentiate it from an integer value.
+                    format!("{d:.1}")
+                } else {
+                    format!("{d}")
+                }
+            }
+            Literal::Integer(i) => format!("{i}"),
+            Literal::Qubit(q) => format!("q[{q}]"),
+            Literal::Result(r) => format!("c[{r}]"),
+            Literal::Pointer => panic!("pointers are not supported in OpenQASM"),
+        },
+        Operand::Variable(variable) => variable_to_qasm3(*variable),
+    }
+}
@@ -0,0 +1,470 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::rir_to_qasm3;
+use expect_test::expect;
+use qsc_rir::{
+    builder,
+    rir::{
+        Block, BlockId, CallableId, ConditionCode, FcmpConditionCode, Instruction, Literal,
+        Operand, Ty, Variable, VariableId,
+    },
+};
+
+fn var(id: u32, ty: Ty) -> Variable {
+    Variable {
+        variable_id: VariableId(id),
+        ty,
+    }
+}
+
+#[test]
+fn bell_program() {
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        output bit output_0;
+        output bit output_1;
+        qubit[2] q;
+        bit[2] c;
+        h q[0];
+        cx q[0], q[1];
+        c[0] = measure q[0];
+        c[1] = measure q[1];
+        output_0 = c[0];
+        output_1 = c[1];
+    "#]]
+    .assert_eq(&rir_to_qasm3(&builder::bell_program()).expect("program should be supported"));
+}
+
+#[test]
+fn teleport_program() {
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        output bit output_0;
+        qubit[3] q;
+        bit[3] c;
+        bool var_0;
+        bool var_1;
+        x q[0];
+        h q[2];
+        cx q[2], q[1];
+        cx q[0], q[2];
+        h q[0];
+        c[0] = measure q[0];
+        reset q[0];
+        var_0 = bool(c[0]);
+        if (var_0) {
+            z q[1];
+        }
+        c[1] = measure q[2];
+        reset q[2];
+        var_1 = bool(c[1]);
+        if (var_1) {
+            x q[1];
+        }
+        c[2] = measure q[1];
+        reset q[1];
+        output_0 = c[2];
+    "#]]
+    .assert_eq(&rir_to_qasm3(&builder::teleport_program()).expect("program should be supported"));
+}
+
+#[allow(clippy::too_many_lines)]
+#[test]
+fn phi_becomes_assignment_in_each_branch() {
+    let mut program = builder::new_program();
+    program.callables.insert(CallableId(1), builder::mz_decl());
+    program
+        .callables
+        .insert(CallableId(2), builder::read_result_decl());
+    program
+        .callables
+        .insert(CallableId(3), builder::int_record_decl());
+    program.callables.insert(CallableId(4), builder::rx_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(
+                CallableId(4),
+                vec![
+                    Operand::Literal(Literal::Double(2.0)),
+                    Operand::Literal(Literal::Qubit(0)),
+                ],
+                None,
+            ),
+            Instruction::Call(
+                CallableId(1),
+                vec![
+                    Operand::Literal(Literal::Qubit(0)),
+                    Operand::Literal(Literal::Result(0)),
+                ],
+                None,
+            ),
+            Instruction::Call(
+                CallableId(2),
+                vec![Operand::Literal(Literal::Result(0))],
+                Some(var(0, Ty::Boolean)),
+            ),
+            Instruction::Icmp(
+                ConditionCode::Eq,
+                Operand::Variable(var(0, Ty::Boolean)),
+                Operand::Literal(Literal::Bool(false)),
+                var(1, Ty::Boolean),
+            ),
+            Instruction::Branch(var(1, Ty::Boolean), BlockId(1), BlockId(2)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Add(
+                Operand::Literal(Literal::Integer(3)),
+                Operand::Literal(Literal::Integer(4)),
+                var(2, Ty::Integer),
+            ),
+            Instruction::Jump(BlockId(3)),
+        ]),
+    );
+    program
+        .blocks
+        .insert(BlockId(2), Block(vec![Instruction::Jump(BlockId(3))]));
+    program.blocks.insert(
+        BlockId(3),
+        Block(vec![
+            Instruction::Phi(
+                vec![
+                    (Operand::Variable(var(2, Ty::Integer)), BlockId(1)),
+                    (Operand::Literal(Literal::Integer(0)), BlockId(2)),
+                ],
+                var(3, Ty::Integer),
+            ),
+            Instruction::Call(
+                CallableId(3),
+                vec![
+                    Operand::Variable(var(3, Ty::Integer)),
+                    Operand::Literal(Literal::Pointer),
+                ],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+    program.num_qubits = 1;
+    program.num_results = 1;
+
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        output int[64] output_0;
+        qubit[1] q;
+        bit[1] c;
+        bool var_0;
+        bool var_1;
+        int[64] var_2;
+        int[64] var_3;
+        rx(2.0) q[0];
+        c[0] = measure q[0];
+        var_0 = bool(c[0]);
+        var_1 = var_0 == false;
+        if (var_1) {
+            var_2 = 3 + 4;
+            var_3 = var_2;
+        } else {
+            var_3 = 0;
+        }
+        output_0 = var_3;
+    "#]]
+    .assert_eq(&rir_to_qasm3(&program).expect("program should be supported"));
+}
+
+#[test]
+fn float_instructions_become_expressions() {
+    let mut program = builder::new_program();
+    program.callables.insert(CallableId(1), builder::rx_decl());
+    program
+        .callables
+        .insert(CallableId(2), builder::bool_record_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Fmul(
+                Operand::Literal(Literal::Double(2.0)),
+                Operand::Literal(Literal::Double(0.25)),
+                var(0, Ty::Double),
+            ),
+            Instruction::Call(
+                CallableId(1),
+                vec![
+                    Operand::Variable(var(0, Ty::Double)),
+                    Operand::Literal(Literal::Qubit(0)),
+                ],
+                None,
+            ),
+            Instruction::Fcmp(
+                FcmpConditionCode::Unordered,
+                Operand::Variable(var(0, Ty::Double)),
+                Operand::Literal(Literal::Double(1.0)),
+                var(1, Ty::Boolean),
+            ),
+            Instruction::Call(
+                CallableId(2),
+                vec![
+                    Operand::Variable(var(1, Ty::Boolean)),
+                    Operand::Literal(Literal::Pointer),
+                ],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+    program.num_qubits = 1;
+
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        output bool output_0;
+        qubit[1] q;
+        float[64] var_0;
+        bool var_1;
+        var_0 = 2.0 * 0.25;
+        rx(var_0) q[0];
+        var_1 = var_0 != var_0 || 1.0 != 1.0;
+        output_0 = var_1;
+    "#]]
+    .assert_eq(&rir_to_qasm3(&program).expect("program should be supported"));
+}
+
+#[test]
+fn self_loop_becomes_while_loop() {
+    let mut program = builder::new_program();
+    program
+        .callables
+        .insert(CallableId(1), builder::mresetz_decl());
+    program
+        .callables
+        .insert(CallableId(2), builder::read_result_decl());
+    program
+        .blocks
+        .insert(BlockId(0), Block(vec![Instruction::Jump(BlockId(1))]));
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Call(
+                CallableId(1),
+                vec![
+                    Operand::Literal(Literal::Qubit(0)),
+                    Operand::Literal(Literal::Result(0)),
+                ],
+                None,
+            ),
+            Instruction::Call(
+                CallableId(2),
+                vec![Operand::Literal(Literal::Result(0))],
+                Some(var(0, Ty::Boolean)),
+            ),
+            Instruction::Branch(var(0, Ty::Boolean), BlockId(1), BlockId(2)),
+        ]),
+    );
+    program
+        .blocks
+        .insert(BlockId(2), Block(vec![Instruction::Return]));
+    program.num_qubits = 1;
+    program.num_results = 1;
+
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        qubit[1] q;
+        bit[1] c;
+        bool var_0;
+        while (true) {
+            c[0] = measure q[0];
+            reset q[0];
+            var_0 = bool(c[0]);
+            if (!var_0) {
+                break;
+            }
+        }
+    "#]]
+    .assert_eq(&rir_to_qasm3(&program).expect("program should be supported"));
+}
+
+#[allow(clippy::too_many_lines)]
+#[test]
+fn loop_phi_becomes_assignment_before_entry_and_at_end_of_body() {
+    let mut program = builder::new_program();
+    program.callables.insert(CallableId(1), builder::h_decl());
+    program
+        .callables
+        .insert(CallableId(2), builder::mresetz_decl());
+    program
+        .callables
+        .insert(CallableId(3), builder::read_result_decl());
+    program
+        .callables
+        .insert(CallableId(4), builder::int_record_decl());
+    program
+        .blocks
+        .insert(BlockId(0), Block(vec![Instruction::Jump(BlockId(1))]));
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Phi(
+                vec![
+                    (Operand::Literal(Literal::Bool(false)), BlockId(0)),
+                    (Operand::Variable(var(5, Ty::Boolean)), BlockId(2)),
+                ],
+                var(6, Ty::Boolean),
+            ),
+            Instruction::Phi(
+                vec![
+                    (Operand::Literal(Literal::Integer(0)), BlockId(0)),
+                    (Operand::Variable(var(3, Ty::Integer)), BlockId(2)),
+                ],
+                var(7, Ty::Integer),
+            ),
+            Instruction::LogicalNot(Operand::Variable(var(6, Ty::Boolean)), var(2, Ty::Boolean)),
+            Instruction::Branch(var(2, Ty::Boolean), BlockId(2), BlockId(3)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(2),
+        Block(vec![
+            Instruction::Call(
+                CallableId(1),
+                vec![Operand::Literal(Literal::Qubit(0))],
+                None,
+            ),
+            Instruction::Add(
+                Operand::Variable(var(7, Ty::Integer)),
+                Operand::Literal(Literal::Integer(1)),
+                var(3, Ty::Integer),
+            ),
+            Instruction::Call(
+                CallableId(2),
+                vec![
+                    Operand::Literal(Literal::Qubit(0)),
+                    Operand::Literal(Literal::Result(0)),
+                ],
+                None,
+            ),
+            Instruction::Call(
+                CallableId(3),
+                vec![Operand::Literal(Literal::Result(0))],
+                Some(var(4, Ty::Boolean)),
+            ),
+            Instruction::Icmp(
+                ConditionCode::Eq,
+                Operand::Variable(var(4, Ty::Boolean)),
+                Operand::Literal(Literal::Bool(true)),
+                var(5, Ty::Boolean),
+            ),
+            Instruction::Jump(BlockId(1)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(3),
+        Block(vec![
+            Instruction::Call(
+                CallableId(4),
+                vec![
+                    Operand::Variable(var(7, Ty::Integer)),
+                    Operand::Literal(Literal::Pointer),
+                ],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+    program.num_qubits = 1;
+    program.num_results = 1;
+
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        output int[64] output_0;
+        qubit[1] q;
+        bit[1] c;
+        bool var_2;
+        int[64] var_3;
+        bool var_4;
+        bool var_5;
+        bool var_6;
+        int[64] var_7;
+        var_6 = false;
+        var_7 = 0;
+        while (true) {
+            var_2 = !var_6;
+            if (!var_2) {
+                break;
+            }
+            h q[0];
+            var_3 = var_7 + 1;
+            c[0] = measure q[0];
+            reset q[0];
+            var_4 = bool(c[0]);
+            var_5 = var_4 == true;
+            var_6 = var_5;
+            var_7 = var_3;
+        }
+        output_0 = var_7;
+    "#]]
+    .assert_eq(&rir_to_qasm3(&program).expect("program should be supported"));
+}
+
+#[test]
+fn unsupported_intrinsic_is_an_error() {
+    let mut program = builder::new_program();
+    let mut custom_decl = builder::x_decl();
+    custom_decl.name = "__quantum__qis__custom__body".to_string();
+    program.callables.insert(CallableId(1), custom_decl);
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(
+                CallableId(1),
+                vec![Operand::Literal(Literal::Qubit(0))],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+    program.num_qubits = 1;
+
+    expect![[r#"
+        UnsupportedIntrinsic(
+            "__quantum__qis__custom__body",
+        )
+    "#]]
+    .assert_debug_eq(&rir_to_qasm3(&program).expect_err("program should not be supported"));
+}
+
+#[test]
+fn non_finite_double_literal_is_an_error() {
+    let mut program = builder::new_program();
+    program.callables.insert(CallableId(1), builder::rx_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(
+                CallableId(1),
+                vec![
+                    Operand::Literal(Literal::Double(f64::INFINITY)),
+                    Operand::Literal(Literal::Qubit(0)),
+                ],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+    program.num_qubits = 1;
+
+    expect![[r"
+        NonFiniteLiteral(
+            inf,
+        )
+    "]]
+    .assert_debug_eq(&rir_to_qasm3(&program).expect_err("program should not be supported"));
+}
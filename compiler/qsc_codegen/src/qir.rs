@@ -1,6 +1,8 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+mod bitcode;
+
 #[cfg(test)]
 mod instruction_tests;
 
@@ -16,6 +18,8 @@ use qsc_rir::{
     rir::{self, ConditionCode},
     utils::get_all_block_successors,
 };
+pub use qsc_rir::{passes::QubitReuseReport, rir::Program};
+use std::fmt::Write;
 
 fn lower_store(package_store: &qsc_frontend::compile::PackageStore) -> qsc_fir::fir::PackageStore {
     let mut fir_store = qsc_fir::fir::PackageStore::new();
@@ -30,22 +34,90 @@ fn lower_store(package_store: &qsc_frontend::compile::PackageStore) -> qsc_fir::
 pub fn hir_to_qir(
     package_store: &qsc_frontend::compile::PackageStore,
     capabilities: TargetCapabilityFlags,
+    optimize_gates: bool,
+    reuse_qubits: bool,
     compute_properties: Option<PackageStoreComputeProperties>,
     entry: &ProgramEntry,
 ) -> Result<String, qsc_partial_eval::Error> {
     let fir_store = lower_store(package_store);
-    fir_to_qir(&fir_store, capabilities, compute_properties, entry)
+    fir_to_qir(
+        &fir_store,
+        capabilities,
+        optimize_gates,
+        reuse_qubits,
+        compute_properties,
+        entry,
+    )
 }
 
 pub fn fir_to_qir(
     fir_store: &qsc_fir::fir::PackageStore,
     capabilities: TargetCapabilityFlags,
+    optimize_gates: bool,
+    reuse_qubits: bool,
     compute_properties: Option<PackageStoreComputeProperties>,
     entry: &ProgramEntry,
 ) -> Result<String, qsc_partial_eval::Error> {
+    let (program, _) = fir_to_rir(
+        fir_store,
+        capabilities,
+        optimize_gates,
+        reuse_qubits,
+        compute_properties,
+        entry,
+    )?;
+    Ok(rir_to_qir(&program))
+}
+
+/// Converts the given RIR program to QIR in the LLVM text format.
+#[must_use]
+pub fn rir_to_qir(program: &rir::Program) -> String {
+    ToQir::<String>::to_qir(program, program)
+}
+
+/// Converts the given entry to QIR in the LLVM bitcode format.
+pub fn fir_to_qir_bitcode(
+    fir_store: &qsc_fir::fir::PackageStore,
+    capabilities: TargetCapabilityFlags,
+    optimize_gates: bool,
+    reuse_qubits: bool,
+    compute_properties: Option<PackageStoreComputeProperties>,
+    entry: &ProgramEntry,
+) -> Result<Vec<u8>, qsc_partial_eval::Error> {
+    let (program, _) = fir_to_rir(
+        fir_store,
+        capabilities,
+        optimize_gates,
+        reuse_qubits,
+        compute_properties,
+        entry,
+    )?;
+    Ok(rir_to_qir_bitcode(&program))
+}
+
+/// Converts the given RIR program to QIR in the LLVM bitcode format.
+#[must_use]
+pub fn rir_to_qir_bitcode(program: &rir::Program) -> Vec<u8> {
+    bitcode::write_bitcode(program)
+}
+
+/// Partially evaluates the given entry into RIR and runs the default RIR passes on it.
+/// When `optimize_gates` is set, adjacent gates that cancel out or can be merged are also optimized away.
+/// When `reuse_qubits` is set, qubits with lifetimes that do not overlap are mapped onto the same qubit ID,
+/// and the report of the qubits saved is returned along with the program.
+pub fn fir_to_rir(
+    fir_store: &qsc_fir::fir::PackageStore,
+    capabilities: TargetCapabilityFlags,
+    optimize_gates: bool,
+    reuse_qubits: bool,
+    compute_properties: Option<PackageStoreComputeProperties>,
+    entry: &ProgramEntry,
+) -> Result<(rir::Program, Option<QubitReuseReport>), qsc_partial_eval::Error> {
     let mut program = get_rir_from_compilation(fir_store, compute_properties, entry, capabilities)?;
-    check_and_transform(&mut program);
-    Ok(ToQir::<String>::to_qir(&program, &program))
+    program.config.optimize_gates = optimize_gates;
+    program.config.reuse_qubits = reuse_qubits;
+    let reuse_report = check_and_transform(&mut program);
+    Ok((program, reuse_report))
 }
 
 fn get_rir_from_compilation(
@@ -149,6 +221,29 @@ impl ToQir<String> for rir::ConditionCode {
     }
 }
 
+impl ToQir<String> for rir::FcmpConditionCode {
+    fn to_qir(&self, _program: &rir::Program) -> String {
+        match self {
+            rir::FcmpConditionCode::False => "false".to_string(),
+            rir::FcmpConditionCode::OrderedAndEqual => "oeq".to_string(),
+            rir::FcmpConditionCode::OrderedAndGreaterThan => "ogt".to_string(),
+            rir::FcmpConditionCode::OrderedAndGreaterThanOrEqual => "oge".to_string(),
+            rir::FcmpConditionCode::OrderedAndLessThan => "olt".to_string(),
+            rir::FcmpConditionCode::OrderedAndLessThanOrEqual => "ole".to_string(),
+            rir::FcmpConditionCode::OrderedAndNotEqual => "one".to_string(),
+            rir::FcmpConditionCode::Ordered => "ord".to_string(),
+            rir::FcmpConditionCode::UnorderedOrEqual => "ueq".to_string(),
+            rir::FcmpConditionCode::UnorderedOrGreaterThan => "ugt".to_string(),
+            rir::FcmpConditionCode::UnorderedOrGreaterThanOrEqual => "uge".to_string(),
+            rir::FcmpConditionCode::UnorderedOrLessThan => "ult".to_string(),
+            rir::FcmpConditionCode::UnorderedOrLessThanOrEqual => "ule".to_string(),
+            rir::FcmpConditionCode::UnorderedOrNotEqual => "une".to_string(),
+            rir::FcmpConditionCode::Unordered => "uno".to_string(),
+            rir::FcmpConditionCode::True => "true".to_string(),
+        }
+    }
+}
+
 impl ToQir<String> for rir::Instruction {
     fn to_qir(&self, program: &rir::Program) -> String {
         match self {
@@ -196,6 +291,21 @@ impl ToQir<String> for rir::Instruction {
             rir::Instruction::Icmp(op, lhs, rhs, variable) => {
                 icmp_to_qir(*op, lhs, rhs, *variable, program)
             }
+            rir::Instruction::Fadd(lhs, rhs, variable) => {
+                fbinop_to_qir("fadd", lhs, rhs, *variable, program)
+            }
+            rir::Instruction::Fsub(lhs, rhs, variable) => {
+                fbinop_to_qir("fsub", lhs, rhs, *variable, program)
+            }
+            rir::Instruction::Fmul(lhs, rhs, variable) => {
+                fbinop_to_qir("fmul", lhs, rhs, *variable, program)
+            }
+            rir::Instruction::Fdiv(lhs, rhs, variable) => {
+                fbinop_to_qir("fdiv", lhs, rhs, *variable, program)
+            }
+            rir::Instruction::Fcmp(op, lhs, rhs, variable) => {
+                fcmp_to_qir(*op, lhs, rhs, *variable, program)
+            }
             rir::Instruction::Jump(block_id) => {
                 format!("  br label %{}", ToQir::<String>::to_qir(block_id, program))
             }
@@ -339,6 +449,60 @@ fn icmp_to_qir(
     )
 }
 
+fn fcmp_to_qir(
+    op: rir::FcmpConditionCode,
+    lhs: &rir::Operand,
+    rhs: &rir::Operand,
+    variable: rir::Variable,
+    program: &rir::Program,
+) -> String {
+    let lhs_ty = get_value_ty(lhs);
+    let rhs_ty = get_value_ty(rhs);
+    let var_ty = get_variable_ty(variable);
+    assert_eq!(
+        lhs_ty, rhs_ty,
+        "mismatched input types ({lhs_ty}, {rhs_ty}) for fcmp {op}"
+    );
+    assert_eq!(lhs_ty, "f64", "unsupported input type {lhs_ty} for fcmp");
+    assert_eq!(var_ty, "i1", "unsupported output type {var_ty} for fcmp");
+
+    format!(
+        "  {} = fcmp {} double {}, {}",
+        ToQir::<String>::to_qir(&variable.variable_id, program),
+        ToQir::<String>::to_qir(&op, program),
+        get_value_as_str(lhs, program),
+        get_value_as_str(rhs, program)
+    )
+}
+
+fn fbinop_to_qir(
+    op: &str,
+    lhs: &rir::Operand,
+    rhs: &rir::Operand,
+    variable: rir::Variable,
+    program: &rir::Program,
+) -> String {
+    let lhs_ty = get_value_ty(lhs);
+    let rhs_ty = get_value_ty(rhs);
+    let var_ty = get_variable_ty(variable);
+    assert_eq!(
+        lhs_ty, rhs_ty,
+        "mismatched input types ({lhs_ty}, {rhs_ty}) for {op}"
+    );
+    assert_eq!(
+        lhs_ty, var_ty,
+        "mismatched input/output types ({lhs_ty}, {var_ty}) for {op}"
+    );
+    assert_eq!(var_ty, "f64", "unsupported type {var_ty} for {op}");
+
+    format!(
+        "  {} = {op} double {}, {}",
+        ToQir::<String>::to_qir(&variable.variable_id, program),
+        get_value_as_str(lhs, program),
+        get_value_as_str(rhs, program)
+    )
+}
+
 fn binop_to_qir(
     op: &str,
     lhs: &rir::Operand,
@@ -423,8 +587,9 @@ fn phi_to_qir(
         .join(", ");
 
     format!(
-        "  {} = phi {var_ty} {args}",
-        ToQir::<String>::to_qir(&variable.variable_id, program)
+        "  {} = phi {} {args}",
+        ToQir::<String>::to_qir(&variable.variable_id, program),
+        ToQir::<String>::to_qir(&variable.ty, program)
     )
 }
 
@@ -553,23 +718,27 @@ impl ToQir<String> for rir::Program {
     }
 }
 
-/// Create the module metadata for the given program.
-/// creating the `llvm.module.flags` and its associated values.
-fn get_module_metadata(program: &rir::Program) -> String {
-    let mut flags = String::new();
+/// The value of a module flag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ModuleFlagValue {
+    I32(u32),
+    Bool(bool),
+}
+
+/// A module flag as its merge behavior, name and value, in the order they appear in
+/// `llvm.module.flags`.
+pub(crate) type ModuleFlag = (u32, &'static str, ModuleFlagValue);
 
+/// Gets the flags describing the QIR version and the capabilities of the given program.
+pub(crate) fn get_module_flags(program: &rir::Program) -> Vec<ModuleFlag> {
     // push the default attrs, we don't have any config values
     // for now that would change any of them.
-    flags.push_str(
-        r#"
-!0 = !{i32 1, !"qir_major_version", i32 1}
-!1 = !{i32 7, !"qir_minor_version", i32 0}
-!2 = !{i32 1, !"dynamic_qubit_management", i1 false}
-!3 = !{i32 1, !"dynamic_result_management", i1 false}
-"#,
-    );
-
-    let mut index = 4;
+    let mut flags = vec![
+        (1, "qir_major_version", ModuleFlagValue::I32(1)),
+        (7, "qir_minor_version", ModuleFlagValue::I32(0)),
+        (1, "dynamic_qubit_management", ModuleFlagValue::Bool(false)),
+        (1, "dynamic_result_management", ModuleFlagValue::Bool(false)),
+    ];
 
     // If we are not in the base profile, we need to add the capabilities
     // associated with the adaptive profile.
@@ -577,36 +746,18 @@ fn get_module_metadata(program: &rir::Program) -> String {
         // loop through the capabilities and add them to the metadata
         // for values that we can generate.
         for cap in program.config.capabilities.iter() {
-            let name = match cap {
-                TargetCapabilityFlags::QubitReset => "qubit_resetting",
-                TargetCapabilityFlags::IntegerComputations => "classical_ints",
-                TargetCapabilityFlags::FloatingPointComputations => "classical_floats",
-                TargetCapabilityFlags::BackwardsBranching => "backwards_branching",
-                _ => continue,
-            };
-            flags.push_str(&format!(
-                "!{} = !{{i32 {}, !\"{}\", i1 {}}}\n",
-                index, 1, name, true
-            ));
-            index += 1;
+            if let Some(name) = capability_flag_name(cap) {
+                flags.push((1, name, ModuleFlagValue::Bool(true)));
+            }
         }
 
         // loop through the capabilities that are missing and add them to the metadata
         // as not supported.
         let missing = TargetCapabilityFlags::all().difference(program.config.capabilities);
         for cap in missing.iter() {
-            let name = match cap {
-                TargetCapabilityFlags::QubitReset => "qubit_resetting",
-                TargetCapabilityFlags::IntegerComputations => "classical_ints",
-                TargetCapabilityFlags::FloatingPointComputations => "classical_floats",
-                TargetCapabilityFlags::BackwardsBranching => "backwards_branching",
-                _ => continue,
-            };
-            flags.push_str(&format!(
-                "!{} = !{{i32 {}, !\"{}\", i1 {}}}\n",
-                index, 1, name, false
-            ));
-            index += 1;
+            if let Some(name) = capability_flag_name(cap) {
+                flags.push((1, name, ModuleFlagValue::Bool(false)));
+            }
         }
 
         // Add the remaining extension capabilities as not supported.
@@ -617,19 +768,46 @@ fn get_module_metadata(program: &rir::Program) -> String {
             "multiple_target_branching",
         ];
         for capability in unmapped_capabilities {
-            flags.push_str(&format!(
-                "!{} = !{{i32 {}, !\"{}\", i1 {}}}\n",
-                index, 1, capability, false
-            ));
-            index += 1;
+            flags.push((1, capability, ModuleFlagValue::Bool(false)));
         }
     }
 
+    flags
+}
+
+fn capability_flag_name(cap: TargetCapabilityFlags) -> Option<&'static str> {
+    match cap {
+        TargetCapabilityFlags::QubitReset => Some("qubit_resetting"),
+        TargetCapabilityFlags::IntegerComputations => Some("classical_ints"),
+        TargetCapabilityFlags::FloatingPointComputations => Some("classical_floats"),
+        TargetCapabilityFlags::BackwardsBranching => Some("backwards_branching"),
+        _ => None,
+    }
+}
+
+/// Create the module metadata for the given program.
+/// creating the `llvm.module.flags` and its associated values.
+fn get_module_metadata(program: &rir::Program) -> String {
+    let flags = get_module_flags(program);
     let mut metadata_def = String::new();
     metadata_def.push_str("!llvm.module.flags = !{");
-    for i in 0..index - 1 {
-        metadata_def.push_str(&format!("!{i}, "));
+    metadata_def.push_str(
+        &(0..flags.len())
+            .map(|i| format!("!{i}"))
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+    metadata_def.push_str("}\n\n");
+    for (index, (behavior, name, value)) in flags.iter().enumerate() {
+        let value = match value {
+            ModuleFlagValue::I32(value) => format!("i32 {value}"),
+            ModuleFlagValue::Bool(value) => format!("i1 {value}"),
+        };
+        writeln!(
+            metadata_def,
+            "!{index} = !{{i32 {behavior}, !\"{name}\", {value}}}"
+        )
+        .expect("writing to string should succeed");
     }
-    metadata_def.push_str(&format!("!{}}}\n", index - 1));
-    metadata_def + &flags
+    metadata_def
 }
@@ -0,0 +1,944 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Writes a RIR program as an LLVM bitcode module equivalent to the textual QIR produced by
+//! [`super::ToQir`], so that bitcode can be emitted without an LLVM installation.
+//!
+//! Only the parts of the format needed for QIR are supported. Records are written unabbreviated,
+//! except for the string table blob, and all constants are placed in the module-level constant
+//! block so that both function bodies and module flags can refer to them.
+
+#[cfg(test)]
+mod tests;
+
+use super::{get_module_flags, ModuleFlagValue};
+use qsc_rir::{
+    rir::{self, ConditionCode, FcmpConditionCode},
+    utils::get_all_block_successors,
+};
+use rustc_hash::FxHashMap;
+use std::collections::{btree_map::Entry, BTreeMap};
+
+/// The version of the module block, which uses relative value ids and a string table.
+const MODULE_VERSION: u64 = 2;
+
+/// The abbreviation width used by every block.
+const ABBREV_WIDTH: u32 = 3;
+
+mod abbrev {
+    pub(super) const END_BLOCK: u64 = 0;
+    pub(super) const ENTER_SUBBLOCK: u64 = 1;
+    pub(super) const DEFINE_ABBREV: u64 = 2;
+    pub(super) const UNABBREV_RECORD: u64 = 3;
+    /// The first abbreviation defined in a block.
+    pub(super) const FIRST: u64 = 4;
+}
+
+mod block {
+    pub(super) const MODULE: u64 = 8;
+    pub(super) const PARAMATTR: u64 = 9;
+    pub(super) const PARAMATTR_GROUP: u64 = 10;
+    pub(super) const CONSTANTS: u64 = 11;
+    pub(super) const FUNCTION: u64 = 12;
+    pub(super) const VALUE_SYMTAB: u64 = 14;
+    pub(super) const METADATA: u64 = 15;
+    pub(super) const TYPE: u64 = 17;
+    pub(super) const STRTAB: u64 = 23;
+}
+
+mod code {
+    pub(super) const MODULE_VERSION: u64 = 1;
+    pub(super) const MODULE_FUNCTION: u64 = 8;
+
+    pub(super) const PARAMATTR_ENTRY: u64 = 2;
+    pub(super) const PARAMATTR_GRP_ENTRY: u64 = 3;
+
+    pub(super) const TYPE_NUMENTRY: u64 = 1;
+    pub(super) const TYPE_VOID: u64 = 2;
+    pub(super) const TYPE_DOUBLE: u64 = 4;
+    pub(super) const TYPE_OPAQUE: u64 = 6;
+    pub(super) const TYPE_INTEGER: u64 = 7;
+    pub(super) const TYPE_POINTER: u64 = 8;
+    pub(super) const TYPE_STRUCT_NAME: u64 = 19;
+    pub(super) const TYPE_FUNCTION: u64 = 21;
+
+    pub(super) const CST_SETTYPE: u64 = 1;
+    pub(super) const CST_NULL: u64 = 2;
+    pub(super) const CST_INTEGER: u64 = 4;
+    pub(super) const CST_FLOAT: u64 = 6;
+    pub(super) const CST_CE_CAST: u64 = 11;
+
+    pub(super) const METADATA_STRING: u64 = 1;
+    pub(super) const METADATA_VALUE: u64 = 2;
+    pub(super) const METADATA_NODE: u64 = 3;
+    pub(super) const METADATA_NAME: u64 = 4;
+    pub(super) const METADATA_NAMED_NODE: u64 = 10;
+
+    pub(super) const INST_DECLAREBLOCKS: u64 = 1;
+    pub(super) const INST_BINOP: u64 = 2;
+    pub(super) const INST_RET: u64 = 10;
+    pub(super) const INST_BR: u64 = 11;
+    pub(super) const INST_PHI: u64 = 16;
+    pub(super) const INST_CMP2: u64 = 28;
+    pub(super) const INST_CALL: u64 = 34;
+
+    pub(super) const VST_ENTRY: u64 = 1;
+    pub(super) const VST_BBENTRY: u64 = 2;
+
+    pub(super) const STRTAB_BLOB: u64 = 1;
+}
+
+mod binop {
+    pub(super) const ADD: u64 = 0;
+    pub(super) const SUB: u64 = 1;
+    pub(super) const MUL: u64 = 2;
+    /// Also used for `fdiv` when the operands are floating-point.
+    pub(super) const SDIV: u64 = 4;
+    pub(super) const SREM: u64 = 6;
+    pub(super) const SHL: u64 = 7;
+    pub(super) const ASHR: u64 = 9;
+    pub(super) const AND: u64 = 10;
+    pub(super) const OR: u64 = 11;
+    pub(super) const XOR: u64 = 12;
+}
+
+/// The cast opcode for `inttoptr`.
+const CAST_INTTOPTR: u64 = 10;
+
+/// The attribute index that applies an attribute group to the function itself.
+const FUNCTION_ATTR_INDEX: u64 = 0xFFFF_FFFF;
+
+/// The `CALL_EXPLICIT_TYPE` flag in the calling convention operand of a call.
+const CALL_EXPLICIT_TYPE: u64 = 1 << 15;
+
+/// Writes the given program as an LLVM bitcode module.
+#[must_use]
+pub(super) fn write_bitcode(program: &rir::Program) -> Vec<u8> {
+    let mut writer = ModuleWriter::new(program);
+    writer.write()
+}
+
+/// Writes bits into a byte buffer in the LLVM bitstream format, least significant bit first.
+#[derive(Default)]
+pub(crate) struct BitWriter {
+    bytes: Vec<u8>,
+    pending: u64,
+    pending_bits: u32,
+    abbrev_width: u32,
+    /// The abbreviation width of each enclosing block and the position of its length field.
+    blocks: Vec<(u32, usize)>,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            abbrev_width: 2,
+            ..Self::default()
+        }
+    }
+
+    fn emit(&mut self, value: u64, width: u32) {
+        debug_assert!(width <= 32 && value < 1 << width);
+        self.pending |= value << self.pending_bits;
+        self.pending_bits += width;
+        while self.pending_bits >= 8 {
+            self.bytes.push(self.pending.to_le_bytes()[0]);
+            self.pending >>= 8;
+            self.pending_bits -= 8;
+        }
+    }
+
+    fn emit_vbr(&mut self, mut value: u64, width: u32) {
+        let threshold = 1 << (width - 1);
+        while value >= threshold {
+            self.emit((value & (threshold - 1)) | threshold, width);
+            value >>= width - 1;
+        }
+        self.emit(value, width);
+    }
+
+    fn align32(&mut self) {
+        if self.pending_bits > 0 {
+            self.emit(0, 8 - self.pending_bits);
+        }
+        while !self.bytes.len().is_multiple_of(4) {
+            self.bytes.push(0);
+        }
+    }
+
+    fn enter_block(&mut self, id: u64) {
+        self.emit(abbrev::ENTER_SUBBLOCK, self.abbrev_width);
+        self.emit_vbr(id, 8);
+        self.emit_vbr(ABBREV_WIDTH.into(), 4);
+        self.align32();
+        self.blocks.push((self.abbrev_width, self.bytes.len()));
+        // The length of the block in words is filled in when the block ends.
+        self.bytes.extend([0; 4]);
+        self.abbrev_width = ABBREV_WIDTH;
+    }
+
+    fn exit_block(&mut self) {
+        self.emit(abbrev::END_BLOCK, self.abbrev_width);
+        self.align32();
+        let (abbrev_width, start) = self.blocks.pop().expect("a block should be open");
+        let words = u32::try_from((self.bytes.len() - start - 4) / 4)
+            .expect("block length should fit in 32 bits");
+        self.bytes[start..start + 4].copy_from_slice(&words.to_le_bytes());
+        self.abbrev_width = abbrev_width;
+    }
+
+    fn record(&mut self, code: u64, ops: &[u64]) {
+        self.emit(abbrev::UNABBREV_RECORD, self.abbrev_width);
+        self.emit_vbr(code, 6);
+        self.emit_vbr(ops.len() as u64, 6);
+        for &op in ops {
+            self.emit_vbr(op, 6);
+        }
+    }
+
+    /// Writes a record consisting of the given code and a blob, defining the abbreviation it needs.
+    /// This must be the only abbreviated record in the current block.
+    fn blob_record(&mut self, code: u64, blob: &[u8]) {
+        self.emit(abbrev::DEFINE_ABBREV, self.abbrev_width);
+        self.emit_vbr(2, 5);
+        // A literal operand holding the record code.
+        self.emit(1, 1);
+        self.emit_vbr(code, 8);
+        // A blob operand.
+        self.emit(0, 1);
+        self.emit(5, 3);
+
+        self.emit(abbrev::FIRST, self.abbrev_width);
+        self.emit_vbr(blob.len() as u64, 6);
+        self.align32();
+        self.bytes.extend_from_slice(blob);
+        self.align32();
+    }
+
+    fn finish(self) -> Vec<u8> {
+        assert!(self.blocks.is_empty(), "all blocks should be closed");
+        assert_eq!(self.pending_bits, 0, "the stream should be word aligned");
+        self.bytes
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Type {
+    Void,
+    Integer(u32),
+    Double,
+    Opaque(&'static str),
+    Pointer(u32),
+    Function(u32, Vec<u32>),
+}
+
+/// A constant, keyed so that constants of the same type are grouped together and integers are
+/// numbered before the casts that use them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Constant {
+    Integer(u32, i64),
+    Double(u64),
+    Null(u32),
+    IntToPtr(u32, i64),
+}
+
+struct ModuleWriter<'a> {
+    program: &'a rir::Program,
+    stream: BitWriter,
+    types: Vec<Type>,
+    type_ids: FxHashMap<Type, u32>,
+    /// The value id of each callable, in the order the functions are declared.
+    functions: FxHashMap<rir::CallableId, u32>,
+    constants: BTreeMap<Constant, u32>,
+    strtab: Vec<u8>,
+}
+
+impl<'a> ModuleWriter<'a> {
+    fn new(program: &'a rir::Program) -> Self {
+        Self {
+            program,
+            stream: BitWriter::new(),
+            types: Vec::new(),
+            type_ids: FxHashMap::default(),
+            functions: program
+                .callables
+                .iter()
+                .zip(0..)
+                .map(|((id, _), value_id)| (id, value_id))
+                .collect(),
+            constants: BTreeMap::new(),
+            strtab: Vec::new(),
+        }
+    }
+
+    fn write(&mut self) -> Vec<u8> {
+        for byte in *b"BC\xC0\xDE" {
+            self.stream.emit(byte.into(), 8);
+        }
+
+        self.collect_types();
+        self.collect_constants();
+
+        self.stream.enter_block(block::MODULE);
+        self.stream.record(code::MODULE_VERSION, &[MODULE_VERSION]);
+        self.write_attributes();
+        self.write_types();
+        self.write_functions();
+        self.write_constants();
+        self.write_metadata();
+        for (_, callable) in self.program.callables.iter() {
+            if let Some(entry) = callable.body {
+                self.write_function_body(entry);
+            }
+        }
+        self.stream.exit_block();
+
+        self.stream.enter_block(block::STRTAB);
+        let strtab = std::mem::take(&mut self.strtab);
+        self.stream.blob_record(code::STRTAB_BLOB, &strtab);
+        self.stream.exit_block();
+
+        std::mem::take(&mut self.stream).finish()
+    }
+
+    fn type_id(&mut self, ty: Type) -> u32 {
+        if let Some(&id) = self.type_ids.get(&ty) {
+            return id;
+        }
+        let id = u32::try_from(self.types.len()).expect("type count should fit in 32 bits");
+        self.types.push(ty.clone());
+        self.type_ids.insert(ty, id);
+        id
+    }
+
+    fn rir_type_id(&mut self, ty: rir::Ty) -> u32 {
+        let ty = match ty {
+            rir::Ty::Boolean => Type::Integer(1),
+            rir::Ty::Integer => Type::Integer(64),
+            rir::Ty::Double => Type::Double,
+            rir::Ty::Pointer => {
+                let i8 = self.type_id(Type::Integer(8));
+                Type::Pointer(i8)
+            }
+            rir::Ty::Qubit => {
+                let qubit = self.type_id(Type::Opaque("Qubit"));
+                Type::Pointer(qubit)
+            }
+            rir::Ty::Result => {
+                let result = self.type_id(Type::Opaque("Result"));
+                Type::Pointer(result)
+            }
+        };
+        self.type_id(ty)
+    }
+
+    fn output_type_id(&mut self, ty: Option<rir::Ty>) -> u32 {
+        match ty {
+            Some(ty) => self.rir_type_id(ty),
+            None => self.type_id(Type::Void),
+        }
+    }
+
+    fn function_type_id(&mut self, callable: &rir::Callable) -> u32 {
+        let output = self.output_type_id(callable.output_type);
+        let inputs = callable
+            .input_type
+            .iter()
+            .map(|&ty| self.rir_type_id(ty))
+            .collect();
+        self.type_id(Type::Function(output, inputs))
+    }
+
+    fn collect_types(&mut self) {
+        self.type_id(Type::Opaque("Result"));
+        self.type_id(Type::Opaque("Qubit"));
+        for (_, callable) in self.program.callables.iter() {
+            let ty = self.function_type_id(callable);
+            self.type_id(Type::Pointer(ty));
+        }
+        self.type_id(Type::Integer(32));
+        self.type_id(Type::Integer(1));
+    }
+
+    fn constant(&mut self, literal: rir::Literal) -> Constant {
+        match literal {
+            rir::Literal::Bool(b) => Constant::Integer(self.type_id(Type::Integer(1)), b.into()),
+            rir::Literal::Integer(i) => Constant::Integer(self.type_id(Type::Integer(64)), i),
+            rir::Literal::Double(d) => Constant::Double(d.to_bits()),
+            rir::Literal::Pointer => Constant::Null(self.rir_type_id(rir::Ty::Pointer)),
+            rir::Literal::Qubit(q) => {
+                Constant::IntToPtr(self.rir_type_id(rir::Ty::Qubit), q.into())
+            }
+            rir::Literal::Result(r) => {
+                Constant::IntToPtr(self.rir_type_id(rir::Ty::Result), r.into())
+            }
+        }
+    }
+
+    fn add_constant(&mut self, constant: Constant) {
+        if let Constant::IntToPtr(_, value) = constant {
+            let i64 = self.type_id(Type::Integer(64));
+            self.constants.insert(Constant::Integer(i64, value), 0);
+        }
+        self.constants.insert(constant, 0);
+    }
+
+    /// Gets the constants for the behavior and value of each module flag.
+    fn flag_constants(&mut self) -> Vec<(Constant, Constant)> {
+        let i32 = self.type_id(Type::Integer(32));
+        let i1 = self.type_id(Type::Integer(1));
+        get_module_flags(self.program)
+            .into_iter()
+            .map(|(behavior, _, value)| {
+                let value = match value {
+                    ModuleFlagValue::I32(value) => Constant::Integer(i32, value.into()),
+                    ModuleFlagValue::Bool(value) => Constant::Integer(i1, value.into()),
+                };
+                (Constant::Integer(i32, behavior.into()), value)
+            })
+            .collect()
+    }
+
+    fn collect_constants(&mut self) {
+        for (_, block) in self.program.blocks.iter() {
+            for instruction in &block.0 {
+                for literal in instruction_literals(instruction) {
+                    let constant = self.constant(literal);
+                    self.add_constant(constant);
+                }
+            }
+        }
+        for (behavior, value) in self.flag_constants() {
+            self.add_constant(behavior);
+            self.add_constant(value);
+        }
+
+        // Constants are numbered after the functions, in the order they are written.
+        let first = u32::try_from(self.functions.len()).expect("value count should fit in 32 bits");
+        for (id, value_id) in (first..).zip(self.constants.values_mut()) {
+            *value_id = id;
+        }
+    }
+
+    fn constant_id(&self, constant: Constant) -> u32 {
+        *self
+            .constants
+            .get(&constant)
+            .expect("constant should have been collected")
+    }
+
+    fn write_attributes(&mut self) {
+        let profile = if self.program.config.is_base() {
+            "base_profile"
+        } else {
+            "adaptive_profile"
+        };
+        let mut entry_point = vec![1, FUNCTION_ATTR_INDEX];
+        push_string_attr(&mut entry_point, "entry_point", None);
+        push_string_attr(&mut entry_point, "output_labeling_schema", None);
+        push_string_attr(&mut entry_point, "qir_profiles", Some(profile));
+        push_string_attr(
+            &mut entry_point,
+            "required_num_qubits",
+            Some(&self.program.num_qubits.to_string()),
+        );
+        push_string_attr(
+            &mut entry_point,
+            "required_num_results",
+            Some(&self.program.num_results.to_string()),
+        );
+        let mut irreversible = vec![2, FUNCTION_ATTR_INDEX];
+        push_string_attr(&mut irreversible, "irreversible", None);
+
+        self.stream.enter_block(block::PARAMATTR_GROUP);
+        self.stream.record(code::PARAMATTR_GRP_ENTRY, &entry_point);
+        self.stream.record(code::PARAMATTR_GRP_ENTRY, &irreversible);
+        self.stream.exit_block();
+
+        // Attribute lists are referred to by one-based index, in the order they are written.
+        self.stream.enter_block(block::PARAMATTR);
+        self.stream.record(code::PARAMATTR_ENTRY, &[1]);
+        self.stream.record(code::PARAMATTR_ENTRY, &[2]);
+        self.stream.exit_block();
+    }
+
+    fn write_types(&mut self) {
+        self.stream.enter_block(block::TYPE);
+        self.stream
+            .record(code::TYPE_NUMENTRY, &[self.types.len() as u64]);
+        for ty in &self.types {
+            match ty {
+                Type::Void => self.stream.record(code::TYPE_VOID, &[]),
+                Type::Integer(width) => {
+                    self.stream.record(code::TYPE_INTEGER, &[u64::from(*width)]);
+                }
+                Type::Double => self.stream.record(code::TYPE_DOUBLE, &[]),
+                Type::Opaque(name) => {
+                    self.stream.record(code::TYPE_STRUCT_NAME, &chars(name));
+                    self.stream.record(code::TYPE_OPAQUE, &[0]);
+                }
+                Type::Pointer(pointee) => self
+                    .stream
+                    .record(code::TYPE_POINTER, &[u64::from(*pointee), 0]),
+                Type::Function(output, inputs) => {
+                    let mut ops = vec![0, u64::from(*output)];
+                    ops.extend(inputs.iter().map(|&input| u64::from(input)));
+                    self.stream.record(code::TYPE_FUNCTION, &ops);
+                }
+            }
+        }
+        self.stream.exit_block();
+    }
+
+    fn write_functions(&mut self) {
+        for (_, callable) in self.program.callables.iter() {
+            let name = if callable.body.is_some() {
+                "ENTRYPOINT__main"
+            } else {
+                callable.name.as_str()
+            };
+            let offset = self.strtab.len() as u64;
+            self.strtab.extend_from_slice(name.as_bytes());
+            let ty = self.function_type_id(callable);
+            let is_declaration = callable.body.is_none();
+            let attrs = if !is_declaration {
+                1
+            } else if callable.call_type == rir::CallableType::Measurement {
+                2
+            } else {
+                0
+            };
+            // The name, type, calling convention, whether it is a declaration, linkage and
+            // attributes, followed by the defaults for the remaining properties.
+            let mut ops = vec![
+                offset,
+                name.len() as u64,
+                u64::from(ty),
+                0,
+                is_declaration.into(),
+                0,
+                attrs,
+            ];
+            ops.extend([0; 9]);
+            self.stream.record(code::MODULE_FUNCTION, &ops);
+        }
+    }
+
+    fn write_constants(&mut self) {
+        let i64 = self.type_id(Type::Integer(64));
+        let double = self.type_id(Type::Double);
+        self.stream.enter_block(block::CONSTANTS);
+        let mut current_ty = None;
+        for &constant in self.constants.keys() {
+            let ty = match constant {
+                Constant::Integer(ty, _) | Constant::Null(ty) | Constant::IntToPtr(ty, _) => ty,
+                Constant::Double(_) => double,
+            };
+            if current_ty != Some(ty) {
+                self.stream.record(code::CST_SETTYPE, &[u64::from(ty)]);
+                current_ty = Some(ty);
+            }
+            match constant {
+                Constant::Integer(_, value) => self
+                    .stream
+                    .record(code::CST_INTEGER, &[encode_signed(value)]),
+                Constant::Double(bits) => self.stream.record(code::CST_FLOAT, &[bits]),
+                Constant::Null(_) => self.stream.record(code::CST_NULL, &[]),
+                Constant::IntToPtr(_, value) => {
+                    let int = self.constants[&Constant::Integer(i64, value)];
+                    self.stream.record(
+                        code::CST_CE_CAST,
+                        &[CAST_INTTOPTR, u64::from(i64), u64::from(int)],
+                    );
+                }
+            }
+        }
+        self.stream.exit_block();
+    }
+
+    fn write_metadata(&mut self) {
+        let flags = get_module_flags(self.program);
+        let flag_constants = self.flag_constants();
+        self.stream.enter_block(block::METADATA);
+
+        // Metadata is numbered in the order it is written: first the flag names, then the
+        // constants they use, then a node for each flag.
+        let mut next_id = 0u64;
+        let mut name_ids = Vec::new();
+        for (_, name, _) in &flags {
+            self.stream.record(code::METADATA_STRING, &chars(name));
+            name_ids.push(next_id);
+            next_id += 1;
+        }
+        let mut value_ids = BTreeMap::new();
+        for &(behavior, value) in &flag_constants {
+            for constant in [behavior, value] {
+                if let Entry::Vacant(entry) = value_ids.entry(constant) {
+                    let Constant::Integer(ty, _) = constant else {
+                        unreachable!("module flags should only use integer constants");
+                    };
+                    let id = self.constant_id(constant);
+                    self.stream
+                        .record(code::METADATA_VALUE, &[u64::from(ty), u64::from(id)]);
+                    entry.insert(next_id);
+                    next_id += 1;
+                }
+            }
+        }
+        let mut node_ids = Vec::new();
+        for (&name_id, &(behavior, value)) in name_ids.iter().zip(&flag_constants) {
+            // Node operands are offset by one so that zero can represent a null operand.
+            self.stream.record(
+                code::METADATA_NODE,
+                &[value_ids[&behavior] + 1, name_id + 1, value_ids[&value] + 1],
+            );
+            node_ids.push(next_id);
+            next_id += 1;
+        }
+        self.stream
+            .record(code::METADATA_NAME, &chars("llvm.module.flags"));
+        self.stream.record(code::METADATA_NAMED_NODE, &node_ids);
+        self.stream.exit_block();
+    }
+
+    fn write_function_body(&mut self, entry: rir::BlockId) {
+        let mut block_ids = vec![entry];
+        block_ids.extend(get_all_block_successors(entry, self.program));
+        let block_indices: FxHashMap<_, _> = block_ids
+            .iter()
+            .enumerate()
+            .map(|(index, &id)| (id, index as u64))
+            .collect();
+
+        // Number the instruction results up front, since phis can refer to values defined later.
+        let first = u32::try_from(self.functions.len() + self.constants.len())
+            .expect("value count should fit in 32 bits");
+        let mut variables = FxHashMap::default();
+        for &block_id in &block_ids {
+            for instruction in &self.program.get_block(block_id).0 {
+                if let Some(variable) = instruction_output(instruction) {
+                    let id = first
+                        + u32::try_from(variables.len())
+                            .expect("value count should fit in 32 bits");
+                    variables.insert(variable.variable_id, id);
+                }
+            }
+        }
+
+        self.stream.enter_block(block::FUNCTION);
+        self.stream
+            .record(code::INST_DECLAREBLOCKS, &[block_ids.len() as u64]);
+        let program = self.program;
+        let mut function = FunctionWriter {
+            module: self,
+            variables: &variables,
+            block_indices: &block_indices,
+            next_id: first,
+        };
+        for &block_id in &block_ids {
+            for instruction in &program.get_block(block_id).0 {
+                function.write_instruction(instruction);
+            }
+        }
+
+        // Name the values and blocks the same way as the textual QIR.
+        self.stream.enter_block(block::VALUE_SYMTAB);
+        let mut names = variables
+            .iter()
+            .map(|(variable_id, &id)| (id, variable_id.0))
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        for (id, variable_id) in names {
+            let mut ops = vec![u64::from(id)];
+            ops.extend(chars(&format!("var_{variable_id}")));
+            self.stream.record(code::VST_ENTRY, &ops);
+        }
+        for (index, block_id) in block_ids.iter().enumerate() {
+            let mut ops = vec![index as u64];
+            ops.extend(chars(&format!("block_{}", block_id.0)));
+            self.stream.record(code::VST_BBENTRY, &ops);
+        }
+        self.stream.exit_block();
+        self.stream.exit_block();
+    }
+}
+
+struct FunctionWriter<'a, 'b> {
+    module: &'a mut ModuleWriter<'b>,
+    variables: &'a FxHashMap<rir::VariableId, u32>,
+    block_indices: &'a FxHashMap<rir::BlockId, u64>,
+    /// The value id of the next instruction result.
+    next_id: u32,
+}
+
+impl FunctionWriter<'_, '_> {
+    fn write_instruction(&mut self, instruction: &rir::Instruction) {
+        // Floating-point arithmetic shares opcodes with the integer forms, selected by operand type.
+        match instruction {
+            rir::Instruction::Add(lhs, rhs, _) | rir::Instruction::Fadd(lhs, rhs, _) => {
+                self.binop(binop::ADD, lhs, rhs);
+            }
+            rir::Instruction::Sub(lhs, rhs, _) | rir::Instruction::Fsub(lhs, rhs, _) => {
+                self.binop(binop::SUB, lhs, rhs);
+            }
+            rir::Instruction::Mul(lhs, rhs, _) | rir::Instruction::Fmul(lhs, rhs, _) => {
+                self.binop(binop::MUL, lhs, rhs);
+            }
+            rir::Instruction::Sdiv(lhs, rhs, _) | rir::Instruction::Fdiv(lhs, rhs, _) => {
+                self.binop(binop::SDIV, lhs, rhs);
+            }
+            rir::Instruction::Srem(lhs, rhs, _) => self.binop(binop::SREM, lhs, rhs),
+            rir::Instruction::Shl(lhs, rhs, _) => self.binop(binop::SHL, lhs, rhs),
+            rir::Instruction::Ashr(lhs, rhs, _) => self.binop(binop::ASHR, lhs, rhs),
+            rir::Instruction::BitwiseAnd(lhs, rhs, _)
+            | rir::Instruction::LogicalAnd(lhs, rhs, _) => {
+                self.binop(binop::AND, lhs, rhs);
+            }
+            rir::Instruction::BitwiseOr(lhs, rhs, _) | rir::Instruction::LogicalOr(lhs, rhs, _) => {
+                self.binop(binop::OR, lhs, rhs);
+            }
+            rir::Instruction::BitwiseXor(lhs, rhs, _) => self.binop(binop::XOR, lhs, rhs),
+            rir::Instruction::BitwiseNot(value, _) => {
+                self.binop(
+                    binop::XOR,
+                    value,
+                    &rir::Operand::Literal(rir::Literal::Integer(-1)),
+                );
+            }
+            rir::Instruction::LogicalNot(value, _) => {
+                self.binop(
+                    binop::XOR,
+                    value,
+                    &rir::Operand::Literal(rir::Literal::Bool(true)),
+                );
+            }
+            rir::Instruction::Icmp(op, lhs, rhs, _) => {
+                let mut ops = Vec::new();
+                self.push_value_and_type(&mut ops, lhs);
+                self.push_value(&mut ops, rhs);
+                ops.push(icmp_predicate(*op));
+                self.record(code::INST_CMP2, &ops, true);
+            }
+            rir::Instruction::Fcmp(op, lhs, rhs, _) => {
+                let mut ops = Vec::new();
+                self.push_value_and_type(&mut ops, lhs);
+                self.push_value(&mut ops, rhs);
+                ops.push(fcmp_predicate(*op));
+                self.record(code::INST_CMP2, &ops, true);
+            }
+            rir::Instruction::Branch(cond, true_id, false_id) => {
+                let mut ops = vec![self.block_indices[true_id], self.block_indices[false_id]];
+                self.push_value(&mut ops, &rir::Operand::Variable(*cond));
+                self.record(code::INST_BR, &ops, false);
+            }
+            rir::Instruction::Jump(block_id) => {
+                self.record(code::INST_BR, &[self.block_indices[block_id]], false);
+            }
+            rir::Instruction::Phi(args, variable) => {
+                assert!(
+                    !args.is_empty(),
+                    "phi instruction should have at least one argument"
+                );
+                let mut ops = vec![u64::from(self.module.rir_type_id(variable.ty))];
+                for (arg, block_id) in args {
+                    // Phi operands may refer to later values, so they are signed.
+                    let relative = i64::from(self.next_id) - i64::from(self.value_id(arg));
+                    ops.push(encode_signed(relative));
+                    ops.push(self.block_indices[block_id]);
+                }
+                self.record(code::INST_PHI, &ops, true);
+            }
+            rir::Instruction::Call(callable_id, args, output) => {
+                let callable = self.module.program.get_callable(*callable_id);
+                let ty = self.module.function_type_id(callable);
+                let callee = self.module.functions[callable_id];
+                let mut ops = vec![
+                    0,
+                    CALL_EXPLICIT_TYPE,
+                    u64::from(ty),
+                    u64::from(self.next_id - callee),
+                ];
+                for arg in args {
+                    self.push_value(&mut ops, arg);
+                }
+                self.record(code::INST_CALL, &ops, output.is_some());
+            }
+            rir::Instruction::Return => self.record(code::INST_RET, &[], false),
+            rir::Instruction::Store(..) => unimplemented!("store should be removed by pass"),
+        }
+    }
+
+    fn binop(&mut self, opcode: u64, lhs: &rir::Operand, rhs: &rir::Operand) {
+        let mut ops = Vec::new();
+        self.push_value_and_type(&mut ops, lhs);
+        self.push_value(&mut ops, rhs);
+        ops.push(opcode);
+        self.record(code::INST_BINOP, &ops, true);
+    }
+
+    fn record(&mut self, code: u64, ops: &[u64], has_result: bool) {
+        self.module.stream.record(code, ops);
+        if has_result {
+            self.next_id += 1;
+        }
+    }
+
+    fn value_id(&mut self, operand: &rir::Operand) -> u32 {
+        match operand {
+            rir::Operand::Literal(literal) => {
+                let constant = self.module.constant(*literal);
+                self.module.constant_id(constant)
+            }
+            rir::Operand::Variable(variable) => self.variables[&variable.variable_id],
+        }
+    }
+
+    /// Pushes the value relative to the current instruction.
+    fn push_value(&mut self, ops: &mut Vec<u64>, operand: &rir::Operand) {
+        let id = self.value_id(operand);
+        ops.push(u64::from(self.next_id.wrapping_sub(id)));
+    }
+
+    /// Pushes the value relative to the current instruction, followed by its type if the value
+    /// is defined later.
+    fn push_value_and_type(&mut self, ops: &mut Vec<u64>, operand: &rir::Operand) {
+        let id = self.value_id(operand);
+        ops.push(u64::from(self.next_id.wrapping_sub(id)));
+        if id >= self.next_id {
+            let ty = match operand {
+                rir::Operand::Literal(_) => unreachable!("constants are defined before use"),
+                rir::Operand::Variable(variable) => variable.ty,
+            };
+            ops.push(u64::from(self.module.rir_type_id(ty)));
+        }
+    }
+}
+
+fn instruction_literals(instruction: &rir::Instruction) -> Vec<rir::Literal> {
+    let (operands, implicit): (Vec<&rir::Operand>, _) = match instruction {
+        rir::Instruction::Add(lhs, rhs, _)
+        | rir::Instruction::Sub(lhs, rhs, _)
+        | rir::Instruction::Mul(lhs, rhs, _)
+        | rir::Instruction::Sdiv(lhs, rhs, _)
+        | rir::Instruction::Srem(lhs, rhs, _)
+        | rir::Instruction::Shl(lhs, rhs, _)
+        | rir::Instruction::Ashr(lhs, rhs, _)
+        | rir::Instruction::Icmp(_, lhs, rhs, _)
+        | rir::Instruction::Fadd(lhs, rhs, _)
+        | rir::Instruction::Fsub(lhs, rhs, _)
+        | rir::Instruction::Fmul(lhs, rhs, _)
+        | rir::Instruction::Fdiv(lhs, rhs, _)
+        | rir::Instruction::Fcmp(_, lhs, rhs, _)
+        | rir::Instruction::LogicalAnd(lhs, rhs, _)
+        | rir::Instruction::LogicalOr(lhs, rhs, _)
+        | rir::Instruction::BitwiseAnd(lhs, rhs, _)
+        | rir::Instruction::BitwiseOr(lhs, rhs, _)
+        | rir::Instruction::BitwiseXor(lhs, rhs, _) => (vec![lhs, rhs], None),
+        // Negation is written as an `xor` with all bits set.
+        rir::Instruction::LogicalNot(value, _) => (vec![value], Some(rir::Literal::Bool(true))),
+        rir::Instruction::BitwiseNot(value, _) => (vec![value], Some(rir::Literal::Integer(-1))),
+        rir::Instruction::Store(value, _) => (vec![value], None),
+        rir::Instruction::Call(_, args, _) => (args.iter().collect(), None),
+        rir::Instruction::Phi(args, _) => (args.iter().map(|(arg, _)| arg).collect(), None),
+        rir::Instruction::Jump(_) | rir::Instruction::Branch(..) | rir::Instruction::Return => {
+            (Vec::new(), None)
+        }
+    };
+    operands
+        .into_iter()
+        .filter_map(|operand| match operand {
+            rir::Operand::Literal(literal) => Some(*literal),
+            rir::Operand::Variable(_) => None,
+        })
+        .chain(implicit)
+        .collect()
+}
+
+fn instruction_output(instruction: &rir::Instruction) -> Option<rir::Variable> {
+    match instruction {
+        rir::Instruction::Add(_, _, variable)
+        | rir::Instruction::Sub(_, _, variable)
+        | rir::Instruction::Mul(_, _, variable)
+        | rir::Instruction::Sdiv(_, _, variable)
+        | rir::Instruction::Srem(_, _, variable)
+        | rir::Instruction::Shl(_, _, variable)
+        | rir::Instruction::Ashr(_, _, variable)
+        | rir::Instruction::Icmp(_, _, _, variable)
+        | rir::Instruction::Fadd(_, _, variable)
+        | rir::Instruction::Fsub(_, _, variable)
+        | rir::Instruction::Fmul(_, _, variable)
+        | rir::Instruction::Fdiv(_, _, variable)
+        | rir::Instruction::Fcmp(_, _, _, variable)
+        | rir::Instruction::LogicalNot(_, variable)
+        | rir::Instruction::LogicalAnd(_, _, variable)
+        | rir::Instruction::LogicalOr(_, _, variable)
+        | rir::Instruction::BitwiseNot(_, variable)
+        | rir::Instruction::BitwiseAnd(_, _, variable)
+        | rir::Instruction::BitwiseOr(_, _, variable)
+        | rir::Instruction::BitwiseXor(_, _, variable)
+        | rir::Instruction::Phi(_, variable) => Some(*variable),
+        rir::Instruction::Call(_, _, output) => *output,
+        rir::Instruction::Store(..)
+        | rir::Instruction::Jump(_)
+        | rir::Instruction::Branch(..)
+        | rir::Instruction::Return => None,
+    }
+}
+
+fn icmp_predicate(op: ConditionCode) -> u64 {
+    match op {
+        ConditionCode::Eq => 32,
+        ConditionCode::Ne => 33,
+        ConditionCode::Sgt => 38,
+        ConditionCode::Sge => 39,
+        ConditionCode::Slt => 40,
+        ConditionCode::Sle => 41,
+    }
+}
+
+fn push_string_attr(ops: &mut Vec<u64>, key: &str, value: Option<&str>) {
+    ops.push(if value.is_some() { 4 } else { 3 });
+    ops.extend(chars(key));
+    ops.push(0);
+    if let Some(value) = value {
+        ops.extend(chars(value));
+        ops.push(0);
+    }
+}
+
+fn chars(s: &str) -> Vec<u64> {
+    s.bytes().map(u64::from).collect()
+}
+
+/// Encodes a signed value with the sign in the lowest bit, as LLVM does for signed operands.
+fn encode_signed(value: i64) -> u64 {
+    if value == i64::MIN {
+        // The magnitude does not fit after the shift, so LLVM reserves "negative zero" for it.
+        1
+    } else if value >= 0 {
+        value.unsigned_abs() << 1
+    } else {
+        (value.unsigned_abs() << 1) | 1
+    }
+}
+
+fn fcmp_predicate(op: FcmpConditionCode) -> u64 {
+    match op {
+        FcmpConditionCode::False => 0,
+        FcmpConditionCode::OrderedAndEqual => 1,
+        FcmpConditionCode::OrderedAndGreaterThan => 2,
+        FcmpConditionCode::OrderedAndGreaterThanOrEqual => 3,
+        FcmpConditionCode::OrderedAndLessThan => 4,
+        FcmpConditionCode::OrderedAndLessThanOrEqual => 5,
+        FcmpConditionCode::OrderedAndNotEqual => 6,
+        FcmpConditionCode::Ordered => 7,
+        FcmpConditionCode::Unordered => 8,
+        FcmpConditionCode::UnorderedOrEqual => 9,
+        FcmpConditionCode::UnorderedOrGreaterThan => 10,
+        FcmpConditionCode::UnorderedOrGreaterThanOrEqual => 11,
+        FcmpConditionCode::UnorderedOrLessThan => 12,
+        FcmpConditionCode::UnorderedOrLessThanOrEqual => 13,
+        FcmpConditionCode::UnorderedOrNotEqual => 14,
+        FcmpConditionCode::True => 15,
+    }
+}
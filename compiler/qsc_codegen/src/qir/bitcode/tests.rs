@@ -0,0 +1,293 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{encode_signed, write_bitcode, BitWriter};
+use expect_test::expect;
+use qsc_rir::{builder, rir};
+use std::fmt::Write;
+
+/// Reads back a bitstream written with unabbreviated records and literal or blob abbreviations,
+/// rendering each block and record along with runs of printable operands as strings.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    bit: usize,
+}
+
+enum AbbrevOp {
+    Literal(u64),
+    Blob,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, width: u32) -> u64 {
+        let mut value = 0;
+        for i in 0..width {
+            let byte = self.bytes[self.bit / 8];
+            value |= u64::from((byte >> (self.bit % 8)) & 1) << i;
+            self.bit += 1;
+        }
+        value
+    }
+
+    fn read_vbr(&mut self, width: u32) -> u64 {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let chunk = self.read(width);
+            value |= (chunk & ((1 << (width - 1)) - 1)) << shift;
+            if chunk & (1 << (width - 1)) == 0 {
+                return value;
+            }
+            shift += width - 1;
+        }
+    }
+
+    fn align32(&mut self) {
+        self.bit = self.bit.next_multiple_of(32);
+    }
+
+    fn block(&mut self, out: &mut String, depth: usize) {
+        let id = self.read_vbr(8);
+        let width = u32::try_from(self.read_vbr(4)).expect("width should fit in u32");
+        self.align32();
+        let words = usize::try_from(self.read(32)).expect("length should fit in usize");
+        let end = self.bit + words * 32;
+        let indent = "    ".repeat(depth);
+        writeln!(out, "{indent}block {id} {{").expect("writing should succeed");
+        let mut abbrevs = Vec::new();
+        loop {
+            match self.read(width) {
+                0 => {
+                    self.align32();
+                    assert_eq!(self.bit, end, "block length should match its contents");
+                    writeln!(out, "{indent}}}").expect("writing should succeed");
+                    return;
+                }
+                1 => self.block(out, depth + 1),
+                2 => {
+                    let ops = (0..self.read_vbr(5))
+                        .map(|_| {
+                            if self.read(1) == 1 {
+                                AbbrevOp::Literal(self.read_vbr(8))
+                            } else {
+                                assert_eq!(self.read(3), 5, "only blobs should be abbreviated");
+                                AbbrevOp::Blob
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    abbrevs.push(ops);
+                }
+                3 => {
+                    let code = self.read_vbr(6);
+                    let ops = (0..self.read_vbr(6))
+                        .map(|_| self.read_vbr(6))
+                        .collect::<Vec<_>>();
+                    writeln!(out, "{indent}    {code}: {}", render(&ops))
+                        .expect("writing should succeed");
+                }
+                id => {
+                    let abbrev = &abbrevs[usize::try_from(id - 4).expect("id should fit")];
+                    let mut fields = Vec::new();
+                    for op in abbrev {
+                        match op {
+                            AbbrevOp::Literal(value) => fields.push(value.to_string()),
+                            AbbrevOp::Blob => {
+                                let len =
+                                    usize::try_from(self.read_vbr(6)).expect("len should fit");
+                                self.align32();
+                                let start = self.bit / 8;
+                                let blob = &self.bytes[start..start + len];
+                                fields.push(format!("{:?}", String::from_utf8_lossy(blob)));
+                                self.bit += len * 8;
+                                self.align32();
+                            }
+                        }
+                    }
+                    writeln!(out, "{indent}    {}", fields.join(": "))
+                        .expect("writing should succeed");
+                }
+            }
+        }
+    }
+}
+
+fn render(ops: &[u64]) -> String {
+    let printable = |op: &u64| (0x20..0x7f).contains(op);
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < ops.len() {
+        let run = ops[i..].iter().take_while(|op| printable(op)).count();
+        if run >= 4 {
+            let text = ops[i..i + run]
+                .iter()
+                .map(|&op| char::from(u8::try_from(op).expect("op should be ascii")))
+                .collect::<String>();
+            parts.push(format!("{text:?}"));
+            i += run;
+        } else {
+            parts.push(ops[i].to_string());
+            i += 1;
+        }
+    }
+    format!("[{}]", parts.join(", "))
+}
+
+fn dump(bytes: &[u8]) -> String {
+    assert_eq!(
+        &bytes[..4],
+        b"BC\xC0\xDE",
+        "bitcode should start with the magic number"
+    );
+    assert_eq!(bytes.len() % 4, 0, "bitcode should be word aligned");
+    let mut reader = BitReader { bytes, bit: 32 };
+    let mut out = String::new();
+    while reader.bit < bytes.len() * 8 {
+        assert_eq!(reader.read(2), 1, "top level should only contain blocks");
+        reader.block(&mut out, 0);
+    }
+    out
+}
+
+#[test]
+fn signed_values_put_sign_in_lowest_bit() {
+    assert_eq!(encode_signed(0), 0);
+    assert_eq!(encode_signed(3), 6);
+    assert_eq!(encode_signed(-3), 7);
+    assert_eq!(encode_signed(i64::MIN), 1);
+}
+
+#[test]
+fn vbr_values_are_split_into_chunks() {
+    let mut writer = BitWriter::new();
+    writer.emit_vbr(1000, 6);
+    writer.align32();
+    // 1000 = 0b11111_01000, written as 0b1_01000 then 0b0_11111.
+    assert_eq!(writer.finish(), [0xE8, 0x07, 0, 0]);
+}
+
+#[test]
+#[allow(clippy::too_many_lines)]
+fn bell_program_bitcode() {
+    let program = builder::bell_program();
+    expect![[r#"
+        block 8 {
+            1: [2]
+            block 10 {
+                3: [1, 4294967295, 3, "entry_point", 0, 3, "output_labeling_schema", 0, 4, "qir_profiles", 0, "base_profile", 0, 4, "required_num_qubits", 0, 50, 0, 4, "required_num_results", 0, 50, 0]
+                3: [2, 4294967295, 3, "irreversible", 0]
+            }
+            block 9 {
+                2: [1]
+                2: [2]
+            }
+            block 17 {
+                1: [22]
+                19: ["Result"]
+                6: [0]
+                19: ["Qubit"]
+                6: [0]
+                2: []
+                8: [1, 0]
+                21: [0, 2, 3]
+                8: [4, 0]
+                21: [0, 2, 3, 3]
+                8: [6, 0]
+                8: [0, 0]
+                21: [0, 2, 3, 8]
+                8: [9, 0]
+                7: [64]
+                7: [8]
+                8: [12, 0]
+                21: [0, 2, 11, 13]
+                8: [14, 0]
+                21: [0, 2, 8, 13]
+                8: [16, 0]
+                21: [0, 2]
+                8: [18, 0]
+                7: [32]
+                7: [1]
+            }
+            8: [0, 23, 4, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+            8: [23, 24, 6, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+            8: [47, 24, 9, 0, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+            8: [71, 34, 14, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+            8: [105, 35, 16, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+            8: [140, 16, 18, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+            block 11 {
+                1: [11]
+                4: [0]
+                4: [2]
+                4: [4]
+                1: [20]
+                4: [0]
+                4: [2]
+                4: [14]
+                1: [21]
+                4: [0]
+                1: [13]
+                2: []
+                1: [3]
+                11: [10, 11, 6]
+                11: [10, 11, 7]
+                1: [8]
+                11: [10, 11, 6]
+                11: [10, 11, 7]
+            }
+            block 15 {
+                1: ["qir_major_version"]
+                1: ["qir_minor_version"]
+                1: ["dynamic_qubit_management"]
+                1: ["dynamic_result_management"]
+                2: [20, 10]
+                2: [20, 11]
+                2: [20, 9]
+                2: [21, 12]
+                3: [5, 1, 5]
+                3: [6, 2, 7]
+                3: [5, 3, 8]
+                3: [5, 4, 8]
+                4: ["llvm.module.flags"]
+                10: [8, 9, 10, 11]
+            }
+            block 12 {
+                1: [1]
+                34: [0, 32768, 4, 18, 4]
+                34: [0, 32768, 6, 17, 4, 3]
+                34: [0, 32768, 9, 16, 4, 2]
+                34: [0, 32768, 9, 16, 3, 1]
+                34: [0, 32768, 14, 15, 10, 5]
+                34: [0, 32768, 16, 14, 2, 5]
+                34: [0, 32768, 16, 14, 1, 5]
+                10: []
+                block 14 {
+                    2: [0, "block_0"]
+                }
+            }
+        }
+        block 23 {
+            1: "__quantum__qis__h__body__quantum__qis__cx__body__quantum__qis__mz__body__quantum__rt__array_record_output__quantum__rt__result_record_outputENTRYPOINT__main"
+        }
+    "#]]
+    .assert_eq(&dump(&write_bitcode(&program)));
+}
+
+#[test]
+fn teleport_program_bitcode_is_well_formed() {
+    let program = builder::teleport_program();
+    let dump = dump(&write_bitcode(&program));
+    assert!(dump.contains(r#"1: ["qir_major_version"]"#));
+    assert!(dump.contains(r#"2: [0, "block_0"]"#));
+}
+
+#[test]
+fn adaptive_program_flags_capabilities() {
+    let mut program = builder::bell_program();
+    program.config.capabilities = qsc_data_structures::target::TargetCapabilityFlags::all();
+    program
+        .blocks
+        .insert(rir::BlockId(0), rir::Block(vec![rir::Instruction::Return]));
+    let dump = dump(&write_bitcode(&program));
+    assert!(dump.contains(r#""qir_profiles", 0, "adaptive_profile", 0"#));
+    assert!(dump.contains(r#"1: ["classical_ints"]"#));
+    assert!(dump.contains(r#"1: ["multiple_target_branching"]"#));
+}
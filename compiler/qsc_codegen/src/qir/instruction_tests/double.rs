@@ -2,6 +2,7 @@
 // Licensed under the MIT License.
 
 use crate::qir::ToQir;
+use expect_test::expect;
 use qsc_rir::rir;
 
 #[test]
@@ -86,3 +87,83 @@ fn bitwise_xor_double_literals() {
     );
     let _ = &inst.to_qir(&rir::Program::default());
 }
+
+#[test]
+fn fadd_double_literals() {
+    let inst = rir::Instruction::Fadd(
+        rir::Operand::Literal(rir::Literal::Double(1.5)),
+        rir::Operand::Literal(rir::Literal::Double(2.0)),
+        rir::Variable {
+            variable_id: rir::VariableId(0),
+            ty: rir::Ty::Double,
+        },
+    );
+    expect!["  %var_0 = fadd double 1.5, 2.0"].assert_eq(&inst.to_qir(&rir::Program::default()));
+}
+
+#[test]
+fn fdiv_double_variables() {
+    let inst = rir::Instruction::Fdiv(
+        rir::Operand::Variable(rir::Variable {
+            variable_id: rir::VariableId(1),
+            ty: rir::Ty::Double,
+        }),
+        rir::Operand::Variable(rir::Variable {
+            variable_id: rir::VariableId(2),
+            ty: rir::Ty::Double,
+        }),
+        rir::Variable {
+            variable_id: rir::VariableId(0),
+            ty: rir::Ty::Double,
+        },
+    );
+    expect!["  %var_0 = fdiv double %var_1, %var_2"]
+        .assert_eq(&inst.to_qir(&rir::Program::default()));
+}
+
+#[test]
+fn fcmp_olt_double_variable_and_literal() {
+    let inst = rir::Instruction::Fcmp(
+        rir::FcmpConditionCode::OrderedAndLessThan,
+        rir::Operand::Variable(rir::Variable {
+            variable_id: rir::VariableId(1),
+            ty: rir::Ty::Double,
+        }),
+        rir::Operand::Literal(rir::Literal::Double(0.5)),
+        rir::Variable {
+            variable_id: rir::VariableId(0),
+            ty: rir::Ty::Boolean,
+        },
+    );
+    expect!["  %var_0 = fcmp olt double %var_1, 0.5"]
+        .assert_eq(&inst.to_qir(&rir::Program::default()));
+}
+
+#[test]
+#[should_panic(expected = "unsupported type i64 for fmul")]
+fn fmul_integer_literals() {
+    let inst = rir::Instruction::Fmul(
+        rir::Operand::Literal(rir::Literal::Integer(2)),
+        rir::Operand::Literal(rir::Literal::Integer(3)),
+        rir::Variable {
+            variable_id: rir::VariableId(0),
+            ty: rir::Ty::Integer,
+        },
+    );
+    let _ = &inst.to_qir(&rir::Program::default());
+}
+
+#[test]
+#[should_panic(expected = "unsupported output type f64 for fcmp")]
+fn fcmp_with_double_output() {
+    let inst = rir::Instruction::Fcmp(
+        rir::FcmpConditionCode::OrderedAndEqual,
+        rir::Operand::Literal(rir::Literal::Double(1.0)),
+        rir::Operand::Literal(rir::Literal::Double(2.0)),
+        rir::Variable {
+            variable_id: rir::VariableId(0),
+            ty: rir::Ty::Double,
+        },
+    );
+    let _ = &inst.to_qir(&rir::Program::default());
+}
@@ -3,6 +3,7 @@
 
 use num_bigint::BigUint;
 use num_complex::Complex;
+use qsc_data_structures::functors::FunctorApp;
 use quantum_sparse_sim::QuantumSim;
 use rand::RngCore;
 
@@ -97,9 +98,18 @@ pub trait Backend {
     }
 
     fn set_seed(&mut self, _seed: Option<u64>) {}
+
+    /// Called when evaluation enters a callable that is implemented in Q# rather than as an
+    /// intrinsic, with the callable's name, the functors applied to it, and its argument.
+    fn enter_callable(&mut self, _name: &str, _functor: FunctorApp, _arg: &Value) {}
+
+    /// Called when evaluation leaves the callable most recently entered with `enter_callable`.
+    fn leave_callable(&mut self) {}
 }
 
 /// Default backend used when targeting sparse simulation.
+/// Cloning it copies the full quantum state, so it is only available with the `snapshot` feature.
+#[cfg_attr(feature = "snapshot", derive(Clone))]
 pub struct SparseSim {
     pub sim: QuantumSim,
 }
@@ -288,6 +298,7 @@ impl Backend for SparseSim {
 /// For any intrinsics that return a value,
 /// the value returned by the chained backend is ignored.
 /// The value returned by the main backend is returned.
+#[derive(Clone)]
 pub struct Chain<T1, T2> {
     pub main: T1,
     pub chained: T2,
@@ -459,4 +470,14 @@ where
         self.chained.set_seed(seed);
         self.main.set_seed(seed);
     }
+
+    fn enter_callable(&mut self, name: &str, functor: FunctorApp, arg: &Value) {
+        self.chained.enter_callable(name, functor, arg);
+        self.main.enter_callable(name, functor, arg);
+    }
+
+    fn leave_callable(&mut self) {
+        self.chained.leave_callable();
+        self.main.leave_callable();
+    }
 }
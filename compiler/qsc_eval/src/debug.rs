@@ -35,6 +35,11 @@ impl CallStack {
         self.frames
     }
 
+    #[must_use]
+    pub fn last_frame(&self) -> Option<&Frame> {
+        self.frames.last()
+    }
+
     pub fn push_frame(&mut self, frame: Frame) {
         self.frames.push(frame);
     }
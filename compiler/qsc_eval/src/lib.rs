@@ -28,7 +28,7 @@ pub mod val;
 use crate::val::Value;
 use backend::Backend;
 use debug::{CallStack, Frame};
-use error::PackageSpan;
+pub use error::PackageSpan;
 use miette::Diagnostic;
 use num_bigint::BigInt;
 use output::Receiver;
@@ -46,6 +46,7 @@ use std::{
     cell::RefCell,
     fmt::{self, Display, Formatter},
     iter,
+    mem::take,
     ops::Neg,
     rc::Rc,
 };
@@ -237,6 +238,45 @@ pub fn eval(
     Ok(value)
 }
 
+/// Invokes the given callable value with the given arguments, running the call to completion.
+/// # Errors
+/// Returns the first error encountered during execution.
+/// # Panics
+/// On internal error where no result is returned.
+#[allow(clippy::too_many_arguments)]
+pub fn invoke(
+    package: PackageId,
+    seed: Option<u64>,
+    globals: &impl PackageStoreLookup,
+    env: &mut Env,
+    sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+    receiver: &mut impl Receiver,
+    callable: Value,
+    args: Value,
+) -> Result<Value, (Error, Vec<Frame>)> {
+    let mut state = State::new(package, Vec::new().into(), seed);
+    // Set up the value stack and register as if the callee and arguments
+    // had just been evaluated as part of a call expression.
+    state.set_val_register(callable);
+    state.push_val();
+    state.set_val_register(args);
+    state
+        .eval_call(
+            env,
+            sim,
+            globals,
+            Span::default(),
+            Span::default(),
+            receiver,
+        )
+        .map_err(|e| (e, state.unwind(sim)))?;
+    let res = state.eval(globals, env, sim, receiver, &[], StepAction::Continue)?;
+    let StepResult::Return(value) = res else {
+        panic!("invoke should always return a value");
+    };
+    Ok(value)
+}
+
 /// The type of step action to take during evaluation
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum StepAction {
@@ -254,6 +294,9 @@ pub enum StepResult {
     StepIn,
     StepOut,
     Return(Value),
+    /// Evaluation failed with the given error. The call stack and environment
+    /// are left as they were at the failing statement, so they can still be inspected.
+    Exception(Error),
 }
 
 trait AsIndex {
@@ -282,6 +325,8 @@ pub struct Variable {
 
 #[derive(Debug, Clone)]
 pub struct VariableInfo {
+    /// The id of the local the variable is bound to, which is unique within its callable.
+    pub id: LocalVarId,
     pub value: Value,
     pub name: Rc<str>,
     pub type_name: String,
@@ -318,6 +363,7 @@ impl Range {
     }
 }
 
+#[derive(Clone)]
 pub struct Env(Vec<Scope>);
 
 impl Default for Env {
@@ -330,7 +376,7 @@ impl Default for Env {
 
 impl Env {
     #[must_use]
-    fn get(&self, id: LocalVarId) -> Option<&Variable> {
+    pub fn get(&self, id: LocalVarId) -> Option<&Variable> {
         self.0.iter().rev().find_map(|scope| scope.bindings.get(id))
     }
 
@@ -359,6 +405,15 @@ impl Env {
         }
     }
 
+    /// Returns the number of variables bound across all scopes.
+    #[must_use]
+    pub fn variable_count(&self) -> usize {
+        self.0
+            .iter()
+            .map(|scope| scope.bindings.iter().count())
+            .sum()
+    }
+
     pub fn bind_variable_in_top_frame(&mut self, local_var_id: LocalVarId, var: Variable) {
         let Some(scope) = self.0.last_mut() else {
             panic!("no frames in scope");
@@ -389,7 +444,8 @@ impl Env {
             .into_iter()
             .map(|bindings| {
                 bindings
-                    .map(|(_, var)| VariableInfo {
+                    .map(|(id, var)| VariableInfo {
+                        id,
                         name: var.name.clone(),
                         type_name: var.value.type_name().to_string(),
                         value: var.value.clone(),
@@ -400,14 +456,38 @@ impl Env {
             .collect();
         variables_by_scope.into_iter().flatten().collect::<Vec<_>>()
     }
+
+    /// Rebinds the variable of the given local in the given frame to a new value.
+    /// Returns `false` if there is no such variable.
+    pub fn update_variable_in_frame(
+        &mut self,
+        frame_id: usize,
+        id: LocalVarId,
+        value: Value,
+    ) -> bool {
+        let var = self
+            .0
+            .iter_mut()
+            .rev()
+            .filter(|scope| scope.frame_id == frame_id)
+            .find_map(|scope| scope.bindings.get_mut(id));
+        match var {
+            Some(var) => {
+                var.value = value;
+                true
+            }
+            None => false,
+        }
+    }
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 struct Scope {
     bindings: IndexMap<LocalVarId, Variable>,
     frame_id: usize,
 }
 
+#[derive(Clone)]
 pub struct State {
     exec_graph_stack: Vec<Rc<[ExecGraphNode]>>,
     idx: u32,
@@ -419,6 +499,8 @@ pub struct State {
     call_stack: CallStack,
     current_span: Span,
     rng: RefCell<StdRng>,
+    /// The number of callables entered on the backend and not yet left.
+    entered_callables: usize,
 }
 
 impl State {
@@ -443,6 +525,7 @@ impl State {
             call_stack: CallStack::default(),
             current_span: Span::default(),
             rng,
+            entered_callables: 0,
         }
     }
 
@@ -465,6 +548,15 @@ impl State {
         self.package = id.package;
     }
 
+    /// Leaves every callable entered on the backend, so that it is left in a consistent state when
+    /// evaluation stops with an error, and returns the stack frames at the point of the error.
+    fn unwind(&mut self, sim: &mut impl Backend) -> Vec<Frame> {
+        for _ in 0..take(&mut self.entered_callables) {
+            sim.leave_callable();
+        }
+        self.get_stack_frames()
+    }
+
     fn leave_frame(&mut self) {
         if let Some(frame) = self.call_stack.pop_frame() {
             self.package = frame.caller;
@@ -550,7 +642,7 @@ impl State {
                 Some(ExecGraphNode::Expr(expr)) => {
                     self.idx += 1;
                     self.eval_expr(env, sim, globals, out, *expr)
-                        .map_err(|e| (e, self.get_stack_frames()))?;
+                        .map_err(|e| (e, self.unwind(sim)))?;
                     continue;
                 }
                 Some(ExecGraphNode::Stmt(stmt)) => {
@@ -613,6 +705,10 @@ impl State {
                     continue;
                 }
                 Some(ExecGraphNode::Ret) => {
+                    if self.call_stack.last_frame().is_some() {
+                        self.entered_callables -= 1;
+                        sim.leave_callable();
+                    }
                     self.leave_frame();
                     env.leave_scope();
                     continue;
@@ -947,6 +1043,8 @@ impl State {
                     Spec::CtlAdj => specialized_implementation.ctl_adj.as_ref(),
                 }
                 .expect("missing specialization should be a compilation error");
+                self.entered_callables += 1;
+                sim.enter_callable(&callee.name.name, functor, &arg);
                 self.push_frame(spec_decl.exec_graph.clone(), callee_id, functor);
                 self.push_scope(env);
 
@@ -6,6 +6,7 @@ mod tests;
 
 use num_bigint::BigUint;
 use num_complex::{Complex, Complex64};
+use rustc_hash::FxHashMap;
 use std::fmt::Write;
 
 #[must_use]
@@ -39,6 +40,67 @@ pub fn fmt_basis_state_label(id: &BigUint, qubit_count: usize) -> String {
     format!("{:0>qubit_count$}", id.to_str_radix(2))
 }
 
+/// The reduced state of a single qubit, obtained by tracing out every other qubit.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct QubitState {
+    /// The Bloch vector `(x, y, z)` of the reduced density matrix.
+    pub bloch: (f64, f64, f64),
+    /// The probabilities of measuring `Zero` and `One`.
+    pub probabilities: (f64, f64),
+    /// Whether the qubit is entangled with other qubits, i.e. its reduced state is mixed.
+    pub entangled: bool,
+}
+
+/// Computes the reduced state of the qubit with the given id from a state as returned by
+/// `Backend::capture_quantum_state`, where qubits are in the order of allocation, left to right.
+#[must_use]
+pub fn get_qubit_state(
+    state: &[(BigUint, Complex64)],
+    qubit_count: usize,
+    qubit: usize,
+) -> QubitState {
+    if qubit >= qubit_count {
+        // Qubits outside of the captured state are in the |0⟩ state.
+        return QubitState {
+            bloch: (0.0, 0.0, 1.0),
+            probabilities: (1.0, 0.0),
+            entangled: false,
+        };
+    }
+
+    let bit = (qubit_count - qubit - 1) as u64;
+    let amplitudes = state
+        .iter()
+        .map(|(id, amplitude)| (id, *amplitude))
+        .collect::<FxHashMap<_, _>>();
+
+    // Accumulate the entries of the reduced density matrix ρ = [[ρ00, ρ01], [ρ10, ρ11]].
+    let mut rho00 = 0.0;
+    let mut rho11 = 0.0;
+    let mut rho10 = Complex64::new(0.0, 0.0);
+    for (id, amplitude) in state {
+        if id.bit(bit) {
+            rho11 += amplitude.norm_sqr();
+        } else {
+            rho00 += amplitude.norm_sqr();
+            let mut flipped = id.clone();
+            flipped.set_bit(bit, true);
+            if let Some(other) = amplitudes.get(&flipped) {
+                rho10 += other * amplitude.conj();
+            }
+        }
+    }
+
+    // ρ = (I + xX + yY + zZ) / 2, so ρ10 = (x + iy) / 2.
+    let bloch = (2.0 * rho10.re, 2.0 * rho10.im, rho00 - rho11);
+    let length_sqr = bloch.0 * bloch.0 + bloch.1 * bloch.1 + bloch.2 * bloch.2;
+    QubitState {
+        bloch,
+        probabilities: (rho00, rho11),
+        entangled: is_significant(1.0 - length_sqr),
+    }
+}
+
 #[must_use]
 fn is_significant(x: f64) -> bool {
     x.abs() > 1e-9
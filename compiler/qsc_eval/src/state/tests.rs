@@ -4,7 +4,7 @@
 #![allow(clippy::needless_raw_string_hashes)]
 
 use super::{
-    get_latex, write_latex_for_algebraic_number, write_latex_for_cartesian_form,
+    get_latex, get_qubit_state, write_latex_for_algebraic_number, write_latex_for_cartesian_form,
     write_latex_for_decimal_number, write_latex_for_polar_form, write_latex_for_real_number,
     write_latex_for_term, AlgebraicNumber, CartesianForm, ComplexNumber, DecimalNumber, PolarForm,
     RationalNumber, RealNumber, Term,
@@ -12,7 +12,10 @@ use super::{
 use crate::state::{is_fractional_part_significant, is_significant};
 use expect_test::{expect, Expect};
 use num_complex::Complex64;
-use std::{f64::consts::PI, time::Instant};
+use std::{
+    f64::consts::{FRAC_1_SQRT_2, PI},
+    time::Instant,
+};
 
 #[test]
 fn check_is_significant() {
@@ -924,3 +927,73 @@ fn check_get_latex_perf() {
         Instant::now().duration_since(start)
     );
 }
+
+#[test]
+fn check_get_qubit_state_basis() {
+    // |01⟩: qubit 0 is |0⟩ and qubit 1 is |1⟩.
+    let state = vec![(1_u8.into(), Complex64::new(1.0, 0.0))];
+    expect![[r#"
+        QubitState {
+            bloch: (
+                0.0,
+                0.0,
+                1.0,
+            ),
+            probabilities: (
+                1.0,
+                0.0,
+            ),
+            entangled: false,
+        }
+    "#]]
+    .assert_debug_eq(&get_qubit_state(&state, 2, 0));
+    expect![[r#"
+        QubitState {
+            bloch: (
+                0.0,
+                0.0,
+                -1.0,
+            ),
+            probabilities: (
+                0.0,
+                1.0,
+            ),
+            entangled: false,
+        }
+    "#]]
+    .assert_debug_eq(&get_qubit_state(&state, 2, 1));
+}
+
+#[test]
+fn check_get_qubit_state_superposition() {
+    // |0⟩ ⊗ |+i⟩, where |+i⟩ = (|0⟩ + i|1⟩) / √2.
+    let state = vec![
+        (0_u8.into(), Complex64::new(FRAC_1_SQRT_2, 0.0)),
+        (1_u8.into(), Complex64::new(0.0, FRAC_1_SQRT_2)),
+    ];
+    let qubit = get_qubit_state(&state, 2, 1);
+    assert!((qubit.bloch.0).abs() < 1e-9);
+    assert!((qubit.bloch.1 - 1.0).abs() < 1e-9);
+    assert!((qubit.bloch.2).abs() < 1e-9);
+    assert!((qubit.probabilities.0 - 0.5).abs() < 1e-9);
+    assert!((qubit.probabilities.1 - 0.5).abs() < 1e-9);
+    assert!(!qubit.entangled);
+}
+
+#[test]
+fn check_get_qubit_state_entangled() {
+    // Bell state (|00⟩ + |11⟩) / √2.
+    let state = vec![
+        (0_u8.into(), Complex64::new(FRAC_1_SQRT_2, 0.0)),
+        (3_u8.into(), Complex64::new(FRAC_1_SQRT_2, 0.0)),
+    ];
+    for id in 0..2 {
+        let qubit = get_qubit_state(&state, 2, id);
+        assert!(qubit.bloch.0.abs() < 1e-9);
+        assert!(qubit.bloch.1.abs() < 1e-9);
+        assert!(qubit.bloch.2.abs() < 1e-9);
+        assert!((qubit.probabilities.0 - 0.5).abs() < 1e-9);
+        assert!((qubit.probabilities.1 - 0.5).abs() < 1e-9);
+        assert!(qubit.entangled);
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use qsc_data_structures::span::Span;
+use qsc_fir::{
+    fir::{
+        Block, BlockId, Expr, ExprId, ExprKind, LocalVarId, Package, PackageLookup, Pat, PatId,
+        Res, Stmt, StmtId,
+    },
+    ty::Ty,
+    visit::{self, Visitor},
+};
+
+/// A local variable that is the target of an assignment.
+pub struct AssignedLocal {
+    pub id: LocalVarId,
+    pub ty: Ty,
+    pub span: Span,
+}
+
+/// Collects the local variables assigned within the given expressions, in order of first assignment.
+pub fn collect_assigned_locals(package: &Package, exprs: &[ExprId]) -> Vec<AssignedLocal> {
+    let mut collector = Collector {
+        package,
+        locals: Vec::new(),
+    };
+    for expr_id in exprs {
+        collector.visit_expr(*expr_id);
+    }
+    collector.locals
+}
+
+struct Collector<'a> {
+    package: &'a Package,
+    locals: Vec<AssignedLocal>,
+}
+
+impl Collector<'_> {
+    fn collect_assignee(&mut self, assignee_expr_id: ExprId) {
+        let assignee_expr = self.get_expr(assignee_expr_id);
+        match &assignee_expr.kind {
+            ExprKind::Var(Res::Local(local_var_id), _)
+                if !self.locals.iter().any(|local| local.id == *local_var_id) =>
+            {
+                self.locals.push(AssignedLocal {
+                    id: *local_var_id,
+                    ty: assignee_expr.ty.clone(),
+                    span: assignee_expr.span,
+                });
+            }
+            ExprKind::Tuple(exprs) => {
+                for expr_id in exprs {
+                    self.collect_assignee(*expr_id);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Collector<'a> {
+    fn get_block(&self, id: BlockId) -> &'a Block {
+        self.package.get_block(id)
+    }
+
+    fn get_expr(&self, id: ExprId) -> &'a Expr {
+        self.package.get_expr(id)
+    }
+
+    fn get_pat(&self, id: PatId) -> &'a Pat {
+        self.package.get_pat(id)
+    }
+
+    fn get_stmt(&self, id: StmtId) -> &'a Stmt {
+        self.package.get_stmt(id)
+    }
+
+    fn visit_expr(&mut self, expr_id: ExprId) {
+        let expr = self.get_expr(expr_id);
+        match &expr.kind {
+            ExprKind::Assign(assignee_expr_id, _)
+            | ExprKind::AssignOp(_, assignee_expr_id, _)
+            | ExprKind::AssignField(assignee_expr_id, _, _)
+            | ExprKind::AssignIndex(assignee_expr_id, _, _) => {
+                self.collect_assignee(*assignee_expr_id);
+            }
+            _ => {}
+        }
+        visit::walk_expr(self, expr_id);
+    }
+}
@@ -8,7 +8,7 @@ use qsc_eval::{
 };
 use qsc_fir::fir::{ExprId, LocalItemId, LocalVarId, PackageId};
 use qsc_rca::{RuntimeKind, ValueKind};
-use qsc_rir::rir::BlockId;
+use qsc_rir::rir::{self, BlockId};
 use rustc_hash::FxHashMap;
 
 pub struct EvaluationContext {
@@ -78,6 +78,9 @@ pub struct Scope {
     last_expr: Option<ExprId>,
     hybrid_exprs: FxHashMap<ExprId, Value>,
     hybrid_vars: FxHashMap<LocalVarId, Value>,
+    /// Mutable locals assigned under dynamic control flow, whose values are held in RIR variables (one per scalar
+    /// element for tuples, UDTs and arrays).
+    mutable_vars: FxHashMap<LocalVarId, Vec<rir::Variable>>,
 }
 
 impl Scope {
@@ -124,6 +127,7 @@ impl Scope {
             last_expr: None,
             hybrid_exprs: FxHashMap::default(),
             hybrid_vars,
+            mutable_vars: FxHashMap::default(),
         }
     }
 
@@ -134,9 +138,19 @@ impl Scope {
             .expect("expression value does not exist")
     }
 
-    pub fn get_local_var_value(&self, local_var_id: LocalVarId) -> &Value {
+    pub fn find_local_var_value(&self, local_var_id: LocalVarId) -> Option<&Value> {
+        // Locals bound by classical statements only exist in the environment.
         self.hybrid_vars
             .get(&local_var_id)
+            .or_else(|| self.env.get(local_var_id).map(|var| &var.value))
+    }
+
+    pub fn find_mutable_vars(&self, local_var_id: LocalVarId) -> Option<&[rir::Variable]> {
+        self.mutable_vars.get(&local_var_id).map(Vec::as_slice)
+    }
+
+    pub fn get_local_var_value(&self, local_var_id: LocalVarId) -> &Value {
+        self.find_local_var_value(local_var_id)
             .expect("local variable value does not exist")
     }
 
@@ -149,6 +163,15 @@ impl Scope {
         self.hybrid_vars.insert(local_var_id, value);
     }
 
+    pub fn insert_mutable_vars(&mut self, local_var_id: LocalVarId, vars: Vec<rir::Variable>) {
+        self.mutable_vars.insert(local_var_id, vars);
+    }
+
+    pub fn remove_local_var(&mut self, local_var_id: LocalVarId) {
+        self.hybrid_vars.remove(&local_var_id);
+        self.mutable_vars.remove(&local_var_id);
+    }
+
     pub fn clear_last_expr(&mut self) {
         self.last_expr = None;
     }
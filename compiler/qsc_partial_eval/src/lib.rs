@@ -1,9 +1,11 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+mod assigned_locals;
 mod evaluation_context;
 mod management;
 
+use assigned_locals::collect_assigned_locals;
 use evaluation_context::{Arg, BlockNode, EvaluationContext, Scope};
 use management::{QuantumIntrinsicsChecker, ResourceManager};
 use miette::Diagnostic;
@@ -13,24 +15,25 @@ use qsc_eval::{
     self, exec_graph_section,
     output::GenericReceiver,
     val::{self, Value, Var},
-    State, StepAction, StepResult, Variable,
+    PackageSpan, State, StepAction, StepResult, Variable,
 };
 use qsc_fir::{
     fir::{
         self, BinOp, Block, BlockId, CallableDecl, CallableImpl, ExecGraphNode, Expr, ExprId,
-        ExprKind, Global, Ident, PackageId, PackageStore, PackageStoreLookup, Pat, PatId, PatKind,
-        Res, SpecDecl, SpecImpl, Stmt, StmtId, StmtKind, StoreBlockId, StoreExprId, StoreItemId,
-        StorePatId, StoreStmtId,
+        ExprKind, Field, Global, Ident, ItemKind, PackageId, PackageLookup, PackageStore,
+        PackageStoreLookup, Pat, PatId, PatKind, Res, SpecDecl, SpecImpl, Stmt, StmtId, StmtKind,
+        StoreBlockId, StoreExprId, StoreItemId, StorePatId, StoreStmtId, UnOp,
     },
     ty::{Prim, Ty},
     visit::Visitor,
 };
+use qsc_lowerer::map_fir_package_to_hir;
 use qsc_rca::{ComputeKind, ComputePropertiesLookup, PackageStoreComputeProperties};
 use qsc_rir::{
     builder,
     rir::{
-        self, Callable, CallableId, CallableType, ConditionCode, Instruction, Literal, Operand,
-        Program,
+        self, Callable, CallableId, CallableType, ConditionCode, FcmpConditionCode, Instruction,
+        Literal, Operand, Program,
     },
 };
 use rustc_hash::FxHashMap;
@@ -61,58 +64,136 @@ pub enum Error {
 
     #[error("failed to evaluate array element expression")]
     #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateArrayElementExpression"))]
-    FailedToEvaluateArrayElementExpression(#[label] Span),
+    FailedToEvaluateArrayElementExpression(#[label] PackageSpan),
 
     #[error("failed to evaluate {0}")]
     #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateCallable"))]
-    FailedToEvaluateCallable(String, #[label] Span),
+    FailedToEvaluateCallable(String, #[label] PackageSpan),
 
     #[error("failed to evaluate callee expression")]
     #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateCalleeExpression"))]
-    FailedToEvaluateCalleeExpression(#[label] Span),
+    FailedToEvaluateCalleeExpression(#[label] PackageSpan),
 
     #[error("failed to evaluate call arguments expression")]
     #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateCallArgsExpression"))]
-    FailedToEvaluateCallArgsExpression(#[label] Span),
+    FailedToEvaluateCallArgsExpression(#[label] PackageSpan),
 
     #[error("failed to evaluate tuple element expression")]
     #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateTupleElementExpression"))]
-    FailedToEvaluateTupleElementExpression(#[label] Span),
+    FailedToEvaluateTupleElementExpression(#[label] PackageSpan),
+
+    #[error("failed to evaluate assignment value")]
+    #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateAssignmentValue"))]
+    FailedToEvaluateAssignmentValue(#[label] PackageSpan),
 
     #[error("failed to evaluate binary expression operand")]
     #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateBinaryExpressionOperand"))]
-    FailedToEvaluateBinaryExpressionOperand(#[label] Span),
+    FailedToEvaluateBinaryExpressionOperand(#[label] PackageSpan),
+
+    #[error("failed to evaluate unary expression operand")]
+    #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateUnaryExpressionOperand"))]
+    FailedToEvaluateUnaryExpressionOperand(#[label] PackageSpan),
+
+    #[error("failed to evaluate record expression")]
+    #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateRecordExpression"))]
+    FailedToEvaluateRecordExpression(#[label] PackageSpan),
+
+    #[error("failed to evaluate array expression")]
+    #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateArrayExpression"))]
+    FailedToEvaluateArrayExpression(#[label] PackageSpan),
+
+    #[error("failed to evaluate index expression")]
+    #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateIndexExpression"))]
+    FailedToEvaluateIndexExpression(#[label] PackageSpan),
+
+    #[error("failed to evaluate replacement value")]
+    #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateReplacementValue"))]
+    FailedToEvaluateReplacementValue(#[label] PackageSpan),
+
+    #[error("index out of range: {0}")]
+    #[diagnostic(code("Qsc.PartialEval.IndexOutOfRange"))]
+    IndexOutOfRange(i64, #[label("out of range")] PackageSpan),
+
+    #[error("dynamic index into an empty array")]
+    #[diagnostic(code("Qsc.PartialEval.DynamicIndexIntoEmptyArray"))]
+    DynamicIndexIntoEmptyArray(#[label] PackageSpan),
 
     #[error("failed to evaluate condition expression")]
     #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateConditionExpression"))]
-    FailedToEvaluateConditionExpression(#[label] Span),
+    FailedToEvaluateConditionExpression(#[label] PackageSpan),
 
     #[error("failed to evaluate a branch block of an if expression")]
     #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateIfExpressionBranchBlock"))]
-    FailedToEvaluateIfExpressionBranchBlock(#[label] Span),
+    FailedToEvaluateIfExpressionBranchBlock(#[label] PackageSpan),
 
     #[error("failed to evaluate block expression")]
     #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateBlockExpression"))]
-    FailedToEvaluateBlockExpression(#[label] Span),
+    FailedToEvaluateBlockExpression(#[label] PackageSpan),
 
     #[error("failed to evaluate loop condition")]
     #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateLoopCondition"))]
-    FailedToEvaluateLoopCondition(#[label] Span),
+    FailedToEvaluateLoopCondition(#[label] PackageSpan),
 
     #[error("failed to evaluate loop body")]
     #[diagnostic(code("Qsc.PartialEval.FailedToEvaluateLoopBody"))]
-    FailedToEvaluateLoopBody(#[label] Span),
+    FailedToEvaluateLoopBody(#[label] PackageSpan),
 
     #[error("failed to evaluate: {0} not yet implemented")]
     #[diagnostic(code("Qsc.PartialEval.Unimplemented"))]
-    Unimplemented(String, #[label] Span),
+    Unimplemented(String, #[label] PackageSpan),
+
+    #[error("unsupported intrinsic `{0}`")]
+    #[diagnostic(help(
+        "only intrinsic operations that take qubits and classical values and return `Unit` can be used in generated code"
+    ))]
+    #[diagnostic(code("Qsc.PartialEval.UnsupportedIntrinsic"))]
+    UnsupportedIntrinsic(String, #[label] PackageSpan),
+
+    #[error("unsupported type `{0}` in output")]
+    #[diagnostic(help(
+        "only `Result`, `Bool` and `Int` values, and tuples and arrays of them, can be included in generated output recording"
+    ))]
+    #[diagnostic(code("Qsc.PartialEval.UnsupportedOutputType"))]
+    UnsupportedOutputType(String, #[label] PackageSpan),
 
     #[error("unsupported Result literal in output")]
     #[diagnostic(help(
         "Result literals `One` and `Zero` cannot be included in generated QIR output recording."
     ))]
     #[diagnostic(code("Qsc.PartialEval.OutputResultLiteral"))]
-    OutputResultLiteral(#[label] Span),
+    OutputResultLiteral(#[label] PackageSpan),
+}
+
+impl Error {
+    #[must_use]
+    pub fn span(&self) -> &PackageSpan {
+        match self {
+            Self::EvaluationFailed(e) => e.span(),
+            Self::FailedToEvaluateArrayElementExpression(span)
+            | Self::FailedToEvaluateCallable(_, span)
+            | Self::FailedToEvaluateCalleeExpression(span)
+            | Self::FailedToEvaluateCallArgsExpression(span)
+            | Self::FailedToEvaluateTupleElementExpression(span)
+            | Self::FailedToEvaluateAssignmentValue(span)
+            | Self::FailedToEvaluateBinaryExpressionOperand(span)
+            | Self::FailedToEvaluateUnaryExpressionOperand(span)
+            | Self::FailedToEvaluateRecordExpression(span)
+            | Self::FailedToEvaluateArrayExpression(span)
+            | Self::FailedToEvaluateIndexExpression(span)
+            | Self::FailedToEvaluateReplacementValue(span)
+            | Self::IndexOutOfRange(_, span)
+            | Self::DynamicIndexIntoEmptyArray(span)
+            | Self::FailedToEvaluateConditionExpression(span)
+            | Self::FailedToEvaluateIfExpressionBranchBlock(span)
+            | Self::FailedToEvaluateBlockExpression(span)
+            | Self::FailedToEvaluateLoopCondition(span)
+            | Self::FailedToEvaluateLoopBody(span)
+            | Self::Unimplemented(_, span)
+            | Self::UnsupportedIntrinsic(_, span)
+            | Self::UnsupportedOutputType(_, span)
+            | Self::OutputResultLiteral(span) => span,
+        }
+    }
 }
 
 struct PartialEvaluator<'a> {
@@ -186,27 +267,48 @@ impl<'a> PartialEvaluator<'a> {
     }
 
     fn bind_value_to_ident(&mut self, ident: &Ident, value: Value) {
-        self.eval_context
-            .get_current_scope_mut()
-            .insert_local_var_value(ident.id, value);
+        // A declaration replaces anything left from an earlier evaluation of the same statement, such as in a previous
+        // iteration of a loop.
+        let scope = self.eval_context.get_current_scope_mut();
+        scope.remove_local_var(ident.id);
+        scope.insert_local_var_value(ident.id, value);
+    }
+
+    fn unbind_pat(&mut self, pat_id: PatId) {
+        let pat = self.get_pat(pat_id);
+        match &pat.kind {
+            PatKind::Bind(ident) => {
+                self.eval_context
+                    .get_current_scope_mut()
+                    .remove_local_var(ident.id);
+            }
+            PatKind::Tuple(pats) => {
+                for pat_id in pats {
+                    self.unbind_pat(*pat_id);
+                }
+            }
+            PatKind::Discard => {
+                // Nothing to unbind.
+            }
+        }
     }
 
     fn create_intrinsic_callable(
         &self,
         store_item_id: StoreItemId,
         callable_decl: &CallableDecl,
-    ) -> Callable {
+    ) -> Option<Callable> {
         let callable_package = self.package_store.get(store_item_id.package);
         let name = callable_decl.name.name.to_string();
         let input_type: Vec<rir::Ty> = callable_package
             .derive_callable_input_params(callable_decl)
             .iter()
-            .map(|input_param| map_fir_type_to_rir_type(&input_param.ty))
-            .collect();
+            .map(|input_param| try_map_fir_type_to_rir_type(&input_param.ty))
+            .collect::<Option<_>>()?;
         let output_type = if callable_decl.output == Ty::UNIT {
             None
         } else {
-            Some(map_fir_type_to_rir_type(&callable_decl.output))
+            Some(try_map_fir_type_to_rir_type(&callable_decl.output)?)
         };
         let body = None;
         let call_type = if name.eq("__quantum__qis__reset__body") {
@@ -214,13 +316,13 @@ impl<'a> PartialEvaluator<'a> {
         } else {
             CallableType::Regular
         };
-        Callable {
+        Some(Callable {
             name,
             input_type,
             output_type,
             body,
             call_type,
-        }
+        })
     }
 
     fn create_program_block(&mut self) -> rir::BlockId {
@@ -233,21 +335,17 @@ impl<'a> PartialEvaluator<'a> {
         // Visit the entry point expression.
         self.visit_expr(self.entry.expr.expr);
 
-        // Return the first error, if any.
-        // We should eventually return all the errors but since that is an interface change, we will do that as its own
-        // change.
-        if let Some(error) = self.errors.pop() {
-            return Err(error);
+        // Return the first error, if any, since it is the one that points to the construct that could not be evaluated.
+        // The errors that follow it only report the enclosing expressions and callables that failed as a consequence.
+        if !self.errors.is_empty() {
+            return Err(self.errors.swap_remove(0));
         }
 
         // Get the final value from the execution context.
         let ret_val = self.eval_context.get_current_scope().last_expr_value();
-        let output_recording: Vec<Instruction> = self
-            .generate_output_recording_instructions(
-                ret_val,
-                &self.get_expr(self.entry.expr.expr).ty,
-            )
-            .map_err(|()| Error::OutputResultLiteral(self.get_expr(self.entry.expr.expr).span))?;
+        let ret_ty = self.resolve_pure_ty(&self.get_expr(self.entry.expr.expr).ty);
+        let output_recording: Vec<Instruction> =
+            self.generate_output_recording_instructions(ret_val, &ret_ty)?;
 
         // Insert the return expression and return the generated program.
         let current_block = self.get_current_block_mut();
@@ -324,25 +422,22 @@ impl<'a> PartialEvaluator<'a> {
         match &expr.kind {
             ExprKind::Array(exprs) => self.eval_expr_array(exprs),
             ExprKind::ArrayLit(_) => panic!("array of literal values should always be classical"),
-            ExprKind::ArrayRepeat(_, _) => {
-                Err(Error::Unimplemented("Array Repeat".to_string(), expr.span))
-            }
-            ExprKind::Assign(_, _) => Err(Error::Unimplemented(
-                "Assignment Expr".to_string(),
-                expr.span,
-            )),
-            ExprKind::AssignField(_, _, _) => Err(Error::Unimplemented(
-                "Field Assignment Expr".to_string(),
-                expr.span,
-            )),
-            ExprKind::AssignIndex(_, _, _) => Err(Error::Unimplemented(
-                "Assignment Index Expr".to_string(),
-                expr.span,
-            )),
-            ExprKind::AssignOp(_, _, _) => Err(Error::Unimplemented(
-                "Assignment Op Expr".to_string(),
-                expr.span,
+            ExprKind::ArrayRepeat(_, _) => Err(Error::Unimplemented(
+                "Array Repeat".to_string(),
+                self.to_global_span(expr.span),
             )),
+            ExprKind::Assign(assignee_expr_id, value_expr_id) => {
+                self.eval_expr_assign(*assignee_expr_id, *value_expr_id)
+            }
+            ExprKind::AssignField(record_expr_id, field, replace_expr_id) => {
+                self.eval_expr_assign_field(*record_expr_id, field, *replace_expr_id)
+            }
+            ExprKind::AssignIndex(array_expr_id, index_expr_id, replace_expr_id) => {
+                self.eval_expr_assign_index(*array_expr_id, *index_expr_id, *replace_expr_id)
+            }
+            ExprKind::AssignOp(bin_op, assignee_expr_id, value_expr_id) => {
+                self.eval_expr_assign_op(*bin_op, *assignee_expr_id, *value_expr_id)
+            }
             ExprKind::BinOp(bin_op, lhs_expr_id, rhs_expr_id) => {
                 self.eval_expr_bin_op(expr_id, *bin_op, *lhs_expr_id, *rhs_expr_id)
             }
@@ -350,11 +445,17 @@ impl<'a> PartialEvaluator<'a> {
             ExprKind::Call(callee_expr_id, args_expr_id) => {
                 self.eval_expr_call(*callee_expr_id, *args_expr_id)
             }
-            ExprKind::Closure(_, _) => {
-                panic!("instruction generation for closure expressions is unsupported")
+            ExprKind::Closure(_, _) => Err(Error::Unimplemented(
+                "Closure Expr".to_string(),
+                self.to_global_span(expr.span),
+            )),
+            ExprKind::Fail(_) => Err(Error::Unimplemented(
+                "Fail Expr".to_string(),
+                self.to_global_span(expr.span),
+            )),
+            ExprKind::Field(record_expr_id, field) => {
+                self.eval_expr_field(*record_expr_id, field, expr.span)
             }
-            ExprKind::Fail(_) => panic!("instruction generation for fail expression is invalid"),
-            ExprKind::Field(_, _) => Err(Error::Unimplemented("Field Expr".to_string(), expr.span)),
             ExprKind::Hole => panic!("instruction generation for hole expressions is invalid"),
             ExprKind::If(condition_expr_id, body_expr_id, otherwise_expr_id) => self.eval_expr_if(
                 expr_id,
@@ -362,33 +463,319 @@ impl<'a> PartialEvaluator<'a> {
                 *body_expr_id,
                 *otherwise_expr_id,
             ),
-            ExprKind::Index(_, _) => Err(Error::Unimplemented("Index Expr".to_string(), expr.span)),
-            ExprKind::Lit(_) => panic!("instruction generation for literal expressions is invalid"),
-            ExprKind::Range(_, _, _) => {
-                panic!("instruction generation for range expressions is invalid")
-            }
-            ExprKind::Return(_) => Err(Error::Unimplemented("Return Expr".to_string(), expr.span)),
-            ExprKind::String(_) => {
-                panic!("instruction generation for string expressions is invalid")
+            ExprKind::Index(array_expr_id, index_expr_id) => {
+                self.eval_expr_index(expr_id, *array_expr_id, *index_expr_id)
             }
-            ExprKind::Tuple(exprs) => self.eval_expr_tuple(exprs),
-            ExprKind::UnOp(_, _) => Err(Error::Unimplemented("Unary Expr".to_string(), expr.span)),
-            ExprKind::UpdateField(_, _, _) => Err(Error::Unimplemented(
-                "Updated Field Expr".to_string(),
-                expr.span,
+            ExprKind::Lit(_) => panic!("instruction generation for literal expressions is invalid"),
+            ExprKind::Range(_, _, _) => Err(Error::Unimplemented(
+                "Range Expr".to_string(),
+                self.to_global_span(expr.span),
+            )),
+            ExprKind::Return(_) => Err(Error::Unimplemented(
+                "Return Expr".to_string(),
+                self.to_global_span(expr.span),
             )),
-            ExprKind::UpdateIndex(_, _, _) => Err(Error::Unimplemented(
-                "Update Index Expr".to_string(),
-                expr.span,
+            ExprKind::String(_) => Err(Error::Unimplemented(
+                "String Expr".to_string(),
+                self.to_global_span(expr.span),
             )),
+            ExprKind::Tuple(exprs) => self.eval_expr_tuple(exprs),
+            ExprKind::UnOp(un_op, operand_expr_id) => {
+                self.eval_expr_un_op(expr_id, *un_op, *operand_expr_id)
+            }
+            ExprKind::UpdateField(record_expr_id, field, replace_expr_id) => {
+                self.eval_expr_update_field(*record_expr_id, field, *replace_expr_id, expr.span)
+            }
+            ExprKind::UpdateIndex(array_expr_id, index_expr_id, replace_expr_id) => {
+                self.eval_expr_update_index(*array_expr_id, *index_expr_id, *replace_expr_id)
+            }
             ExprKind::Var(res, _) => Ok(self.eval_expr_var(res)),
             ExprKind::While(condition_expr_id, body_block_id) => {
-                self.eval_expr_while(*condition_expr_id, *body_block_id)
+                self.eval_expr_while(expr_id, *condition_expr_id, *body_block_id)
+            }
+        }
+    }
+
+    fn eval_expr_assign(
+        &mut self,
+        assignee_expr_id: ExprId,
+        value_expr_id: ExprId,
+    ) -> Result<Value, Error> {
+        let maybe_value = self.try_eval_expr(value_expr_id);
+        let Ok(value) = maybe_value else {
+            let value_expr = self.get_expr(value_expr_id);
+            let error =
+                Error::FailedToEvaluateAssignmentValue(self.to_global_span(value_expr.span));
+            return Err(error);
+        };
+
+        self.update_bindings(assignee_expr_id, value)?;
+        Ok(Value::unit())
+    }
+
+    fn eval_expr_assign_op(
+        &mut self,
+        bin_op: BinOp,
+        assignee_expr_id: ExprId,
+        value_expr_id: ExprId,
+    ) -> Result<Value, Error> {
+        // Visit the both the assignee and value expressions to get their value.
+        let maybe_assignee_value = self.try_eval_place_expr(assignee_expr_id);
+        let Ok(assignee_value) = maybe_assignee_value else {
+            let assignee_expr = self.get_expr(assignee_expr_id);
+            let error =
+                Error::FailedToEvaluateAssignmentValue(self.to_global_span(assignee_expr.span));
+            return Err(error);
+        };
+
+        let maybe_value = self.try_eval_expr(value_expr_id);
+        let Ok(value) = maybe_value else {
+            let value_expr = self.get_expr(value_expr_id);
+            let error =
+                Error::FailedToEvaluateAssignmentValue(self.to_global_span(value_expr.span));
+            return Err(error);
+        };
+
+        // The new value is the result of the binary operation, which has the same type as the assignee.
+        let assignee_expr = self.get_expr(assignee_expr_id);
+        let updated_value = self.eval_bin_op_with_values(
+            bin_op,
+            &assignee_value,
+            &value,
+            &assignee_expr.ty,
+            &assignee_expr.ty,
+            assignee_expr.span,
+        )?;
+        self.update_bindings(assignee_expr_id, updated_value)?;
+        Ok(Value::unit())
+    }
+
+    fn eval_expr_field(
+        &mut self,
+        record_expr_id: ExprId,
+        field: &Field,
+        span: Span,
+    ) -> Result<Value, Error> {
+        let maybe_record_value = self.try_eval_expr(record_expr_id);
+        let Ok(record_value) = maybe_record_value else {
+            let record_expr = self.get_expr(record_expr_id);
+            let error =
+                Error::FailedToEvaluateRecordExpression(self.to_global_span(record_expr.span));
+            return Err(error);
+        };
+
+        // Fields of primitive types (ranges) are only accessed classically.
+        let Field::Path(path) = field else {
+            return Err(Error::Unimplemented(
+                "Field Expr".to_string(),
+                self.to_global_span(span),
+            ));
+        };
+        Ok(follow_field_path(record_value, &path.indices))
+    }
+
+    fn eval_expr_update_field(
+        &mut self,
+        record_expr_id: ExprId,
+        field: &Field,
+        replace_expr_id: ExprId,
+        span: Span,
+    ) -> Result<Value, Error> {
+        let maybe_record_value = self.try_eval_place_expr(record_expr_id);
+        let Ok(record_value) = maybe_record_value else {
+            let record_expr = self.get_expr(record_expr_id);
+            let error =
+                Error::FailedToEvaluateRecordExpression(self.to_global_span(record_expr.span));
+            return Err(error);
+        };
+
+        let maybe_replace_value = self.try_eval_expr(replace_expr_id);
+        let Ok(replace_value) = maybe_replace_value else {
+            let replace_expr = self.get_expr(replace_expr_id);
+            let error =
+                Error::FailedToEvaluateReplacementValue(self.to_global_span(replace_expr.span));
+            return Err(error);
+        };
+
+        let Field::Path(path) = field else {
+            return Err(Error::Unimplemented(
+                "Update Field Expr".to_string(),
+                self.to_global_span(span),
+            ));
+        };
+        Ok(update_field_path(
+            &record_value,
+            &path.indices,
+            replace_value,
+        ))
+    }
+
+    fn eval_expr_assign_field(
+        &mut self,
+        record_expr_id: ExprId,
+        field: &Field,
+        replace_expr_id: ExprId,
+    ) -> Result<Value, Error> {
+        let record_expr = self.get_expr(record_expr_id);
+        let updated_value =
+            self.eval_expr_update_field(record_expr_id, field, replace_expr_id, record_expr.span)?;
+        self.update_bindings(record_expr_id, updated_value)?;
+        Ok(Value::unit())
+    }
+
+    fn eval_expr_index(
+        &mut self,
+        index_expr_id: ExprId,
+        array_expr_id: ExprId,
+        index_value_expr_id: ExprId,
+    ) -> Result<Value, Error> {
+        let (elements, index_value) =
+            self.eval_array_and_index_exprs(array_expr_id, index_value_expr_id)?;
+        let index_expr = self.get_expr(index_expr_id);
+        let index_value_expr = self.get_expr(index_value_expr_id);
+        match index_value {
+            Value::Int(index) => {
+                let Some(element) = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| elements.get(index))
+                else {
+                    return Err(Error::IndexOutOfRange(
+                        index,
+                        self.to_global_span(index_value_expr.span),
+                    ));
+                };
+                Ok(element.clone())
+            }
+            Value::Var(var) => {
+                // Select the element whose index matches the dynamic index. An out of range index cannot be detected,
+                // so it selects the last element.
+                let Some((last_element, elements)) = elements.split_last() else {
+                    return Err(Error::DynamicIndexIntoEmptyArray(
+                        self.to_global_span(index_value_expr.span),
+                    ));
+                };
+                let element_ty = self.resolve_pure_ty(&index_expr.ty);
+                let index_operand = Operand::Variable(rir::Variable {
+                    variable_id: var.0.into(),
+                    ty: rir::Ty::Integer,
+                });
+                let mut selected_value = last_element.clone();
+                for (index, element) in elements.iter().enumerate() {
+                    let condition = self.eval_index_equals(index_operand, index);
+                    selected_value = self.eval_select(
+                        condition,
+                        element,
+                        &selected_value,
+                        &element_ty,
+                        index_expr.span,
+                    )?;
+                }
+                Ok(selected_value)
             }
+            _ => Err(Error::Unimplemented(
+                "Index Expr".to_string(),
+                self.to_global_span(index_expr.span),
+            )),
         }
     }
 
-    #[allow(clippy::similar_names)]
+    fn eval_expr_update_index(
+        &mut self,
+        array_expr_id: ExprId,
+        index_expr_id: ExprId,
+        replace_expr_id: ExprId,
+    ) -> Result<Value, Error> {
+        let (elements, index_value) =
+            self.eval_array_and_index_exprs(array_expr_id, index_expr_id)?;
+        let maybe_replace_value = self.try_eval_expr(replace_expr_id);
+        let Ok(replace_value) = maybe_replace_value else {
+            let replace_expr = self.get_expr(replace_expr_id);
+            let error =
+                Error::FailedToEvaluateReplacementValue(self.to_global_span(replace_expr.span));
+            return Err(error);
+        };
+
+        let array_expr = self.get_expr(array_expr_id);
+        let index_expr = self.get_expr(index_expr_id);
+        let mut elements = (*elements).clone();
+        match index_value {
+            Value::Int(index) => {
+                let Some(element) = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| elements.get_mut(index))
+                else {
+                    return Err(Error::IndexOutOfRange(
+                        index,
+                        self.to_global_span(index_expr.span),
+                    ));
+                };
+                *element = replace_value;
+            }
+            Value::Var(var) => {
+                // Each element is either replaced or kept depending on whether its index matches the dynamic index.
+                let Ty::Array(element_ty) = self.resolve_pure_ty(&array_expr.ty) else {
+                    panic!("updated expression should be an array");
+                };
+                let index_operand = Operand::Variable(rir::Variable {
+                    variable_id: var.0.into(),
+                    ty: rir::Ty::Integer,
+                });
+                for (index, element) in elements.iter_mut().enumerate() {
+                    let condition = self.eval_index_equals(index_operand, index);
+                    *element = self.eval_select(
+                        condition,
+                        &replace_value,
+                        element,
+                        &element_ty,
+                        index_expr.span,
+                    )?;
+                }
+            }
+            _ => {
+                return Err(Error::Unimplemented(
+                    "Update Index Expr".to_string(),
+                    self.to_global_span(index_expr.span),
+                ))
+            }
+        }
+        Ok(Value::Array(elements.into()))
+    }
+
+    fn eval_expr_assign_index(
+        &mut self,
+        array_expr_id: ExprId,
+        index_expr_id: ExprId,
+        replace_expr_id: ExprId,
+    ) -> Result<Value, Error> {
+        let updated_value =
+            self.eval_expr_update_index(array_expr_id, index_expr_id, replace_expr_id)?;
+        self.update_bindings(array_expr_id, updated_value)?;
+        Ok(Value::unit())
+    }
+
+    fn eval_array_and_index_exprs(
+        &mut self,
+        array_expr_id: ExprId,
+        index_expr_id: ExprId,
+    ) -> Result<(Rc<Vec<Value>>, Value), Error> {
+        let maybe_array_value = self.try_eval_place_expr(array_expr_id);
+        let Ok(array_value) = maybe_array_value else {
+            let array_expr = self.get_expr(array_expr_id);
+            let error =
+                Error::FailedToEvaluateArrayExpression(self.to_global_span(array_expr.span));
+            return Err(error);
+        };
+
+        let maybe_index_value = self.try_eval_expr(index_expr_id);
+        let Ok(index_value) = maybe_index_value else {
+            let index_expr = self.get_expr(index_expr_id);
+            let error =
+                Error::FailedToEvaluateIndexExpression(self.to_global_span(index_expr.span));
+            return Err(error);
+        };
+        Ok((array_value.unwrap_array(), index_value))
+    }
+
     fn eval_expr_bin_op(
         &mut self,
         bin_op_expr_id: ExprId,
@@ -400,47 +787,191 @@ impl<'a> PartialEvaluator<'a> {
         let maybe_lhs_expr_value = self.try_eval_expr(lhs_expr_id);
         let Ok(lhs_expr_value) = maybe_lhs_expr_value else {
             let lhs_expr = self.get_expr(lhs_expr_id);
-            let error = Error::FailedToEvaluateBinaryExpressionOperand(lhs_expr.span);
+            let error =
+                Error::FailedToEvaluateBinaryExpressionOperand(self.to_global_span(lhs_expr.span));
             return Err(error);
         };
 
         let maybe_rhs_expr_value = self.try_eval_expr(rhs_expr_id);
         let Ok(rhs_expr_value) = maybe_rhs_expr_value else {
             let rhs_expr = self.get_expr(rhs_expr_id);
-            let error = Error::FailedToEvaluateBinaryExpressionOperand(rhs_expr.span);
+            let error =
+                Error::FailedToEvaluateBinaryExpressionOperand(self.to_global_span(rhs_expr.span));
             return Err(error);
         };
 
+        let operand_ty = &self.get_expr(lhs_expr_id).ty;
+        let bin_op_expr = self.get_expr(bin_op_expr_id);
+        self.eval_bin_op_with_values(
+            bin_op,
+            &lhs_expr_value,
+            &rhs_expr_value,
+            operand_ty,
+            &bin_op_expr.ty,
+            bin_op_expr.span,
+        )
+    }
+
+    #[allow(clippy::similar_names, clippy::too_many_lines)]
+    fn eval_bin_op_with_values(
+        &mut self,
+        bin_op: BinOp,
+        lhs_value: &Value,
+        rhs_value: &Value,
+        operand_ty: &Ty,
+        result_ty: &Ty,
+        span: Span,
+    ) -> Result<Value, Error> {
+        // Concatenating arrays only rearranges their elements, so it does not need any instructions.
+        if let (BinOp::Add, Value::Array(lhs_items), Value::Array(rhs_items)) =
+            (bin_op, lhs_value, rhs_value)
+        {
+            let items: Vec<_> = lhs_items.iter().chain(rhs_items.iter()).cloned().collect();
+            return Ok(Value::Array(items.into()));
+        }
+
+        // Operations on literals, such as on the length of the controls of a controlled specialization, are folded so
+        // that conditions on them stay classical.
+        if let Some(value) = fold_bin_op(bin_op, lhs_value, rhs_value) {
+            return Ok(value);
+        }
+
+        // Only operations on primitive types have corresponding instructions.
+        if !matches!(
+            operand_ty,
+            Ty::Prim(Prim::Bool | Prim::Double | Prim::Int | Prim::Result)
+        ) {
+            return Err(Error::Unimplemented(
+                format!("BinOp Expr ({bin_op:?})"),
+                self.to_global_span(span),
+            ));
+        }
+
         // Get the operands to use when generating the binary operation instruction depending on the type of the
         // expression's value.
-        let lhs_operand = if let Value::Result(result) = lhs_expr_value {
-            self.eval_result_as_bool_operand(result)
+        let lhs_operand = if let Value::Result(result) = lhs_value {
+            self.eval_result_as_bool_operand(*result)
         } else {
-            map_eval_value_to_rir_operand(&lhs_expr_value)
+            map_eval_value_to_typed_rir_operand(lhs_value, operand_ty)
         };
-        let rhs_operand = if let Value::Result(result) = rhs_expr_value {
-            self.eval_result_as_bool_operand(result)
+        let rhs_operand = if let Value::Result(result) = rhs_value {
+            self.eval_result_as_bool_operand(*result)
         } else {
-            map_eval_value_to_rir_operand(&rhs_expr_value)
+            map_eval_value_to_typed_rir_operand(rhs_value, operand_ty)
         };
 
         // Create a variable to store the result of the expression.
-        let bin_op_expr = self.get_expr(bin_op_expr_id);
         let variable_id = self.resource_manager.next_var();
-        let variable_ty = map_fir_type_to_rir_type(&bin_op_expr.ty);
+        let variable_ty = map_fir_type_to_rir_type(result_ty);
         let variable = rir::Variable {
             variable_id,
             ty: variable_ty,
         };
 
-        // Create the binary operation instruction and add it to the current block.
-        let instruction = match bin_op {
-            BinOp::Eq => Instruction::Icmp(ConditionCode::Eq, lhs_operand, rhs_operand, variable),
-            BinOp::Neq => Instruction::Icmp(ConditionCode::Ne, lhs_operand, rhs_operand, variable),
+        // Create the binary operation instruction and add it to the current block. Operations on doubles use the
+        // floating-point instructions, with comparisons being ordered so that they are false for NaN (except for
+        // inequality, which is true for NaN). Operations on integers are signed.
+        let instruction = match (bin_op, operand_ty) {
+            (BinOp::Add, Ty::Prim(Prim::Int)) => {
+                Instruction::Add(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Sub, Ty::Prim(Prim::Int)) => {
+                Instruction::Sub(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Mul, Ty::Prim(Prim::Int)) => {
+                Instruction::Mul(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Div, Ty::Prim(Prim::Int)) => {
+                Instruction::Sdiv(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Mod, Ty::Prim(Prim::Int)) => {
+                Instruction::Srem(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Shl, Ty::Prim(Prim::Int)) => {
+                Instruction::Shl(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Shr, Ty::Prim(Prim::Int)) => {
+                Instruction::Ashr(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::AndB, Ty::Prim(Prim::Int)) => {
+                Instruction::BitwiseAnd(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::OrB, Ty::Prim(Prim::Int)) => {
+                Instruction::BitwiseOr(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::XorB, Ty::Prim(Prim::Int)) => {
+                Instruction::BitwiseXor(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Lt, Ty::Prim(Prim::Int)) => {
+                Instruction::Icmp(ConditionCode::Slt, lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Lte, Ty::Prim(Prim::Int)) => {
+                Instruction::Icmp(ConditionCode::Sle, lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Gt, Ty::Prim(Prim::Int)) => {
+                Instruction::Icmp(ConditionCode::Sgt, lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Gte, Ty::Prim(Prim::Int)) => {
+                Instruction::Icmp(ConditionCode::Sge, lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Add, Ty::Prim(Prim::Double)) => {
+                Instruction::Fadd(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Sub, Ty::Prim(Prim::Double)) => {
+                Instruction::Fsub(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Mul, Ty::Prim(Prim::Double)) => {
+                Instruction::Fmul(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Div, Ty::Prim(Prim::Double)) => {
+                Instruction::Fdiv(lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Eq, Ty::Prim(Prim::Double)) => Instruction::Fcmp(
+                FcmpConditionCode::OrderedAndEqual,
+                lhs_operand,
+                rhs_operand,
+                variable,
+            ),
+            (BinOp::Neq, Ty::Prim(Prim::Double)) => Instruction::Fcmp(
+                FcmpConditionCode::UnorderedOrNotEqual,
+                lhs_operand,
+                rhs_operand,
+                variable,
+            ),
+            (BinOp::Lt, Ty::Prim(Prim::Double)) => Instruction::Fcmp(
+                FcmpConditionCode::OrderedAndLessThan,
+                lhs_operand,
+                rhs_operand,
+                variable,
+            ),
+            (BinOp::Lte, Ty::Prim(Prim::Double)) => Instruction::Fcmp(
+                FcmpConditionCode::OrderedAndLessThanOrEqual,
+                lhs_operand,
+                rhs_operand,
+                variable,
+            ),
+            (BinOp::Gt, Ty::Prim(Prim::Double)) => Instruction::Fcmp(
+                FcmpConditionCode::OrderedAndGreaterThan,
+                lhs_operand,
+                rhs_operand,
+                variable,
+            ),
+            (BinOp::Gte, Ty::Prim(Prim::Double)) => Instruction::Fcmp(
+                FcmpConditionCode::OrderedAndGreaterThanOrEqual,
+                lhs_operand,
+                rhs_operand,
+                variable,
+            ),
+            (BinOp::Eq, _) => {
+                Instruction::Icmp(ConditionCode::Eq, lhs_operand, rhs_operand, variable)
+            }
+            (BinOp::Neq, _) => {
+                Instruction::Icmp(ConditionCode::Ne, lhs_operand, rhs_operand, variable)
+            }
             _ => {
                 return Err(Error::Unimplemented(
                     format!("BinOp Expr ({bin_op:?})"),
-                    bin_op_expr.span,
+                    self.to_global_span(span),
                 ))
             }
         };
@@ -452,11 +983,76 @@ impl<'a> PartialEvaluator<'a> {
         Ok(value)
     }
 
+    fn eval_expr_un_op(
+        &mut self,
+        un_op_expr_id: ExprId,
+        un_op: UnOp,
+        operand_expr_id: ExprId,
+    ) -> Result<Value, Error> {
+        // Visit the operand expression to get its value.
+        let maybe_operand_value = self.try_eval_expr(operand_expr_id);
+        let Ok(operand_value) = maybe_operand_value else {
+            let operand_expr = self.get_expr(operand_expr_id);
+            let error = Error::FailedToEvaluateUnaryExpressionOperand(
+                self.to_global_span(operand_expr.span),
+            );
+            return Err(error);
+        };
+
+        // The unary plus operation leaves the value unchanged.
+        if un_op == UnOp::Pos {
+            return Ok(operand_value);
+        }
+
+        // Only operations on primitive types have corresponding instructions.
+        let operand_ty = &self.get_expr(operand_expr_id).ty;
+        let un_op_expr = self.get_expr(un_op_expr_id);
+        if !matches!(operand_ty, Ty::Prim(Prim::Bool | Prim::Double | Prim::Int)) {
+            return Err(Error::Unimplemented(
+                format!("UnOp Expr ({un_op:?})"),
+                self.to_global_span(un_op_expr.span),
+            ));
+        }
+
+        // Create a variable to store the result of the expression.
+        let operand = map_eval_value_to_typed_rir_operand(&operand_value, operand_ty);
+        let variable_id = self.resource_manager.next_var();
+        let variable_ty = map_fir_type_to_rir_type(&un_op_expr.ty);
+        let variable = rir::Variable {
+            variable_id,
+            ty: variable_ty,
+        };
+
+        // Create the unary operation instruction and add it to the current block. Negation is expressed in terms of
+        // the binary instructions.
+        let instruction = match (un_op, operand_ty) {
+            (UnOp::NotL, _) => Instruction::LogicalNot(operand, variable),
+            (UnOp::NotB, Ty::Prim(Prim::Int)) => Instruction::BitwiseNot(operand, variable),
+            (UnOp::Neg, Ty::Prim(Prim::Int)) => {
+                Instruction::Sub(Operand::Literal(Literal::Integer(0)), operand, variable)
+            }
+            (UnOp::Neg, Ty::Prim(Prim::Double)) => {
+                Instruction::Fmul(operand, Operand::Literal(Literal::Double(-1.0)), variable)
+            }
+            _ => {
+                return Err(Error::Unimplemented(
+                    format!("UnOp Expr ({un_op:?})"),
+                    self.to_global_span(un_op_expr.span),
+                ))
+            }
+        };
+        self.get_current_block_mut().0.push(instruction);
+
+        // Return the variable as a value.
+        let value = Value::Var(Var(variable_id.into()));
+        Ok(value)
+    }
+
     fn eval_expr_block(&mut self, block_id: BlockId) -> Result<Value, Error> {
         let maybe_block_value = self.try_eval_block(block_id);
         maybe_block_value.map_err(|()| {
             let block = self.get_block(block_id);
-            Error::FailedToEvaluateBlockExpression(block.span)
+            Error::FailedToEvaluateBlockExpression(self.to_global_span(block.span))
         })
     }
 
@@ -469,14 +1065,16 @@ impl<'a> PartialEvaluator<'a> {
         let maybe_callable_value = self.try_eval_expr(callee_expr_id);
         let Ok(callable_value) = maybe_callable_value else {
             let callee_expr = self.get_expr(callee_expr_id);
-            let error = Error::FailedToEvaluateCalleeExpression(callee_expr.span);
+            let error =
+                Error::FailedToEvaluateCalleeExpression(self.to_global_span(callee_expr.span));
             return Err(error);
         };
 
         let maybe_args_value = self.try_eval_expr(args_expr_id);
         let Ok(args_value) = maybe_args_value else {
             let args_expr = self.get_expr(args_expr_id);
-            let error = Error::FailedToEvaluateCallArgsExpression(args_expr.span);
+            let error =
+                Error::FailedToEvaluateCallArgsExpression(self.to_global_span(args_expr.span));
             return Err(error);
         };
 
@@ -487,18 +1085,19 @@ impl<'a> PartialEvaluator<'a> {
             .get_global(store_item_id)
             .expect("global not present");
         let Global::Callable(callable_decl) = global else {
-            // Instruction generation for UDTs is not supported.
-            panic!("global is not a callable");
+            // UDTs are represented by their underlying tuple, so constructing one just results in the arguments value.
+            return Ok(args_value);
         };
 
         // We generate instructions differently depending on whether we are calling an intrinsic or a specialization
         // with an implementation.
         match &callable_decl.implementation {
-            CallableImpl::Intrinsic => {
-                let value =
-                    self.eval_expr_call_to_intrinsic(store_item_id, callable_decl, args_value);
-                Ok(value)
-            }
+            CallableImpl::Intrinsic => self.eval_expr_call_to_intrinsic(
+                store_item_id,
+                callable_decl,
+                args_value,
+                callee_expr_id,
+            ),
             CallableImpl::Spec(spec_impl) => self.eval_expr_call_to_spec(
                 store_item_id,
                 functor_app,
@@ -514,9 +1113,10 @@ impl<'a> PartialEvaluator<'a> {
         store_item_id: StoreItemId,
         callable_decl: &CallableDecl,
         args_value: Value,
-    ) -> Value {
+        callee_expr_id: ExprId,
+    ) -> Result<Value, Error> {
         // There are a few special cases regarding intrinsic callables. Identify them and handle them properly.
-        match callable_decl.name.name.as_ref() {
+        let value = match callable_decl.name.name.as_ref() {
             // Qubit allocations and measurements have special handling.
             "__quantum__rt__qubit_allocate" => self.allocate_qubit(),
             "__quantum__rt__qubit_release" => self.release_qubit(args_value),
@@ -526,9 +1126,21 @@ impl<'a> PartialEvaluator<'a> {
             }
             // The following operations should be conditionally compiled out for all targets for which QIR generation is
             // supported.
-            "CheckZero" | "DrawRandomInt" | "DrawRandomDouble" => panic!(
-                "`{}` is not a supported by partial evaluation",
-                callable_decl.name.name
+            "CheckZero" | "DrawRandomInt" | "DrawRandomDouble" => {
+                let callee_expr = self.get_expr(callee_expr_id);
+                return Err(Error::UnsupportedIntrinsic(
+                    callable_decl.name.name.to_string(),
+                    self.to_global_span(callee_expr.span),
+                ));
+            }
+            // Arrays are always statically sized, so their length is known even if they are not classical, such as
+            // the controls of a controlled specialization.
+            "Length" => Value::Int(
+                args_value
+                    .unwrap_array()
+                    .len()
+                    .try_into()
+                    .expect("array length should fit into an integer"),
             ),
             // The following intrinsic operations and functions are no-ops.
             "BeginEstimateCaching" => Value::Bool(true),
@@ -536,8 +1148,14 @@ impl<'a> PartialEvaluator<'a> {
             | "AccountForEstimatesInternal"
             | "BeginRepeatEstimatesInternal"
             | "EndRepeatEstimatesInternal" => Value::unit(),
-            _ => self.eval_expr_call_to_intrinsic_qis(store_item_id, callable_decl, args_value),
-        }
+            _ => self.eval_expr_call_to_intrinsic_qis(
+                store_item_id,
+                callable_decl,
+                args_value,
+                callee_expr_id,
+            )?,
+        };
+        Ok(value)
     }
 
     fn eval_expr_call_to_intrinsic_qis(
@@ -545,12 +1163,25 @@ impl<'a> PartialEvaluator<'a> {
         store_item_id: StoreItemId,
         callable_decl: &CallableDecl,
         args_value: Value,
-    ) -> Value {
-        // Intrinsic callables that make it to this point are expected to be unitary.
-        assert_eq!(callable_decl.output, Ty::UNIT);
+        callee_expr_id: ExprId,
+    ) -> Result<Value, Error> {
+        // Intrinsic callables that make it to this point are expected to be unitary and to only take types that can
+        // be represented in RIR.
+        let callable = if callable_decl.output == Ty::UNIT {
+            self.create_intrinsic_callable(store_item_id, callable_decl)
+        } else {
+            None
+        };
+        let Some(callable) = callable else {
+            let callee_expr = self.get_expr(callee_expr_id);
+            return Err(Error::UnsupportedIntrinsic(
+                callable_decl.name.name.to_string(),
+                self.to_global_span(callee_expr.span),
+            ));
+        };
 
         // Check if the callable is already in the program, and if not add it.
-        let callable = self.create_intrinsic_callable(store_item_id, callable_decl);
+        let input_type = callable.input_type.clone();
         let callable_id = self.get_or_insert_callable(callable);
 
         // Resove the call arguments, create the call instruction and insert it to the current block.
@@ -560,13 +1191,14 @@ impl<'a> PartialEvaluator<'a> {
         );
         let args_operands = args
             .into_iter()
-            .map(|arg| map_eval_value_to_rir_operand(&arg.into_value()))
+            .zip(input_type)
+            .map(|(arg, ty)| map_eval_value_to_rir_operand_with_ty(&arg.into_value(), ty))
             .collect();
 
         let instruction = Instruction::Call(callable_id, args_operands, None);
         let current_block = self.get_current_block_mut();
         current_block.0.push(instruction);
-        Value::unit()
+        Ok(Value::unit())
     }
 
     fn eval_expr_call_to_spec(
@@ -579,13 +1211,39 @@ impl<'a> PartialEvaluator<'a> {
     ) -> Result<Value, Error> {
         let spec_decl = get_spec_decl(spec_impl, functor_app);
 
+        // The arguments of a controlled specialization are nested in one tuple of controls and remaining arguments
+        // per controlled functor, and the controls of all of them are bound to the specialization input together.
+        let mut args_value = args_value;
+        let mut ctls = Vec::new();
+        if spec_decl.input.is_some() {
+            for _ in 0..functor_app.controlled {
+                let [c, rest] = &*args_value.unwrap_tuple() else {
+                    panic!("controlled arguments should be a tuple of arity 2");
+                };
+                ctls.extend_from_slice(&c.clone().unwrap_array());
+                args_value = rest.clone();
+            }
+        }
+
         // Create new call scope.
         let args = self.resolve_args((global_callable_id.package, args_pat).into(), args_value);
-        let call_scope = Scope::new(
+        let mut call_scope = Scope::new(
             global_callable_id.package,
             Some((global_callable_id.item, functor_app)),
             args,
         );
+        // The controls are not a parameter of the callable, so they are bound as a local of the scope instead.
+        if let Some(spec_pat) = spec_decl.input {
+            let ctls_args = self.resolve_args(
+                (global_callable_id.package, spec_pat).into(),
+                Value::Array(ctls.into()),
+            );
+            for arg in ctls_args {
+                if let Arg::Var(local_var_id, var) = arg {
+                    call_scope.env.bind_variable_in_top_frame(local_var_id, var);
+                }
+            }
+        }
         self.eval_context.push_scope(call_scope);
         self.visit_block(spec_decl.block);
         let popped_scope = self.eval_context.pop_scope();
@@ -616,7 +1274,10 @@ impl<'a> PartialEvaluator<'a> {
             };
             Err(Error::FailedToEvaluateCallable(
                 callable_decl.name.name.to_string(),
-                callable_decl.name.span,
+                PackageSpan {
+                    package: map_fir_package_to_hir(global_callable_id.package),
+                    span: callable_decl.name.span,
+                },
             ))
         }
     }
@@ -632,7 +1293,9 @@ impl<'a> PartialEvaluator<'a> {
         let maybe_condition_value = self.try_eval_expr(condition_expr_id);
         let Ok(condition_value) = maybe_condition_value else {
             let condition_expr = self.get_expr(condition_expr_id);
-            let error = Error::FailedToEvaluateConditionExpression(condition_expr.span);
+            let error = Error::FailedToEvaluateConditionExpression(
+                self.to_global_span(condition_expr.span),
+            );
             return Err(error);
         };
 
@@ -647,7 +1310,25 @@ impl<'a> PartialEvaluator<'a> {
         }
 
         // At this point the condition value is not classical, so we need to generate a branching instruction.
-        // First, we pop the current block node and generate a new one which the new branches will jump to when their
+        // Locals assigned in either branch have a value that depends on the branch taken, so they need to hold their
+        // value in a variable.
+        let mut branch_expr_ids = vec![body_expr_id];
+        branch_expr_ids.extend(otherwise_expr_id);
+        self.promote_assigned_locals(&branch_expr_ids)?;
+
+        // Since the if expression can represent a dynamic value, create variables to store it, one for each of the
+        // scalar elements of its type. Unit if expressions do not need any.
+        let if_expr = self.get_expr(if_expr_id);
+        let if_expr_ty = self.resolve_pure_ty(&if_expr.ty);
+        let mut if_expr_vars = Vec::new();
+        let Some(if_expr_value) = self.create_vars(&if_expr_ty, None, &mut if_expr_vars) else {
+            return Err(Error::Unimplemented(
+                format!("dynamic if expression of type {}", if_expr.ty),
+                self.to_global_span(if_expr.span),
+            ));
+        };
+
+        // Then, we pop the current block node and generate a new one which the new branches will jump to when their
         // instructions end.
         let current_block_node = self.eval_context.pop_block_node();
         let continuation_block_node_id = self.create_program_block();
@@ -657,31 +1338,13 @@ impl<'a> PartialEvaluator<'a> {
         };
         self.eval_context.push_block_node(continuation_block_node);
 
-        // Since the if expression can represent a dynamic value, create a variable to store it if the expression is
-        // non-unit.
-        let if_expr = self.get_expr(if_expr_id);
-        let maybe_if_expr_var = if if_expr.ty == Ty::UNIT {
-            None
-        } else {
-            let variable_id = self.resource_manager.next_var();
-            let variable_ty = map_fir_type_to_rir_type(&if_expr.ty);
-            Some(rir::Variable {
-                variable_id,
-                ty: variable_ty,
-            })
-        };
-
         // Evaluate the body expression.
         let if_true_block_id =
-            self.eval_expr_if_branch(body_expr_id, continuation_block_node_id, maybe_if_expr_var)?;
+            self.eval_expr_if_branch(body_expr_id, continuation_block_node_id, &if_expr_vars)?;
 
         // Evaluate the otherwise expression (if any), and determine the block to branch to if the condition is false.
         let if_false_block_id = if let Some(otherwise_expr_id) = otherwise_expr_id {
-            self.eval_expr_if_branch(
-                otherwise_expr_id,
-                continuation_block_node_id,
-                maybe_if_expr_var,
-            )?
+            self.eval_expr_if_branch(otherwise_expr_id, continuation_block_node_id, &if_expr_vars)?
         } else {
             continuation_block_node_id
         };
@@ -699,11 +1362,6 @@ impl<'a> PartialEvaluator<'a> {
             .push(branch_ins);
 
         // Return the value of the if expression.
-        let if_expr_value = if let Some(if_expr_var) = maybe_if_expr_var {
-            Value::Var(Var(if_expr_var.variable_id.into()))
-        } else {
-            Value::unit()
-        };
         Ok(if_expr_value)
     }
 
@@ -711,7 +1369,7 @@ impl<'a> PartialEvaluator<'a> {
         &mut self,
         branch_body_expr_id: ExprId,
         continuation_block_id: rir::BlockId,
-        if_expr_var: Option<rir::Variable>,
+        if_expr_vars: &[rir::Variable],
     ) -> Result<rir::BlockId, Error> {
         // Create the block node that corresponds to the branch body and push it as the active one.
         let block_node_id = self.create_program_block();
@@ -725,16 +1383,16 @@ impl<'a> PartialEvaluator<'a> {
         let maybe_body_value = self.try_eval_expr(branch_body_expr_id);
         let Ok(body_value) = maybe_body_value else {
             let body_body_expr = self.get_expr(branch_body_expr_id);
-            let error = Error::FailedToEvaluateIfExpressionBranchBlock(body_body_expr.span);
+            let error = Error::FailedToEvaluateIfExpressionBranchBlock(
+                self.to_global_span(body_body_expr.span),
+            );
             return Err(error);
         };
 
-        // If there is a variable to save the value of the if expression to, add a store instruction.
-        if let Some(if_expr_var) = if_expr_var {
-            let body_operand = map_eval_value_to_rir_operand(&body_value);
-            let store_ins = Instruction::Store(body_operand, if_expr_var);
-            self.get_current_block_mut().0.push(store_ins);
-        }
+        // Store the value of the branch in the variables of the if expression.
+        let store_instrs = store_value_in_vars(&body_value, if_expr_vars)
+            .expect("branch value should match the type of the if expression");
+        self.get_current_block_mut().0.extend(store_instrs);
 
         // Finally, jump to the continuation block and pop the current block node.
         let jump_ins = Instruction::Jump(continuation_block_id);
@@ -753,13 +1411,15 @@ impl<'a> PartialEvaluator<'a> {
             let maybe_body_value = self.try_eval_expr(body_expr_id);
             maybe_body_value.map_err(|()| {
                 let body_expr = self.get_expr(body_expr_id);
-                Error::FailedToEvaluateIfExpressionBranchBlock(body_expr.span)
+                Error::FailedToEvaluateIfExpressionBranchBlock(self.to_global_span(body_expr.span))
             })
         } else if let Some(otherwise_expr_id) = otherwise_expr_id {
             let maybe_otherwise_value = self.try_eval_expr(otherwise_expr_id);
             maybe_otherwise_value.map_err(|()| {
                 let otherwise_expr = self.get_expr(otherwise_expr_id);
-                Error::FailedToEvaluateIfExpressionBranchBlock(otherwise_expr.span)
+                Error::FailedToEvaluateIfExpressionBranchBlock(
+                    self.to_global_span(otherwise_expr.span),
+                )
             })
         } else {
             // A the classical condition evaluated to false, but there is not otherwise block so there is nothing to
@@ -775,7 +1435,9 @@ impl<'a> PartialEvaluator<'a> {
             let maybe_value = self.try_eval_expr(*expr_id);
             let Ok(value) = maybe_value else {
                 let expr = self.get_expr(*expr_id);
-                return Err(Error::FailedToEvaluateArrayElementExpression(expr.span));
+                return Err(Error::FailedToEvaluateArrayElementExpression(
+                    self.to_global_span(expr.span),
+                ));
             };
             values.push(value);
         }
@@ -788,7 +1450,9 @@ impl<'a> PartialEvaluator<'a> {
             let maybe_value = self.try_eval_expr(*expr_id);
             let Ok(value) = maybe_value else {
                 let expr = self.get_expr(*expr_id);
-                return Err(Error::FailedToEvaluateTupleElementExpression(expr.span));
+                return Err(Error::FailedToEvaluateTupleElementExpression(
+                    self.to_global_span(expr.span),
+                ));
             };
             values.push(value);
         }
@@ -815,14 +1479,11 @@ impl<'a> PartialEvaluator<'a> {
 
     fn eval_expr_while(
         &mut self,
+        while_expr_id: ExprId,
         condition_expr_id: ExprId,
         body_block_id: BlockId,
     ) -> Result<Value, Error> {
         // Verify assumptions.
-        assert!(
-            self.is_classical_expr(condition_expr_id),
-            "loop conditions must be purely classical"
-        );
         let body_block = self.get_block(body_block_id);
         assert_eq!(
             body_block.ty,
@@ -830,12 +1491,21 @@ impl<'a> PartialEvaluator<'a> {
             "the type of a loop block is expected to be Unit"
         );
 
+        // If the condition is not classical, the loop cannot be unrolled and we need to generate a loop in the program.
+        if !self.is_classical_expr(condition_expr_id) {
+            return self.eval_expr_while_with_dynamic_condition(
+                while_expr_id,
+                condition_expr_id,
+                body_block_id,
+            );
+        }
+
         // Evaluate the block until the loop condition is false.
         while self.eval_expr_while_condition(condition_expr_id)? {
             let maybe_block_value = self.try_eval_block(body_block_id);
             if maybe_block_value.is_err() {
                 let block = self.get_block(body_block_id);
-                let error = Error::FailedToEvaluateLoopBody(block.span);
+                let error = Error::FailedToEvaluateLoopBody(self.to_global_span(block.span));
                 return Err(error);
             }
         }
@@ -843,13 +1513,85 @@ impl<'a> PartialEvaluator<'a> {
         Ok(Value::unit())
     }
 
+    fn eval_expr_while_with_dynamic_condition(
+        &mut self,
+        while_expr_id: ExprId,
+        condition_expr_id: ExprId,
+        body_block_id: BlockId,
+    ) -> Result<Value, Error> {
+        // Locals assigned within the loop take a different value on each iteration, so they need to hold their value
+        // in a variable.
+        self.promote_assigned_locals(&[while_expr_id])?;
+
+        // The loop is made of a header block that evaluates the condition and branches either to the body, which jumps
+        // back to the header, or to a continuation block that the instructions following the loop are added to.
+        let current_block_node = self.eval_context.pop_block_node();
+        let header_block_id = self.create_program_block();
+        let continuation_block_id = self.create_program_block();
+        self.get_program_block_mut(current_block_node.id)
+            .0
+            .push(Instruction::Jump(header_block_id));
+        let continuation_block_node = BlockNode {
+            id: continuation_block_id,
+            next: current_block_node.next,
+        };
+        self.eval_context.push_block_node(continuation_block_node);
+
+        // Evaluate the condition expression. Since it might generate blocks of its own, the branch instruction goes to
+        // whichever block is active after evaluating it.
+        let header_block_node = BlockNode {
+            id: header_block_id,
+            next: Some(continuation_block_id),
+        };
+        self.eval_context.push_block_node(header_block_node);
+        let maybe_condition_value = self.try_eval_expr(condition_expr_id);
+        let Ok(condition_value) = maybe_condition_value else {
+            let condition_expr = self.get_expr(condition_expr_id);
+            let error =
+                Error::FailedToEvaluateLoopCondition(self.to_global_span(condition_expr.span));
+            return Err(error);
+        };
+        let condition_block_node = self.eval_context.pop_block_node();
+
+        // Evaluate the body, which jumps back to the header block when its instructions end.
+        let body_block_node_id = self.create_program_block();
+        let body_block_node = BlockNode {
+            id: body_block_node_id,
+            next: Some(header_block_id),
+        };
+        self.eval_context.push_block_node(body_block_node);
+        if self.try_eval_block(body_block_id).is_err() {
+            let block = self.get_block(body_block_id);
+            let error = Error::FailedToEvaluateLoopBody(self.to_global_span(block.span));
+            return Err(error);
+        }
+        self.get_current_block_mut()
+            .0
+            .push(Instruction::Jump(header_block_id));
+        let _ = self.eval_context.pop_block_node();
+
+        // Finally, we insert the branch instruction.
+        let condition_value_var = condition_value.unwrap_var();
+        let condition_rir_var = rir::Variable {
+            variable_id: condition_value_var.0.into(),
+            ty: rir::Ty::Boolean,
+        };
+        let branch_ins =
+            Instruction::Branch(condition_rir_var, body_block_node_id, continuation_block_id);
+        self.get_program_block_mut(condition_block_node.id)
+            .0
+            .push(branch_ins);
+        Ok(Value::unit())
+    }
+
     fn eval_expr_while_condition(&mut self, condition_expr_id: ExprId) -> Result<bool, Error> {
         let maybe_condition_expr_value = self.try_eval_expr(condition_expr_id);
         if let Ok(condition_expr_value) = maybe_condition_expr_value {
             Ok(condition_expr_value.unwrap_bool())
         } else {
             let condition_expr = self.get_expr(condition_expr_id);
-            let error = Error::FailedToEvaluateLoopCondition(condition_expr.span);
+            let error =
+                Error::FailedToEvaluateLoopCondition(self.to_global_span(condition_expr.span));
             Err(error)
         }
     }
@@ -890,6 +1632,20 @@ impl<'a> PartialEvaluator<'a> {
         self.eval_context.get_current_scope().package_id
     }
 
+    fn get_entry_expr_span(&self) -> PackageSpan {
+        PackageSpan {
+            package: map_fir_package_to_hir(self.entry.expr.package),
+            span: self.package_store.get_expr(self.entry.expr).span,
+        }
+    }
+
+    fn to_global_span(&self, span: Span) -> PackageSpan {
+        PackageSpan {
+            package: map_fir_package_to_hir(self.get_current_package_id()),
+            span,
+        }
+    }
+
     fn get_current_scope_exec_graph(&self) -> &Rc<[ExecGraphNode]> {
         if let Some(spec_decl) = self.get_current_scope_spec_decl() {
             &spec_decl.exec_graph
@@ -1029,6 +1785,225 @@ impl<'a> PartialEvaluator<'a> {
         }
     }
 
+    fn promote_assigned_locals(&mut self, expr_ids: &[ExprId]) -> Result<(), Error> {
+        let package = self.package_store.get(self.get_current_package_id());
+        for assigned_local in collect_assigned_locals(package, expr_ids) {
+            // Locals that already hold their value in a variable need no promotion, and locals declared within the
+            // expressions do not exist yet.
+            let scope = self.eval_context.get_current_scope();
+            if scope.find_mutable_vars(assigned_local.id).is_some() {
+                continue;
+            }
+            let Some(value) = scope.find_local_var_value(assigned_local.id).cloned() else {
+                continue;
+            };
+
+            // Store the current value of the local in new variables, one for each of its scalar elements, which is
+            // what the local resolves to from now on.
+            let ty = self.resolve_pure_ty(&assigned_local.ty);
+            let mut vars = Vec::new();
+            let Some(vars_value) = self.create_vars(&ty, Some(&value), &mut vars) else {
+                return Err(Error::Unimplemented(
+                    format!("dynamic assignment of {}", assigned_local.ty),
+                    self.to_global_span(assigned_local.span),
+                ));
+            };
+            let store_instrs = store_value_in_vars(&value, &vars)
+                .expect("value should match the variables created from it");
+            self.get_current_block_mut().0.extend(store_instrs);
+            let scope = self.eval_context.get_current_scope_mut();
+            scope.insert_mutable_vars(assigned_local.id, vars);
+            scope.insert_local_var_value(assigned_local.id, vars_value);
+        }
+        Ok(())
+    }
+
+    fn resolve_pure_ty(&self, ty: &Ty) -> Ty {
+        self.resolve_pure_ty_in_package(ty, self.get_current_package_id())
+    }
+
+    fn resolve_pure_ty_in_package(&self, ty: &Ty, package_id: PackageId) -> Ty {
+        match ty {
+            Ty::Array(item_ty) => Ty::Array(Box::new(
+                self.resolve_pure_ty_in_package(item_ty, package_id),
+            )),
+            Ty::Tuple(item_tys) => Ty::Tuple(
+                item_tys
+                    .iter()
+                    .map(|item_ty| self.resolve_pure_ty_in_package(item_ty, package_id))
+                    .collect(),
+            ),
+            Ty::Udt(Res::Item(item_id)) => {
+                // Types within the UDT definition are relative to the package the UDT is declared in.
+                let udt_package_id = item_id.package.unwrap_or(package_id);
+                let item = self
+                    .package_store
+                    .get(udt_package_id)
+                    .get_item(item_id.item);
+                let ItemKind::Ty(_, udt) = &item.kind else {
+                    panic!("UDT item is not a type");
+                };
+                self.resolve_pure_ty_in_package(&udt.get_pure_ty(), udt_package_id)
+            }
+            _ => ty.clone(),
+        }
+    }
+
+    /// Creates variables for each of the scalar elements of a (pure) type, returning a value of that type made of the
+    /// variables. The shape of arrays is taken from the given value, since it is not part of the type.
+    fn create_vars(
+        &mut self,
+        ty: &Ty,
+        shape: Option<&Value>,
+        vars: &mut Vec<rir::Variable>,
+    ) -> Option<Value> {
+        match (ty, shape) {
+            (Ty::Prim(Prim::Bool | Prim::Double | Prim::Int), _) => {
+                let variable = rir::Variable {
+                    variable_id: self.resource_manager.next_var(),
+                    ty: map_fir_type_to_rir_type(ty),
+                };
+                vars.push(variable);
+                Some(Value::Var(Var(variable.variable_id.into())))
+            }
+            (Ty::Tuple(item_tys), None) => {
+                let values: Option<Vec<_>> = item_tys
+                    .iter()
+                    .map(|item_ty| self.create_vars(item_ty, None, vars))
+                    .collect();
+                Some(Value::Tuple(values?.into()))
+            }
+            (Ty::Tuple(item_tys), Some(Value::Tuple(items))) => {
+                let values: Option<Vec<_>> = item_tys
+                    .iter()
+                    .zip(items.iter())
+                    .map(|(item_ty, item)| self.create_vars(item_ty, Some(item), vars))
+                    .collect();
+                Some(Value::Tuple(values?.into()))
+            }
+            (Ty::Array(item_ty), Some(Value::Array(items))) => {
+                let values: Option<Vec<_>> = items
+                    .iter()
+                    .map(|item| self.create_vars(item_ty, Some(item), vars))
+                    .collect();
+                Some(Value::Array(values?.into()))
+            }
+            _ => None,
+        }
+    }
+
+    fn eval_select(
+        &mut self,
+        condition: rir::Variable,
+        true_value: &Value,
+        false_value: &Value,
+        ty: &Ty,
+        span: Span,
+    ) -> Result<Value, Error> {
+        // The selected value is held in variables that each alternative stores its value in.
+        let mut vars = Vec::new();
+        let maybe_selected_value = self.create_vars(ty, Some(true_value), &mut vars);
+        let maybe_true_instrs = store_value_in_vars(true_value, &vars);
+        let maybe_false_instrs = store_value_in_vars(false_value, &vars);
+        let (Some(selected_value), Some(true_instrs), Some(false_instrs)) =
+            (maybe_selected_value, maybe_true_instrs, maybe_false_instrs)
+        else {
+            return Err(Error::Unimplemented(
+                format!("dynamic selection of {ty}"),
+                self.to_global_span(span),
+            ));
+        };
+
+        // Branch to a block for each alternative, which then jumps to a new continuation block.
+        let current_block_node = self.eval_context.pop_block_node();
+        let true_block_id = self.create_program_block();
+        let false_block_id = self.create_program_block();
+        let continuation_block_id = self.create_program_block();
+        self.get_program_block_mut(current_block_node.id)
+            .0
+            .push(Instruction::Branch(
+                condition,
+                true_block_id,
+                false_block_id,
+            ));
+        for (block_id, instrs) in [(true_block_id, true_instrs), (false_block_id, false_instrs)] {
+            let block = self.get_program_block_mut(block_id);
+            block.0.extend(instrs);
+            block.0.push(Instruction::Jump(continuation_block_id));
+        }
+        let continuation_block_node = BlockNode {
+            id: continuation_block_id,
+            next: current_block_node.next,
+        };
+        self.eval_context.push_block_node(continuation_block_node);
+        Ok(selected_value)
+    }
+
+    fn eval_index_equals(&mut self, index_operand: Operand, index: usize) -> rir::Variable {
+        let variable = rir::Variable {
+            variable_id: self.resource_manager.next_var(),
+            ty: rir::Ty::Boolean,
+        };
+        let index = index
+            .try_into()
+            .expect("array index should fit into an i64");
+        self.get_current_block_mut().0.push(Instruction::Icmp(
+            ConditionCode::Eq,
+            index_operand,
+            Operand::Literal(Literal::Integer(index)),
+            variable,
+        ));
+        variable
+    }
+
+    fn update_bindings(&mut self, assignee_expr_id: ExprId, value: Value) -> Result<(), Error> {
+        let assignee_expr = self.get_expr(assignee_expr_id);
+        match &assignee_expr.kind {
+            ExprKind::Hole => {
+                // Nothing to update.
+            }
+            ExprKind::Var(Res::Local(local_var_id), _) => {
+                // Locals that hold their value in a variable are updated by storing the new value in it, while any
+                // other local is just bound to the new value.
+                let scope = self.eval_context.get_current_scope();
+                match scope
+                    .find_mutable_vars(*local_var_id)
+                    .map(|vars| store_value_in_vars(&value, vars))
+                {
+                    Some(Some(store_instrs)) => self.get_current_block_mut().0.extend(store_instrs),
+                    Some(None) => {
+                        return Err(Error::Unimplemented(
+                            "dynamic assignment of an array with a different length".to_string(),
+                            self.to_global_span(assignee_expr.span),
+                        ))
+                    }
+                    None => self
+                        .eval_context
+                        .get_current_scope_mut()
+                        .insert_local_var_value(*local_var_id, value),
+                }
+            }
+            ExprKind::Tuple(assignee_exprs) => {
+                let values = value.unwrap_tuple();
+                assert_eq!(
+                    assignee_exprs.len(),
+                    values.len(),
+                    "assignee tuple and value tuple have different arity"
+                );
+                for (assignee_expr_id, value) in assignee_exprs.iter().zip(values.iter()) {
+                    self.update_bindings(*assignee_expr_id, value.clone())?;
+                }
+            }
+            _ => {
+                return Err(Error::Unimplemented(
+                    "Assignment Expr".to_string(),
+                    self.to_global_span(assignee_expr.span),
+                ))
+            }
+        }
+        Ok(())
+    }
+
     fn try_eval_block(&mut self, block_id: BlockId) -> Result<Value, ()> {
         self.visit_block(block_id);
         if self.errors.is_empty() {
@@ -1038,6 +2013,17 @@ impl<'a> PartialEvaluator<'a> {
         }
     }
 
+    /// Evaluates an expression that can be the target of an assignment or update. Local variables in such positions are
+    /// not necessarily part of the execution graph, so their value is read from the bindings directly.
+    fn try_eval_place_expr(&mut self, expr_id: ExprId) -> Result<Value, ()> {
+        let expr = self.get_expr(expr_id);
+        if let ExprKind::Var(res @ Res::Local(_), _) = &expr.kind {
+            Ok(self.eval_expr_var(res))
+        } else {
+            self.try_eval_expr(expr_id)
+        }
+    }
+
     fn try_eval_expr(&mut self, expr_id: ExprId) -> Result<Value, ()> {
         // Visit the expression, which will either populate the expression entry in the scope's value map or add an
         // error.
@@ -1053,16 +2039,18 @@ impl<'a> PartialEvaluator<'a> {
         &mut self,
         ret_val: Value,
         ty: &Ty,
-    ) -> Result<Vec<Instruction>, ()> {
+    ) -> Result<Vec<Instruction>, Error> {
         let mut instrs = Vec::new();
 
         match ret_val {
-            Value::Result(val::Result::Val(_)) => return Err(()),
+            Value::Result(val::Result::Val(_)) => {
+                return Err(Error::OutputResultLiteral(self.get_entry_expr_span()))
+            }
 
             Value::Array(vals) => self.record_array(ty, &mut instrs, &vals)?,
             Value::Tuple(vals) => self.record_tuple(ty, &mut instrs, &vals)?,
             Value::Result(res) => self.record_result(&mut instrs, res),
-            Value::Var(var) => self.record_variable(ty, &mut instrs, var),
+            Value::Var(var) => self.record_variable(ty, &mut instrs, var)?,
             Value::Bool(val) => self.record_bool(&mut instrs, val),
             Value::Int(val) => self.record_int(&mut instrs, val),
 
@@ -1073,7 +2061,12 @@ impl<'a> PartialEvaluator<'a> {
             | Value::Pauli(_)
             | Value::Qubit(_)
             | Value::Range(_)
-            | Value::String(_) => panic!("unsupported value type in output recording"),
+            | Value::String(_) => {
+                return Err(Error::UnsupportedOutputType(
+                    ty.to_string(),
+                    self.get_entry_expr_span(),
+                ))
+            }
         }
 
         Ok(instrs)
@@ -1103,11 +2096,21 @@ impl<'a> PartialEvaluator<'a> {
         ));
     }
 
-    fn record_variable(&mut self, ty: &Ty, instrs: &mut Vec<Instruction>, var: Var) {
+    fn record_variable(
+        &mut self,
+        ty: &Ty,
+        instrs: &mut Vec<Instruction>,
+        var: Var,
+    ) -> Result<(), Error> {
         let (record_callable_id, record_ty) = match ty {
             Ty::Prim(Prim::Bool) => (self.get_bool_record_callable(), rir::Ty::Boolean),
             Ty::Prim(Prim::Int) => (self.get_int_record_callable(), rir::Ty::Integer),
-            _ => panic!("unsupported variable type in output recording"),
+            _ => {
+                return Err(Error::UnsupportedOutputType(
+                    ty.to_string(),
+                    self.get_entry_expr_span(),
+                ))
+            }
         };
         instrs.push(Instruction::Call(
             record_callable_id,
@@ -1120,6 +2123,7 @@ impl<'a> PartialEvaluator<'a> {
             ],
             None,
         ));
+        Ok(())
     }
 
     fn record_result(&mut self, instrs: &mut Vec<Instruction>, res: val::Result) {
@@ -1143,7 +2147,7 @@ impl<'a> PartialEvaluator<'a> {
         ty: &Ty,
         instrs: &mut Vec<Instruction>,
         vals: &Rc<[Value]>,
-    ) -> Result<(), ()> {
+    ) -> Result<(), Error> {
         let Ty::Tuple(elem_tys) = ty else {
             panic!("expected tuple type for tuple value");
         };
@@ -1172,7 +2176,7 @@ impl<'a> PartialEvaluator<'a> {
         ty: &Ty,
         instrs: &mut Vec<Instruction>,
         vals: &Rc<Vec<Value>>,
-    ) -> Result<(), ()> {
+    ) -> Result<(), Error> {
         let Ty::Array(elem_ty) = ty else {
             panic!("expected array type for array value");
         };
@@ -1322,14 +2326,30 @@ impl<'a> Visitor<'a> for PartialEvaluator<'a> {
 
     fn visit_stmt(&mut self, stmt_id: StmtId) {
         // If the statement is classical, we can just evaluate it.
+        let store_stmt_id = StoreStmtId::from((self.get_current_package_id(), stmt_id));
+        let stmt = self.package_store.get_stmt(store_stmt_id);
         if self.is_classical_stmt(stmt_id) {
-            self.eval_classical_stmt(stmt_id);
+            match stmt.kind {
+                // The value of a classical trailing expression is the value of the block, so it is evaluated as an
+                // expression to keep track of it.
+                StmtKind::Expr(expr_id) => self.visit_expr(expr_id),
+                StmtKind::Semi(_) => {
+                    self.eval_classical_stmt(stmt_id);
+                    self.eval_context.get_current_scope_mut().clear_last_expr();
+                }
+                // Locals declared by classical statements are bound in the environment, which takes precedence over
+                // anything left from an earlier evaluation of the same statement, such as in a previous iteration of a
+                // loop.
+                StmtKind::Local(_, pat_id, _) => {
+                    self.eval_classical_stmt(stmt_id);
+                    self.unbind_pat(pat_id);
+                }
+                StmtKind::Item(_) => self.eval_classical_stmt(stmt_id),
+            }
             return;
         }
 
         // If the statement is not classical, we need to generate instructions for it.
-        let store_stmt_id = StoreStmtId::from((self.get_current_package_id(), stmt_id));
-        let stmt = self.package_store.get_stmt(store_stmt_id);
         match stmt.kind {
             StmtKind::Expr(expr_id) => {
                 self.visit_expr(expr_id);
@@ -1371,6 +2391,81 @@ fn get_spec_decl(spec_impl: &SpecImpl, functor_app: FunctorApp) -> &SpecDecl {
     }
 }
 
+fn flatten_value(value: &Value, scalars: &mut Vec<Value>) {
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| flatten_value(item, scalars)),
+        Value::Tuple(items) => items.iter().for_each(|item| flatten_value(item, scalars)),
+        _ => scalars.push(value.clone()),
+    }
+}
+
+fn follow_field_path(mut value: Value, path: &[usize]) -> Value {
+    for &index in path {
+        value = value.unwrap_tuple()[index].clone();
+    }
+    value
+}
+
+fn update_field_path(record: &Value, path: &[usize], replace: Value) -> Value {
+    let Some((&next_index, path)) = path.split_first() else {
+        return replace;
+    };
+    let Value::Tuple(items) = record else {
+        panic!("field path should only go through tuples");
+    };
+    let mut items = items.to_vec();
+    items[next_index] = update_field_path(&items[next_index], path, replace);
+    Value::Tuple(items.into())
+}
+
+/// Computes the value of a binary operation on integer or Boolean literals, or `None` if an operand is not a literal or
+/// the operation would fail, in which case an instruction is generated for it.
+fn fold_bin_op(bin_op: BinOp, lhs: &Value, rhs: &Value) -> Option<Value> {
+    match (lhs, rhs) {
+        (Value::Int(lhs), Value::Int(rhs)) => match bin_op {
+            BinOp::Add => lhs.checked_add(*rhs).map(Value::Int),
+            BinOp::Sub => lhs.checked_sub(*rhs).map(Value::Int),
+            BinOp::Mul => lhs.checked_mul(*rhs).map(Value::Int),
+            BinOp::Div => lhs.checked_div(*rhs).map(Value::Int),
+            BinOp::Mod => lhs.checked_rem(*rhs).map(Value::Int),
+            BinOp::Eq => Some(Value::Bool(lhs == rhs)),
+            BinOp::Neq => Some(Value::Bool(lhs != rhs)),
+            BinOp::Lt => Some(Value::Bool(lhs < rhs)),
+            BinOp::Lte => Some(Value::Bool(lhs <= rhs)),
+            BinOp::Gt => Some(Value::Bool(lhs > rhs)),
+            BinOp::Gte => Some(Value::Bool(lhs >= rhs)),
+            _ => None,
+        },
+        (Value::Bool(lhs), Value::Bool(rhs)) => match bin_op {
+            BinOp::Eq => Some(Value::Bool(lhs == rhs)),
+            BinOp::Neq => Some(Value::Bool(lhs != rhs)),
+            BinOp::AndL => Some(Value::Bool(*lhs && *rhs)),
+            BinOp::OrL => Some(Value::Bool(*lhs || *rhs)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Creates the instructions that store each of the scalar elements of a value in the corresponding variable, or `None`
+/// if the value does not have as many scalar elements as there are variables.
+fn store_value_in_vars(value: &Value, vars: &[rir::Variable]) -> Option<Vec<Instruction>> {
+    let mut scalars = Vec::new();
+    flatten_value(value, &mut scalars);
+    if scalars.len() != vars.len() {
+        return None;
+    }
+    // Scalars that are already held in their variable do not need to be stored again.
+    let instrs = scalars
+        .iter()
+        .zip(vars.iter())
+        .map(|(scalar, var)| (map_eval_value_to_rir_operand_with_ty(scalar, var.ty), *var))
+        .filter(|(operand, var)| *operand != Operand::Variable(*var))
+        .map(|(operand, var)| Instruction::Store(operand, var))
+        .collect();
+    Some(instrs)
+}
+
 fn map_eval_value_to_rir_operand(value: &Value) -> Operand {
     match value {
         Value::Bool(b) => Operand::Literal(Literal::Bool(*b)),
@@ -1391,9 +2486,32 @@ fn map_eval_value_to_rir_operand(value: &Value) -> Operand {
     }
 }
 
+fn map_eval_value_to_typed_rir_operand(value: &Value, ty: &Ty) -> Operand {
+    if let Value::Var(_) = value {
+        map_eval_value_to_rir_operand_with_ty(value, map_fir_type_to_rir_type(ty))
+    } else {
+        map_eval_value_to_rir_operand(value)
+    }
+}
+
+/// Maps a value to an operand, using the given type for dynamic values since variables do not carry their type.
+fn map_eval_value_to_rir_operand_with_ty(value: &Value, ty: rir::Ty) -> Operand {
+    match value {
+        Value::Var(var) => Operand::Variable(rir::Variable {
+            variable_id: var.0.into(),
+            ty,
+        }),
+        _ => map_eval_value_to_rir_operand(value),
+    }
+}
+
 fn map_fir_type_to_rir_type(ty: &Ty) -> rir::Ty {
+    try_map_fir_type_to_rir_type(ty).unwrap_or_else(|| panic!("{ty} is not a supported type"))
+}
+
+fn try_map_fir_type_to_rir_type(ty: &Ty) -> Option<rir::Ty> {
     let Ty::Prim(prim) = ty else {
-        panic!("only some primitive types are supported");
+        return None;
     };
 
     match prim {
@@ -1403,11 +2521,11 @@ fn map_fir_type_to_rir_type(ty: &Ty) -> rir::Ty {
         | Prim::RangeFrom
         | Prim::RangeFull
         | Prim::RangeTo
-        | Prim::String => panic!("{prim:?} is not a supported primitive type"),
-        Prim::Bool => rir::Ty::Boolean,
-        Prim::Double => rir::Ty::Double,
-        Prim::Int => rir::Ty::Integer,
-        Prim::Qubit => rir::Ty::Qubit,
-        Prim::Result => rir::Ty::Result,
+        | Prim::String => None,
+        Prim::Bool => Some(rir::Ty::Boolean),
+        Prim::Double => Some(rir::Ty::Double),
+        Prim::Int => Some(rir::Ty::Integer),
+        Prim::Qubit => Some(rir::Ty::Qubit),
+        Prim::Result => Some(rir::Ty::Result),
     }
 }
@@ -0,0 +1,203 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+pub mod test_utils;
+
+use expect_test::expect;
+use indoc::indoc;
+use test_utils::{assert_blocks, compile_and_partially_evaluate, get_partial_evaluation_error};
+
+#[test]
+fn qubit_array_element_at_classical_index() {
+    let program = compile_and_partially_evaluate(indoc! {
+        r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Unit {
+                use qs = Qubit[2];
+                X(qs[1]);
+            }
+        }
+        "#,
+    });
+    assert_blocks(
+        &program,
+        &expect![[r#"
+        Blocks:
+        Block 0:Block:
+            Call id(1), args( Qubit(1), )
+            Call id(2), args( Integer(0), Pointer, )
+            Return"#]],
+    );
+}
+
+#[test]
+fn array_element_with_dynamic_value_at_classical_index() {
+    let program = compile_and_partially_evaluate(indoc! {
+        r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Bool {
+                use q = Qubit();
+                let results = [MResetZ(q) == One, true];
+                results[0]
+            }
+        }
+        "#,
+    });
+    assert_blocks(
+        &program,
+        &expect![[r#"
+        Blocks:
+        Block 0:Block:
+            Call id(1), args( Qubit(0), Result(0), )
+            Variable(0, Boolean) = Call id(2), args( Result(0), )
+            Variable(1, Boolean) = Icmp Eq, Variable(0, Boolean), Bool(true)
+            Call id(3), args( Variable(1, Boolean), Pointer, )
+            Return"#]],
+    );
+}
+
+#[test]
+fn array_element_at_dynamic_index() {
+    let program = compile_and_partially_evaluate(indoc! {
+        r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                use q = Qubit();
+                let index = MResetZ(q) == One ? 1 | 0;
+                let values = [10, 20, 30];
+                values[index]
+            }
+        }
+        "#,
+    });
+    assert_blocks(
+        &program,
+        &expect![[r#"
+        Blocks:
+        Block 0:Block:
+            Call id(1), args( Qubit(0), Result(0), )
+            Variable(0, Boolean) = Call id(2), args( Result(0), )
+            Variable(1, Boolean) = Icmp Eq, Variable(0, Boolean), Bool(true)
+            Branch Variable(1, Boolean), 2, 3
+        Block 1:Block:
+            Variable(3, Boolean) = Icmp Eq, Variable(2, Integer), Integer(0)
+            Branch Variable(3, Boolean), 4, 5
+        Block 2:Block:
+            Variable(2, Integer) = Store Integer(1)
+            Jump(1)
+        Block 3:Block:
+            Variable(2, Integer) = Store Integer(0)
+            Jump(1)
+        Block 4:Block:
+            Variable(4, Integer) = Store Integer(10)
+            Jump(6)
+        Block 5:Block:
+            Variable(4, Integer) = Store Integer(30)
+            Jump(6)
+        Block 6:Block:
+            Variable(5, Boolean) = Icmp Eq, Variable(2, Integer), Integer(1)
+            Branch Variable(5, Boolean), 7, 8
+        Block 7:Block:
+            Variable(6, Integer) = Store Integer(20)
+            Jump(9)
+        Block 8:Block:
+            Variable(6, Integer) = Store Variable(4, Integer)
+            Jump(9)
+        Block 9:Block:
+            Call id(3), args( Variable(6, Integer), Pointer, )
+            Return"#]],
+    );
+}
+
+#[test]
+fn array_updated_at_dynamic_index() {
+    let program = compile_and_partially_evaluate(indoc! {
+        r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Int[] {
+                use q = Qubit();
+                let index = MResetZ(q) == One ? 1 | 0;
+                mutable values = [0, 0];
+                set values w/= index <- 5;
+                values
+            }
+        }
+        "#,
+    });
+    assert_blocks(
+        &program,
+        &expect![[r#"
+            Blocks:
+            Block 0:Block:
+                Call id(1), args( Qubit(0), Result(0), )
+                Variable(0, Boolean) = Call id(2), args( Result(0), )
+                Variable(1, Boolean) = Icmp Eq, Variable(0, Boolean), Bool(true)
+                Branch Variable(1, Boolean), 2, 3
+            Block 1:Block:
+                Variable(3, Boolean) = Icmp Eq, Variable(2, Integer), Integer(0)
+                Branch Variable(3, Boolean), 4, 5
+            Block 2:Block:
+                Variable(2, Integer) = Store Integer(1)
+                Jump(1)
+            Block 3:Block:
+                Variable(2, Integer) = Store Integer(0)
+                Jump(1)
+            Block 4:Block:
+                Variable(4, Integer) = Store Integer(5)
+                Jump(6)
+            Block 5:Block:
+                Variable(4, Integer) = Store Integer(0)
+                Jump(6)
+            Block 6:Block:
+                Variable(5, Boolean) = Icmp Eq, Variable(2, Integer), Integer(1)
+                Branch Variable(5, Boolean), 7, 8
+            Block 7:Block:
+                Variable(6, Integer) = Store Integer(5)
+                Jump(9)
+            Block 8:Block:
+                Variable(6, Integer) = Store Integer(0)
+                Jump(9)
+            Block 9:Block:
+                Call id(3), args( Integer(2), Pointer, )
+                Call id(4), args( Variable(4, Integer), Pointer, )
+                Call id(4), args( Variable(6, Integer), Pointer, )
+                Return"#]],
+    );
+}
+
+#[test]
+fn qubit_array_at_dynamic_index_is_unsupported() {
+    let error = get_partial_evaluation_error(indoc! {
+        r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Unit {
+                use qs = Qubit[2];
+                let index = MResetZ(qs[0]) == One ? 1 | 0;
+                X(qs[index]);
+            }
+        }
+        "#,
+    });
+    expect![[r#"
+        Unimplemented(
+            "dynamic selection of Qubit",
+            PackageSpan {
+                package: PackageId(
+                    2,
+                ),
+                span: Span {
+                    lo: 154,
+                    hi: 163,
+                },
+            },
+        )
+    "#]]
+    .assert_debug_eq(&error);
+}
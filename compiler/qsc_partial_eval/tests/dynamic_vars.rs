@@ -79,9 +79,8 @@ fn dynamic_int_from_if_expression_with_single_measurement_comparison_and_classic
 }
 
 #[test]
-#[should_panic(expected = "() cannot be mapped to a RIR operand")]
 fn dynamic_int_from_if_expression_with_single_measurement_comparison_and_non_classical_blocks() {
-    let _ = compile_and_partially_evaluate(indoc! {
+    let program = compile_and_partially_evaluate(indoc! {
         r#"
         namespace Test {
             operation opA(q : Qubit) : Unit { body intrinsic; }
@@ -101,4 +100,109 @@ fn dynamic_int_from_if_expression_with_single_measurement_comparison_and_non_cla
         }
         "#,
     });
+    assert_blocks(
+        &program,
+        &expect![[r#"
+        Blocks:
+        Block 0:Block:
+            Call id(1), args( Qubit(0), Result(0), )
+            Variable(0, Boolean) = Call id(2), args( Result(0), )
+            Variable(1, Boolean) = Icmp Eq, Variable(0, Boolean), Bool(false)
+            Branch Variable(1, Boolean), 2, 3
+        Block 1:Block:
+            Call id(5), args( Integer(0), Pointer, )
+            Return
+        Block 2:Block:
+            Call id(3), args( Qubit(1), )
+            Variable(2, Integer) = Store Integer(0)
+            Jump(1)
+        Block 3:Block:
+            Call id(4), args( Qubit(1), )
+            Variable(2, Integer) = Store Integer(1)
+            Jump(1)"#]],
+    );
+}
+
+#[test]
+fn dynamic_double_arithmetic_used_as_rotation_angle() {
+    let program = compile_and_partially_evaluate(indoc! {
+        r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Unit {
+                use q = Qubit();
+                let r = QIR.Intrinsic.__quantum__qis__mresetz__body(q);
+                let theta = if r == Zero { 0.5 } else { 1.5 };
+                let angle = (theta + 1.0) * 2.0 - theta / 4.0;
+                QIR.Intrinsic.__quantum__qis__rx__body(angle, q);
+            }
+        }
+        "#,
+    });
+
+    assert_blocks(
+        &program,
+        &expect![[r#"
+        Blocks:
+        Block 0:Block:
+            Call id(1), args( Qubit(0), Result(0), )
+            Variable(0, Boolean) = Call id(2), args( Result(0), )
+            Variable(1, Boolean) = Icmp Eq, Variable(0, Boolean), Bool(false)
+            Branch Variable(1, Boolean), 2, 3
+        Block 1:Block:
+            Variable(3, Double) = Fadd Variable(2, Double), Double(1)
+            Variable(4, Double) = Fmul Variable(3, Double), Double(2)
+            Variable(5, Double) = Fdiv Variable(2, Double), Double(4)
+            Variable(6, Double) = Fsub Variable(4, Double), Variable(5, Double)
+            Call id(3), args( Variable(6, Double), Qubit(0), )
+            Call id(4), args( Integer(0), Pointer, )
+            Return
+        Block 2:Block:
+            Variable(2, Double) = Store Double(0.5)
+            Jump(1)
+        Block 3:Block:
+            Variable(2, Double) = Store Double(1.5)
+            Jump(1)"#]],
+    );
+}
+
+#[test]
+fn dynamic_double_comparisons_use_fcmp() {
+    let program = compile_and_partially_evaluate(indoc! {
+        r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : (Bool, Bool) {
+                use q = Qubit();
+                let r = QIR.Intrinsic.__quantum__qis__mresetz__body(q);
+                let theta = if r == Zero { 0.5 } else { 1.5 };
+                (theta < 1.0, theta != 0.5)
+            }
+        }
+        "#,
+    });
+
+    assert_blocks(
+        &program,
+        &expect![[r#"
+        Blocks:
+        Block 0:Block:
+            Call id(1), args( Qubit(0), Result(0), )
+            Variable(0, Boolean) = Call id(2), args( Result(0), )
+            Variable(1, Boolean) = Icmp Eq, Variable(0, Boolean), Bool(false)
+            Branch Variable(1, Boolean), 2, 3
+        Block 1:Block:
+            Variable(3, Boolean) = Fcmp Olt, Variable(2, Double), Double(1)
+            Variable(4, Boolean) = Fcmp Une, Variable(2, Double), Double(0.5)
+            Call id(3), args( Integer(2), Pointer, )
+            Call id(4), args( Variable(3, Boolean), Pointer, )
+            Call id(4), args( Variable(4, Boolean), Pointer, )
+            Return
+        Block 2:Block:
+            Variable(2, Double) = Store Double(0.5)
+            Jump(1)
+        Block 3:Block:
+            Variable(2, Double) = Store Double(1.5)
+            Jump(1)"#]],
+    );
 }
@@ -10,6 +10,7 @@ use indoc::{formatdoc, indoc};
 use qsc_rir::rir::{BlockId, CallableId};
 use test_utils::{
     assert_block_instructions, assert_blocks, assert_callable, compile_and_partially_evaluate,
+    get_partial_evaluation_error,
 };
 
 fn check_call_to_single_qubit_instrinsic_adds_callable_and_generates_instruction(
@@ -928,9 +929,8 @@ fn call_to_dump_register_does_not_generate_instructions() {
 }
 
 #[test]
-#[should_panic(expected = "`CheckZero` is not a supported by partial evaluation")]
-fn call_to_check_zero_panics() {
-    _ = compile_and_partially_evaluate(indoc! {
+fn call_to_check_zero_is_unsupported() {
+    let error = get_partial_evaluation_error(indoc! {
         r#"
         namespace Test {
             open Microsoft.Quantum.Diagnostics;
@@ -942,12 +942,26 @@ fn call_to_check_zero_panics() {
         }
         "#,
     });
+    expect![[r#"
+        UnsupportedIntrinsic(
+            "CheckZero",
+            PackageSpan {
+                package: PackageId(
+                    2,
+                ),
+                span: Span {
+                    lo: 147,
+                    hi: 156,
+                },
+            },
+        )
+    "#]]
+    .assert_debug_eq(&error);
 }
 
 #[test]
-#[should_panic(expected = "`DrawRandomInt` is not a supported by partial evaluation")]
-fn call_to_draw_random_int_panics() {
-    _ = compile_and_partially_evaluate(indoc! {
+fn call_to_draw_random_int_is_unsupported() {
+    let error = get_partial_evaluation_error(indoc! {
         r#"
         namespace Test {
             open Microsoft.Quantum.Random;
@@ -958,12 +972,26 @@ fn call_to_draw_random_int_panics() {
         }
         "#,
     });
+    expect![[r#"
+        UnsupportedIntrinsic(
+            "DrawRandomInt",
+            PackageSpan {
+                package: PackageId(
+                    2,
+                ),
+                span: Span {
+                    lo: 117,
+                    hi: 130,
+                },
+            },
+        )
+    "#]]
+    .assert_debug_eq(&error);
 }
 
 #[test]
-#[should_panic(expected = "`DrawRandomDouble` is not a supported by partial evaluation")]
-fn call_to_draw_random_double_panics() {
-    _ = compile_and_partially_evaluate(indoc! {
+fn call_to_draw_random_double_is_unsupported() {
+    let error = get_partial_evaluation_error(indoc! {
         r#"
         namespace Test {
             open Microsoft.Quantum.Random;
@@ -974,4 +1002,52 @@ fn call_to_draw_random_double_panics() {
         }
         "#,
     });
+    expect![[r#"
+        UnsupportedIntrinsic(
+            "DrawRandomDouble",
+            PackageSpan {
+                package: PackageId(
+                    2,
+                ),
+                span: Span {
+                    lo: 117,
+                    hi: 133,
+                },
+            },
+        )
+    "#]]
+    .assert_debug_eq(&error);
+}
+
+#[test]
+fn call_to_intrinsic_with_non_unit_output_is_unsupported() {
+    let error = get_partial_evaluation_error(indoc! {
+        r#"
+        namespace Test {
+            operation op(q : Qubit) : Int {
+                body intrinsic;
+            }
+            @EntryPoint()
+            operation Main() : Unit {
+                use q = Qubit();
+                let _ = op(q);
+            }
+        }
+        "#,
+    });
+    expect![[r#"
+        UnsupportedIntrinsic(
+            "op",
+            PackageSpan {
+                package: PackageId(
+                    2,
+                ),
+                span: Span {
+                    lo: 173,
+                    hi: 175,
+                },
+            },
+        )
+    "#]]
+    .assert_debug_eq(&error);
 }
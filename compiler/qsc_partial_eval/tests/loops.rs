@@ -8,7 +8,9 @@ pub mod test_utils;
 use expect_test::expect;
 use indoc::indoc;
 use qsc_rir::rir::{BlockId, CallableId};
-use test_utils::{assert_block_instructions, assert_callable, compile_and_partially_evaluate};
+use test_utils::{
+    assert_block_instructions, assert_blocks, assert_callable, compile_and_partially_evaluate,
+};
 
 #[test]
 fn unitary_call_within_a_for_loop() {
@@ -280,3 +282,143 @@ fn rotation_call_within_a_repeat_until_loop() {
                 Return"#]],
     );
 }
+
+#[test]
+fn while_loop_with_dynamic_condition() {
+    let program = compile_and_partially_evaluate(indoc! {
+        r#"
+        namespace Test {
+            operation op(q : Qubit) : Unit { body intrinsic; }
+            @EntryPoint()
+            operation Main() : Unit {
+                use q = Qubit();
+                mutable done = false;
+                while not done {
+                    op(q);
+                    set done = MResetZ(q) == Zero;
+                }
+            }
+        }
+        "#,
+    });
+    assert_blocks(
+        &program,
+        &expect![[r#"
+        Blocks:
+        Block 0:Block:
+            Variable(0, Boolean) = Store Bool(false)
+            Jump(1)
+        Block 1:Block:
+            Variable(1, Boolean) = LogicalNot Variable(0, Boolean)
+            Branch Variable(1, Boolean), 3, 2
+        Block 2:Block:
+            Call id(4), args( Integer(0), Pointer, )
+            Return
+        Block 3:Block:
+            Call id(1), args( Qubit(0), )
+            Call id(2), args( Qubit(0), Result(0), )
+            Variable(2, Boolean) = Call id(3), args( Result(0), )
+            Variable(3, Boolean) = Icmp Eq, Variable(2, Boolean), Bool(false)
+            Variable(0, Boolean) = Store Variable(3, Boolean)
+            Jump(1)"#]],
+    );
+}
+
+#[test]
+fn repeat_until_loop_with_dynamic_condition() {
+    let program = compile_and_partially_evaluate(indoc! {
+        r#"
+        namespace Test {
+            operation op(q : Qubit) : Unit { body intrinsic; }
+            @EntryPoint()
+            operation Main() : Unit {
+                use q = Qubit();
+                mutable done = false;
+                repeat {
+                    op(q);
+                    let r = MResetZ(q);
+                    set done = r == One;
+                } until done;
+            }
+        }
+        "#,
+    });
+    assert_blocks(
+        &program,
+        &expect![[r#"
+        Blocks:
+        Block 0:Block:
+            Variable(0, Boolean) = Store Bool(false)
+            Variable(1, Boolean) = Store Bool(true)
+            Jump(1)
+        Block 1:Block:
+            Branch Variable(1, Boolean), 3, 2
+        Block 2:Block:
+            Call id(4), args( Integer(0), Pointer, )
+            Return
+        Block 3:Block:
+            Call id(1), args( Qubit(0), )
+            Call id(2), args( Qubit(0), Result(0), )
+            Variable(2, Boolean) = Call id(3), args( Result(0), )
+            Variable(3, Boolean) = Icmp Eq, Variable(2, Boolean), Bool(true)
+            Variable(0, Boolean) = Store Variable(3, Boolean)
+            Variable(4, Boolean) = LogicalNot Variable(0, Boolean)
+            Variable(1, Boolean) = Store Variable(4, Boolean)
+            Jump(1)"#]],
+    );
+}
+
+#[test]
+fn while_loop_with_dynamic_condition_updates_counter() {
+    let program = compile_and_partially_evaluate(indoc! {
+        r#"
+        namespace Test {
+            operation op(q : Qubit) : Unit { body intrinsic; }
+            @EntryPoint()
+            operation Main() : Unit {
+                use q = Qubit();
+                mutable attempts = 0;
+                mutable done = false;
+                while not done {
+                    op(q);
+                    set attempts += 1;
+                    set done = MResetZ(q) == One;
+                    if attempts >= 5 {
+                        set done = true;
+                    }
+                }
+            }
+        }
+        "#,
+    });
+    assert_blocks(
+        &program,
+        &expect![[r#"
+        Blocks:
+        Block 0:Block:
+            Variable(0, Integer) = Store Integer(0)
+            Variable(1, Boolean) = Store Bool(false)
+            Jump(1)
+        Block 1:Block:
+            Variable(2, Boolean) = LogicalNot Variable(1, Boolean)
+            Branch Variable(2, Boolean), 3, 2
+        Block 2:Block:
+            Call id(4), args( Integer(0), Pointer, )
+            Return
+        Block 3:Block:
+            Call id(1), args( Qubit(0), )
+            Variable(3, Integer) = Add Variable(0, Integer), Integer(1)
+            Variable(0, Integer) = Store Variable(3, Integer)
+            Call id(2), args( Qubit(0), Result(0), )
+            Variable(4, Boolean) = Call id(3), args( Result(0), )
+            Variable(5, Boolean) = Icmp Eq, Variable(4, Boolean), Bool(true)
+            Variable(1, Boolean) = Store Variable(5, Boolean)
+            Variable(6, Boolean) = Icmp Sge, Variable(0, Integer), Integer(5)
+            Branch Variable(6, Boolean), 5, 4
+        Block 4:Block:
+            Jump(1)
+        Block 5:Block:
+            Variable(1, Boolean) = Store Bool(true)
+            Jump(4)"#]],
+    );
+}
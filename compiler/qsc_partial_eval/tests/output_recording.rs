@@ -5,7 +5,7 @@
 
 use expect_test::expect;
 use indoc::indoc;
-use test_utils::compile_and_partially_evaluate;
+use test_utils::{compile_and_partially_evaluate, get_partial_evaluation_error};
 
 pub mod test_utils;
 
@@ -520,7 +520,7 @@ fn output_recording_for_mix_of_literal_and_variable() {
 
 #[test]
 #[should_panic(
-    expected = "partial evaluation failed: OutputResultLiteral(Span { lo: 50, hi: 54 })"
+    expected = "partial evaluation failed: OutputResultLiteral(PackageSpan { package: PackageId(2), span: Span { lo: 50, hi: 54 } })"
 )]
 fn output_recording_fails_with_result_literal_one() {
     let _ = compile_and_partially_evaluate(indoc! {
@@ -537,7 +537,7 @@ fn output_recording_fails_with_result_literal_one() {
 
 #[test]
 #[should_panic(
-    expected = "partial evaluation failed: OutputResultLiteral(Span { lo: 50, hi: 54 })"
+    expected = "partial evaluation failed: OutputResultLiteral(PackageSpan { package: PackageId(2), span: Span { lo: 50, hi: 54 } })"
 )]
 fn output_recording_fails_with_result_literal_zero() {
     let _ = compile_and_partially_evaluate(indoc! {
@@ -554,7 +554,7 @@ fn output_recording_fails_with_result_literal_zero() {
 
 #[test]
 #[should_panic(
-    expected = "partial evaluation failed: OutputResultLiteral(Span { lo: 50, hi: 54 })"
+    expected = "partial evaluation failed: OutputResultLiteral(PackageSpan { package: PackageId(2), span: Span { lo: 50, hi: 54 } })"
 )]
 fn output_recording_fails_with_result_literal_in_array() {
     let _ = compile_and_partially_evaluate(indoc! {
@@ -572,7 +572,7 @@ fn output_recording_fails_with_result_literal_in_array() {
 
 #[test]
 #[should_panic(
-    expected = "partial evaluation failed: OutputResultLiteral(Span { lo: 50, hi: 54 })"
+    expected = "partial evaluation failed: OutputResultLiteral(PackageSpan { package: PackageId(2), span: Span { lo: 50, hi: 54 } })"
 )]
 fn output_recording_fails_with_result_literal_in_tuple() {
     let _ = compile_and_partially_evaluate(indoc! {
@@ -587,3 +587,34 @@ fn output_recording_fails_with_result_literal_in_tuple() {
         "#,
     });
 }
+
+#[test]
+fn output_recording_fails_with_unsupported_type() {
+    let error = get_partial_evaluation_error(indoc! {
+        r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Double {
+                use q = Qubit();
+                let r = MResetZ(q);
+                1.0
+            }
+        }
+        "#,
+    });
+    expect![[r#"
+        UnsupportedOutputType(
+            "Double",
+            PackageSpan {
+                package: PackageId(
+                    2,
+                ),
+                span: Span {
+                    lo: 50,
+                    hi: 54,
+                },
+            },
+        )
+    "#]]
+    .assert_debug_eq(&error);
+}
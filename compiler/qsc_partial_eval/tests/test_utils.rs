@@ -7,7 +7,7 @@ use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCap
 use qsc_fir::fir::PackageStore;
 use qsc_frontend::compile::{PackageStore as HirPackageStore, SourceMap};
 use qsc_lowerer::{map_hir_package_to_fir, Lowerer};
-use qsc_partial_eval::{partially_evaluate, ProgramEntry};
+use qsc_partial_eval::{partially_evaluate, Error, ProgramEntry};
 use qsc_rca::{Analyzer, PackageStoreComputeProperties};
 use qsc_rir::rir::{BlockId, CallableId, Program};
 
@@ -46,6 +46,21 @@ pub fn compile_and_partially_evaluate(source: &str) -> Program {
     }
 }
 
+#[must_use]
+pub fn get_partial_evaluation_error(source: &str) -> Error {
+    let compilation_context = CompilationContext::new(source);
+    let maybe_program = partially_evaluate(
+        &compilation_context.fir_store,
+        &compilation_context.compute_properties,
+        &compilation_context.entry,
+        TargetCapabilityFlags::empty(),
+    );
+    match maybe_program {
+        Ok(_) => panic!("partial evaluation succeeded"),
+        Err(error) => error,
+    }
+}
+
 struct CompilationContext {
     fir_store: PackageStore,
     compute_properties: PackageStoreComputeProperties,
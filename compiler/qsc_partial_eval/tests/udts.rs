@@ -0,0 +1,161 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+pub mod test_utils;
+
+use expect_test::expect;
+use indoc::indoc;
+use test_utils::{assert_blocks, compile_and_partially_evaluate};
+
+#[test]
+fn udt_field_with_dynamic_value_used_as_condition() {
+    let program = compile_and_partially_evaluate(indoc! {
+        r#"
+        namespace Test {
+            newtype Pair = (First : Bool, Second : Int);
+            operation op(q : Qubit) : Unit { body intrinsic; }
+            @EntryPoint()
+            operation Main() : Unit {
+                use q = Qubit();
+                let pair = Pair(MResetZ(q) == One, 5);
+                if pair::First {
+                    op(q);
+                }
+            }
+        }
+        "#,
+    });
+    assert_blocks(
+        &program,
+        &expect![[r#"
+        Blocks:
+        Block 0:Block:
+            Call id(1), args( Qubit(0), Result(0), )
+            Variable(0, Boolean) = Call id(2), args( Result(0), )
+            Variable(1, Boolean) = Icmp Eq, Variable(0, Boolean), Bool(true)
+            Branch Variable(1, Boolean), 2, 1
+        Block 1:Block:
+            Call id(4), args( Integer(0), Pointer, )
+            Return
+        Block 2:Block:
+            Call id(3), args( Qubit(0), )
+            Jump(1)"#]],
+    );
+}
+
+#[test]
+fn udt_with_dynamic_fields_as_output() {
+    let program = compile_and_partially_evaluate(indoc! {
+        r#"
+        namespace Test {
+            newtype Pair = (First : Bool, Second : Int);
+            @EntryPoint()
+            operation Main() : Pair {
+                use q = Qubit();
+                let b = MResetZ(q) == One;
+                Pair(b, b ? 1 | 0)
+            }
+        }
+        "#,
+    });
+    assert_blocks(
+        &program,
+        &expect![[r#"
+        Blocks:
+        Block 0:Block:
+            Call id(1), args( Qubit(0), Result(0), )
+            Variable(0, Boolean) = Call id(2), args( Result(0), )
+            Variable(1, Boolean) = Icmp Eq, Variable(0, Boolean), Bool(true)
+            Branch Variable(1, Boolean), 2, 3
+        Block 1:Block:
+            Call id(3), args( Integer(2), Pointer, )
+            Call id(4), args( Variable(1, Boolean), Pointer, )
+            Call id(5), args( Variable(2, Integer), Pointer, )
+            Return
+        Block 2:Block:
+            Variable(2, Integer) = Store Integer(1)
+            Jump(1)
+        Block 3:Block:
+            Variable(2, Integer) = Store Integer(0)
+            Jump(1)"#]],
+    );
+}
+
+#[test]
+fn udt_field_updated_within_dynamic_if() {
+    let program = compile_and_partially_evaluate(indoc! {
+        r#"
+        namespace Test {
+            newtype Pair = (First : Bool, Second : Int);
+            @EntryPoint()
+            operation Main() : Int {
+                use q = Qubit();
+                mutable pair = Pair(false, 0);
+                if MResetZ(q) == One {
+                    set pair w/= Second <- 1;
+                }
+                pair::Second
+            }
+        }
+        "#,
+    });
+    assert_blocks(
+        &program,
+        &expect![[r#"
+            Blocks:
+            Block 0:Block:
+                Call id(1), args( Qubit(0), Result(0), )
+                Variable(0, Boolean) = Call id(2), args( Result(0), )
+                Variable(1, Boolean) = Icmp Eq, Variable(0, Boolean), Bool(true)
+                Variable(2, Boolean) = Store Bool(false)
+                Variable(3, Integer) = Store Integer(0)
+                Branch Variable(1, Boolean), 2, 1
+            Block 1:Block:
+                Call id(3), args( Variable(3, Integer), Pointer, )
+                Return
+            Block 2:Block:
+                Variable(3, Integer) = Store Integer(1)
+                Jump(1)"#]],
+    );
+}
+
+#[test]
+fn tuple_returned_from_dynamic_if() {
+    let program = compile_and_partially_evaluate(indoc! {
+        r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : (Int, Bool) {
+                use q = Qubit();
+                let (i, b) = if MResetZ(q) == One { (1, true) } else { (2, false) };
+                (i, b)
+            }
+        }
+        "#,
+    });
+    assert_blocks(
+        &program,
+        &expect![[r#"
+        Blocks:
+        Block 0:Block:
+            Call id(1), args( Qubit(0), Result(0), )
+            Variable(0, Boolean) = Call id(2), args( Result(0), )
+            Variable(1, Boolean) = Icmp Eq, Variable(0, Boolean), Bool(true)
+            Branch Variable(1, Boolean), 2, 3
+        Block 1:Block:
+            Call id(3), args( Integer(2), Pointer, )
+            Call id(4), args( Variable(2, Integer), Pointer, )
+            Call id(5), args( Variable(3, Boolean), Pointer, )
+            Return
+        Block 2:Block:
+            Variable(2, Integer) = Store Integer(1)
+            Variable(3, Boolean) = Store Bool(true)
+            Jump(1)
+        Block 3:Block:
+            Variable(2, Integer) = Store Integer(2)
+            Variable(3, Boolean) = Store Bool(false)
+            Jump(1)"#]],
+    );
+}
@@ -3,6 +3,7 @@
 
 use crate::manifest::ManifestDescriptor;
 use std::{
+    ffi::OsStr,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -12,6 +13,9 @@ use std::{
 pub struct Project {
     pub sources: Vec<(Arc<str>, Arc<str>)>,
     pub manifest: crate::Manifest,
+    /// Errors for source files that could not be imported, such as invalid OpenQASM files.
+    /// These files are left out of `sources`, and the rest of the project still loads.
+    pub errors: Vec<miette::Report>,
 }
 
 /// This enum represents a filesystem object type. It is analogous to [`std::fs::FileType`].
@@ -58,9 +62,9 @@ pub trait FileSystemAsync {
     async fn read_file(&self, path: &Path) -> miette::Result<(Arc<str>, Arc<str>)>;
 
     /// Given a path, list its directory contents (if any).
-    /// This function should only return files that end in *.qs and folders.
+    /// This function should only return files that end in *.qs or *.qasm and folders.
     async fn list_directory(&self, path: &Path) -> miette::Result<Vec<Self::Entry>>;
-    /// Given an initial path, fetch files matching <initial_path>/**/*.qs
+    /// Given an initial path, fetch files matching <initial_path>/**/*.qs and <initial_path>/**/*.qasm
     async fn collect_project_sources(
         &self,
         initial_path: &Path,
@@ -88,7 +92,7 @@ pub trait FileSystemAsync {
         let mut files = vec![];
         for item in filter_hidden_files(listing.into_iter()) {
             match item.entry_type() {
-                Ok(EntryType::File) if item.entry_extension() == "qs" => files.push(item),
+                Ok(EntryType::File) if is_source_file(&item) => files.push(item),
                 Ok(EntryType::Folder) => {
                     files.append(&mut self.collect_project_sources_inner(&item.path()).await?);
                 }
@@ -105,17 +109,58 @@ pub trait FileSystemAsync {
         let qs_files = qs_files.into_iter().map(|file| file.path());
 
         let mut sources = Vec::with_capacity(qs_files.len());
+        let mut errors = vec![];
         for path in qs_files {
-            sources.push(self.read_file(&path).await?);
+            match import_qasm(&path, self.read_file(&path).await?) {
+                Ok(source) => sources.push(source),
+                Err(error) => errors.push(error),
+            }
         }
 
         Ok(Project {
             manifest: manifest.manifest.clone(),
             sources,
+            errors,
         })
     }
 }
 
+fn is_source_file<Entry: DirEntry>(entry: &Entry) -> bool {
+    matches!(entry.entry_extension().as_str(), "qs" | "qasm")
+}
+
+/// OpenQASM files in a project are imported as Q# operations named after the file, each in its
+/// own `Qasm.<operation>` namespace so that the helpers and gates generated for different files
+/// do not clash. Other files are returned unchanged. Errors are reported against the original
+/// OpenQASM source.
+fn import_qasm(
+    path: &Path,
+    (name, contents): (Arc<str>, Arc<str>),
+) -> miette::Result<(Arc<str>, Arc<str>)> {
+    if path.extension() != Some(OsStr::new("qasm")) {
+        return Ok((name, contents));
+    }
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let mut operation = stem
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if !operation.starts_with(char::is_alphabetic) {
+        operation.insert(0, '_');
+    }
+    if !qsc_qasm::is_identifier(&operation) {
+        operation.push('_');
+    }
+    match qsc_qasm::qasm_to_qsharp(&contents, &format!("Qasm.{operation}"), &operation) {
+        Ok(qsharp) => Ok((name, qsharp.into())),
+        Err(error) => Err(miette::Report::new(error)
+            .with_source_code(miette::NamedSource::new(name, contents.to_string()))),
+    }
+}
+
 /// Filters out any hidden files (files that start with '.')
 fn filter_hidden_files<Entry: DirEntry>(
     listing: impl Iterator<Item = Entry>,
@@ -135,7 +180,7 @@ pub trait FileSystem {
 
     /// Given a path, list its directory contents (if any).
     fn list_directory(&self, path: &Path) -> miette::Result<Vec<Self::Entry>>;
-    /// Given an initial path, fetch files matching <`initial_path`>/**/*.qs
+    /// Given an initial path, fetch files matching <`initial_path`>/**/*.qs and <`initial_path`>/**/*.qasm
     fn collect_project_sources(&self, initial_path: &Path) -> miette::Result<Vec<Self::Entry>> {
         let listing = self.list_directory(initial_path)?;
         if let Some(src_dir) = listing.into_iter().find(|x| {
@@ -160,7 +205,7 @@ pub trait FileSystem {
         let mut files = vec![];
         for item in filter_hidden_files(listing.into_iter()) {
             match item.entry_type() {
-                Ok(EntryType::File) if item.entry_extension() == "qs" => files.push(item),
+                Ok(EntryType::File) if is_source_file(&item) => files.push(item),
                 Ok(EntryType::Folder) => {
                     files.append(&mut self.collect_project_sources_inner(&item.path())?);
                 }
@@ -177,12 +222,19 @@ pub trait FileSystem {
 
         let qs_files = qs_files.into_iter().map(|file| file.path());
 
-        let qs_sources = qs_files.map(|path| self.read_file(&path));
+        let mut sources = vec![];
+        let mut errors = vec![];
+        for path in qs_files {
+            match import_qasm(&path, self.read_file(&path)?) {
+                Ok(source) => sources.push(source),
+                Err(error) => errors.push(error),
+            }
+        }
 
-        let sources = qs_sources.collect::<miette::Result<_>>()?;
         Ok(Project {
             manifest: manifest.manifest.clone(),
             sources,
+            errors,
         })
     }
 }
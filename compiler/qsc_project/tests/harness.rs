@@ -4,9 +4,15 @@
 use std::{path::PathBuf, sync::Arc};
 
 use expect_test::Expect;
-use qsc_project::{FileSystem, Manifest, StdFs};
+use qsc_project::{FileSystem, Manifest, Project, StdFs};
 
 pub fn check(project_path: &PathBuf, expect: &Expect) {
+    let project = load(project_path);
+    expect.assert_eq(&format!("{project:#?}"));
+}
+
+#[must_use]
+pub fn load(project_path: &PathBuf) -> Project {
     let mut root_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     root_path.push(PathBuf::from("tests/projects"));
     let mut absolute_project_path = root_path.clone();
@@ -29,6 +35,5 @@ pub fn check(project_path: &PathBuf, expect: &Expect) {
     }
 
     project.sources.sort();
-
-    expect.assert_eq(&format!("{project:#?}"));
+    project
 }
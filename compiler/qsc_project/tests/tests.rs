@@ -8,7 +8,7 @@
 mod harness;
 
 use expect_test::expect;
-use harness::check;
+use harness::{check, load};
 
 #[test]
 fn basic_manifest() {
@@ -38,6 +38,7 @@ fn basic_manifest() {
                     language_features: [],
                     lints: [],
                 },
+                errors: [],
             }"#]],
     );
 }
@@ -70,6 +71,7 @@ fn circular_imports() {
                     language_features: [],
                     lints: [],
                 },
+                errors: [],
             }"#]],
     );
 }
@@ -102,6 +104,7 @@ fn different_files_same_manifest() {
                     language_features: [],
                     lints: [],
                 },
+                errors: [],
             }"#]],
     );
 }
@@ -124,6 +127,7 @@ fn empty_manifest() {
                     language_features: [],
                     lints: [],
                 },
+                errors: [],
             }"#]],
     );
 }
@@ -158,6 +162,7 @@ fn folder_structure() {
                     language_features: [],
                     lints: [],
                 },
+                errors: [],
             }"#]],
     );
 }
@@ -187,6 +192,7 @@ fn hidden_files() {
                     language_features: [],
                     lints: [],
                 },
+                errors: [],
             }"#]],
     );
 }
@@ -220,6 +226,7 @@ fn peer_file() {
                     language_features: [],
                     lints: [],
                 },
+                errors: [],
             }"#]],
     );
 }
@@ -244,6 +251,59 @@ fn language_feature() {
                     ],
                     lints: [],
                 },
+                errors: [],
             }"#]],
     );
 }
+
+#[test]
+fn qasm_file() {
+    check(
+        &"qasm_file".into(),
+        &expect![[r#"
+            Project {
+                sources: [
+                    (
+                        "qasm_file/src/Bell.qasm",
+                        "namespace Qasm.Bell {\n    open Microsoft.Quantum.Convert;\n    open Microsoft.Quantum.Math;\n\n    operation Bell() : Result[] {\n        use q = Qubit[2];\n        mutable c = [Zero, size = 2];\n        H(q[0]);\n        CNOT(q[0], q[1]);\n        set c w/= 0 <- M(q[0]);\n        set c w/= 1 <- M(q[1]);\n        ResetAll(q);\n        c\n    }\n}\n",
+                    ),
+                    (
+                        "qasm_file/src/Main.qs",
+                        "namespace Main {\n    @EntryPoint()\n    operation Main() : (Result[], Result[], Result[]) {\n        (Qasm.Bell.Bell(), Qasm.Plus.Plus(), Qasm.Minus.Minus())\n    }\n}\n",
+                    ),
+                    (
+                        "qasm_file/src/Minus.qasm",
+                        "namespace Qasm.Minus {\n    open Microsoft.Quantum.Convert;\n    open Microsoft.Quantum.Math;\n\n    operation U(theta : Double, phi : Double, lambda : Double, qubit : Qubit) : Unit is Adj + Ctl {\n        R1(lambda, qubit);\n        Ry(theta, qubit);\n        R1(phi, qubit);\n    }\n\n    operation Minus() : Result[] {\n        use q = Qubit[1];\n        mutable c = [Zero, size = 1];\n        U(PI() / 2.0, PI(), 0.0, q[0]);\n        set c w/= 0 <- M(q[0]);\n        ResetAll(q);\n        c\n    }\n}\n",
+                    ),
+                    (
+                        "qasm_file/src/Plus.qasm",
+                        "namespace Qasm.Plus {\n    open Microsoft.Quantum.Convert;\n    open Microsoft.Quantum.Math;\n\n    operation U(theta : Double, phi : Double, lambda : Double, qubit : Qubit) : Unit is Adj + Ctl {\n        R1(lambda, qubit);\n        Ry(theta, qubit);\n        R1(phi, qubit);\n    }\n\n    operation Plus() : Result[] {\n        use q = Qubit[1];\n        mutable c = [Zero, size = 1];\n        U(PI() / 2.0, 0.0, PI(), q[0]);\n        set c w/= 0 <- M(q[0]);\n        ResetAll(q);\n        c\n    }\n}\n",
+                    ),
+                ],
+                manifest: Manifest {
+                    author: None,
+                    license: None,
+                    language_features: [],
+                    lints: [],
+                },
+                errors: [],
+            }"#]],
+    );
+}
+
+#[test]
+fn qasm_file_with_error_is_reported_separately() {
+    let project = load(&"qasm_error".into());
+    let sources = project
+        .sources
+        .iter()
+        .map(|(name, _)| name.as_ref())
+        .collect::<Vec<_>>();
+    assert_eq!(sources, ["qasm_error/src/Main.qs"]);
+    let errors = project
+        .errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(errors, ["undefined gate `foo`"]);
+}
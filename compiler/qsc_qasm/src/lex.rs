@@ -0,0 +1,211 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Splits OpenQASM source into tokens. Whitespace and comments are discarded, and keywords are
+//! treated as identifiers so that the parser can decide how to interpret them.
+
+use crate::Error;
+use qsc_data_structures::span::Span;
+use std::{
+    fmt::{self, Display, Formatter},
+    iter::Peekable,
+    str::CharIndices,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Token {
+    pub(crate) kind: TokenKind,
+    pub(crate) span: Span,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum TokenKind {
+    Ident,
+    Int,
+    Float,
+    String,
+    /// `->`
+    Arrow,
+    /// `@`
+    At,
+    /// `==`
+    EqEq,
+    /// `=`
+    Eq,
+    /// `+`
+    Plus,
+    /// `-`
+    Minus,
+    /// `*`
+    Star,
+    /// `/`
+    Slash,
+    /// `^`
+    Caret,
+    Comma,
+    Semi,
+    Open(Delim),
+    Close(Delim),
+    Eof,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Delim {
+    Paren,
+    Bracket,
+    Brace,
+}
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            TokenKind::Ident => f.write_str("identifier"),
+            TokenKind::Int => f.write_str("integer"),
+            TokenKind::Float => f.write_str("float"),
+            TokenKind::String => f.write_str("string"),
+            TokenKind::Arrow => f.write_str("`->`"),
+            TokenKind::At => f.write_str("`@`"),
+            TokenKind::EqEq => f.write_str("`==`"),
+            TokenKind::Eq => f.write_str("`=`"),
+            TokenKind::Plus => f.write_str("`+`"),
+            TokenKind::Minus => f.write_str("`-`"),
+            TokenKind::Star => f.write_str("`*`"),
+            TokenKind::Slash => f.write_str("`/`"),
+            TokenKind::Caret => f.write_str("`^`"),
+            TokenKind::Comma => f.write_str("`,`"),
+            TokenKind::Semi => f.write_str("`;`"),
+            TokenKind::Open(Delim::Paren) => f.write_str("`(`"),
+            TokenKind::Open(Delim::Bracket) => f.write_str("`[`"),
+            TokenKind::Open(Delim::Brace) => f.write_str("`{`"),
+            TokenKind::Close(Delim::Paren) => f.write_str("`)`"),
+            TokenKind::Close(Delim::Bracket) => f.write_str("`]`"),
+            TokenKind::Close(Delim::Brace) => f.write_str("`}`"),
+            TokenKind::Eof => f.write_str("EOF"),
+        }
+    }
+}
+
+/// Tokenizes the whole input, ending with an [`TokenKind::Eof`] token.
+pub(crate) fn tokenize(input: &str) -> Result<Vec<Token>, Error> {
+    let mut lexer = Lexer {
+        input,
+        chars: input.char_indices().peekable(),
+    };
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next_token()? {
+        tokens.push(token);
+    }
+    let end = offset(input.len());
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        span: Span { lo: end, hi: end },
+    });
+    Ok(tokens)
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl Lexer<'_> {
+    fn next_token(&mut self) -> Result<Option<Token>, Error> {
+        loop {
+            let Some((lo, c)) = self.chars.next() else {
+                return Ok(None);
+            };
+            let kind = match c {
+                c if c.is_whitespace() => continue,
+                '/' if self.next_if_eq('/') => {
+                    while self.chars.next_if(|&(_, c)| c != '\n').is_some() {}
+                    continue;
+                }
+                '/' if self.next_if_eq('*') => {
+                    self.block_comment(lo)?;
+                    continue;
+                }
+                c if c == '_' || c.is_alphabetic() => {
+                    while self
+                        .chars
+                        .next_if(|&(_, c)| c == '_' || c.is_alphanumeric())
+                        .is_some()
+                    {}
+                    TokenKind::Ident
+                }
+                c if c.is_ascii_digit() || c == '.' => self.number(c),
+                '"' => {
+                    if self.chars.find(|&(_, c)| c == '"').is_none() {
+                        return Err(Error::UnterminatedString(self.span_from(lo)));
+                    }
+                    TokenKind::String
+                }
+                '-' if self.next_if_eq('>') => TokenKind::Arrow,
+                '=' if self.next_if_eq('=') => TokenKind::EqEq,
+                '@' => TokenKind::At,
+                '=' => TokenKind::Eq,
+                '+' => TokenKind::Plus,
+                '-' => TokenKind::Minus,
+                '*' => TokenKind::Star,
+                '/' => TokenKind::Slash,
+                '^' => TokenKind::Caret,
+                ',' => TokenKind::Comma,
+                ';' => TokenKind::Semi,
+                '(' => TokenKind::Open(Delim::Paren),
+                '[' => TokenKind::Open(Delim::Bracket),
+                '{' => TokenKind::Open(Delim::Brace),
+                ')' => TokenKind::Close(Delim::Paren),
+                ']' => TokenKind::Close(Delim::Bracket),
+                '}' => TokenKind::Close(Delim::Brace),
+                c => return Err(Error::UnexpectedChar(c, self.span_from(lo))),
+            };
+            return Ok(Some(Token {
+                kind,
+                span: self.span_from(lo),
+            }));
+        }
+    }
+
+    fn next_if_eq(&mut self, c: char) -> bool {
+        self.chars.next_if(|&(_, next)| next == c).is_some()
+    }
+
+    fn block_comment(&mut self, lo: usize) -> Result<(), Error> {
+        while let Some((_, c)) = self.chars.next() {
+            if c == '*' && self.next_if_eq('/') {
+                return Ok(());
+            }
+        }
+        Err(Error::UnterminatedComment(self.span_from(lo)))
+    }
+
+    fn number(&mut self, first: char) -> TokenKind {
+        let mut is_float = first == '.';
+        while let Some((_, c)) = self.chars.next_if(|&(_, c)| c.is_ascii_digit() || c == '.') {
+            is_float |= c == '.';
+        }
+        if self.chars.next_if(|&(_, c)| c == 'e' || c == 'E').is_some() {
+            is_float = true;
+            self.chars.next_if(|&(_, c)| c == '+' || c == '-');
+            while self.chars.next_if(|&(_, c)| c.is_ascii_digit()).is_some() {}
+        }
+        if is_float {
+            TokenKind::Float
+        } else {
+            TokenKind::Int
+        }
+    }
+
+    fn span_from(&mut self, lo: usize) -> Span {
+        let hi = self.chars.peek().map_or(self.input.len(), |&(i, _)| i);
+        Span {
+            lo: offset(lo),
+            hi: offset(hi),
+        }
+    }
+}
+
+fn offset(index: usize) -> u32 {
+    index
+        .try_into()
+        .expect("source should be smaller than 4 GiB")
+}
@@ -0,0 +1,122 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Imports OpenQASM 2.0 programs, and the gate and measurement subset of OpenQASM 3, as Q# source.
+//!
+//! The whole program becomes a single operation that allocates the declared qubit registers,
+//! applies the gates and measurements in order, and returns the classical registers. Gate
+//! definitions become adjointable and controllable operations in the same namespace.
+
+#[cfg(test)]
+mod tests;
+
+mod lex;
+mod parse;
+mod qsharp;
+
+use miette::Diagnostic;
+use qsc_data_structures::span::Span;
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("expected {0}, found {1}")]
+    #[diagnostic(code("Qsc.Qasm.Token"))]
+    Token(String, String, #[label] Span),
+
+    #[error("unexpected character `{0}`")]
+    #[diagnostic(code("Qsc.Qasm.UnexpectedChar"))]
+    UnexpectedChar(char, #[label] Span),
+
+    #[error("unterminated string literal")]
+    #[diagnostic(code("Qsc.Qasm.UnterminatedString"))]
+    UnterminatedString(#[label] Span),
+
+    #[error("unterminated block comment")]
+    #[diagnostic(code("Qsc.Qasm.UnterminatedComment"))]
+    UnterminatedComment(#[label] Span),
+
+    #[error("invalid integer literal")]
+    #[diagnostic(code("Qsc.Qasm.InvalidInt"))]
+    InvalidInt(#[label] Span),
+
+    #[error("invalid floating-point literal")]
+    #[diagnostic(code("Qsc.Qasm.InvalidFloat"))]
+    InvalidFloat(#[label] Span),
+
+    #[error("unsupported OpenQASM version {0}")]
+    #[diagnostic(code("Qsc.Qasm.UnsupportedVersion"))]
+    #[diagnostic(help("only OpenQASM 2.0 and 3 programs can be imported"))]
+    UnsupportedVersion(String, #[label] Span),
+
+    #[error("unsupported include file `{0}`")]
+    #[diagnostic(code("Qsc.Qasm.UnsupportedInclude"))]
+    #[diagnostic(help(
+        "only the standard gate libraries `qelib1.inc` and `stdgates.inc` can be included"
+    ))]
+    UnsupportedInclude(String, #[label] Span),
+
+    #[error("{0} are not supported")]
+    #[diagnostic(code("Qsc.Qasm.Unsupported"))]
+    #[diagnostic(help(
+        "only register declarations, gates, measurements, resets and conditionals on classical registers can be imported"
+    ))]
+    Unsupported(String, #[label] Span),
+
+    #[error("undefined gate `{0}`")]
+    #[diagnostic(code("Qsc.Qasm.UndefinedGate"))]
+    UndefinedGate(String, #[label] Span),
+
+    #[error("undefined register `{0}`")]
+    #[diagnostic(code("Qsc.Qasm.UndefinedRegister"))]
+    UndefinedRegister(String, #[label] Span),
+
+    #[error("undefined identifier `{0}`")]
+    #[diagnostic(code("Qsc.Qasm.UndefinedIdent"))]
+    UndefinedIdent(String, #[label] Span),
+
+    #[error("`{0}` is already defined")]
+    #[diagnostic(code("Qsc.Qasm.Redefined"))]
+    Redefined(String, #[label] Span),
+
+    #[error("gate `{0}` expects {1} parameters, found {2}")]
+    #[diagnostic(code("Qsc.Qasm.ParamCount"))]
+    ParamCount(String, usize, usize, #[label] Span),
+
+    #[error("gate `{0}` expects {1} qubits, found {2}")]
+    #[diagnostic(code("Qsc.Qasm.QubitCount"))]
+    QubitCount(String, usize, usize, #[label] Span),
+
+    #[error("index {1} is out of range for register `{0}` of size {2}")]
+    #[diagnostic(code("Qsc.Qasm.IndexOutOfRange"))]
+    IndexOutOfRange(String, u32, u32, #[label] Span),
+
+    #[error("`{0}` is not a register and cannot be indexed")]
+    #[diagnostic(code("Qsc.Qasm.NotIndexable"))]
+    NotIndexable(String, #[label] Span),
+
+    #[error("registers used in the same statement must have the same size")]
+    #[diagnostic(code("Qsc.Qasm.SizeMismatch"))]
+    SizeMismatch(#[label] Span),
+
+    #[error("a single bit can only be compared to 0 or 1")]
+    #[diagnostic(code("Qsc.Qasm.BitComparison"))]
+    BitComparison(#[label] Span),
+}
+
+/// Converts an OpenQASM program to a Q# namespace containing an operation with the given name.
+///
+/// # Errors
+///
+/// Returns an error if the program cannot be parsed, or uses features that have no Q# equivalent.
+pub fn qasm_to_qsharp(source: &str, namespace: &str, operation: &str) -> Result<String, Error> {
+    let program = parse::parse(source)?;
+    qsharp::generate(&program, namespace, operation)
+}
+
+/// Returns true if the name can be used as-is for the generated operation or namespace, that is,
+/// if it is a Q# identifier and not a Q# keyword.
+#[must_use]
+pub fn is_identifier(name: &str) -> bool {
+    qsharp::is_identifier(name)
+}
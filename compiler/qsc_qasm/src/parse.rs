@@ -0,0 +1,506 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Parses the gate and measurement subset of OpenQASM 2.0 and 3 into a flat list of statements.
+
+use crate::{
+    lex::{tokenize, Delim, Token, TokenKind},
+    Error,
+};
+use qsc_data_structures::span::Span;
+
+#[derive(Debug)]
+pub(crate) struct Program {
+    pub(crate) stmts: Vec<Stmt>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Ident {
+    pub(crate) name: String,
+    pub(crate) span: Span,
+}
+
+#[derive(Debug)]
+pub(crate) enum Stmt {
+    /// `qreg q[2];`, `qubit[2] q;` or `qubit q;`. The size is `None` for a single qubit.
+    QubitDecl(Ident, Option<u32>),
+    /// `creg c[2];`, `bit[2] c;` or `bit c;`. The size is `None` for a single bit.
+    BitDecl(Ident, Option<u32>),
+    GateDef(GateDef),
+    GateCall(GateCall),
+    /// `measure q -> c;`, `c = measure q;` or `measure q;`.
+    Measure(Operand, Option<Operand>),
+    Reset(Operand),
+    /// `if (c == 1) stmt;` applies the statements when the bits compare equal to the value.
+    If(Operand, u64, Vec<Stmt>),
+}
+
+#[derive(Debug)]
+pub(crate) struct GateDef {
+    pub(crate) name: Ident,
+    pub(crate) params: Vec<Ident>,
+    pub(crate) qubits: Vec<Ident>,
+    pub(crate) body: Vec<GateCall>,
+}
+
+#[derive(Debug)]
+pub(crate) struct GateCall {
+    pub(crate) modifiers: Vec<Modifier>,
+    pub(crate) name: Ident,
+    pub(crate) args: Vec<Expr>,
+    pub(crate) qubits: Vec<Operand>,
+    pub(crate) span: Span,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Modifier {
+    Inv,
+    Ctrl(u32),
+}
+
+/// A register or a single element of a register.
+#[derive(Debug)]
+pub(crate) struct Operand {
+    pub(crate) name: Ident,
+    pub(crate) index: Option<u32>,
+    pub(crate) span: Span,
+}
+
+#[derive(Debug)]
+pub(crate) enum Expr {
+    Lit(f64),
+    Ident(Ident),
+    Neg(Box<Expr>),
+    BinOp(BinOp, Box<Expr>, Box<Expr>),
+    Call(Ident, Box<Expr>),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+pub(crate) fn parse(input: &str) -> Result<Program, Error> {
+    let mut parser = Parser {
+        input,
+        tokens: tokenize(input)?,
+        pos: 0,
+    };
+    parser.header()?;
+    let mut stmts = Vec::new();
+    while parser.peek().kind != TokenKind::Eof {
+        if let Some(stmt) = parser.stmt()? {
+            stmts.push(stmt);
+        }
+    }
+    Ok(Program { stmts })
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Token {
+        self.tokens[self.pos]
+    }
+
+    fn peek_text(&self) -> &str {
+        &self.input[self.peek().span]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.peek();
+        if token.kind != TokenKind::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, kind: TokenKind) -> bool {
+        if self.peek().kind == kind {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek().kind == TokenKind::Ident && self.peek_text() == keyword {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<Token, Error> {
+        if self.peek().kind == kind {
+            Ok(self.advance())
+        } else {
+            Err(self.unexpected(&kind.to_string()))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> Error {
+        let token = self.peek();
+        Error::Token(expected.to_string(), token.kind.to_string(), token.span)
+    }
+
+    fn ident(&mut self) -> Result<Ident, Error> {
+        let token = self.expect(TokenKind::Ident)?;
+        Ok(Ident {
+            name: self.input[token.span].to_string(),
+            span: token.span,
+        })
+    }
+
+    fn int(&mut self) -> Result<u32, Error> {
+        let token = self.expect(TokenKind::Int)?;
+        self.input[token.span]
+            .parse()
+            .map_err(|_| Error::InvalidInt(token.span))
+    }
+
+    /// Parses `[n]`.
+    fn size(&mut self) -> Result<u32, Error> {
+        self.expect(TokenKind::Open(Delim::Bracket))?;
+        let size = self.int()?;
+        self.expect(TokenKind::Close(Delim::Bracket))?;
+        Ok(size)
+    }
+
+    fn header(&mut self) -> Result<(), Error> {
+        if !self.eat_keyword("OPENQASM") {
+            return Ok(());
+        }
+        let version = self.advance();
+        let text = &self.input[version.span];
+        if !matches!(version.kind, TokenKind::Int | TokenKind::Float)
+            || !matches!(text, "2.0" | "3" | "3.0")
+        {
+            return Err(Error::UnsupportedVersion(text.to_string(), version.span));
+        }
+        self.expect(TokenKind::Semi)?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn stmt(&mut self) -> Result<Option<Stmt>, Error> {
+        let token = self.peek();
+        if token.kind != TokenKind::Ident {
+            return Err(self.unexpected("statement"));
+        }
+        let stmt = match self.peek_text() {
+            "include" => {
+                self.advance();
+                let file = self.expect(TokenKind::String)?;
+                let name = &self.input[file.span];
+                let name = &name[1..name.len() - 1];
+                if !matches!(name, "qelib1.inc" | "stdgates.inc") {
+                    return Err(Error::UnsupportedInclude(name.to_string(), file.span));
+                }
+                self.expect(TokenKind::Semi)?;
+                return Ok(None);
+            }
+            "qreg" | "creg" => {
+                let is_qubit = self.advance_text() == "qreg";
+                let name = self.ident()?;
+                let size = self.size()?;
+                self.expect(TokenKind::Semi)?;
+                if is_qubit {
+                    Stmt::QubitDecl(name, Some(size))
+                } else {
+                    Stmt::BitDecl(name, Some(size))
+                }
+            }
+            "qubit" | "bit" => {
+                let is_qubit = self.advance_text() == "qubit";
+                let size = if self.peek().kind == TokenKind::Open(Delim::Bracket) {
+                    Some(self.size()?)
+                } else {
+                    None
+                };
+                let name = self.ident()?;
+                self.expect(TokenKind::Semi)?;
+                if is_qubit {
+                    Stmt::QubitDecl(name, size)
+                } else {
+                    Stmt::BitDecl(name, size)
+                }
+            }
+            "gate" => {
+                self.advance();
+                Stmt::GateDef(self.gate_def()?)
+            }
+            "barrier" => {
+                self.advance();
+                self.comma_separated(TokenKind::Semi, Self::operand)?;
+                return Ok(None);
+            }
+            "measure" => {
+                self.advance();
+                let qubit = self.operand()?;
+                let target = if self.eat(TokenKind::Arrow) {
+                    Some(self.operand()?)
+                } else {
+                    None
+                };
+                self.expect(TokenKind::Semi)?;
+                Stmt::Measure(qubit, target)
+            }
+            "reset" => {
+                self.advance();
+                let qubit = self.operand()?;
+                self.expect(TokenKind::Semi)?;
+                Stmt::Reset(qubit)
+            }
+            "if" => {
+                self.advance();
+                self.expect(TokenKind::Open(Delim::Paren))?;
+                let bits = self.operand()?;
+                self.expect(TokenKind::EqEq)?;
+                let value = self.expect(TokenKind::Int)?;
+                let value = self.input[value.span]
+                    .parse()
+                    .map_err(|_| Error::InvalidInt(value.span))?;
+                self.expect(TokenKind::Close(Delim::Paren))?;
+                let body = if self.eat(TokenKind::Open(Delim::Brace)) {
+                    let mut body = Vec::new();
+                    while !self.eat(TokenKind::Close(Delim::Brace)) {
+                        body.extend(self.stmt()?);
+                    }
+                    body
+                } else {
+                    self.stmt()?.into_iter().collect()
+                };
+                Stmt::If(bits, value, body)
+            }
+            "opaque" | "OPENQASM" | "input" | "output" | "const" | "int" | "uint" | "float"
+            | "angle" | "bool" | "def" | "for" | "while" | "else" | "defcal" | "cal" | "let"
+            | "box" | "delay" | "extern" | "pragma" => {
+                return Err(Error::Unsupported(
+                    format!("`{}` statements", self.peek_text()),
+                    token.span,
+                ));
+            }
+            _ if self.is_measure_assignment() => {
+                let target = self.operand()?;
+                self.expect(TokenKind::Eq)?;
+                if !self.eat_keyword("measure") {
+                    return Err(self.unexpected("`measure`"));
+                }
+                let qubit = self.operand()?;
+                self.expect(TokenKind::Semi)?;
+                Stmt::Measure(qubit, Some(target))
+            }
+            _ => Stmt::GateCall(self.gate_call()?),
+        };
+        Ok(Some(stmt))
+    }
+
+    fn advance_text(&mut self) -> &str {
+        let token = self.advance();
+        &self.input[token.span]
+    }
+
+    /// Whether the next statement has the form `c = ...` or `c[0] = ...`.
+    fn is_measure_assignment(&self) -> bool {
+        let kinds = self.tokens[self.pos..]
+            .iter()
+            .map(|token| token.kind)
+            .take(5)
+            .collect::<Vec<_>>();
+        matches!(
+            kinds.as_slice(),
+            [TokenKind::Ident, TokenKind::Eq, ..]
+                | [
+                    TokenKind::Ident,
+                    TokenKind::Open(Delim::Bracket),
+                    TokenKind::Int,
+                    TokenKind::Close(Delim::Bracket),
+                    TokenKind::Eq
+                ]
+        )
+    }
+
+    fn gate_def(&mut self) -> Result<GateDef, Error> {
+        let name = self.ident()?;
+        let params = if self.eat(TokenKind::Open(Delim::Paren)) {
+            self.comma_separated(TokenKind::Close(Delim::Paren), Self::ident)?
+        } else {
+            Vec::new()
+        };
+        let qubits = self.comma_separated(TokenKind::Open(Delim::Brace), Self::ident)?;
+        let mut body = Vec::new();
+        while !self.eat(TokenKind::Close(Delim::Brace)) {
+            if self.eat_keyword("barrier") {
+                self.comma_separated(TokenKind::Semi, Self::operand)?;
+            } else {
+                body.push(self.gate_call()?);
+            }
+        }
+        Ok(GateDef {
+            name,
+            params,
+            qubits,
+            body,
+        })
+    }
+
+    /// Parses items separated by commas until the `end` token, which is consumed.
+    fn comma_separated<T>(
+        &mut self,
+        end: TokenKind,
+        mut item: impl FnMut(&mut Self) -> Result<T, Error>,
+    ) -> Result<Vec<T>, Error> {
+        let mut items = Vec::new();
+        while !self.eat(end) {
+            items.push(item(self)?);
+            if !self.eat(TokenKind::Comma) {
+                self.expect(end)?;
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    fn gate_call(&mut self) -> Result<GateCall, Error> {
+        let lo = self.peek().span.lo;
+        let mut modifiers = Vec::new();
+        loop {
+            let token = self.peek();
+            let modifier = match self.peek_text() {
+                "inv" => {
+                    self.advance();
+                    Modifier::Inv
+                }
+                "ctrl" => {
+                    self.advance();
+                    if self.eat(TokenKind::Open(Delim::Paren)) {
+                        let count = self.int()?;
+                        self.expect(TokenKind::Close(Delim::Paren))?;
+                        Modifier::Ctrl(count)
+                    } else {
+                        Modifier::Ctrl(1)
+                    }
+                }
+                "negctrl" | "pow" => {
+                    return Err(Error::Unsupported(
+                        format!("`{}` modifiers", self.peek_text()),
+                        token.span,
+                    ))
+                }
+                _ => break,
+            };
+            self.expect(TokenKind::At)?;
+            modifiers.push(modifier);
+        }
+        let name = self.ident()?;
+        let args = if self.eat(TokenKind::Open(Delim::Paren)) {
+            self.comma_separated(TokenKind::Close(Delim::Paren), Self::expr)?
+        } else {
+            Vec::new()
+        };
+        let qubits = self.comma_separated(TokenKind::Semi, Self::operand)?;
+        let hi = self.tokens[self.pos - 1].span.hi;
+        Ok(GateCall {
+            modifiers,
+            name,
+            args,
+            qubits,
+            span: Span { lo, hi },
+        })
+    }
+
+    fn operand(&mut self) -> Result<Operand, Error> {
+        let name = self.ident()?;
+        let index = if self.peek().kind == TokenKind::Open(Delim::Bracket) {
+            Some(self.size()?)
+        } else {
+            None
+        };
+        let hi = self.tokens[self.pos - 1].span.hi;
+        let span = Span {
+            lo: name.span.lo,
+            hi,
+        };
+        Ok(Operand { name, index, span })
+    }
+
+    fn expr(&mut self) -> Result<Expr, Error> {
+        self.expr_op(0)
+    }
+
+    /// Parses a binary expression whose operators bind at least as tightly as `min_prec`.
+    fn expr_op(&mut self, min_prec: u8) -> Result<Expr, Error> {
+        let mut lhs = if self.eat(TokenKind::Minus) {
+            Expr::Neg(Box::new(self.expr_op(PREFIX_PREC)?))
+        } else {
+            self.expr_base()?
+        };
+        loop {
+            let (op, prec) = match self.peek().kind {
+                TokenKind::Plus => (BinOp::Add, 1),
+                TokenKind::Minus => (BinOp::Sub, 1),
+                TokenKind::Star => (BinOp::Mul, 2),
+                TokenKind::Slash => (BinOp::Div, 2),
+                TokenKind::Caret => (BinOp::Pow, 4),
+                _ => break,
+            };
+            if prec < min_prec {
+                break;
+            }
+            self.advance();
+            // Exponentiation is right-associative.
+            let next_prec = if matches!(op, BinOp::Pow) {
+                prec
+            } else {
+                prec + 1
+            };
+            let rhs = self.expr_op(next_prec)?;
+            lhs = Expr::BinOp(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn expr_base(&mut self) -> Result<Expr, Error> {
+        let token = self.peek();
+        match token.kind {
+            TokenKind::Int | TokenKind::Float => {
+                self.advance();
+                let value = self.input[token.span]
+                    .parse()
+                    .map_err(|_| Error::InvalidFloat(token.span))?;
+                Ok(Expr::Lit(value))
+            }
+            TokenKind::Ident => {
+                let ident = self.ident()?;
+                if self.eat(TokenKind::Open(Delim::Paren)) {
+                    let arg = self.expr()?;
+                    self.expect(TokenKind::Close(Delim::Paren))?;
+                    Ok(Expr::Call(ident, Box::new(arg)))
+                } else {
+                    Ok(Expr::Ident(ident))
+                }
+            }
+            TokenKind::Open(Delim::Paren) => {
+                self.advance();
+                let expr = self.expr()?;
+                self.expect(TokenKind::Close(Delim::Paren))?;
+                Ok(expr)
+            }
+            _ => Err(self.unexpected("expression")),
+        }
+    }
+}
+
+/// Unary minus binds tighter than multiplication but looser than exponentiation.
+const PREFIX_PREC: u8 = 3;
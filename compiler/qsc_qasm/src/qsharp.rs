@@ -0,0 +1,673 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Generates Q# source from a parsed OpenQASM program.
+//!
+//! Standard gates map to Q# operations that implement them exactly, including their global phase,
+//! so that they stay correct under `Controlled`. Statements that apply to whole registers are
+//! unrolled into one call per register element.
+
+use crate::{
+    parse::{BinOp, Expr, GateCall, GateDef, Ident, Modifier, Operand, Program, Stmt},
+    Error,
+};
+use qsc_data_structures::span::Span;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+pub(crate) fn generate(
+    program: &Program,
+    namespace: &str,
+    operation: &str,
+) -> Result<String, Error> {
+    let mut generator = Generator {
+        indent: 2,
+        ..Generator::default()
+    };
+    for stmt in &program.stmts {
+        generator.stmt(stmt)?;
+    }
+    // Qubits must be in the zero state when they are released.
+    let resets = generator
+        .qubit_regs
+        .iter()
+        .map(|(name, size)| {
+            let reset = if size.is_some() { "ResetAll" } else { "Reset" };
+            format!("{reset}({});", escape(name))
+        })
+        .collect::<Vec<_>>();
+    for reset in resets {
+        generator.line(reset);
+    }
+
+    let outputs = generator
+        .bit_regs
+        .iter()
+        .map(|(name, size)| {
+            let ty = if size.is_some() { "Result[]" } else { "Result" };
+            (escape(name), ty)
+        })
+        .collect::<Vec<_>>();
+    let output_ty = match outputs.as_slice() {
+        [] => "Unit".to_string(),
+        [(_, ty)] => (*ty).to_string(),
+        _ => format!(
+            "({})",
+            outputs
+                .iter()
+                .map(|(_, ty)| *ty)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    match outputs.as_slice() {
+        [] => {}
+        [(name, _)] => generator.line(name),
+        _ => generator.line(format!(
+            "({})",
+            outputs
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+
+    let mut qsharp = String::new();
+    let _ = writeln!(qsharp, "namespace {namespace} {{");
+    qsharp.push_str("    open Microsoft.Quantum.Convert;\n    open Microsoft.Quantum.Math;\n\n");
+    for (name, def) in HELPERS {
+        if generator.helpers.contains(name) {
+            qsharp.push_str(def);
+        }
+    }
+    for gate_def in &generator.gate_defs {
+        qsharp.push_str(gate_def);
+    }
+    let _ = writeln!(qsharp, "    operation {operation}() : {output_ty} {{");
+    for line in &generator.lines {
+        let _ = writeln!(qsharp, "{line}");
+    }
+    qsharp.push_str("    }\n}\n");
+    Ok(qsharp)
+}
+
+/// The operations generated for standard gates that have no exact Q# equivalent, as
+/// (name, definition) in the order they are generated in.
+const HELPERS: &[(&str, &str)] = &[("U", U_DEF), ("CU", CU_DEF), ("SX", SX_DEF)];
+
+/// The general single-qubit gate `U` of OpenQASM. Using `R1` rather than `Rz` for the outer
+/// rotations includes the global phase of `U`.
+const U_DEF: &str = "    operation U(theta : Double, phi : Double, lambda : Double, qubit : Qubit) : Unit is Adj + Ctl {
+        R1(lambda, qubit);
+        Ry(theta, qubit);
+        R1(phi, qubit);
+    }
+
+";
+
+/// The controlled `U` gate of `stdgates.inc`, with an additional phase on the control.
+const CU_DEF: &str = "    operation CU(theta : Double, phi : Double, lambda : Double, gamma : Double, control : Qubit, target : Qubit) : Unit is Adj + Ctl {
+        R1(gamma, control);
+        Controlled U([control], (theta, phi, lambda, target));
+    }
+
+";
+
+/// The square root of `X`.
+const SX_DEF: &str = "    operation SX(qubit : Qubit) : Unit is Adj + Ctl {
+        H(qubit);
+        S(qubit);
+        H(qubit);
+    }
+
+";
+
+/// How to apply a gate in Q#.
+struct Gate {
+    /// The Q# callable, including any functors.
+    callable: String,
+    /// The number of leading qubit arguments that are passed as controls.
+    controls: usize,
+    /// The number of classical parameters the gate takes in OpenQASM.
+    params: usize,
+    /// The total number of qubit arguments, including controls.
+    qubits: usize,
+    /// An argument that is passed to the callable before the gate parameters.
+    leading_arg: Option<&'static str>,
+}
+
+/// The standard gates of `qelib1.inc` and `stdgates.inc`, as
+/// (name, Q# callable, controls, parameters, qubits).
+const STD_GATES: &[(&str, &str, usize, usize, usize)] = &[
+    ("id", "I", 0, 0, 1),
+    ("x", "X", 0, 0, 1),
+    ("y", "Y", 0, 0, 1),
+    ("z", "Z", 0, 0, 1),
+    ("h", "H", 0, 0, 1),
+    ("s", "S", 0, 0, 1),
+    ("sdg", "Adjoint S", 0, 0, 1),
+    ("t", "T", 0, 0, 1),
+    ("tdg", "Adjoint T", 0, 0, 1),
+    ("sx", "SX", 0, 0, 1),
+    ("sxdg", "Adjoint SX", 0, 0, 1),
+    ("rx", "Rx", 0, 1, 1),
+    ("ry", "Ry", 0, 1, 1),
+    ("rz", "Rz", 0, 1, 1),
+    ("p", "R1", 0, 1, 1),
+    ("phase", "R1", 0, 1, 1),
+    ("u1", "R1", 0, 1, 1),
+    ("U", "U", 0, 3, 1),
+    ("u", "U", 0, 3, 1),
+    ("u3", "U", 0, 3, 1),
+    ("CX", "CNOT", 0, 0, 2),
+    ("cx", "CNOT", 0, 0, 2),
+    ("cy", "CY", 0, 0, 2),
+    ("cz", "CZ", 0, 0, 2),
+    ("ch", "H", 1, 0, 2),
+    ("crx", "Rx", 1, 1, 2),
+    ("cry", "Ry", 1, 1, 2),
+    ("crz", "Rz", 1, 1, 2),
+    ("cp", "R1", 1, 1, 2),
+    ("cphase", "R1", 1, 1, 2),
+    ("cu1", "R1", 1, 1, 2),
+    ("cu3", "U", 1, 3, 2),
+    ("cu", "CU", 0, 4, 2),
+    ("csx", "SX", 1, 0, 2),
+    ("swap", "SWAP", 0, 0, 2),
+    ("rxx", "Rxx", 0, 1, 2),
+    ("ryy", "Ryy", 0, 1, 2),
+    ("rzz", "Rzz", 0, 1, 2),
+    ("ccx", "CCNOT", 0, 0, 3),
+    ("cswap", "SWAP", 1, 0, 3),
+];
+
+#[derive(Default)]
+struct Generator {
+    /// The declared qubit registers with their sizes, or `None` for single qubits.
+    qubit_regs: Vec<(String, Option<u32>)>,
+    /// The declared classical registers with their sizes, or `None` for single bits.
+    bit_regs: Vec<(String, Option<u32>)>,
+    /// The user-defined gates with their number of parameters and qubits.
+    gates: BTreeMap<String, (usize, usize)>,
+    gate_defs: Vec<String>,
+    /// The names of the helper operations that the generated code uses.
+    helpers: BTreeSet<&'static str>,
+    lines: Vec<String>,
+    indent: usize,
+}
+
+impl Generator {
+    fn line(&mut self, line: impl AsRef<str>) {
+        self.lines.push(format!(
+            "{:width$}{}",
+            "",
+            line.as_ref(),
+            width = self.indent * 4
+        ));
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) -> Result<(), Error> {
+        match stmt {
+            Stmt::QubitDecl(name, size) => {
+                self.declare(name)?;
+                self.qubit_regs.push((name.name.clone(), *size));
+                let init = match size {
+                    Some(size) => format!("Qubit[{size}]"),
+                    None => "Qubit()".to_string(),
+                };
+                self.line(format!("use {} = {init};", escape(&name.name)));
+            }
+            Stmt::BitDecl(name, size) => {
+                self.declare(name)?;
+                self.bit_regs.push((name.name.clone(), *size));
+                let init = match size {
+                    Some(size) => format!("[Zero, size = {size}]"),
+                    None => "Zero".to_string(),
+                };
+                self.line(format!("mutable {} = {init};", escape(&name.name)));
+            }
+            Stmt::GateDef(gate_def) => self.gate_def(gate_def)?,
+            Stmt::GateCall(call) => {
+                let qubits = call
+                    .qubits
+                    .iter()
+                    .map(|qubit| Self::register(&self.qubit_regs, qubit))
+                    .collect::<Result<Vec<_>, _>>()?;
+                for i in 0..broadcast_count(&qubits, call.span)? {
+                    let qubits = qubits
+                        .iter()
+                        .map(|qubit| qubit.element(i))
+                        .collect::<Vec<_>>();
+                    let call = self.gate_call(call, &qubits, &[])?;
+                    self.line(call);
+                }
+            }
+            Stmt::Measure(qubit, target) => {
+                let span = target.as_ref().map_or(qubit.span, |target| Span {
+                    lo: qubit.span.lo.min(target.span.lo),
+                    hi: qubit.span.hi.max(target.span.hi),
+                });
+                let qubit = Self::register(&self.qubit_regs, qubit)?;
+                let target = target
+                    .as_ref()
+                    .map(|target| Self::register(&self.bit_regs, target))
+                    .transpose()?;
+                let registers = [Some(qubit.clone()), target.clone()];
+                for i in 0..broadcast_count(registers.iter().flatten(), span)? {
+                    let measure = format!("M({})", qubit.element(i));
+                    match &target {
+                        None => self.line(format!("let _ = {measure};")),
+                        Some(target) if target.size.is_none() => {
+                            self.line(format!("set {} = {measure};", target.name));
+                        }
+                        Some(target) => self.line(format!(
+                            "set {} w/= {} <- {measure};",
+                            target.name,
+                            target.index.unwrap_or(i)
+                        )),
+                    }
+                }
+            }
+            Stmt::Reset(qubit) => {
+                let qubit = Self::register(&self.qubit_regs, qubit)?;
+                for i in 0..broadcast_count([&qubit], qubit.span)? {
+                    self.line(format!("Reset({});", qubit.element(i)));
+                }
+            }
+            Stmt::If(bits, value, body) => {
+                let span = bits.span;
+                let bits = Self::register(&self.bit_regs, bits)?;
+                let condition = if bits.size.is_some() && bits.index.is_none() {
+                    format!("ResultArrayAsInt({}) == {value}", bits.name)
+                } else {
+                    let result = match value {
+                        0 => "Zero",
+                        1 => "One",
+                        _ => return Err(Error::BitComparison(span)),
+                    };
+                    format!("{} == {result}", bits.element(0))
+                };
+                self.line(format!("if {condition} {{"));
+                self.indent += 1;
+                for stmt in body {
+                    self.stmt(stmt)?;
+                }
+                self.indent -= 1;
+                self.line("}");
+            }
+        }
+        Ok(())
+    }
+
+    fn declare(&self, name: &Ident) -> Result<(), Error> {
+        if self
+            .qubit_regs
+            .iter()
+            .chain(&self.bit_regs)
+            .any(|(reg, _)| *reg == name.name)
+        {
+            Err(Error::Redefined(name.name.clone(), name.span))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Resolves an operand to a declared register.
+    fn register(regs: &[(String, Option<u32>)], operand: &Operand) -> Result<Register, Error> {
+        let name = &operand.name.name;
+        let size = regs
+            .iter()
+            .find_map(|(reg, size)| (reg == name).then_some(*size))
+            .ok_or_else(|| Error::UndefinedRegister(name.clone(), operand.name.span))?;
+        match (size, operand.index) {
+            (None, Some(_)) => return Err(Error::NotIndexable(name.clone(), operand.span)),
+            (Some(size), Some(index)) if index >= size => {
+                return Err(Error::IndexOutOfRange(
+                    name.clone(),
+                    index,
+                    size,
+                    operand.span,
+                ))
+            }
+            _ => {}
+        }
+        Ok(Register {
+            name: escape(name),
+            size,
+            index: operand.index,
+            span: operand.span,
+        })
+    }
+
+    fn gate_def(&mut self, gate_def: &GateDef) -> Result<(), Error> {
+        let name = &gate_def.name;
+        if self.gates.contains_key(&name.name) {
+            return Err(Error::Redefined(name.name.clone(), name.span));
+        }
+        let params = gate_def
+            .params
+            .iter()
+            .map(|param| param.name.as_str())
+            .collect::<Vec<_>>();
+        let mut body = Vec::new();
+        for call in &gate_def.body {
+            let qubits = call
+                .qubits
+                .iter()
+                .map(|qubit| {
+                    if qubit.index.is_some() {
+                        Err(Error::NotIndexable(qubit.name.name.clone(), qubit.span))
+                    } else if gate_def.qubits.iter().any(|q| q.name == qubit.name.name) {
+                        Ok(escape(&qubit.name.name))
+                    } else {
+                        Err(Error::UndefinedRegister(
+                            qubit.name.name.clone(),
+                            qubit.name.span,
+                        ))
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            body.push(self.gate_call(call, &qubits, &params)?);
+        }
+
+        let mut def = String::new();
+        let inputs = gate_def
+            .params
+            .iter()
+            .map(|param| format!("{} : Double", escape(&param.name)))
+            .chain(
+                gate_def
+                    .qubits
+                    .iter()
+                    .map(|qubit| format!("{} : Qubit", escape(&qubit.name))),
+            )
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = writeln!(
+            def,
+            "    operation {}({inputs}) : Unit is Adj + Ctl {{",
+            escape(&name.name)
+        );
+        for call in body {
+            let _ = writeln!(def, "        {call}");
+        }
+        def.push_str("    }\n\n");
+        self.gate_defs.push(def);
+        self.gates.insert(
+            name.name.clone(),
+            (gate_def.params.len(), gate_def.qubits.len()),
+        );
+        Ok(())
+    }
+
+    fn resolve_gate(&mut self, name: &Ident) -> Result<Gate, Error> {
+        if let Some(&(params, qubits)) = self.gates.get(&name.name) {
+            return Ok(Gate {
+                callable: escape(&name.name),
+                controls: 0,
+                params,
+                qubits,
+                leading_arg: None,
+            });
+        }
+        if name.name == "u2" {
+            self.helpers.insert("U");
+            return Ok(Gate {
+                callable: "U".to_string(),
+                controls: 0,
+                params: 2,
+                qubits: 1,
+                leading_arg: Some("PI() / 2.0"),
+            });
+        }
+        let &(_, callable, controls, params, qubits) = STD_GATES
+            .iter()
+            .find(|(std_name, ..)| *std_name == name.name)
+            .ok_or_else(|| Error::UndefinedGate(name.name.clone(), name.span))?;
+        let helper = callable.trim_start_matches("Adjoint ");
+        if let Some(&(helper, _)) = HELPERS.iter().find(|(name, _)| *name == helper) {
+            self.helpers.insert(helper);
+            if helper == "CU" {
+                self.helpers.insert("U");
+            }
+        }
+        Ok(Gate {
+            callable: callable.to_string(),
+            controls,
+            params,
+            qubits,
+            leading_arg: None,
+        })
+    }
+
+    /// Generates the Q# call for a gate applied to the given qubits, where `params` are
+    /// the names of the parameters of the enclosing gate definition.
+    fn gate_call(
+        &mut self,
+        call: &GateCall,
+        qubits: &[String],
+        params: &[&str],
+    ) -> Result<String, Error> {
+        let gate = self.resolve_gate(&call.name)?;
+        if call.args.len() != gate.params {
+            return Err(Error::ParamCount(
+                call.name.name.clone(),
+                gate.params,
+                call.args.len(),
+                call.span,
+            ));
+        }
+        let mut controls = gate.controls;
+        let mut adjoint = false;
+        let mut expected_qubits = gate.qubits;
+        for modifier in &call.modifiers {
+            match modifier {
+                Modifier::Inv => adjoint = !adjoint,
+                Modifier::Ctrl(count) => {
+                    controls += *count as usize;
+                    expected_qubits += *count as usize;
+                }
+            }
+        }
+        if qubits.len() != expected_qubits {
+            return Err(Error::QubitCount(
+                call.name.name.clone(),
+                expected_qubits,
+                qubits.len(),
+                call.span,
+            ));
+        }
+
+        let mut args = gate
+            .leading_arg
+            .map(ToString::to_string)
+            .into_iter()
+            .collect::<Vec<_>>();
+        for arg in &call.args {
+            args.push(expr(arg, params, false)?);
+        }
+        args.extend(qubits[controls..].iter().cloned());
+
+        let mut callable = gate.callable;
+        if adjoint {
+            callable = format!("Adjoint {callable}");
+        }
+        Ok(if controls == 0 {
+            format!("{callable}({});", args.join(", "))
+        } else {
+            let args = if args.len() == 1 {
+                args.remove(0)
+            } else {
+                format!("({})", args.join(", "))
+            };
+            format!(
+                "Controlled {callable}([{}], {args});",
+                qubits[..controls].join(", ")
+            )
+        })
+    }
+}
+
+/// A resolved register operand.
+#[derive(Clone)]
+struct Register {
+    name: String,
+    size: Option<u32>,
+    index: Option<u32>,
+    span: Span,
+}
+
+impl Register {
+    fn element(&self, i: u32) -> String {
+        match (self.size, self.index) {
+            (None, _) => self.name.clone(),
+            (Some(_), Some(index)) => format!("{}[{index}]", self.name),
+            (Some(_), None) => format!("{}[{i}]", self.name),
+        }
+    }
+}
+
+/// Returns how many times a statement is applied, which is the size of the whole registers
+/// among its operands, or one if every operand is a single element.
+fn broadcast_count<'a>(
+    registers: impl IntoIterator<Item = &'a Register>,
+    span: Span,
+) -> Result<u32, Error> {
+    let mut count = None;
+    for register in registers {
+        if let (Some(size), None) = (register.size, register.index) {
+            if count.is_some_and(|count| count != size) {
+                return Err(Error::SizeMismatch(span));
+            }
+            count = Some(size);
+        }
+    }
+    Ok(count.unwrap_or(1))
+}
+
+fn expr(expr_: &Expr, params: &[&str], nested: bool) -> Result<String, Error> {
+    Ok(match expr_ {
+        Expr::Lit(value) => fmt_double(*value),
+        Expr::Ident(ident) => match ident.name.as_str() {
+            "pi" | "π" => "PI()".to_string(),
+            "tau" | "τ" => "2.0 * PI()".to_string(),
+            "euler" | "ℇ" => "E()".to_string(),
+            name if params.contains(&name) => escape(name),
+            name => return Err(Error::UndefinedIdent(name.to_string(), ident.span)),
+        },
+        Expr::Neg(operand) => format!("-{}", expr(operand, params, true)?),
+        Expr::BinOp(op, lhs, rhs) => {
+            let op = match op {
+                BinOp::Add => "+",
+                BinOp::Sub => "-",
+                BinOp::Mul => "*",
+                BinOp::Div => "/",
+                BinOp::Pow => "^",
+            };
+            let expr = format!(
+                "{} {op} {}",
+                expr(lhs, params, true)?,
+                expr(rhs, params, true)?
+            );
+            if nested {
+                format!("({expr})")
+            } else {
+                expr
+            }
+        }
+        Expr::Call(function, arg) => {
+            let arg = expr(arg, params, false)?;
+            match function.name.as_str() {
+                "sin" => format!("Sin({arg})"),
+                "cos" => format!("Cos({arg})"),
+                "tan" => format!("Tan({arg})"),
+                "arcsin" => format!("ArcSin({arg})"),
+                "arccos" => format!("ArcCos({arg})"),
+                "arctan" => format!("ArcTan({arg})"),
+                "exp" => format!("E() ^ ({arg})"),
+                "ln" => format!("Log({arg})"),
+                "sqrt" => format!("Sqrt({arg})"),
+                name => return Err(Error::UndefinedIdent(name.to_string(), function.span)),
+            }
+        }
+    })
+}
+
+/// Formats a literal with a decimal point or an exponent, so that Q# parses it as a `Double`
+/// rather than an `Int`.
+fn fmt_double(value: f64) -> String {
+    format!("{value:?}")
+}
+
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+        && !Q_SHARP_KEYWORDS.contains(&name)
+}
+
+/// Escapes OpenQASM identifiers that are reserved words in Q#.
+fn escape(name: &str) -> String {
+    if Q_SHARP_KEYWORDS.contains(&name) {
+        format!("{name}_")
+    } else {
+        name.to_string()
+    }
+}
+
+const Q_SHARP_KEYWORDS: &[&str] = &[
+    "Adj",
+    "adjoint",
+    "Adjoint",
+    "and",
+    "apply",
+    "as",
+    "auto",
+    "body",
+    "borrow",
+    "controlled",
+    "Controlled",
+    "Ctl",
+    "distribute",
+    "elif",
+    "else",
+    "fail",
+    "false",
+    "fixup",
+    "for",
+    "function",
+    "if",
+    "in",
+    "internal",
+    "intrinsic",
+    "invert",
+    "is",
+    "let",
+    "mutable",
+    "namespace",
+    "newtype",
+    "not",
+    "One",
+    "open",
+    "operation",
+    "or",
+    "PauliI",
+    "PauliX",
+    "PauliY",
+    "PauliZ",
+    "repeat",
+    "return",
+    "self",
+    "set",
+    "true",
+    "until",
+    "use",
+    "while",
+    "within",
+    "Zero",
+];
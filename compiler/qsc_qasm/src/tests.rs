@@ -0,0 +1,424 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{is_identifier, qasm_to_qsharp};
+use expect_test::{expect, Expect};
+
+fn check(source: &str, expect: &Expect) {
+    match qasm_to_qsharp(source, "Qasm", "Circuit") {
+        Ok(qsharp) => expect.assert_eq(&qsharp),
+        Err(error) => expect.assert_debug_eq(&error),
+    }
+}
+
+#[test]
+fn qasm2_bell() {
+    check(
+        r#"
+        OPENQASM 2.0;
+        include "qelib1.inc";
+        qreg q[2];
+        creg c[2];
+        h q[0];
+        cx q[0], q[1];
+        barrier q;
+        measure q -> c;
+        "#,
+        &expect![[r"
+            namespace Qasm {
+                open Microsoft.Quantum.Convert;
+                open Microsoft.Quantum.Math;
+
+                operation Circuit() : Result[] {
+                    use q = Qubit[2];
+                    mutable c = [Zero, size = 2];
+                    H(q[0]);
+                    CNOT(q[0], q[1]);
+                    set c w/= 0 <- M(q[0]);
+                    set c w/= 1 <- M(q[1]);
+                    ResetAll(q);
+                    c
+                }
+            }
+        "]],
+    );
+}
+
+#[test]
+fn qasm3_declarations_and_measurement() {
+    check(
+        r#"
+        OPENQASM 3;
+        include "stdgates.inc";
+        qubit q;
+        qubit[2] r;
+        bit b;
+        bit[2] c;
+        x q;
+        b = measure q;
+        c[1] = measure r[0];
+        c = measure r;
+        "#,
+        &expect![[r"
+            namespace Qasm {
+                open Microsoft.Quantum.Convert;
+                open Microsoft.Quantum.Math;
+
+                operation Circuit() : (Result, Result[]) {
+                    use q = Qubit();
+                    use r = Qubit[2];
+                    mutable b = Zero;
+                    mutable c = [Zero, size = 2];
+                    X(q);
+                    set b = M(q);
+                    set c w/= 1 <- M(r[0]);
+                    set c w/= 0 <- M(r[0]);
+                    set c w/= 1 <- M(r[1]);
+                    Reset(q);
+                    ResetAll(r);
+                    (b, c)
+                }
+            }
+        "]],
+    );
+}
+
+#[test]
+fn gate_definitions_and_parameters() {
+    check(
+        r#"
+        OPENQASM 2.0;
+        include "qelib1.inc";
+        gate rot(theta, phi) a, b {
+            rz(theta / 2) a;
+            cx a, b;
+            u3(theta, -phi, pi ^ 2) b;
+        }
+        qreg q[2];
+        rot(pi/4, 2 * cos(0.5)) q[1], q[0];
+        u2(0, pi) q[0];
+        "#,
+        &expect![[r"
+            namespace Qasm {
+                open Microsoft.Quantum.Convert;
+                open Microsoft.Quantum.Math;
+
+                operation U(theta : Double, phi : Double, lambda : Double, qubit : Qubit) : Unit is Adj + Ctl {
+                    R1(lambda, qubit);
+                    Ry(theta, qubit);
+                    R1(phi, qubit);
+                }
+
+                operation rot(theta : Double, phi : Double, a : Qubit, b : Qubit) : Unit is Adj + Ctl {
+                    Rz(theta / 2.0, a);
+                    CNOT(a, b);
+                    U(theta, -phi, PI() ^ 2.0, b);
+                }
+
+                operation Circuit() : Unit {
+                    use q = Qubit[2];
+                    rot(PI() / 4.0, 2.0 * Cos(0.5), q[1], q[0]);
+                    U(PI() / 2.0, 0.0, PI(), q[0]);
+                    ResetAll(q);
+                }
+            }
+        "]],
+    );
+}
+
+#[test]
+fn broadcast_and_modifiers() {
+    check(
+        r"
+        OPENQASM 3.0;
+        qubit[2] a;
+        qubit[2] b;
+        qubit c;
+        h a;
+        cx a, b;
+        inv @ s c;
+        ctrl @ rx(0.5) a[0], c;
+        ctrl(2) @ inv @ t a[0], a[1], c;
+        ctrl @ cp(1.5) a[0], b[0], c;
+        reset a;
+        ",
+        &expect![[r"
+            namespace Qasm {
+                open Microsoft.Quantum.Convert;
+                open Microsoft.Quantum.Math;
+
+                operation Circuit() : Unit {
+                    use a = Qubit[2];
+                    use b = Qubit[2];
+                    use c = Qubit();
+                    H(a[0]);
+                    H(a[1]);
+                    CNOT(a[0], b[0]);
+                    CNOT(a[1], b[1]);
+                    Adjoint S(c);
+                    Controlled Rx([a[0]], (0.5, c));
+                    Controlled Adjoint T([a[0], a[1]], c);
+                    Controlled R1([a[0], b[0]], (1.5, c));
+                    Reset(a[0]);
+                    Reset(a[1]);
+                    ResetAll(a);
+                    ResetAll(b);
+                    Reset(c);
+                }
+            }
+        "]],
+    );
+}
+
+#[test]
+fn classical_conditions() {
+    check(
+        r#"
+        OPENQASM 2.0;
+        include "qelib1.inc";
+        qreg q[2];
+        creg c[2];
+        measure q[0] -> c[0];
+        if (c == 1) x q[1];
+        if (c[0] == 0) { z q[1]; h q[1]; }
+        "#,
+        &expect![[r"
+            namespace Qasm {
+                open Microsoft.Quantum.Convert;
+                open Microsoft.Quantum.Math;
+
+                operation Circuit() : Result[] {
+                    use q = Qubit[2];
+                    mutable c = [Zero, size = 2];
+                    set c w/= 0 <- M(q[0]);
+                    if ResultArrayAsInt(c) == 1 {
+                        X(q[1]);
+                    }
+                    if c[0] == Zero {
+                        Z(q[1]);
+                        H(q[1]);
+                    }
+                    ResetAll(q);
+                    c
+                }
+            }
+        "]],
+    );
+}
+
+#[test]
+fn reserved_names_are_escaped() {
+    check(
+        "qreg in[1]; gate is a { x a; } is in[0];",
+        &expect![[r"
+            namespace Qasm {
+                open Microsoft.Quantum.Convert;
+                open Microsoft.Quantum.Math;
+
+                operation is_(a : Qubit) : Unit is Adj + Ctl {
+                    X(a);
+                }
+
+                operation Circuit() : Unit {
+                    use in_ = Qubit[1];
+                    is_(in_[0]);
+                    ResetAll(in_);
+                }
+            }
+        "]],
+    );
+}
+
+#[test]
+fn unsupported_version() {
+    check(
+        "OPENQASM 1.0;",
+        &expect![[r#"
+            UnsupportedVersion(
+                "1.0",
+                Span {
+                    lo: 9,
+                    hi: 12,
+                },
+            )
+        "#]],
+    );
+}
+
+#[test]
+fn unsupported_include() {
+    check(
+        r#"include "mygates.inc";"#,
+        &expect![[r#"
+            UnsupportedInclude(
+                "mygates.inc",
+                Span {
+                    lo: 8,
+                    hi: 21,
+                },
+            )
+        "#]],
+    );
+}
+
+#[test]
+fn unsupported_statement() {
+    check(
+        "qubit q; int x = 1;",
+        &expect![[r#"
+            Unsupported(
+                "`int` statements",
+                Span {
+                    lo: 9,
+                    hi: 12,
+                },
+            )
+        "#]],
+    );
+}
+
+#[test]
+fn undefined_gate() {
+    check(
+        "qubit q; foo q;",
+        &expect![[r#"
+            UndefinedGate(
+                "foo",
+                Span {
+                    lo: 9,
+                    hi: 12,
+                },
+            )
+        "#]],
+    );
+}
+
+#[test]
+fn wrong_qubit_count() {
+    check(
+        "qubit[2] q; ctrl @ x q[0];",
+        &expect![[r#"
+            QubitCount(
+                "x",
+                2,
+                1,
+                Span {
+                    lo: 12,
+                    hi: 26,
+                },
+            )
+        "#]],
+    );
+}
+
+#[test]
+fn index_out_of_range() {
+    check(
+        "qreg q[2]; h q[2];",
+        &expect![[r#"
+            IndexOutOfRange(
+                "q",
+                2,
+                2,
+                Span {
+                    lo: 13,
+                    hi: 17,
+                },
+            )
+        "#]],
+    );
+}
+
+#[test]
+fn mismatched_broadcast() {
+    check(
+        "qreg a[2]; qreg b[3]; cx a, b;",
+        &expect![[r"
+            SizeMismatch(
+                Span {
+                    lo: 22,
+                    hi: 30,
+                },
+            )
+        "]],
+    );
+}
+
+#[test]
+fn missing_semicolon() {
+    check(
+        "qreg q[1]\nh q[0];",
+        &expect![[r#"
+            Token(
+                "`;`",
+                "identifier",
+                Span {
+                    lo: 10,
+                    hi: 11,
+                },
+            )
+        "#]],
+    );
+}
+
+#[test]
+fn controlled_and_square_root_gates() {
+    check(
+        r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        qubit[2] q;
+        sx q[0];
+        sxdg q[1];
+        csx q[0], q[1];
+        cu(0.5, 0.25, 0.125, 1e15) q[0], q[1];
+        ctrl @ u(1, 2, 3) q[1], q[0];
+        rz(2e20) q[1];
+        "#,
+        &expect![[r"
+            namespace Qasm {
+                open Microsoft.Quantum.Convert;
+                open Microsoft.Quantum.Math;
+
+                operation U(theta : Double, phi : Double, lambda : Double, qubit : Qubit) : Unit is Adj + Ctl {
+                    R1(lambda, qubit);
+                    Ry(theta, qubit);
+                    R1(phi, qubit);
+                }
+
+                operation CU(theta : Double, phi : Double, lambda : Double, gamma : Double, control : Qubit, target : Qubit) : Unit is Adj + Ctl {
+                    R1(gamma, control);
+                    Controlled U([control], (theta, phi, lambda, target));
+                }
+
+                operation SX(qubit : Qubit) : Unit is Adj + Ctl {
+                    H(qubit);
+                    S(qubit);
+                    H(qubit);
+                }
+
+                operation Circuit() : Unit {
+                    use q = Qubit[2];
+                    SX(q[0]);
+                    Adjoint SX(q[1]);
+                    Controlled SX([q[0]], q[1]);
+                    CU(0.5, 0.25, 0.125, 1000000000000000.0, q[0], q[1]);
+                    Controlled U([q[1]], (1.0, 2.0, 3.0, q[0]));
+                    Rz(2e20, q[1]);
+                    ResetAll(q);
+                }
+            }
+        "]],
+    );
+}
+
+#[test]
+fn identifiers() {
+    assert!(is_identifier("Bell"));
+    assert!(is_identifier("_bell_2"));
+    assert!(!is_identifier(""));
+    assert!(!is_identifier("2bell"));
+    assert!(!is_identifier("bell.qasm"));
+    assert!(!is_identifier("Qasm.Bell"));
+    assert!(!is_identifier("operation"));
+}
@@ -587,6 +587,15 @@ impl LocalsComputeKindMap {
         local_compute_kind.compute_kind = local_compute_kind.compute_kind.aggregate(delta);
     }
 
+    /// Counts the local variables whose value is dynamic. Since compute kinds are only ever aggregated, this count
+    /// can be used to detect whether analyzing a section of code made any local dynamic.
+    pub fn count_dynamic_locals(&self) -> usize {
+        self.0
+            .values()
+            .filter(|local_compute_kind| local_compute_kind.compute_kind.is_dynamic())
+            .count()
+    }
+
     pub fn find_local_compute_kind(&self, local_var_id: LocalVarId) -> Option<&LocalComputeKind> {
         self.0.get(local_var_id)
     }
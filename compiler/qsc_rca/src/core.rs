@@ -171,20 +171,24 @@ impl<'a> Analyzer<'a> {
         self.visit_expr(replacement_value_expr_id);
 
         // Since this is an assignment, the compute kind of the local variable (array var expression) needs to be updated.
-        // The compute kind of the update is determined by the runtime features of the replacement value expression.
+        // The compute kind of the update is determined by the runtime features of the index and replacement value
+        // expressions.
         let application_instance = self.get_current_application_instance();
+        let index_compute_kind = *application_instance.get_expr_compute_kind(index_expr_id);
         let replacement_value_compute_kind =
             *application_instance.get_expr_compute_kind(replacement_value_expr_id);
         let default_value_kind = ValueKind::Array(RuntimeKind::Static, RuntimeKind::Static);
         let mut updated_compute_kind = ComputeKind::Classical;
+        updated_compute_kind =
+            updated_compute_kind.aggregate_runtime_features(index_compute_kind, default_value_kind);
         updated_compute_kind = updated_compute_kind
             .aggregate_runtime_features(replacement_value_compute_kind, default_value_kind);
 
-        // If the replacement value expression is dynamic, the runtime features and value kind of the update have to
-        // take this into account.
-        if replacement_value_compute_kind.is_dynamic() {
+        // If either the index or the replacement value expression is dynamic, the contents of the array are dynamic
+        // since which element gets replaced, or what it gets replaced with, is only known at runtime.
+        if index_compute_kind.is_dynamic() || replacement_value_compute_kind.is_dynamic() {
             let ComputeKind::Quantum(quantum_properties) = &mut updated_compute_kind else {
-                panic!("the compute kind of the update must be quantum if the replacement value is dynamic");
+                panic!("the compute kind of the update must be quantum if the index or replacement value is dynamic");
             };
 
             let ValueKind::Array(content_runtime_value, _) = &mut quantum_properties.value_kind
@@ -210,7 +214,6 @@ impl<'a> Analyzer<'a> {
         // We do not care about the value kind for this kind of expression because it is an assignment, but we still
         // need a default one.
         let default_value_kind = ValueKind::Element(RuntimeKind::Static);
-        let index_compute_kind = *application_instance.get_expr_compute_kind(index_expr_id);
         let mut compute_kind = ComputeKind::Classical;
         compute_kind =
             compute_kind.aggregate_runtime_features(index_compute_kind, default_value_kind);
@@ -903,27 +906,42 @@ impl<'a> Analyzer<'a> {
     }
 
     fn analyze_expr_while(&mut self, condition_expr_id: ExprId, block_id: BlockId) -> ComputeKind {
-        // Visit the condition expression to determine its compute kind.
-        self.visit_expr(condition_expr_id);
-
-        // If the condition expression is dynamic, we push a new dynamic scope before visiting the block.
-        let application_instance = self.get_current_application_instance_mut();
-        let condition_expr_compute_kind =
-            *application_instance.get_expr_compute_kind(condition_expr_id);
-        let within_dynamic_scope = condition_expr_compute_kind.is_dynamic();
-        if within_dynamic_scope {
-            application_instance
-                .active_dynamic_scopes
-                .push(condition_expr_id);
-        }
-        self.visit_block(block_id);
-        if within_dynamic_scope {
+        // A local that becomes dynamic in the body of the loop is dynamic in the condition and in the body of later
+        // iterations too, so the loop is analyzed until no more locals become dynamic.
+        let mut condition_expr_compute_kind;
+        loop {
+            let dynamic_locals_count = self
+                .get_current_application_instance()
+                .locals_map
+                .count_dynamic_locals();
+
+            // Visit the condition expression to determine its compute kind.
+            self.visit_expr(condition_expr_id);
+
+            // If the condition expression is dynamic, we push a new dynamic scope before visiting the block.
             let application_instance = self.get_current_application_instance_mut();
-            let dynamic_scope_expr_id = application_instance
-                .active_dynamic_scopes
-                .pop()
-                .expect("at least one dynamic scope should exist");
-            assert!(dynamic_scope_expr_id == condition_expr_id);
+            condition_expr_compute_kind =
+                *application_instance.get_expr_compute_kind(condition_expr_id);
+            let within_dynamic_scope = condition_expr_compute_kind.is_dynamic();
+            if within_dynamic_scope {
+                application_instance
+                    .active_dynamic_scopes
+                    .push(condition_expr_id);
+            }
+            self.visit_block(block_id);
+            if within_dynamic_scope {
+                let application_instance = self.get_current_application_instance_mut();
+                let dynamic_scope_expr_id = application_instance
+                    .active_dynamic_scopes
+                    .pop()
+                    .expect("at least one dynamic scope should exist");
+                assert!(dynamic_scope_expr_id == condition_expr_id);
+            }
+
+            let application_instance = self.get_current_application_instance();
+            if application_instance.locals_map.count_dynamic_locals() == dynamic_locals_count {
+                break;
+            }
         }
 
         // Return the aggregated runtime features of the condition expression and the block.
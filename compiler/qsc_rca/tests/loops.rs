@@ -51,3 +51,29 @@ fn check_rca_for_dynamic_for_loop() {
         ],
     );
 }
+
+#[test]
+fn check_rca_for_local_updated_in_loop_whose_condition_becomes_dynamic() {
+    let mut compilation_context = CompilationContext::default();
+    compilation_context.update(
+        r#"
+        use q = Qubit();
+        mutable count = 0;
+        mutable done = false;
+        while not done {
+            set count += 1;
+            set done = M(q) == One;
+        }
+        count"#,
+    );
+    let package_store_compute_properties = compilation_context.get_compute_properties();
+    check_last_statement_compute_properties(
+        package_store_compute_properties,
+        &expect![[r#"
+            ApplicationsGeneratorSet:
+                inherent: Quantum: QuantumProperties:
+                    runtime_features: RuntimeFeatureFlags(UseOfDynamicInt)
+                    value_kind: Element(Dynamic)
+                dynamic_param_applications: <empty>"#]],
+    );
+}
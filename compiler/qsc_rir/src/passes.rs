@@ -2,9 +2,14 @@
 // Licensed under the MIT License.
 
 mod build_dominator_graph;
+mod const_fold;
+mod copy_prop;
+mod dead_code_elim;
 mod defer_meas;
+mod optimize_gates;
 mod reindex_qubits;
 mod remap_block_ids;
+mod reuse_qubits;
 mod simplify_control_flow;
 mod ssa_check;
 mod ssa_transform;
@@ -12,31 +17,56 @@ mod type_check;
 mod unreachable_code_check;
 
 use build_dominator_graph::build_dominator_graph;
+pub use const_fold::fold_constants;
+pub use copy_prop::propagate_copies;
+pub use dead_code_elim::eliminate_dead_instructions;
 use defer_meas::defer_measurements;
+pub use optimize_gates::optimize_gates;
 use qsc_data_structures::target::TargetCapabilityFlags;
 use reindex_qubits::reindex_qubits;
 use remap_block_ids::remap_block_ids;
-use simplify_control_flow::simplify_control_flow;
+pub use reuse_qubits::{reuse_qubits, QubitReuseReport};
+use simplify_control_flow::{remove_redundant_branches, simplify_control_flow};
 use ssa_check::check_ssa_form;
 use ssa_transform::transform_to_ssa;
 pub use type_check::check_types;
 pub use unreachable_code_check::check_unreachable_code;
 
-use crate::{rir::Program, utils::build_predecessors_map};
+use crate::{
+    rir::Program,
+    utils::{build_predecessors_map, remove_unreachable_callables},
+};
 
 /// Run the default set of RIR check and transformation passes.
 /// This includes:
 /// - Checking for unreachable code
 /// - Remapping block IDs
 /// - Transforming the program to SSA form
+/// - Optimizing the program
+/// - Optimizing gates, if enabled in the program configuration
+/// - Reusing qubits, if enabled in the program configuration and the target supports qubit reset
 /// - Checking that the program is in SSA form
-pub fn check_and_transform(program: &mut Program) {
+///
+/// Returns the report of the qubit reuse pass if it ran.
+pub fn check_and_transform(program: &mut Program) -> Option<QubitReuseReport> {
     simplify_control_flow(program);
     check_unreachable_code(program);
     check_types(program);
     remap_block_ids(program);
     let preds = build_predecessors_map(program);
     transform_to_ssa(program, &preds);
+    optimize(program);
+    if program.config.optimize_gates {
+        optimize_gates(program);
+    }
+    // Without qubit reset, qubit IDs are reindexed after measurement below, which would undo the reuse.
+    let reuse_report = (program.config.reuse_qubits
+        && program
+            .config
+            .capabilities
+            .contains(TargetCapabilityFlags::QubitReset))
+    .then(|| reuse_qubits(program));
+    let preds = build_predecessors_map(program);
     let doms = build_dominator_graph(program, &preds);
     check_ssa_form(program, &preds, &doms);
     check_unreachable_code(program);
@@ -55,4 +85,45 @@ pub fn check_and_transform(program: &mut Program) {
     if program.config.capabilities == TargetCapabilityFlags::empty() {
         defer_measurements(program);
     }
+
+    // Reindexing may have given reused qubits fresh IDs again, so report the final qubit count.
+    reuse_report.map(|report| QubitReuseReport {
+        qubits_after: program.num_qubits,
+        ..report
+    })
+}
+
+/// Run the RIR optimization passes on a program in SSA form until they no longer change it.
+/// This includes:
+/// - Folding constants, which may remove branches and the blocks only they reached
+/// - Propagating copies
+/// - Eliminating dead instructions
+/// - Removing branches that lead to the same block either way
+/// - Merging redundant blocks
+///
+/// Callables that are no longer called are removed, and block IDs are remapped afterwards.
+fn optimize(program: &mut Program) {
+    let size = |program: &Program| {
+        (
+            program.blocks.iter().count(),
+            program
+                .blocks
+                .values()
+                .map(|block| block.0.len())
+                .sum::<usize>(),
+        )
+    };
+    loop {
+        let size_before = size(program);
+        fold_constants(program);
+        propagate_copies(program);
+        eliminate_dead_instructions(program);
+        remove_redundant_branches(program);
+        simplify_control_flow(program);
+        if size(program) == size_before {
+            break;
+        }
+    }
+    remove_unreachable_callables(program);
+    remap_block_ids(program);
 }
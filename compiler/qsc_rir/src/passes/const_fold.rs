@@ -0,0 +1,208 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use std::cmp::Ordering;
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    rir::{ConditionCode, FcmpConditionCode, Instruction, Literal, Operand, Program, Variable},
+    utils::{get_assigned_variable, remove_unreachable_blocks, replace_variable_uses},
+};
+
+/// Folds instructions whose result is known from their literal operands into that literal, replacing the uses of the
+/// variables they assign. Branches on conditions that fold to a literal become jumps, and blocks that are no longer
+/// reachable are removed. Instructions whose result depends on runtime behavior, such as division by zero, are kept,
+/// as are floating-point instructions whose result is infinite or NaN, since such values have no literal form.
+/// The program is expected to be in SSA form.
+pub fn fold_constants(program: &mut Program) {
+    loop {
+        let mut replacements = FxHashMap::default();
+        for (_, block) in program.blocks.iter_mut() {
+            block.0.retain(|instr| match fold_instruction(instr) {
+                Some((var, literal)) => {
+                    replacements.insert(var.variable_id, Operand::Literal(literal));
+                    false
+                }
+                None => true,
+            });
+        }
+        if replacements.is_empty() {
+            break;
+        }
+        replace_variable_uses(program, &replacements);
+        remove_unreachable_blocks(program);
+    }
+}
+
+fn fold_instruction(instr: &Instruction) -> Option<(Variable, Literal)> {
+    let literal = match instr {
+        Instruction::Add(lhs, rhs, _) => fold_integers(lhs, rhs, |l, r| Some(l.wrapping_add(r)))?,
+        Instruction::Sub(lhs, rhs, _) => fold_integers(lhs, rhs, |l, r| Some(l.wrapping_sub(r)))?,
+        Instruction::Mul(lhs, rhs, _) => {
+            if is_integer(lhs, 0) || is_integer(rhs, 0) {
+                Literal::Integer(0)
+            } else {
+                fold_integers(lhs, rhs, |l, r| Some(l.wrapping_mul(r)))?
+            }
+        }
+        Instruction::Sdiv(lhs, rhs, _) => fold_integers(lhs, rhs, i64::checked_div)?,
+        Instruction::Srem(lhs, rhs, _) => fold_integers(lhs, rhs, i64::checked_rem)?,
+        Instruction::Shl(lhs, rhs, _) => {
+            fold_integers(lhs, rhs, |l, r| l.checked_shl(u32::try_from(r).ok()?))?
+        }
+        Instruction::Ashr(lhs, rhs, _) => {
+            fold_integers(lhs, rhs, |l, r| l.checked_shr(u32::try_from(r).ok()?))?
+        }
+        Instruction::Icmp(condition, lhs, rhs, _) => fold_icmp(*condition, lhs, rhs)?,
+        Instruction::Fadd(lhs, rhs, _) => fold_doubles(lhs, rhs, |l, r| l + r)?,
+        Instruction::Fsub(lhs, rhs, _) => fold_doubles(lhs, rhs, |l, r| l - r)?,
+        Instruction::Fmul(lhs, rhs, _) => fold_doubles(lhs, rhs, |l, r| l * r)?,
+        Instruction::Fdiv(lhs, rhs, _) => fold_doubles(lhs, rhs, |l, r| l / r)?,
+        Instruction::Fcmp(condition, lhs, rhs, _) => fold_fcmp(*condition, lhs, rhs)?,
+        Instruction::LogicalNot(Operand::Literal(Literal::Bool(value)), _) => Literal::Bool(!value),
+        Instruction::LogicalAnd(lhs, rhs, _) => {
+            if is_bool(lhs, false) || is_bool(rhs, false) {
+                Literal::Bool(false)
+            } else {
+                fold_bools(lhs, rhs, |l, r| l && r)?
+            }
+        }
+        Instruction::LogicalOr(lhs, rhs, _) => {
+            if is_bool(lhs, true) || is_bool(rhs, true) {
+                Literal::Bool(true)
+            } else {
+                fold_bools(lhs, rhs, |l, r| l || r)?
+            }
+        }
+        Instruction::BitwiseNot(Operand::Literal(Literal::Integer(value)), _) => {
+            Literal::Integer(!value)
+        }
+        Instruction::BitwiseAnd(lhs, rhs, _) => {
+            if is_integer(lhs, 0) || is_integer(rhs, 0) {
+                Literal::Integer(0)
+            } else {
+                fold_integers(lhs, rhs, |l, r| Some(l & r))?
+            }
+        }
+        Instruction::BitwiseOr(lhs, rhs, _) => {
+            if is_integer(lhs, -1) || is_integer(rhs, -1) {
+                Literal::Integer(-1)
+            } else {
+                fold_integers(lhs, rhs, |l, r| Some(l | r))?
+            }
+        }
+        Instruction::BitwiseXor(lhs, rhs, _) => fold_integers(lhs, rhs, |l, r| Some(l ^ r))?,
+        _ => return None,
+    };
+    let var = get_assigned_variable(instr)?;
+    Some((var, literal))
+}
+
+fn is_integer(operand: &Operand, value: i64) -> bool {
+    *operand == Operand::Literal(Literal::Integer(value))
+}
+
+fn is_bool(operand: &Operand, value: bool) -> bool {
+    *operand == Operand::Literal(Literal::Bool(value))
+}
+
+fn fold_integers(
+    lhs: &Operand,
+    rhs: &Operand,
+    op: impl FnOnce(i64, i64) -> Option<i64>,
+) -> Option<Literal> {
+    match (lhs, rhs) {
+        (Operand::Literal(Literal::Integer(lhs)), Operand::Literal(Literal::Integer(rhs))) => {
+            op(*lhs, *rhs).map(Literal::Integer)
+        }
+        _ => None,
+    }
+}
+
+fn fold_doubles(lhs: &Operand, rhs: &Operand, op: impl FnOnce(f64, f64) -> f64) -> Option<Literal> {
+    match (lhs, rhs) {
+        (Operand::Literal(Literal::Double(lhs)), Operand::Literal(Literal::Double(rhs))) => {
+            let result = op(*lhs, *rhs);
+            result.is_finite().then_some(Literal::Double(result))
+        }
+        _ => None,
+    }
+}
+
+fn fold_bools(
+    lhs: &Operand,
+    rhs: &Operand,
+    op: impl FnOnce(bool, bool) -> bool,
+) -> Option<Literal> {
+    match (lhs, rhs) {
+        (Operand::Literal(Literal::Bool(lhs)), Operand::Literal(Literal::Bool(rhs))) => {
+            Some(Literal::Bool(op(*lhs, *rhs)))
+        }
+        _ => None,
+    }
+}
+
+fn fold_icmp(condition: ConditionCode, lhs: &Operand, rhs: &Operand) -> Option<Literal> {
+    let ordering = match (lhs, rhs) {
+        (Operand::Literal(Literal::Integer(lhs)), Operand::Literal(Literal::Integer(rhs))) => {
+            lhs.cmp(rhs)
+        }
+        // Booleans are only folded for equality, since signed comparisons treat `true` as -1.
+        (Operand::Literal(Literal::Bool(lhs)), Operand::Literal(Literal::Bool(rhs)))
+            if matches!(condition, ConditionCode::Eq | ConditionCode::Ne) =>
+        {
+            lhs.cmp(rhs)
+        }
+        _ => return None,
+    };
+    let value = match condition {
+        ConditionCode::Eq => ordering == Ordering::Equal,
+        ConditionCode::Ne => ordering != Ordering::Equal,
+        ConditionCode::Slt => ordering == Ordering::Less,
+        ConditionCode::Sle => ordering != Ordering::Greater,
+        ConditionCode::Sgt => ordering == Ordering::Greater,
+        ConditionCode::Sge => ordering != Ordering::Less,
+    };
+    Some(Literal::Bool(value))
+}
+
+fn fold_fcmp(condition: FcmpConditionCode, lhs: &Operand, rhs: &Operand) -> Option<Literal> {
+    let (Operand::Literal(Literal::Double(lhs)), Operand::Literal(Literal::Double(rhs))) =
+        (lhs, rhs)
+    else {
+        return None;
+    };
+    // The comparison is unordered when either operand is NaN.
+    let ordering = lhs.partial_cmp(rhs);
+    let value = match condition {
+        FcmpConditionCode::False => false,
+        FcmpConditionCode::OrderedAndEqual => ordering == Some(Ordering::Equal),
+        FcmpConditionCode::OrderedAndGreaterThan => ordering == Some(Ordering::Greater),
+        FcmpConditionCode::OrderedAndGreaterThanOrEqual => {
+            matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+        }
+        FcmpConditionCode::OrderedAndLessThan => ordering == Some(Ordering::Less),
+        FcmpConditionCode::OrderedAndLessThanOrEqual => {
+            matches!(ordering, Some(Ordering::Less | Ordering::Equal))
+        }
+        FcmpConditionCode::OrderedAndNotEqual => {
+            matches!(ordering, Some(Ordering::Less | Ordering::Greater))
+        }
+        FcmpConditionCode::Ordered => ordering.is_some(),
+        FcmpConditionCode::UnorderedOrEqual => matches!(ordering, None | Some(Ordering::Equal)),
+        FcmpConditionCode::UnorderedOrGreaterThan => {
+            matches!(ordering, None | Some(Ordering::Greater))
+        }
+        FcmpConditionCode::UnorderedOrGreaterThanOrEqual => ordering != Some(Ordering::Less),
+        FcmpConditionCode::UnorderedOrLessThan => matches!(ordering, None | Some(Ordering::Less)),
+        FcmpConditionCode::UnorderedOrLessThanOrEqual => ordering != Some(Ordering::Greater),
+        FcmpConditionCode::UnorderedOrNotEqual => ordering != Some(Ordering::Equal),
+        FcmpConditionCode::Unordered => ordering.is_none(),
+        FcmpConditionCode::True => true,
+    };
+    Some(Literal::Bool(value))
+}
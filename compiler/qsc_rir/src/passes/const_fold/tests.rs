@@ -0,0 +1,297 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::too_many_lines, clippy::needless_raw_string_hashes)]
+
+use expect_test::expect;
+
+use crate::{
+    builder::{bool_record_decl, h_decl, int_record_decl, new_program, rx_decl, x_decl},
+    rir::{
+        Block, BlockId, CallableId, ConditionCode, FcmpConditionCode, Instruction, Literal,
+        Operand, Ty, Variable, VariableId,
+    },
+};
+
+use super::fold_constants;
+
+fn int_var(id: u32) -> Variable {
+    Variable {
+        variable_id: VariableId(id),
+        ty: Ty::Integer,
+    }
+}
+
+fn bool_var(id: u32) -> Variable {
+    Variable {
+        variable_id: VariableId(id),
+        ty: Ty::Boolean,
+    }
+}
+
+#[test]
+fn fold_constants_folds_chain_of_integer_arithmetic() {
+    const RECORD: CallableId = CallableId(1);
+    let mut program = new_program();
+    program.callables.insert(RECORD, int_record_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Add(
+                Operand::Literal(Literal::Integer(1)),
+                Operand::Literal(Literal::Integer(2)),
+                int_var(0),
+            ),
+            Instruction::Mul(
+                Operand::Variable(int_var(0)),
+                Operand::Literal(Literal::Integer(3)),
+                int_var(1),
+            ),
+            Instruction::Shl(
+                Operand::Variable(int_var(1)),
+                Operand::Literal(Literal::Integer(2)),
+                int_var(2),
+            ),
+            Instruction::Call(
+                RECORD,
+                vec![
+                    Operand::Variable(int_var(2)),
+                    Operand::Literal(Literal::Pointer),
+                ],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    fold_constants(&mut program);
+    expect![[r#"
+        Block:
+            Call id(1), args( Integer(36), Pointer, )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(0)).to_string());
+}
+
+#[test]
+fn fold_constants_leaves_division_by_zero() {
+    const RECORD: CallableId = CallableId(1);
+    let mut program = new_program();
+    program.callables.insert(RECORD, int_record_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Sdiv(
+                Operand::Literal(Literal::Integer(1)),
+                Operand::Literal(Literal::Integer(0)),
+                int_var(0),
+            ),
+            Instruction::Call(
+                RECORD,
+                vec![
+                    Operand::Variable(int_var(0)),
+                    Operand::Literal(Literal::Pointer),
+                ],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    let program_before = program.to_string();
+    fold_constants(&mut program);
+    assert_eq!(program.to_string(), program_before);
+}
+
+#[test]
+fn fold_constants_leaves_non_finite_double_results() {
+    const RX: CallableId = CallableId(1);
+    let double_var = |id| Variable {
+        variable_id: VariableId(id),
+        ty: Ty::Double,
+    };
+    let mut program = new_program();
+    program.callables.insert(RX, rx_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Fdiv(
+                Operand::Literal(Literal::Double(1.0)),
+                Operand::Literal(Literal::Double(0.0)),
+                double_var(0),
+            ),
+            Instruction::Fmul(
+                Operand::Literal(Literal::Double(f64::MAX)),
+                Operand::Literal(Literal::Double(2.0)),
+                double_var(1),
+            ),
+            Instruction::Call(
+                RX,
+                vec![
+                    Operand::Variable(double_var(0)),
+                    Operand::Literal(Literal::Qubit(0)),
+                ],
+                None,
+            ),
+            Instruction::Call(
+                RX,
+                vec![
+                    Operand::Variable(double_var(1)),
+                    Operand::Literal(Literal::Qubit(0)),
+                ],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    let program_before = program.to_string();
+    fold_constants(&mut program);
+    assert_eq!(program.to_string(), program_before);
+}
+
+#[test]
+fn fold_constants_folds_comparisons_and_logical_ops() {
+    const RECORD: CallableId = CallableId(1);
+    let mut program = new_program();
+    program.callables.insert(RECORD, bool_record_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Icmp(
+                ConditionCode::Sle,
+                Operand::Literal(Literal::Integer(3)),
+                Operand::Literal(Literal::Integer(3)),
+                bool_var(0),
+            ),
+            Instruction::Fcmp(
+                FcmpConditionCode::OrderedAndEqual,
+                Operand::Literal(Literal::Double(f64::NAN)),
+                Operand::Literal(Literal::Double(f64::NAN)),
+                bool_var(1),
+            ),
+            Instruction::LogicalOr(
+                Operand::Variable(bool_var(0)),
+                Operand::Variable(bool_var(1)),
+                bool_var(2),
+            ),
+            Instruction::LogicalAnd(
+                Operand::Variable(bool_var(3)),
+                Operand::Variable(bool_var(1)),
+                bool_var(4),
+            ),
+            Instruction::Call(
+                RECORD,
+                vec![
+                    Operand::Variable(bool_var(2)),
+                    Operand::Literal(Literal::Pointer),
+                ],
+                None,
+            ),
+            Instruction::Call(
+                RECORD,
+                vec![
+                    Operand::Variable(bool_var(4)),
+                    Operand::Literal(Literal::Pointer),
+                ],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    fold_constants(&mut program);
+    expect![[r#"
+        Block:
+            Call id(1), args( Bool(true), Pointer, )
+            Call id(1), args( Bool(false), Pointer, )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(0)).to_string());
+}
+
+#[test]
+fn fold_constants_replaces_constant_branch_with_jump_and_removes_untaken_block() {
+    const X: CallableId = CallableId(1);
+    const H: CallableId = CallableId(2);
+    let mut program = new_program();
+    program.callables.insert(X, x_decl());
+    program.callables.insert(H, h_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Icmp(
+                ConditionCode::Slt,
+                Operand::Literal(Literal::Integer(1)),
+                Operand::Literal(Literal::Integer(2)),
+                bool_var(0),
+            ),
+            Instruction::Branch(bool_var(0), BlockId(1), BlockId(2)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Call(X, vec![Operand::Literal(Literal::Qubit(0))], None),
+            Instruction::Jump(BlockId(3)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(2),
+        Block(vec![
+            Instruction::Call(H, vec![Operand::Literal(Literal::Qubit(0))], None),
+            Instruction::Jump(BlockId(3)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(3),
+        Block(vec![
+            Instruction::Phi(
+                vec![
+                    (Operand::Literal(Literal::Integer(1)), BlockId(1)),
+                    (Operand::Literal(Literal::Integer(2)), BlockId(2)),
+                ],
+                int_var(1),
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    fold_constants(&mut program);
+    expect![[r#"
+        Program:
+            entry: 0
+            callables:
+                Callable 0: Callable:
+                    name: main
+                    call_type: Regular
+                    input_type: <VOID>
+                    output_type: <VOID>
+                    body: 0
+                Callable 1: Callable:
+                    name: __quantum__qis__x__body
+                    call_type: Regular
+                    input_type:
+                        [0]: Qubit
+                    output_type: <VOID>
+                    body: <NONE>
+                Callable 2: Callable:
+                    name: __quantum__qis__h__body
+                    call_type: Regular
+                    input_type:
+                        [0]: Qubit
+                    output_type: <VOID>
+                    body: <NONE>
+            blocks:
+                Block 0: Block:
+                    Jump(1)
+                Block 1: Block:
+                    Call id(1), args( Qubit(0), )
+                    Jump(3)
+                Block 3: Block:
+                    Variable(1, Integer) = Phi ( [Integer(1), 1], )
+                    Return
+            config: Config:
+                capabilities: Base
+            num_qubits: 0
+            num_results: 0"#]]
+    .assert_eq(&program.to_string());
+}
@@ -0,0 +1,92 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    rir::{Instruction, Literal, Operand, Program, VariableId},
+    utils::{replace_variable_uses, resolve_operand},
+};
+
+/// Propagates copies of values through the program, replacing the uses of variables that are only copies of
+/// another operand with that operand. This covers phi nodes that select the same value from every predecessor
+/// and arithmetic or logical instructions with an identity operand, such as adding zero or and-ing with true.
+/// The program is expected to be in SSA form.
+pub fn propagate_copies(program: &mut Program) {
+    loop {
+        let mut replacements = FxHashMap::default();
+        for (_, block) in program.blocks.iter_mut() {
+            block
+                .0
+                .retain(|instr| match get_copied_value(instr, &replacements) {
+                    Some((var_id, operand)) => {
+                        replacements.insert(var_id, operand);
+                        false
+                    }
+                    None => true,
+                });
+        }
+        if replacements.is_empty() {
+            break;
+        }
+        replace_variable_uses(program, &replacements);
+    }
+}
+
+// Returns the variable assigned by the instruction along with the operand it copies, if any.
+fn get_copied_value(
+    instr: &Instruction,
+    replacements: &FxHashMap<VariableId, Operand>,
+) -> Option<(VariableId, Operand)> {
+    let (operand, var) = match instr {
+        Instruction::Phi(args, var) => {
+            // A phi node that refers to itself, as in loops, only copies a value if every other argument agrees.
+            let mut values = Vec::new();
+            for (arg, _) in args {
+                let arg = resolve_operand(replacements, *arg);
+                if arg != Operand::Variable(*var) && !values.contains(&arg) {
+                    values.push(arg);
+                }
+            }
+            match values.as_slice() {
+                [value] => (*value, var),
+                _ => return None,
+            }
+        }
+        Instruction::Add(lhs, rhs, var)
+        | Instruction::BitwiseOr(lhs, rhs, var)
+        | Instruction::BitwiseXor(lhs, rhs, var) => {
+            (identity_operand(lhs, rhs, Literal::Integer(0))?, var)
+        }
+        Instruction::Sub(lhs, Operand::Literal(Literal::Integer(0)), var)
+        | Instruction::Sdiv(lhs, Operand::Literal(Literal::Integer(1)), var)
+        | Instruction::Shl(lhs, Operand::Literal(Literal::Integer(0)), var)
+        | Instruction::Ashr(lhs, Operand::Literal(Literal::Integer(0)), var) => (*lhs, var),
+        Instruction::Mul(lhs, rhs, var) => (identity_operand(lhs, rhs, Literal::Integer(1))?, var),
+        Instruction::BitwiseAnd(lhs, rhs, var) => {
+            (identity_operand(lhs, rhs, Literal::Integer(-1))?, var)
+        }
+        Instruction::LogicalAnd(lhs, rhs, var) => {
+            (identity_operand(lhs, rhs, Literal::Bool(true))?, var)
+        }
+        Instruction::LogicalOr(lhs, rhs, var) => {
+            (identity_operand(lhs, rhs, Literal::Bool(false))?, var)
+        }
+        _ => return None,
+    };
+    Some((var.variable_id, operand))
+}
+
+// Returns the other operand of a commutative instruction if one of the operands is the identity for that instruction.
+fn identity_operand(lhs: &Operand, rhs: &Operand, identity: Literal) -> Option<Operand> {
+    if *lhs == Operand::Literal(identity) {
+        Some(*rhs)
+    } else if *rhs == Operand::Literal(identity) {
+        Some(*lhs)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,263 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::too_many_lines, clippy::needless_raw_string_hashes)]
+
+use expect_test::expect;
+
+use crate::{
+    builder::{bool_record_decl, int_record_decl, mz_decl, new_program, read_result_decl},
+    rir::{Block, BlockId, CallableId, Instruction, Literal, Operand, Ty, Variable, VariableId},
+};
+
+use super::propagate_copies;
+
+fn int_var(id: u32) -> Variable {
+    Variable {
+        variable_id: VariableId(id),
+        ty: Ty::Integer,
+    }
+}
+
+fn bool_var(id: u32) -> Variable {
+    Variable {
+        variable_id: VariableId(id),
+        ty: Ty::Boolean,
+    }
+}
+
+#[test]
+fn propagate_copies_replaces_identity_operations() {
+    const READ: CallableId = CallableId(1);
+    const RECORD: CallableId = CallableId(2);
+    let mut program = new_program();
+    program.callables.insert(READ, read_result_decl());
+    program.callables.insert(RECORD, bool_record_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(
+                READ,
+                vec![Operand::Literal(Literal::Result(0))],
+                Some(bool_var(0)),
+            ),
+            Instruction::LogicalAnd(
+                Operand::Literal(Literal::Bool(true)),
+                Operand::Variable(bool_var(0)),
+                bool_var(1),
+            ),
+            Instruction::LogicalOr(
+                Operand::Variable(bool_var(1)),
+                Operand::Literal(Literal::Bool(false)),
+                bool_var(2),
+            ),
+            Instruction::Call(
+                RECORD,
+                vec![
+                    Operand::Variable(bool_var(2)),
+                    Operand::Literal(Literal::Pointer),
+                ],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    propagate_copies(&mut program);
+    expect![[r#"
+        Block:
+            Variable(0, Boolean) = Call id(1), args( Result(0), )
+            Call id(2), args( Variable(0, Boolean), Pointer, )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(0)).to_string());
+}
+
+#[test]
+fn propagate_copies_leaves_non_identity_operations() {
+    const RECORD: CallableId = CallableId(1);
+    let mut program = new_program();
+    program.callables.insert(RECORD, int_record_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Sub(
+                Operand::Literal(Literal::Integer(0)),
+                Operand::Variable(int_var(0)),
+                int_var(1),
+            ),
+            Instruction::Mul(
+                Operand::Variable(int_var(1)),
+                Operand::Literal(Literal::Integer(2)),
+                int_var(2),
+            ),
+            Instruction::Call(
+                RECORD,
+                vec![
+                    Operand::Variable(int_var(2)),
+                    Operand::Literal(Literal::Pointer),
+                ],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    let program_before = program.to_string();
+    propagate_copies(&mut program);
+    assert_eq!(program.to_string(), program_before);
+}
+
+#[test]
+fn propagate_copies_removes_phi_with_same_value_from_all_predecessors() {
+    const MZ: CallableId = CallableId(1);
+    const READ: CallableId = CallableId(2);
+    const RECORD: CallableId = CallableId(3);
+    let mut program = new_program();
+    program.callables.insert(MZ, mz_decl());
+    program.callables.insert(READ, read_result_decl());
+    program.callables.insert(RECORD, int_record_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(
+                MZ,
+                vec![
+                    Operand::Literal(Literal::Qubit(0)),
+                    Operand::Literal(Literal::Result(0)),
+                ],
+                None,
+            ),
+            Instruction::Call(
+                READ,
+                vec![Operand::Literal(Literal::Result(0))],
+                Some(bool_var(0)),
+            ),
+            Instruction::Branch(bool_var(0), BlockId(1), BlockId(2)),
+        ]),
+    );
+    program
+        .blocks
+        .insert(BlockId(1), Block(vec![Instruction::Jump(BlockId(2))]));
+    program.blocks.insert(
+        BlockId(2),
+        Block(vec![
+            Instruction::Phi(
+                vec![
+                    (Operand::Literal(Literal::Integer(5)), BlockId(0)),
+                    (Operand::Literal(Literal::Integer(5)), BlockId(1)),
+                ],
+                int_var(1),
+            ),
+            Instruction::Call(
+                RECORD,
+                vec![
+                    Operand::Variable(int_var(1)),
+                    Operand::Literal(Literal::Pointer),
+                ],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    propagate_copies(&mut program);
+    expect![[r#"
+        Block:
+            Call id(3), args( Integer(5), Pointer, )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(2)).to_string());
+}
+
+#[test]
+fn propagate_copies_removes_loop_phi_that_only_refers_to_itself_and_one_value() {
+    const READ: CallableId = CallableId(1);
+    const RECORD: CallableId = CallableId(2);
+    let mut program = new_program();
+    program.callables.insert(READ, read_result_decl());
+    program.callables.insert(RECORD, int_record_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Add(
+                Operand::Variable(int_var(5)),
+                Operand::Literal(Literal::Integer(1)),
+                int_var(0),
+            ),
+            Instruction::Jump(BlockId(1)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Phi(
+                vec![
+                    (Operand::Variable(int_var(0)), BlockId(0)),
+                    (Operand::Variable(int_var(1)), BlockId(1)),
+                ],
+                int_var(1),
+            ),
+            Instruction::Call(
+                READ,
+                vec![Operand::Literal(Literal::Result(0))],
+                Some(bool_var(2)),
+            ),
+            Instruction::Branch(bool_var(2), BlockId(1), BlockId(2)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(2),
+        Block(vec![
+            Instruction::Call(
+                RECORD,
+                vec![
+                    Operand::Variable(int_var(1)),
+                    Operand::Literal(Literal::Pointer),
+                ],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    propagate_copies(&mut program);
+    expect![[r#"
+        Program:
+            entry: 0
+            callables:
+                Callable 0: Callable:
+                    name: main
+                    call_type: Regular
+                    input_type: <VOID>
+                    output_type: <VOID>
+                    body: 0
+                Callable 1: Callable:
+                    name: __quantum__qis__read_result__body
+                    call_type: Readout
+                    input_type:
+                        [0]: Result
+                    output_type: Boolean
+                    body: <NONE>
+                Callable 2: Callable:
+                    name: __quantum__rt__integer_record_output
+                    call_type: OutputRecording
+                    input_type:
+                        [0]: Integer
+                        [1]: Pointer
+                    output_type: <VOID>
+                    body: <NONE>
+            blocks:
+                Block 0: Block:
+                    Variable(0, Integer) = Add Variable(5, Integer), Integer(1)
+                    Jump(1)
+                Block 1: Block:
+                    Variable(2, Boolean) = Call id(1), args( Result(0), )
+                    Branch Variable(2, Boolean), 1, 2
+                Block 2: Block:
+                    Call id(2), args( Variable(0, Integer), Pointer, )
+                    Return
+            config: Config:
+                capabilities: Base
+            num_qubits: 0
+            num_results: 0"#]]
+    .assert_eq(&program.to_string());
+}
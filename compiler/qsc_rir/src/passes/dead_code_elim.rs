@@ -0,0 +1,67 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use rustc_hash::FxHashSet;
+
+use crate::{
+    rir::{Instruction, Operand, Program, VariableId},
+    utils::{get_assigned_variable, get_instruction_operands, get_variable_assignments},
+};
+
+/// Removes instructions whose results are never used. Calls, terminators and stores are always kept, along with
+/// every instruction that computes a value they use, directly or through other instructions.
+/// The program is expected to be in SSA form.
+pub fn eliminate_dead_instructions(program: &mut Program) {
+    let assignments = get_variable_assignments(program);
+
+    // Start from the operands of the instructions that must be kept and mark the instructions assigning them as
+    // live, following their operands in turn.
+    let mut live_vars = FxHashSet::default();
+    let mut vars_to_visit = Vec::new();
+    for block in program.blocks.values() {
+        for instr in block.0.iter().filter(|instr| has_side_effects(instr)) {
+            vars_to_visit.extend(get_variable_operands(instr));
+        }
+    }
+    while let Some(var_id) = vars_to_visit.pop() {
+        if !live_vars.insert(var_id) {
+            continue;
+        }
+        let Some((block_id, idx)) = assignments.get(var_id) else {
+            continue;
+        };
+        let instr = &program.get_block(*block_id).0[*idx];
+        vars_to_visit.extend(get_variable_operands(instr));
+    }
+
+    for block in program.blocks.values_mut() {
+        block.0.retain(|instr| {
+            has_side_effects(instr)
+                || !matches!(get_assigned_variable(instr), Some(var) if !live_vars.contains(&var.variable_id))
+        });
+    }
+}
+
+// Calls are kept even when their result is unused, since they may act on qubits or record output.
+fn has_side_effects(instr: &Instruction) -> bool {
+    matches!(
+        instr,
+        Instruction::Call(..)
+            | Instruction::Store(..)
+            | Instruction::Jump(..)
+            | Instruction::Branch(..)
+            | Instruction::Return
+    )
+}
+
+fn get_variable_operands(instr: &Instruction) -> impl Iterator<Item = VariableId> {
+    get_instruction_operands(instr)
+        .into_iter()
+        .filter_map(|operand| match operand {
+            Operand::Variable(var) => Some(var.variable_id),
+            Operand::Literal(_) => None,
+        })
+}
@@ -0,0 +1,140 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::too_many_lines, clippy::needless_raw_string_hashes)]
+
+use expect_test::expect;
+
+use crate::{
+    builder::{int_record_decl, new_program, read_result_decl, teleport_program},
+    rir::{
+        Block, BlockId, CallableId, ConditionCode, Instruction, Literal, Operand, Ty, Variable,
+        VariableId,
+    },
+};
+
+use super::eliminate_dead_instructions;
+
+fn int_var(id: u32) -> Variable {
+    Variable {
+        variable_id: VariableId(id),
+        ty: Ty::Integer,
+    }
+}
+
+fn bool_var(id: u32) -> Variable {
+    Variable {
+        variable_id: VariableId(id),
+        ty: Ty::Boolean,
+    }
+}
+
+#[test]
+fn eliminate_dead_instructions_leaves_program_without_dead_instructions_unchanged() {
+    let mut program = teleport_program();
+    let program_before = program.to_string();
+    eliminate_dead_instructions(&mut program);
+    assert_eq!(program.to_string(), program_before);
+}
+
+#[test]
+fn eliminate_dead_instructions_removes_chain_of_unused_instructions() {
+    const READ: CallableId = CallableId(1);
+    const RECORD: CallableId = CallableId(2);
+    let mut program = new_program();
+    program.callables.insert(READ, read_result_decl());
+    program.callables.insert(RECORD, int_record_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(
+                READ,
+                vec![Operand::Literal(Literal::Result(0))],
+                Some(bool_var(0)),
+            ),
+            Instruction::Add(
+                Operand::Variable(int_var(5)),
+                Operand::Literal(Literal::Integer(1)),
+                int_var(1),
+            ),
+            Instruction::Mul(
+                Operand::Variable(int_var(1)),
+                Operand::Literal(Literal::Integer(2)),
+                int_var(2),
+            ),
+            Instruction::Icmp(
+                ConditionCode::Eq,
+                Operand::Variable(int_var(2)),
+                Operand::Literal(Literal::Integer(0)),
+                bool_var(3),
+            ),
+            Instruction::Sub(
+                Operand::Variable(int_var(1)),
+                Operand::Literal(Literal::Integer(1)),
+                int_var(4),
+            ),
+            Instruction::Call(
+                RECORD,
+                vec![
+                    Operand::Variable(int_var(4)),
+                    Operand::Literal(Literal::Pointer),
+                ],
+                None,
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    eliminate_dead_instructions(&mut program);
+    expect![[r#"
+        Block:
+            Variable(0, Boolean) = Call id(1), args( Result(0), )
+            Variable(1, Integer) = Add Variable(5, Integer), Integer(1)
+            Variable(4, Integer) = Sub Variable(1, Integer), Integer(1)
+            Call id(2), args( Variable(4, Integer), Pointer, )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(0)).to_string());
+}
+
+#[test]
+fn eliminate_dead_instructions_removes_phi_only_used_by_itself() {
+    const READ: CallableId = CallableId(1);
+    let mut program = new_program();
+    program.callables.insert(READ, read_result_decl());
+    program
+        .blocks
+        .insert(BlockId(0), Block(vec![Instruction::Jump(BlockId(1))]));
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Phi(
+                vec![
+                    (Operand::Literal(Literal::Integer(0)), BlockId(0)),
+                    (Operand::Variable(int_var(1)), BlockId(1)),
+                ],
+                int_var(0),
+            ),
+            Instruction::Add(
+                Operand::Variable(int_var(0)),
+                Operand::Literal(Literal::Integer(1)),
+                int_var(1),
+            ),
+            Instruction::Call(
+                READ,
+                vec![Operand::Literal(Literal::Result(0))],
+                Some(bool_var(2)),
+            ),
+            Instruction::Branch(bool_var(2), BlockId(1), BlockId(2)),
+        ]),
+    );
+    program
+        .blocks
+        .insert(BlockId(2), Block(vec![Instruction::Return]));
+
+    eliminate_dead_instructions(&mut program);
+    expect![[r#"
+        Block:
+            Variable(2, Boolean) = Call id(1), args( Result(0), )
+            Branch Variable(2, Boolean), 1, 2"#]]
+    .assert_eq(&program.get_block(BlockId(1)).to_string());
+}
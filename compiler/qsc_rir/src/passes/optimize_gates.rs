@@ -0,0 +1,213 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    rir::{CallableId, Instruction, Literal, Operand, Program, Ty},
+    utils::remove_unreachable_callables,
+};
+
+/// The effect a gate has when it directly follows another gate on the same qubits.
+enum GateKind {
+    /// A gate that is its own inverse, such as `h` or `cx`.
+    SelfInverse,
+    /// A gate whose inverse is the gate with the given name, such as `s` and `s__adj`.
+    InverseOf(&'static str),
+    /// A rotation whose angle is the first argument, such as `rx`.
+    Rotation,
+}
+
+/// The result of combining a gate with the gate directly preceding it on the same qubits.
+enum Combined {
+    /// The gates cancel out and both are removed.
+    Cancel,
+    /// The gates merge into the given instruction.
+    Merge(Instruction),
+    /// The gates cannot be combined.
+    Separate,
+}
+
+fn gate_kind(name: &str) -> Option<GateKind> {
+    match name {
+        "__quantum__qis__h__body"
+        | "__quantum__qis__x__body"
+        | "__quantum__qis__y__body"
+        | "__quantum__qis__z__body"
+        | "__quantum__qis__cx__body"
+        | "__quantum__qis__cy__body"
+        | "__quantum__qis__cz__body"
+        | "__quantum__qis__swap__body" => Some(GateKind::SelfInverse),
+        "__quantum__qis__s__body" => Some(GateKind::InverseOf("__quantum__qis__s__adj")),
+        "__quantum__qis__s__adj" => Some(GateKind::InverseOf("__quantum__qis__s__body")),
+        "__quantum__qis__t__body" => Some(GateKind::InverseOf("__quantum__qis__t__adj")),
+        "__quantum__qis__t__adj" => Some(GateKind::InverseOf("__quantum__qis__t__body")),
+        "__quantum__qis__rx__body" | "__quantum__qis__ry__body" | "__quantum__qis__rz__body" => {
+            Some(GateKind::Rotation)
+        }
+        _ => None,
+    }
+}
+
+/// Cancels pairs of adjacent gates that are inverses of each other, such as `h; h` or `s; s__adj`, and merges
+/// adjacent `rx`, `ry` or `rz` rotations with constant angles on the same qubit, removing them when the angles
+/// cancel out. Gates are adjacent when no other instruction acts on any of their qubits in between.
+/// Measurements, resets and any other call acting on a qubit end the sequence of gates on that qubit, and
+/// gates are never combined across blocks, so branches are respected.
+/// Callables that are no longer called afterwards are removed from the program.
+pub fn optimize_gates(program: &mut Program) {
+    let block_ids = program
+        .blocks
+        .iter()
+        .map(|(block_id, _)| block_id)
+        .collect::<Vec<_>>();
+    for block_id in block_ids {
+        let instrs = std::mem::take(&mut program.get_block_mut(block_id).0);
+        let instrs = optimize_block_gates(program, instrs);
+        program.get_block_mut(block_id).0 = instrs;
+    }
+    remove_unreachable_callables(program);
+}
+
+fn optimize_block_gates(program: &Program, instrs: Vec<Instruction>) -> Vec<Instruction> {
+    // The instructions are kept in place while optimizing, with removed gates left as `None`, so that the
+    // indices of the gates pending on each qubit stay valid.
+    let mut instrs = instrs.into_iter().map(Some).collect::<Vec<_>>();
+
+    // For each qubit, the indices of the gates that act on it and have not been followed by any other
+    // instruction acting on it, in order. Gates below the top of a stack are only reachable again once the
+    // gates above them have been removed.
+    let mut pending: FxHashMap<u32, Vec<usize>> = FxHashMap::default();
+
+    for idx in 0..instrs.len() {
+        let Some(Instruction::Call(callable_id, args, output)) = &instrs[idx] else {
+            continue;
+        };
+        let callable_id = *callable_id;
+        let mut qubits = Vec::new();
+        let mut has_dynamic_qubits = false;
+        for arg in args {
+            match arg {
+                Operand::Literal(Literal::Qubit(qubit)) => qubits.push(*qubit),
+                Operand::Variable(var) if var.ty == Ty::Qubit => {
+                    has_dynamic_qubits = true;
+                }
+                _ => {}
+            }
+        }
+        if has_dynamic_qubits {
+            // The call could act on any qubit, so no gate before it can be combined with one after it.
+            pending.clear();
+            continue;
+        }
+        if qubits.is_empty() {
+            continue;
+        }
+
+        // Gates never return a value, so a call with an output is never treated as one.
+        let kind = if output.is_none() {
+            gate_kind(&program.get_callable(callable_id).name)
+        } else {
+            None
+        };
+        let Some(kind) = kind else {
+            // Any other call, such as a measurement or reset, ends the sequence of gates on its qubits.
+            for qubit in &qubits {
+                pending.remove(qubit);
+            }
+            continue;
+        };
+
+        // The previous gate can only be combined with this one if it is the latest gate on all of this gate's
+        // qubits, which also means it acts on no other qubits.
+        let prev_idx = qubits
+            .iter()
+            .map(|qubit| pending.get(qubit).and_then(|stack| stack.last()).copied())
+            .reduce(|a, b| if a == b { a } else { None })
+            .flatten();
+        let combined = prev_idx.map(|prev_idx| {
+            let Some(Instruction::Call(prev_callable_id, prev_args, None)) = &instrs[prev_idx]
+            else {
+                panic!("pending gate should be a call");
+            };
+            let combined = combine(
+                program,
+                &kind,
+                callable_id,
+                args,
+                *prev_callable_id,
+                prev_args,
+            );
+            (prev_idx, combined)
+        });
+
+        match combined {
+            Some((prev_idx, Combined::Merge(merged))) => {
+                instrs[prev_idx] = Some(merged);
+                instrs[idx] = None;
+            }
+            Some((prev_idx, Combined::Cancel)) => {
+                instrs[prev_idx] = None;
+                instrs[idx] = None;
+                for qubit in &qubits {
+                    pending
+                        .get_mut(qubit)
+                        .expect("qubit should have pending gates")
+                        .pop();
+                }
+            }
+            Some((_, Combined::Separate)) | None => {
+                for qubit in qubits {
+                    pending.entry(qubit).or_default().push(idx);
+                }
+            }
+        }
+    }
+
+    instrs.into_iter().flatten().collect()
+}
+
+// Combines a gate with the gate directly preceding it on the same qubits.
+fn combine(
+    program: &Program,
+    kind: &GateKind,
+    callable_id: CallableId,
+    args: &[Operand],
+    prev_callable_id: CallableId,
+    prev_args: &[Operand],
+) -> Combined {
+    match kind {
+        GateKind::SelfInverse if callable_id == prev_callable_id && args == prev_args => {
+            Combined::Cancel
+        }
+        GateKind::InverseOf(inverse)
+            if program.get_callable(prev_callable_id).name == *inverse && args == prev_args =>
+        {
+            Combined::Cancel
+        }
+        GateKind::SelfInverse | GateKind::InverseOf(_) => Combined::Separate,
+        GateKind::Rotation => {
+            if callable_id != prev_callable_id || args[1..] != prev_args[1..] {
+                return Combined::Separate;
+            }
+            let (
+                Operand::Literal(Literal::Double(angle)),
+                Operand::Literal(Literal::Double(prev_angle)),
+            ) = (args[0], prev_args[0])
+            else {
+                return Combined::Separate;
+            };
+            let merged_angle = prev_angle + angle;
+            if merged_angle.abs() < f64::EPSILON {
+                Combined::Cancel
+            } else {
+                let mut merged_args = args.to_vec();
+                merged_args[0] = Operand::Literal(Literal::Double(merged_angle));
+                Combined::Merge(Instruction::Call(callable_id, merged_args, None))
+            }
+        }
+    }
+}
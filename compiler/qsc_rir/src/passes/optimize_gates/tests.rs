@@ -0,0 +1,308 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::too_many_lines, clippy::needless_raw_string_hashes)]
+
+use expect_test::expect;
+
+use crate::{
+    builder::{bell_program, cx_decl, h_decl, mresetz_decl, new_program, read_result_decl, x_decl},
+    rir::{
+        Block, BlockId, Callable, CallableId, CallableType, Instruction, Literal, Operand, Ty,
+        Variable, VariableId,
+    },
+};
+
+use super::optimize_gates;
+
+fn gate_decl(name: &str, input_type: Vec<Ty>) -> Callable {
+    Callable {
+        name: format!("__quantum__qis__{name}"),
+        input_type,
+        output_type: None,
+        body: None,
+        call_type: CallableType::Regular,
+    }
+}
+
+fn qubit(id: u32) -> Operand {
+    Operand::Literal(Literal::Qubit(id))
+}
+
+fn angle(value: f64) -> Operand {
+    Operand::Literal(Literal::Double(value))
+}
+
+#[test]
+fn optimize_gates_leaves_program_without_redundant_gates_unchanged() {
+    let mut program = bell_program();
+    let program_before = program.to_string();
+    optimize_gates(&mut program);
+    assert_eq!(program.to_string(), program_before);
+}
+
+#[test]
+fn optimize_gates_cancels_nested_self_inverse_gates() {
+    const H: CallableId = CallableId(1);
+    const X: CallableId = CallableId(2);
+    const CX: CallableId = CallableId(3);
+    let mut program = new_program();
+    program.callables.insert(H, h_decl());
+    program.callables.insert(X, x_decl());
+    program.callables.insert(CX, cx_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(H, vec![qubit(0)], None),
+            Instruction::Call(CX, vec![qubit(0), qubit(1)], None),
+            Instruction::Call(X, vec![qubit(2)], None),
+            Instruction::Call(CX, vec![qubit(0), qubit(1)], None),
+            Instruction::Call(H, vec![qubit(0)], None),
+            Instruction::Call(X, vec![qubit(2)], None),
+            Instruction::Call(X, vec![qubit(1)], None),
+            Instruction::Return,
+        ]),
+    );
+
+    optimize_gates(&mut program);
+    expect![[r#"
+        Block:
+            Call id(2), args( Qubit(1), )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(0)).to_string());
+    assert!(program.callables.get(H).is_none());
+    assert!(program.callables.get(CX).is_none());
+}
+
+#[test]
+fn optimize_gates_cancels_gates_and_their_adjoints() {
+    const S: CallableId = CallableId(1);
+    const S_ADJ: CallableId = CallableId(2);
+    const T: CallableId = CallableId(3);
+    const T_ADJ: CallableId = CallableId(4);
+    let mut program = new_program();
+    program
+        .callables
+        .insert(S, gate_decl("s__body", vec![Ty::Qubit]));
+    program
+        .callables
+        .insert(S_ADJ, gate_decl("s__adj", vec![Ty::Qubit]));
+    program
+        .callables
+        .insert(T, gate_decl("t__body", vec![Ty::Qubit]));
+    program
+        .callables
+        .insert(T_ADJ, gate_decl("t__adj", vec![Ty::Qubit]));
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(S, vec![qubit(0)], None),
+            Instruction::Call(S_ADJ, vec![qubit(0)], None),
+            Instruction::Call(T_ADJ, vec![qubit(0)], None),
+            Instruction::Call(T, vec![qubit(0)], None),
+            Instruction::Call(S, vec![qubit(1)], None),
+            Instruction::Call(S, vec![qubit(1)], None),
+            Instruction::Return,
+        ]),
+    );
+
+    optimize_gates(&mut program);
+    expect![[r#"
+        Block:
+            Call id(1), args( Qubit(1), )
+            Call id(1), args( Qubit(1), )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(0)).to_string());
+}
+
+#[test]
+fn optimize_gates_merges_rotations_on_same_qubit() {
+    const RX: CallableId = CallableId(1);
+    const RZ: CallableId = CallableId(2);
+    let mut program = new_program();
+    program
+        .callables
+        .insert(RX, gate_decl("rx__body", vec![Ty::Double, Ty::Qubit]));
+    program
+        .callables
+        .insert(RZ, gate_decl("rz__body", vec![Ty::Double, Ty::Qubit]));
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(RX, vec![angle(0.5), qubit(0)], None),
+            Instruction::Call(RZ, vec![angle(1.0), qubit(1)], None),
+            Instruction::Call(RX, vec![angle(0.25), qubit(0)], None),
+            Instruction::Call(RZ, vec![angle(-1.0), qubit(1)], None),
+            Instruction::Call(RZ, vec![angle(2.0), qubit(0)], None),
+            Instruction::Call(RX, vec![angle(1.0), qubit(0)], None),
+            Instruction::Return,
+        ]),
+    );
+
+    optimize_gates(&mut program);
+    expect![[r#"
+        Block:
+            Call id(1), args( Double(0.75), Qubit(0), )
+            Call id(2), args( Double(2), Qubit(0), )
+            Call id(1), args( Double(1), Qubit(0), )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(0)).to_string());
+}
+
+#[test]
+fn optimize_gates_does_not_merge_rotations_with_dynamic_angles() {
+    const RY: CallableId = CallableId(1);
+    let mut program = new_program();
+    program
+        .callables
+        .insert(RY, gate_decl("ry__body", vec![Ty::Double, Ty::Qubit]));
+    let angle_var = Operand::Variable(Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Double,
+    });
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(RY, vec![angle_var, qubit(0)], None),
+            Instruction::Call(RY, vec![angle(1.0), qubit(0)], None),
+            Instruction::Return,
+        ]),
+    );
+
+    optimize_gates(&mut program);
+    expect![[r#"
+        Block:
+            Call id(1), args( Variable(0, Double), Qubit(0), )
+            Call id(1), args( Double(1), Qubit(0), )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(0)).to_string());
+}
+
+#[test]
+fn optimize_gates_does_not_cancel_across_measurements() {
+    const H: CallableId = CallableId(1);
+    const MRESETZ: CallableId = CallableId(2);
+    let mut program = new_program();
+    program.callables.insert(H, h_decl());
+    program.callables.insert(MRESETZ, mresetz_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(H, vec![qubit(0)], None),
+            Instruction::Call(H, vec![qubit(1)], None),
+            Instruction::Call(
+                MRESETZ,
+                vec![qubit(0), Operand::Literal(Literal::Result(0))],
+                None,
+            ),
+            Instruction::Call(H, vec![qubit(0)], None),
+            Instruction::Call(H, vec![qubit(1)], None),
+            Instruction::Return,
+        ]),
+    );
+
+    optimize_gates(&mut program);
+    expect![[r#"
+        Block:
+            Call id(1), args( Qubit(0), )
+            Call id(2), args( Qubit(0), Result(0), )
+            Call id(1), args( Qubit(0), )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(0)).to_string());
+}
+
+#[test]
+fn optimize_gates_does_not_cancel_across_blocks() {
+    const H: CallableId = CallableId(1);
+    const MRESETZ: CallableId = CallableId(2);
+    const READ: CallableId = CallableId(3);
+    let cond = Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Boolean,
+    };
+    let mut program = new_program();
+    program.callables.insert(H, h_decl());
+    program.callables.insert(MRESETZ, mresetz_decl());
+    program.callables.insert(READ, read_result_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(H, vec![qubit(1)], None),
+            Instruction::Call(
+                MRESETZ,
+                vec![qubit(0), Operand::Literal(Literal::Result(0))],
+                None,
+            ),
+            Instruction::Call(READ, vec![Operand::Literal(Literal::Result(0))], Some(cond)),
+            Instruction::Branch(cond, BlockId(1), BlockId(2)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Call(H, vec![qubit(1)], None),
+            Instruction::Jump(BlockId(2)),
+        ]),
+    );
+    program
+        .blocks
+        .insert(BlockId(2), Block(vec![Instruction::Return]));
+
+    let program_before = program.to_string();
+    optimize_gates(&mut program);
+    assert_eq!(program.to_string(), program_before);
+}
+
+#[test]
+fn optimize_gates_does_not_cancel_gates_on_different_qubit_order() {
+    const CX: CallableId = CallableId(1);
+    let mut program = new_program();
+    program.callables.insert(CX, cx_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(CX, vec![qubit(0), qubit(1)], None),
+            Instruction::Call(CX, vec![qubit(1), qubit(0)], None),
+            Instruction::Return,
+        ]),
+    );
+
+    let program_before = program.to_string();
+    optimize_gates(&mut program);
+    assert_eq!(program.to_string(), program_before);
+}
+
+#[test]
+fn optimize_gates_does_not_cancel_across_calls_with_output() {
+    const H: CallableId = CallableId(1);
+    const MZ: CallableId = CallableId(2);
+    let result = Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Boolean,
+    };
+    let mut program = new_program();
+    program.callables.insert(H, h_decl());
+    program.callables.insert(
+        MZ,
+        Callable {
+            name: "__quantum__qis__m__body".to_string(),
+            input_type: vec![Ty::Qubit],
+            output_type: Some(Ty::Boolean),
+            body: None,
+            call_type: CallableType::Measurement,
+        },
+    );
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(H, vec![qubit(0)], None),
+            Instruction::Call(MZ, vec![qubit(0)], Some(result)),
+            Instruction::Call(H, vec![qubit(0)], None),
+            Instruction::Return,
+        ]),
+    );
+
+    let program_before = program.to_string();
+    optimize_gates(&mut program);
+    assert_eq!(program.to_string(), program_before);
+}
@@ -3,7 +3,7 @@
 
 use std::collections::VecDeque;
 
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::{
     rir::{BlockId, Instruction, Program},
@@ -16,35 +16,22 @@ mod tests;
 /// Remaps block IDs in the given program to be contiguous, starting from 0,
 /// and in a topological ordering if the program is Directed Acyclic Graph (DAG).
 /// Toplogical ordering is useful for passes that assume each block's successors
-/// have higher IDs than the block itself. This is best effort; if the program has a cycle,
-/// the function will still remap block IDs but the ordering may not be topological.
+/// have higher IDs than the block itself. If the program has a cycle, no topological ordering exists,
+/// so the blocks are instead remapped in reverse postorder, where only the targets of back edges
+/// have IDs lower than or equal to their predecessors.
 pub fn remap_block_ids(program: &mut Program) {
-    // Check if the program is acyclic, which lets us construct a topological ordering.
-    let is_acyclic = check_acyclic(program);
-
     // Only update the entry point.
     let entry_block_id = program
         .get_callable(program.entry)
         .body
         .expect("entry point should have a body block");
 
-    // Because we know the program is acyclic, we can keep a list as the map from old block IDs to new block IDs, where
-    // the new block ID is the index in the list.
-    let mut block_id_map = Vec::new();
-    let mut blocks_to_visit: VecDeque<BlockId> = vec![entry_block_id].into();
-    while let Some(block_id) = blocks_to_visit.pop_front() {
-        // If we've already visited this block, remove it from the previous ordering so that we can insert it at the end.
-        // This effectively remaps all the blocks in the list and updates the mapped id of the current block.
-        // This is only safe without cycles, so on a cyclic graph the node is skipped and not remapped.
-        if is_acyclic {
-            block_id_map.retain_mut(|id| *id != block_id);
-        } else if block_id_map.contains(&block_id) {
-            continue;
-        }
-        block_id_map.push(block_id);
-
-        blocks_to_visit.extend(get_block_successors(program.get_block(block_id)));
-    }
+    // Check if the program is acyclic, which lets us construct a topological ordering.
+    let block_id_map = if check_acyclic(program) {
+        topological_order(program, entry_block_id)
+    } else {
+        reverse_postorder(program, entry_block_id)
+    };
 
     let block_id_map = block_id_map
         .into_iter()
@@ -72,6 +59,50 @@ pub fn remap_block_ids(program: &mut Program) {
         .body = Some(block_id_map[&entry_block_id].into());
 }
 
+fn topological_order(program: &Program, entry_block_id: BlockId) -> Vec<BlockId> {
+    // Because we know the program is acyclic, we can keep a list as the map from old block IDs to new block IDs, where
+    // the new block ID is the index in the list.
+    let mut block_id_map = Vec::new();
+    let mut blocks_to_visit: VecDeque<BlockId> = vec![entry_block_id].into();
+    while let Some(block_id) = blocks_to_visit.pop_front() {
+        // If we've already visited this block, remove it from the previous ordering so that we can insert it at the end.
+        // This effectively remaps all the blocks in the list and updates the mapped id of the current block.
+        // This is only safe without cycles.
+        block_id_map.retain_mut(|id| *id != block_id);
+        block_id_map.push(block_id);
+
+        blocks_to_visit.extend(get_block_successors(program.get_block(block_id)));
+    }
+    block_id_map
+}
+
+fn reverse_postorder(program: &Program, entry_block_id: BlockId) -> Vec<BlockId> {
+    // Successors are popped from the end of their list and so visited in reverse, which places the first
+    // successor of a branch, such as the body of a loop, earlier in the final ordering than the second.
+    let mut postorder = Vec::new();
+    let mut visited = FxHashSet::default();
+    visited.insert(entry_block_id);
+    let mut stack = vec![(
+        entry_block_id,
+        get_block_successors(program.get_block(entry_block_id)),
+    )];
+    while let Some((block_id, successors)) = stack.last_mut() {
+        if let Some(successor) = successors.pop() {
+            if visited.insert(successor) {
+                stack.push((
+                    successor,
+                    get_block_successors(program.get_block(successor)),
+                ));
+            }
+        } else {
+            postorder.push(*block_id);
+            stack.pop();
+        }
+    }
+    postorder.reverse();
+    postorder
+}
+
 fn check_acyclic(program: &Program) -> bool {
     for (block_id, _) in program.blocks.iter() {
         if get_all_block_successors(block_id, program).contains(&block_id) {
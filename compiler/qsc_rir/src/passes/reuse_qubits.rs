@@ -0,0 +1,293 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt::{self, Display, Formatter};
+
+use qsc_data_structures::target::TargetCapabilityFlags;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    rir::{
+        BlockId, Callable, CallableId, CallableType, Instruction, Literal, Operand, Program, Ty,
+    },
+    utils::get_block_successors,
+};
+
+/// The outcome of the qubit reuse pass.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QubitReuseReport {
+    /// The number of qubits the program required before the pass.
+    pub qubits_before: u32,
+    /// The number of qubits the program requires after the pass.
+    pub qubits_after: u32,
+    /// The number of resets inserted to return reused qubits to the zero state.
+    pub resets_inserted: u32,
+}
+
+impl QubitReuseReport {
+    #[must_use]
+    pub fn qubits_saved(&self) -> u32 {
+        self.qubits_before.saturating_sub(self.qubits_after)
+    }
+}
+
+impl Display for QubitReuseReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "qubits: {} -> {} ({} saved), resets inserted: {}",
+            self.qubits_before,
+            self.qubits_after,
+            self.qubits_saved(),
+            self.resets_inserted
+        )
+    }
+}
+
+// The position of an instruction in the program, as its block and its index in that block. For acyclic programs
+// with topologically ordered block IDs, positions are ordered consistently with every path through the program.
+type Position = (BlockId, usize);
+
+// The uses of a qubit in the program.
+struct Lifetime {
+    qubit: u32,
+    first: Position,
+    last: Position,
+    // The blocks in which the qubit is used.
+    blocks: FxHashSet<BlockId>,
+}
+
+// A physical qubit that the qubits of the program are mapped onto.
+struct Slot {
+    id: u32,
+    // The position of the last use of the slot by a qubit mapped onto it.
+    last: Position,
+    // The blocks in which the slot is used.
+    blocks: FxHashSet<BlockId>,
+    // Whether the last use of the slot leaves it in the zero state, such as a reset or a measurement that resets.
+    ends_in_zero: bool,
+}
+
+impl Slot {
+    // The slot is known to be in the zero state after its last use if that use leaves it there and all of its uses
+    // are in the same block, since paths that skip that block never touch the slot.
+    fn is_zero_after_last_use(&self) -> bool {
+        self.ends_in_zero && self.blocks.len() == 1
+    }
+}
+
+/// Maps qubits whose lifetimes do not overlap onto the same qubit ID, reducing the number of qubits the program
+/// requires. A qubit's lifetime spans from its first to its last use in the program. A qubit can take over the ID
+/// of another qubit whose lifetime ended before it starts. A reset is inserted before its first use unless the
+/// previous qubit is known to be left in the zero state, which is only possible if the target supports qubit
+/// reset; otherwise the qubit only takes over IDs that need no reset.
+/// Qubit IDs are assigned so that the order of the lowest original ID mapped onto each ID is preserved.
+/// Note that the pass leaves the program unchanged if any of the following is true:
+/// 1. A callable other than the entry point has a body.
+/// 2. The program has loops.
+/// 3. Dynamic qubits are used.
+pub fn reuse_qubits(program: &mut Program) -> QubitReuseReport {
+    let mut report = QubitReuseReport {
+        qubits_before: program.num_qubits,
+        qubits_after: program.num_qubits,
+        resets_inserted: 0,
+    };
+    if !is_supported(program) {
+        return report;
+    }
+
+    let lifetimes = get_lifetimes(program);
+    let can_reset = program
+        .config
+        .capabilities
+        .contains(TargetCapabilityFlags::QubitReset);
+
+    // Assign qubits to slots greedily in the order their lifetimes start, preferring slots that need no reset.
+    let mut slots: Vec<Slot> = Vec::new();
+    let mut qubit_map = FxHashMap::default();
+    let mut resets = Vec::new();
+    for lifetime in &lifetimes {
+        let available = |slot: &&Slot| slot.last < lifetime.first;
+        let reused = slots
+            .iter()
+            .filter(available)
+            .find(|slot| slot.is_zero_after_last_use())
+            .or_else(|| {
+                if can_reset {
+                    slots.iter().find(available)
+                } else {
+                    None
+                }
+            })
+            .map(|slot| (slot.id, !slot.is_zero_after_last_use()));
+        let slot_id = if let Some((slot_id, needs_reset)) = reused {
+            if needs_reset {
+                resets.push((lifetime.first, slot_id));
+            }
+            slot_id
+        } else {
+            let slot_id = u32::try_from(slots.len()).expect("qubit count should fit into u32");
+            slots.push(Slot {
+                id: slot_id,
+                last: lifetime.last,
+                blocks: FxHashSet::default(),
+                ends_in_zero: false,
+            });
+            slot_id
+        };
+        let slot = &mut slots[slot_id as usize];
+        slot.last = lifetime.last;
+        slot.blocks.extend(lifetime.blocks.iter().copied());
+        slot.ends_in_zero = leaves_qubit_in_zero(program, lifetime.last);
+        qubit_map.insert(lifetime.qubit, slot_id);
+    }
+
+    // Renumber the slots so that their IDs follow the lowest original qubit ID mapped onto each of them.
+    let mut lowest_qubits = vec![u32::MAX; slots.len()];
+    for (qubit, slot_id) in &qubit_map {
+        let lowest = &mut lowest_qubits[*slot_id as usize];
+        *lowest = (*lowest).min(*qubit);
+    }
+    let mut slot_order = (0..slots.len()).collect::<Vec<_>>();
+    slot_order.sort_by_key(|slot_id| lowest_qubits[*slot_id]);
+    let mut slot_renumbering = vec![0; slots.len()];
+    for (new_id, slot_id) in slot_order.into_iter().enumerate() {
+        slot_renumbering[slot_id] = u32::try_from(new_id).expect("qubit count should fit into u32");
+    }
+    for slot_id in qubit_map.values_mut() {
+        *slot_id = slot_renumbering[*slot_id as usize];
+    }
+
+    for block in program.blocks.values_mut() {
+        for instr in &mut block.0 {
+            if let Instruction::Call(_, args, _) = instr {
+                for arg in args.iter_mut() {
+                    if let Operand::Literal(Literal::Qubit(qubit)) = arg {
+                        *qubit = qubit_map[qubit];
+                    }
+                }
+            }
+        }
+    }
+
+    // Insert the resets in reverse order so that earlier positions in the same block stay valid.
+    if !resets.is_empty() {
+        let reset_id = find_or_add_reset(program);
+        resets.sort_unstable();
+        for ((block_id, idx), slot_id) in resets.iter().rev() {
+            let qubit = Operand::Literal(Literal::Qubit(slot_renumbering[*slot_id as usize]));
+            program
+                .get_block_mut(*block_id)
+                .0
+                .insert(*idx, Instruction::Call(reset_id, vec![qubit], None));
+        }
+    }
+
+    program.num_qubits = u32::try_from(slots.len()).expect("qubit count should fit into u32");
+    report.qubits_after = program.num_qubits;
+    report.resets_inserted = u32::try_from(resets.len()).expect("reset count should fit into u32");
+    report
+}
+
+fn is_supported(program: &Program) -> bool {
+    let only_entry_has_body = program
+        .callables
+        .iter()
+        .all(|(callable_id, callable)| callable.body.is_none() || callable_id == program.entry);
+
+    // Block IDs are topologically ordered for acyclic programs, so any edge to a block with a lower or equal ID
+    // is a back edge.
+    let is_acyclic = program.blocks.iter().all(|(block_id, block)| {
+        get_block_successors(block)
+            .into_iter()
+            .all(|successor| successor > block_id)
+    });
+
+    let has_dynamic_qubits = program.blocks.values().any(|block| {
+        block.0.iter().any(|instr| match instr {
+            Instruction::Store(_, var) => var.ty == Ty::Qubit,
+            Instruction::Call(_, args, _) => args
+                .iter()
+                .any(|arg| matches!(arg, Operand::Variable(var) if var.ty == Ty::Qubit)),
+            _ => false,
+        })
+    });
+
+    only_entry_has_body && is_acyclic && !has_dynamic_qubits
+}
+
+// Returns the lifetimes of the qubits used in the program, ordered by their first use.
+fn get_lifetimes(program: &Program) -> Vec<Lifetime> {
+    let mut lifetimes: Vec<Lifetime> = Vec::new();
+    let mut lifetime_indices = FxHashMap::default();
+    for (block_id, block) in program.blocks.iter() {
+        for (idx, instr) in block.0.iter().enumerate() {
+            let Instruction::Call(_, args, _) = instr else {
+                continue;
+            };
+            for arg in args {
+                let Operand::Literal(Literal::Qubit(qubit)) = arg else {
+                    continue;
+                };
+                let position = (block_id, idx);
+                if let Some(lifetime_idx) = lifetime_indices.get(qubit) {
+                    let lifetime: &mut Lifetime = &mut lifetimes[*lifetime_idx];
+                    lifetime.last = position;
+                    lifetime.blocks.insert(block_id);
+                } else {
+                    lifetime_indices.insert(*qubit, lifetimes.len());
+                    let mut blocks = FxHashSet::default();
+                    blocks.insert(block_id);
+                    lifetimes.push(Lifetime {
+                        qubit: *qubit,
+                        first: position,
+                        last: position,
+                        blocks,
+                    });
+                }
+            }
+        }
+    }
+    lifetimes
+}
+
+fn leaves_qubit_in_zero(program: &Program, (block_id, idx): Position) -> bool {
+    let Instruction::Call(callable_id, ..) = &program.get_block(block_id).0[idx] else {
+        panic!("qubit should only be used in calls");
+    };
+    let callable = program.get_callable(*callable_id);
+    callable.call_type == CallableType::Reset || callable.name == "__quantum__qis__mresetz__body"
+}
+
+fn find_or_add_reset(program: &mut Program) -> CallableId {
+    if let Some((callable_id, _)) = program
+        .callables
+        .iter()
+        .find(|(_, callable)| callable.call_type == CallableType::Reset)
+    {
+        return callable_id;
+    }
+    let reset_id = CallableId(
+        program
+            .callables
+            .iter()
+            .map(|(id, _)| id.0)
+            .max()
+            .expect("should be at least one callable")
+            + 1,
+    );
+    program.callables.insert(
+        reset_id,
+        Callable {
+            name: "__quantum__qis__reset__body".to_string(),
+            input_type: vec![Ty::Qubit],
+            output_type: None,
+            body: None,
+            call_type: CallableType::Reset,
+        },
+    );
+    reset_id
+}
@@ -0,0 +1,342 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::too_many_lines, clippy::needless_raw_string_hashes)]
+
+use expect_test::expect;
+use qsc_data_structures::target::TargetCapabilityFlags;
+
+use crate::{
+    builder::{
+        bell_program, cx_decl, h_decl, mresetz_decl, mz_decl, new_program, read_result_decl, x_decl,
+    },
+    passes::check_and_transform,
+    rir::{
+        Block, BlockId, CallableId, CallableType, Instruction, Literal, Operand, Program, Ty,
+        Variable, VariableId,
+    },
+};
+
+use super::{reuse_qubits, QubitReuseReport};
+
+fn qubit(id: u32) -> Operand {
+    Operand::Literal(Literal::Qubit(id))
+}
+
+fn result(id: u32) -> Operand {
+    Operand::Literal(Literal::Result(id))
+}
+
+#[test]
+fn reuse_qubits_leaves_program_with_overlapping_lifetimes_unchanged() {
+    let mut program = bell_program();
+    program.config.capabilities =
+        TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::QubitReset;
+    let program_before = program.to_string();
+    let report = reuse_qubits(&mut program);
+    assert_eq!(program.to_string(), program_before);
+    assert_eq!(
+        report,
+        QubitReuseReport {
+            qubits_before: 2,
+            qubits_after: 2,
+            resets_inserted: 0,
+        }
+    );
+}
+
+#[test]
+fn reuse_qubits_reuses_qubit_after_mresetz_without_reset() {
+    const H: CallableId = CallableId(1);
+    const MRESETZ: CallableId = CallableId(2);
+    let mut program = new_program();
+    program.num_qubits = 3;
+    program.callables.insert(H, h_decl());
+    program.callables.insert(MRESETZ, mresetz_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(H, vec![qubit(0)], None),
+            Instruction::Call(MRESETZ, vec![qubit(0), result(0)], None),
+            Instruction::Call(H, vec![qubit(1)], None),
+            Instruction::Call(MRESETZ, vec![qubit(1), result(1)], None),
+            Instruction::Call(H, vec![qubit(2)], None),
+            Instruction::Call(MRESETZ, vec![qubit(2), result(2)], None),
+            Instruction::Return,
+        ]),
+    );
+
+    let report = reuse_qubits(&mut program);
+    expect![[r#"
+        Block:
+            Call id(1), args( Qubit(0), )
+            Call id(2), args( Qubit(0), Result(0), )
+            Call id(1), args( Qubit(0), )
+            Call id(2), args( Qubit(0), Result(1), )
+            Call id(1), args( Qubit(0), )
+            Call id(2), args( Qubit(0), Result(2), )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(0)).to_string());
+    assert_eq!(program.num_qubits, 1);
+    assert_eq!(report.qubits_saved(), 2);
+    assert_eq!(report.resets_inserted, 0);
+}
+
+#[test]
+fn reuse_qubits_inserts_reset_after_mz_when_target_supports_reset() {
+    const X: CallableId = CallableId(1);
+    const MZ: CallableId = CallableId(2);
+    let mut program = new_program();
+    program.config.capabilities =
+        TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::QubitReset;
+    program.num_qubits = 2;
+    program.callables.insert(X, x_decl());
+    program.callables.insert(MZ, mz_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(X, vec![qubit(0)], None),
+            Instruction::Call(MZ, vec![qubit(0), result(0)], None),
+            Instruction::Call(X, vec![qubit(1)], None),
+            Instruction::Call(MZ, vec![qubit(1), result(1)], None),
+            Instruction::Return,
+        ]),
+    );
+
+    let report = reuse_qubits(&mut program);
+    expect![[r#"
+        Block:
+            Call id(1), args( Qubit(0), )
+            Call id(2), args( Qubit(0), Result(0), )
+            Call id(3), args( Qubit(0), )
+            Call id(1), args( Qubit(0), )
+            Call id(2), args( Qubit(0), Result(1), )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(0)).to_string());
+    assert_eq!(
+        program.get_callable(CallableId(3)).call_type,
+        CallableType::Reset
+    );
+    assert_eq!(
+        report,
+        QubitReuseReport {
+            qubits_before: 2,
+            qubits_after: 1,
+            resets_inserted: 1,
+        }
+    );
+}
+
+#[test]
+fn reuse_qubits_does_not_reuse_qubit_needing_reset_when_target_does_not_support_reset() {
+    const X: CallableId = CallableId(1);
+    const MZ: CallableId = CallableId(2);
+    let mut program = new_program();
+    program.config.capabilities = TargetCapabilityFlags::Adaptive;
+    program.num_qubits = 2;
+    program.callables.insert(X, x_decl());
+    program.callables.insert(MZ, mz_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(X, vec![qubit(0)], None),
+            Instruction::Call(MZ, vec![qubit(0), result(0)], None),
+            Instruction::Call(X, vec![qubit(1)], None),
+            Instruction::Call(MZ, vec![qubit(1), result(1)], None),
+            Instruction::Return,
+        ]),
+    );
+
+    let program_before = program.to_string();
+    let report = reuse_qubits(&mut program);
+    assert_eq!(program.to_string(), program_before);
+    assert_eq!(report.qubits_saved(), 0);
+}
+
+#[test]
+fn reuse_qubits_keeps_order_of_qubit_ids() {
+    const H: CallableId = CallableId(1);
+    const CX: CallableId = CallableId(2);
+    const MRESETZ: CallableId = CallableId(3);
+    let mut program = new_program();
+    program.num_qubits = 3;
+    program.callables.insert(H, h_decl());
+    program.callables.insert(CX, cx_decl());
+    program.callables.insert(MRESETZ, mresetz_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(H, vec![qubit(2)], None),
+            Instruction::Call(MRESETZ, vec![qubit(2), result(0)], None),
+            Instruction::Call(CX, vec![qubit(1), qubit(0)], None),
+            Instruction::Call(MRESETZ, vec![qubit(0), result(1)], None),
+            Instruction::Call(MRESETZ, vec![qubit(1), result(2)], None),
+            Instruction::Return,
+        ]),
+    );
+
+    let report = reuse_qubits(&mut program);
+    expect![[r#"
+        Block:
+            Call id(1), args( Qubit(1), )
+            Call id(3), args( Qubit(1), Result(0), )
+            Call id(2), args( Qubit(1), Qubit(0), )
+            Call id(3), args( Qubit(0), Result(1), )
+            Call id(3), args( Qubit(1), Result(2), )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(0)).to_string());
+    assert_eq!(report.qubits_saved(), 1);
+}
+
+#[test]
+fn reuse_qubits_inserts_reset_when_previous_qubit_is_only_reset_on_some_paths() {
+    const H: CallableId = CallableId(1);
+    const MRESETZ: CallableId = CallableId(2);
+    const READ: CallableId = CallableId(3);
+    const MZ: CallableId = CallableId(4);
+    let cond = Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Boolean,
+    };
+    let mut program = new_program();
+    program.config.capabilities =
+        TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::QubitReset;
+    program.num_qubits = 3;
+    program.callables.insert(H, h_decl());
+    program.callables.insert(MRESETZ, mresetz_decl());
+    program.callables.insert(READ, read_result_decl());
+    program.callables.insert(MZ, mz_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(H, vec![qubit(0)], None),
+            Instruction::Call(MZ, vec![qubit(2), result(0)], None),
+            Instruction::Call(READ, vec![result(0)], Some(cond)),
+            Instruction::Branch(cond, BlockId(1), BlockId(2)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Call(MRESETZ, vec![qubit(0), result(1)], None),
+            Instruction::Jump(BlockId(2)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(2),
+        Block(vec![
+            Instruction::Call(H, vec![qubit(1)], None),
+            Instruction::Call(MRESETZ, vec![qubit(1), result(2)], None),
+            Instruction::Return,
+        ]),
+    );
+
+    let report = reuse_qubits(&mut program);
+    expect![[r#"
+        Block:
+            Call id(5), args( Qubit(0), )
+            Call id(1), args( Qubit(0), )
+            Call id(2), args( Qubit(0), Result(2), )
+            Return"#]]
+    .assert_eq(&program.get_block(BlockId(2)).to_string());
+    assert_eq!(
+        report,
+        QubitReuseReport {
+            qubits_before: 3,
+            qubits_after: 2,
+            resets_inserted: 1,
+        }
+    );
+}
+
+#[test]
+fn reuse_qubits_leaves_program_with_loop_unchanged() {
+    const H: CallableId = CallableId(1);
+    const MRESETZ: CallableId = CallableId(2);
+    const READ: CallableId = CallableId(3);
+    let cond = Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Boolean,
+    };
+    let mut program = new_program();
+    program.config.capabilities =
+        TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::QubitReset;
+    program.num_qubits = 2;
+    program.callables.insert(H, h_decl());
+    program.callables.insert(MRESETZ, mresetz_decl());
+    program.callables.insert(READ, read_result_decl());
+    program
+        .blocks
+        .insert(BlockId(0), Block(vec![Instruction::Jump(BlockId(1))]));
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Call(H, vec![qubit(0)], None),
+            Instruction::Call(MRESETZ, vec![qubit(0), result(0)], None),
+            Instruction::Call(READ, vec![result(0)], Some(cond)),
+            Instruction::Branch(cond, BlockId(1), BlockId(2)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(2),
+        Block(vec![
+            Instruction::Call(H, vec![qubit(1)], None),
+            Instruction::Call(MRESETZ, vec![qubit(1), result(1)], None),
+            Instruction::Return,
+        ]),
+    );
+
+    let program_before = program.to_string();
+    let report = reuse_qubits(&mut program);
+    assert_eq!(program.to_string(), program_before);
+    assert_eq!(report.qubits_saved(), 0);
+}
+
+fn mresetz_program(capabilities: TargetCapabilityFlags) -> Program {
+    const H: CallableId = CallableId(1);
+    const MRESETZ: CallableId = CallableId(2);
+    let mut program = new_program();
+    program.config.capabilities = capabilities;
+    program.config.reuse_qubits = true;
+    program.num_qubits = 3;
+    program.num_results = 3;
+    program.callables.insert(H, h_decl());
+    program.callables.insert(MRESETZ, mresetz_decl());
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(H, vec![qubit(0)], None),
+            Instruction::Call(MRESETZ, vec![qubit(0), result(0)], None),
+            Instruction::Call(H, vec![qubit(1)], None),
+            Instruction::Call(MRESETZ, vec![qubit(1), result(1)], None),
+            Instruction::Call(H, vec![qubit(2)], None),
+            Instruction::Call(MRESETZ, vec![qubit(2), result(2)], None),
+            Instruction::Return,
+        ]),
+    );
+    program
+}
+
+#[test]
+fn check_and_transform_reuses_qubits_when_target_supports_reset() {
+    let mut program =
+        mresetz_program(TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::QubitReset);
+    let report = check_and_transform(&mut program);
+    assert_eq!(program.num_qubits, 1);
+    assert_eq!(
+        report,
+        Some(QubitReuseReport {
+            qubits_before: 3,
+            qubits_after: 1,
+            resets_inserted: 0,
+        })
+    );
+}
+
+#[test]
+fn check_and_transform_skips_qubit_reuse_when_target_does_not_support_reset() {
+    let mut program = mresetz_program(TargetCapabilityFlags::Adaptive);
+    let report = check_and_transform(&mut program);
+    assert_eq!(program.num_qubits, 3);
+    assert_eq!(report, None);
+}
@@ -7,12 +7,14 @@ mod tests;
 use rustc_hash::FxHashMap;
 
 use crate::{
-    rir::{Instruction, Program},
-    utils::build_predecessors_map,
+    rir::{BlockId, Instruction, Program},
+    utils::{build_predecessors_map, remove_unreachable_blocks},
 };
 
 /// Simplify control flow of the program.
 /// For now, this only removes single redundant blocks where the block is the only successor of its only predecessor.
+/// Blocks that start with a phi node are left in place, and phi nodes in later blocks that refer to a removed block
+/// are updated to refer to the block it was merged into.
 pub fn simplify_control_flow(program: &mut Program) {
     // Use a map to track which blocks have been merged into their predecessors. This helps
     // find the ultimate target block for later merges.
@@ -24,6 +26,10 @@ pub fn simplify_control_flow(program: &mut Program) {
     for (block_id, preds) in preds_map.iter() {
         if preds.len() == 1
             && program.get_block(preds[0]).0.last() == Some(&Instruction::Jump(block_id))
+            && !matches!(
+                program.get_block(block_id).0.first(),
+                Some(Instruction::Phi(..))
+            )
         {
             merge_map.insert(block_id, preds[0]);
 
@@ -52,4 +58,61 @@ pub fn simplify_control_flow(program: &mut Program) {
     for block_id in merge_map.keys() {
         program.blocks.remove(*block_id);
     }
+
+    // The terminators of merged blocks now end their ultimate target blocks, so any phi node that refers to a merged
+    // block as a predecessor must refer to that target block instead.
+    if merge_map.is_empty() {
+        return;
+    }
+    for block in program.blocks.values_mut() {
+        for instr in &mut block.0 {
+            if let Instruction::Phi(args, _) = instr {
+                for (_, pred) in args {
+                    while let Some(mapped_block) = merge_map.get(pred) {
+                        *pred = *mapped_block;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Replaces branches whose targets are the same block, or empty blocks that jump to the same block, with a jump to
+/// that block, removing any blocks that are no longer reachable. Branches to a block with phi nodes are left in place
+/// since the phi nodes refer to the branch targets.
+pub fn remove_redundant_branches(program: &mut Program) {
+    let jump_target =
+        |program: &Program, block_id: BlockId| match program.get_block(block_id).0.as_slice() {
+            [Instruction::Jump(target)] => *target,
+            _ => block_id,
+        };
+    let mut changed = false;
+    let block_ids = program
+        .blocks
+        .iter()
+        .map(|(block_id, _)| block_id)
+        .collect::<Vec<_>>();
+    for block_id in block_ids {
+        let Some(Instruction::Branch(_, if_true, if_false)) = program.get_block(block_id).0.last()
+        else {
+            continue;
+        };
+        let target = jump_target(program, *if_true);
+        if target != jump_target(program, *if_false)
+            || program
+                .get_block(target)
+                .0
+                .iter()
+                .any(|instr| matches!(instr, Instruction::Phi(..)))
+        {
+            continue;
+        }
+        let block = program.get_block_mut(block_id);
+        block.0.pop();
+        block.0.push(Instruction::Jump(target));
+        changed = true;
+    }
+    if changed {
+        remove_unreachable_blocks(program);
+    }
 }
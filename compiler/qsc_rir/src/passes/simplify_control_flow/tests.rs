@@ -6,11 +6,11 @@
 use expect_test::expect;
 
 use crate::{
-    builder::{bell_program, teleport_program},
+    builder::{bell_program, new_program, teleport_program},
     rir::{Block, BlockId, Instruction, Literal, Operand, Program, Ty, Variable, VariableId},
 };
 
-use super::simplify_control_flow;
+use super::{remove_redundant_branches, simplify_control_flow};
 
 #[test]
 fn simplify_control_flow_leaves_program_with_single_block_unchanged() {
@@ -472,3 +472,149 @@ fn simplify_control_flow_removes_redundant_blocks_across_out_of_order_branches()
             num_results: 0"#]]
     .assert_eq(&program.to_string());
 }
+
+#[test]
+fn simplify_control_flow_updates_phi_nodes_referring_to_merged_blocks() {
+    let mut program = Program::new();
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![Instruction::Branch(
+            Variable {
+                variable_id: VariableId(0),
+                ty: Ty::Boolean,
+            },
+            BlockId(1),
+            BlockId(3),
+        )]),
+    );
+    program
+        .blocks
+        .insert(BlockId(1), Block(vec![Instruction::Jump(BlockId(2))]));
+    program
+        .blocks
+        .insert(BlockId(2), Block(vec![Instruction::Jump(BlockId(3))]));
+    program.blocks.insert(
+        BlockId(3),
+        Block(vec![
+            Instruction::Phi(
+                vec![
+                    (Operand::Literal(Literal::Integer(1)), BlockId(2)),
+                    (Operand::Literal(Literal::Integer(2)), BlockId(0)),
+                ],
+                Variable {
+                    variable_id: VariableId(1),
+                    ty: Ty::Integer,
+                },
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    simplify_control_flow(&mut program);
+    expect![[r#"
+        Program:
+            entry: 0
+            callables:
+            blocks:
+                Block 0: Block:
+                    Branch Variable(0, Boolean), 1, 3
+                Block 1: Block:
+                    Jump(3)
+                Block 3: Block:
+                    Variable(1, Integer) = Phi ( [Integer(1), 1], [Integer(2), 0], )
+                    Return
+            config: Config:
+                capabilities: Base
+            num_qubits: 0
+            num_results: 0"#]]
+    .assert_eq(&program.to_string());
+}
+
+#[test]
+fn remove_redundant_branches_replaces_branch_to_empty_blocks_with_jump() {
+    let mut program = new_program();
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![Instruction::Branch(
+            Variable {
+                variable_id: VariableId(0),
+                ty: Ty::Boolean,
+            },
+            BlockId(1),
+            BlockId(2),
+        )]),
+    );
+    program
+        .blocks
+        .insert(BlockId(1), Block(vec![Instruction::Jump(BlockId(3))]));
+    program
+        .blocks
+        .insert(BlockId(2), Block(vec![Instruction::Jump(BlockId(3))]));
+    program
+        .blocks
+        .insert(BlockId(3), Block(vec![Instruction::Return]));
+
+    remove_redundant_branches(&mut program);
+    expect![[r#"
+        Program:
+            entry: 0
+            callables:
+                Callable 0: Callable:
+                    name: main
+                    call_type: Regular
+                    input_type: <VOID>
+                    output_type: <VOID>
+                    body: 0
+            blocks:
+                Block 0: Block:
+                    Jump(3)
+                Block 3: Block:
+                    Return
+            config: Config:
+                capabilities: Base
+            num_qubits: 0
+            num_results: 0"#]]
+    .assert_eq(&program.to_string());
+}
+
+#[test]
+fn remove_redundant_branches_leaves_branch_to_block_with_phi_unchanged() {
+    let mut program = new_program();
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![Instruction::Branch(
+            Variable {
+                variable_id: VariableId(0),
+                ty: Ty::Boolean,
+            },
+            BlockId(1),
+            BlockId(2),
+        )]),
+    );
+    program
+        .blocks
+        .insert(BlockId(1), Block(vec![Instruction::Jump(BlockId(3))]));
+    program
+        .blocks
+        .insert(BlockId(2), Block(vec![Instruction::Jump(BlockId(3))]));
+    program.blocks.insert(
+        BlockId(3),
+        Block(vec![
+            Instruction::Phi(
+                vec![
+                    (Operand::Literal(Literal::Integer(1)), BlockId(1)),
+                    (Operand::Literal(Literal::Integer(2)), BlockId(2)),
+                ],
+                Variable {
+                    variable_id: VariableId(1),
+                    ty: Ty::Integer,
+                },
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    let program_before = program.to_string();
+    remove_redundant_branches(&mut program);
+    assert_eq!(program.to_string(), program_before);
+}
@@ -107,63 +107,48 @@ fn get_variable_uses(program: &Program) -> IndexMap<VariableId, Vec<(BlockId, us
     for (block_id, block) in program.blocks.iter() {
         for (idx, instr) in block.0.iter().enumerate() {
             match instr {
-                // Single variable
-                Instruction::Add(Operand::Variable(var), Operand::Literal(_), _)
-                | Instruction::Add(Operand::Literal(_), Operand::Variable(var), _)
-                | Instruction::Sub(Operand::Variable(var), Operand::Literal(_), _)
-                | Instruction::Sub(Operand::Literal(_), Operand::Variable(var), _)
-                | Instruction::Mul(Operand::Variable(var), Operand::Literal(_), _)
-                | Instruction::Mul(Operand::Literal(_), Operand::Variable(var), _)
-                | Instruction::Sdiv(Operand::Variable(var), Operand::Literal(_), _)
-                | Instruction::Sdiv(Operand::Literal(_), Operand::Variable(var), _)
-                | Instruction::Srem(Operand::Variable(var), Operand::Literal(_), _)
-                | Instruction::Srem(Operand::Literal(_), Operand::Variable(var), _)
-                | Instruction::Shl(Operand::Variable(var), Operand::Literal(_), _)
-                | Instruction::Shl(Operand::Literal(_), Operand::Variable(var), _)
-                | Instruction::Ashr(Operand::Variable(var), Operand::Literal(_), _)
-                | Instruction::Ashr(Operand::Literal(_), Operand::Variable(var), _)
-                | Instruction::Icmp(_, Operand::Variable(var), Operand::Literal(_), _)
-                | Instruction::Icmp(_, Operand::Literal(_), Operand::Variable(var), _)
-                | Instruction::LogicalNot(Operand::Variable(var), _)
-                | Instruction::LogicalAnd(Operand::Variable(var), Operand::Literal(_), _)
-                | Instruction::LogicalAnd(Operand::Literal(_), Operand::Variable(var), _)
-                | Instruction::LogicalOr(Operand::Variable(var), Operand::Literal(_), _)
-                | Instruction::LogicalOr(Operand::Literal(_), Operand::Variable(var), _)
-                | Instruction::BitwiseNot(Operand::Variable(var), _)
-                | Instruction::BitwiseAnd(Operand::Variable(var), Operand::Literal(_), _)
-                | Instruction::BitwiseAnd(Operand::Literal(_), Operand::Variable(var), _)
-                | Instruction::BitwiseOr(Operand::Variable(var), Operand::Literal(_), _)
-                | Instruction::BitwiseOr(Operand::Literal(_), Operand::Variable(var), _)
-                | Instruction::BitwiseXor(Operand::Variable(var), Operand::Literal(_), _)
-                | Instruction::BitwiseXor(Operand::Literal(_), Operand::Variable(var), _)
-                | Instruction::Branch(var, _, _) => {
+                Instruction::Add(lhs, rhs, _)
+                | Instruction::Sub(lhs, rhs, _)
+                | Instruction::Mul(lhs, rhs, _)
+                | Instruction::Sdiv(lhs, rhs, _)
+                | Instruction::Srem(lhs, rhs, _)
+                | Instruction::Shl(lhs, rhs, _)
+                | Instruction::Ashr(lhs, rhs, _)
+                | Instruction::Icmp(_, lhs, rhs, _)
+                | Instruction::Fadd(lhs, rhs, _)
+                | Instruction::Fsub(lhs, rhs, _)
+                | Instruction::Fmul(lhs, rhs, _)
+                | Instruction::Fdiv(lhs, rhs, _)
+                | Instruction::Fcmp(_, lhs, rhs, _)
+                | Instruction::LogicalAnd(lhs, rhs, _)
+                | Instruction::LogicalOr(lhs, rhs, _)
+                | Instruction::BitwiseAnd(lhs, rhs, _)
+                | Instruction::BitwiseOr(lhs, rhs, _)
+                | Instruction::BitwiseXor(lhs, rhs, _) => {
+                    // If an instruction has no variables, it should have been inlined by partial eval.
+                    let vars = operand_variables([lhs, rhs]);
+                    assert!(
+                        !vars.is_empty(),
+                        "{block_id:?}, instruction {idx} has no variables: {instr}"
+                    );
+                    for var_id in vars {
+                        add_use(var_id, block_id, idx);
+                    }
+                }
+                Instruction::LogicalNot(opr, _) | Instruction::BitwiseNot(opr, _) => {
+                    let Operand::Variable(var) = opr else {
+                        panic!("{block_id:?}, instruction {idx} has no variables: {instr}")
+                    };
                     add_use(var.variable_id, block_id, idx);
                 }
-
-                // Double variable
-                Instruction::Add(Operand::Variable(var1), Operand::Variable(var2), _)
-                | Instruction::Sub(Operand::Variable(var1), Operand::Variable(var2), _)
-                | Instruction::Mul(Operand::Variable(var1), Operand::Variable(var2), _)
-                | Instruction::Sdiv(Operand::Variable(var1), Operand::Variable(var2), _)
-                | Instruction::Srem(Operand::Variable(var1), Operand::Variable(var2), _)
-                | Instruction::Shl(Operand::Variable(var1), Operand::Variable(var2), _)
-                | Instruction::Ashr(Operand::Variable(var1), Operand::Variable(var2), _)
-                | Instruction::Icmp(_, Operand::Variable(var1), Operand::Variable(var2), _)
-                | Instruction::LogicalAnd(Operand::Variable(var1), Operand::Variable(var2), _)
-                | Instruction::LogicalOr(Operand::Variable(var1), Operand::Variable(var2), _)
-                | Instruction::BitwiseAnd(Operand::Variable(var1), Operand::Variable(var2), _)
-                | Instruction::BitwiseOr(Operand::Variable(var1), Operand::Variable(var2), _)
-                | Instruction::BitwiseXor(Operand::Variable(var1), Operand::Variable(var2), _) => {
-                    add_use(var1.variable_id, block_id, idx);
-                    add_use(var2.variable_id, block_id, idx);
+                Instruction::Branch(var, _, _) => {
+                    add_use(var.variable_id, block_id, idx);
                 }
 
                 // Multiple variables
                 Instruction::Call(_, vals, _) => {
-                    for val in vals {
-                        if let Operand::Variable(var) = val {
-                            add_use(var.variable_id, block_id, idx);
-                        }
+                    for var_id in operand_variables(vals.iter()) {
+                        add_use(var_id, block_id, idx);
                     }
                 }
                 Instruction::Phi(args, _) => {
@@ -177,25 +162,6 @@ fn get_variable_uses(program: &Program) -> IndexMap<VariableId, Vec<(BlockId, us
                     }
                 }
 
-                // If an instruction has no variables, it should have been inlined by partial eval.
-                Instruction::Add(Operand::Literal(_), Operand::Literal(_), _)
-                | Instruction::Sub(Operand::Literal(_), Operand::Literal(_), _)
-                | Instruction::Mul(Operand::Literal(_), Operand::Literal(_), _)
-                | Instruction::Sdiv(Operand::Literal(_), Operand::Literal(_), _)
-                | Instruction::Srem(Operand::Literal(_), Operand::Literal(_), _)
-                | Instruction::Shl(Operand::Literal(_), Operand::Literal(_), _)
-                | Instruction::Ashr(Operand::Literal(_), Operand::Literal(_), _)
-                | Instruction::Icmp(_, Operand::Literal(_), Operand::Literal(_), _)
-                | Instruction::LogicalNot(Operand::Literal(_), _)
-                | Instruction::LogicalAnd(Operand::Literal(_), Operand::Literal(_), _)
-                | Instruction::LogicalOr(Operand::Literal(_), Operand::Literal(_), _)
-                | Instruction::BitwiseNot(Operand::Literal(_), _)
-                | Instruction::BitwiseAnd(Operand::Literal(_), Operand::Literal(_), _)
-                | Instruction::BitwiseOr(Operand::Literal(_), Operand::Literal(_), _)
-                | Instruction::BitwiseXor(Operand::Literal(_), Operand::Literal(_), _) => {
-                    panic!("{block_id:?}, instruction {idx} has no variables: {instr}")
-                }
-
                 Instruction::Jump(..) | Instruction::Return => {}
 
                 Instruction::Store(..) => {
@@ -206,3 +172,13 @@ fn get_variable_uses(program: &Program) -> IndexMap<VariableId, Vec<(BlockId, us
     }
     uses
 }
+
+fn operand_variables<'a>(operands: impl IntoIterator<Item = &'a Operand>) -> Vec<VariableId> {
+    operands
+        .into_iter()
+        .filter_map(|opr| match opr {
+            Operand::Variable(var) => Some(var.variable_id),
+            Operand::Literal(_) => None,
+        })
+        .collect()
+}
@@ -6,16 +6,20 @@ mod tests;
 
 use crate::{
     rir::{Block, BlockId, Instruction, Operand, Program, Variable, VariableId},
-    utils::get_variable_assignments,
+    utils::{get_variable_assignments, resolve_operand},
 };
 use qsc_data_structures::index_map::IndexMap;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 /// Transforms the program into Single Static Assignment (SSA) form by inserting phi nodes
 /// at the beginning of blocks where necessary, allowing the removal of store instructions.
 pub fn transform_to_ssa(program: &mut Program, preds: &IndexMap<BlockId, Vec<BlockId>>) {
-    // Ensure that the graph is acyclic before proceeding. Current approach does not support cycles.
-    ensure_acyclic(preds);
+    // The single pass approach below only works when the graph is acyclic, so programs with loops
+    // are handled separately.
+    if !is_acyclic(preds) {
+        transform_cyclic_to_ssa(program, preds);
+        return;
+    }
 
     // First, remove store instructions and propagate variables through individual blocks.
     // This produces a per-block map of dynamic variables to their values.
@@ -47,8 +51,17 @@ pub fn transform_to_ssa(program: &mut Program, preds: &IndexMap<BlockId, Vec<Blo
             .split_first()
             .expect("block should have at least one predecessor");
 
-        // The block is only a candidate for phi nodes if it has multiple predecessors.
-        if !rest_preds.is_empty() {
+        if rest_preds.is_empty() {
+            // A block with a single predecessor inherits the values resolved in that predecessor
+            // after the initial mapping, such as the variables created by phi nodes, which replace
+            // any orphaned uses in this block and propagate on to its successors.
+            var_map_updates.clone_from(
+                block_var_map
+                    .get(*first_pred)
+                    .expect("block should have variable map"),
+            );
+        } else {
+            // The block is only a candidate for phi nodes if it has multiple predecessors.
             // Check each variable in the first predecessor's variable map, and if any other
             // predecessor has a different value for the variable, a phi node is needed.
             for (var_id, operand) in block_var_map
@@ -57,27 +70,32 @@ pub fn transform_to_ssa(program: &mut Program, preds: &IndexMap<BlockId, Vec<Blo
             {
                 let mut phi_nodes = FxHashMap::default();
 
-                if rest_preds.iter().any(|pred| {
-                    block_var_map
-                        .get(*pred)
-                        .expect("block should have variable map")
-                        .get(var_id)
-                        != Some(operand)
-                }) {
+                let rest_operands = rest_preds
+                    .iter()
+                    .map(|pred| {
+                        block_var_map
+                            .get(*pred)
+                            .expect("block should have variable map")
+                            .get(var_id)
+                            .copied()
+                    })
+                    .collect::<Vec<_>>();
+                if rest_operands.contains(&None) {
+                    // A variable that is not defined in all predecessors went out of scope on some
+                    // path and cannot be used in this block, so it is not propagated.
+                    continue;
+                }
+
+                if rest_operands.iter().any(|rest| *rest != Some(*operand)) {
                     // Some predecessors have different values for this variable, so a phi node is needed.
                     // Start with the first predecessor's value and block id, then add the values from the other predecessors.
                     let mut phi_args = vec![(*operand, *first_pred)];
-                    for pred in rest_preds {
-                        phi_args.push((
-                            block_var_map
-                                .get(*pred)
-                                .expect("block should have variable map")
-                                .get(var_id)
-                                .copied()
-                                .expect("variable should be defined in all predecessors"),
-                            *pred,
-                        ));
-                    }
+                    phi_args.extend(
+                        rest_operands
+                            .into_iter()
+                            .flatten()
+                            .zip(rest_preds.iter().copied()),
+                    );
                     phi_nodes.insert(*var_id, phi_args);
                 } else {
                     // If all predecessors have the same value for this variable, the value can be propagated.
@@ -99,28 +117,243 @@ pub fn transform_to_ssa(program: &mut Program, preds: &IndexMap<BlockId, Vec<Blo
                     next_var_id = next_var_id.successor();
                 }
             }
+        }
+
+        // Now that the block has finished processing, apply any updates to the block and
+        // merge those updates into the stored variable map to propagate to successors.
+        map_variable_use_in_block(block, &mut var_map_updates);
+        // Values stored in the block may refer to orphaned variables as well, so those are replaced first.
+        let var_map = block_var_map
+            .get_mut(block_id)
+            .expect("block should have variable map");
+        for operand in var_map.values_mut() {
+            *operand = operand.mapped(&mut var_map_updates);
+        }
+        for (var_id, operand) in var_map_updates {
+            var_map.entry(var_id).or_insert(operand);
+        }
+    }
+}
+
+// Block ids are expected to be in topological order when the graph is acyclic, so any block with a
+// predecessor with a block id greater than or equal to its own indicates a back edge and therefore a cycle.
+fn is_acyclic(preds: &IndexMap<BlockId, Vec<BlockId>>) -> bool {
+    preds
+        .iter()
+        .all(|(block_id, block_preds)| block_preds.iter().all(|pred| *pred < block_id))
+}
 
-            // Now that the block has finished processing, apply any updates to the block and
-            // merge those updates into the stored variable map to propagate to successors.
-            map_variable_use_in_block(block, &mut var_map_updates);
-            for (var_id, operand) in var_map_updates {
-                let var_map = block_var_map
-                    .get_mut(block_id)
-                    .expect("block should have variable map");
-                var_map.entry(var_id).or_insert(operand);
+/// Transforms a program with cycles into SSA form. Since the value of a variable at the start of a loop header
+/// depends on the values at the end of blocks that have not been visited yet, values are resolved on demand, as
+/// described in [Simple and Efficient Construction of Static Single Assignment Form](https://doi.org/10.1007/978-3-642-37051-9_6)
+/// by Braun et al.:
+/// - Each block is first processed independently, leaving uses of variables not stored earlier in the same block
+///   to refer to the value of the variable on entry to the block.
+/// - The value on entry to a block is the value at the end of its predecessor if it has only one, or a phi node
+///   over the values at the end of all its predecessors otherwise.
+/// - Phi nodes that only ever refer to a single value besides themselves are replaced by that value.
+fn transform_cyclic_to_ssa(program: &mut Program, preds: &IndexMap<BlockId, Vec<BlockId>>) {
+    // Remove store instructions and propagate variables through individual blocks, tracking which stored
+    // variables are used before being stored in each block.
+    let mut next_var_id = VariableId::default();
+    let mut block_var_map = IndexMap::default();
+    let mut entry_uses = Vec::new();
+    for (block_id, block) in program.blocks.iter_mut() {
+        for instr in &block.0 {
+            if let Instruction::Store(_, var) = instr {
+                next_var_id = next_var_id.max(var.variable_id.successor());
             }
         }
+        let mut mapper = EntryUseTracker {
+            var_map: FxHashMap::default(),
+            entry_uses: Vec::new(),
+        };
+        map_variable_use_in_block(block, &mut mapper);
+        block_var_map.insert(block_id, mapper.var_map);
+        entry_uses.extend(mapper.entry_uses.into_iter().map(|var| (block_id, var)));
+    }
+    let stored_vars = block_var_map
+        .values()
+        .flat_map(FxHashMap::keys)
+        .copied()
+        .collect::<FxHashSet<_>>();
+    entry_uses.retain(|(_, var)| stored_vars.contains(&var.variable_id));
+
+    // Get the next available variable ID for use in newly generated phi nodes, which must not conflict with
+    // the ids of the removed stored variables still referred to by entry uses.
+    if let Some((var_id, _)) = get_variable_assignments(program).iter().last() {
+        next_var_id = next_var_id.max(var_id.successor());
+    }
+
+    // Resolve the entry value for each use, creating phi nodes where necessary.
+    let mut resolver = EntryValueResolver {
+        preds,
+        block_var_map: &block_var_map,
+        stored_vars: &stored_vars,
+        entry_values: FxHashMap::default(),
+        phi_nodes: Vec::new(),
+        next_var_id,
+    };
+    for (block_id, var) in &entry_uses {
+        resolver.value_at_entry(*block_id, *var);
     }
+    let EntryValueResolver {
+        entry_values,
+        mut phi_nodes,
+        ..
+    } = resolver;
+
+    // Remove trivial phi nodes until no more can be removed, since removing one phi node may make another trivial.
+    let mut replacements = FxHashMap::default();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        phi_nodes.retain(|(_, phi_var, args)| {
+            let mut values = Vec::new();
+            for (arg, _) in args {
+                let arg = resolve_operand(&replacements, *arg);
+                if arg != Operand::Variable(*phi_var) && !values.contains(&arg) {
+                    values.push(arg);
+                }
+            }
+            match values.as_slice() {
+                [] => panic!("{phi_var:?} has no defined value"),
+                [value] => {
+                    replacements.insert(phi_var.variable_id, *value);
+                    changed = true;
+                    false
+                }
+                _ => true,
+            }
+        });
+    }
+
+    // Insert the remaining phi nodes at the beginning of their blocks and replace all entry uses and uses of
+    // removed phi nodes with their final values.
+    for (block_id, block) in program.blocks.iter_mut() {
+        let mut var_map = replacements
+            .keys()
+            .map(|var_id| {
+                (
+                    *var_id,
+                    resolve_operand(&replacements, replacements[var_id]),
+                )
+            })
+            .collect::<FxHashMap<_, _>>();
+        for (use_block_id, var) in &entry_uses {
+            if *use_block_id == block_id {
+                let value = entry_values[&(block_id, var.variable_id)];
+                var_map.insert(var.variable_id, resolve_operand(&replacements, value));
+            }
+        }
+        map_variable_use_in_block(block, &mut var_map);
+        let phis = phi_nodes
+            .iter()
+            .filter(|(phi_block_id, _, _)| *phi_block_id == block_id)
+            .map(|(_, phi_var, args)| {
+                Instruction::Phi(
+                    args.iter()
+                        .map(|(arg, pred)| (resolve_operand(&replacements, *arg), *pred))
+                        .collect(),
+                    *phi_var,
+                )
+            })
+            .collect::<Vec<_>>();
+        block.0.splice(0..0, phis);
+    }
+}
+
+// Maps stored variables within a single block like the plain variable map, additionally recording the variables
+// used before being stored in the block.
+struct EntryUseTracker {
+    var_map: FxHashMap<VariableId, Operand>,
+    entry_uses: Vec<Variable>,
 }
 
-// For now, SSA transform assumes the graph is acyclic, so verify that no block has a predecessor with
-// a block id less than itself, which would indicate a cycle.
-fn ensure_acyclic(preds: &IndexMap<BlockId, Vec<BlockId>>) {
-    for (block_id, block_preds) in preds.iter() {
-        assert!(
-            !block_preds.iter().any(|pred| *pred >= block_id),
-            "block {block_id:?} has a cycle in its predecessors"
-        );
+impl VariableMapper for EntryUseTracker {
+    fn insert(&mut self, var: Variable, operand: Operand) {
+        self.var_map.insert(var.variable_id, operand);
+    }
+
+    fn to_operand(&mut self, var: Variable) -> Operand {
+        if !self.var_map.contains_key(&var.variable_id)
+            && !self
+                .entry_uses
+                .iter()
+                .any(|used| used.variable_id == var.variable_id)
+        {
+            self.entry_uses.push(var);
+        }
+        self.var_map.to_operand(var)
+    }
+
+    fn to_variable(&mut self, var: Variable) -> Variable {
+        match self.to_operand(var) {
+            Operand::Literal(_) => panic!("literal not supported in this context"),
+            Operand::Variable(var) => var,
+        }
+    }
+}
+
+// A phi node to insert at the start of a block, along with the values it selects from each predecessor.
+type PhiNode = (BlockId, Variable, Vec<(Operand, BlockId)>);
+
+// Resolves the values of stored variables on entry to blocks, creating phi nodes for blocks with multiple
+// predecessors.
+struct EntryValueResolver<'a> {
+    preds: &'a IndexMap<BlockId, Vec<BlockId>>,
+    block_var_map: &'a IndexMap<BlockId, FxHashMap<VariableId, Operand>>,
+    stored_vars: &'a FxHashSet<VariableId>,
+    entry_values: FxHashMap<(BlockId, VariableId), Operand>,
+    phi_nodes: Vec<PhiNode>,
+    next_var_id: VariableId,
+}
+
+impl EntryValueResolver<'_> {
+    fn value_at_end(&mut self, block_id: BlockId, var: Variable) -> Operand {
+        match self
+            .block_var_map
+            .get(block_id)
+            .expect("block should have variable map")
+            .get(&var.variable_id)
+        {
+            // A value stored in the block may itself be the entry value of another stored variable.
+            Some(Operand::Variable(value)) if self.stored_vars.contains(&value.variable_id) => {
+                self.value_at_entry(block_id, *value)
+            }
+            Some(operand) => *operand,
+            None => self.value_at_entry(block_id, var),
+        }
+    }
+
+    fn value_at_entry(&mut self, block_id: BlockId, var: Variable) -> Operand {
+        let key = (block_id, var.variable_id);
+        if let Some(value) = self.entry_values.get(&key) {
+            return *value;
+        }
+        let block_preds = self
+            .preds
+            .get(block_id)
+            .unwrap_or_else(|| panic!("{:?} is used before it is stored", var.variable_id));
+        if let [pred] = block_preds.as_slice() {
+            let value = self.value_at_end(*pred, var);
+            self.entry_values.insert(key, value);
+            return value;
+        }
+
+        // The phi node is recorded as the entry value before visiting the predecessors to break cycles.
+        let phi_var = Variable {
+            variable_id: self.next_var_id,
+            ty: var.ty,
+        };
+        self.next_var_id = self.next_var_id.successor();
+        self.entry_values.insert(key, Operand::Variable(phi_var));
+        let args = block_preds
+            .iter()
+            .map(|pred| (self.value_at_end(*pred, var), *pred))
+            .collect();
+        self.phi_nodes.push((block_id, phi_var, args));
+        Operand::Variable(phi_var)
     }
 }
 
@@ -159,7 +392,8 @@ fn map_variable_use_in_block(block: &mut Block, var_map: &mut impl VariableMappe
         match &mut instr {
             // Track the new value of the variable and omit the store instruction.
             Instruction::Store(operand, var) => {
-                var_map.insert(*var, *operand);
+                let operand = operand.mapped(var_map);
+                var_map.insert(*var, operand);
                 continue;
             }
 
@@ -188,6 +422,11 @@ fn map_variable_use_in_block(block: &mut Block, var_map: &mut impl VariableMappe
             | Instruction::Shl(lhs, rhs, _)
             | Instruction::Ashr(lhs, rhs, _)
             | Instruction::Icmp(_, lhs, rhs, _)
+            | Instruction::Fadd(lhs, rhs, _)
+            | Instruction::Fsub(lhs, rhs, _)
+            | Instruction::Fmul(lhs, rhs, _)
+            | Instruction::Fdiv(lhs, rhs, _)
+            | Instruction::Fcmp(_, lhs, rhs, _)
             | Instruction::LogicalAnd(lhs, rhs, _)
             | Instruction::LogicalOr(lhs, rhs, _)
             | Instruction::BitwiseAnd(lhs, rhs, _)
@@ -211,7 +450,7 @@ fn map_variable_use_in_block(block: &mut Block, var_map: &mut impl VariableMappe
 }
 
 impl Operand {
-    fn mapped(&self, var_map: &impl VariableMapper) -> Operand {
+    fn mapped(&self, var_map: &mut impl VariableMapper) -> Operand {
         match self {
             Operand::Literal(_) => *self,
             Operand::Variable(var) => var_map.to_operand(*var),
@@ -221,8 +460,8 @@ impl Operand {
 
 trait VariableMapper {
     fn insert(&mut self, var: Variable, operand: Operand);
-    fn to_operand(&self, var: Variable) -> Operand;
-    fn to_variable(&self, var: Variable) -> Variable;
+    fn to_operand(&mut self, var: Variable) -> Operand;
+    fn to_variable(&mut self, var: Variable) -> Variable;
 }
 
 impl VariableMapper for FxHashMap<VariableId, Operand> {
@@ -230,13 +469,13 @@ impl VariableMapper for FxHashMap<VariableId, Operand> {
         self.insert(var.variable_id, operand);
     }
 
-    fn to_operand(&self, var: Variable) -> Operand {
+    fn to_operand(&mut self, var: Variable) -> Operand {
         self.get(&var.variable_id)
             .copied()
             .unwrap_or(Operand::Variable(var))
     }
 
-    fn to_variable(&self, var: Variable) -> Variable {
+    fn to_variable(&mut self, var: Variable) -> Variable {
         self.get(&var.variable_id)
             .copied()
             .map_or(var, |operand| match operand {
@@ -8,15 +8,34 @@ use qsc_data_structures::target::TargetCapabilityFlags;
 
 use crate::{
     builder::{bell_program, new_program, teleport_program},
-    passes::check_and_transform,
+    passes::{
+        build_dominator_graph::build_dominator_graph, check_types, check_unreachable_code,
+        remap_block_ids::remap_block_ids, simplify_control_flow::simplify_control_flow,
+        ssa_check::check_ssa_form,
+    },
     rir::{
-        Block, BlockId, Callable, CallableId, CallableType, Instruction, Operand, Program, Ty,
-        Variable, VariableId,
+        Block, BlockId, Callable, CallableId, CallableType, ConditionCode, Instruction, Literal,
+        Operand, Program, Ty, Variable, VariableId,
     },
+    utils::build_predecessors_map,
 };
+
+use super::transform_to_ssa;
+
+// Runs the same passes as `check_and_transform` up to and including the SSA check, leaving out the optimizations
+// so that the phi nodes inserted by the transformation are kept.
 fn transform_program(program: &mut Program) {
     program.config.capabilities = TargetCapabilityFlags::all();
-    check_and_transform(program);
+    simplify_control_flow(program);
+    check_unreachable_code(program);
+    check_types(program);
+    remap_block_ids(program);
+    let preds = build_predecessors_map(program);
+    transform_to_ssa(program, &preds);
+    let doms = build_dominator_graph(program, &preds);
+    check_ssa_form(program, &preds, &doms);
+    check_unreachable_code(program);
+    check_types(program);
 }
 
 #[test]
@@ -2055,3 +2074,435 @@ fn ssa_transform_inerts_phi_nodes_for_early_return_graph_pattern() {
             num_qubits: 0
             num_results: 0"#]].assert_eq(&program.to_string());
 }
+
+#[test]
+fn ssa_transform_inserts_phi_for_value_stored_in_loop() {
+    let mut program = new_program();
+    let counter = Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Integer,
+    };
+    let condition = Variable {
+        variable_id: VariableId(1),
+        ty: Ty::Boolean,
+    };
+    let incremented = Variable {
+        variable_id: VariableId(2),
+        ty: Ty::Integer,
+    };
+    let result = Variable {
+        variable_id: VariableId(3),
+        ty: Ty::Integer,
+    };
+
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Store(Operand::Literal(Literal::Integer(0)), counter),
+            Instruction::Jump(BlockId(1)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Icmp(
+                ConditionCode::Slt,
+                Operand::Variable(counter),
+                Operand::Literal(Literal::Integer(10)),
+                condition,
+            ),
+            Instruction::Branch(condition, BlockId(2), BlockId(3)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(2),
+        Block(vec![
+            Instruction::Add(
+                Operand::Variable(counter),
+                Operand::Literal(Literal::Integer(1)),
+                incremented,
+            ),
+            Instruction::Store(Operand::Variable(incremented), counter),
+            Instruction::Jump(BlockId(1)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(3),
+        Block(vec![
+            Instruction::Mul(
+                Operand::Variable(counter),
+                Operand::Literal(Literal::Integer(2)),
+                result,
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    // Before
+    expect![[r#"
+        Program:
+            entry: 0
+            callables:
+                Callable 0: Callable:
+                    name: main
+                    call_type: Regular
+                    input_type: <VOID>
+                    output_type: <VOID>
+                    body: 0
+            blocks:
+                Block 0: Block:
+                    Variable(0, Integer) = Store Integer(0)
+                    Jump(1)
+                Block 1: Block:
+                    Variable(1, Boolean) = Icmp Slt, Variable(0, Integer), Integer(10)
+                    Branch Variable(1, Boolean), 2, 3
+                Block 2: Block:
+                    Variable(2, Integer) = Add Variable(0, Integer), Integer(1)
+                    Variable(0, Integer) = Store Variable(2, Integer)
+                    Jump(1)
+                Block 3: Block:
+                    Variable(3, Integer) = Mul Variable(0, Integer), Integer(2)
+                    Return
+            config: Config:
+                capabilities: Base
+            num_qubits: 0
+            num_results: 0"#]]
+    .assert_eq(&program.to_string());
+
+    // After
+    transform_program(&mut program);
+    expect![[r#"
+        Program:
+            entry: 0
+            callables:
+                Callable 0: Callable:
+                    name: main
+                    call_type: Regular
+                    input_type: <VOID>
+                    output_type: <VOID>
+                    body: 0
+            blocks:
+                Block 0: Block:
+                    Jump(1)
+                Block 1: Block:
+                    Variable(4, Integer) = Phi ( [Integer(0), 0], [Variable(2, Integer), 2], )
+                    Variable(1, Boolean) = Icmp Slt, Variable(4, Integer), Integer(10)
+                    Branch Variable(1, Boolean), 2, 3
+                Block 2: Block:
+                    Variable(2, Integer) = Add Variable(4, Integer), Integer(1)
+                    Jump(1)
+                Block 3: Block:
+                    Variable(3, Integer) = Mul Variable(4, Integer), Integer(2)
+                    Return
+            config: Config:
+                capabilities: TargetCapabilityFlags(Adaptive | IntegerComputations | FloatingPointComputations | BackwardsBranching | HigherLevelConstructs | QubitReset)
+            num_qubits: 0
+            num_results: 0"#]].assert_eq(&program.to_string());
+}
+
+#[test]
+fn ssa_transform_propagates_value_not_stored_in_loop_without_phi() {
+    let mut program = new_program();
+    program.callables.insert(
+        CallableId(1),
+        Callable {
+            name: "dynamic_bool".to_string(),
+            input_type: Vec::new(),
+            output_type: Some(Ty::Boolean),
+            body: None,
+            call_type: CallableType::Regular,
+        },
+    );
+    let dynamic_bool = Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Boolean,
+    };
+    let stored = Variable {
+        variable_id: VariableId(1),
+        ty: Ty::Boolean,
+    };
+    let loop_condition = Variable {
+        variable_id: VariableId(2),
+        ty: Ty::Boolean,
+    };
+    let negated = Variable {
+        variable_id: VariableId(3),
+        ty: Ty::Boolean,
+    };
+    let result = Variable {
+        variable_id: VariableId(4),
+        ty: Ty::Boolean,
+    };
+
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(CallableId(1), Vec::new(), Some(dynamic_bool)),
+            Instruction::Store(Operand::Variable(dynamic_bool), stored),
+            Instruction::Jump(BlockId(1)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Call(CallableId(1), Vec::new(), Some(loop_condition)),
+            Instruction::Branch(loop_condition, BlockId(2), BlockId(3)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(2),
+        Block(vec![
+            Instruction::LogicalNot(Operand::Variable(stored), negated),
+            Instruction::Jump(BlockId(1)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(3),
+        Block(vec![
+            Instruction::LogicalNot(Operand::Variable(stored), result),
+            Instruction::Return,
+        ]),
+    );
+
+    // Before
+    expect![[r#"
+        Program:
+            entry: 0
+            callables:
+                Callable 0: Callable:
+                    name: main
+                    call_type: Regular
+                    input_type: <VOID>
+                    output_type: <VOID>
+                    body: 0
+                Callable 1: Callable:
+                    name: dynamic_bool
+                    call_type: Regular
+                    input_type: <VOID>
+                    output_type: Boolean
+                    body: <NONE>
+            blocks:
+                Block 0: Block:
+                    Variable(0, Boolean) = Call id(1), args( )
+                    Variable(1, Boolean) = Store Variable(0, Boolean)
+                    Jump(1)
+                Block 1: Block:
+                    Variable(2, Boolean) = Call id(1), args( )
+                    Branch Variable(2, Boolean), 2, 3
+                Block 2: Block:
+                    Variable(3, Boolean) = LogicalNot Variable(1, Boolean)
+                    Jump(1)
+                Block 3: Block:
+                    Variable(4, Boolean) = LogicalNot Variable(1, Boolean)
+                    Return
+            config: Config:
+                capabilities: Base
+            num_qubits: 0
+            num_results: 0"#]]
+    .assert_eq(&program.to_string());
+
+    // After
+    transform_program(&mut program);
+    expect![[r#"
+        Program:
+            entry: 0
+            callables:
+                Callable 0: Callable:
+                    name: main
+                    call_type: Regular
+                    input_type: <VOID>
+                    output_type: <VOID>
+                    body: 0
+                Callable 1: Callable:
+                    name: dynamic_bool
+                    call_type: Regular
+                    input_type: <VOID>
+                    output_type: Boolean
+                    body: <NONE>
+            blocks:
+                Block 0: Block:
+                    Variable(0, Boolean) = Call id(1), args( )
+                    Jump(1)
+                Block 1: Block:
+                    Variable(2, Boolean) = Call id(1), args( )
+                    Branch Variable(2, Boolean), 2, 3
+                Block 2: Block:
+                    Variable(3, Boolean) = LogicalNot Variable(0, Boolean)
+                    Jump(1)
+                Block 3: Block:
+                    Variable(4, Boolean) = LogicalNot Variable(0, Boolean)
+                    Return
+            config: Config:
+                capabilities: TargetCapabilityFlags(Adaptive | IntegerComputations | FloatingPointComputations | BackwardsBranching | HigherLevelConstructs | QubitReset)
+            num_qubits: 0
+            num_results: 0"#]].assert_eq(&program.to_string());
+}
+
+#[test]
+fn ssa_transform_propagates_phi_value_through_single_predecessor_block() {
+    let mut program = new_program();
+    program.callables.insert(
+        CallableId(1),
+        Callable {
+            name: "dynamic_bool".to_string(),
+            input_type: Vec::new(),
+            output_type: Some(Ty::Boolean),
+            body: None,
+            call_type: CallableType::Regular,
+        },
+    );
+    let first_condition = Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Boolean,
+    };
+    let stored = Variable {
+        variable_id: VariableId(1),
+        ty: Ty::Integer,
+    };
+    let second_condition = Variable {
+        variable_id: VariableId(2),
+        ty: Ty::Boolean,
+    };
+    let incremented = Variable {
+        variable_id: VariableId(3),
+        ty: Ty::Integer,
+    };
+    let result = Variable {
+        variable_id: VariableId(4),
+        ty: Ty::Integer,
+    };
+
+    program.blocks.insert(
+        BlockId(0),
+        Block(vec![
+            Instruction::Call(CallableId(1), Vec::new(), Some(first_condition)),
+            Instruction::Branch(first_condition, BlockId(1), BlockId(2)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(1),
+        Block(vec![
+            Instruction::Store(Operand::Literal(Literal::Integer(3)), stored),
+            Instruction::Jump(BlockId(3)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(2),
+        Block(vec![
+            Instruction::Store(Operand::Literal(Literal::Integer(1)), stored),
+            Instruction::Jump(BlockId(3)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(3),
+        Block(vec![
+            Instruction::Call(CallableId(1), Vec::new(), Some(second_condition)),
+            Instruction::Branch(second_condition, BlockId(4), BlockId(5)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(4),
+        Block(vec![
+            Instruction::Add(
+                Operand::Variable(stored),
+                Operand::Literal(Literal::Integer(1)),
+                incremented,
+            ),
+            Instruction::Jump(BlockId(5)),
+        ]),
+    );
+    program.blocks.insert(
+        BlockId(5),
+        Block(vec![
+            Instruction::Mul(
+                Operand::Variable(stored),
+                Operand::Literal(Literal::Integer(2)),
+                result,
+            ),
+            Instruction::Return,
+        ]),
+    );
+
+    // Before
+    expect![[r#"
+        Program:
+            entry: 0
+            callables:
+                Callable 0: Callable:
+                    name: main
+                    call_type: Regular
+                    input_type: <VOID>
+                    output_type: <VOID>
+                    body: 0
+                Callable 1: Callable:
+                    name: dynamic_bool
+                    call_type: Regular
+                    input_type: <VOID>
+                    output_type: Boolean
+                    body: <NONE>
+            blocks:
+                Block 0: Block:
+                    Variable(0, Boolean) = Call id(1), args( )
+                    Branch Variable(0, Boolean), 1, 2
+                Block 1: Block:
+                    Variable(1, Integer) = Store Integer(3)
+                    Jump(3)
+                Block 2: Block:
+                    Variable(1, Integer) = Store Integer(1)
+                    Jump(3)
+                Block 3: Block:
+                    Variable(2, Boolean) = Call id(1), args( )
+                    Branch Variable(2, Boolean), 4, 5
+                Block 4: Block:
+                    Variable(3, Integer) = Add Variable(1, Integer), Integer(1)
+                    Jump(5)
+                Block 5: Block:
+                    Variable(4, Integer) = Mul Variable(1, Integer), Integer(2)
+                    Return
+            config: Config:
+                capabilities: Base
+            num_qubits: 0
+            num_results: 0"#]]
+    .assert_eq(&program.to_string());
+
+    // After
+    transform_program(&mut program);
+    expect![[r#"
+        Program:
+            entry: 0
+            callables:
+                Callable 0: Callable:
+                    name: main
+                    call_type: Regular
+                    input_type: <VOID>
+                    output_type: <VOID>
+                    body: 0
+                Callable 1: Callable:
+                    name: dynamic_bool
+                    call_type: Regular
+                    input_type: <VOID>
+                    output_type: Boolean
+                    body: <NONE>
+            blocks:
+                Block 0: Block:
+                    Variable(0, Boolean) = Call id(1), args( )
+                    Branch Variable(0, Boolean), 1, 2
+                Block 1: Block:
+                    Jump(3)
+                Block 2: Block:
+                    Jump(3)
+                Block 3: Block:
+                    Variable(5, Integer) = Phi ( [Integer(3), 1], [Integer(1), 2], )
+                    Variable(2, Boolean) = Call id(1), args( )
+                    Branch Variable(2, Boolean), 4, 5
+                Block 4: Block:
+                    Variable(3, Integer) = Add Variable(5, Integer), Integer(1)
+                    Jump(5)
+                Block 5: Block:
+                    Variable(4, Integer) = Mul Variable(5, Integer), Integer(2)
+                    Return
+            config: Config:
+                capabilities: TargetCapabilityFlags(Adaptive | IntegerComputations | FloatingPointComputations | BackwardsBranching | HigherLevelConstructs | QubitReset)
+            num_qubits: 0
+            num_results: 0"#]].assert_eq(&program.to_string());
+}
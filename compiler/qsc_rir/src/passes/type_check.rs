@@ -31,12 +31,31 @@ fn check_instr_types(program: &Program, instr: &Instruction) {
         | Instruction::LogicalOr(opr1, opr2, var)
         | Instruction::BitwiseAnd(opr1, opr2, var)
         | Instruction::BitwiseOr(opr1, opr2, var)
-        | Instruction::BitwiseXor(opr1, opr2, var)
-        | Instruction::Icmp(_, opr1, opr2, var) => {
+        | Instruction::BitwiseXor(opr1, opr2, var) => {
             assert_eq!(opr1.get_type(), opr2.get_type());
             assert_eq!(opr1.get_type(), var.ty);
         }
 
+        Instruction::Icmp(_, opr1, opr2, var) => {
+            assert_eq!(opr1.get_type(), opr2.get_type());
+            assert_eq!(var.ty, Ty::Boolean);
+        }
+
+        Instruction::Fadd(opr1, opr2, var)
+        | Instruction::Fsub(opr1, opr2, var)
+        | Instruction::Fmul(opr1, opr2, var)
+        | Instruction::Fdiv(opr1, opr2, var) => {
+            assert_eq!(opr1.get_type(), Ty::Double);
+            assert_eq!(opr2.get_type(), Ty::Double);
+            assert_eq!(var.ty, Ty::Double);
+        }
+
+        Instruction::Fcmp(_, opr1, opr2, var) => {
+            assert_eq!(opr1.get_type(), Ty::Double);
+            assert_eq!(opr2.get_type(), Ty::Double);
+            assert_eq!(var.ty, Ty::Boolean);
+        }
+
         Instruction::Store(opr, var)
         | Instruction::LogicalNot(opr, var)
         | Instruction::BitwiseNot(opr, var) => {
@@ -2,8 +2,8 @@
 // Licensed under the MIT License.
 
 use crate::rir::{
-    BlockId, Callable, CallableId, CallableType, Instruction, Literal, Operand, Program, Ty,
-    Variable, VariableId,
+    BlockId, Callable, CallableId, CallableType, ConditionCode, FcmpConditionCode, Instruction,
+    Literal, Operand, Program, Ty, Variable, VariableId,
 };
 
 use super::check_instr_types;
@@ -33,6 +33,99 @@ fn binop_instr_mismatching_types_fails_check() {
     check_instr_types(&Program::new(), &Instruction::Add(opr1, opr2, var));
 }
 
+#[test]
+fn icmp_instr_integer_operands_boolean_result_passes_check() {
+    let opr1 = Operand::Variable(Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Integer,
+    });
+    let opr2 = Operand::Literal(Literal::Integer(3));
+    let var = Variable {
+        variable_id: VariableId(1),
+        ty: Ty::Boolean,
+    };
+
+    check_instr_types(
+        &Program::new(),
+        &Instruction::Icmp(ConditionCode::Slt, opr1, opr2, var),
+    );
+}
+
+#[test]
+#[should_panic(expected = "assertion `left == right` failed")]
+fn icmp_instr_integer_result_fails_check() {
+    let var = Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Integer,
+    };
+    let opr1 = Operand::Variable(var);
+    let opr2 = Operand::Literal(Literal::Integer(3));
+
+    check_instr_types(
+        &Program::new(),
+        &Instruction::Icmp(ConditionCode::Eq, opr1, opr2, var),
+    );
+}
+
+#[test]
+fn float_binop_instr_matching_types_passes_check() {
+    let var = Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Double,
+    };
+    let opr1 = Operand::Variable(var);
+    let opr2 = Operand::Literal(Literal::Double(1.0));
+
+    check_instr_types(&Program::new(), &Instruction::Fmul(opr1, opr2, var));
+}
+
+#[test]
+#[should_panic(expected = "assertion `left == right` failed")]
+fn float_binop_instr_integer_types_fails_check() {
+    let var = Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Integer,
+    };
+    let opr1 = Operand::Variable(var);
+    let opr2 = Operand::Literal(Literal::Integer(1));
+
+    check_instr_types(&Program::new(), &Instruction::Fadd(opr1, opr2, var));
+}
+
+#[test]
+fn fcmp_instr_double_operands_boolean_result_passes_check() {
+    let opr1 = Operand::Variable(Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Double,
+    });
+    let opr2 = Operand::Literal(Literal::Double(0.5));
+    let var = Variable {
+        variable_id: VariableId(1),
+        ty: Ty::Boolean,
+    };
+
+    check_instr_types(
+        &Program::new(),
+        &Instruction::Fcmp(FcmpConditionCode::OrderedAndLessThan, opr1, opr2, var),
+    );
+}
+
+#[test]
+#[should_panic(expected = "assertion `left == right` failed")]
+fn fcmp_instr_double_result_fails_check() {
+    let var = Variable {
+        variable_id: VariableId(0),
+        ty: Ty::Double,
+    };
+    let opr1 = Operand::Variable(var);
+    let opr2 = Operand::Literal(Literal::Double(0.5));
+
+    check_instr_types(
+        &Program::new(),
+        &Instruction::Fcmp(FcmpConditionCode::OrderedAndEqual, opr1, opr2, var),
+    );
+}
+
 #[test]
 fn unop_instr_matching_types_passes_check() {
     let var = Variable {
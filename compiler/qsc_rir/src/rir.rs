@@ -66,6 +66,10 @@ impl Program {
 #[derive(Default)]
 pub struct Config {
     pub capabilities: TargetCapabilityFlags,
+    /// Whether adjacent gates that cancel out or can be merged are optimized away.
+    pub optimize_gates: bool,
+    /// Whether qubits with lifetimes that do not overlap are mapped onto the same qubit ID.
+    pub reuse_qubits: bool,
 }
 
 impl Display for Config {
@@ -238,6 +242,52 @@ impl Display for ConditionCode {
     }
 }
 
+/// Condition codes for floating-point comparisons. Ordered comparisons are false when either
+/// operand is NaN, while unordered comparisons are true in that case.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FcmpConditionCode {
+    False,
+    OrderedAndEqual,
+    OrderedAndGreaterThan,
+    OrderedAndGreaterThanOrEqual,
+    OrderedAndLessThan,
+    OrderedAndLessThanOrEqual,
+    OrderedAndNotEqual,
+    Ordered,
+    UnorderedOrEqual,
+    UnorderedOrGreaterThan,
+    UnorderedOrGreaterThanOrEqual,
+    UnorderedOrLessThan,
+    UnorderedOrLessThanOrEqual,
+    UnorderedOrNotEqual,
+    Unordered,
+    True,
+}
+
+impl Display for FcmpConditionCode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match &self {
+            Self::False => write!(f, "False")?,
+            Self::OrderedAndEqual => write!(f, "Oeq")?,
+            Self::OrderedAndGreaterThan => write!(f, "Ogt")?,
+            Self::OrderedAndGreaterThanOrEqual => write!(f, "Oge")?,
+            Self::OrderedAndLessThan => write!(f, "Olt")?,
+            Self::OrderedAndLessThanOrEqual => write!(f, "Ole")?,
+            Self::OrderedAndNotEqual => write!(f, "One")?,
+            Self::Ordered => write!(f, "Ord")?,
+            Self::UnorderedOrEqual => write!(f, "Ueq")?,
+            Self::UnorderedOrGreaterThan => write!(f, "Ugt")?,
+            Self::UnorderedOrGreaterThanOrEqual => write!(f, "Uge")?,
+            Self::UnorderedOrLessThan => write!(f, "Ult")?,
+            Self::UnorderedOrLessThanOrEqual => write!(f, "Ule")?,
+            Self::UnorderedOrNotEqual => write!(f, "Une")?,
+            Self::Unordered => write!(f, "Uno")?,
+            Self::True => write!(f, "True")?,
+        }
+        Ok(())
+    }
+}
+
 impl Display for CallableType {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match &self {
@@ -265,6 +315,11 @@ pub enum Instruction {
     Shl(Operand, Operand, Variable),
     Ashr(Operand, Operand, Variable),
     Icmp(ConditionCode, Operand, Operand, Variable),
+    Fadd(Operand, Operand, Variable),
+    Fsub(Operand, Operand, Variable),
+    Fmul(Operand, Operand, Variable),
+    Fdiv(Operand, Operand, Variable),
+    Fcmp(FcmpConditionCode, Operand, Operand, Variable),
     LogicalNot(Operand, Variable),
     LogicalAnd(Operand, Operand, Variable),
     LogicalOr(Operand, Operand, Variable),
@@ -343,6 +398,18 @@ impl Display for Instruction {
             Ok(())
         }
 
+        fn write_fcmp_instruction(
+            f: &mut Formatter,
+            condition: FcmpConditionCode,
+            lhs: &Operand,
+            rhs: &Operand,
+            variable: Variable,
+        ) -> fmt::Result {
+            let mut indent = set_indentation(indented(f), 0);
+            write!(indent, "{variable} = Fcmp {condition}, {lhs}, {rhs}")?;
+            Ok(())
+        }
+
         fn write_phi_instruction(
             f: &mut Formatter,
             args: &[(Operand, BlockId)],
@@ -411,6 +478,21 @@ impl Display for Instruction {
             Self::Icmp(op, lhs, rhs, variable) => {
                 write_icmp_instruction(f, *op, lhs, rhs, *variable)?;
             }
+            Self::Fadd(lhs, rhs, variable) => {
+                write_binary_instruction(f, "Fadd", lhs, rhs, *variable)?;
+            }
+            Self::Fsub(lhs, rhs, variable) => {
+                write_binary_instruction(f, "Fsub", lhs, rhs, *variable)?;
+            }
+            Self::Fmul(lhs, rhs, variable) => {
+                write_binary_instruction(f, "Fmul", lhs, rhs, *variable)?;
+            }
+            Self::Fdiv(lhs, rhs, variable) => {
+                write_binary_instruction(f, "Fdiv", lhs, rhs, *variable)?;
+            }
+            Self::Fcmp(op, lhs, rhs, variable) => {
+                write_fcmp_instruction(f, *op, lhs, rhs, *variable)?;
+            }
             Self::Phi(args, variable) => {
                 write_phi_instruction(f, args, *variable)?;
             }
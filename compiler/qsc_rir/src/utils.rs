@@ -1,9 +1,11 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::rir::{Block, BlockId, Instruction, Program, VariableId};
+use crate::rir::{
+    Block, BlockId, CallableId, Instruction, Literal, Operand, Program, Variable, VariableId,
+};
 use qsc_data_structures::index_map::IndexMap;
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 /// Given a block, return the block IDs of its successors.
 #[must_use]
@@ -45,6 +47,28 @@ pub fn get_all_block_successors(block: BlockId, program: &Program) -> Vec<BlockI
     successors
 }
 
+/// Given the two targets of a branch in an acyclic program, return the block where control flow
+/// from both targets joins again, if any. This is the block reachable from both targets from which
+/// every other block reachable from both targets is also reachable.
+#[must_use]
+pub fn get_merge_block(if_true: BlockId, if_false: BlockId, program: &Program) -> Option<BlockId> {
+    let reachable = |block_id| {
+        let mut blocks = get_all_block_successors(block_id, program);
+        blocks.push(block_id);
+        blocks.into_iter().collect::<FxHashSet<_>>()
+    };
+    let from_if_false = reachable(if_false);
+    reachable(if_true)
+        .into_iter()
+        .filter(|block_id| from_if_false.contains(block_id))
+        .max_by_key(|block_id| {
+            (
+                get_all_block_successors(*block_id, program).len(),
+                *block_id,
+            )
+        })
+}
+
 /// Given a program, return a map from block IDs to the block IDs of their predecessors.
 /// The vectors used as values in the map are sorted in ascending order, ensuring that block ids
 /// for predecessors are listed lowest to highest.
@@ -84,6 +108,11 @@ pub fn get_variable_assignments(program: &Program) -> IndexMap<VariableId, (Bloc
                 | Instruction::Shl(_, _, var)
                 | Instruction::Ashr(_, _, var)
                 | Instruction::Icmp(_, _, _, var)
+                | Instruction::Fadd(_, _, var)
+                | Instruction::Fsub(_, _, var)
+                | Instruction::Fmul(_, _, var)
+                | Instruction::Fdiv(_, _, var)
+                | Instruction::Fcmp(_, _, _, var)
                 | Instruction::LogicalNot(_, var)
                 | Instruction::LogicalAnd(_, _, var)
                 | Instruction::LogicalOr(_, _, var)
@@ -112,3 +141,214 @@ pub fn get_variable_assignments(program: &Program) -> IndexMap<VariableId, (Bloc
     }
     assignments
 }
+
+/// Given an instruction, return the variable it assigns, if any.
+#[must_use]
+pub fn get_assigned_variable(instr: &Instruction) -> Option<Variable> {
+    match instr {
+        Instruction::Call(_, _, Some(var))
+        | Instruction::Add(_, _, var)
+        | Instruction::Sub(_, _, var)
+        | Instruction::Mul(_, _, var)
+        | Instruction::Sdiv(_, _, var)
+        | Instruction::Srem(_, _, var)
+        | Instruction::Shl(_, _, var)
+        | Instruction::Ashr(_, _, var)
+        | Instruction::Icmp(_, _, _, var)
+        | Instruction::Fadd(_, _, var)
+        | Instruction::Fsub(_, _, var)
+        | Instruction::Fmul(_, _, var)
+        | Instruction::Fdiv(_, _, var)
+        | Instruction::Fcmp(_, _, _, var)
+        | Instruction::LogicalNot(_, var)
+        | Instruction::LogicalAnd(_, _, var)
+        | Instruction::LogicalOr(_, _, var)
+        | Instruction::BitwiseNot(_, var)
+        | Instruction::BitwiseAnd(_, _, var)
+        | Instruction::BitwiseOr(_, _, var)
+        | Instruction::BitwiseXor(_, _, var)
+        | Instruction::Phi(_, var) => Some(*var),
+        Instruction::Store(..)
+        | Instruction::Call(_, _, None)
+        | Instruction::Jump(..)
+        | Instruction::Branch(..)
+        | Instruction::Return => None,
+    }
+}
+
+/// Given an instruction, return the operands it uses, including the condition of a branch.
+#[must_use]
+pub fn get_instruction_operands(instr: &Instruction) -> Vec<Operand> {
+    match instr {
+        Instruction::Store(operand, _)
+        | Instruction::LogicalNot(operand, _)
+        | Instruction::BitwiseNot(operand, _) => vec![*operand],
+        Instruction::Call(_, args, _) => args.clone(),
+        Instruction::Branch(var, _, _) => vec![Operand::Variable(*var)],
+        Instruction::Add(lhs, rhs, _)
+        | Instruction::Sub(lhs, rhs, _)
+        | Instruction::Mul(lhs, rhs, _)
+        | Instruction::Sdiv(lhs, rhs, _)
+        | Instruction::Srem(lhs, rhs, _)
+        | Instruction::Shl(lhs, rhs, _)
+        | Instruction::Ashr(lhs, rhs, _)
+        | Instruction::Icmp(_, lhs, rhs, _)
+        | Instruction::Fadd(lhs, rhs, _)
+        | Instruction::Fsub(lhs, rhs, _)
+        | Instruction::Fmul(lhs, rhs, _)
+        | Instruction::Fdiv(lhs, rhs, _)
+        | Instruction::Fcmp(_, lhs, rhs, _)
+        | Instruction::LogicalAnd(lhs, rhs, _)
+        | Instruction::LogicalOr(lhs, rhs, _)
+        | Instruction::BitwiseAnd(lhs, rhs, _)
+        | Instruction::BitwiseOr(lhs, rhs, _)
+        | Instruction::BitwiseXor(lhs, rhs, _) => vec![*lhs, *rhs],
+        Instruction::Phi(args, _) => args.iter().map(|(arg, _)| *arg).collect(),
+        Instruction::Jump(..) | Instruction::Return => Vec::new(),
+    }
+}
+
+// Given an instruction, return mutable references to the operands it uses. The condition of a branch is a variable
+// rather than an operand, so it is not included.
+fn get_instruction_operands_mut(instr: &mut Instruction) -> Vec<&mut Operand> {
+    match instr {
+        Instruction::Store(operand, _)
+        | Instruction::LogicalNot(operand, _)
+        | Instruction::BitwiseNot(operand, _) => vec![operand],
+        Instruction::Call(_, args, _) => args.iter_mut().collect(),
+        Instruction::Add(lhs, rhs, _)
+        | Instruction::Sub(lhs, rhs, _)
+        | Instruction::Mul(lhs, rhs, _)
+        | Instruction::Sdiv(lhs, rhs, _)
+        | Instruction::Srem(lhs, rhs, _)
+        | Instruction::Shl(lhs, rhs, _)
+        | Instruction::Ashr(lhs, rhs, _)
+        | Instruction::Icmp(_, lhs, rhs, _)
+        | Instruction::Fadd(lhs, rhs, _)
+        | Instruction::Fsub(lhs, rhs, _)
+        | Instruction::Fmul(lhs, rhs, _)
+        | Instruction::Fdiv(lhs, rhs, _)
+        | Instruction::Fcmp(_, lhs, rhs, _)
+        | Instruction::LogicalAnd(lhs, rhs, _)
+        | Instruction::LogicalOr(lhs, rhs, _)
+        | Instruction::BitwiseAnd(lhs, rhs, _)
+        | Instruction::BitwiseOr(lhs, rhs, _)
+        | Instruction::BitwiseXor(lhs, rhs, _) => vec![lhs, rhs],
+        Instruction::Phi(args, _) => args.iter_mut().map(|(arg, _)| arg).collect(),
+        Instruction::Branch(..) | Instruction::Jump(..) | Instruction::Return => Vec::new(),
+    }
+}
+
+/// Follows the chain of replacements for the given operand until reaching a value that is not replaced.
+#[must_use]
+pub(crate) fn resolve_operand(
+    replacements: &FxHashMap<VariableId, Operand>,
+    mut operand: Operand,
+) -> Operand {
+    while let Operand::Variable(var) = operand {
+        match replacements.get(&var.variable_id) {
+            Some(replacement) => operand = *replacement,
+            None => break,
+        }
+    }
+    operand
+}
+
+/// Replaces every use of the given variables in the program with their replacement operands.
+/// A branch whose condition is replaced by a boolean literal becomes a jump to the taken target, and the
+/// untaken target no longer lists the branching block in its phi nodes.
+pub(crate) fn replace_variable_uses(
+    program: &mut Program,
+    replacements: &FxHashMap<VariableId, Operand>,
+) {
+    let mut removed_edges = Vec::new();
+    for (block_id, block) in program.blocks.iter_mut() {
+        for instr in &mut block.0 {
+            for operand in get_instruction_operands_mut(instr) {
+                *operand = resolve_operand(replacements, *operand);
+            }
+            if let Instruction::Branch(cond, if_true, if_false) = instr {
+                match resolve_operand(replacements, Operand::Variable(*cond)) {
+                    Operand::Variable(var) => *cond = var,
+                    Operand::Literal(Literal::Bool(value)) => {
+                        let (taken, untaken) = if value {
+                            (*if_true, *if_false)
+                        } else {
+                            (*if_false, *if_true)
+                        };
+                        if taken != untaken {
+                            removed_edges.push((block_id, untaken));
+                        }
+                        *instr = Instruction::Jump(taken);
+                    }
+                    Operand::Literal(literal) => {
+                        panic!(
+                            "branch condition in {block_id:?} replaced with non-boolean {literal}"
+                        )
+                    }
+                }
+            }
+        }
+    }
+    for (pred, block_id) in removed_edges {
+        remove_phi_args_from(program.get_block_mut(block_id), &[pred]);
+    }
+}
+
+/// Removes blocks that are not reachable from the body of any callable, along with any references to them in the
+/// phi nodes of the remaining blocks.
+pub fn remove_unreachable_blocks(program: &mut Program) {
+    let mut live_blocks = FxHashSet::default();
+    for (_, callable) in program.callables.iter() {
+        if let Some(body) = callable.body {
+            live_blocks.insert(body);
+            live_blocks.extend(get_all_block_successors(body, program));
+        }
+    }
+    let dead_blocks = program
+        .blocks
+        .iter()
+        .filter_map(|(block_id, _)| (!live_blocks.contains(&block_id)).then_some(block_id))
+        .collect::<Vec<_>>();
+    if dead_blocks.is_empty() {
+        return;
+    }
+    for block_id in &dead_blocks {
+        program.blocks.remove(*block_id);
+    }
+    for block in program.blocks.values_mut() {
+        remove_phi_args_from(block, &dead_blocks);
+    }
+}
+
+/// Removes callables that are neither the entry point nor called from any block of the program.
+pub fn remove_unreachable_callables(program: &mut Program) {
+    let mut live_callables = FxHashSet::default();
+    live_callables.insert(program.entry);
+    for block in program.blocks.values() {
+        for instr in &block.0 {
+            if let Instruction::Call(callable_id, ..) = instr {
+                live_callables.insert(*callable_id);
+            }
+        }
+    }
+    let dead_callables = program
+        .callables
+        .iter()
+        .filter_map(|(callable_id, _)| {
+            (!live_callables.contains(&callable_id)).then_some(callable_id)
+        })
+        .collect::<Vec<CallableId>>();
+    for callable_id in dead_callables {
+        program.callables.remove(callable_id);
+    }
+}
+
+// Removes the arguments of phi nodes in the block that come from any of the given predecessors.
+fn remove_phi_args_from(block: &mut Block, preds: &[BlockId]) {
+    for instr in &mut block.0 {
+        if let Instruction::Phi(args, _) = instr {
+            args.retain(|(_, pred)| !preds.contains(pred));
+        }
+    }
+}
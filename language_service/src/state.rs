@@ -225,16 +225,21 @@ impl<'a> CompilationStateUpdater<'a> {
         if let Some(ref manifest) = manifest {
             let res = self.load_project(manifest).await;
             match res {
-                Ok(o) => Some(LoadManifestResult {
-                    compilation_uri: manifest.compilation_uri(),
-                    sources: o.sources,
-                    language_features: manifest
-                        .manifest
-                        .language_features
-                        .iter()
-                        .collect::<LanguageFeatures>(),
-                    lints: manifest.manifest.lints.clone(),
-                }),
+                Ok(o) => {
+                    for e in &o.errors {
+                        error!("failed to load project file: {e:?}");
+                    }
+                    Some(LoadManifestResult {
+                        compilation_uri: manifest.compilation_uri(),
+                        sources: o.sources,
+                        language_features: manifest
+                            .manifest
+                            .language_features
+                            .iter()
+                            .collect::<LanguageFeatures>(),
+                        lints: manifest.manifest.lints.clone(),
+                    })
+                }
                 Err(e) => {
                     error!("failed to load manifest: {e:?}, defaulting to single-file mode");
                     None
@@ -158,7 +158,11 @@ export function getLanguageServiceWorker(
   return createProxy(worker, wasmModule, languageServiceProtocol);
 }
 
-export { StepResultId, type IStructStepResult } from "../lib/web/qsc_wasm.js";
+export {
+  StepResultId,
+  type IStructStepResult,
+  type IQubitVariable,
+} from "../lib/web/qsc_wasm.js";
 export type {
   IBreakpointSpan,
   ICodeLens,
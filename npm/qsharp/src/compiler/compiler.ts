@@ -72,7 +72,12 @@ export interface ICompiler {
     sources: [string, string][],
     languageFeatures?: string[],
   ): Promise<string>;
-  getQir(config: ProgramConfig): Promise<string>;
+  getQir(
+    config: ProgramConfig,
+    optimizeGates?: boolean,
+    reuseQubits?: boolean,
+  ): Promise<string>;
+  getQasm3(config: ProgramConfig): Promise<string>;
 
   /** @deprecated -- switch to using `ProgramConfig`-based overload. Instead of passing
    * sources and language features separately, pass an object with named properties. This change was made
@@ -86,11 +91,13 @@ export interface ICompiler {
     languageFeatures?: string[],
   ): Promise<string>;
   getEstimates(config: ProgramConfig, params: string): Promise<string>;
+  getLogicalTrace(config: ProgramConfig): Promise<string>;
   getCircuit(
     config: ProgramConfig,
     target: TargetProfile,
     simulate: boolean,
     operation?: IOperationInfo,
+    operationDepth?: number,
   ): Promise<CircuitData>;
 
   getDocumentation(): Promise<IDocFile[]>;
@@ -149,29 +156,57 @@ export class Compiler implements ICompiler {
 
   async getQir(
     sourcesOrConfig: [string, string][] | ProgramConfig,
-    languageFeatures?: string[],
+    languageFeaturesOrOptimizeGates?: string[] | boolean,
+    reuseQubits?: boolean,
   ): Promise<string> {
     if (Array.isArray(sourcesOrConfig)) {
-      return this.deprecatedGetQir(sourcesOrConfig, languageFeatures || []);
+      return this.deprecatedGetQir(
+        sourcesOrConfig,
+        (languageFeaturesOrOptimizeGates as string[] | undefined) || [],
+      );
     } else {
       const config = sourcesOrConfig as ProgramConfig;
-      return this.newGetQir(config);
+      return this.newGetQir(
+        config,
+        languageFeaturesOrOptimizeGates as boolean | undefined,
+        reuseQubits,
+      );
     }
   }
 
-  async newGetQir({
+  async newGetQir(
+    { sources, languageFeatures = [], profile = "base" }: ProgramConfig,
+    optimizeGates?: boolean,
+    reuseQubits?: boolean,
+  ): Promise<string> {
+    return this.wasm.get_qir(
+      sources,
+      languageFeatures,
+      profile,
+      optimizeGates,
+      reuseQubits,
+    );
+  }
+
+  async getQasm3({
     sources,
     languageFeatures = [],
-    profile = "base",
+    profile = "quantinuum",
   }: ProgramConfig): Promise<string> {
-    return this.wasm.get_qir(sources, languageFeatures, profile);
+    return this.wasm.get_qasm3(sources, languageFeatures, profile);
   }
 
   async deprecatedGetQir(
     sources: [string, string][],
     languageFeatures: string[],
   ): Promise<string> {
-    return this.wasm.get_qir(sources, languageFeatures, "base");
+    return this.wasm.get_qir(
+      sources,
+      languageFeatures,
+      "base",
+      undefined,
+      undefined,
+    );
   }
 
   async getEstimates(
@@ -205,6 +240,13 @@ export class Compiler implements ICompiler {
     return this.wasm.get_estimates(sources, params, languageFeatures);
   }
 
+  async getLogicalTrace({
+    sources,
+    languageFeatures = [],
+  }: ProgramConfig): Promise<string> {
+    return this.wasm.get_logical_trace(sources, languageFeatures);
+  }
+
   async getAst(
     code: string,
     languageFeatures?: string[],
@@ -263,6 +305,7 @@ export class Compiler implements ICompiler {
     target: TargetProfile,
     simulate: boolean,
     operation?: IOperationInfo,
+    operationDepth?: number,
   ): Promise<CircuitData> {
     return this.wasm.get_circuit(
       config.sources,
@@ -270,6 +313,7 @@ export class Compiler implements ICompiler {
       config.languageFeatures || [],
       simulate,
       operation,
+      operationDepth,
     );
   }
 
@@ -331,7 +375,9 @@ export const compilerProtocol: ServiceProtocol<ICompiler, QscEventData> = {
     getAst: "request",
     getHir: "request",
     getQir: "request",
+    getQasm3: "request",
     getEstimates: "request",
+    getLogicalTrace: "request",
     getCircuit: "request",
     getDocumentation: "request",
     run: "requestWithProgress",
@@ -37,6 +37,12 @@ export interface IDebugService {
   captureQuantumState(): Promise<Array<IQuantumState>>;
   getCircuit(): Promise<CircuitData>;
   getStackFrames(): Promise<IStackFrame[]>;
+  evalWatch(frameId: number, expr: string): Promise<string>;
+  setVariable(
+    frameId: number,
+    name: string,
+    valueExpr: string,
+  ): Promise<string>;
   evalContinue(
     bps: number[],
     eventHandler: IQscEventTarget,
@@ -53,6 +59,11 @@ export interface IDebugService {
     bps: number[],
     eventHandler: IQscEventTarget,
   ): Promise<IStructStepResult>;
+  setBreakOnExceptions(enabled: boolean): Promise<void>;
+  getException(): Promise<string | undefined>;
+  enableReverseStepping(memoryCap: number): Promise<void>;
+  stepBack(): Promise<IStructStepResult>;
+  reverseContinue(bps: number[]): Promise<IStructStepResult>;
   dispose(): Promise<void>;
 }
 
@@ -104,6 +115,18 @@ export class QSharpDebugService implements IDebugService {
     return this.debugService.get_stack_frames().frames;
   }
 
+  async evalWatch(frameId: number, expr: string): Promise<string> {
+    return this.debugService.eval_watch(frameId, expr);
+  }
+
+  async setVariable(
+    frameId: number,
+    name: string,
+    valueExpr: string,
+  ): Promise<string> {
+    return this.debugService.set_variable(frameId, name, valueExpr);
+  }
+
   async evalContinue(
     bps: number[],
     eventHandler: IQscEventTarget,
@@ -140,6 +163,27 @@ export class QSharpDebugService implements IDebugService {
     return this.debugService.eval_step_out(event_cb, ids);
   }
 
+  async setBreakOnExceptions(enabled: boolean): Promise<void> {
+    this.debugService.set_break_on_exceptions(enabled);
+  }
+
+  async getException(): Promise<string | undefined> {
+    return this.debugService.get_exception();
+  }
+
+  async enableReverseStepping(memoryCap: number): Promise<void> {
+    this.debugService.enable_reverse_stepping(memoryCap);
+  }
+
+  async stepBack(): Promise<IStructStepResult> {
+    return this.debugService.step_back();
+  }
+
+  async reverseContinue(bps: number[]): Promise<IStructStepResult> {
+    const ids = new Uint32Array(bps);
+    return this.debugService.reverse_continue(ids);
+  }
+
   async dispose() {
     this.debugService.free();
   }
@@ -189,10 +233,17 @@ export const debugServiceProtocol: ServiceProtocol<
     captureQuantumState: "request",
     getCircuit: "request",
     getStackFrames: "request",
+    evalWatch: "request",
+    setVariable: "request",
     evalContinue: "requestWithProgress",
     evalNext: "requestWithProgress",
     evalStepIn: "requestWithProgress",
     evalStepOut: "requestWithProgress",
+    setBreakOnExceptions: "request",
+    getException: "request",
+    enableReverseStepping: "request",
+    stepBack: "request",
+    reverseContinue: "request",
     dispose: "request",
   },
   eventNames: ["DumpMachine", "Message", "Result"],
@@ -6,8 +6,13 @@ from ._qsharp import (
     eval,
     run,
     compile,
+    qasm,
+    import_qasm,
     circuit,
     estimate,
+    estimate_qir,
+    logical_trace,
+    logical_trace_qir,
     set_quantum_seed,
     set_classical_seed,
     dump_machine,
@@ -38,8 +43,13 @@ __all__ = [
     "dump_machine",
     "dump_circuit",
     "compile",
+    "qasm",
+    "import_qasm",
     "circuit",
     "estimate",
+    "estimate_qir",
+    "logical_trace",
+    "logical_trace_qir",
     "Result",
     "Pauli",
     "QSharpError",
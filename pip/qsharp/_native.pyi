@@ -68,6 +68,20 @@ class Interpreter:
         """
         ...
 
+    def import_qasm(
+        self, source: str, name: str, output_fn: Callable[[Output], None]
+    ) -> None:
+        """
+        Imports an OpenQASM 2.0 or 3 program as a Q# operation in the `Qasm.<name>` namespace.
+
+        :param source: The OpenQASM program.
+        :param name: The name of the generated operation.
+        :param output_fn: A callback function that will be called with each output.
+
+        :raises QSharpError: If the name is not a valid Q# identifier or the program cannot be imported.
+        """
+        ...
+
     def run(self, entry_expr: str, output_fn: Callable[[Output], None]) -> Any:
         """
         Runs the given Q# expression with an independent instance of the simulator.
@@ -81,20 +95,40 @@ class Interpreter:
         """
         ...
 
-    def qir(self, entry_expr: str) -> str:
+    def qir(
+        self,
+        entry_expr: str,
+        optimize_gates: Optional[bool],
+        reuse_qubits: Optional[bool],
+    ) -> str:
         """
         Generates QIR from Q# source code.
 
         :param entry_expr: The entry expression.
+        :param optimize_gates: Whether adjacent gates that cancel out or can be merged
+            are optimized away.
+        :param reuse_qubits: Whether qubits with lifetimes that do not overlap are
+            mapped onto the same qubit.
 
         :returns qir: The QIR string.
         """
         ...
 
+    def qasm(self, entry_expr: str) -> str:
+        """
+        Generates an OpenQASM 3 program from Q# source code.
+
+        :param entry_expr: The entry expression.
+
+        :returns qasm: The OpenQASM 3 program.
+        """
+        ...
+
     def circuit(
         self,
         entry_expr: Optional[str],
         operation: Optional[str],
+        operation_depth: Optional[int],
     ) -> Circuit:
         """
         Synthesizes a circuit for a Q# program. Either an entry
@@ -106,6 +140,9 @@ class Interpreter:
         an operation of a lambda expression. The operation must take only
         qubits or arrays of qubits as parameters.
 
+        :param operation_depth: The maximum depth of nested operation calls that are
+        grouped into parent operations. `0` produces a flat list of gates.
+
         :raises QSharpError: If there is an error synthesizing the circuit.
         """
         ...
@@ -121,6 +158,16 @@ class Interpreter:
         """
         ...
 
+    def trace(self, entry_expr: str) -> str:
+        """
+        Traces the non-Clifford operations and measurements of Q# source code.
+
+        :param entry_expr: The entry expression.
+
+        :returns trace: The operations with their layers and qubits as JSON.
+        """
+        ...
+
     def set_quantum_seed(self, seed: Optional[int]) -> None:
         """
         Sets the seed for the quantum random number generator.
@@ -226,3 +273,25 @@ def physical_estimates(logical_resources: str, params: str) -> str:
     :returns resources: The estimated resources.
     """
     ...
+
+def qir_estimates(qir: str, params: str) -> str:
+    """
+    Estimates physical resources for a QIR program in the textual LLVM format.
+
+    :param qir: The QIR program to estimate.
+    :param params: The parameters to configure physical estimation.
+
+    :returns resources: The estimated resources.
+    """
+    ...
+
+def qir_trace(qir: str) -> str:
+    """
+    Traces the non-Clifford operations and measurements of a QIR program in
+    the textual LLVM format.
+
+    :param qir: The QIR program to trace.
+
+    :returns trace: The operations with their layers and qubits as JSON.
+    """
+    ...
@@ -8,10 +8,17 @@ from ._native import (
     QSharpError,
     Output,
     Circuit,
+    qir_estimates,
+    qir_trace,
 )
 from warnings import warn
 from typing import Any, Callable, Dict, Optional, Tuple, TypedDict, Union, List
-from .estimator._estimator import EstimatorResult, EstimatorParams
+from .estimator._estimator import (
+    EstimatorBatchResult,
+    EstimatorResult,
+    EstimatorParams,
+    is_batch_specification,
+)
 import json
 
 _interpreter = None
@@ -140,6 +147,26 @@ def eval(source: str) -> Any:
     return get_interpreter().interpret(source, callback)
 
 
+def import_qasm(source: str, name: str) -> None:
+    """
+    Imports an OpenQASM 2.0 or 3 program as a Q# operation.
+
+    The operation is defined in its own `Qasm.<name>` namespace, takes no
+    arguments and returns the program's classical registers, so it can be
+    called from Q# like any other operation, e.g. `Qasm.Bell.Bell()`.
+
+    :param source: The OpenQASM program.
+    :param name: The name of the generated operation. Must be a Q# identifier.
+    :raises QSharpError: If the name is not a valid Q# identifier or the
+        program cannot be imported.
+    """
+
+    def callback(output: Output) -> None:
+        print(output)
+
+    get_interpreter().import_qasm(source, name, callback)
+
+
 class ShotResult(TypedDict):
     """
     A single result of a shot.
@@ -219,12 +246,18 @@ class QirInputData:
         return self._ll_str
 
 
-def compile(entry_expr: str) -> QirInputData:
+def compile(
+    entry_expr: str, *, optimize_gates: bool = False, reuse_qubits: bool = False
+) -> QirInputData:
     """
     Compiles the Q# source code into a program that can be submitted to a target.
 
     :param entry_expr: The Q# expression that will be used as the entrypoint
         for the program.
+    :param optimize_gates: Whether adjacent gates that cancel out or can be merged,
+        such as `H; H`, are optimized away.
+    :param reuse_qubits: Whether qubits with lifetimes that do not overlap are
+        mapped onto the same qubit, reducing the number of qubits the program requires.
 
     :returns QirInputData: The compiled program.
 
@@ -237,12 +270,32 @@ def compile(entry_expr: str) -> QirInputData:
         with open('myfile.ll', 'w') as file:
             file.write(str(program))
     """
-    ll_str = get_interpreter().qir(entry_expr)
+    ll_str = get_interpreter().qir(entry_expr, optimize_gates, reuse_qubits)
     return QirInputData("main", ll_str)
 
 
+def qasm(entry_expr: str) -> str:
+    """
+    Generates an OpenQASM 3 program for the given entry expression.
+
+    The program is partially evaluated for the current target profile, so
+    branches on measurement results become `if` statements in the output.
+
+    :param entry_expr: The Q# expression that will be used as the entrypoint
+        for the program.
+
+    :returns qasm: The OpenQASM 3 program.
+
+    :raises QSharpError: If the program cannot be represented for the target profile.
+    """
+    return get_interpreter().qasm(entry_expr)
+
+
 def circuit(
-    entry_expr: Optional[str] = None, *, operation: Optional[str] = None
+    entry_expr: Optional[str] = None,
+    *,
+    operation: Optional[str] = None,
+    operation_depth: int = 0,
 ) -> Circuit:
     """
     Synthesizes a circuit for a Q# program. Either an entry
@@ -254,22 +307,81 @@ def circuit(
     an operation of a lambda expression. The operation must take only
     qubits or arrays of qubits as parameters.
 
+    :param operation_depth: The maximum depth of nested operation calls that are
+    grouped into parent operations. `0` produces a flat list of gates.
+
     :raises QSharpError: If there is an error synthesizing the circuit.
     """
-    return get_interpreter().circuit(entry_expr, operation)
+    return get_interpreter().circuit(entry_expr, operation, operation_depth)
 
 
 def estimate(
     entry_expr, params: Optional[Union[Dict[str, Any], List, EstimatorParams]] = None
-) -> EstimatorResult:
+) -> Union[EstimatorResult, EstimatorBatchResult]:
     """
     Estimates resources for Q# source code.
 
     :param entry_expr: The entry expression.
-    :param params: The parameters to configure physical estimation.
+    :param params: The parameters to configure physical estimation, or a
+        batch specification with the keys ``base``, ``items``, and ``grid``.
 
     :returns resources: The estimated resources.
     """
+    return _estimate(
+        lambda params: get_interpreter().estimate(entry_expr, params), params
+    )
+
+
+def estimate_qir(
+    qir: Union[str, QirInputData],
+    params: Optional[Union[Dict[str, Any], List, EstimatorParams]] = None,
+) -> Union[EstimatorResult, EstimatorBatchResult]:
+    """
+    Estimates resources for a QIR program in the textual LLVM format, such as
+    a program of the base or adaptive profile returned from `compile`.
+
+    :param qir: The QIR program.
+    :param params: The parameters to configure physical estimation, or a
+        batch specification with the keys ``base``, ``items``, and ``grid``.
+
+    :returns resources: The estimated resources.
+    """
+    return _estimate(lambda params: qir_estimates(str(qir), params), params)
+
+
+def logical_trace(entry_expr: str) -> Dict[str, Any]:
+    """
+    Traces the non-Clifford operations and measurements of Q# source code
+    together with the layers in which they are scheduled and their qubits.
+
+    :param entry_expr: The entry expression.
+
+    :returns trace: The trace with the keys ``numQubits``, ``numLayers``, and
+        ``operations``.
+    """
+    return json.loads(get_interpreter().trace(entry_expr))
+
+
+def logical_trace_qir(qir: Union[str, QirInputData]) -> Dict[str, Any]:
+    """
+    Traces the non-Clifford operations and measurements of a QIR program in
+    the textual LLVM format together with the layers in which they are
+    scheduled and their qubits.
+
+    :param qir: The QIR program.
+
+    :returns trace: The trace with the keys ``numQubits``, ``numLayers``, and
+        ``operations``.
+    """
+    return json.loads(qir_trace(str(qir)))
+
+
+def _estimate(
+    estimate_json: Callable[[str], str],
+    params: Optional[Union[Dict[str, Any], List, EstimatorParams]],
+) -> Union[EstimatorResult, EstimatorBatchResult]:
+    if is_batch_specification(params):
+        return EstimatorBatchResult(json.loads(estimate_json(json.dumps(params))))
     if params is None:
         params = [{}]
     elif isinstance(params, EstimatorParams):
@@ -279,9 +391,7 @@ def estimate(
             params = [params.as_dict()]
     elif isinstance(params, dict):
         params = [params]
-    return EstimatorResult(
-        json.loads(get_interpreter().estimate(entry_expr, json.dumps(params)))
-    )
+    return EstimatorResult(json.loads(estimate_json(json.dumps(params))))
 
 
 def set_quantum_seed(seed: Optional[int]) -> None:
@@ -5,6 +5,7 @@ from ._estimator import (
     EstimatorError,
     LogicalCounts,
     EstimatorResult,
+    EstimatorBatchResult,
     QubitParams,
     QECScheme,
     MeasurementErrorRate,
@@ -22,6 +23,7 @@ __all__ = [
     "EstimatorError",
     "LogicalCounts",
     "EstimatorResult",
+    "EstimatorBatchResult",
     "QubitParams",
     "QECScheme",
     "MeasurementErrorRate",
@@ -0,0 +1,65 @@
+# Copyright (c) Microsoft Corporation.
+# Licensed under the MIT License.
+
+"""
+Estimates physical resources from the command line.
+
+Example:
+
+    python -m qsharp.estimator --qir program.ll --params params.json
+    python -m qsharp.estimator --qir program.ll --trace
+"""
+
+import argparse
+import json
+import sys
+from typing import List, Optional
+
+from .._native import QSharpError
+from .._qsharp import estimate_qir, logical_trace_qir
+from ._estimator import EstimatorError
+
+
+def main(argv: Optional[List[str]] = None) -> int:
+    parser = argparse.ArgumentParser(
+        prog="python -m qsharp.estimator",
+        description="Estimates physical resources for a quantum program.",
+    )
+    parser.add_argument(
+        "--qir",
+        required=True,
+        help="path to a QIR program in the textual LLVM format",
+    )
+    parser.add_argument(
+        "--params",
+        help="path to a JSON file with the job parameters or a batch specification",
+    )
+    parser.add_argument(
+        "--trace",
+        action="store_true",
+        help="print the trace of non-Clifford operations and measurements",
+    )
+    args = parser.parse_args(argv)
+
+    with open(args.qir, encoding="utf-8") as f:
+        qir = f.read()
+
+    params = None
+    if args.params is not None:
+        with open(args.params, encoding="utf-8") as f:
+            params = json.load(f)
+
+    try:
+        if args.trace:
+            result = logical_trace_qir(qir)
+        else:
+            result = estimate_qir(qir, params)
+    except (QSharpError, EstimatorError) as e:
+        print(f"error: {e}", file=sys.stderr)
+        return 1
+    print(json.dumps(result, indent=2))
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
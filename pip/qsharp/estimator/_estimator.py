@@ -229,6 +229,10 @@ class EstimatorQecScheme(AutoValidatingParams):
     crossing_prefactor: Optional[float] = None
     logical_cycle_time: Optional[str] = None
     physical_qubits_per_logical_qubit: Optional[str] = None
+    logical_qubits_per_code_block: Optional[str] = None
+    logical_error_rate: Optional[str] = None
+    code_parameters: Optional[List[Dict[str, float]]] = None
+    code_parameter_order: Optional[str] = None
 
 
 @dataclass
@@ -1002,6 +1006,33 @@ class EstimatorResult(dict):
         return "status" in obj and obj["status"] == "success"
 
 
+class EstimatorBatchResult(dict):
+    """
+    Microsoft Resource Estimator result for a batch specification.
+
+    The results of all points in the batch are available as a batching
+    ``EstimatorResult`` in ``results``, and the Pareto frontier of physical
+    qubits and runtime across all points in ``pareto_frontier``.  Each entry in
+    the Pareto frontier refers to a result by its ``index`` and, for frontier
+    estimates, to the frontier entry by its ``frontierEntry`` index.
+    """
+
+    def __init__(self, data: Dict):
+        super().__init__(data)
+        self.results = EstimatorResult(data["results"])
+        self.pareto_frontier = data["paretoFrontier"]
+
+
+def is_batch_specification(params: Any) -> bool:
+    """
+    Returns true, if the parameters are a batch specification, i.e., a
+    dictionary with any of the keys ``base``, ``items``, or ``grid``.
+    """
+    return isinstance(params, dict) and any(
+        key in params for key in ("base", "items", "grid")
+    )
+
+
 class EstimatorResultDiagram:
     def __init__(self, data):
         data.pop("reportData")
@@ -1056,16 +1087,21 @@ class LogicalCounts(dict):
 
     def estimate(
         self, params: Union[dict, List, EstimatorParams] = None
-    ) -> EstimatorResult:
+    ) -> Union[EstimatorResult, EstimatorBatchResult]:
         """
         Estimates resources for the current logical counts, using the
         Parallel Synthesis Sequential Pauli Computation (PSSPC) layout method.
 
         :param logical_counts: The logical counts.
-        :param params: The parameters to configure physical estimation.
+        :param params: The parameters to configure physical estimation, or a
+            batch specification with the keys ``base``, ``items``, and ``grid``.
 
         :returns resources: The estimated resources.
         """
+        if is_batch_specification(params):
+            return EstimatorBatchResult(
+                json.loads(physical_estimates(self.json, json.dumps(params)))
+            )
         if params is None:
             params = [{}]
         elif isinstance(params, EstimatorParams):
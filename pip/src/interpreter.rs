@@ -26,7 +26,7 @@ use qsc::{
     target::Profile,
     LanguageFeatures, PackageType, SourceMap,
 };
-use resource_estimator::{self as re, estimate_expr};
+use resource_estimator::{self as re, estimate_expr, estimate_qir, trace_expr, trace_qir};
 use std::fmt::Write;
 
 #[pymodule]
@@ -39,6 +39,8 @@ fn _native(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<StateDumpData>()?;
     m.add_class::<Circuit>()?;
     m.add_function(wrap_pyfunction!(physical_estimates, m)?)?;
+    m.add_function(wrap_pyfunction!(qir_estimates, m)?)?;
+    m.add_function(wrap_pyfunction!(qir_trace, m)?)?;
     m.add("QSharpError", py.get_type::<QSharpError>())?;
 
     Ok(())
@@ -140,6 +142,16 @@ impl Interpreter {
             )
             .load_project(&manifest_descriptor.0)
             .map_py_err()?;
+            if !project.errors.is_empty() {
+                return Err(QSharpError::new_err(
+                    project
+                        .errors
+                        .iter()
+                        .map(|error| format!("{error:?}"))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ));
+            }
             SourceMap::new(project.sources, None)
         } else {
             SourceMap::default()
@@ -180,6 +192,34 @@ impl Interpreter {
         }
     }
 
+    /// Imports an OpenQASM 2.0 or 3 program as a Q# operation in the `Qasm.<name>` namespace.
+    ///
+    /// :param source: The OpenQASM program.
+    /// :param name: The name of the generated operation.
+    /// :param output_fn: A callback function that will be called with each output.
+    ///
+    /// :raises QSharpError: If the name is not a valid Q# identifier or the program cannot be imported.
+    fn import_qasm(
+        &mut self,
+        py: Python,
+        source: &str,
+        name: &str,
+        callback: Option<PyObject>,
+    ) -> PyResult<()> {
+        if !qsc::qasm::is_identifier(name) {
+            return Err(QSharpError::new_err(format!(
+                "`{name}` is not a valid Q# identifier"
+            )));
+        }
+        let qsharp = qsc::qasm::qasm_to_qsharp(source, &format!("Qasm.{name}"), name)
+            .map_err(|error| QSharpError::new_err(format!("{:?}", Report::new(error))))?;
+        let mut receiver = OptionalCallbackReceiver { callback, py };
+        match self.interpreter.eval_fragments(&mut receiver, &qsharp) {
+            Ok(_) => Ok(()),
+            Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
+        }
+    }
+
     /// Sets the quantum seed for the interpreter.
     fn set_quantum_seed(&mut self, seed: Option<u64>) {
         self.interpreter.set_quantum_seed(seed);
@@ -222,13 +262,46 @@ impl Interpreter {
         }
     }
 
-    fn qir(&mut self, _py: Python, entry_expr: &str) -> PyResult<String> {
+    /// Generates QIR from Q# source code.
+    ///
+    /// :param entry_expr: The entry expression.
+    ///
+    /// :param optimize_gates: Whether adjacent gates that cancel out or can be merged are
+    /// optimized away.
+    ///
+    /// :param reuse_qubits: Whether qubits with lifetimes that do not overlap are mapped onto
+    /// the same qubit.
+    ///
+    /// :raises QSharpError: If there is an error generating the program.
+    fn qir(
+        &mut self,
+        _py: Python,
+        entry_expr: &str,
+        optimize_gates: Option<bool>,
+        reuse_qubits: Option<bool>,
+    ) -> PyResult<String> {
+        self.interpreter
+            .set_optimize_gates(optimize_gates.unwrap_or_default());
+        self.interpreter
+            .set_reuse_qubits(reuse_qubits.unwrap_or_default());
         match self.interpreter.qirgen(entry_expr) {
             Ok(qir) => Ok(qir),
             Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
         }
     }
 
+    /// Generates an OpenQASM 3 program from Q# source code.
+    ///
+    /// :param entry_expr: The entry expression.
+    ///
+    /// :raises QSharpError: If there is an error generating the program.
+    fn qasm(&mut self, _py: Python, entry_expr: &str) -> PyResult<String> {
+        match self.interpreter.qasm(entry_expr) {
+            Ok(qasm) => Ok(qasm),
+            Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
+        }
+    }
+
     /// Synthesizes a circuit for a Q# program. Either an entry
     /// expression or an operation must be provided.
     ///
@@ -238,12 +311,16 @@ impl Interpreter {
     /// an operation of a lambda expression. The operation must take only
     /// qubits or arrays of qubits as parameters.
     ///
+    /// :param operation_depth: The maximum depth of nested operation calls that are
+    /// grouped into parent operations. `0` produces a flat list of gates.
+    ///
     /// :raises QSharpError: If there is an error synthesizing the circuit.
     fn circuit(
         &mut self,
         py: Python,
         entry_expr: Option<String>,
         operation: Option<String>,
+        operation_depth: Option<usize>,
     ) -> PyResult<PyObject> {
         let entrypoint = match (entry_expr, operation) {
             (Some(entry_expr), None) => CircuitEntryPoint::EntryExpr(entry_expr),
@@ -255,6 +332,8 @@ impl Interpreter {
             }
         };
 
+        self.interpreter
+            .set_circuit_operation_depth(operation_depth.unwrap_or_default());
         match self.interpreter.circuit(entrypoint, false) {
             Ok(circuit) => Ok(Circuit(circuit).into_py(py)),
             Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
@@ -270,7 +349,7 @@ impl Interpreter {
                         .into_iter()
                         .map(|e| match e {
                             re::Error::Interpreter(e) => e,
-                            re::Error::Estimation(_) => unreachable!(),
+                            re::Error::Qir(_) | re::Error::Estimation(_) => unreachable!(),
                         })
                         .collect::<Vec<_>>(),
                 )))
@@ -280,13 +359,30 @@ impl Interpreter {
                     .into_iter()
                     .map(|e| match e {
                         re::Error::Estimation(e) => e.to_string(),
-                        re::Error::Interpreter(_) => unreachable!(),
+                        re::Error::Interpreter(_) | re::Error::Qir(_) => unreachable!(),
                     })
                     .collect::<Vec<_>>()
                     .join("\n"),
             )),
         }
     }
+
+    /// Evaluates the entry expression and returns the trace of all non-Clifford
+    /// operations and measurements, with their layers and qubits, as JSON.
+    fn trace(&mut self, _py: Python, entry_expr: &str) -> PyResult<String> {
+        match trace_expr(&mut self.interpreter, entry_expr) {
+            Ok(trace) => Ok(trace),
+            Err(errors) => Err(QSharpError::new_err(format_errors(
+                errors
+                    .into_iter()
+                    .map(|e| match e {
+                        re::Error::Interpreter(e) => e,
+                        re::Error::Qir(_) | re::Error::Estimation(_) => unreachable!(),
+                    })
+                    .collect::<Vec<_>>(),
+            ))),
+        }
+    }
 }
 
 #[pyfunction]
@@ -297,6 +393,34 @@ pub fn physical_estimates(logical_resources: &str, job_params: &str) -> PyResult
     }
 }
 
+#[pyfunction]
+pub fn qir_estimates(qir: &str, job_params: &str) -> PyResult<String> {
+    match estimate_qir(qir, job_params) {
+        Ok(estimates) => Ok(estimates),
+        Err(errors) => Err(QSharpError::new_err(
+            errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )),
+    }
+}
+
+#[pyfunction]
+pub fn qir_trace(qir: &str) -> PyResult<String> {
+    match trace_qir(qir) {
+        Ok(trace) => Ok(trace),
+        Err(errors) => Err(QSharpError::new_err(
+            errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )),
+    }
+}
+
 create_exception!(
     module,
     QSharpError,
@@ -1,6 +1,7 @@
 # Copyright (c) Microsoft Corporation.
 # Licensed under the MIT License.
 
+import json
 from textwrap import dedent
 from qsharp._native import (
     Interpreter,
@@ -465,6 +466,16 @@ def test_operation_circuit() -> None:
     )
 
 
+def test_operation_circuit_with_operation_depth() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret("operation Foo(q: Qubit) : Unit { H(q); }")
+    circuit = json.loads(e.circuit(operation="Foo", operation_depth=1).json())
+    assert [op["gate"] for op in circuit["operations"]] == ["Foo"]
+    assert [op["gate"] for op in circuit["operations"][0]["children"]] == ["H"]
+    circuit = json.loads(e.circuit(operation="Foo").json())
+    assert [op["gate"] for op in circuit["operations"]] == ["H"]
+
+
 def test_unsupported_operation_circuit() -> None:
     e = Interpreter(TargetProfile.Unrestricted)
     e.interpret("operation Foo(n: Int) : Result { return One }")
@@ -5,6 +5,7 @@ import qsharp
 import qsharp.utils
 from contextlib import redirect_stdout
 import io
+import pytest
 
 # Tests for the Python library for Q#
 
@@ -265,6 +266,63 @@ def test_compile_qir_str() -> None:
     assert "define void @ENTRYPOINT__main()" in qir
 
 
+def test_compile_qir_with_gate_optimization_and_qubit_reuse() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Quantinuum)
+    qsharp.eval(
+        """
+        operation Program() : Result[] {
+            use (a, b, c) = (Qubit(), Qubit(), Qubit());
+            H(a);
+            H(a);
+            X(a);
+            let r1 = MResetZ(a);
+            X(b);
+            let r2 = MResetZ(b);
+            X(c);
+            let r3 = MResetZ(c);
+            [r1, r2, r3]
+        }
+        """
+    )
+    qir = str(qsharp.compile("Program()"))
+    assert "__quantum__qis__h__body" in qir
+    assert '"required_num_qubits"="3"' in qir
+    qir = str(qsharp.compile("Program()", optimize_gates=True, reuse_qubits=True))
+    assert "__quantum__qis__h__body" not in qir
+    assert '"required_num_qubits"="1"' in qir
+
+
+def test_qasm_str() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Quantinuum)
+    qsharp.eval("operation Program() : Result { use q = Qubit(); H(q); return M(q) }")
+    qasm = qsharp.qasm("Program()")
+    assert qasm.startswith("OPENQASM 3.0;")
+    assert "c[0] = measure q[0];" in qasm
+
+
+def test_import_qasm() -> None:
+    qsharp.init()
+    qsharp.import_qasm(
+        """
+        OPENQASM 2.0;
+        include "qelib1.inc";
+        qreg q[2];
+        creg c[2];
+        x q[0];
+        cx q[0], q[1];
+        measure q -> c;
+        """,
+        "Flip",
+    )
+    assert qsharp.eval("Qasm.Flip.Flip()") == [qsharp.Result.One, qsharp.Result.One]
+
+
+def test_import_qasm_rejects_invalid_name() -> None:
+    qsharp.init()
+    with pytest.raises(qsharp.QSharpError):
+        qsharp.import_qasm("OPENQASM 2.0;", "not a name")
+
+
 def test_run_with_result(capsys) -> None:
     qsharp.init()
     qsharp.eval('operation Foo() : Result { Message("Hello, world!"); Zero }')
@@ -1,6 +1,7 @@
 # Copyright (c) Microsoft Corporation.
 # Licensed under the MIT License.
 
+import json
 import qsharp
 from qsharp.estimator import EstimatorParams, QubitParams, QECScheme, LogicalCounts
 
@@ -57,6 +58,100 @@ def test_qsharp_estimation_from_precalculated_counts() -> None:
     )
 
 
+def test_qir_estimation_matches_qsharp_estimation() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Base)
+    qsharp.eval(
+        """
+        operation Program() : Result[] {
+            use qs = Qubit[3];
+            T(qs[0]);
+            CCNOT(qs[0], qs[1], qs[2]);
+            Rx(0.1, qs[1]);
+            MResetEachZ(qs)
+        }
+        """
+    )
+    expected = qsharp.estimate("Program()")
+    res = qsharp.estimate_qir(qsharp.compile("Program()"))
+
+    assert res["status"] == "success"
+    assert res.logical_counts == expected.logical_counts
+    assert res["physicalCounts"] == expected["physicalCounts"]
+
+
+def test_qir_estimation_from_command_line(tmp_path, capsys) -> None:
+    from qsharp.estimator.__main__ import main
+
+    qir_file = tmp_path / "program.ll"
+    qir_file.write_text(
+        """
+        define void @main() #0 {
+          call void @__quantum__qis__t__body(%Qubit* null)
+          call void @__quantum__qis__mz__body(%Qubit* null, %Result* null)
+          ret void
+        }
+
+        attributes #0 = { "entry_point" }
+        """
+    )
+    params_file = tmp_path / "params.json"
+    params_file.write_text('{"qubitParams": {"name": "qubit_maj_ns_e4"}}')
+
+    assert main(["--qir", str(qir_file), "--params", str(params_file)]) == 0
+    res = json.loads(capsys.readouterr().out)
+    assert res["status"] == "success"
+    assert res["logicalCounts"]["tCount"] == 1
+    assert res["jobParams"]["qubitParams"]["name"] == "qubit_maj_ns_e4"
+
+    qir_file.write_text("define void @main() {\n  call void @foo()\n}")
+    assert main(["--qir", str(qir_file)]) == 1
+
+
+def test_qsharp_logical_trace() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    trace = qsharp.logical_trace(
+        """{{
+        use qs = Qubit[3];
+        T(qs[0]);
+        CCNOT(qs[0], qs[1], qs[2]);
+        MResetZ(qs[2])
+        }}"""
+    )
+
+    assert trace["numQubits"] == 3
+    assert trace["numLayers"] == 2
+    assert trace["operations"] == [
+        {"kind": "t", "layer": 0, "qubits": [0]},
+        {"kind": "ccz", "layer": 1, "qubits": [0, 1, 2]},
+        {"kind": "measurement", "layer": 2, "qubits": [2]},
+    ]
+
+
+def test_qir_logical_trace_from_command_line(tmp_path, capsys) -> None:
+    from qsharp.estimator.__main__ import main
+
+    qir_file = tmp_path / "program.ll"
+    qir_file.write_text(
+        """
+        define void @main() #0 {
+          call void @__quantum__qis__t__body(%Qubit* null)
+          call void @__quantum__qis__mz__body(%Qubit* null, %Result* null)
+          ret void
+        }
+
+        attributes #0 = { "entry_point" }
+        """
+    )
+
+    assert main(["--qir", str(qir_file), "--trace"]) == 0
+    trace = json.loads(capsys.readouterr().out)
+    assert trace["numQubits"] == 1
+    assert [operation["kind"] for operation in trace["operations"]] == [
+        "t",
+        "measurement",
+    ]
+
+
 def test_qsharp_estimation_with_single_params() -> None:
     qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
 
@@ -228,6 +323,63 @@ def test_estimation_from_logical_counts_with_multiple_params() -> None:
     assert res[2]["jobParams"]["qecScheme"]["name"] == QECScheme.FLOQUET_CODE
 
 
+def test_batch_estimation_from_logical_counts() -> None:
+    logical_counts = LogicalCounts(
+        {
+            "numQubits": 12581,
+            "tCount": 12,
+            "rotationCount": 12,
+            "rotationDepth": 12,
+            "cczCount": 3731607428,
+            "measurementCount": 1078154040,
+        }
+    )
+    res = logical_counts.estimate(
+        params={
+            "grid": {
+                "qubitParams": [
+                    {"name": QubitParams.GATE_NS_E3},
+                    {"name": QubitParams.MAJ_NS_E4},
+                ],
+                "errorBudget": [0.001, 0.01],
+            }
+        }
+    )
+
+    assert len(res.results) == 4
+    for idx in res.results:
+        assert res.results[idx]["status"] == "success"
+    assert len(res.pareto_frontier) > 0
+    runtimes = [point["runtime"] for point in res.pareto_frontier]
+    assert runtimes == sorted(runtimes)
+
+
+def test_batch_estimation_with_items_from_logical_counts() -> None:
+    logical_counts = LogicalCounts(
+        {
+            "numQubits": 12581,
+            "tCount": 12,
+            "rotationCount": 12,
+            "rotationDepth": 12,
+            "cczCount": 3731607428,
+            "measurementCount": 1078154040,
+        }
+    )
+    res = logical_counts.estimate(
+        params={
+            "items": [
+                {"qubitParams": {"name": QubitParams.GATE_NS_E3}},
+                {"qubitParams": {"name": QubitParams.MAJ_NS_E4}},
+            ]
+        }
+    )
+
+    assert len(res.results) == 2
+    for idx in res.results:
+        assert res.results[idx]["status"] == "success"
+    assert len(res.pareto_frontier) > 0
+
+
 def test_building_frontier_from_logical_counts_with_single_params() -> None:
     logical_counts = LogicalCounts(
         {
@@ -4,9 +4,12 @@
 #[cfg(test)]
 mod tests;
 
+mod call_stack;
+mod trace;
+
 use num_bigint::BigUint;
 use num_complex::Complex;
-use qsc::{interpret::Value, Backend};
+use qsc::{functors::FunctorApp, interpret::Value, Backend};
 use rand::{rngs::StdRng, Rng, SeedableRng};
 use rustc_hash::FxHashMap;
 use std::{
@@ -17,7 +20,12 @@ use std::{
     iter::Sum,
 };
 
-use crate::system::LogicalResourceCounts;
+use crate::system::{
+    CallStackCounts, CallStackProfile, LayerCounts, LayerProfile, LogicalResourceCounts,
+};
+
+use self::call_stack::CallStackProfiler;
+pub use self::trace::{LogicalTrace, TracedOperation, TracedOperationKind};
 
 /// Resource counter implementation
 ///
@@ -49,6 +57,10 @@ pub struct LogicalCounter {
     caching_layers: FxHashMap<String, LayerCache>,
     /// Repeating
     repeats: Vec<RepeatEntry>,
+    /// Call-stack profiling, if enabled
+    call_stack: Option<CallStackProfiler>,
+    /// Traced operations, if tracing is enabled
+    trace: Option<Vec<TracedOperation>>,
     /// Random number generator
     rnd: RefCell<StdRng>,
 }
@@ -68,12 +80,67 @@ impl Default for LogicalCounter {
             caching_stack: vec![],
             caching_layers: FxHashMap::default(),
             repeats: vec![],
+            call_stack: None,
+            trace: None,
             rnd: RefCell::new(StdRng::seed_from_u64(0)),
         }
     }
 }
 
 impl LogicalCounter {
+    /// Creates a counter that additionally attributes logical resources to
+    /// call stacks of up to `depth` frames.
+    #[must_use]
+    pub fn with_call_stack_depth(depth: usize) -> Self {
+        Self {
+            call_stack: Some(CallStackProfiler::new(depth)),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the logical resources per call stack, if call-stack profiling
+    /// is enabled.
+    #[must_use]
+    pub fn call_stack_profile(&self) -> Option<CallStackProfile> {
+        self.call_stack.as_ref().map(CallStackProfiler::profile)
+    }
+
+    /// Enables tracing of all non-Clifford operations and measurements
+    /// together with their layers and qubits.
+    #[must_use]
+    pub fn with_trace(self) -> Self {
+        Self {
+            trace: Some(vec![]),
+            ..self
+        }
+    }
+
+    /// Returns the traced operations, if tracing is enabled.
+    #[must_use]
+    pub fn trace(&self) -> Option<LogicalTrace> {
+        self.trace.as_ref().map(|operations| LogicalTrace {
+            num_qubits: self.next_free,
+            num_layers: self.layers.len(),
+            operations: operations.clone(),
+        })
+    }
+
+    fn record(&mut self, kind: TracedOperationKind, layer: usize, qubits: &[usize]) {
+        if let Some(trace) = &mut self.trace {
+            trace.push(TracedOperation {
+                kind,
+                layer,
+                qubits: qubits.to_vec(),
+            });
+        }
+    }
+
+    fn profile(&mut self, counts: CallStackCounts) {
+        if let Some(call_stack) = &mut self.call_stack {
+            call_stack.record(counts);
+        }
+    }
+
     #[must_use]
     pub fn logical_resources(&self) -> LogicalResourceCounts {
         LogicalResourceCounts {
@@ -87,6 +154,17 @@ impl LogicalCounter {
         }
     }
 
+    /// Returns the non-Clifford operations and active qubits per layer
+    #[must_use]
+    pub fn layer_profile(&self) -> LayerProfile {
+        LayerProfile::new(self.layers.iter().map(|layer| LayerCounts {
+            t_count: layer.t as _,
+            rotation_count: layer.r as _,
+            ccz_count: layer.ccz as _,
+            active_qubits: layer.qubits as _,
+        }))
+    }
+
     fn schedule_r(&mut self, q: usize) {
         let level = self.level_at(q);
 
@@ -95,6 +173,8 @@ impl LogicalCounter {
         } else {
             self.layers[level].r += 1;
         }
+        self.track_active_qubits(level);
+        self.record(TracedOperationKind::Rotation, level, &[q]);
 
         self.max_layer[q] += 1;
     }
@@ -107,6 +187,8 @@ impl LogicalCounter {
         } else {
             self.layers[level].t += 1;
         }
+        self.track_active_qubits(level);
+        self.record(TracedOperationKind::T, level, &[q]);
 
         self.max_layer[q] += 1;
     }
@@ -122,6 +204,8 @@ impl LogicalCounter {
         } else {
             self.layers[max_depth].ccz += 1;
         }
+        self.track_active_qubits(max_depth);
+        self.record(TracedOperationKind::Ccz, max_depth, &[q1, q2, q3]);
 
         self.max_layer[q1] = max_depth + 1;
         self.max_layer[q2] = max_depth + 1;
@@ -136,6 +220,14 @@ impl LogicalCounter {
         self.max_layer[q2] = max_depth;
     }
 
+    /// Updates the maximum number of qubits that are allocated while
+    /// operations are scheduled in the layer at `level`
+    fn track_active_qubits(&mut self, level: usize) {
+        let active_qubits = self.next_free - self.free_list.len();
+        let layer = &mut self.layers[level];
+        layer.qubits = layer.qubits.max(active_qubits);
+    }
+
     fn level_at(&mut self, q: usize) -> usize {
         while self.max_layer.len() <= q {
             self.qubit_allocate();
@@ -172,6 +264,10 @@ impl LogicalCounter {
             self.ccz_count += combined_layer.ccz;
             self.m_count += *m_count;
 
+            if let Some(call_stack) = &mut self.call_stack {
+                call_stack.replay_cached(&label);
+            }
+
             false
         } else {
             let depth = self.global_barrier();
@@ -183,6 +279,9 @@ impl LogicalCounter {
                     m_count: self.m_count,
                 },
             );
+            if let Some(call_stack) = &mut self.call_stack {
+                call_stack.begin_region();
+            }
             self.caching_stack.push(label);
 
             true
@@ -212,6 +311,10 @@ impl LogicalCounter {
         let range = &self.layers[start_depth..end_depth];
         let sum: LayerInfo = range.iter().sum();
 
+        if let Some(call_stack) = &mut self.call_stack {
+            call_stack.end_caching(label.clone());
+        }
+
         self.caching_layers.insert(
             label,
             LayerCache::End {
@@ -237,6 +340,10 @@ impl LogicalCounter {
             m_count: self.m_count,
         });
 
+        if let Some(call_stack) = &mut self.call_stack {
+            call_stack.begin_region();
+        }
+
         Ok(())
     }
 
@@ -248,6 +355,10 @@ impl LogicalCounter {
             m_count,
         }) = self.repeats.pop()
         {
+            if let Some(call_stack) = &mut self.call_stack {
+                call_stack.end_repeat(count.max(1) as u64);
+            }
+
             if count == 0 {
                 return;
             }
@@ -273,15 +384,20 @@ impl LogicalCounter {
                     ccz: combined_ccz_count,
                     r: first_layer_r_count,
                     t: combined_t_count,
+                    qubits: sum.qubits,
                 });
                 for _ in 1..combined_r_depth {
-                    self.layers.push(LayerInfo::new_with_r());
+                    self.layers.push(LayerInfo {
+                        qubits: sum.qubits,
+                        ..LayerInfo::new_with_r()
+                    });
                 }
             } else {
                 self.layers.push(LayerInfo {
                     ccz: combined_ccz_count,
                     r: combined_r_count,
                     t: combined_t_count,
+                    qubits: sum.qubits,
                 });
             }
 
@@ -346,6 +462,8 @@ impl LogicalCounter {
             self.max_layer[*qubit] = max_depth;
         }
 
+        let active_qubits = self.next_free - self.free_list.len();
+
         // Add up the estimates, dividing up between layers if appropriate.
         let num_layers = if r_depth == 0 {
             if r_count != 0 {
@@ -356,6 +474,7 @@ impl LogicalCounter {
                 t: t_count,
                 r: r_count,
                 ccz: ccz_count,
+                qubits: active_qubits,
             });
 
             1
@@ -373,6 +492,7 @@ impl LogicalCounter {
                 t: t_count,
                 r: r_count_per_layer + extra_count,
                 ccz: ccz_count,
+                qubits: active_qubits,
             });
 
             for _ in 1..r_depth {
@@ -380,6 +500,7 @@ impl LogicalCounter {
                     t: 0,
                     r: r_count_per_layer,
                     ccz: 0,
+                    qubits: active_qubits,
                 });
             }
 
@@ -391,6 +512,14 @@ impl LogicalCounter {
         self.ccz_count += ccz_count;
         self.m_count += m_count;
 
+        self.profile(CallStackCounts {
+            t_count: t_count as u64,
+            rotation_count: r_count as u64,
+            ccz_count: ccz_count as u64,
+            measurement_count: m_count as u64,
+            qubit_allocations: 0,
+        });
+
         for qubit in qubits {
             self.max_layer[*qubit] += num_layers;
         }
@@ -409,6 +538,10 @@ impl Backend for LogicalCounter {
 
     fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
         self.ccz_count += 1;
+        self.profile(CallStackCounts {
+            ccz_count: 1,
+            ..CallStackCounts::default()
+        });
         self.schedule_ccz(ctl0, ctl1, q);
     }
 
@@ -426,8 +559,22 @@ impl Backend for LogicalCounter {
 
     fn h(&mut self, _q: usize) {}
 
-    fn m(&mut self, _q: usize) -> Self::ResultType {
+    fn m(&mut self, q: usize) -> Self::ResultType {
         self.m_count += 1;
+        self.profile(CallStackCounts {
+            measurement_count: 1,
+            ..CallStackCounts::default()
+        });
+        if self.trace.is_some() {
+            // Tracing must not change the counts, so unlike `level_at` this
+            // does not allocate qubits that have not been used yet.
+            let level = self
+                .max_layer
+                .get(q)
+                .copied()
+                .unwrap_or(self.allocation_barrier);
+            self.record(TracedOperationKind::Measurement, level, &[q]);
+        }
 
         self.rnd.borrow_mut().gen_bool(0.5)
     }
@@ -463,6 +610,10 @@ impl Backend for LogicalCounter {
             }
         } else {
             self.r_count += 1;
+            self.profile(CallStackCounts {
+                rotation_count: 1,
+                ..CallStackCounts::default()
+            });
             self.schedule_r(q);
         }
     }
@@ -483,11 +634,19 @@ impl Backend for LogicalCounter {
 
     fn tadj(&mut self, q: usize) {
         self.t_count += 1;
+        self.profile(CallStackCounts {
+            t_count: 1,
+            ..CallStackCounts::default()
+        });
         self.schedule_t(q);
     }
 
     fn t(&mut self, q: usize) {
         self.t_count += 1;
+        self.profile(CallStackCounts {
+            t_count: 1,
+            ..CallStackCounts::default()
+        });
         self.schedule_t(q);
     }
 
@@ -498,6 +657,11 @@ impl Backend for LogicalCounter {
     fn z(&mut self, _q: usize) {}
 
     fn qubit_allocate(&mut self) -> usize {
+        self.profile(CallStackCounts {
+            qubit_allocations: 1,
+            ..CallStackCounts::default()
+        });
+
         if let Some(index) = self.free_list.pop() {
             index
         } else {
@@ -567,6 +731,22 @@ impl Backend for LogicalCounter {
             _ => None,
         }
     }
+
+    fn enter_callable(&mut self, name: &str, functor: FunctorApp, _arg: &Value) {
+        if let Some(call_stack) = &mut self.call_stack {
+            if functor == FunctorApp::default() {
+                call_stack.enter(name.to_string());
+            } else {
+                call_stack.enter(format!("{functor} {name}"));
+            }
+        }
+    }
+
+    fn leave_callable(&mut self) {
+        if let Some(call_stack) = &mut self.call_stack {
+            call_stack.leave();
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -574,22 +754,33 @@ pub struct LayerInfo {
     t: usize,
     r: usize,
     ccz: usize,
+    /// Maximum number of allocated qubits while scheduling the layer
+    qubits: usize,
 }
 
 impl LayerInfo {
     #[must_use]
     pub fn new_with_t() -> Self {
-        Self { t: 1, r: 0, ccz: 0 }
+        Self {
+            t: 1,
+            ..Self::default()
+        }
     }
 
     #[must_use]
     pub fn new_with_r() -> Self {
-        Self { t: 0, r: 1, ccz: 0 }
+        Self {
+            r: 1,
+            ..Self::default()
+        }
     }
 
     #[must_use]
     pub fn new_with_ccz() -> Self {
-        Self { t: 0, r: 0, ccz: 1 }
+        Self {
+            ccz: 1,
+            ..Self::default()
+        }
     }
 }
 
@@ -601,6 +792,7 @@ impl<'a> Sum<&'a LayerInfo> for LayerInfo {
             layer.t += current.t;
             layer.r += current.r;
             layer.ccz += current.ccz;
+            layer.qubits = layer.qubits.max(current.qubits);
         }
 
         layer
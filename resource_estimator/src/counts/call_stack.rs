@@ -0,0 +1,168 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use rustc_hash::FxHashMap;
+
+use crate::system::{CallStackCounts, CallStackProfile};
+
+/// Attributes logical resources to the call stack in which they are consumed
+///
+/// Call stacks are tracked up to a maximum depth; resources consumed in deeper
+/// frames are attributed to their ancestor at the maximum depth.
+pub struct CallStackProfiler {
+    /// Maximum number of frames in a call stack
+    max_depth: usize,
+    /// Frame names
+    frames: Vec<String>,
+    /// Frame indexes by name
+    frame_ids: FxHashMap<String, usize>,
+    /// Current call stack, up to the maximum depth
+    stack: Vec<usize>,
+    /// Depth of the current call stack, including frames beyond the maximum depth
+    depth: usize,
+    /// Resources per call stack for nested regions that are cached or
+    /// repeated; the first region covers the whole program
+    regions: Vec<Region>,
+    /// Resources of cached regions, with call stacks relative to the call
+    /// stack at the beginning of the region
+    cached: FxHashMap<String, Vec<(Vec<usize>, CallStackCounts)>>,
+}
+
+struct Region {
+    /// Length of the call stack at the beginning of the region
+    stack_len: usize,
+    counts: FxHashMap<Vec<usize>, CallStackCounts>,
+}
+
+impl Region {
+    fn new(stack_len: usize) -> Self {
+        Self {
+            stack_len,
+            counts: FxHashMap::default(),
+        }
+    }
+}
+
+impl CallStackProfiler {
+    #[must_use]
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            max_depth,
+            frames: vec![],
+            frame_ids: FxHashMap::default(),
+            stack: vec![],
+            depth: 0,
+            regions: vec![Region::new(0)],
+            cached: FxHashMap::default(),
+        }
+    }
+
+    pub fn enter(&mut self, name: String) {
+        self.depth += 1;
+        if self.stack.len() < self.max_depth {
+            let next_id = self.frames.len();
+            let id = *self.frame_ids.entry(name).or_insert_with_key(|name| {
+                self.frames.push(name.clone());
+                next_id
+            });
+            self.stack.push(id);
+        }
+    }
+
+    pub fn leave(&mut self) {
+        if self.depth == self.stack.len() {
+            self.stack.pop();
+        }
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    pub fn record(&mut self, counts: CallStackCounts) {
+        *self
+            .regions
+            .last_mut()
+            .expect("there should always be a region")
+            .counts
+            .entry(self.stack.clone())
+            .or_default() += counts;
+    }
+
+    pub fn begin_region(&mut self) {
+        self.regions.push(Region::new(self.stack.len()));
+    }
+
+    /// Ends the innermost region and returns its resources, with call stacks
+    /// relative to the call stack at the beginning of the region.
+    fn end_region(&mut self) -> Vec<(Vec<usize>, CallStackCounts)> {
+        if self.regions.len() == 1 {
+            return vec![];
+        }
+
+        let region = self.regions.pop().expect("there should be a region");
+        let parent = self.regions.last_mut().expect("there should be a region");
+        region
+            .counts
+            .into_iter()
+            .map(|(stack, counts)| {
+                let relative = stack[region.stack_len.min(stack.len())..].to_vec();
+                *parent.counts.entry(stack).or_default() += counts;
+                (relative, counts)
+            })
+            .collect()
+    }
+
+    /// Ends the innermost region and records its resources `factor` times in
+    /// the call stacks in which they were consumed.
+    pub fn end_repeat(&mut self, factor: u64) {
+        if self.regions.len() == 1 {
+            return;
+        }
+
+        let region = self.regions.pop().expect("there should be a region");
+        let parent = self.regions.last_mut().expect("there should be a region");
+        for (stack, counts) in region.counts {
+            *parent.counts.entry(stack).or_default() += counts * factor;
+        }
+    }
+
+    /// Records the resources of a region, given relative to the current call
+    /// stack.
+    fn replay(&mut self, counts: &[(Vec<usize>, CallStackCounts)]) {
+        for (relative, counts) in counts {
+            let mut stack = self.stack.clone();
+            stack.extend_from_slice(relative);
+            stack.truncate(self.max_depth);
+            *self
+                .regions
+                .last_mut()
+                .expect("there should always be a region")
+                .counts
+                .entry(stack)
+                .or_default() += *counts;
+        }
+    }
+
+    pub fn end_caching(&mut self, label: String) {
+        let counts = self.end_region();
+        self.cached.insert(label, counts);
+    }
+
+    pub fn replay_cached(&mut self, label: &str) {
+        if let Some(counts) = self.cached.remove(label) {
+            self.replay(&counts);
+            self.cached.insert(label.to_string(), counts);
+        }
+    }
+
+    #[must_use]
+    pub fn profile(&self) -> CallStackProfile {
+        CallStackProfile::new(
+            self.frames.clone(),
+            self.regions.iter().flat_map(|region| {
+                region
+                    .counts
+                    .iter()
+                    .map(|(stack, counts)| (stack.clone(), *counts))
+            }),
+        )
+    }
+}
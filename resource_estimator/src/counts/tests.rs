@@ -10,10 +10,12 @@ use indoc::indoc;
 use qsc::{
     interpret::{GenericReceiver, Interpreter},
     target::Profile,
-    LanguageFeatures, PackageType, SourceMap,
+    Backend, LanguageFeatures, PackageType, SourceMap,
 };
 
-use super::LogicalCounter;
+use crate::system::{CallStackCounts, CallStackProfile, LayerCounts};
+
+use super::{LogicalCounter, TracedOperation, TracedOperationKind};
 
 fn verify_logical_counts(source: &str, entry: Option<&str>, expect: &Expect) {
     let source_map = SourceMap::new([("test".into(), source.into())], entry.map(Into::into));
@@ -34,6 +36,27 @@ fn verify_logical_counts(source: &str, entry: Option<&str>, expect: &Expect) {
     expect.assert_debug_eq(&counter.logical_resources());
 }
 
+fn call_stack_profile(source: &str, depth: usize) -> CallStackProfile {
+    let source_map = SourceMap::new([("test".into(), source.into())], None);
+    let mut interpreter = Interpreter::new(
+        true,
+        source_map,
+        PackageType::Exe,
+        Profile::Unrestricted.into(),
+        LanguageFeatures::default(),
+    )
+    .expect("compilation should succeed");
+    let mut counter = LogicalCounter::with_call_stack_depth(depth);
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    interpreter
+        .eval_entry_with_sim(&mut counter, &mut out)
+        .expect("evaluation should succeed");
+    counter
+        .call_stack_profile()
+        .expect("call-stack profiling should be enabled")
+}
+
 #[test]
 fn gates_are_counted() {
     verify_logical_counts(
@@ -208,3 +231,311 @@ fn account_for_estimates_works() {
         "]],
     );
 }
+
+#[test]
+fn call_stack_profile_attributes_counts_to_callables() {
+    let profile = call_stack_profile(
+        indoc! {"
+            namespace Test {
+                operation ApplyT(q: Qubit) : Unit is Adj {
+                    T(q);
+                }
+
+                operation Rotate(q: Qubit) : Unit {
+                    Rx(1.0, q);
+                    ApplyT(q);
+                }
+
+                @EntryPoint()
+                operation Main() : Result {
+                    use q = Qubit();
+                    Rotate(q);
+                    Adjoint ApplyT(q);
+                    M(q)
+                }
+            }
+        "},
+        2,
+    );
+
+    assert_eq!(profile.frames(), ["Main", "Rotate", "Adjoint ApplyT", "M"]);
+    assert_eq!(
+        profile.samples(),
+        [
+            (
+                vec![0],
+                CallStackCounts {
+                    qubit_allocations: 1,
+                    ..CallStackCounts::default()
+                }
+            ),
+            (
+                vec![0, 1],
+                CallStackCounts {
+                    t_count: 1,
+                    rotation_count: 1,
+                    ..CallStackCounts::default()
+                }
+            ),
+            (
+                vec![0, 2],
+                CallStackCounts {
+                    t_count: 1,
+                    ..CallStackCounts::default()
+                }
+            ),
+            (
+                vec![0, 3],
+                CallStackCounts {
+                    measurement_count: 1,
+                    ..CallStackCounts::default()
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn call_stack_profile_multiplies_repeated_counts() {
+    let profile = call_stack_profile(
+        indoc! {r#"
+            namespace Test {
+                open Microsoft.Quantum.ResourceEstimation;
+
+                operation Rotate(qs: Qubit[]) : Unit {
+                    for q in qs {
+                        Rx(1.0, q);
+                        Ry(1.0, q);
+                        Rz(1.0, q);
+                    }
+                }
+
+                @EntryPoint()
+                operation Main() : Unit {
+                    use qs = Qubit[10];
+                    within {
+                        RepeatEstimates(10);
+                    }
+                    apply {
+                        Rotate(qs);
+                    }
+                }
+            }
+        "#},
+        2,
+    );
+
+    assert_eq!(
+        profile.frames(),
+        [
+            "Main",
+            "AllocateQubitArray",
+            "RepeatEstimates",
+            "Rotate",
+            "Adjoint RepeatEstimates",
+            "ReleaseQubitArray"
+        ]
+    );
+    assert_eq!(
+        profile.samples(),
+        [
+            (
+                vec![0, 1],
+                CallStackCounts {
+                    qubit_allocations: 10,
+                    ..CallStackCounts::default()
+                }
+            ),
+            (
+                vec![0, 3],
+                CallStackCounts {
+                    rotation_count: 300,
+                    ..CallStackCounts::default()
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn layer_profile_tracks_active_qubits() {
+    let source_map = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Test {
+                    @EntryPoint()
+                    operation Main() : Unit {
+                        use q = Qubit();
+                        T(q);
+                        T(q);
+                        use qs = Qubit[2];
+                        CCNOT(q, qs[0], qs[1]);
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let mut interpreter = Interpreter::new(
+        true,
+        source_map,
+        PackageType::Exe,
+        Profile::Unrestricted.into(),
+        LanguageFeatures::default(),
+    )
+    .expect("compilation should succeed");
+    let mut counter = LogicalCounter::default();
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    interpreter
+        .eval_entry_with_sim(&mut counter, &mut out)
+        .expect("evaluation should succeed");
+
+    assert_eq!(
+        counter.layer_profile().layers(),
+        [
+            LayerCounts {
+                t_count: 1,
+                active_qubits: 1,
+                ..LayerCounts::default()
+            },
+            LayerCounts {
+                t_count: 1,
+                active_qubits: 1,
+                ..LayerCounts::default()
+            },
+            LayerCounts {
+                ccz_count: 1,
+                active_qubits: 3,
+                ..LayerCounts::default()
+            },
+        ]
+    );
+}
+
+#[test]
+fn trace_records_layers_and_qubits() {
+    let source_map = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Test {
+                    @EntryPoint()
+                    operation Main() : Result {
+                        use qs = Qubit[3];
+                        T(qs[0]);
+                        Rx(1.0, qs[1]);
+                        CCNOT(qs[0], qs[1], qs[2]);
+                        M(qs[2])
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let mut interpreter = Interpreter::new(
+        true,
+        source_map,
+        PackageType::Exe,
+        Profile::Unrestricted.into(),
+        LanguageFeatures::default(),
+    )
+    .expect("compilation should succeed");
+    let mut counter = LogicalCounter::default().with_trace();
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    interpreter
+        .eval_entry_with_sim(&mut counter, &mut out)
+        .expect("evaluation should succeed");
+
+    let trace = counter.trace().expect("tracing should be enabled");
+    assert_eq!(trace.num_qubits, 3);
+    assert_eq!(trace.num_layers, 2);
+    assert_eq!(
+        trace.operations,
+        [
+            TracedOperation {
+                kind: TracedOperationKind::T,
+                layer: 0,
+                qubits: vec![0],
+            },
+            TracedOperation {
+                kind: TracedOperationKind::Rotation,
+                layer: 0,
+                qubits: vec![1],
+            },
+            TracedOperation {
+                kind: TracedOperationKind::Ccz,
+                layer: 1,
+                qubits: vec![0, 1, 2],
+            },
+            TracedOperation {
+                kind: TracedOperationKind::Measurement,
+                layer: 2,
+                qubits: vec![2],
+            },
+        ]
+    );
+    expect![[r#"{"kind":"t","layer":0,"qubits":[0]}"#]].assert_eq(
+        &serde_json::to_string(&trace.operations[0]).expect("serialization should succeed"),
+    );
+}
+
+#[test]
+fn trace_does_not_change_counts() {
+    let source = indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Result[] {
+                use qs = Qubit[3];
+                T(qs[0]);
+                Rx(1.0, qs[1]);
+                let r = M(qs[2]);
+                CCNOT(qs[0], qs[1], qs[2]);
+                Rz(2.0, qs[2]);
+                MResetEachZ(qs) + [r]
+            }
+        }
+    "};
+    let run = |counter: &mut LogicalCounter| {
+        let source_map = SourceMap::new([("test".into(), source.into())], None);
+        let mut interpreter = Interpreter::new(
+            true,
+            source_map,
+            PackageType::Exe,
+            Profile::Unrestricted.into(),
+            LanguageFeatures::default(),
+        )
+        .expect("compilation should succeed");
+        let mut stdout = std::io::sink();
+        let mut out = GenericReceiver::new(&mut stdout);
+        interpreter
+            .eval_entry_with_sim(counter, &mut out)
+            .expect("evaluation should succeed");
+        // measuring a qubit that was never used does not allocate it
+        counter.m(10);
+    };
+
+    let mut counter = LogicalCounter::with_call_stack_depth(2);
+    run(&mut counter);
+    let mut traced_counter = LogicalCounter::with_call_stack_depth(2).with_trace();
+    run(&mut traced_counter);
+
+    assert_eq!(
+        format!("{:?}", traced_counter.logical_resources()),
+        format!("{:?}", counter.logical_resources())
+    );
+    assert_eq!(traced_counter.layer_profile(), counter.layer_profile());
+    assert_eq!(
+        traced_counter.call_stack_profile(),
+        counter.call_stack_profile()
+    );
+}
+
+#[test]
+fn trace_is_disabled_by_default() {
+    assert!(LogicalCounter::default().trace().is_none());
+}
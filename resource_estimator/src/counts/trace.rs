@@ -0,0 +1,46 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use serde::Serialize;
+
+/// Kind of a logical operation in a trace
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub enum TracedOperationKind {
+    T,
+    Rotation,
+    Ccz,
+    Measurement,
+}
+
+/// A logical operation together with the layer in which it is scheduled and
+/// the qubits it acts on
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TracedOperation {
+    pub kind: TracedOperationKind,
+    pub layer: usize,
+    pub qubits: Vec<usize>,
+}
+
+/// All non-Clifford operations and measurements in the order in which they
+/// were executed
+///
+/// Operations in regions that are cached or repeated with
+/// `RepeatEstimates` are traced only for their explicit execution, and
+/// operations accounted for with `AccountForEstimates` are not traced.
+/// Measurements are not scheduled into layers; their layer is the layer of
+/// the next operation on the measured qubit.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct LogicalTrace {
+    pub num_qubits: usize,
+    pub num_layers: usize,
+    pub operations: Vec<TracedOperation>,
+}
+
+impl LogicalTrace {
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("serializing to json string should succeed")
+    }
+}
@@ -39,8 +39,9 @@ pub enum Error {
     MaxPhysicalQubitsTooSmall,
     /// Constraint-based search only supports one magic state type.
     ///
-    /// ✅ This error cannot be triggered by the system, since only one magic
-    /// state type is supported.
+    /// ✅ This does not contain user data and can be logged
+    /// 🧑‍💻 This indicates a user error, e.g., when CCZ factories are used
+    /// together with constraints or frontier estimation
     #[error("Constraint-based search only supports one magic state type.")]
     #[diagnostic(code("Qsc.Estimates.MultipleMagicStatesNotSupported"))]
     MultipleMagicStatesNotSupported,
@@ -315,6 +315,17 @@ impl<P: Clone> RoundBasedFactory<P> {
             .map(|round| round.code_parameter.as_ref())
             .collect()
     }
+
+    /// Returns the factory with the code parameter of each round mapped by
+    /// `f`, e.g., to report code parameters in a different form; all other
+    /// properties of the factory are unchanged.
+    #[must_use]
+    pub fn map_code_parameters(mut self, f: impl Fn(&P) -> P) -> Self {
+        for round in &mut self.rounds {
+            round.code_parameter = round.code_parameter.as_ref().map(&f);
+        }
+        self
+    }
 }
 
 impl<P: Clone> Factory for RoundBasedFactory<P> {
@@ -56,6 +56,15 @@ impl<E: ErrorCorrection> LogicalPatch<E> {
         &self.code_parameter
     }
 
+    /// Returns the patch with its code parameter mapped by `f`, e.g., to
+    /// report the code parameter in a different form; all other properties
+    /// of the patch are unchanged.
+    #[must_use]
+    pub fn map_code_parameter(mut self, f: impl FnOnce(&E::Parameter) -> E::Parameter) -> Self {
+        self.code_parameter = f(&self.code_parameter);
+        self
+    }
+
     /// Returns the number of logical qubits in the patch.
     pub fn logical_qubits(&self) -> u64 {
         self.logical_qubits
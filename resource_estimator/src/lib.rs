@@ -17,6 +17,8 @@ pub mod counts;
 /// Provides traits to define a fault-tolerant quantum computing architecture
 /// and functions to perform resource estimation on such architectures.
 pub mod estimates;
+/// Replays QIR programs into a backend to count logical resources.
+pub mod qir;
 /// Models a fault-tolerant quantum computing architecture based on
 /// customizaable gate-based and Majorana qubits, planar codes, and T-factories.
 pub mod system;
@@ -26,7 +28,10 @@ pub use system::estimate_physical_resources_from_json;
 use counts::LogicalCounter;
 use miette::Diagnostic;
 use qsc::interpret::{self, GenericReceiver, Interpreter};
-use system::estimate_physical_resources;
+use system::{
+    estimate_physical_resources_batch, estimate_physical_resources_with_profile,
+    is_batch_specification, max_call_stack_depth,
+};
 use thiserror::Error;
 
 #[derive(Debug, Diagnostic, Error)]
@@ -34,18 +39,18 @@ use thiserror::Error;
 #[diagnostic(transparent)]
 pub enum Error {
     Interpreter(interpret::Error),
+    Qir(qir::Error),
     Estimation(system::Error),
 }
 
 pub fn estimate_entry(interpreter: &mut Interpreter, params: &str) -> Result<String, Vec<Error>> {
-    let mut counter = LogicalCounter::default();
+    let mut counter = new_counter(params);
     let mut stdout = std::io::sink();
     let mut out = GenericReceiver::new(&mut stdout);
     interpreter
         .eval_entry_with_sim(&mut counter, &mut out)
         .map_err(|e| e.into_iter().map(Error::Interpreter).collect::<Vec<_>>())?;
-    estimate_physical_resources(counter.logical_resources(), params)
-        .map_err(|e| vec![Error::Estimation(e)])
+    estimate_counts(&counter, params).map_err(|e| vec![Error::Estimation(e)])
 }
 
 pub fn estimate_expr(
@@ -53,13 +58,91 @@ pub fn estimate_expr(
     expr: &str,
     params: &str,
 ) -> Result<String, Vec<Error>> {
-    let mut counter = LogicalCounter::default();
+    let mut counter = new_counter(params);
     let mut stdout = std::io::sink();
     let mut out = GenericReceiver::new(&mut stdout);
     interpreter
         .run_with_sim(&mut counter, &mut out, expr)
         .map_err(|e| e.into_iter().map(Error::Interpreter).collect::<Vec<_>>())?
         .map_err(|e| vec![Error::Interpreter(e[0].clone())])?;
-    estimate_physical_resources(counter.logical_resources(), params)
-        .map_err(|e| vec![Error::Estimation(e)])
+    estimate_counts(&counter, params).map_err(|e| vec![Error::Estimation(e)])
+}
+
+/// Estimates physical resources for the entry point of a QIR program in the
+/// textual LLVM format.
+pub fn estimate_qir(qir: &str, params: &str) -> Result<String, Vec<Error>> {
+    let mut counter = new_counter(params);
+    qir::replay(qir, &mut counter).map_err(|e| vec![Error::Qir(e)])?;
+    estimate_counts(&counter, params).map_err(|e| vec![Error::Estimation(e)])
+}
+
+/// Runs the entry point and returns the trace of all non-Clifford operations
+/// and measurements, with their layers and qubits, as JSON.
+pub fn trace_entry(interpreter: &mut Interpreter) -> Result<String, Vec<Error>> {
+    let mut counter = LogicalCounter::default().with_trace();
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    interpreter
+        .eval_entry_with_sim(&mut counter, &mut out)
+        .map_err(|e| e.into_iter().map(Error::Interpreter).collect::<Vec<_>>())?;
+    Ok(trace_json(&counter))
+}
+
+/// Evaluates `expr` and returns the trace of all non-Clifford operations and
+/// measurements, with their layers and qubits, as JSON.
+pub fn trace_expr(interpreter: &mut Interpreter, expr: &str) -> Result<String, Vec<Error>> {
+    let mut counter = LogicalCounter::default().with_trace();
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    interpreter
+        .run_with_sim(&mut counter, &mut out, expr)
+        .map_err(|e| e.into_iter().map(Error::Interpreter).collect::<Vec<_>>())?
+        .map_err(|e| vec![Error::Interpreter(e[0].clone())])?;
+    Ok(trace_json(&counter))
+}
+
+/// Replays the entry point of a QIR program in the textual LLVM format and
+/// returns the trace of all non-Clifford operations and measurements, with
+/// their layers and qubits, as JSON.
+pub fn trace_qir(qir: &str) -> Result<String, Vec<Error>> {
+    let mut counter = LogicalCounter::default().with_trace();
+    qir::replay(qir, &mut counter).map_err(|e| vec![Error::Qir(e)])?;
+    Ok(trace_json(&counter))
+}
+
+fn trace_json(counter: &LogicalCounter) -> String {
+    counter
+        .trace()
+        .expect("tracing should be enabled")
+        .to_json()
+}
+
+/// Estimates physical resources for the logical resources in `counter`,
+/// either for an array of job parameters or for a batch specification.
+fn estimate_counts(counter: &LogicalCounter, params: &str) -> Result<String, system::Error> {
+    let logical_resources = counter.logical_resources();
+    let call_stack_profile = counter.call_stack_profile();
+    let layer_profile = counter.layer_profile();
+    if is_batch_specification(params) {
+        estimate_physical_resources_batch(
+            &logical_resources,
+            call_stack_profile.as_ref(),
+            Some(&layer_profile),
+            params,
+        )
+    } else {
+        estimate_physical_resources_with_profile(
+            logical_resources,
+            call_stack_profile.as_ref(),
+            Some(&layer_profile),
+            params,
+        )
+    }
+}
+
+fn new_counter(params: &str) -> LogicalCounter {
+    match max_call_stack_depth(params) {
+        Some(depth) => LogicalCounter::with_call_stack_depth(depth),
+        None => LogicalCounter::default(),
+    }
 }
@@ -0,0 +1,820 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use qsc::Backend;
+use rustc_hash::FxHashMap;
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error, PartialEq, Eq)]
+pub enum Error {
+    /// The program has no function with the `entry_point` attribute
+    ///
+    /// ✅ This does not contain user data and can be logged
+    /// 🧑‍💻 This indicates a user error
+    #[error("QIR program has no entry point")]
+    #[diagnostic(code("Qsc.Estimates.QirError.MissingEntryPoint"))]
+    MissingEntryPoint,
+    /// The entry point contains an instruction outside of the supported
+    /// subset of LLVM
+    ///
+    /// ❌ This may contain user data and cannot be logged
+    /// 🧑‍💻 This indicates a user error
+    #[error("unsupported instruction in line {0}: '{1}'")]
+    #[diagnostic(code("Qsc.Estimates.QirError.UnsupportedInstruction"))]
+    UnsupportedInstruction(usize, String),
+    /// The entry point calls a function that is neither a supported quantum
+    /// instruction nor a runtime function
+    ///
+    /// ❌ This may contain user data and cannot be logged
+    /// 🧑‍💻 This indicates a user error
+    #[error("unsupported call to '{1}' in line {0}")]
+    #[diagnostic(code("Qsc.Estimates.QirError.UnsupportedCall"))]
+    UnsupportedCall(usize, String),
+    /// An operand is malformed, undefined, or of an unexpected type
+    ///
+    /// ❌ This may contain user data and cannot be logged
+    /// 🧑‍💻 This indicates a user error
+    #[error("invalid operand in line {0}: '{1}'")]
+    #[diagnostic(code("Qsc.Estimates.QirError.InvalidOperand"))]
+    InvalidOperand(usize, String),
+    /// A branch targets a label that is not defined in the entry point
+    ///
+    /// ❌ This may contain user data and cannot be logged
+    /// 🧑‍💻 This indicates a user error
+    #[error("undefined label '{1}' in line {0}")]
+    #[diagnostic(code("Qsc.Estimates.QirError.UndefinedLabel"))]
+    UndefinedLabel(usize, String),
+}
+
+/// Executes the entry point of a QIR program in the textual LLVM format on
+/// `backend`
+///
+/// The supported subset of LLVM covers programs of the base and adaptive
+/// profiles: calls to `__quantum__qis__*` instructions, qubit allocation and
+/// release, reading measurement results, branches, and returns, as well as
+/// integer and floating-point arithmetic, comparisons, `select`, casts, and
+/// `phi` nodes on registers.  Calls to all other `__quantum__rt__*`
+/// functions, such as output recording, are ignored.  Qubits and results are
+/// given either as constant pointers or as values returned from qubit
+/// allocation, and rotation angles either as constants or as registers.
+/// Branches on measurement results follow the results returned by `backend`.
+pub fn replay(qir: &str, backend: &mut impl Backend<ResultType = bool>) -> Result<(), Error> {
+    let blocks = entry_point_blocks(qir)?;
+    Replay::new(backend).run(&blocks)
+}
+
+/// An instruction together with its line number
+type Line<'a> = (usize, &'a str);
+
+/// A basic block with its label and instructions
+type Block<'a> = (&'a str, Vec<Line<'a>>);
+
+/// Returns the basic blocks of the function that is marked as entry point.
+/// If the program has a single function definition, it is the entry point.
+fn entry_point_blocks(qir: &str) -> Result<Vec<Block<'_>>, Error> {
+    let lines = qir
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, strip_comment(line).trim()))
+        .collect::<Vec<_>>();
+
+    let entry_point_attributes = lines
+        .iter()
+        .filter_map(|(_, line)| line.strip_prefix("attributes "))
+        .filter(|attributes| attributes.contains("\"entry_point\""))
+        .filter_map(|attributes| attributes.split_whitespace().next())
+        .collect::<Vec<_>>();
+
+    let definitions = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, line))| line.starts_with("define "))
+        .collect::<Vec<_>>();
+
+    let start = definitions
+        .iter()
+        .find(|(_, (_, line))| {
+            line.trim_end_matches('{')
+                .split_whitespace()
+                .any(|token| entry_point_attributes.contains(&token))
+        })
+        .or(match definitions.as_slice() {
+            [definition] => Some(definition),
+            _ => None,
+        })
+        .map(|(index, _)| index + 1)
+        .ok_or(Error::MissingEntryPoint)?;
+
+    let mut blocks: Vec<Block> = vec![];
+    for &(line_number, line) in &lines[start..] {
+        if line == "}" {
+            break;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(label) = line.strip_suffix(':') {
+            blocks.push((label, vec![]));
+        } else {
+            if blocks.is_empty() {
+                // the entry block does not need to be labeled
+                blocks.push(("", vec![]));
+            }
+            blocks
+                .last_mut()
+                .expect("there should be a current block")
+                .1
+                .push((line_number, line));
+        }
+    }
+
+    Ok(blocks)
+}
+
+fn strip_comment(line: &str) -> &str {
+    line.split_once(';').map_or(line, |(code, _)| code)
+}
+
+/// Values of SSA registers
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Value {
+    Bool(bool),
+    Int(i64),
+    Double(f64),
+    Qubit(usize),
+}
+
+impl Value {
+    fn as_bool(self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an integer, where `i1` values are 0 or 1
+    fn as_int(self) -> Option<i64> {
+        match self {
+            Self::Bool(value) => Some(value.into()),
+            Self::Int(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_double(self) -> Option<f64> {
+        match self {
+            Self::Double(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+struct Replay<'a, B> {
+    backend: &'a mut B,
+    /// Backend qubits for constant qubit pointers
+    static_qubits: Vec<usize>,
+    /// Measurement results by constant result pointer
+    results: FxHashMap<usize, bool>,
+    /// Values of SSA registers by name
+    registers: FxHashMap<String, Value>,
+}
+
+impl<'a, B: Backend<ResultType = bool>> Replay<'a, B> {
+    fn new(backend: &'a mut B) -> Self {
+        Self {
+            backend,
+            static_qubits: vec![],
+            results: FxHashMap::default(),
+            registers: FxHashMap::default(),
+        }
+    }
+
+    fn run(&mut self, blocks: &[Block]) -> Result<(), Error> {
+        let mut current = 0;
+        let mut predecessor = None;
+        'blocks: while let Some((name, instructions)) = blocks.get(current) {
+            // The `phi` nodes at the start of a block all read the registers
+            // as they were when leaving the predecessor
+            let num_phis = instructions
+                .iter()
+                .take_while(|(_, instruction)| phi_node(instruction).is_some())
+                .count();
+            let phi_values = instructions[..num_phis]
+                .iter()
+                .map(|&(line_number, instruction)| self.phi(line_number, instruction, predecessor))
+                .collect::<Result<Vec<_>, _>>()?;
+            for (register, value) in phi_values {
+                self.registers.insert(register.to_string(), value);
+            }
+
+            for &(line_number, instruction) in &instructions[num_phis..] {
+                if instruction.starts_with("ret") {
+                    return Ok(());
+                }
+                if let Some(branch) = instruction.strip_prefix("br ") {
+                    let label = self.branch_target(line_number, branch)?;
+                    current = blocks
+                        .iter()
+                        .position(|(name, _)| *name == label)
+                        .ok_or_else(|| Error::UndefinedLabel(line_number, label.to_string()))?;
+                    predecessor = Some(*name);
+                    continue 'blocks;
+                }
+                self.execute(line_number, instruction)?;
+            }
+            // fall through to the next block
+            predecessor = Some(*name);
+            current += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the register and the value of a `phi` node for the block that
+    /// was left from `predecessor`
+    fn phi<'b>(
+        &self,
+        line_number: usize,
+        instruction: &'b str,
+        predecessor: Option<&str>,
+    ) -> Result<(&'b str, Value), Error> {
+        let unsupported = || Error::UnsupportedInstruction(line_number, instruction.to_string());
+        let (register, phi) = phi_node(instruction).ok_or_else(unsupported)?;
+        let (ty, incoming) = phi.split_once(' ').ok_or_else(unsupported)?;
+
+        for pair in split_operands(incoming) {
+            let (value, label) = pair
+                .strip_prefix('[')
+                .and_then(|pair| pair.strip_suffix(']'))
+                .and_then(|pair| pair.split_once(','))
+                .ok_or_else(unsupported)?;
+            if label.trim().strip_prefix('%') == predecessor {
+                return Ok((register, self.operand(line_number, ty, value.trim())?));
+            }
+        }
+
+        Err(Error::InvalidOperand(
+            line_number,
+            format!(
+                "no incoming value for '{}'",
+                predecessor.unwrap_or_default()
+            ),
+        ))
+    }
+
+    /// Returns the label of the block that `br` continues with
+    fn branch_target<'b>(&self, line_number: usize, branch: &'b str) -> Result<&'b str, Error> {
+        let operands = split_operands(branch);
+        let label = |operand: &'b str| {
+            operand
+                .strip_prefix("label %")
+                .ok_or_else(|| Error::InvalidOperand(line_number, operand.to_string()))
+        };
+
+        match operands.as_slice() {
+            [target] => label(target),
+            [condition, if_true, if_false] => {
+                let condition = condition
+                    .strip_prefix("i1 ")
+                    .ok_or_else(|| Error::InvalidOperand(line_number, (*condition).to_string()))?;
+                if self.bool_operand(line_number, condition)? {
+                    label(if_true)
+                } else {
+                    label(if_false)
+                }
+            }
+            _ => Err(Error::UnsupportedInstruction(
+                line_number,
+                format!("br {branch}"),
+            )),
+        }
+    }
+
+    /// Executes a call, or evaluates an instruction into a register
+    fn execute(&mut self, line_number: usize, instruction: &str) -> Result<(), Error> {
+        let (register, operation) = match instruction.split_once(" = ") {
+            Some((register, operation)) => (Some(register.trim()), operation.trim()),
+            None => (None, instruction),
+        };
+
+        let unsupported = || Error::UnsupportedInstruction(line_number, instruction.to_string());
+        let call = operation.strip_prefix("tail ").unwrap_or(operation);
+        let Some(call) = call.strip_prefix("call ") else {
+            let register = register.ok_or_else(unsupported)?;
+            let value = self.evaluate(line_number, operation)?;
+            self.registers.insert(register.to_string(), value);
+            return Ok(());
+        };
+        let (name, arguments) = call
+            .split_once('@')
+            .and_then(|(_, callee)| callee.split_once('('))
+            .ok_or_else(unsupported)?;
+        let arguments = arguments
+            .rsplit_once(')')
+            .map(|(arguments, _)| split_operands(arguments))
+            .ok_or_else(unsupported)?;
+
+        if self.apply_gate(line_number, name, &arguments)? {
+            return Ok(());
+        }
+
+        let qubit = |replay: &mut Self, index: usize| {
+            replay.qubit_operand(line_number, argument(line_number, &arguments, index)?)
+        };
+
+        match name {
+            "__quantum__qis__m__body" | "__quantum__qis__mz__body" => {
+                let q = qubit(self, 0)?;
+                let value = self.backend.m(q);
+                self.store_result(line_number, &arguments, value)?;
+            }
+            "__quantum__qis__mresetz__body" => {
+                let q = qubit(self, 0)?;
+                let value = self.backend.mresetz(q);
+                self.store_result(line_number, &arguments, value)?;
+            }
+            "__quantum__qis__reset__body" => {
+                let q = qubit(self, 0)?;
+                self.backend.reset(q);
+            }
+            "__quantum__qis__read_result__body" | "__quantum__rt__read_result" => {
+                let result = pointer_operand(line_number, argument(line_number, &arguments, 0)?)?;
+                let value = self.results.get(&result).copied().unwrap_or_default();
+                self.store_register(line_number, register, Value::Bool(value))?;
+            }
+            "__quantum__rt__qubit_allocate" => {
+                let q = self.backend.qubit_allocate();
+                self.store_register(line_number, register, Value::Qubit(q))?;
+            }
+            "__quantum__rt__qubit_release" => {
+                let q = qubit(self, 0)?;
+                self.backend.qubit_release(q);
+            }
+            _ if name.starts_with("__quantum__rt__") => {}
+            _ => return Err(Error::UnsupportedCall(line_number, name.to_string())),
+        }
+
+        Ok(())
+    }
+
+    /// Returns the value of an instruction that is not a call
+    fn evaluate(&self, line_number: usize, operation: &str) -> Result<Value, Error> {
+        let unsupported = || Error::UnsupportedInstruction(line_number, operation.to_string());
+        let invalid = || Error::InvalidOperand(line_number, operation.to_string());
+        let (opcode, operands) = operation.split_once(' ').ok_or_else(unsupported)?;
+        let operands = skip_flags(operands);
+
+        match opcode {
+            "icmp" | "fcmp" => {
+                let (condition, operands) = operands.split_once(' ').ok_or_else(unsupported)?;
+                let (ty, lhs, rhs) = self.binary_operands(line_number, operands)?;
+                let value = if opcode == "icmp" {
+                    let (lhs, rhs) = (
+                        lhs.as_int().ok_or_else(invalid)?,
+                        rhs.as_int().ok_or_else(invalid)?,
+                    );
+                    compare_ints(condition, lhs, rhs, ty == "i1")
+                } else {
+                    let (lhs, rhs) = (
+                        lhs.as_double().ok_or_else(invalid)?,
+                        rhs.as_double().ok_or_else(invalid)?,
+                    );
+                    compare_doubles(condition, lhs, rhs)
+                };
+                value.map(Value::Bool).ok_or_else(unsupported)
+            }
+            "add" | "sub" | "mul" | "sdiv" | "srem" | "shl" | "ashr" | "and" | "or" | "xor" => {
+                let (_, lhs, rhs) = self.binary_operands(line_number, operands)?;
+                integer_operation(opcode, lhs, rhs).ok_or_else(invalid)
+            }
+            "fadd" | "fsub" | "fmul" | "fdiv" => {
+                let (_, lhs, rhs) = self.binary_operands(line_number, operands)?;
+                let (lhs, rhs) = (
+                    lhs.as_double().ok_or_else(invalid)?,
+                    rhs.as_double().ok_or_else(invalid)?,
+                );
+                Ok(Value::Double(match opcode {
+                    "fadd" => lhs + rhs,
+                    "fsub" => lhs - rhs,
+                    "fmul" => lhs * rhs,
+                    _ => lhs / rhs,
+                }))
+            }
+            "fneg" => {
+                let value = self.typed_operand(line_number, operands)?;
+                Ok(Value::Double(-value.as_double().ok_or_else(invalid)?))
+            }
+            "select" => match split_operands(operands).as_slice() {
+                [condition, if_true, if_false] => {
+                    let condition = self.typed_operand(line_number, condition)?;
+                    if condition.as_bool().ok_or_else(invalid)? {
+                        self.typed_operand(line_number, if_true)
+                    } else {
+                        self.typed_operand(line_number, if_false)
+                    }
+                }
+                _ => Err(unsupported()),
+            },
+            "zext" | "sext" | "trunc" | "sitofp" | "uitofp" | "fptosi" | "fptoui" => {
+                let (operand, ty) = operands.split_once(" to ").ok_or_else(unsupported)?;
+                let value = self.typed_operand(line_number, operand)?;
+                cast(opcode, value, ty.trim()).ok_or_else(invalid)
+            }
+            _ => Err(unsupported()),
+        }
+    }
+
+    /// Returns the type and the values of the operands of a binary
+    /// instruction, such as `i64 %0, 1`
+    fn binary_operands<'b>(
+        &self,
+        line_number: usize,
+        operands: &'b str,
+    ) -> Result<(&'b str, Value, Value), Error> {
+        let invalid = || Error::InvalidOperand(line_number, operands.to_string());
+        let (lhs, rhs) = operands.split_once(',').ok_or_else(invalid)?;
+        let (ty, lhs) = lhs.trim().rsplit_once(' ').ok_or_else(invalid)?;
+        let ty = ty.trim();
+        Ok((
+            ty,
+            self.operand(line_number, ty, lhs)?,
+            self.operand(line_number, ty, rhs.trim())?,
+        ))
+    }
+
+    /// Returns the value of an operand together with its type, such as
+    /// `double %0`
+    fn typed_operand(&self, line_number: usize, operand: &str) -> Result<Value, Error> {
+        let (ty, value) = operand
+            .trim()
+            .rsplit_once(' ')
+            .ok_or_else(|| Error::InvalidOperand(line_number, operand.to_string()))?;
+        self.operand(line_number, ty.trim(), value)
+    }
+
+    /// Returns the value of a register or of a constant of type `ty`
+    fn operand(&self, line_number: usize, ty: &str, value: &str) -> Result<Value, Error> {
+        let invalid = || Error::InvalidOperand(line_number, format!("{ty} {value}"));
+        if value.starts_with('%') {
+            return self.registers.get(value).copied().ok_or_else(invalid);
+        }
+
+        match ty {
+            "i1" => match value {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                _ => Err(invalid()),
+            },
+            "double" => double_constant(value)
+                .map(Value::Double)
+                .ok_or_else(invalid),
+            _ if ty.starts_with('i') => value.parse().map(Value::Int).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Applies the gate `name` and returns `true`, or returns `false` if
+    /// `name` is not a gate
+    fn apply_gate(
+        &mut self,
+        line_number: usize,
+        name: &str,
+        arguments: &[&str],
+    ) -> Result<bool, Error> {
+        let qubit = |replay: &mut Self, index: usize| {
+            replay.qubit_operand(line_number, argument(line_number, arguments, index)?)
+        };
+        let angle = |replay: &Self, index: usize| {
+            replay.angle_operand(line_number, argument(line_number, arguments, index)?)
+        };
+
+        match name {
+            "__quantum__qis__ccx__body" => {
+                let (ctl0, ctl1, q) = (qubit(self, 0)?, qubit(self, 1)?, qubit(self, 2)?);
+                self.backend.ccx(ctl0, ctl1, q);
+            }
+            "__quantum__qis__cx__body" | "__quantum__qis__cnot__body" => {
+                let (ctl, q) = (qubit(self, 0)?, qubit(self, 1)?);
+                self.backend.cx(ctl, q);
+            }
+            "__quantum__qis__cy__body" => {
+                let (ctl, q) = (qubit(self, 0)?, qubit(self, 1)?);
+                self.backend.cy(ctl, q);
+            }
+            "__quantum__qis__cz__body" => {
+                let (ctl, q) = (qubit(self, 0)?, qubit(self, 1)?);
+                self.backend.cz(ctl, q);
+            }
+            "__quantum__qis__swap__body" => {
+                let (q0, q1) = (qubit(self, 0)?, qubit(self, 1)?);
+                self.backend.swap(q0, q1);
+            }
+            "__quantum__qis__h__body" => {
+                let q = qubit(self, 0)?;
+                self.backend.h(q);
+            }
+            "__quantum__qis__s__body" => {
+                let q = qubit(self, 0)?;
+                self.backend.s(q);
+            }
+            "__quantum__qis__s__adj" => {
+                let q = qubit(self, 0)?;
+                self.backend.sadj(q);
+            }
+            "__quantum__qis__t__body" => {
+                let q = qubit(self, 0)?;
+                self.backend.t(q);
+            }
+            "__quantum__qis__t__adj" => {
+                let q = qubit(self, 0)?;
+                self.backend.tadj(q);
+            }
+            "__quantum__qis__x__body" => {
+                let q = qubit(self, 0)?;
+                self.backend.x(q);
+            }
+            "__quantum__qis__y__body" => {
+                let q = qubit(self, 0)?;
+                self.backend.y(q);
+            }
+            "__quantum__qis__z__body" => {
+                let q = qubit(self, 0)?;
+                self.backend.z(q);
+            }
+            "__quantum__qis__rx__body" => {
+                let (theta, q) = (angle(self, 0)?, qubit(self, 1)?);
+                self.backend.rx(theta, q);
+            }
+            "__quantum__qis__ry__body" => {
+                let (theta, q) = (angle(self, 0)?, qubit(self, 1)?);
+                self.backend.ry(theta, q);
+            }
+            "__quantum__qis__rz__body" => {
+                let (theta, q) = (angle(self, 0)?, qubit(self, 1)?);
+                self.backend.rz(theta, q);
+            }
+            "__quantum__qis__rxx__body" => {
+                let (theta, q0, q1) = (angle(self, 0)?, qubit(self, 1)?, qubit(self, 2)?);
+                self.backend.rxx(theta, q0, q1);
+            }
+            "__quantum__qis__ryy__body" => {
+                let (theta, q0, q1) = (angle(self, 0)?, qubit(self, 1)?, qubit(self, 2)?);
+                self.backend.ryy(theta, q0, q1);
+            }
+            "__quantum__qis__rzz__body" => {
+                let (theta, q0, q1) = (angle(self, 0)?, qubit(self, 1)?, qubit(self, 2)?);
+                self.backend.rzz(theta, q0, q1);
+            }
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    fn store_register(
+        &mut self,
+        line_number: usize,
+        register: Option<&str>,
+        value: Value,
+    ) -> Result<(), Error> {
+        let register = register.ok_or_else(|| {
+            Error::InvalidOperand(line_number, "missing result register".to_string())
+        })?;
+        self.registers.insert(register.to_string(), value);
+        Ok(())
+    }
+
+    /// Stores the measurement result `value` for the result pointer in the
+    /// second argument, if present
+    fn store_result(
+        &mut self,
+        line_number: usize,
+        arguments: &[&str],
+        value: bool,
+    ) -> Result<(), Error> {
+        if let Some(result) = arguments.get(1) {
+            let result = pointer_operand(line_number, result)?;
+            self.results.insert(result, value);
+        }
+        Ok(())
+    }
+
+    fn qubit_operand(&mut self, line_number: usize, operand: &str) -> Result<usize, Error> {
+        if let Some(register) = register_name(operand) {
+            return match self.registers.get(register) {
+                Some(Value::Qubit(q)) => Ok(*q),
+                _ => Err(Error::InvalidOperand(line_number, operand.to_string())),
+            };
+        }
+
+        let id = pointer_operand(line_number, operand)?;
+        while self.static_qubits.len() <= id {
+            let q = self.backend.qubit_allocate();
+            self.static_qubits.push(q);
+        }
+        Ok(self.static_qubits[id])
+    }
+
+    /// Returns the value of a `double` operand, which is either a constant or
+    /// a register
+    fn angle_operand(&self, line_number: usize, operand: &str) -> Result<f64, Error> {
+        let invalid = || Error::InvalidOperand(line_number, operand.to_string());
+        let value = operand.strip_prefix("double ").ok_or_else(invalid)?.trim();
+        self.operand(line_number, "double", value)?
+            .as_double()
+            .ok_or_else(invalid)
+    }
+
+    fn bool_operand(&self, line_number: usize, operand: &str) -> Result<bool, Error> {
+        match operand.trim() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            register => match self.registers.get(register) {
+                Some(Value::Bool(value)) => Ok(*value),
+                _ => Err(Error::InvalidOperand(line_number, operand.to_string())),
+            },
+        }
+    }
+}
+
+fn argument<'b>(line_number: usize, arguments: &[&'b str], index: usize) -> Result<&'b str, Error> {
+    arguments.get(index).copied().ok_or_else(|| {
+        Error::InvalidOperand(line_number, format!("missing argument {}", index + 1))
+    })
+}
+
+/// Returns the name of the register, if the typed operand is a register
+fn register_name(operand: &str) -> Option<&str> {
+    operand
+        .rsplit(' ')
+        .next()
+        .filter(|value| value.starts_with('%') && !value.ends_with(['*', ')']))
+}
+
+/// Returns the integer value of a constant pointer, which is either `null`
+/// or converted from an integer with `inttoptr`
+fn pointer_operand(line_number: usize, operand: &str) -> Result<usize, Error> {
+    if operand.ends_with(" null") {
+        return Ok(0);
+    }
+
+    operand
+        .split_once("inttoptr")
+        .and_then(|(_, cast)| cast.trim().strip_prefix('('))
+        .and_then(|cast| cast.trim().strip_prefix("i64 "))
+        .and_then(|cast| cast.split_whitespace().next())
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| Error::InvalidOperand(line_number, operand.to_string()))
+}
+
+/// Returns the value of a `double` constant, given either in decimal or in
+/// the hexadecimal notation of its bits
+fn double_constant(value: &str) -> Option<f64> {
+    if let Some(bits) = value.strip_prefix("0x") {
+        u64::from_str_radix(bits, 16).ok().map(f64::from_bits)
+    } else {
+        value.parse().ok()
+    }
+}
+
+/// Returns the register and the incoming values of a `phi` node
+fn phi_node(instruction: &str) -> Option<(&str, &str)> {
+    let (register, operation) = instruction.split_once(" = ")?;
+    Some((register.trim(), operation.trim().strip_prefix("phi ")?))
+}
+
+/// Skips the flags of an instruction, such as `nsw` or the fast-math flags,
+/// which do not change its value for the replay
+fn skip_flags(operands: &str) -> &str {
+    const FLAGS: [&str; 11] = [
+        "nuw", "nsw", "exact", "fast", "nnan", "ninf", "nsz", "arcp", "contract", "afn", "reassoc",
+    ];
+    let mut operands = operands.trim_start();
+    while let Some((flag, rest)) = operands.split_once(' ') {
+        if !FLAGS.contains(&flag) {
+            break;
+        }
+        operands = rest.trim_start();
+    }
+    operands
+}
+
+/// Returns the result of the integer instruction `opcode`, where `and`, `or`,
+/// and `xor` on `i1` values are logical operations
+fn integer_operation(opcode: &str, lhs: Value, rhs: Value) -> Option<Value> {
+    if let (Value::Bool(lhs), Value::Bool(rhs)) = (lhs, rhs) {
+        return match opcode {
+            "and" => Some(Value::Bool(lhs && rhs)),
+            "or" => Some(Value::Bool(lhs || rhs)),
+            "xor" => Some(Value::Bool(lhs != rhs)),
+            _ => None,
+        };
+    }
+
+    let (lhs, rhs) = (lhs.as_int()?, rhs.as_int()?);
+    let shift = || u32::try_from(rhs).ok().filter(|&shift| shift < 64);
+    let value = match opcode {
+        "add" => lhs.wrapping_add(rhs),
+        "sub" => lhs.wrapping_sub(rhs),
+        "mul" => lhs.wrapping_mul(rhs),
+        "sdiv" => lhs.checked_div(rhs)?,
+        "srem" => lhs.checked_rem(rhs)?,
+        "shl" => lhs << shift()?,
+        "ashr" => lhs >> shift()?,
+        "and" => lhs & rhs,
+        "or" => lhs | rhs,
+        "xor" => lhs ^ rhs,
+        _ => return None,
+    };
+    Some(Value::Int(value))
+}
+
+/// Returns the result of the cast instruction `opcode` of `value` to `ty`
+fn cast(opcode: &str, value: Value, ty: &str) -> Option<Value> {
+    match (opcode, value) {
+        ("sext", Value::Bool(value)) => Some(Value::Int(-i64::from(value))),
+        ("trunc", Value::Int(value)) if ty == "i1" => Some(Value::Bool(value & 1 == 1)),
+        ("zext" | "sext" | "trunc", value) => value.as_int().map(Value::Int),
+        ("sitofp", value) => value.as_int().map(|value| Value::Double(value as f64)),
+        ("uitofp", value) => value
+            .as_int()
+            .map(|value| Value::Double(value as u64 as f64)),
+        ("fptosi" | "fptoui", value) => value.as_double().map(|value| Value::Int(value as i64)),
+        _ => None,
+    }
+}
+
+/// Returns the result of `icmp` with `condition`, where `i1` values are
+/// signed as -1 and 0 in signed comparisons
+fn compare_ints(condition: &str, lhs: i64, rhs: i64, is_i1: bool) -> Option<bool> {
+    let signed = |value: i64| if is_i1 { -value } else { value };
+    let unsigned = |value: i64| value as u64;
+    Some(match condition {
+        "eq" => lhs == rhs,
+        "ne" => lhs != rhs,
+        "sgt" => signed(lhs) > signed(rhs),
+        "sge" => signed(lhs) >= signed(rhs),
+        "slt" => signed(lhs) < signed(rhs),
+        "sle" => signed(lhs) <= signed(rhs),
+        "ugt" => unsigned(lhs) > unsigned(rhs),
+        "uge" => unsigned(lhs) >= unsigned(rhs),
+        "ult" => unsigned(lhs) < unsigned(rhs),
+        "ule" => unsigned(lhs) <= unsigned(rhs),
+        _ => return None,
+    })
+}
+
+/// Returns the result of `fcmp` with `condition`, where ordered conditions
+/// are false and unordered conditions are true if an operand is NaN
+#[allow(clippy::float_cmp)]
+fn compare_doubles(condition: &str, lhs: f64, rhs: f64) -> Option<bool> {
+    let unordered = lhs.is_nan() || rhs.is_nan();
+    let (ordered, compare) = match condition {
+        "false" => return Some(false),
+        "true" => return Some(true),
+        "ord" => return Some(!unordered),
+        "uno" => return Some(unordered),
+        _ => (condition.get(..1)?, condition.get(1..)?),
+    };
+    let value = match compare {
+        "eq" => lhs == rhs,
+        "ne" => lhs != rhs,
+        "gt" => lhs > rhs,
+        "ge" => lhs >= rhs,
+        "lt" => lhs < rhs,
+        "le" => lhs <= rhs,
+        _ => return None,
+    };
+    match ordered {
+        "o" => Some(!unordered && value),
+        "u" => Some(unordered || value),
+        _ => None,
+    }
+}
+
+/// Splits operands at commas that are not nested in parentheses or brackets
+fn split_operands(operands: &str) -> Vec<&str> {
+    let mut result = vec![];
+    let mut depth = 0_usize;
+    let mut start = 0;
+    for (index, c) in operands.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                result.push(operands[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = operands[start..].trim();
+    if !last.is_empty() {
+        result.push(last);
+    }
+    result
+}
@@ -0,0 +1,283 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc::{
+    interpret::{GenericReceiver, Interpreter},
+    LanguageFeatures, PackageType, SourceMap, TargetCapabilityFlags,
+};
+
+use crate::counts::LogicalCounter;
+
+use super::{replay, Error};
+
+fn verify_logical_counts(qir: &str, expect: &Expect) {
+    let mut counter = LogicalCounter::default();
+    replay(qir, &mut counter).expect("replay should succeed");
+    expect.assert_debug_eq(&counter.logical_resources());
+}
+
+#[test]
+fn base_profile_gates_are_counted() {
+    verify_logical_counts(
+        indoc! {r#"
+            %Result = type opaque
+            %Qubit = type opaque
+
+            define void @ENTRYPOINT__main() #0 {
+            block_0:
+              call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+              call void @__quantum__qis__t__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+              call void @__quantum__qis__ccx__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Qubit* inttoptr (i64 1 to %Qubit*), %Qubit* inttoptr (i64 2 to %Qubit*))
+              call void @__quantum__qis__rz__body(double 1.000000e-01, %Qubit* inttoptr (i64 1 to %Qubit*))
+              call void @__quantum__qis__rx__body(double 0x3FE921FB54442D18, %Qubit* inttoptr (i64 2 to %Qubit*))
+              call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+              call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Result* inttoptr (i64 1 to %Result*))
+              call void @__quantum__rt__array_record_output(i64 2, i8* null)
+              call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+              call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* null)
+              ret void
+            }
+
+            declare void @__quantum__qis__h__body(%Qubit*)
+
+            attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="base_profile" "required_num_qubits"="3" "required_num_results"="2" }
+        "#},
+        &expect![["
+            LogicalResourceCounts {
+                num_qubits: 3,
+                t_count: 2,
+                rotation_count: 1,
+                rotation_depth: 1,
+                ccz_count: 1,
+                ccix_count: 0,
+                measurement_count: 2,
+            }
+        "]],
+    );
+}
+
+#[test]
+fn adaptive_profile_branches_are_followed() {
+    verify_logical_counts(
+        indoc! {"
+            define void @helper() {
+              ret void
+            }
+
+            define void @main() #0 {
+            entry:
+              call void @__quantum__qis__h__body(ptr null)
+              call void @__quantum__qis__mresetz__body(ptr null, ptr null)
+              %0 = call i1 @__quantum__qis__read_result__body(ptr null)
+              br i1 %0, label %then, label %else
+            then:                                   ; preds = %entry
+              call void @__quantum__qis__t__body(ptr inttoptr (i64 1 to ptr))
+              br label %continue
+            else:                                   ; preds = %entry
+              call void @__quantum__qis__t__adj(ptr inttoptr (i64 1 to ptr))
+              br label %continue
+            continue:
+              ret void
+            }
+
+            attributes #0 = { \"entry_point\" \"qir_profiles\"=\"adaptive_profile\" }
+        "},
+        &expect![["
+            LogicalResourceCounts {
+                num_qubits: 2,
+                t_count: 1,
+                rotation_count: 0,
+                rotation_depth: 0,
+                ccz_count: 0,
+                ccix_count: 0,
+                measurement_count: 1,
+            }
+        "]],
+    );
+}
+
+#[test]
+fn adaptive_profile_registers_are_computed() {
+    // The branch to `wrong` and the incoming value from `block_2` are only
+    // taken if registers are computed incorrectly, and would add a T gate
+    // and a rotation, respectively.
+    verify_logical_counts(
+        indoc! {"
+            define void @main() #0 {
+            block_0:
+              %var_0 = add i64 1, 2
+              %var_1 = icmp sgt i64 %var_0, 2
+              br i1 %var_1, label %block_1, label %block_2
+            block_1:
+              %var_2 = sitofp i64 %var_0 to double
+              %var_3 = fmul double %var_2, 0x3FE921FB54442D18
+              br label %block_3
+            block_2:
+              br label %block_3
+            block_3:
+              %var_4 = phi double [%var_3, %block_1], [0.1, %block_2]
+              call void @__quantum__qis__rz__body(double %var_4, %Qubit* inttoptr (i64 0 to %Qubit*))
+              %var_5 = select i1 %var_1, double 1.000000e-01, double %var_4
+              call void @__quantum__qis__rx__body(double %var_5, %Qubit* inttoptr (i64 1 to %Qubit*))
+              %var_6 = zext i1 %var_1 to i64
+              %var_7 = srem i64 %var_0, 2
+              %var_8 = icmp ne i64 %var_6, %var_7
+              %var_9 = xor i1 %var_8, %var_1
+              %var_10 = and i1 %var_9, false
+              br i1 %var_10, label %wrong, label %block_4
+            wrong:
+              call void @__quantum__qis__t__body(%Qubit* inttoptr (i64 1 to %Qubit*))
+              br label %block_4
+            block_4:
+              ret void
+            }
+
+            attributes #0 = { \"entry_point\" \"qir_profiles\"=\"adaptive_profile\" }
+        "},
+        &expect![["
+            LogicalResourceCounts {
+                num_qubits: 2,
+                t_count: 1,
+                rotation_count: 1,
+                rotation_depth: 1,
+                ccz_count: 0,
+                ccix_count: 0,
+                measurement_count: 0,
+            }
+        "]],
+    );
+}
+
+#[test]
+fn adaptive_qir_from_qirgen_is_replayed() {
+    let source = indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Result[] {
+                use qs = Qubit[2];
+                H(qs[0]);
+                let multiple = if MResetZ(qs[0]) == One { 3.0 } else { 1.0 };
+                Rz(multiple * Microsoft.Quantum.Math.PI() / 4.0, qs[1]);
+                let steps = if MResetZ(qs[1]) == One { 3 } else { 2 };
+                if steps * 2 > 5 {
+                    T(qs[0]);
+                }
+                Rx(multiple / 10.0, qs[0]);
+                [MResetZ(qs[0]), MResetZ(qs[1])]
+            }
+        }
+    "};
+    let capabilities = TargetCapabilityFlags::Adaptive
+        | TargetCapabilityFlags::QubitReset
+        | TargetCapabilityFlags::IntegerComputations
+        | TargetCapabilityFlags::FloatingPointComputations;
+    let mut interpreter = Interpreter::new(
+        true,
+        SourceMap::new([("test".into(), source.into())], None),
+        PackageType::Exe,
+        capabilities,
+        LanguageFeatures::default(),
+    )
+    .expect("compilation should succeed");
+
+    let mut simulated = LogicalCounter::default();
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    interpreter
+        .run_with_sim(&mut simulated, &mut out, "Test.Main()")
+        .expect("compilation should succeed")
+        .expect("evaluation should succeed");
+
+    let qir = interpreter
+        .qirgen("Test.Main()")
+        .expect("QIR generation should succeed");
+    let mut replayed = LogicalCounter::default();
+    replay(&qir, &mut replayed).expect("replay should succeed");
+
+    // Both counters draw the same measurement results, such that the replay
+    // follows the same branches as the simulation
+    assert_eq!(
+        format!("{:?}", replayed.logical_resources()),
+        format!("{:?}", simulated.logical_resources())
+    );
+}
+
+#[test]
+fn dynamic_qubits_are_reused() {
+    verify_logical_counts(
+        indoc! {"
+            define void @main() {
+              %q0 = call %Qubit* @__quantum__rt__qubit_allocate()
+              call void @__quantum__qis__t__body(%Qubit* %q0)
+              call void @__quantum__rt__qubit_release(%Qubit* %q0)
+              %q1 = call %Qubit* @__quantum__rt__qubit_allocate()
+              call void @__quantum__qis__t__body(%Qubit* %q1)
+              ret void
+            }
+        "},
+        &expect![["
+            LogicalResourceCounts {
+                num_qubits: 1,
+                t_count: 2,
+                rotation_count: 0,
+                rotation_depth: 0,
+                ccz_count: 0,
+                ccix_count: 0,
+                measurement_count: 0,
+            }
+        "]],
+    );
+}
+
+#[test]
+fn unsupported_calls_are_rejected() {
+    let qir = indoc! {"
+        define void @main() {
+          call void @__quantum__qis__foo__body(%Qubit* null)
+          ret void
+        }
+    "};
+    let result = replay(qir, &mut LogicalCounter::default());
+    assert_eq!(
+        result,
+        Err(Error::UnsupportedCall(
+            2,
+            "__quantum__qis__foo__body".to_string()
+        ))
+    );
+}
+
+#[test]
+fn entry_point_is_required() {
+    let qir = indoc! {"
+        define void @first() {
+          ret void
+        }
+
+        define void @second() {
+          ret void
+        }
+    "};
+    let result = replay(qir, &mut LogicalCounter::default());
+    assert_eq!(result, Err(Error::MissingEntryPoint));
+}
+
+#[test]
+fn estimate_qir_profiles_call_stack() {
+    let qir = indoc! {r#"
+        define void @main() #0 {
+          call void @__quantum__qis__t__body(%Qubit* null)
+          call void @__quantum__qis__mz__body(%Qubit* null, %Result* null)
+          ret void
+        }
+
+        attributes #0 = { "entry_point" }
+    "#};
+    let result = crate::estimate_qir(qir, r#"[{"profiling": {"callStackDepth": 1}}]"#)
+        .expect("estimation should succeed");
+    let result: serde_json::Value =
+        serde_json::from_str(&result).expect("result should be valid JSON");
+    assert!(result[0]["callStackBreakdown"].is_array());
+}
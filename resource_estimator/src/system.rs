@@ -23,11 +23,16 @@ use crate::estimates::{Overhead, PhysicalResourceEstimation};
 use std::rc::Rc;
 
 pub use self::modeling::{GateBasedPhysicalQubit, MajoranaQubit, PhysicalQubit, Protocol};
+use self::modeling::{LayoutOverhead, MagicStateType};
 use self::optimization::TFactoryBuilder;
-pub use self::{data::LogicalResourceCounts, error::Error};
-use data::{EstimateType, JobParams};
+pub use self::{
+    data::{CallStackCounts, CallStackProfile, LayerCounts, LayerProfile, LogicalResourceCounts},
+    error::Error,
+};
+use data::{pareto_frontier, BatchSpecification, EstimateType, JobParams, ParetoPoint};
 pub use data::{LayoutReportData, PartitioningOverhead};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 pub(crate) type Result<T> = std::result::Result<T, error::Error>;
 
@@ -37,7 +42,11 @@ pub fn estimate_physical_resources_from_json(
 ) -> std::result::Result<String, Error> {
     let logical_resources: LogicalResourceCounts = serde_json::from_str(logical_resources)
         .map_err(|e| error::Error::IO(error::IO::CannotParseJSON(e)))?;
-    estimate_physical_resources(logical_resources, params)
+    if is_batch_specification(params) {
+        estimate_physical_resources_batch(&logical_resources, None, None, params)
+    } else {
+        estimate_physical_resources(logical_resources, params)
+    }
 }
 
 pub fn estimate_physical_resources<
@@ -46,20 +55,39 @@ pub fn estimate_physical_resources<
     logical_resources: L,
     params: &str,
 ) -> Result<String> {
-    let job_params_array = if params.is_empty() {
-        vec![JobParams::default()]
-    } else {
-        serde_json::from_str(params).map_err(|e| error::Error::IO(error::IO::CannotParseJSON(e)))?
-    };
+    estimate_physical_resources_with_profile(logical_resources, None, None, params)
+}
+
+/// Estimates physical resources as `estimate_physical_resources` does, and
+/// additionally reports the per-callable breakdown of `call_stack_profile`
+/// for all job parameters that request a call-stack depth for profiling, and
+/// the space-time diagram of `layer_profile` for all job parameters that
+/// request space-time windows.
+pub fn estimate_physical_resources_with_profile<
+    L: Overhead + LayoutReportData + PartitioningOverhead + Serialize,
+>(
+    logical_resources: L,
+    call_stack_profile: Option<&CallStackProfile>,
+    layer_profile: Option<&LayerProfile>,
+    params: &str,
+) -> Result<String> {
+    let job_params_array = parse_job_params(params)?;
 
     let mut results: Vec<String> = Vec::with_capacity(job_params_array.len());
     let logical_resources = Rc::new(logical_resources);
     for job_params in job_params_array {
-        let result = estimate_single(logical_resources.clone(), job_params);
-        match result {
-            Ok(result) => results.push(
-                serde_json::to_string(&result).expect("serializing to json string should succeed"),
-            ),
+        match estimate_single_with_profile(
+            logical_resources.clone(),
+            call_stack_profile,
+            layer_profile,
+            job_params,
+        ) {
+            Ok(result) => {
+                results.push(
+                    serde_json::to_string(&result)
+                        .expect("serializing to json string should succeed"),
+                );
+            }
             Err(err) => {
                 results.push(serialize_error(err));
             }
@@ -69,26 +97,248 @@ pub fn estimate_physical_resources<
     Ok(format!("[{}]", results.join(",")))
 }
 
+/// Parses job parameters that are given either as an array or as a single
+/// JSON object; empty parameters stand for the default job parameters.
+fn parse_job_params(params: &str) -> Result<Vec<JobParams>> {
+    if params.is_empty() {
+        return Ok(vec![JobParams::default()]);
+    }
+    if params.trim_start().starts_with('{') {
+        serde_json::from_str(params).map(|job_params| vec![job_params])
+    } else {
+        serde_json::from_str(params)
+    }
+    .map_err(|e| error::Error::IO(error::IO::CannotParseJSON(e)))
+}
+
+/// Returns true, if `params` is a batch specification, i.e., a JSON object
+/// with any of the keys `base`, `items`, or `grid`.
+#[must_use]
+pub fn is_batch_specification(params: &str) -> bool {
+    serde_json::from_str::<Map<String, Value>>(params).is_ok_and(|params| {
+        ["base", "items", "grid"]
+            .iter()
+            .any(|key| params.contains_key(*key))
+    })
+}
+
+/// Estimates physical resources for all points of the batch specification
+/// `batch`, which are expanded from a base, items, and a grid of job
+/// parameters.  The points are estimated in parallel on native targets.
+///
+/// The result contains the results of all points in the order of the batch,
+/// and the Pareto frontier of physical qubits and runtime across all
+/// successful results, including all entries of frontier estimates.
+pub fn estimate_physical_resources_batch<
+    L: Overhead + LayoutReportData + PartitioningOverhead + Serialize + Clone + Sync,
+>(
+    logical_resources: &L,
+    call_stack_profile: Option<&CallStackProfile>,
+    layer_profile: Option<&LayerProfile>,
+    batch: &str,
+) -> Result<String> {
+    let batch: BatchSpecification =
+        serde_json::from_str(batch).map_err(|e| error::Error::IO(error::IO::CannotParseJSON(e)))?;
+
+    let estimates = estimate_points(
+        logical_resources,
+        call_stack_profile,
+        layer_profile,
+        &batch.points(),
+    );
+
+    let mut results: Vec<String> = Vec::with_capacity(estimates.len());
+    let mut points = vec![];
+    for (result, result_points) in estimates {
+        results.push(result);
+        points.extend(result_points);
+    }
+
+    Ok(format!(
+        r#"{{"results":[{}],"paretoFrontier":{}}}"#,
+        results.join(","),
+        serde_json::to_string(&pareto_frontier(points))
+            .expect("serializing to json string should succeed")
+    ))
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn estimate_points<
+    L: Overhead + LayoutReportData + PartitioningOverhead + Serialize + Clone + Sync,
+>(
+    logical_resources: &L,
+    call_stack_profile: Option<&CallStackProfile>,
+    layer_profile: Option<&LayerProfile>,
+    points: &[Value],
+) -> Vec<(String, Vec<ParetoPoint>)> {
+    let num_threads = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+    let chunk_size = points.len().div_ceil(num_threads).max(1);
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = points
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(chunk_index, chunk)| {
+                scope.spawn(move || {
+                    let logical_resources = Rc::new(logical_resources.clone());
+                    chunk
+                        .iter()
+                        .enumerate()
+                        .map(|(offset, point)| {
+                            estimate_point(
+                                logical_resources.clone(),
+                                call_stack_profile,
+                                layer_profile,
+                                chunk_index * chunk_size + offset,
+                                point,
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("estimation thread should not panic"))
+            .collect()
+    })
+}
+
+#[cfg(target_family = "wasm")]
+fn estimate_points<
+    L: Overhead + LayoutReportData + PartitioningOverhead + Serialize + Clone + Sync,
+>(
+    logical_resources: &L,
+    call_stack_profile: Option<&CallStackProfile>,
+    layer_profile: Option<&LayerProfile>,
+    points: &[Value],
+) -> Vec<(String, Vec<ParetoPoint>)> {
+    let logical_resources = Rc::new(logical_resources.clone());
+    points
+        .iter()
+        .enumerate()
+        .map(|(index, point)| {
+            estimate_point(
+                logical_resources.clone(),
+                call_stack_profile,
+                layer_profile,
+                index,
+                point,
+            )
+        })
+        .collect()
+}
+
+/// Estimates the point with index `index` in a batch and returns the
+/// serialized result together with its Pareto points.
+fn estimate_point<L: Overhead + LayoutReportData + PartitioningOverhead + Serialize>(
+    logical_resources: Rc<L>,
+    call_stack_profile: Option<&CallStackProfile>,
+    layer_profile: Option<&LayerProfile>,
+    index: usize,
+    point: &Value,
+) -> (String, Vec<ParetoPoint>) {
+    let result = JobParams::deserialize(point)
+        .map_err(|e| error::Error::IO(error::IO::CannotParseJSON(e)))
+        .and_then(|job_params| {
+            estimate_single_with_profile(
+                logical_resources,
+                call_stack_profile,
+                layer_profile,
+                job_params,
+            )
+        });
+
+    match result {
+        Ok(result) => (
+            serde_json::to_string(&result).expect("serializing to json string should succeed"),
+            result.pareto_points(index),
+        ),
+        Err(err) => (serialize_error(err), vec![]),
+    }
+}
+
+/// Returns the largest call-stack depth that any of the job parameters in
+/// `params` requests for profiling, or `None` if no profiling is requested.
+#[must_use]
+pub fn max_call_stack_depth(params: &str) -> Option<usize> {
+    let job_params_array: Vec<JobParams> = if is_batch_specification(params) {
+        serde_json::from_str::<BatchSpecification>(params)
+            .ok()?
+            .points()
+            .iter()
+            .filter_map(|point| JobParams::deserialize(point).ok())
+            .collect()
+    } else {
+        parse_job_params(params).ok()?
+    };
+    job_params_array
+        .iter()
+        .filter_map(|job_params| job_params.profiling().call_stack_depth)
+        .max()
+}
+
+fn estimate_single_with_profile<
+    L: Overhead + LayoutReportData + PartitioningOverhead + Serialize,
+>(
+    logical_resources: Rc<L>,
+    call_stack_profile: Option<&CallStackProfile>,
+    layer_profile: Option<&LayerProfile>,
+    job_params: JobParams,
+) -> Result<data::Success<LayoutOverhead<L>>> {
+    let call_stack_depth = job_params.profiling().call_stack_depth;
+    let space_time_windows = job_params.profiling().space_time_windows;
+    let mut result = estimate_single(logical_resources, job_params)?;
+    if let (Some(profile), Some(depth)) = (call_stack_profile, call_stack_depth) {
+        result.set_call_stack_profile(profile, depth);
+    }
+    if let (Some(profile), Some(num_windows)) = (layer_profile, space_time_windows) {
+        result.set_layer_profile(profile, num_windows);
+    }
+    Ok(result)
+}
+
 fn estimate_single<L: Overhead + LayoutReportData + PartitioningOverhead + Serialize>(
     logical_resources: Rc<L>,
     mut job_params: JobParams,
-) -> Result<data::Success<L>> {
+) -> Result<data::Success<LayoutOverhead<L>>> {
     let qubit = job_params.qubit_params().clone();
 
-    let ftp = Protocol::load_from_specification(job_params.qec_scheme_mut(), &qubit)?;
     let distillation_unit_templates = job_params
         .distillation_unit_specifications()
         .as_templates()?;
+
+    let has_ccz_distillation_units = distillation_unit_templates
+        .iter()
+        .any(|template| template.output_state == MagicStateType::Ccz);
+    if has_ccz_distillation_units
+        && (matches!(job_params.estimate_type(), EstimateType::Frontier)
+            || job_params.constraints().max_duration.is_some()
+            || job_params.constraints().max_physical_qubits.is_some())
+    {
+        return Err(error::Error::InvalidInput(
+            error::InvalidInput::UnsupportedCczDistillationUnits,
+        ));
+    }
+
+    let layout = *job_params.layout();
+    layout.validate()?;
+    let mut layout_overhead = LayoutOverhead::new(logical_resources, layout);
+    layout_overhead.set_ccz_magic_states(has_ccz_distillation_units);
+    let layout_overhead = Rc::new(layout_overhead);
+
+    let ftp = Protocol::load_from_specification(job_params.qec_scheme_mut(), &qubit)?;
     // create error budget partitioning
     let partitioning = job_params
         .error_budget()
-        .partitioning(logical_resources.as_ref())?;
+        .partitioning(layout_overhead.as_ref())?;
 
     let mut estimation = PhysicalResourceEstimation::new(
         ftp,
         qubit,
         TFactoryBuilder::default(),
-        logical_resources,
+        layout_overhead,
         partitioning,
     );
     if let Some(logical_depth_factor) = job_params.constraints().logical_depth_factor {
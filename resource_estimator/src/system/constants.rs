@@ -18,6 +18,10 @@ pub const NUM_MEASUREMENTS_PER_R: u64 = 1;
 #[allow(clippy::doc_markdown)]
 pub const NUM_MEASUREMENTS_PER_TOF: u64 = 3;
 
+/// Maximum number of logical cycles per multi-qubit Pauli measurement in the
+/// compact block layout
+pub const COMPACT_BLOCK_CYCLES_PER_MEASUREMENT: u64 = 9;
+
 /// A coefficient in Ts per rotation
 pub const NUM_TS_PER_ROTATION_A_COEFFICIENT: f64 = 0.53;
 
@@ -47,3 +51,9 @@ pub const PROCESS: &str = "process";
 
 #[cfg(test)]
 pub const FLOAT_COMPARISON_EPSILON: f64 = 0.000_000_000_1;
+
+// QEC scheme variable names
+pub const CODE_DISTANCE: &str = "codeDistance";
+pub const PHYSICAL_ERROR_RATE: &str = "physicalErrorRate";
+pub const ERROR_CORRECTION_THRESHOLD: &str = "errorCorrectionThreshold";
+pub const CROSSING_PREFACTOR: &str = "crossingPrefactor";
@@ -1,20 +1,26 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+mod batch;
 mod constraints;
 mod job_params;
 mod logical_counts;
 mod physical_counts;
+mod profile;
 mod report;
 mod result;
+mod space_time;
 mod tfactory;
 
+pub use batch::{pareto_frontier, BatchSpecification, ParetoPoint};
 pub use constraints::Constraints;
 pub use job_params::{EstimateType, JobParams, PartitioningOverhead};
 pub use logical_counts::{LayoutReportData, LogicalResourceCounts};
 pub use physical_counts::{PhysicalResourceCounts, PhysicalResourceCountsBreakdown};
+pub use profile::{CallStackCounts, CallStackProfile};
 pub use report::{FormattedPhysicalResourceCounts, Report};
 pub use result::{Failure, Success};
+pub use space_time::{LayerCounts, LayerProfile};
 
 #[cfg(test)]
 pub use tfactory::{
@@ -0,0 +1,109 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::estimates::optimization::{Point2D, Population};
+
+/// Specification of a batch of job parameters
+///
+/// Each point in the batch is obtained by merging the top-level entries of
+/// `base`, one combination of values in `grid`, and one of the `items`, in
+/// that order of priority.  Grid combinations are enumerated in lexicographic
+/// order of their keys, with the values of the last key varying fastest.
+#[derive(Default, Deserialize)]
+#[serde(
+    rename_all(serialize = "camelCase", deserialize = "camelCase"),
+    deny_unknown_fields
+)]
+pub struct BatchSpecification {
+    #[serde(default)]
+    base: Map<String, Value>,
+    #[serde(default)]
+    items: Vec<Map<String, Value>>,
+    #[serde(default)]
+    grid: BTreeMap<String, Vec<Value>>,
+}
+
+impl BatchSpecification {
+    /// Returns the job parameters of all points in the batch
+    #[must_use]
+    pub fn points(&self) -> Vec<Value> {
+        let mut assignments = vec![Map::new()];
+        for (key, values) in &self.grid {
+            assignments = assignments
+                .into_iter()
+                .flat_map(|assignment| {
+                    values.iter().map(move |value| {
+                        let mut assignment = assignment.clone();
+                        assignment.insert(key.clone(), value.clone());
+                        assignment
+                    })
+                })
+                .collect();
+        }
+
+        let items = if self.items.is_empty() {
+            vec![Map::new()]
+        } else {
+            self.items.clone()
+        };
+
+        items
+            .iter()
+            .flat_map(|item| {
+                assignments.iter().map(|assignment| {
+                    let mut point = self.base.clone();
+                    point.extend(assignment.clone());
+                    point.extend(item.clone());
+                    Value::Object(point)
+                })
+            })
+            .collect()
+    }
+}
+
+/// A successful estimate in a batch, identified by the index of its result
+/// and, for frontier estimates, the index of the frontier entry
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct ParetoPoint {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frontier_entry: Option<usize>,
+    pub physical_qubits: u64,
+    pub runtime: u64,
+}
+
+/// Returns the points that are not dominated by any other point in terms of
+/// physical qubits and runtime, starting with the shortest runtime.  Of
+/// multiple points with the same physical qubits and runtime, only the first
+/// one in the batch is returned.
+#[must_use]
+pub fn pareto_frontier(points: impl IntoIterator<Item = ParetoPoint>) -> Vec<ParetoPoint> {
+    let mut population = Population::new();
+    for point in points {
+        population.push(Point2D::new(
+            point,
+            point.physical_qubits as f64,
+            point.runtime,
+        ));
+    }
+
+    population.filter_out_dominated();
+
+    let mut frontier: Vec<_> = population
+        .extract()
+        .into_iter()
+        .map(|point| point.item)
+        .collect();
+    frontier.sort_by_key(|point| (point.runtime, point.index, point.frontier_entry));
+    frontier.dedup_by_key(|point| (point.physical_qubits, point.runtime));
+    frontier
+}
@@ -0,0 +1,72 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use serde_json::json;
+
+use super::{pareto_frontier, BatchSpecification, ParetoPoint};
+
+#[test]
+fn batch_points_combine_items_and_grid() {
+    let batch: BatchSpecification = serde_json::from_value(json!({
+        "base": {"errorBudget": 0.01, "estimateType": "singlePoint"},
+        "items": [{}, {"errorBudget": 0.001}],
+        "grid": {
+            "qubitParams": [{"name": "qubit_gate_ns_e3"}, {"name": "qubit_gate_ns_e4"}],
+            "errorBudget": [0.1, 0.2]
+        }
+    }))
+    .expect("batch specification should be valid");
+
+    let points = batch.points();
+    assert_eq!(points.len(), 8);
+    assert_eq!(
+        points[0],
+        json!({"errorBudget": 0.1, "estimateType": "singlePoint", "qubitParams": {"name": "qubit_gate_ns_e3"}})
+    );
+    assert_eq!(
+        points[1],
+        json!({"errorBudget": 0.1, "estimateType": "singlePoint", "qubitParams": {"name": "qubit_gate_ns_e4"}})
+    );
+    assert_eq!(points[2]["errorBudget"], json!(0.2));
+    // item entries have priority over grid entries
+    assert_eq!(points[4]["errorBudget"], json!(0.001));
+    assert_eq!(
+        points[7]["qubitParams"],
+        json!({"name": "qubit_gate_ns_e4"})
+    );
+}
+
+#[test]
+fn batch_without_items_and_grid_has_single_point() {
+    let batch: BatchSpecification =
+        serde_json::from_value(json!({})).expect("batch specification should be valid");
+    assert_eq!(batch.points(), [json!({})]);
+}
+
+#[test]
+fn batch_rejects_unknown_fields() {
+    assert!(serde_json::from_value::<BatchSpecification>(json!({"errorBudget": 0.1})).is_err());
+}
+
+#[test]
+fn pareto_frontier_removes_dominated_points() {
+    let point = |index, physical_qubits, runtime| ParetoPoint {
+        index,
+        frontier_entry: None,
+        physical_qubits,
+        runtime,
+    };
+
+    let frontier = pareto_frontier([
+        point(0, 100, 50),
+        point(1, 200, 20),
+        point(2, 150, 60),
+        point(3, 300, 10),
+        point(4, 300, 30),
+    ]);
+
+    assert_eq!(
+        frontier,
+        [point(3, 300, 10), point(1, 200, 20), point(0, 100, 50)]
+    );
+}
@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 
 use super::super::{
     error::InvalidInput::{self, InvalidErrorBudget},
-    modeling::{PhysicalQubit, ProtocolSpecification},
+    modeling::{Layout, PhysicalQubit, ProtocolSpecification},
 };
 use crate::estimates::ErrorBudget;
 
@@ -28,6 +28,9 @@ pub struct JobParams {
     #[serde(default, skip_serializing_if = "Constraints::is_default")]
     constraints: Constraints,
 
+    #[serde(default, skip_serializing_if = "Layout::is_default")]
+    layout: Layout,
+
     #[serde(default, skip_serializing_if = "Profiling::is_default")]
     profiling: Profiling,
 
@@ -69,6 +72,18 @@ impl JobParams {
         &self.constraints
     }
 
+    #[must_use]
+    #[inline]
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn profiling(&self) -> &Profiling {
+        &self.profiling
+    }
+
     #[must_use]
     #[inline]
     pub fn distillation_unit_specifications(&self) -> &TFactoryDistillationUnitSpecifications {
@@ -85,10 +100,16 @@ impl JobParams {
 #[derive(Serialize, Deserialize, Default, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Profiling {
+    /// Number of call-stack frames to which logical resources are attributed
+    /// in the per-callable breakdown and the flame graph.  Resources of deeper
+    /// frames are attributed to their ancestor at this depth.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub call_stack_depth: Option<usize>,
+    /// Number of windows of logical cycles into which the runtime is divided
+    /// for the space-time diagram of active algorithm qubits and factory
+    /// copies.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub inline_functions: Option<bool>,
+    pub space_time_windows: Option<usize>,
 }
 
 impl Profiling {
@@ -21,10 +21,22 @@ pub trait LayoutReportData {
     fn ccix_count(&self) -> u64;
     fn measurement_count(&self) -> u64;
     fn num_ts_per_rotation(&self, eps_synthesis: f64) -> Option<u64>;
+
+    /// The number of multi-qubit Pauli measurements to execute the algorithm
+    /// using Parallel Synthesis Sequential Pauli Computation (PSSPC)
+    fn num_pauli_measurements(&self, budget: &ErrorBudget) -> u64 {
+        (self.measurement_count() + self.rotation_count() + self.t_count()) * NUM_MEASUREMENTS_PER_R
+            + (self.ccz_count() + self.ccix_count()) * NUM_MEASUREMENTS_PER_TOF
+            + self
+                .num_ts_per_rotation(budget.rotations())
+                .unwrap_or_default()
+                * self.rotation_depth()
+                * NUM_MEASUREMENTS_PER_R
+    }
 }
 
 /// Resource counts output from `qir_estimate_counts` program
-#[derive(Default, Debug, Deserialize, Serialize)]
+#[derive(Clone, Default, Debug, Deserialize, Serialize)]
 #[serde(
     rename_all(deserialize = "camelCase", serialize = "camelCase"),
     deny_unknown_fields
@@ -60,13 +72,7 @@ impl Overhead for LogicalResourceCounts {
     }
 
     fn logical_depth(&self, budget: &ErrorBudget) -> u64 {
-        (self.measurement_count + self.rotation_count + self.t_count) * NUM_MEASUREMENTS_PER_R
-            + (self.ccz_count + self.ccix_count) * NUM_MEASUREMENTS_PER_TOF
-            + self
-                .num_ts_per_rotation(budget.rotations())
-                .unwrap_or_default()
-                * self.rotation_depth
-                * NUM_MEASUREMENTS_PER_R
+        self.num_pauli_measurements(budget)
     }
 
     fn num_magic_states(&self, budget: &ErrorBudget, _index: usize) -> u64 {
@@ -46,4 +46,17 @@ pub struct PhysicalResourceCountsBreakdown {
     pub(crate) num_ts_per_rotation: Option<u64>,
     /// The Clifford error rate based on the qubit parameters
     pub(crate) clifford_error_rate: f64,
+    /// The number of CCZ states consumed by the algorithm, if CCZ factories
+    /// are used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) num_ccz_states: Option<u64>,
+    /// The number of CCZ factories, if CCZ factories are used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) num_ccz_factories: Option<u64>,
+    /// The number of how often all parallel CCZ factories should run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) num_ccz_factory_runs: Option<u64>,
+    /// The required logical CCZ-state error rate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) required_logical_ccz_state_error_rate: Option<f64>,
 }
@@ -0,0 +1,257 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use std::ops::{AddAssign, Mul};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Serialize;
+
+/// Logical resources attributed to a call stack
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct CallStackCounts {
+    pub t_count: u64,
+    pub rotation_count: u64,
+    pub ccz_count: u64,
+    pub measurement_count: u64,
+    pub qubit_allocations: u64,
+}
+
+impl CallStackCounts {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl AddAssign for CallStackCounts {
+    fn add_assign(&mut self, rhs: Self) {
+        self.t_count += rhs.t_count;
+        self.rotation_count += rhs.rotation_count;
+        self.ccz_count += rhs.ccz_count;
+        self.measurement_count += rhs.measurement_count;
+        self.qubit_allocations += rhs.qubit_allocations;
+    }
+}
+
+impl Mul<u64> for CallStackCounts {
+    type Output = Self;
+
+    fn mul(self, rhs: u64) -> Self {
+        Self {
+            t_count: self.t_count * rhs,
+            rotation_count: self.rotation_count * rhs,
+            ccz_count: self.ccz_count * rhs,
+            measurement_count: self.measurement_count * rhs,
+            qubit_allocations: self.qubit_allocations * rhs,
+        }
+    }
+}
+
+/// Logical resources attributed to the call stacks in which they were
+/// consumed
+///
+/// Each call stack is a list of indices into the frame names, starting with
+/// the outermost callable.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CallStackProfile {
+    frames: Vec<String>,
+    samples: Vec<(Vec<usize>, CallStackCounts)>,
+}
+
+impl CallStackProfile {
+    #[must_use]
+    pub fn new(
+        frames: Vec<String>,
+        samples: impl IntoIterator<Item = (Vec<usize>, CallStackCounts)>,
+    ) -> Self {
+        let mut merged: FxHashMap<Vec<usize>, CallStackCounts> = FxHashMap::default();
+        for (stack, counts) in samples {
+            if !counts.is_empty() {
+                *merged.entry(stack).or_default() += counts;
+            }
+        }
+
+        let mut samples = merged.into_iter().collect::<Vec<_>>();
+        samples.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        Self { frames, samples }
+    }
+
+    #[must_use]
+    pub fn frames(&self) -> &[String] {
+        &self.frames
+    }
+
+    #[must_use]
+    pub fn samples(&self) -> &[(Vec<usize>, CallStackCounts)] {
+        &self.samples
+    }
+
+    /// Attributes the resources of frames deeper than `depth` to their
+    /// ancestor at that depth.
+    #[must_use]
+    pub fn truncated(&self, depth: usize) -> Self {
+        Self::new(
+            self.frames.clone(),
+            self.samples
+                .iter()
+                .map(|(stack, counts)| (stack[..stack.len().min(depth)].to_vec(), *counts)),
+        )
+    }
+
+    /// Returns the resources per callable, both including the resources of
+    /// the callables it calls and excluding them.  Callables are listed in
+    /// the order in which they were first called.
+    #[must_use]
+    pub fn breakdown(&self) -> Vec<CallableBreakdown> {
+        let mut breakdown = self
+            .frames
+            .iter()
+            .map(|name| CallableBreakdown {
+                name: name.clone(),
+                inclusive: CallStackCounts::default(),
+                exclusive: CallStackCounts::default(),
+            })
+            .collect::<Vec<_>>();
+
+        for (stack, counts) in &self.samples {
+            // recursive calls must only be counted once per call stack
+            let mut seen = FxHashSet::default();
+            for frame in stack {
+                if seen.insert(*frame) {
+                    breakdown[*frame].inclusive += *counts;
+                }
+            }
+            if let Some(frame) = stack.last() {
+                breakdown[*frame].exclusive += *counts;
+            }
+        }
+
+        breakdown.retain(|entry| !entry.inclusive.is_empty());
+        breakdown
+    }
+
+    /// Exports the profile in the speedscope file format, with one sampled
+    /// profile per resource kind.
+    #[must_use]
+    pub fn flame_graph(&self) -> File {
+        let frames = self.frames.iter().map(String::as_str).collect::<Vec<_>>();
+        let mut file = File::new(&frames);
+
+        file.push(self.sampled_profile("T gates", |counts| counts.t_count));
+        file.push(self.sampled_profile("Rotation gates", |counts| counts.rotation_count));
+        file.push(self.sampled_profile("CCZ gates", |counts| counts.ccz_count));
+        file.push(self.sampled_profile("Measurements", |counts| counts.measurement_count));
+        file.push(self.sampled_profile("Qubit allocations", |counts| counts.qubit_allocations));
+
+        file
+    }
+
+    fn sampled_profile(
+        &self,
+        name: &str,
+        weight: impl Fn(&CallStackCounts) -> u64,
+    ) -> SampledProfile {
+        let mut profile = SampledProfile::new(name);
+        for (stack, counts) in &self.samples {
+            let weight = weight(counts);
+            if weight != 0 {
+                profile.push_sample(stack, weight);
+            }
+        }
+        profile
+    }
+}
+
+/// Logical resources attributed to a single callable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct CallableBreakdown {
+    pub name: String,
+    /// Resources consumed by the callable and all callables it calls
+    pub inclusive: CallStackCounts,
+    /// Resources consumed by the callable itself
+    pub exclusive: CallStackCounts,
+}
+
+/// A profile in the speedscope file format (see
+/// <https://www.speedscope.app/file-format-schema.json>)
+#[derive(Debug, Serialize)]
+pub struct File {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    shared: Shared,
+    profiles: Vec<SampledProfile>,
+}
+
+impl File {
+    #[must_use]
+    pub fn new(frames: &[&str]) -> Self {
+        Self {
+            schema: "https://www.speedscope.app/file-format-schema.json",
+            shared: Shared {
+                frames: frames
+                    .iter()
+                    .map(|name| Frame {
+                        name: (*name).to_string(),
+                    })
+                    .collect(),
+            },
+            profiles: vec![],
+        }
+    }
+
+    pub fn push(&mut self, profile: SampledProfile) {
+        self.profiles.push(profile);
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Shared {
+    frames: Vec<Frame>,
+}
+
+#[derive(Debug, Serialize)]
+struct Frame {
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct SampledProfile {
+    #[serde(rename = "type")]
+    ty: &'static str,
+    name: String,
+    unit: &'static str,
+    start_value: u64,
+    end_value: u64,
+    samples: Vec<Vec<usize>>,
+    weights: Vec<u64>,
+}
+
+impl SampledProfile {
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            ty: "sampled",
+            name: name.to_string(),
+            unit: "none",
+            start_value: 0,
+            end_value: 0,
+            samples: vec![],
+            weights: vec![],
+        }
+    }
+
+    /// Adds a sample for the call stack `stack`, given as indices into the
+    /// frames of the file, starting with the outermost frame.
+    pub fn push_sample(&mut self, stack: &[usize], weight: u64) {
+        self.samples.push(stack.to_vec());
+        self.weights.push(weight);
+        self.end_value += weight;
+    }
+}
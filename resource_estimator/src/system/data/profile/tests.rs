@@ -1,9 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use super::SampledProfile;
-
-use super::File;
+use super::{CallStackCounts, CallStackProfile, CallableBreakdown, File, SampledProfile};
 
 #[test]
 fn test_two_sampled() {
@@ -27,3 +25,59 @@ fn test_two_sampled() {
 
     println!("{}", serde_json::to_string(&two_sampled).unwrap());
 }
+
+fn counts(t_count: u64, rotation_count: u64) -> CallStackCounts {
+    CallStackCounts {
+        t_count,
+        rotation_count,
+        ..CallStackCounts::default()
+    }
+}
+
+#[test]
+fn truncated_profile_attributes_deeper_frames_to_ancestor() {
+    let profile = CallStackProfile::new(
+        vec!["Main".into(), "A".into(), "B".into()],
+        [
+            (vec![0], counts(1, 0)),
+            (vec![0, 1], counts(2, 1)),
+            (vec![0, 1, 2], counts(4, 2)),
+        ],
+    );
+
+    let truncated = profile.truncated(2);
+    assert_eq!(
+        truncated.samples(),
+        &[(vec![0], counts(1, 0)), (vec![0, 1], counts(6, 3))]
+    );
+}
+
+#[test]
+fn breakdown_counts_recursive_frames_once() {
+    let profile = CallStackProfile::new(
+        vec!["Main".into(), "A".into(), "B".into()],
+        [
+            (vec![0], counts(1, 0)),
+            (vec![0, 1], counts(2, 0)),
+            (vec![0, 1, 1], counts(4, 0)),
+            (vec![0, 1, 1], counts(0, 8)),
+        ],
+    );
+
+    let breakdown = profile.breakdown();
+    assert_eq!(
+        breakdown,
+        vec![
+            CallableBreakdown {
+                name: "Main".into(),
+                inclusive: counts(7, 8),
+                exclusive: counts(1, 0),
+            },
+            CallableBreakdown {
+                name: "A".into(),
+                inclusive: counts(6, 8),
+                exclusive: counts(6, 8),
+            },
+        ]
+    );
+}
@@ -9,11 +9,11 @@ mod tests;
 use serde::Serialize;
 
 use crate::estimates::{Factory, FactoryPart, Overhead, PhysicalResourceEstimationResult};
-use crate::system::modeling::Protocol;
+use crate::system::modeling::{MagicStateType, Protocol};
 
 use super::LayoutReportData;
 use super::{
-    super::modeling::{PhysicalInstructionSet, TFactory},
+    super::modeling::{Layout, PhysicalInstructionSet, ProtocolSpecification, TFactory},
     job_params::JobParams,
 };
 
@@ -36,8 +36,8 @@ impl Report {
         formatted_counts: &FormattedPhysicalResourceCounts,
     ) -> Self {
         let logical_counts = result.layout_overhead();
-        // In this system, we consider T as the only magic state type, therefore
-        // there is only one factory part in the result.
+        // The first factory part produces T states; CCZ factories, if used,
+        // are reported in a separate group below.
         let part = result.factory_parts()[0].as_ref();
         let factory = part.map(FactoryPart::factory);
 
@@ -216,6 +216,62 @@ impl Report {
             entries,
         });
 
+        // The generated explanations of the algorithmic logical qubits and
+        // depth assume the PSSPC layout.
+        if let Some((qubits_explanation, depth_explanation)) =
+            layout_explanations(job_params.layout(), result)
+        {
+            for entry in groups.iter_mut().flat_map(|group| group.entries.iter_mut()) {
+                match entry.path.as_str() {
+                    "physicalCountsFormatted/algorithmicLogicalQubits" => {
+                        entry.explanation.clone_from(&qubits_explanation);
+                    }
+                    "physicalCountsFormatted/algorithmicLogicalDepth" => {
+                        entry.explanation.clone_from(&depth_explanation);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // The generated explanations of the code distance and the logical
+        // error rate assume the default error model.
+        if let Some((code_distance_explanation, logical_error_rate_explanation)) =
+            qec_explanations(job_params.qec_scheme(), result)
+        {
+            for entry in groups.iter_mut().flat_map(|group| group.entries.iter_mut()) {
+                match entry.path.as_str() {
+                    "logicalQubit/codeDistance" => {
+                        entry.explanation.clone_from(&code_distance_explanation);
+                    }
+                    "physicalCountsFormatted/logicalErrorRate" => {
+                        entry
+                            .explanation
+                            .clone_from(&logical_error_rate_explanation);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // The generated explanation of the number of T states assumes that
+        // each CCZ gate consumes four T states.
+        if let Some((num_tstates_explanation, ccz_group)) = ccz_explanations(result) {
+            for entry in groups.iter_mut().flat_map(|group| group.entries.iter_mut()) {
+                if entry.path == "physicalCountsFormatted/numTstates" {
+                    entry.explanation.clone_from(&num_tstates_explanation);
+                }
+            }
+
+            if !ccz_group.entries.is_empty() {
+                let position = groups
+                    .iter()
+                    .position(|group| group.title == "T factory parameters")
+                    .map_or(groups.len(), |index| index + 1);
+                groups.insert(position, ccz_group);
+            }
+        }
+
         let assumptions = vec![
             String::from("_More details on the following lists of assumptions can be found in the paper [Accessing requirements for scaling quantum computers and their applications](https://aka.ms/AQ/RE/Paper)._"),
             String::from("**Uniform independent physical noise.** We assume that the noise on physical qubits and physical qubit operations is the standard circuit noise model. In particular we assume error events at different space-time locations are independent and that error rates are uniform across the system in time and space."),
@@ -233,6 +289,143 @@ impl Report {
     }
 }
 
+/// Returns the explanations of the algorithmic logical qubits and the
+/// algorithmic logical depth for layouts other than PSSPC.
+fn layout_explanations(
+    layout: &Layout,
+    result: &PhysicalResourceEstimationResult<Protocol, TFactory, impl Overhead + LayoutReportData>,
+) -> Option<(String, String)> {
+    let cycles = layout.cycles_per_measurement()?;
+    let logical_counts = result.layout_overhead();
+    let num_qubits = format_thousand_sep(&logical_counts.num_qubits());
+    let logical_qubits = format_thousand_sep(&logical_counts.logical_qubits());
+    let depth = format_thousand_sep(&result.algorithmic_logical_depth());
+    let measurements = format!(
+        r#"Based on the input algorithm, we require {} multi-qubit Pauli measurements: one for each of the {} single-qubit measurements, the {} arbitrary single-qubit rotations, and the {} T gates, three for each of the {} CCZ and {} CCiX gates, as well as {} for each of the {} non-Clifford layers in which there is at least one single-qubit rotation with an arbitrary angle rotation."#,
+        format_thousand_sep(&logical_counts.num_pauli_measurements(result.error_budget())),
+        format_thousand_sep(&logical_counts.measurement_count()),
+        format_thousand_sep(&logical_counts.rotation_count()),
+        format_thousand_sep(&logical_counts.t_count()),
+        format_thousand_sep(&logical_counts.ccz_count()),
+        format_thousand_sep(&logical_counts.ccix_count()),
+        logical_counts
+            .num_ts_per_rotation(result.error_budget().rotations())
+            .unwrap_or_default(),
+        format_thousand_sep(&logical_counts.rotation_depth()),
+    );
+
+    match *layout {
+        Layout::Psspc => None,
+        Layout::LatticeSurgery { routing_overhead } => Some((
+            format!(
+                r#"To route multi-qubit Pauli measurements between the $Q_{{\rm alg}} = {num_qubits}$ logical qubits in the input algorithm using lattice surgery, we add {routing_overhead} routing qubits per logical qubit, and require in total $Q_{{\rm alg}} + \lceil {routing_overhead} \cdot Q_{{\rm alg}}\rceil = {logical_qubits}$ logical qubits."#
+            ),
+            format!(
+                r#"{measurements}  With {routing_overhead} routing qubits per logical qubit, each multi-qubit Pauli measurement takes $\lceil 1 / {routing_overhead}\rceil = {cycles}$ logical cycles, which results in {depth} logical cycles."#
+            ),
+        )),
+        Layout::CompactBlock => Some((
+            format!(
+                r#"The compact block layout arranges the $Q_{{\rm alg}} = {num_qubits}$ logical qubits in the input algorithm in a block of $\lceil 1.5 \cdot Q_{{\rm alg}}\rceil + 3 = {logical_qubits}$ logical qubits [[arXiv:1808.02892](https://arxiv.org/abs/1808.02892)]."#
+            ),
+            format!(
+                r#"{measurements}  Since only one boundary of each logical qubit is accessible in the compact block layout, each multi-qubit Pauli measurement takes up to {cycles} logical cycles, which results in {depth} logical cycles."#
+            ),
+        )),
+    }
+}
+
+/// Returns the explanations of the code distance and the logical error rate
+/// for QEC schemes with a user-specified error model.
+fn qec_explanations(
+    qec_scheme: &ProtocolSpecification,
+    result: &PhysicalResourceEstimationResult<Protocol, TFactory, impl Overhead + LayoutReportData>,
+) -> Option<(String, String)> {
+    if !qec_scheme.has_custom_error_model() {
+        return None;
+    }
+
+    let code_distances = if qec_scheme.code_parameters.is_empty() {
+        "the smallest odd code distance"
+    } else {
+        "the code distance of the first code parameter, in the order of the code parameters,"
+    };
+    let code_distance_explanation = format!(
+        r#"The code distance is {code_distances} for which the logical error rate per logical qubit does not exceed the required logical error rate of {:.2e}"#,
+        result.required_logical_error_rate()
+    );
+
+    let logical_error_rate_explanation = match &qec_scheme.logical_error_rate {
+        Some(formula) => format!(
+            r#"The logical qubit error rate is computed with the formula `{formula}` for code distance {}"#,
+            qec_scheme.code_distance(*result.logical_patch().code_parameter())
+        ),
+        None => format!(
+            r#"The logical qubit error rate is computed as ${} \cdot \left(\dfrac{{{}}}{{{}}}\right)^\frac{{{} + 1}}{{2}}$"#,
+            qec_scheme
+                .crossing_prefactor
+                .expect("crossing prefactor should be set"),
+            result
+                .logical_patch()
+                .physical_qubit()
+                .clifford_error_rate(),
+            qec_scheme
+                .error_correction_threshold
+                .expect("error correction threshold should be set"),
+            qec_scheme.code_distance(*result.logical_patch().code_parameter())
+        ),
+    };
+
+    Some((code_distance_explanation, logical_error_rate_explanation))
+}
+
+/// Returns the explanation of the number of T states and the group of CCZ
+/// factory parameters, if CCZ gates consume CCZ states from CCZ factories.
+fn ccz_explanations(
+    result: &PhysicalResourceEstimationResult<Protocol, TFactory, impl Overhead + LayoutReportData>,
+) -> Option<(String, ReportEntryGroup)> {
+    let ccz_part = result
+        .factory_parts()
+        .get(MagicStateType::Ccz.index())?
+        .as_ref();
+    let logical_counts = result.layout_overhead();
+
+    let num_tstates_explanation = format!(
+        r#"To execute the algorithm, we require one T state for each of the {} T gates, four T states for each of the {} CCiX gates, as well as {} for each of the {} single-qubit rotation gates with arbitrary angle rotation.  The {} CCZ gates consume CCZ states from CCZ factories instead."#,
+        format_thousand_sep(&logical_counts.t_count()),
+        format_thousand_sep(&logical_counts.ccix_count()),
+        logical_counts
+            .num_ts_per_rotation(result.error_budget().rotations())
+            .unwrap_or_default(),
+        format_thousand_sep(&logical_counts.rotation_count()),
+        format_thousand_sep(&logical_counts.ccz_count()),
+    );
+
+    let mut entries = vec![];
+    if let Some(part) = ccz_part {
+        entries.push(ReportEntry::new("physicalCounts/breakdown/numCczFactories", "Number of CCZ factories", r#"Number of CCZ factories capable of producing the demanded CCZ states during the algorithm's runtime"#, &format!(r#"In order to prepare the {} CCZ states, the {} copies of the CCZ factory are repeatedly invoked {} times."#, format_thousand_sep(&result.num_magic_states(MagicStateType::Ccz.index())), format_thousand_sep(&part.copies()), format_thousand_sep(&part.runs()))));
+        entries.push(ReportEntry::new("cczFactory/physicalQubits", "Physical qubits", r#"Number of physical qubits for a single CCZ factory"#, r#"This corresponds to the maximum number of physical qubits over all rounds of distillation units in a CCZ factory.  All rounds but the last one distill T states, which are consumed by the CCZ distillation units in the last round."#));
+        entries.push(ReportEntry::new("cczFactory/runtime", "Runtime", r#"Runtime of a single CCZ factory"#, r#"The runtime of a single CCZ factory is the accumulated runtime of executing each round in a CCZ factory."#));
+        entries.push(ReportEntry::new("cczFactory/numTstates", "Number of output CCZ states per run", r#"Number of output CCZ states produced in a single run of CCZ factory"#, &format!(r#"The CCZ factory takes as input {} noisy physical T states and produces {} CCZ states with an error rate of {:.2e}."#, format_thousand_sep(&part.factory().num_input_states()), format_thousand_sep(&part.factory().num_output_states()), part.factory().output_error_rate())));
+        entries.push(ReportEntry::new(
+            "cczFactory/unitNamePerRound",
+            "Distillation units",
+            r#"The types of distillation units"#,
+            r#"These are the types of distillation units that are executed in each round."#,
+        ));
+        entries.push(ReportEntry::new("cczFactory/logicalErrorRate", "Logical CCZ state error rate", r#"Logical CCZ state error rate"#, &format!(r#"This is the logical CCZ state error rate achieved by the CCZ factory which is equal or smaller than the required error rate {:.2e}."#, part.required_output_error_rate())));
+    }
+
+    Some((
+        num_tstates_explanation,
+        ReportEntryGroup {
+            title: "CCZ factory parameters".into(),
+            always_visible: false,
+            entries,
+        },
+    ))
+}
+
 #[derive(Serialize)]
 #[serde(rename_all(serialize = "camelCase"))]
 struct ReportEntryGroup {
@@ -446,7 +639,10 @@ impl FormattedPhysicalResourceCounts {
             tfactory
                 .code_parameter_per_round()
                 .iter()
-                .map(|&num| num.copied().unwrap_or(1).to_string())
+                .map(|&num| {
+                    num.map_or(1, |&p| job_params.qec_scheme().code_distance(p))
+                        .to_string()
+                })
                 .collect::<Vec<_>>()
                 .join(", ")
         });
@@ -4,10 +4,13 @@
 use std::rc::Rc;
 
 use crate::estimates::{
-    ErrorBudget, FactoryPart, LogicalPatch, Overhead, PhysicalResourceEstimationResult,
+    ErrorBudget, Factory, FactoryPart, LogicalPatch, Overhead, PhysicalResourceEstimationResult,
 };
-use crate::system::modeling::{Protocol, TFactory};
+use crate::system::modeling::{MagicStateType, Protocol, TFactory};
 
+use super::batch::ParetoPoint;
+use super::profile::{CallStackProfile, CallableBreakdown, File};
+use super::space_time::{FactoryCopies, LayerProfile, SpaceTimeDiagram};
 use super::LayoutReportData;
 use super::{
     super::Error, FormattedPhysicalResourceCounts, JobParams, PhysicalResourceCounts,
@@ -30,11 +33,19 @@ pub struct Success<L: Serialize> {
     #[serde(skip_serializing_if = "Option::is_none")]
     tfactory: Option<TFactory>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    ccz_factory: Option<TFactory>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     error_budget: Option<ErrorBudget>,
     logical_counts: Rc<L>,
     report_data: Report,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     frontier_entries: Vec<FrontierEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    call_stack_breakdown: Option<Vec<CallableBreakdown>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flame_graph: Option<File>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    space_time_diagram: Option<SpaceTimeDiagram>,
 }
 
 impl<L: Overhead + LayoutReportData + Serialize> Success<L> {
@@ -50,8 +61,14 @@ impl<L: Overhead + LayoutReportData + Serialize> Success<L> {
         let report_data = Report::new(&job_params, &result, &formatted_counts);
 
         let logical_counts = result.layout_overhead().clone();
-        let (logical_qubit, mut parts, error_budget) = result.take();
-        let tfactory = parts.swap_remove(0).map(FactoryPart::into_factory);
+        let (logical_qubit, parts, error_budget) = result.take();
+        let logical_qubit = report_code_distance(&job_params, logical_qubit);
+        // The second factory part, if present, produces CCZ states
+        let mut parts = parts
+            .into_iter()
+            .map(|part| part.map(|part| report_code_distances(&job_params, part.into_factory())));
+        let tfactory = parts.next().flatten();
+        let ccz_factory = parts.next().flatten();
 
         Self {
             status: "success",
@@ -60,10 +77,14 @@ impl<L: Overhead + LayoutReportData + Serialize> Success<L> {
             physical_counts_formatted: Some(formatted_counts),
             logical_qubit: Some(logical_qubit),
             tfactory,
+            ccz_factory,
             error_budget: Some(error_budget),
             logical_counts,
             report_data,
             frontier_entries: Vec::new(),
+            call_stack_breakdown: None,
+            flame_graph: None,
+            space_time_diagram: None,
         }
     }
 
@@ -97,10 +118,79 @@ impl<L: Overhead + LayoutReportData + Serialize> Success<L> {
             physical_counts_formatted: None,
             logical_qubit: None,
             tfactory: None,
+            ccz_factory: None,
             error_budget: None,
             logical_counts,
             report_data: report_data.expect("error should have report"), // Here we assume that at least a single solution was found.
             frontier_entries,
+            call_stack_breakdown: None,
+            flame_graph: None,
+            space_time_diagram: None,
+        }
+    }
+
+    /// Adds the per-callable breakdown of logical resources and its flame
+    /// graph, considering call stacks up to `depth` frames.
+    pub fn set_call_stack_profile(&mut self, profile: &CallStackProfile, depth: usize) {
+        let profile = profile.truncated(depth);
+        self.call_stack_breakdown = Some(profile.breakdown());
+        self.flame_graph = Some(profile.flame_graph());
+    }
+
+    /// Adds the space-time diagram of active algorithm qubits and factory
+    /// copies for the layers in `profile`, divided into at most
+    /// `num_windows` windows.  Frontier estimates have no single schedule
+    /// and therefore no space-time diagram.
+    pub fn set_layer_profile(&mut self, profile: &LayerProfile, num_windows: usize) {
+        let (Some(counts), Some(logical_qubit)) = (&self.physical_counts, &self.logical_qubit)
+        else {
+            return;
+        };
+        let breakdown = &counts.breakdown;
+
+        let factory_copies = |factory: &TFactory, copies: u64| FactoryCopies {
+            copies,
+            duration: factory.duration(),
+            num_output_states: factory.num_output_states(),
+        };
+
+        self.space_time_diagram = Some(SpaceTimeDiagram::new(
+            profile,
+            num_windows,
+            breakdown.logical_depth,
+            logical_qubit.logical_cycle_time(),
+            breakdown.num_ts_per_rotation.unwrap_or_default(),
+            self.tfactory
+                .as_ref()
+                .map(|factory| factory_copies(factory, breakdown.num_tfactories)),
+            self.ccz_factory.as_ref().map(|factory| {
+                factory_copies(factory, breakdown.num_ccz_factories.unwrap_or_default())
+            }),
+        ));
+    }
+
+    /// Returns the physical qubits and runtime of the estimate, or of each
+    /// frontier entry for frontier estimates, as points of the result with
+    /// index `index` in a batch.
+    pub fn pareto_points(&self, index: usize) -> Vec<ParetoPoint> {
+        match &self.physical_counts {
+            Some(counts) => vec![ParetoPoint {
+                index,
+                frontier_entry: None,
+                physical_qubits: counts.physical_qubits,
+                runtime: counts.runtime,
+            }],
+            None => self
+                .frontier_entries
+                .iter()
+                .enumerate()
+                .map(|(frontier_entry, entry)| ParetoPoint {
+                    index,
+                    frontier_entry: Some(frontier_entry),
+                    physical_qubits: entry.physical_counts.physical_qubits,
+                    runtime: entry.physical_counts.runtime,
+                })
+                .collect(),
         }
     }
 }
@@ -132,7 +222,10 @@ fn create_frontier_entry(
     };
 
     let (logical_qubit, mut parts, error_budget) = result.take();
-    let tfactory = parts.swap_remove(0).map(FactoryPart::into_factory);
+    let logical_qubit = report_code_distance(job_params, logical_qubit);
+    let tfactory = parts
+        .swap_remove(0)
+        .map(|part| report_code_distances(job_params, part.into_factory()));
 
     (
         FrontierEntry {
@@ -146,6 +239,21 @@ fn create_frontier_entry(
     )
 }
 
+/// Replaces the code parameter of the logical qubit by its code distance,
+/// which differ if the QEC scheme specifies code parameters.
+fn report_code_distance(
+    job_params: &JobParams,
+    logical_qubit: LogicalPatch<Protocol>,
+) -> LogicalPatch<Protocol> {
+    logical_qubit.map_code_parameter(|&p| job_params.qec_scheme().code_distance(p))
+}
+
+/// Replaces the code parameters of the factory rounds by their code
+/// distances, which differ if the QEC scheme specifies code parameters.
+fn report_code_distances(job_params: &JobParams, factory: TFactory) -> TFactory {
+    factory.map_code_parameters(|&p| job_params.qec_scheme().code_distance(p))
+}
+
 fn create_physical_resource_counts(
     result: &PhysicalResourceEstimationResult<Protocol, TFactory, impl Overhead + LayoutReportData>,
 ) -> PhysicalResourceCounts {
@@ -167,6 +275,8 @@ fn create_physical_resource_counts_breakdown(
         .num_ts_per_rotation(result.error_budget().rotations());
 
     let part = result.factory_parts()[0].as_ref();
+    let ccz_parts = result.factory_parts().get(MagicStateType::Ccz.index());
+    let ccz_part = ccz_parts.and_then(Option::as_ref);
 
     PhysicalResourceCountsBreakdown {
         algorithmic_logical_qubits: result.layout_overhead().logical_qubits(),
@@ -189,6 +299,11 @@ fn create_physical_resource_counts_breakdown(
             .logical_patch()
             .physical_qubit()
             .clifford_error_rate(),
+        num_ccz_states: ccz_parts.map(|_| result.num_magic_states(MagicStateType::Ccz.index())),
+        num_ccz_factories: ccz_parts.map(|_| ccz_part.map_or(0, FactoryPart::copies)),
+        num_ccz_factory_runs: ccz_parts.map(|_| ccz_part.map_or(0, FactoryPart::runs)),
+        required_logical_ccz_state_error_rate: ccz_part
+            .map(FactoryPart::required_output_error_rate),
     }
 }
 
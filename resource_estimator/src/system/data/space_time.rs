@@ -0,0 +1,202 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use serde::Serialize;
+
+use crate::system::constants::{NUM_MEASUREMENTS_PER_R, NUM_MEASUREMENTS_PER_TOF};
+
+/// Non-Clifford operations and active qubits of a single layer of the
+/// scheduled algorithm
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LayerCounts {
+    pub t_count: u64,
+    pub rotation_count: u64,
+    pub ccz_count: u64,
+    /// Maximum number of allocated algorithm qubits while the layer is
+    /// executed
+    pub active_qubits: u64,
+}
+
+/// Non-Clifford operations and active qubits of all layers of the scheduled
+/// algorithm, in execution order
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LayerProfile {
+    layers: Vec<LayerCounts>,
+}
+
+impl LayerProfile {
+    #[must_use]
+    pub fn new(layers: impl IntoIterator<Item = LayerCounts>) -> Self {
+        Self {
+            layers: layers.into_iter().collect(),
+        }
+    }
+
+    #[must_use]
+    pub fn layers(&self) -> &[LayerCounts] {
+        &self.layers
+    }
+
+    /// The number of logical cycles that the layer takes relative to the
+    /// other layers, following the multi-qubit Pauli measurements in PSSPC
+    fn weight(layer: &LayerCounts, num_ts_per_rotation: u64) -> u64 {
+        let synthesis_depth = if layer.rotation_count > 0 {
+            num_ts_per_rotation
+        } else {
+            0
+        };
+
+        (layer.t_count + layer.rotation_count + synthesis_depth) * NUM_MEASUREMENTS_PER_R
+            + layer.ccz_count * NUM_MEASUREMENTS_PER_TOF
+    }
+}
+
+/// Copies of a magic state factory that are placed next to the algorithm
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FactoryCopies {
+    pub copies: u64,
+    /// Duration of a single factory run in nanoseconds
+    pub duration: u64,
+    /// Number of magic states produced by a single factory run
+    pub num_output_states: u64,
+}
+
+impl FactoryCopies {
+    /// The number of copies that need to run in a window of `window_duration`
+    /// nanoseconds to produce `num_states` magic states in time
+    fn active(&self, num_states: f64, window_duration: u64) -> u64 {
+        let runs = (num_states * self.duration as f64)
+            / (self.num_output_states as f64 * window_duration as f64);
+        (runs.ceil() as u64).min(self.copies)
+    }
+}
+
+/// Active algorithm qubits and factory copies over the runtime of the
+/// algorithm, divided into windows of logical cycles
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct SpaceTimeDiagram {
+    pub cycles_per_window: u64,
+    pub windows: Vec<SpaceTimeWindow>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct SpaceTimeWindow {
+    pub start_cycle: u64,
+    /// Maximum number of allocated algorithm qubits in the window
+    pub active_qubits: u64,
+    /// Number of T states consumed in the window
+    pub tstates: u64,
+    /// Number of T factory copies needed to produce the T states in the
+    /// window, which is at most the number of T factories
+    pub active_tfactories: u64,
+    /// Number of CCZ states consumed in the window, if CCZ factories are used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ccz_states: Option<u64>,
+    /// Number of CCZ factory copies needed to produce the CCZ states in the
+    /// window, if CCZ factories are used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_ccz_factories: Option<u64>,
+}
+
+impl SpaceTimeDiagram {
+    /// Distributes the layers of `profile` over `num_cycles` logical cycles
+    /// proportionally to their number of multi-qubit Pauli measurements and
+    /// divides the runtime into at most `num_windows` windows of equal size.
+    ///
+    /// Magic states are assumed to be consumed in the window in which their
+    /// layer executes.  If `ccz_factory` is `None`, CCZ gates consume four T
+    /// states each.
+    #[must_use]
+    pub fn new(
+        profile: &LayerProfile,
+        num_windows: usize,
+        num_cycles: u64,
+        logical_cycle_time: u64,
+        num_ts_per_rotation: u64,
+        tfactory: Option<FactoryCopies>,
+        ccz_factory: Option<FactoryCopies>,
+    ) -> Self {
+        let num_cycles = num_cycles.max(1);
+        let cycles_per_window = num_cycles.div_ceil((num_windows as u64).clamp(1, num_cycles));
+        let num_windows = num_cycles.div_ceil(cycles_per_window) as usize;
+
+        let mut active_qubits = vec![0; num_windows];
+        let mut tstates = vec![0.0; num_windows];
+        let mut ccz_states = vec![0.0; num_windows];
+
+        let mut weights = profile
+            .layers
+            .iter()
+            .map(|layer| LayerProfile::weight(layer, num_ts_per_rotation))
+            .collect::<Vec<_>>();
+        if weights.iter().all(|&weight| weight == 0) {
+            // distribute layers without non-Clifford operations evenly
+            weights.fill(1);
+        }
+        let total_weight = weights.iter().sum::<u64>() as f64;
+        let cycles_per_weight = num_cycles as f64 / total_weight;
+
+        let mut start = 0.0;
+        for (layer, weight) in profile.layers.iter().zip(weights) {
+            let end = start + weight as f64 * cycles_per_weight;
+
+            let layer_tstates = layer.t_count + layer.rotation_count * num_ts_per_rotation;
+            let layer_tstates = if ccz_factory.is_some() {
+                layer_tstates as f64
+            } else {
+                (layer_tstates + 4 * layer.ccz_count) as f64
+            };
+
+            let first_window = ((start as u64 / cycles_per_window) as usize).min(num_windows - 1);
+            for window in first_window..num_windows {
+                let window_start = (window as u64 * cycles_per_window) as f64;
+                if window_start >= end && window != first_window {
+                    break;
+                }
+                let window_end = window_start + cycles_per_window as f64;
+
+                let fraction = if end > start {
+                    (end.min(window_end) - start.max(window_start)) / (end - start)
+                } else {
+                    1.0
+                };
+
+                active_qubits[window] = active_qubits[window].max(layer.active_qubits);
+                tstates[window] += fraction * layer_tstates;
+                ccz_states[window] += fraction * layer.ccz_count as f64;
+            }
+
+            start = end;
+        }
+
+        let windows = (0..num_windows)
+            .map(|window| {
+                let start_cycle = window as u64 * cycles_per_window;
+                let window_duration = ((start_cycle + cycles_per_window).min(num_cycles)
+                    - start_cycle)
+                    * logical_cycle_time;
+
+                SpaceTimeWindow {
+                    start_cycle,
+                    active_qubits: active_qubits[window],
+                    tstates: tstates[window].round() as u64,
+                    active_tfactories: tfactory
+                        .map_or(0, |f| f.active(tstates[window], window_duration)),
+                    ccz_states: ccz_factory.map(|_| ccz_states[window].round() as u64),
+                    active_ccz_factories: ccz_factory
+                        .map(|f| f.active(ccz_states[window], window_duration)),
+                }
+            })
+            .collect();
+
+        Self {
+            cycles_per_window,
+            windows,
+        }
+    }
+}
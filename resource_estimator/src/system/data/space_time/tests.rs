@@ -0,0 +1,113 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{FactoryCopies, LayerCounts, LayerProfile, SpaceTimeDiagram};
+
+fn layer(t_count: u64, rotation_count: u64, ccz_count: u64, active_qubits: u64) -> LayerCounts {
+    LayerCounts {
+        t_count,
+        rotation_count,
+        ccz_count,
+        active_qubits,
+    }
+}
+
+#[test]
+fn layers_are_distributed_by_weight() {
+    // weights are 2, 2, and 4 Pauli measurements
+    let profile = LayerProfile::new([layer(2, 0, 0, 3), layer(0, 2, 0, 5), layer(4, 0, 0, 2)]);
+    let tfactory = FactoryCopies {
+        copies: 10,
+        duration: 1_000,
+        num_output_states: 1,
+    };
+
+    let diagram = SpaceTimeDiagram::new(&profile, 4, 80, 100, 0, Some(tfactory), None);
+
+    assert_eq!(diagram.cycles_per_window, 20);
+    assert_eq!(diagram.windows.len(), 4);
+    assert_eq!(
+        diagram
+            .windows
+            .iter()
+            .map(|window| window.active_qubits)
+            .collect::<Vec<_>>(),
+        vec![3, 5, 2, 2]
+    );
+    assert_eq!(
+        diagram
+            .windows
+            .iter()
+            .map(|window| window.tstates)
+            .collect::<Vec<_>>(),
+        vec![2, 0, 2, 2]
+    );
+    // each window of 2,000ns allows for two runs per factory copy
+    assert_eq!(
+        diagram
+            .windows
+            .iter()
+            .map(|window| window.active_tfactories)
+            .collect::<Vec<_>>(),
+        vec![1, 0, 1, 1]
+    );
+    assert!(diagram.windows.iter().all(|w| w.ccz_states.is_none()));
+}
+
+#[test]
+fn rotations_consume_synthesized_t_states() {
+    let profile = LayerProfile::new([layer(0, 3, 0, 4), layer(2, 1, 0, 4)]);
+    let tfactory = FactoryCopies {
+        copies: 2,
+        duration: 1_000,
+        num_output_states: 1,
+    };
+
+    let diagram = SpaceTimeDiagram::new(&profile, 2, 20, 10, 7, Some(tfactory), None);
+
+    // weights are 3 + 7 and 2 + 1 + 7 Pauli measurements
+    assert_eq!(diagram.cycles_per_window, 10);
+    assert_eq!(diagram.windows[0].tstates, 21);
+    assert_eq!(diagram.windows[1].tstates, 9);
+    // factory copies are capped by the number of factories
+    assert_eq!(diagram.windows[0].active_tfactories, 2);
+}
+
+#[test]
+fn ccz_states_use_ccz_factories() {
+    let profile = LayerProfile::new([layer(1, 0, 2, 6)]);
+    let factory = FactoryCopies {
+        copies: 4,
+        duration: 100,
+        num_output_states: 1,
+    };
+
+    let with_ccz = SpaceTimeDiagram::new(&profile, 1, 7, 100, 0, Some(factory), Some(factory));
+    assert_eq!(with_ccz.windows.len(), 1);
+    assert_eq!(with_ccz.windows[0].tstates, 1);
+    assert_eq!(with_ccz.windows[0].ccz_states, Some(2));
+    assert_eq!(with_ccz.windows[0].active_ccz_factories, Some(1));
+
+    let without_ccz = SpaceTimeDiagram::new(&profile, 1, 7, 100, 0, Some(factory), None);
+    assert_eq!(without_ccz.windows[0].tstates, 9);
+    assert_eq!(without_ccz.windows[0].ccz_states, None);
+}
+
+#[test]
+fn number_of_windows_is_bounded_by_cycles() {
+    let profile = LayerProfile::new([layer(0, 0, 0, 1), layer(0, 0, 0, 2)]);
+
+    let diagram = SpaceTimeDiagram::new(&profile, 100, 3, 100, 0, None, None);
+
+    assert_eq!(diagram.cycles_per_window, 1);
+    assert_eq!(diagram.windows.len(), 3);
+    assert_eq!(
+        diagram
+            .windows
+            .iter()
+            .map(|window| window.active_qubits)
+            .collect::<Vec<_>>(),
+        vec![1, 2, 2]
+    );
+    assert!(diagram.windows.iter().all(|w| w.active_tfactories == 0));
+}
@@ -8,7 +8,7 @@ use super::super::{
     compiled_expression::CompiledExpression,
     error::IO::{self, CannotParseJSON},
     modeling::{
-        TFactoryDistillationUnitResources, TFactoryDistillationUnitTemplate,
+        MagicStateType, TFactoryDistillationUnitResources, TFactoryDistillationUnitTemplate,
         TFactoryDistillationUnitType, TFactoryFormula,
     },
     Error, Result,
@@ -165,6 +165,8 @@ impl TryFrom<&TFactoryDistillationUnitSpecification> for TFactoryDistillationUni
                     physical_qubit_specification,
                     logical_qubit_specification,
                     logical_qubit_specification_first_round_override,
+                    output_state: MagicStateType::T,
+                    requires_accurate_cliffords: true,
                 })
             }
         }
@@ -63,6 +63,28 @@ pub enum InvalidInput {
         "Qsc.Estimates.InvalidInputError.NonPositivePhysicalQubitsPerLogicalQubit"
     ))]
     NonPositivePhysicalQubitsPerLogicalQubit(u64),
+    /// Number of logical qubits per code block is non-positive for some code
+    /// distance value
+    ///
+    /// ✅ This does not contain user data and can be logged
+    /// 🧑‍💻 This indicates a user error
+    #[error("logicalQubitsPerCodeBlock formula yields non-positive value for code distance = {0}")]
+    #[diagnostic(code("Qsc.Estimates.InvalidInputError.NonPositiveLogicalQubitsPerCodeBlock"))]
+    NonPositiveLogicalQubitsPerCodeBlock(u64),
+    /// Logical error rate is not a probability for some code distance value
+    ///
+    /// ✅ This does not contain user data and can be logged
+    /// 🧑‍💻 This indicates a user error
+    #[error("logicalErrorRate formula yields value outside of [0, 1] for code distance = {0}")]
+    #[diagnostic(code("Qsc.Estimates.InvalidInputError.InvalidLogicalErrorRate"))]
+    InvalidLogicalErrorRate(u64),
+    /// A code parameter is not part of the specified code parameters
+    ///
+    /// ✅ This does not contain user data and can be logged
+    /// 🐞 We want this to be tracked as error and investigate
+    #[error("code parameter {0} is not part of the code parameters")]
+    #[diagnostic(code("Qsc.Estimates.InvalidInputError.UnknownCodeParameter"))]
+    UnknownCodeParameter(u64),
     /// Invalid error budget (<= 0.0 or >= 1.0)
     ///
     /// ✅ This does not contain user data and can be logged
@@ -81,6 +103,16 @@ pub enum InvalidInput {
         "Qsc.Estimates.InvalidInputError.ConstraintsProvidedForFrontierEstimation"
     ))]
     ConstraintsProvidedForFrontierEstimation,
+    /// Distillation units that produce CCZ states are only supported for
+    /// single-point estimation without maximal time or maximal number of
+    /// qubits, since the frontier and the constrained estimations search for a
+    /// single magic state factory.
+    ///
+    /// ✅ This does not contain user data and can be logged
+    /// 🧑‍💻 This indicates a user error
+    #[error("distillation units that produce CCZ states are not supported for frontier estimation or with maxDuration or maxPhysicalQubits constraints")]
+    #[diagnostic(code("Qsc.Estimates.InvalidInputError.UnsupportedCczDistillationUnits"))]
+    UnsupportedCczDistillationUnits,
 }
 
 #[derive(Debug, Error, Diagnostic)]
@@ -2,8 +2,10 @@
 // Licensed under the MIT License.
 
 mod fault_tolerance;
+mod layout;
 mod physical_qubit;
 mod tfactory;
 pub use fault_tolerance::*;
+pub use layout::*;
 pub use physical_qubit::*;
 pub use tfactory::*;
@@ -8,7 +8,10 @@ use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
 use crate::estimates::{
-    Error::{LogicalCycleTimeComputationFailed, PhysicalQubitComputationFailed},
+    Error::{
+        LogicalCycleTimeComputationFailed, LogicalErrorRateComputationFailed,
+        LogicalQubitComputationFailed, PhysicalQubitComputationFailed,
+    },
     ErrorCorrection,
 };
 
@@ -16,15 +19,17 @@ use super::{
     super::{
         compiled_expression::CompiledExpression,
         constants::{
-            IDLE_ERROR_RATE, MAX_CODE_DISTANCE, ONE_QUBIT_GATE_ERROR_RATE, ONE_QUBIT_GATE_TIME,
+            CODE_DISTANCE, CROSSING_PREFACTOR, ERROR_CORRECTION_THRESHOLD, IDLE_ERROR_RATE,
+            MAX_CODE_DISTANCE, ONE_QUBIT_GATE_ERROR_RATE, ONE_QUBIT_GATE_TIME,
             ONE_QUBIT_MEASUREMENT_PROCESS_ERROR_RATE, ONE_QUBIT_MEASUREMENT_TIME,
-            TWO_QUBIT_GATE_ERROR_RATE, TWO_QUBIT_GATE_TIME,
+            PHYSICAL_ERROR_RATE, TWO_QUBIT_GATE_ERROR_RATE, TWO_QUBIT_GATE_TIME,
             TWO_QUBIT_JOINT_MEASUREMENT_PROCESS_ERROR_RATE, TWO_QUBIT_JOINT_MEASUREMENT_TIME,
         },
         error::{
             InvalidInput::{
-                InvalidFaultToleranceProtocol, NonPositiveLogicalCycleTime,
-                NonPositivePhysicalQubitsPerLogicalQubit,
+                InvalidFaultToleranceProtocol, InvalidLogicalErrorRate,
+                NonPositiveLogicalCycleTime, NonPositiveLogicalQubitsPerCodeBlock,
+                NonPositivePhysicalQubitsPerLogicalQubit, UnknownCodeParameter,
             },
             IO::CannotParseJSON,
         },
@@ -46,10 +51,54 @@ pub struct ProtocolSpecification {
     pub(crate) logical_cycle_time: Option<String>,
     #[serde(default)]
     pub(crate) physical_qubits_per_logical_qubit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) logical_qubits_per_code_block: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) logical_error_rate: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) code_parameters: Vec<BTreeMap<String, f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) code_parameter_order: Option<String>,
     #[serde(default = "Protocol::default_max_code_distance")]
     pub(crate) max_code_distance: u64,
 }
 
+impl ProtocolSpecification {
+    /// Returns true, if the logical error rate or the code parameters are
+    /// user-specified, i.e., the code is not modeled by the default error
+    /// model over odd code distances.
+    pub(crate) fn has_custom_error_model(&self) -> bool {
+        self.logical_error_rate.is_some() || !self.code_parameters.is_empty()
+    }
+
+    /// Returns the code distance of a code parameter, i.e., the code distance
+    /// that is assigned to it, if code parameters are specified.  Other code
+    /// parameters, such as 1 for physical qubits in T factories, are code
+    /// distances themselves.
+    pub(crate) fn code_distance(&self, code_parameter: u64) -> u64 {
+        code_distance(&self.code_parameters, code_parameter)
+    }
+}
+
+/// Code parameters of specified code parameter domains refer to the variable
+/// assignments by their index, starting from this value, since code parameter
+/// 1 refers to physical qubits in T factories.
+const FIRST_ASSIGNED_CODE_PARAMETER: u64 = 2;
+
+fn code_parameter_assignment(
+    code_parameters: &[BTreeMap<String, f64>],
+    code_parameter: u64,
+) -> Option<&BTreeMap<String, f64>> {
+    let index = code_parameter.checked_sub(FIRST_ASSIGNED_CODE_PARAMETER)?;
+    code_parameters.get(usize::try_from(index).ok()?)
+}
+
+fn code_distance(code_parameters: &[BTreeMap<String, f64>], code_parameter: u64) -> u64 {
+    code_parameter_assignment(code_parameters, code_parameter)
+        .and_then(|assignment| assignment.get(CODE_DISTANCE))
+        .map_or(code_parameter, |&code_distance| code_distance as u64)
+}
+
 impl Default for ProtocolSpecification {
     fn default() -> Self {
         Self {
@@ -58,6 +107,10 @@ impl Default for ProtocolSpecification {
             error_correction_threshold: None,
             logical_cycle_time: None,
             physical_qubits_per_logical_qubit: None,
+            logical_qubits_per_code_block: None,
+            logical_error_rate: None,
+            code_parameters: vec![],
+            code_parameter_order: None,
             max_code_distance: Protocol::default_max_code_distance(),
         }
     }
@@ -79,6 +132,29 @@ impl Default for ProtocolSpecification {
 ///
 /// Note that all physical qubit related variables are not available as variable
 /// in formulas for `physical_qubits_per_logical_qubit`.
+///
+/// Codes other than the surface code and the floquet code can be modeled by
+/// additionally specifying
+///
+/// - `logical_qubits_per_code_block`, the number of logical qubits encoded in
+///   one code block of `physical_qubits_per_logical_qubit` physical qubits
+///   (default: 1),
+/// - `logical_error_rate`, the logical error rate of one code block, in which
+///   additionally the variables `physicalErrorRate`,
+///   `errorCorrectionThreshold`, and `crossingPrefactor` are available
+///   (default: `crossingPrefactor * (physicalErrorRate /
+///   errorCorrectionThreshold)^((codeDistance + 1) / 2)`), and
+/// - `code_parameters`, a list of variable assignments, each of which assigns
+///   a code distance to `codeDistance`, and values to further variables that
+///   are available in all formulas, e.g., the distances of rectangular patches
+///   or the block size of a qLDPC code (default: all odd code distances up to
+///   `max_code_distance`), and
+/// - `code_parameter_order`, a formula over the variables of an assignment by
+///   which the code parameters are ordered from the weakest to the strongest
+///   code (default: `codeDistance`).
+///
+/// Each assignment is a code parameter of its own, such that several
+/// assignments may share a code distance.
 #[derive(Debug)]
 pub struct Protocol {
     error_correction_threshold: f64,
@@ -87,6 +163,11 @@ pub struct Protocol {
     logical_cycle_time: CompiledExpression,
     physical_qubits_per_logical_qubit_expr: String,
     physical_qubits_per_logical_qubit: CompiledExpression,
+    logical_qubits_per_code_block: Option<CompiledExpression>,
+    logical_error_rate: Option<CompiledExpression>,
+    /// Variable assignments of the code parameters, ordered by
+    /// `code_parameter_order`
+    code_parameters: Vec<BTreeMap<String, f64>>,
     max_code_distance: u64,
 }
 
@@ -108,6 +189,8 @@ impl Protocol {
             ftp.update_default_from_specification(model)?;
         }
 
+        ftp.update_error_model_from_specification(model)?;
+
         if ftp.crossing_prefactor > 0.5 {
             return Err(Error::InvalidValue(
                 String::from("crossingPrefactor"),
@@ -141,12 +224,23 @@ impl Protocol {
         }
 
         // validate that formulas only yield positive values
-        for code_distance in (1..=model.max_code_distance).skip(2) {
+        let code_parameters: Vec<u64> = if ftp.code_parameters.is_empty() {
+            (1..=model.max_code_distance).skip(2).collect()
+        } else {
+            ftp.code_parameter_range(None).collect()
+        };
+        for code_parameter in code_parameters {
             // can you compute logical cycle time and number of physical qubits with code distance?
-            ftp.logical_cycle_time(qubit, &code_distance)
+            ftp.logical_cycle_time(qubit, &code_parameter)
                 .map_err(LogicalCycleTimeComputationFailed)?;
-            ftp.physical_qubits(&code_distance)
+            ftp.physical_qubits(&code_parameter)
                 .map_err(PhysicalQubitComputationFailed)?;
+            ftp.logical_qubits(&code_parameter)
+                .map_err(LogicalQubitComputationFailed)?;
+            if ftp.logical_error_rate.is_some() {
+                ftp.logical_error_rate(qubit, &code_parameter)
+                    .map_err(LogicalErrorRateComputationFailed)?;
+            }
         }
 
         Ok(ftp)
@@ -191,7 +285,7 @@ impl Protocol {
                 .clone();
 
             let physical_qubits_per_logical_qubit_expr = model
-                .logical_cycle_time
+                .physical_qubits_per_logical_qubit
                 .as_ref()
                 .ok_or_else(|| {
                     CannotParseJSON(serde::de::Error::missing_field(
@@ -216,6 +310,9 @@ impl Protocol {
                     logical_cycle_time,
                     physical_qubits_per_logical_qubit_expr,
                     physical_qubits_per_logical_qubit,
+                    logical_qubits_per_code_block: None,
+                    logical_error_rate: None,
+                    code_parameters: vec![],
                     max_code_distance,
                 },
                 false,
@@ -267,6 +364,69 @@ impl Protocol {
         Ok(())
     }
 
+    /// Updates the number of logical qubits per code block, the logical error
+    /// rate, and the code parameters from `model`, if specified.  The code
+    /// parameters in `model` are reordered by `code_parameter_order`, such
+    /// that they match the order of the code parameters in `self`.
+    fn update_error_model_from_specification(
+        &mut self,
+        model: &mut ProtocolSpecification,
+    ) -> crate::system::Result<()> {
+        if let Some(logical_qubits_per_code_block) = model.logical_qubits_per_code_block.as_ref() {
+            self.logical_qubits_per_code_block = Some(CompiledExpression::from_string(
+                logical_qubits_per_code_block,
+                "logicalQubitsPerCodeBlock",
+            )?);
+        }
+
+        if let Some(logical_error_rate) = model.logical_error_rate.as_ref() {
+            self.logical_error_rate = Some(CompiledExpression::from_string(
+                logical_error_rate,
+                "logicalErrorRate",
+            )?);
+        }
+
+        let code_parameter_order = CompiledExpression::from_string(
+            model
+                .code_parameter_order
+                .as_deref()
+                .unwrap_or(CODE_DISTANCE),
+            "codeParameterOrder",
+        )?;
+
+        let mut code_parameters = Vec::with_capacity(model.code_parameters.len());
+        for assignment in &model.code_parameters {
+            let code_distance = *assignment
+                .get(CODE_DISTANCE)
+                .ok_or_else(|| CannotParseJSON(serde::de::Error::missing_field(CODE_DISTANCE)))?;
+
+            if code_distance < 1.0
+                || code_distance.fract() != 0.0
+                || code_distance > model.max_code_distance as f64
+            {
+                return Err(Error::InvalidValue(
+                    String::from(CODE_DISTANCE),
+                    1.0,
+                    model.max_code_distance as f64,
+                ));
+            }
+
+            let order = code_parameter_order.evaluate(&mut assignment.clone())?;
+            code_parameters.push((order, assignment.clone()));
+        }
+
+        // The sort is stable, such that assignments of the same order keep
+        // their specified order.
+        code_parameters.sort_by(|(order1, _), (order2, _)| order1.total_cmp(order2));
+        self.code_parameters = code_parameters
+            .into_iter()
+            .map(|(_, assignment)| assignment)
+            .collect();
+        model.code_parameters.clone_from(&self.code_parameters);
+
+        Ok(())
+    }
+
     /// Default floquet code FTP for gate based qubits
     ///
     /// ```yaml
@@ -308,6 +468,9 @@ impl Protocol {
             logical_cycle_time,
             physical_qubits_per_logical_qubit_expr,
             physical_qubits_per_logical_qubit,
+            logical_qubits_per_code_block: None,
+            logical_error_rate: None,
+            code_parameters: vec![],
             max_code_distance: Self::default_max_code_distance(),
         }
     }
@@ -347,6 +510,9 @@ impl Protocol {
             logical_cycle_time,
             physical_qubits_per_logical_qubit_expr,
             physical_qubits_per_logical_qubit,
+            logical_qubits_per_code_block: None,
+            logical_error_rate: None,
+            code_parameters: vec![],
             max_code_distance: Self::default_max_code_distance(),
         }
     }
@@ -385,6 +551,9 @@ impl Protocol {
             logical_cycle_time,
             physical_qubits_per_logical_qubit_expr,
             physical_qubits_per_logical_qubit,
+            logical_qubits_per_code_block: None,
+            logical_error_rate: None,
+            code_parameters: vec![],
             max_code_distance: Self::default_max_code_distance(),
         }
     }
@@ -403,12 +572,19 @@ impl Protocol {
     /// protocol files.
     ///
     /// Based on whether the formula can contain qubit or code distance values,
-    /// additional values are provided in the context.
+    /// additional values are provided in the context.  If code parameters are
+    /// specified, the context contains all variables assigned to the code
+    /// parameter, otherwise the code parameter is the code distance.
     fn create_evaluation_context(
+        &self,
         qubit: Option<&PhysicalQubit>,
-        code_distance: u64,
-    ) -> BTreeMap<String, f64> {
-        let mut context = BTreeMap::new();
+        code_parameter: u64,
+    ) -> Result<BTreeMap<String, f64>, String> {
+        let mut context = if self.code_parameters.is_empty() {
+            BTreeMap::from([(CODE_DISTANCE.to_string(), code_parameter as f64)])
+        } else {
+            self.code_parameter_assignment(code_parameter)?.clone()
+        };
 
         if let Some(qubit) = qubit {
             context.insert(
@@ -434,9 +610,41 @@ impl Protocol {
             }
         }
 
-        context.insert("codeDistance".to_string(), code_distance as f64);
+        Ok(context)
+    }
+
+    fn code_parameter_assignment(
+        &self,
+        code_parameter: u64,
+    ) -> Result<&BTreeMap<String, f64>, String> {
+        code_parameter_assignment(&self.code_parameters, code_parameter)
+            .ok_or_else(|| UnknownCodeParameter(code_parameter).to_string())
+    }
+
+    /// Returns the code distance of a code parameter, see
+    /// [`ProtocolSpecification::code_distance`].
+    fn code_distance(&self, code_parameter: u64) -> u64 {
+        code_distance(&self.code_parameters, code_parameter)
+    }
 
-        context
+    /// The logical error rate of the default error model
+    fn default_logical_error_rate(
+        &self,
+        physical_error_rate: f64,
+        code_distance: u64,
+    ) -> Result<f64, String> {
+        if physical_error_rate > self.error_correction_threshold {
+            Err(Error::InvalidValue(
+                String::from("physical_error_rate"),
+                0.0,
+                self.error_correction_threshold,
+            )
+            .to_string())
+        } else {
+            Ok(self.crossing_prefactor
+                * ((physical_error_rate / self.error_correction_threshold)
+                    .powi((code_distance as i32 + 1) / 2)))
+        }
     }
 
     fn parse_compiled_expressions(
@@ -472,23 +680,44 @@ impl ErrorCorrection for Protocol {
     ///
     /// The formula for this field has a default value of `2 * code_distance *
     /// code_distance`.
-    fn physical_qubits(&self, code_distance: &u64) -> Result<u64, String> {
-        let mut context = Self::create_evaluation_context(None, *code_distance);
+    fn physical_qubits(&self, code_parameter: &u64) -> Result<u64, String> {
+        let mut context = self.create_evaluation_context(None, *code_parameter)?;
         let value = self
             .physical_qubits_per_logical_qubit
             .evaluate(&mut context)
             .map_err(|err| err.to_string())?;
 
         if value <= 0.0 {
-            Err(NonPositivePhysicalQubitsPerLogicalQubit(*code_distance).to_string())
+            Err(
+                NonPositivePhysicalQubitsPerLogicalQubit(self.code_distance(*code_parameter))
+                    .to_string(),
+            )
         } else {
             Ok(value as u64)
         }
     }
 
-    /// The planar codes considered in this system encode 1 logical qubit
-    fn logical_qubits(&self, _code_parameter: &Self::Parameter) -> Result<u64, String> {
-        Ok(1)
+    /// The planar codes considered in this system encode 1 logical qubit,
+    /// unless specified otherwise by the `logical_qubits_per_code_block`
+    /// formula.
+    fn logical_qubits(&self, code_parameter: &u64) -> Result<u64, String> {
+        let Some(logical_qubits_per_code_block) = &self.logical_qubits_per_code_block else {
+            return Ok(1);
+        };
+
+        let mut context = self.create_evaluation_context(None, *code_parameter)?;
+        let value = logical_qubits_per_code_block
+            .evaluate(&mut context)
+            .map_err(|err| err.to_string())?;
+
+        if value < 1.0 {
+            Err(
+                NonPositiveLogicalQubitsPerCodeBlock(self.code_distance(*code_parameter))
+                    .to_string(),
+            )
+        } else {
+            Ok(value as u64)
+        }
     }
 
     /// Returns the time of one logical cycle.
@@ -501,9 +730,9 @@ impl ErrorCorrection for Protocol {
     fn logical_cycle_time(
         &self,
         qubit: &PhysicalQubit,
-        code_distance: &u64,
+        code_parameter: &u64,
     ) -> Result<u64, String> {
-        let mut context = Self::create_evaluation_context(Some(qubit), *code_distance);
+        let mut context = self.create_evaluation_context(Some(qubit), *code_parameter)?;
 
         let result = self
             .logical_cycle_time
@@ -511,7 +740,7 @@ impl ErrorCorrection for Protocol {
             .map_err(|err| err.to_string())?;
 
         if result <= 0.0 {
-            Err(NonPositiveLogicalCycleTime(*code_distance).to_string())
+            Err(NonPositiveLogicalCycleTime(self.code_distance(*code_parameter)).to_string())
         } else {
             Ok(result.round() as u64)
         }
@@ -520,25 +749,38 @@ impl ErrorCorrection for Protocol {
     /// Computes the logical failure probability.
     ///
     /// Computes the logical failure probability based on a physical error rate
-    /// and a code distance
+    /// and a code distance, either using the `logical_error_rate` formula, or
+    /// the default error model.
     fn logical_error_rate(
         &self,
         qubit: &PhysicalQubit,
-        code_distance: &u64,
+        code_parameter: &u64,
     ) -> Result<f64, String> {
         let physical_error_rate = qubit.clifford_error_rate().max(qubit.readout_error_rate());
 
-        if physical_error_rate > self.error_correction_threshold() {
-            Err(Error::InvalidValue(
-                String::from("physical_error_rate"),
-                0.0,
+        if let Some(logical_error_rate) = &self.logical_error_rate {
+            let mut context = self.create_evaluation_context(Some(qubit), *code_parameter)?;
+            context.insert(PHYSICAL_ERROR_RATE.to_string(), physical_error_rate);
+            context.insert(
+                ERROR_CORRECTION_THRESHOLD.to_string(),
                 self.error_correction_threshold,
-            )
-            .to_string())
+            );
+            context.insert(CROSSING_PREFACTOR.to_string(), self.crossing_prefactor);
+
+            let value = logical_error_rate
+                .evaluate(&mut context)
+                .map_err(|err| err.to_string())?;
+
+            if (0.0..=1.0).contains(&value) {
+                Ok(value)
+            } else {
+                Err(InvalidLogicalErrorRate(self.code_distance(*code_parameter)).to_string())
+            }
+        } else if !self.code_parameters.is_empty() {
+            let code_distance = self.code_parameter_assignment(*code_parameter)?[CODE_DISTANCE];
+            self.default_logical_error_rate(physical_error_rate, code_distance as u64)
         } else {
-            Ok(self.crossing_prefactor()
-                * ((physical_error_rate / self.error_correction_threshold())
-                    .powi((*code_distance as i32 + 1) / 2)))
+            self.default_logical_error_rate(physical_error_rate, *code_parameter)
         }
     }
 
@@ -548,6 +790,24 @@ impl ErrorCorrection for Protocol {
         qubit: &PhysicalQubit,
         required_logical_qubit_error_rate: f64,
     ) -> Result<u64, String> {
+        // There is no closed formula for user-specified error models, instead
+        // the first code parameter that achieves the required logical error
+        // rate is selected.
+        if self.logical_error_rate.is_some() || !self.code_parameters.is_empty() {
+            for code_parameter in self.code_parameter_range(None) {
+                if let (Ok(probability), Ok(logical_qubits)) = (
+                    self.logical_error_rate(qubit, &code_parameter),
+                    self.logical_qubits(&code_parameter),
+                ) {
+                    if probability / (logical_qubits as f64) <= required_logical_qubit_error_rate {
+                        return Ok(code_parameter);
+                    }
+                }
+            }
+
+            return Err(format!("No code distance achieves required logical error rate {required_logical_qubit_error_rate:.3e}; try increasing the total logical error budget"));
+        }
+
         let physical_error_rate = qubit.clifford_error_rate().max(qubit.readout_error_rate());
         let numerator = 2.0 * (self.crossing_prefactor() / required_logical_qubit_error_rate).ln();
         let denominator = (self.error_correction_threshold() / physical_error_rate).ln();
@@ -562,7 +822,16 @@ impl ErrorCorrection for Protocol {
     }
 
     fn code_parameter_range(&self, lower_bound: Option<&u64>) -> impl Iterator<Item = u64> {
-        (lower_bound.copied().unwrap_or(1)..=self.max_code_distance).step_by(2)
+        let lower_bound = lower_bound.copied().unwrap_or(1);
+
+        let code_parameters: Vec<_> = if self.code_parameters.is_empty() {
+            (lower_bound..=self.max_code_distance).step_by(2).collect()
+        } else {
+            let end = FIRST_ASSIGNED_CODE_PARAMETER + self.code_parameters.len() as u64;
+            (lower_bound.max(FIRST_ASSIGNED_CODE_PARAMETER)..end).collect()
+        };
+
+        code_parameters.into_iter()
     }
 
     fn code_parameter_cmp(&self, _qubit: &PhysicalQubit, p1: &u64, p2: &u64) -> std::cmp::Ordering {
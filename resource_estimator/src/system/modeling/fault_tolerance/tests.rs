@@ -3,7 +3,7 @@
 
 use crate::{
     estimates::ErrorCorrection,
-    system::modeling::{PhysicalQubit, Protocol},
+    system::modeling::{PhysicalQubit, Protocol, ProtocolSpecification},
 };
 
 #[test]
@@ -17,3 +17,171 @@ fn compute_code_distance() -> Result<(), String> {
 
     Ok(())
 }
+
+#[test]
+fn custom_protocol_uses_physical_qubits_formula() -> Result<(), String> {
+    let qubit = PhysicalQubit::default();
+    let mut specification: ProtocolSpecification = serde_json::from_str(
+        r#"{
+            "name": "custom",
+            "errorCorrectionThreshold": 0.01,
+            "crossingPrefactor": 0.03,
+            "logicalCycleTime": "(4 * twoQubitGateTime + 2 * oneQubitMeasurementTime) * codeDistance",
+            "physicalQubitsPerLogicalQubit": "3 * codeDistance * codeDistance"
+        }"#,
+    )
+    .map_err(|err| err.to_string())?;
+    let ftp = Protocol::load_from_specification(&mut specification, &qubit)
+        .map_err(|err| err.to_string())?;
+
+    assert_eq!(ftp.physical_qubits(&5)?, 75);
+    assert_eq!(ftp.logical_cycle_time(&qubit, &5)?, 2000);
+
+    Ok(())
+}
+
+#[test]
+fn custom_error_model_with_rectangular_patches() -> Result<(), String> {
+    let qubit = PhysicalQubit::default();
+    let mut specification: ProtocolSpecification = serde_json::from_str(
+        r#"{
+            "name": "surface_code",
+            "logicalErrorRate": "0.03 * (physicalErrorRate / 0.01)^((dx + 1) / 2) + 0.03 * (physicalErrorRate / 0.01)^((dz + 1) / 2)",
+            "physicalQubitsPerLogicalQubit": "2 * dx * dz",
+            "codeParameters": [
+                {"codeDistance": 3, "dx": 3, "dz": 5},
+                {"codeDistance": 5, "dx": 5, "dz": 7},
+                {"codeDistance": 7, "dx": 7, "dz": 9}
+            ]
+        }"#,
+    )
+    .map_err(|err| err.to_string())?;
+    let ftp = Protocol::load_from_specification(&mut specification, &qubit)
+        .map_err(|err| err.to_string())?;
+
+    // Code parameters refer to the assignments by their index
+    assert_eq!(
+        ftp.code_parameter_range(None).collect::<Vec<_>>(),
+        [2, 3, 4]
+    );
+    assert_eq!(
+        ftp.code_parameter_range(Some(&3)).collect::<Vec<_>>(),
+        [3, 4]
+    );
+    assert_eq!(ftp.code_distance(3), 5);
+    assert_eq!(ftp.physical_qubits(&3)?, 70);
+    assert!(
+        (ftp.logical_error_rate(&qubit, &3)? - (0.03 * 0.1_f64.powi(3) + 0.03 * 0.1_f64.powi(4)))
+            .abs()
+            <= f64::EPSILON
+    );
+    assert_eq!(ftp.compute_code_parameter(&qubit, 1e-4)?, 3);
+    assert!(ftp.logical_error_rate(&qubit, &5).is_err());
+    assert!(ftp.compute_code_parameter(&qubit, 1e-30).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn custom_error_model_with_multiple_logical_qubits_per_code_block() -> Result<(), String> {
+    let qubit = PhysicalQubit::default();
+    let mut specification: ProtocolSpecification = serde_json::from_str(
+        r#"{
+            "name": "qldpc",
+            "errorCorrectionThreshold": 0.007,
+            "crossingPrefactor": 0.1,
+            "logicalCycleTime": "(4 * twoQubitGateTime + 2 * oneQubitMeasurementTime) * codeDistance",
+            "physicalQubitsPerLogicalQubit": "2 * n",
+            "logicalQubitsPerCodeBlock": "k",
+            "logicalErrorRate": "k * crossingPrefactor * (physicalErrorRate / errorCorrectionThreshold)^(codeDistance / 2)",
+            "codeParameters": [
+                {"codeDistance": 6, "n": 72, "k": 12},
+                {"codeDistance": 12, "n": 144, "k": 12}
+            ]
+        }"#,
+    )
+    .map_err(|err| err.to_string())?;
+    let ftp = Protocol::load_from_specification(&mut specification, &qubit)
+        .map_err(|err| err.to_string())?;
+
+    assert_eq!(ftp.physical_qubits(&3)?, 288);
+    assert_eq!(ftp.logical_qubits(&3)?, 12);
+    assert_eq!(ftp.logical_cycle_time(&qubit, &2)?, 2400);
+    assert_eq!(ftp.compute_code_parameter(&qubit, 1e-3)?, 2);
+    assert_eq!(ftp.compute_code_parameter(&qubit, 1e-6)?, 3);
+
+    Ok(())
+}
+
+#[test]
+fn custom_error_model_with_equal_code_distances() -> Result<(), String> {
+    let qubit = PhysicalQubit::default();
+    let mut specification: ProtocolSpecification = serde_json::from_str(
+        r#"{
+            "name": "surface_code",
+            "logicalErrorRate": "0.03 * (physicalErrorRate / 0.01)^((dx + 1) / 2) + 0.03 * (physicalErrorRate / 0.01)^((dz + 1) / 2)",
+            "physicalQubitsPerLogicalQubit": "2 * dx * dz",
+            "codeParameters": [
+                {"codeDistance": 3, "dx": 3, "dz": 5},
+                {"codeDistance": 3, "dx": 5, "dz": 3},
+                {"codeDistance": 5, "dx": 5, "dz": 5}
+            ]
+        }"#,
+    )
+    .map_err(|err| err.to_string())?;
+    let ftp = Protocol::load_from_specification(&mut specification, &qubit)
+        .map_err(|err| err.to_string())?;
+
+    assert_eq!(
+        ftp.code_parameter_range(None)
+            .map(|code_parameter| ftp.code_distance(code_parameter))
+            .collect::<Vec<_>>(),
+        [3, 3, 5]
+    );
+    assert_eq!(ftp.physical_qubits(&2)?, 30);
+    assert_eq!(ftp.physical_qubits(&3)?, 30);
+    assert_eq!(ftp.compute_code_parameter(&qubit, 1e-4)?, 4);
+
+    Ok(())
+}
+
+#[test]
+fn custom_error_model_with_code_parameter_order() -> Result<(), String> {
+    let qubit = PhysicalQubit::default();
+    let mut specification: ProtocolSpecification = serde_json::from_str(
+        r#"{
+            "name": "surface_code",
+            "logicalErrorRate": "0.03 * (physicalErrorRate / 0.01)^((dx + 1) / 2) + 0.03 * (physicalErrorRate / 0.01)^((dz + 1) / 2)",
+            "physicalQubitsPerLogicalQubit": "2 * dx * dz",
+            "codeParameterOrder": "2 * dx * dz",
+            "codeParameters": [
+                {"codeDistance": 7, "dx": 7, "dz": 7},
+                {"codeDistance": 5, "dx": 5, "dz": 11},
+                {"codeDistance": 5, "dx": 5, "dz": 7}
+            ]
+        }"#,
+    )
+    .map_err(|err| err.to_string())?;
+    let ftp = Protocol::load_from_specification(&mut specification, &qubit)
+        .map_err(|err| err.to_string())?;
+
+    // The assignments are ordered by the number of physical qubits, and the
+    // specification reports them in that order
+    assert_eq!(
+        specification
+            .code_parameters
+            .iter()
+            .map(|assignment| assignment["dz"])
+            .collect::<Vec<_>>(),
+        [7.0, 7.0, 11.0]
+    );
+    assert_eq!(
+        ftp.code_parameter_range(None)
+            .map(|code_parameter| ftp.physical_qubits(&code_parameter))
+            .collect::<Result<Vec<_>, _>>()?,
+        [70, 98, 110]
+    );
+    assert_eq!(ftp.compute_code_parameter(&qubit, 1e-5)?, 3);
+
+    Ok(())
+}
@@ -0,0 +1,209 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::{
+    estimates::{ErrorBudget, Overhead},
+    system::{
+        constants::COMPACT_BLOCK_CYCLES_PER_MEASUREMENT,
+        data::{LayoutReportData, PartitioningOverhead},
+        error::Error,
+        modeling::MagicStateType,
+    },
+};
+
+/// Layout method to map the logical qubits of the input algorithm onto a 2D
+/// grid of logical qubits
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(
+    tag = "name",
+    rename_all(serialize = "snake_case", deserialize = "snake_case"),
+    deny_unknown_fields
+)]
+pub enum Layout {
+    /// Parallel Synthesis Sequential Pauli Computation
+    /// [arXiv:2211.07629](https://arxiv.org/abs/2211.07629)
+    #[default]
+    Psspc,
+    /// Lattice surgery with a configurable number of routing qubits per
+    /// logical qubit; multi-qubit Pauli measurements take longer the less
+    /// routing space is available
+    #[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+    LatticeSurgery {
+        #[serde(default = "Layout::default_routing_overhead")]
+        routing_overhead: f64,
+    },
+    /// Compact block [arXiv:1808.02892](https://arxiv.org/abs/1808.02892),
+    /// which trades fewer logical qubits for more logical cycles per
+    /// multi-qubit Pauli measurement
+    CompactBlock,
+}
+
+impl Layout {
+    fn default_routing_overhead() -> f64 {
+        1.0
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn validate(&self) -> crate::system::Result<()> {
+        match *self {
+            Self::LatticeSurgery { routing_overhead }
+                if !(routing_overhead > 0.0 && routing_overhead.is_finite()) =>
+            {
+                Err(Error::InvalidValue(
+                    String::from("routingOverhead"),
+                    0.0,
+                    f64::INFINITY,
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The number of logical qubits after layout for `num_qubits` logical
+    /// qubits in the input algorithm, or `None` for the PSSPC layout, for
+    /// which this is computed by the logical resource counts
+    pub fn logical_qubits(&self, num_qubits: u64) -> Option<u64> {
+        match *self {
+            Self::Psspc => None,
+            Self::LatticeSurgery { routing_overhead } => {
+                Some(num_qubits + (routing_overhead * num_qubits as f64).ceil() as u64)
+            }
+            Self::CompactBlock => Some((3 * num_qubits).div_ceil(2) + 3),
+        }
+    }
+
+    /// The number of logical cycles to execute a multi-qubit Pauli
+    /// measurement, or `None` for the PSSPC layout, for which the logical
+    /// depth is computed by the logical resource counts
+    pub fn cycles_per_measurement(&self) -> Option<u64> {
+        match *self {
+            Self::Psspc => None,
+            Self::LatticeSurgery { routing_overhead } => {
+                Some((1.0 / routing_overhead).ceil().max(1.0) as u64)
+            }
+            Self::CompactBlock => Some(COMPACT_BLOCK_CYCLES_PER_MEASUREMENT),
+        }
+    }
+}
+
+/// Logical overhead of the logical resources of an algorithm after layout
+pub struct LayoutOverhead<L> {
+    logical_resources: Rc<L>,
+    layout: Layout,
+    ccz_magic_states: bool,
+}
+
+impl<L> LayoutOverhead<L> {
+    pub fn new(logical_resources: Rc<L>, layout: Layout) -> Self {
+        Self {
+            logical_resources,
+            layout,
+            ccz_magic_states: false,
+        }
+    }
+
+    /// If set, CCZ gates consume CCZ states as second magic state type
+    /// instead of four T states each
+    pub fn set_ccz_magic_states(&mut self, ccz_magic_states: bool) {
+        self.ccz_magic_states = ccz_magic_states;
+    }
+}
+
+impl<L: Overhead + LayoutReportData> Overhead for LayoutOverhead<L> {
+    fn logical_qubits(&self) -> u64 {
+        self.layout
+            .logical_qubits(self.logical_resources.num_qubits())
+            .unwrap_or_else(|| self.logical_resources.logical_qubits())
+    }
+
+    fn logical_depth(&self, budget: &ErrorBudget) -> u64 {
+        match self.layout.cycles_per_measurement() {
+            Some(cycles) => cycles * self.logical_resources.num_pauli_measurements(budget),
+            None => self.logical_resources.logical_depth(budget),
+        }
+    }
+
+    fn num_magic_states(&self, budget: &ErrorBudget, index: usize) -> u64 {
+        if !self.ccz_magic_states {
+            return self.logical_resources.num_magic_states(budget, index);
+        }
+
+        let num_ccz_states = self.logical_resources.ccz_count();
+        if index == MagicStateType::Ccz.index() {
+            num_ccz_states
+        } else {
+            self.logical_resources
+                .num_magic_states(budget, index)
+                .saturating_sub(4 * num_ccz_states)
+        }
+    }
+}
+
+impl<L: LayoutReportData> LayoutReportData for LayoutOverhead<L> {
+    fn num_qubits(&self) -> u64 {
+        self.logical_resources.num_qubits()
+    }
+
+    fn t_count(&self) -> u64 {
+        self.logical_resources.t_count()
+    }
+
+    fn rotation_count(&self) -> u64 {
+        self.logical_resources.rotation_count()
+    }
+
+    fn rotation_depth(&self) -> u64 {
+        self.logical_resources.rotation_depth()
+    }
+
+    fn ccz_count(&self) -> u64 {
+        self.logical_resources.ccz_count()
+    }
+
+    fn ccix_count(&self) -> u64 {
+        self.logical_resources.ccix_count()
+    }
+
+    fn measurement_count(&self) -> u64 {
+        self.logical_resources.measurement_count()
+    }
+
+    fn num_ts_per_rotation(&self, eps_synthesis: f64) -> Option<u64> {
+        self.logical_resources.num_ts_per_rotation(eps_synthesis)
+    }
+
+    fn num_pauli_measurements(&self, budget: &ErrorBudget) -> u64 {
+        self.logical_resources.num_pauli_measurements(budget)
+    }
+}
+
+impl<L: PartitioningOverhead> PartitioningOverhead for LayoutOverhead<L> {
+    fn has_tgates(&self) -> bool {
+        self.logical_resources.has_tgates()
+    }
+
+    fn has_rotations(&self) -> bool {
+        self.logical_resources.has_rotations()
+    }
+}
+
+// The layout is part of the job parameters, therefore only the logical
+// resources are serialized.
+impl<L: Serialize> Serialize for LayoutOverhead<L> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.logical_resources.serialize(serializer)
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use std::rc::Rc;
+
+use crate::{
+    estimates::{ErrorBudget, Overhead},
+    system::data::LogicalResourceCounts,
+};
+
+use super::{Layout, LayoutOverhead};
+
+fn load_layout_from_json_string(data: &str) -> Layout {
+    serde_json::from_str(data).expect("test json should be parsable")
+}
+
+fn logical_resources() -> Rc<LogicalResourceCounts> {
+    Rc::new(LogicalResourceCounts {
+        num_qubits: 100,
+        t_count: 10,
+        rotation_count: 0,
+        rotation_depth: 0,
+        ccz_count: 5,
+        ccix_count: 0,
+        measurement_count: 20,
+    })
+}
+
+#[test]
+fn test_layouts_from_names() {
+    assert_eq!(
+        load_layout_from_json_string(r#"{"name": "psspc"}"#),
+        Layout::Psspc
+    );
+    assert_eq!(
+        load_layout_from_json_string(r#"{"name": "lattice_surgery"}"#),
+        Layout::LatticeSurgery {
+            routing_overhead: 1.0
+        }
+    );
+    assert_eq!(
+        load_layout_from_json_string(r#"{"name": "lattice_surgery", "routingOverhead": 0.5}"#),
+        Layout::LatticeSurgery {
+            routing_overhead: 0.5
+        }
+    );
+    assert_eq!(
+        load_layout_from_json_string(r#"{"name": "compact_block"}"#),
+        Layout::CompactBlock
+    );
+}
+
+#[test]
+fn test_psspc_layout_uses_logical_resource_counts() {
+    let budget = ErrorBudget::new(0.5e-3, 0.5e-3, 0.0);
+    let logical_resources = logical_resources();
+    let layout_overhead = LayoutOverhead::new(logical_resources.clone(), Layout::Psspc);
+
+    assert_eq!(
+        layout_overhead.logical_qubits(),
+        logical_resources.logical_qubits()
+    );
+    assert_eq!(
+        layout_overhead.logical_depth(&budget),
+        logical_resources.logical_depth(&budget)
+    );
+}
+
+#[test]
+fn test_lattice_surgery_layout() {
+    let budget = ErrorBudget::new(0.5e-3, 0.5e-3, 0.0);
+
+    let layout_overhead = LayoutOverhead::new(
+        logical_resources(),
+        Layout::LatticeSurgery {
+            routing_overhead: 1.0,
+        },
+    );
+    assert_eq!(layout_overhead.logical_qubits(), 200);
+    // 20 measurements, 10 T gates, and 3 measurements for each of the 5 CCZ gates
+    assert_eq!(layout_overhead.logical_depth(&budget), 45);
+
+    let layout_overhead = LayoutOverhead::new(
+        logical_resources(),
+        Layout::LatticeSurgery {
+            routing_overhead: 0.25,
+        },
+    );
+    assert_eq!(layout_overhead.logical_qubits(), 125);
+    assert_eq!(layout_overhead.logical_depth(&budget), 4 * 45);
+}
+
+#[test]
+fn test_compact_block_layout() {
+    let budget = ErrorBudget::new(0.5e-3, 0.5e-3, 0.0);
+    let layout_overhead = LayoutOverhead::new(logical_resources(), Layout::CompactBlock);
+
+    assert_eq!(layout_overhead.logical_qubits(), 153);
+    assert_eq!(layout_overhead.logical_depth(&budget), 9 * 45);
+}
+
+#[test]
+fn test_invalid_routing_overhead() {
+    assert!(Layout::LatticeSurgery {
+        routing_overhead: 0.0
+    }
+    .validate()
+    .is_err());
+    assert!(Layout::LatticeSurgery {
+        routing_overhead: 0.5
+    }
+    .validate()
+    .is_ok());
+}
+
+#[test]
+fn test_ccz_magic_states() {
+    let budget = ErrorBudget::new(0.5e-3, 0.5e-3, 0.0);
+    let mut layout_overhead = LayoutOverhead::new(logical_resources(), Layout::Psspc);
+
+    assert_eq!(layout_overhead.num_magic_states(&budget, 0), 30);
+
+    layout_overhead.set_ccz_magic_states(true);
+
+    // 10 T gates, and one CCZ state for each of the 5 CCZ gates
+    assert_eq!(layout_overhead.num_magic_states(&budget, 0), 10);
+    assert_eq!(layout_overhead.num_magic_states(&budget, 1), 5);
+}
@@ -122,6 +122,24 @@ pub enum TFactoryDistillationUnitType {
     Combined,
 }
 
+/// The type of magic state that is produced by a distillation unit
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum MagicStateType {
+    #[default]
+    T,
+    Ccz,
+}
+
+impl MagicStateType {
+    /// The index of the magic state type in the factory builder
+    pub fn index(self) -> usize {
+        match self {
+            Self::T => 0,
+            Self::Ccz => 1,
+        }
+    }
+}
+
 pub struct TFactoryDistillationUnitResources {
     /// The number of unit qubits utilized in distillation.
     pub(crate) num_unit_qubits: u64,
@@ -149,6 +167,12 @@ pub struct TFactoryDistillationUnitTemplate {
     /// Specification for the logical qubit protocol if necessary to override for the first round of distillation.
     pub(crate) logical_qubit_specification_first_round_override:
         Option<TFactoryDistillationUnitResources>,
+    /// The type of magic state produced by the distillation unit; all units
+    /// consume T states.
+    pub(crate) output_state: MagicStateType,
+    /// Whether the unit requires Clifford operations that are considerably
+    /// more accurate than the T states it consumes.
+    pub(crate) requires_accurate_cliffords: bool,
 }
 
 impl TFactoryDistillationUnitTemplate {
@@ -163,6 +187,8 @@ impl TFactoryDistillationUnitTemplate {
             | "15-to-1 space efficient" => {
                 Ok(Self::create_distillation_unit_15_to_1_rm_space_efficient_template())
             }
+            "8-CCZ" | "8-to-CCZ" => Ok(Self::create_distillation_unit_8_to_ccz_template()),
+            "cultivation" | "T cultivation" => Ok(Self::create_t_cultivation_template()),
             _ => Err(CannotParseJSON(serde::de::Error::custom(format!(
                 "Invalid distillation unit specification name: {name}."
             )))),
@@ -187,6 +213,8 @@ impl TFactoryDistillationUnitTemplate {
             }),
 
             logical_qubit_specification_first_round_override: None,
+            output_state: MagicStateType::T,
+            requires_accurate_cliffords: true,
         }
     }
 
@@ -207,6 +235,52 @@ impl TFactoryDistillationUnitTemplate {
                 duration_in_qubit_cycle_time: 13,
             }),
             logical_qubit_specification_first_round_override: None,
+            output_state: MagicStateType::T,
+            requires_accurate_cliffords: true,
+        }
+    }
+
+    /// CCZ factory [arXiv:1812.01238](https://arxiv.org/abs/1812.01238),
+    /// which consumes 8 T states to produce one CCZ state on logical qubits
+    pub fn create_distillation_unit_8_to_ccz_template() -> Self {
+        Self {
+            name: String::from("8-to-CCZ"),
+            num_input_ts: 8,
+            num_output_ts: 1,
+            failure_probability_function: Box::new(Self::ccz_failure_probability),
+            output_error_rate_function: Box::new(Self::ccz_output_error_rate),
+            unit_type: TFactoryDistillationUnitType::Logical,
+            physical_qubit_specification: None,
+            logical_qubit_specification: Some(TFactoryDistillationUnitResources {
+                num_unit_qubits: 36,
+                duration_in_qubit_cycle_time: 6,
+            }),
+            logical_qubit_specification_first_round_override: None,
+            output_state: MagicStateType::Ccz,
+            requires_accurate_cliffords: true,
+        }
+    }
+
+    /// Magic state cultivation [arXiv:2409.17595](https://arxiv.org/abs/2409.17595),
+    /// which grows a T state from a single noisy injection on physical qubits
+    /// and discards attempts in which an error is detected.  Its output error
+    /// rate only depends on the Clifford error rate.
+    pub fn create_t_cultivation_template() -> Self {
+        Self {
+            name: String::from("T cultivation"),
+            num_input_ts: 1,
+            num_output_ts: 1,
+            failure_probability_function: Box::new(Self::cultivation_failure_probability),
+            output_error_rate_function: Box::new(Self::cultivation_output_error_rate),
+            unit_type: TFactoryDistillationUnitType::Physical,
+            physical_qubit_specification: Some(TFactoryDistillationUnitResources {
+                num_unit_qubits: 450,
+                duration_in_qubit_cycle_time: 30,
+            }),
+            logical_qubit_specification: None,
+            logical_qubit_specification_first_round_override: None,
+            output_state: MagicStateType::T,
+            requires_accurate_cliffords: false,
         }
     }
 
@@ -224,6 +298,8 @@ impl TFactoryDistillationUnitTemplate {
                 duration_in_qubit_cycle_time: 1,
             }),
             logical_qubit_specification_first_round_override: None,
+            output_state: MagicStateType::T,
+            requires_accurate_cliffords: true,
         }
     }
 
@@ -243,6 +319,38 @@ impl TFactoryDistillationUnitTemplate {
         35.0 * input_error_rate.powi(3) + 7.1 * clifford_error_rate
     }
 
+    fn ccz_failure_probability(
+        input_error_rate: f64,
+        clifford_error_rate: f64,
+        _readout_error_rate: f64,
+    ) -> f64 {
+        8.0 * input_error_rate + 180.0 * clifford_error_rate
+    }
+
+    fn ccz_output_error_rate(
+        input_error_rate: f64,
+        clifford_error_rate: f64,
+        _readout_error_rate: f64,
+    ) -> f64 {
+        28.0 * input_error_rate.powi(2) + 3.6 * clifford_error_rate
+    }
+
+    fn cultivation_failure_probability(
+        _input_error_rate: f64,
+        clifford_error_rate: f64,
+        _readout_error_rate: f64,
+    ) -> f64 {
+        1.0 - (-600.0 * clifford_error_rate).exp()
+    }
+
+    fn cultivation_output_error_rate(
+        _input_error_rate: f64,
+        clifford_error_rate: f64,
+        _readout_error_rate: f64,
+    ) -> f64 {
+        2.0 * clifford_error_rate.powi(3)
+    }
+
     fn trivial_failure_probability(
         _input_error_rate: f64,
         _clifford_error_rate: f64,
@@ -284,6 +392,8 @@ pub struct TFactoryDistillationUnit<'a> {
     /// This is the qubit's T error rate that we need only to decide the input T
     /// error rate for the first unit
     qubit_t_error_rate: f64,
+    pub(crate) output_state: MagicStateType,
+    requires_accurate_cliffords: bool,
 }
 
 impl<'a> fmt::Debug for TFactoryDistillationUnit<'a> {
@@ -309,6 +419,7 @@ impl<'a> fmt::Debug for TFactoryDistillationUnit<'a> {
             .field("name", &self.name)
             .field("clifford_error_rate", &self.clifford_error_rate)
             .field("qubit_t_error_rate", &self.qubit_t_error_rate)
+            .field("output_state", &self.output_state)
             .finish()
     }
 }
@@ -394,6 +505,8 @@ impl<'a> TFactoryDistillationUnit<'a> {
             failure_probability_formula,
             output_error_rate_formula,
             readout_error_rate,
+            output_state: template.output_state,
+            requires_accurate_cliffords: template.requires_accurate_cliffords,
         }
     }
 
@@ -406,7 +519,8 @@ impl<'a> TFactoryDistillationUnit<'a> {
     }
 
     pub fn is_valid(&self) -> bool {
-        self.clifford_error_rate() <= 0.1 * self.qubit_t_error_rate
+        !self.requires_accurate_cliffords
+            || self.clifford_error_rate() <= 0.1 * self.qubit_t_error_rate
     }
 }
 
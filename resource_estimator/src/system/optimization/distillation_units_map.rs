@@ -10,8 +10,8 @@ use std::rc::Rc;
 
 use crate::estimates::LogicalPatch;
 use crate::system::modeling::{
-    PhysicalQubit, Protocol, TFactoryDistillationUnit, TFactoryDistillationUnitTemplate,
-    TFactoryDistillationUnitType, TFactoryQubit,
+    MagicStateType, PhysicalQubit, Protocol, TFactoryDistillationUnit,
+    TFactoryDistillationUnitTemplate, TFactoryDistillationUnitType, TFactoryQubit,
 };
 
 pub struct DistillationUnitsMap<'a> {
@@ -24,6 +24,8 @@ pub struct DistillationUnitsMap<'a> {
     min_valid_code_distance_indexes: Vec<usize>,
     num_code_distances: usize,
     distances: Vec<u64>,
+    /// Output magic state type per unit index
+    output_states: Vec<MagicStateType>,
 }
 
 impl<'a> DistillationUnitsMap<'a> {
@@ -76,6 +78,16 @@ impl<'a> DistillationUnitsMap<'a> {
         let mut logical_distillation_unit_templates = combined_distillation_unit_templates;
         logical_distillation_unit_templates.append(&mut purely_logical_distillation_unit_templates);
 
+        let output_states = logical_distillation_unit_templates
+            .iter()
+            .map(|x| x.output_state)
+            .chain(
+                physical_distillation_units[num_combined_distillation_units..]
+                    .iter()
+                    .map(|x| x.output_state),
+            )
+            .collect();
+
         let mut logical_distillation_units: Vec<Vec<TFactoryDistillationUnit>> = Vec::new();
 
         for qubit in qubits {
@@ -96,15 +108,13 @@ impl<'a> DistillationUnitsMap<'a> {
         let mut min_valid_code_distance_indexes =
             vec![qubits.len(); num_logical_distillation_units + num_combined_distillation_units];
         for idx in 0..num_logical_distillation_units + num_combined_distillation_units {
-            if let Some(min_valid_code_distance_index) = logical_distillation_units
+            if let Some(min_valid_code_distance_index) = distances
                 .iter()
-                .filter(|x| !x.is_empty())
                 .enumerate()
-                .filter_map(|(distance_index, x)| {
-                    if x[idx].is_valid() {
-                        Some(distance_index)
-                    } else {
-                        None
+                .filter_map(|(distance_index, &distance)| {
+                    match logical_distillation_units[distance as usize].get(idx) {
+                        Some(unit) if unit.is_valid() => Some(distance_index),
+                        _ => None,
                     }
                 })
                 .min()
@@ -123,6 +133,7 @@ impl<'a> DistillationUnitsMap<'a> {
             min_valid_code_distance_indexes,
             num_code_distances,
             distances,
+            output_states,
         }
     }
 
@@ -153,6 +164,18 @@ impl<'a> DistillationUnitsMap<'a> {
         }
     }
 
+    /// Returns true, if the units with indexes `indexes` form a factory for
+    /// magic states of type `magic_state_type`, i.e., all rounds but the last
+    /// one produce T states, and the last one produces the requested type.
+    pub fn is_factory_for(&self, indexes: &[usize], magic_state_type: MagicStateType) -> bool {
+        indexes.split_last().is_some_and(|(&last, rest)| {
+            self.output_states[last] == magic_state_type
+                && rest
+                    .iter()
+                    .all(|&idx| self.output_states[idx] == MagicStateType::T)
+        })
+    }
+
     pub fn get_min_distance_indexes(&self, indexes: &[usize]) -> Vec<usize> {
         indexes
             .iter()
@@ -5,12 +5,13 @@ use std::borrow::Cow;
 use std::rc::Rc;
 
 use crate::system::modeling::{
-    PhysicalQubit, Protocol, TFactory, TFactoryDistillationUnit, TFactoryDistillationUnitTemplate,
+    MagicStateType, PhysicalQubit, Protocol, TFactory, TFactoryDistillationUnit,
+    TFactoryDistillationUnitTemplate,
 };
 use crate::{
     estimates::{
         optimization::{Point, Point2D, Point4D, Population},
-        Factory, FactoryBuildError, FactoryBuilder, LogicalPatch,
+        ErrorCorrection, Factory, FactoryBuildError, FactoryBuilder, LogicalPatch,
     },
     system::modeling::default_t_factory,
 };
@@ -212,6 +213,7 @@ pub(crate) fn find_nondominated_tfactories<'a>(
     ftp: &Protocol,
     qubit: &Rc<PhysicalQubit>,
     distillation_unit_templates: &[TFactoryDistillationUnitTemplate],
+    magic_state_type: MagicStateType,
     output_t_error_rate: f64,
     max_code_distance: u64,
 ) -> Vec<Cow<'a, TFactory>> {
@@ -219,6 +221,7 @@ pub(crate) fn find_nondominated_tfactories<'a>(
         ftp,
         qubit,
         distillation_unit_templates,
+        magic_state_type,
         output_t_error_rate,
         max_code_distance,
     );
@@ -234,16 +237,23 @@ fn find_nondominated_population<P>(
     ftp: &Protocol,
     qubit: &Rc<PhysicalQubit>,
     distillation_unit_templates: &[TFactoryDistillationUnitTemplate],
+    magic_state_type: MagicStateType,
     output_t_error_rate: f64,
     max_code_distance: u64,
 ) -> Population<P>
 where
     P: Point + Ord + ToString + From<TFactory> + TFactoryExhaustiveSearchOptions,
 {
-    let min_code_distance = 1;
-    let distances: Vec<_> = (min_code_distance..=max_code_distance).step_by(2).collect();
+    // Code distance 1 refers to physical qubits in the first distillation round
+    let distances: Vec<_> = std::iter::once(1)
+        .chain(
+            ftp.code_parameter_range(None)
+                .filter(|&distance| 1 < distance && distance <= max_code_distance),
+        )
+        .collect();
 
-    if output_t_error_rate > qubit.t_gate_error_rate() {
+    // CCZ states cannot be obtained from physical T states without distillation
+    if magic_state_type == MagicStateType::T && output_t_error_rate > qubit.t_gate_error_rate() {
         let mut population = Population::<P>::new();
 
         if let Ok(logical_qubit) = LogicalPatch::new(ftp, max_code_distance, qubit.clone()) {
@@ -274,12 +284,22 @@ where
     let mut searcher = TFactoryExhaustiveSearch::<P>::new(output_t_error_rate);
 
     for num_rounds in 1..=MAX_DISTILLATION_ROUNDS {
-        process_for_num_rounds(&mut searcher, &distillation_units_map, num_rounds);
+        process_for_num_rounds(
+            &mut searcher,
+            &distillation_units_map,
+            magic_state_type,
+            num_rounds,
+        );
     }
 
     if searcher.frontier_factories.items().is_empty() || P::ITERATE_MAX_NUM_ROUNDS {
         for num_rounds in MAX_DISTILLATION_ROUNDS + 1..=MAX_EXTRA_DISTILLATION_ROUNDS {
-            process_for_num_rounds(&mut searcher, &distillation_units_map, num_rounds);
+            process_for_num_rounds(
+                &mut searcher,
+                &distillation_units_map,
+                magic_state_type,
+                num_rounds,
+            );
         }
     }
 
@@ -292,12 +312,15 @@ where
 fn process_for_num_rounds<P>(
     searcher: &mut TFactoryExhaustiveSearch<P>,
     distillation_units_map: &DistillationUnitsMap,
+    magic_state_type: MagicStateType,
     num_rounds: usize,
 ) where
     P: Point + Ord + From<TFactory> + TFactoryExhaustiveSearchOptions,
 {
     distillation_units_map.iterate_for_all_distillation_units(num_rounds, &mut |unit_indexes| {
-        process_for_specifications_combination(searcher, distillation_units_map, unit_indexes);
+        if distillation_units_map.is_factory_for(unit_indexes, magic_state_type) {
+            process_for_specifications_combination(searcher, distillation_units_map, unit_indexes);
+        }
     });
 }
 
@@ -367,18 +390,39 @@ impl FactoryBuilder<Protocol> for TFactoryBuilder {
         &self,
         ftp: &Protocol,
         qubit: &Rc<PhysicalQubit>,
-        _magic_state_type: usize,
+        magic_state_type: usize,
         output_t_error_rate: f64,
         max_code_distance: &u64,
     ) -> Vec<Cow<Self::Factory>> {
+        let magic_state_type = if magic_state_type == MagicStateType::Ccz.index() {
+            MagicStateType::Ccz
+        } else {
+            MagicStateType::T
+        };
+
         find_nondominated_tfactories(
             ftp,
             qubit,
             &self.distillation_unit_templates,
+            magic_state_type,
             output_t_error_rate,
             *max_code_distance,
         )
     }
+
+    /// CCZ states are a second magic state type, if any of the distillation
+    /// units produces them.
+    fn num_magic_state_types(&self) -> usize {
+        if self
+            .distillation_unit_templates
+            .iter()
+            .any(|template| template.output_state == MagicStateType::Ccz)
+        {
+            2
+        } else {
+            1
+        }
+    }
 }
 
 #[cfg(test)]
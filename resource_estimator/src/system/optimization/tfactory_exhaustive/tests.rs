@@ -9,7 +9,7 @@ use crate::{
 use super::{
     super::super::{
         data,
-        modeling::{PhysicalQubit, Protocol, TFactoryDistillationUnitTemplate},
+        modeling::{MagicStateType, PhysicalQubit, Protocol, TFactoryDistillationUnitTemplate},
     },
     find_nondominated_population, find_nondominated_tfactories,
 };
@@ -22,6 +22,7 @@ fn test_one_t_error_rate() {
         &Protocol::default(),
         &Rc::new(PhysicalQubit::default()),
         &TFactoryDistillationUnitTemplate::default_distillation_unit_templates(),
+        MagicStateType::T,
         1e-18,
         35,
     );
@@ -141,6 +142,7 @@ fn required_logical_tstate_error_too_high() {
         &ftp,
         &qubit,
         &distillation_unit_templates,
+        MagicStateType::T,
         output_t_error_rate,
         max_code_distance,
     );
@@ -154,6 +156,76 @@ fn required_logical_tstate_error_too_high() {
     assert_eq!(tfactory.unit_names(), vec!["trivial 1-to-1"]);
 }
 
+#[test]
+fn ccz_factories_end_with_ccz_round() {
+    let qubit: Rc<PhysicalQubit> = Rc::new(PhysicalQubit::default());
+    let mut distillation_unit_templates =
+        TFactoryDistillationUnitTemplate::default_distillation_unit_templates();
+    distillation_unit_templates
+        .push(TFactoryDistillationUnitTemplate::create_distillation_unit_8_to_ccz_template());
+
+    let factories = find_nondominated_tfactories(
+        &Protocol::default(),
+        &qubit,
+        &distillation_unit_templates,
+        MagicStateType::Ccz,
+        1e-12,
+        35,
+    );
+
+    assert!(!factories.is_empty());
+    for factory in factories {
+        let unit_names = factory.unit_names();
+        assert_eq!(unit_names.last().map(String::as_str), Some("8-to-CCZ"));
+        assert!(unit_names[..unit_names.len() - 1]
+            .iter()
+            .all(|name| name != "8-to-CCZ"));
+        assert!(factory.output_error_rate() <= 1e-12);
+    }
+
+    // CCZ units must not be used for T factories
+    let factories = find_nondominated_tfactories(
+        &Protocol::default(),
+        &qubit,
+        &distillation_unit_templates,
+        MagicStateType::T,
+        1e-12,
+        35,
+    );
+
+    assert!(!factories.is_empty());
+    assert!(factories
+        .iter()
+        .all(|factory| !factory.unit_names().contains(&String::from("8-to-CCZ"))));
+}
+
+#[test]
+fn cultivation_with_noisy_cliffords() {
+    // The Clifford error rate of this qubit is not sufficiently below its T
+    // gate error rate for physical 15-to-1 distillation, but for cultivation
+    let qubit: Rc<PhysicalQubit> =
+        serde_json::from_str(r#"{"name": "qubit_gate_ns_e3"}"#).expect("json should be valid");
+    let distillation_unit_templates = vec![
+        TFactoryDistillationUnitTemplate::create_t_cultivation_template(),
+        TFactoryDistillationUnitTemplate::create_distillation_unit_15_to_1_rm_prep_template(),
+    ];
+
+    let factories = find_nondominated_tfactories(
+        &Protocol::default(),
+        &qubit,
+        &distillation_unit_templates,
+        MagicStateType::T,
+        1e-8,
+        35,
+    );
+
+    assert!(!factories.is_empty());
+    assert!(factories.iter().any(|factory| {
+        factory.unit_names() == vec!["T cultivation"]
+            && factory.code_parameter_per_round() == vec![Some(&1)]
+    }));
+}
+
 fn find_tfactories<'a>(ftp: &Protocol, qubit_name: &str) -> Vec<Cow<'a, TFactory>> {
     let qubit: Rc<PhysicalQubit> = serde_json::from_str(&format!(r#"{{"name": "{qubit_name}"}}"#))
         .expect("json should be valid");
@@ -163,6 +235,7 @@ fn find_tfactories<'a>(ftp: &Protocol, qubit_name: &str) -> Vec<Cow<'a, TFactory
         ftp,
         &qubit,
         &create_test_templates(),
+        MagicStateType::T,
         output_t_error_rate,
         ftp.max_code_distance(),
     )
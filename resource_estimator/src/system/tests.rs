@@ -8,10 +8,13 @@ use crate::estimates::{
     PhysicalResourceEstimation, PhysicalResourceEstimationResult,
 };
 
-use super::estimate_physical_resources;
+use super::{
+    estimate_physical_resources, estimate_physical_resources_batch,
+    estimate_physical_resources_with_profile, is_batch_specification,
+};
 
 use crate::system::{
-    data::{ErrorBudgetSpecification, JobParams, LogicalResourceCounts},
+    data::{ErrorBudgetSpecification, JobParams, LayerCounts, LayerProfile, LogicalResourceCounts},
     error::IO,
     modeling::GateBasedPhysicalQubit,
     modeling::{PhysicalQubit, Protocol, TFactory},
@@ -47,6 +50,311 @@ fn estimate_single() {
     assert!(map.contains_key("physicalCountsFormatted"));
 }
 
+#[test]
+fn estimate_with_compact_block_layout() {
+    let logical_resources = LogicalResourceCounts {
+        num_qubits: 100,
+        t_count: 0,
+        rotation_count: 112_110,
+        rotation_depth: 2001,
+        ccz_count: 0,
+        ccix_count: 0,
+        measurement_count: 0,
+    };
+
+    let params: &str = r#"[{
+        "layout": {"name": "compact_block"}
+    }]"#;
+    let result = estimate_physical_resources(logical_resources, params);
+
+    let json_value: Vec<Value> =
+        serde_json::from_str(&result.expect("result is err")).expect("Failed to parse JSON");
+    let map = json_value[0].as_object().expect("Failed build map");
+    assert_eq!(
+        map["physicalCounts"]["breakdown"]["algorithmicLogicalQubits"],
+        json!(153)
+    );
+    assert_eq!(map["jobParams"]["layout"], json!({"name": "compact_block"}));
+}
+
+#[test]
+fn estimate_with_custom_code_parameters() {
+    let logical_resources = LogicalResourceCounts {
+        num_qubits: 100,
+        t_count: 0,
+        rotation_count: 112_110,
+        rotation_depth: 2001,
+        ccz_count: 0,
+        ccix_count: 0,
+        measurement_count: 0,
+    };
+
+    let params: &str = r#"[{
+        "qecScheme": {
+            "name": "surface_code",
+            "logicalErrorRate": "0.03 * (physicalErrorRate / 0.01)^((dx + 1) / 2) + 0.03 * (physicalErrorRate / 0.01)^((dz + 1) / 2)",
+            "physicalQubitsPerLogicalQubit": "2 * dx * dz",
+            "codeParameters": [
+                {"codeDistance": 10, "dx": 11, "dz": 9},
+                {"codeDistance": 14, "dx": 15, "dz": 13},
+                {"codeDistance": 18, "dx": 19, "dz": 17},
+                {"codeDistance": 22, "dx": 23, "dz": 21}
+            ]
+        }
+    }]"#;
+    let result = estimate_physical_resources(logical_resources, params);
+
+    let json_value: Vec<Value> =
+        serde_json::from_str(&result.expect("result is err")).expect("Failed to parse JSON");
+    let map = json_value[0].as_object().expect("Failed build map");
+    let code_distance = map["logicalQubit"]["codeDistance"]
+        .as_u64()
+        .expect("code distance should be set");
+    assert!([10, 14, 18, 22].contains(&code_distance));
+    assert_eq!(
+        map["logicalQubit"]["physicalQubits"],
+        json!(2 * (code_distance + 1) * (code_distance - 1))
+    );
+}
+
+#[test]
+fn estimate_with_ccz_factories() {
+    let logical_resources = LogicalResourceCounts {
+        num_qubits: 100,
+        t_count: 1000,
+        rotation_count: 0,
+        rotation_depth: 0,
+        ccz_count: 10_000,
+        ccix_count: 0,
+        measurement_count: 0,
+    };
+
+    let params: &str = r#"[{
+        "distillationUnitSpecifications": [
+            {"name": "15-to-1 RM prep"},
+            {"name": "15-to-1 space efficient"},
+            {"name": "8-to-CCZ"}
+        ]
+    }]"#;
+    let result = estimate_physical_resources(logical_resources, params);
+
+    let json_value: Vec<Value> =
+        serde_json::from_str(&result.expect("result is err")).expect("Failed to parse JSON");
+    let map = json_value[0].as_object().expect("Failed build map");
+    let breakdown = &map["physicalCounts"]["breakdown"];
+    assert_eq!(breakdown["numTstates"], json!(1000));
+    assert_eq!(breakdown["numCczStates"], json!(10_000));
+    assert!(breakdown["numCczFactories"].as_u64().unwrap_or_default() > 0);
+
+    let unit_names = map["cczFactory"]["unitNamePerRound"]
+        .as_array()
+        .expect("unit names should be set");
+    assert_eq!(unit_names.last(), Some(&json!("8-to-CCZ")));
+    assert!(map["tfactory"]["unitNamePerRound"]
+        .as_array()
+        .expect("unit names should be set")
+        .iter()
+        .all(|name| name != "8-to-CCZ"));
+}
+
+#[test]
+fn estimate_with_ccz_factories_rejects_frontier_and_constraints() {
+    let logical_resources = LogicalResourceCounts {
+        num_qubits: 100,
+        t_count: 1000,
+        rotation_count: 0,
+        rotation_depth: 0,
+        ccz_count: 10_000,
+        ccix_count: 0,
+        measurement_count: 0,
+    };
+
+    let params: &str = r#"[{
+        "estimateType": "frontier",
+        "distillationUnitSpecifications": [{"name": "8-to-CCZ"}]
+    }, {
+        "distillationUnitSpecifications": [{"name": "8-to-CCZ"}],
+        "constraints": {"maxDuration": "1 s"}
+    }, {
+        "distillationUnitSpecifications": [{"name": "8-to-CCZ"}],
+        "constraints": {"maxPhysicalQubits": 1000000}
+    }, {
+        "estimateType": "frontier"
+    }]"#;
+    let result = estimate_physical_resources(logical_resources, params);
+
+    let json_value: Vec<Value> =
+        serde_json::from_str(&result.expect("result is err")).expect("Failed to parse JSON");
+    for value in &json_value[..3] {
+        assert_eq!(
+            value["code"],
+            json!("Qsc.Estimates.InvalidInputError.UnsupportedCczDistillationUnits")
+        );
+    }
+    assert_eq!(json_value[3]["status"], json!("success"));
+}
+
+#[test]
+fn estimate_with_space_time_diagram() {
+    let logical_resources = LogicalResourceCounts {
+        num_qubits: 10,
+        t_count: 1000,
+        rotation_count: 0,
+        rotation_depth: 0,
+        ccz_count: 0,
+        ccix_count: 0,
+        measurement_count: 0,
+    };
+
+    // all T gates are in the first half of the layers; rotations in the second
+    // half consume no T states, since the logical counts have no rotations
+    let layer_profile = LayerProfile::new((0..2000).map(|layer| LayerCounts {
+        t_count: u64::from(layer < 1000),
+        rotation_count: u64::from(layer >= 1000),
+        active_qubits: if layer < 1000 { 10 } else { 4 },
+        ..LayerCounts::default()
+    }));
+
+    let params = r#"[{"profiling": {"spaceTimeWindows": 10}}, {}]"#;
+    let result = estimate_physical_resources_with_profile(
+        logical_resources,
+        None,
+        Some(&layer_profile),
+        params,
+    );
+
+    let json_value: Vec<Value> =
+        serde_json::from_str(&result.expect("result is err")).expect("Failed to parse JSON");
+    assert!(!json_value[1]
+        .as_object()
+        .expect("Failed build map")
+        .contains_key("spaceTimeDiagram"));
+
+    let diagram = &json_value[0]["spaceTimeDiagram"];
+    let windows = diagram["windows"]
+        .as_array()
+        .expect("windows should be set");
+    assert!(!windows.is_empty() && windows.len() <= 10);
+
+    let num_tfactories = json_value[0]["physicalCounts"]["breakdown"]["numTfactories"].clone();
+    let tstates = windows
+        .iter()
+        .map(|window| window["tstates"].as_u64().expect("tstates should be set"))
+        .sum::<u64>();
+    assert!(tstates.abs_diff(1000) <= windows.len() as u64);
+
+    let first = &windows[0];
+    let last = windows.last().expect("windows should not be empty");
+    assert_eq!(first["activeQubits"], json!(10));
+    assert_eq!(last["activeQubits"], json!(4));
+    assert_eq!(last["tstates"], json!(0));
+    assert_eq!(last["activeTfactories"], json!(0));
+    assert!(first["activeTfactories"].as_u64() <= num_tfactories.as_u64());
+    assert!(first["activeTfactories"].as_u64() > Some(0));
+}
+
+#[test]
+fn estimate_batch_with_pareto_frontier() {
+    let logical_resources = LogicalResourceCounts {
+        num_qubits: 100,
+        t_count: 0,
+        rotation_count: 112_110,
+        rotation_depth: 2001,
+        ccz_count: 0,
+        ccix_count: 0,
+        measurement_count: 0,
+    };
+
+    let batch = r#"{
+        "items": [{}, {"estimateType": "frontier"}],
+        "grid": {
+            "qubitParams": [{"name": "qubit_gate_ns_e3"}, {"name": "qubit_maj_ns_e6"}],
+            "errorBudget": [0.001, 0.01, 2.0]
+        }
+    }"#;
+    let result = estimate_physical_resources_batch(&logical_resources, None, None, batch);
+
+    let json_value: Value =
+        serde_json::from_str(&result.expect("result is err")).expect("Failed to parse JSON");
+    let results = json_value["results"]
+        .as_array()
+        .expect("results should be an array");
+    assert_eq!(results.len(), 12);
+    // grid keys are enumerated in lexicographic order
+    assert_eq!(results[0]["jobParams"]["errorBudget"], json!(0.001));
+    assert_eq!(results[1]["jobParams"]["errorBudget"], json!(0.001));
+    assert_eq!(results[2]["jobParams"]["errorBudget"], json!(0.01));
+    assert_eq!(
+        results[4]["code"],
+        json!("Qsc.Estimates.InvalidInputError.InvalidErrorBudget")
+    );
+    assert!(results[7]
+        .as_object()
+        .expect("result should be an object")
+        .contains_key("frontierEntries"));
+
+    let frontier = json_value["paretoFrontier"]
+        .as_array()
+        .expect("Pareto frontier should be an array");
+    assert!(!frontier.is_empty());
+    let mut previous: Option<(u64, u64)> = None;
+    for point in frontier {
+        let index = point["index"].as_u64().expect("index should be set") as usize;
+        let counts = match point.get("frontierEntry") {
+            Some(entry) => {
+                &results[index]["frontierEntries"]
+                    [entry.as_u64().expect("frontier entry should be an index") as usize]
+                    ["physicalCounts"]
+            }
+            None => &results[index]["physicalCounts"],
+        };
+        let physical_qubits = point["physicalQubits"]
+            .as_u64()
+            .expect("physical qubits should be set");
+        let runtime = point["runtime"].as_u64().expect("runtime should be set");
+        assert_eq!(counts["physicalQubits"], json!(physical_qubits));
+        assert_eq!(counts["runtime"], json!(runtime));
+
+        // points are sorted by increasing runtime and decreasing physical qubits
+        if let Some((previous_qubits, previous_runtime)) = previous {
+            assert!(physical_qubits < previous_qubits && runtime > previous_runtime);
+        }
+        previous = Some((physical_qubits, runtime));
+    }
+}
+
+#[test]
+fn batch_specifications_are_objects_with_batch_keys() {
+    assert!(is_batch_specification(
+        r#"{"grid": {"errorBudget": [0.01]}}"#
+    ));
+    assert!(is_batch_specification(r#"{"items": [{}, {}]}"#));
+    assert!(is_batch_specification(r#"{"base": {"errorBudget": 0.01}}"#));
+    assert!(!is_batch_specification(r#"{"errorBudget": 0.01}"#));
+    assert!(!is_batch_specification("[{}]"));
+    assert!(!is_batch_specification(""));
+}
+
+#[test]
+fn estimate_single_params_object() {
+    let logical_resources = LogicalResourceCounts {
+        num_qubits: 100,
+        t_count: 0,
+        rotation_count: 112_110,
+        rotation_depth: 2001,
+        ccz_count: 0,
+        ccix_count: 0,
+        measurement_count: 0,
+    };
+
+    let result = estimate_physical_resources(logical_resources, r#"{"errorBudget": 0.01}"#);
+
+    let json_value: Vec<Value> =
+        serde_json::from_str(&result.expect("result is err")).expect("Failed to parse JSON");
+    assert_eq!(json_value.len(), 1);
+    assert_eq!(json_value[0]["jobParams"]["errorBudget"], json!(0.01));
+}
+
 #[test]
 fn estimate_frontier() {
     let logical_resources = LogicalResourceCounts {
@@ -24,6 +24,7 @@ import {
 import { DebugProtocol } from "@vscode/debugprotocol";
 import {
   IDebugServiceWorker,
+  IQubitVariable,
   IStructStepResult,
   QscEventTarget,
   StepResultId,
@@ -69,7 +70,9 @@ export class QscDebugSession extends LoggingDebugSession {
 
   private breakpointLocations: Map<string, IBreakpointLocationData[]>;
   private breakpoints: Map<string, DebugProtocol.Breakpoint[]>;
-  private variableHandles = new Handles<"locals" | "quantum" | "circuit">();
+  private variableHandles = new Handles<
+    "locals" | "quantum" | "circuit" | IQubitVariable[]
+  >();
   private failureMessage: string;
   private eventTarget: QscEventTarget;
   private supportsVariableType = false;
@@ -174,7 +177,7 @@ export class QscDebugSession extends LoggingDebugSession {
     response.body.supportsConfigurationDoneRequest = true;
 
     // make VS Code show a 'step back' button
-    response.body.supportsStepBack = false;
+    response.body.supportsStepBack = true;
 
     // make VS Code support data breakpoints
     response.body.supportsDataBreakpoints = false;
@@ -186,7 +189,15 @@ export class QscDebugSession extends LoggingDebugSession {
     response.body.supportsExceptionFilterOptions = false;
 
     // make VS Code send exceptionInfo request
-    response.body.supportsExceptionInfoRequest = false;
+    response.body.supportsExceptionInfoRequest = true;
+    response.body.exceptionBreakpointFilters = [
+      {
+        filter: "runtimeErrors",
+        label: "Runtime Errors",
+        description: "Pause on `fail` and other runtime errors.",
+        default: false,
+      },
+    ];
 
     // make VS Code able to read and write variable memory
     response.body.supportsReadMemoryRequest = false;
@@ -214,7 +225,7 @@ export class QscDebugSession extends LoggingDebugSession {
     response.body.supportsStepInTargetsRequest = false;
 
     // make VS Code send setVariable request
-    response.body.supportsSetVariable = false;
+    response.body.supportsSetVariable = true;
 
     // make VS Code send setExpression request
     response.body.supportsSetExpression = false;
@@ -290,6 +301,12 @@ export class QscDebugSession extends LoggingDebugSession {
       await this.runWithoutDebugging(args, associationId);
     } else {
       log.trace(`Running with debugging`);
+      const snapshotMemoryLimit = args.snapshotMemoryLimit ?? 64;
+      if (snapshotMemoryLimit > 0) {
+        await this.debugService.enableReverseStepping(
+          snapshotMemoryLimit * 1024 * 1024,
+        );
+      }
       if (this.config.stopOnEntry) {
         sendTelemetryEvent(
           EventType.DebugSessionEvent,
@@ -333,6 +350,12 @@ export class QscDebugSession extends LoggingDebugSession {
       this.sendEvent(evt);
     } else if (result.id == StepResultId.Return) {
       await this.endSession(`ending session`, 0);
+    } else if (result.id == StepResultId.Exception) {
+      const exception = await this.debugService.getException();
+      log.trace(`raising exception event`);
+      this.sendEvent(
+        new StoppedEvent("exception", QscDebugSession.threadID, exception),
+      );
     } else {
       log.trace(`step result: ${result.id} ${result.value}`);
       this.sendEvent(new StoppedEvent("step", QscDebugSession.threadID));
@@ -367,6 +390,42 @@ export class QscDebugSession extends LoggingDebugSession {
     );
   }
 
+  private async eval_reverse_step(step: () => Promise<IStructStepResult>) {
+    let result: IStructStepResult | undefined;
+    try {
+      result = await step();
+    } catch (e) {
+      // There is no earlier step to return to, so stay paused where we are.
+      log.trace(`reverse step failed: ${e}`);
+    }
+
+    await this.updateCircuit();
+
+    if (result?.id == StepResultId.BreakpointHit) {
+      const evt = new StoppedEvent(
+        "breakpoint",
+        QscDebugSession.threadID,
+      ) as DebugProtocol.StoppedEvent;
+      evt.body.hitBreakpointIds = [result.value];
+      this.sendEvent(evt);
+    } else {
+      this.sendEvent(new StoppedEvent("step", QscDebugSession.threadID));
+    }
+  }
+
+  private async stepBack(): Promise<void> {
+    await this.eval_reverse_step(
+      async () => await this.debugService.stepBack(),
+    );
+  }
+
+  private async reverseContinue(): Promise<void> {
+    const bps = this.getBreakpointIds();
+    await this.eval_reverse_step(
+      async () => await this.debugService.reverseContinue(bps),
+    );
+  }
+
   private async endSession(message: string, exitCode: number): Promise<void> {
     log.trace(message);
     this.writeToDebugConsole("");
@@ -476,6 +535,57 @@ export class QscDebugSession extends LoggingDebugSession {
     await this.stepOut();
   }
 
+  protected async stepBackRequest(
+    response: DebugProtocol.StepBackResponse,
+    args: DebugProtocol.StepBackArguments,
+    request?: DebugProtocol.Request,
+  ): Promise<void> {
+    log.trace(`stepBackRequest: %O`, args);
+    this.sendResponse(response);
+
+    await this.stepBack();
+  }
+
+  protected async reverseContinueRequest(
+    response: DebugProtocol.ReverseContinueResponse,
+    args: DebugProtocol.ReverseContinueArguments,
+    request?: DebugProtocol.Request,
+  ): Promise<void> {
+    log.trace(`reverseContinueRequest: %O`, args);
+    this.sendResponse(response);
+
+    await this.reverseContinue();
+  }
+
+  protected async setExceptionBreakPointsRequest(
+    response: DebugProtocol.SetExceptionBreakpointsResponse,
+    args: DebugProtocol.SetExceptionBreakpointsArguments,
+    request?: DebugProtocol.Request,
+  ): Promise<void> {
+    log.trace(`setExceptionBreakPointsRequest: %O`, args);
+    if (this.failureMessage == "") {
+      await this.debugService.setBreakOnExceptions(
+        args.filters.includes("runtimeErrors"),
+      );
+    }
+    this.sendResponse(response);
+  }
+
+  protected async exceptionInfoRequest(
+    response: DebugProtocol.ExceptionInfoResponse,
+    args: DebugProtocol.ExceptionInfoArguments,
+    request?: DebugProtocol.Request,
+  ): Promise<void> {
+    log.trace(`exceptionInfoRequest: %O`, args);
+    const exception = await this.debugService.getException();
+    response.body = {
+      exceptionId: "runtimeError",
+      description: exception,
+      breakMode: "always",
+    };
+    this.sendResponse(response);
+  }
+
   protected async breakpointLocationsRequest(
     response: DebugProtocol.BreakpointLocationsResponse,
     args: DebugProtocol.BreakpointLocationsArguments,
@@ -750,6 +860,63 @@ export class QscDebugSession extends LoggingDebugSession {
     this.sendResponse(response);
   }
 
+  protected async evaluateRequest(
+    response: DebugProtocol.EvaluateResponse,
+    args: DebugProtocol.EvaluateArguments,
+  ): Promise<void> {
+    log.trace(`evaluateRequest: %O`, args);
+    // Stack frame ids are indexes into the debugger's stack frames, so the
+    // top frame is the last one.
+    const frameId =
+      args.frameId ?? (await this.debugService.getStackFrames()).length - 1;
+    try {
+      const result = await this.debugService.evalWatch(
+        frameId,
+        args.expression,
+      );
+      response.body = { result, variablesReference: 0 };
+      this.sendResponse(response);
+    } catch (e) {
+      this.sendErrorResponse(response, {
+        id: -1,
+        format: e instanceof Error ? e.message : String(e),
+        showUser: false,
+      });
+    }
+  }
+
+  protected async setVariableRequest(
+    response: DebugProtocol.SetVariableResponse,
+    args: DebugProtocol.SetVariableArguments,
+  ): Promise<void> {
+    log.trace(`setVariableRequest: %O`, args);
+    if (this.variableHandles.get(args.variablesReference) !== "locals") {
+      this.sendErrorResponse(response, {
+        id: -1,
+        format: "only local variables can be changed",
+        showUser: false,
+      });
+      return;
+    }
+    // The locals scope shows the locals of the top frame.
+    const frameId = (await this.debugService.getStackFrames()).length - 1;
+    try {
+      const value = await this.debugService.setVariable(
+        frameId,
+        args.name,
+        args.value,
+      );
+      response.body = { value };
+      this.sendResponse(response);
+    } catch (e) {
+      this.sendErrorResponse(response, {
+        id: -1,
+        format: e instanceof Error ? e.message : String(e),
+        showUser: false,
+      });
+    }
+  }
+
   protected async variablesRequest(
     response: DebugProtocol.VariablesResponse,
     args: DebugProtocol.VariablesArguments,
@@ -770,7 +937,11 @@ export class QscDebugSession extends LoggingDebugSession {
             const variable: DebugProtocol.Variable = {
               name: local.name,
               value: local.value,
-              variablesReference: 0,
+              // Qubit locals expand to the reduced state of each qubit.
+              variablesReference:
+                local.qubits.length > 0
+                  ? this.variableHandles.create(local.qubits)
+                  : 0,
             };
             if (this.supportsVariableType) {
               variable.type = local.var_type;
@@ -835,6 +1006,23 @@ export class QscDebugSession extends LoggingDebugSession {
           };
         }
         break;
+      default:
+        if (Array.isArray(handle)) {
+          response.body = {
+            variables: handle.map((qubit) => {
+              const [x, y, z] = qubit.bloch.map((c) => c.toFixed(4));
+              const [p0, p1] = qubit.probabilities.map((p) => p.toFixed(4));
+              return {
+                name: `Qubit${qubit.id}`,
+                value:
+                  `Bloch (${x}, ${y}, ${z}), P(|0⟩) = ${p0}, P(|1⟩) = ${p1}` +
+                  (qubit.entangled ? ", entangled" : ""),
+                variablesReference: 0,
+              };
+            }),
+          };
+        }
+        break;
     }
 
     log.trace(`variablesResponse: %O`, response);
@@ -25,4 +25,6 @@ export interface ILaunchRequestArguments
   noDebug?: boolean;
   /** Display the quantum circuit diagram while running */
   showCircuit?: boolean;
+  /** Memory, in megabytes, used to record snapshots for stepping back */
+  snapshotMemoryLimit?: number;
 }
@@ -4,7 +4,7 @@
 use std::str::FromStr;
 
 use qsc::fir::StmtId;
-use qsc::interpret::{Debugger, Error, StepAction, StepResult};
+use qsc::interpret::{Debugger, Error, GenericReceiver, StepAction, StepResult};
 use qsc::line_column::Encoding;
 use qsc::{fmt_complex, target::Profile, LanguageFeatures};
 
@@ -114,6 +114,35 @@ impl DebugService {
         self.eval(event_cb, ids, StepAction::Out)
     }
 
+    pub fn enable_reverse_stepping(&mut self, memory_cap: usize) {
+        self.debugger_mut().enable_reverse_stepping(memory_cap);
+    }
+
+    pub fn set_break_on_exceptions(&mut self, enabled: bool) {
+        self.debugger_mut().set_break_on_exceptions(enabled);
+    }
+
+    pub fn get_exception(&self) -> Option<String> {
+        self.debugger()
+            .exception()
+            .map(|errors| render_errors(errors.to_vec()))
+    }
+
+    pub fn step_back(&mut self) -> Result<IStructStepResult, JsValue> {
+        match self.debugger_mut().step_back() {
+            Some(value) => Ok(StructStepResult::from(value).into()),
+            None => Err(JsError::new("No earlier step to return to").into()),
+        }
+    }
+
+    pub fn reverse_continue(&mut self, ids: &[u32]) -> Result<IStructStepResult, JsValue> {
+        let bps: Vec<_> = ids.iter().map(|f| StmtId::from(*f)).collect();
+        match self.debugger_mut().reverse_continue(&bps) {
+            Some(value) => Ok(StructStepResult::from(value).into()),
+            None => Err(JsError::new("No earlier step to return to").into()),
+        }
+    }
+
     fn eval(
         &mut self,
         event_cb: &js_sys::Function,
@@ -192,19 +221,51 @@ impl DebugService {
         .into()
     }
 
-    pub fn get_locals(&self) -> IVariableList {
+    pub fn get_locals(&mut self) -> IVariableList {
         let locals = self.debugger().get_locals();
         let variables: Vec<_> = locals
             .into_iter()
             .map(|local| Variable {
                 name: (*local.name).to_string(),
                 value: local.value.to_string(),
+                qubits: self
+                    .debugger_mut()
+                    .qubit_states(&local.value)
+                    .into_iter()
+                    .map(|(id, state)| QubitVariable {
+                        id,
+                        bloch: vec![state.bloch.0, state.bloch.1, state.bloch.2],
+                        probabilities: vec![state.probabilities.0, state.probabilities.1],
+                        entangled: state.entangled,
+                    })
+                    .collect(),
                 var_type: local.type_name,
             })
             .collect();
         VariableList { variables }.into()
     }
 
+    pub fn eval_watch(&mut self, frame_id: usize, expr: &str) -> Result<String, String> {
+        let mut sink = std::io::sink();
+        let mut out = GenericReceiver::new(&mut sink);
+        match self.debugger_mut().eval_watch(&mut out, frame_id, expr) {
+            Ok(value) => Ok(value.to_string()),
+            Err(errors) => Err(render_errors(errors)),
+        }
+    }
+
+    pub fn set_variable(
+        &mut self,
+        frame_id: usize,
+        name: &str,
+        value_expr: &str,
+    ) -> Result<String, String> {
+        match self.debugger_mut().set_variable(frame_id, name, value_expr) {
+            Ok(value) => Ok(value.to_string()),
+            Err(errors) => Err(render_errors(errors)),
+        }
+    }
+
     fn debugger(&self) -> &Debugger {
         self.debugger
             .as_ref()
@@ -254,6 +315,10 @@ impl From<StepResult> for StructStepResult {
                 id: StepResultId::Return.into(),
                 value: 0,
             },
+            StepResult::Exception(_) => StructStepResult {
+                id: StepResultId::Exception.into(),
+                value: 0,
+            },
         }
     }
 }
@@ -266,6 +331,7 @@ pub enum StepResultId {
     StepIn = 2,
     StepOut = 3,
     Return = 4,
+    Exception = 5,
 }
 
 impl From<StepResultId> for usize {
@@ -352,10 +418,12 @@ serializable_type! {
         pub name: String,
         pub value: String,
         pub var_type: String,
+        pub qubits: Vec<QubitVariable>,
     },
     r#"export interface IVariable {
         name: string;
         value: string;
+        qubits: Array<IQubitVariable>;
         var_type: "Array"
             | "BigInt"
             | "Bool"
@@ -372,6 +440,22 @@ serializable_type! {
     }"#
 }
 
+serializable_type! {
+    QubitVariable,
+    {
+        pub id: usize,
+        pub bloch: Vec<f64>,
+        pub probabilities: Vec<f64>,
+        pub entangled: bool,
+    },
+    r#"export interface IQubitVariable {
+        id: number;
+        bloch: [number, number, number];
+        probabilities: [number, number];
+        entangled: boolean;
+    }"#
+}
+
 serializable_type! {
     QuantumStateList,
     {
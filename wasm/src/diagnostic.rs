@@ -215,10 +215,16 @@ fn interpret_error_labels(err: &interpret::Error) -> Vec<Label> {
         interpret::Error::Eval(e) => error_labels(e.error()),
         interpret::Error::Compile(e) => error_labels(e),
         interpret::Error::Pass(e) => error_labels(e),
+        interpret::Error::PartialEvaluation(e) => error_labels(e),
         interpret::Error::NoEntryPoint
-        | interpret::Error::PartialEvaluation(_)
         | interpret::Error::UnsupportedRuntimeCapabilities
         | interpret::Error::Circuit(_)
-        | interpret::Error::NotAnOperation => Vec::new(),
+        | interpret::Error::Qasm3(_)
+        | interpret::Error::NotAnOperation
+        | interpret::Error::FrameNotFound(_)
+        | interpret::Error::QuantumWatchExpr
+        | interpret::Error::VariableNotFound(_)
+        | interpret::Error::ImmutableVariable(_)
+        | interpret::Error::UnsupportedVariableType(_) => Vec::new(),
     }
 }
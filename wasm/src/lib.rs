@@ -21,7 +21,7 @@ use qsc::{
     LanguageFeatures, PackageStore, PackageType, SourceContents, SourceMap, SourceName, SparseSim,
 };
 use qsc_codegen::qir_base::generate_qir;
-use resource_estimator::{self as re, estimate_entry};
+use resource_estimator::{self as re, estimate_entry, trace_entry};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::{fmt::Write, str::FromStr, sync::Arc};
@@ -76,6 +76,8 @@ pub fn get_qir(
     sources: Vec<js_sys::Array>,
     language_features: Vec<String>,
     profile: &str,
+    optimize_gates: Option<bool>,
+    reuse_qubits: Option<bool>,
 ) -> Result<String, String> {
     let language_features = LanguageFeatures::from_iter(language_features);
     let sources = get_source_map(sources, &None);
@@ -84,13 +86,42 @@ pub fn get_qir(
     if profile == Profile::Unrestricted {
         return Err("Invalid target profile for QIR generation".to_string());
     }
-    if language_features.contains(LanguageFeatures::PreviewQirGen) {
-        qsc::codegen::get_qir(sources, language_features, profile.into())
+    let optimize_gates = optimize_gates.unwrap_or_default();
+    let reuse_qubits = reuse_qubits.unwrap_or_default();
+    // Gate optimization and qubit reuse run on the partially evaluated program, so they always
+    // go through the partial evaluation code generator.
+    if language_features.contains(LanguageFeatures::PreviewQirGen) || optimize_gates || reuse_qubits
+    {
+        qsc::codegen::get_qir(
+            sources,
+            language_features,
+            profile.into(),
+            optimize_gates,
+            reuse_qubits,
+        )
+        .map_err(interpret_errors_into_message)
     } else {
         _get_qir(sources, language_features)
     }
 }
 
+#[wasm_bindgen]
+pub fn get_qasm3(
+    sources: Vec<js_sys::Array>,
+    language_features: Vec<String>,
+    profile: &str,
+) -> Result<String, String> {
+    let language_features = LanguageFeatures::from_iter(language_features);
+    let sources = get_source_map(sources, &None);
+    let profile =
+        Profile::from_str(profile).map_err(|()| format!("Invalid target profile {profile}"))?;
+    if profile == Profile::Unrestricted {
+        return Err("Invalid target profile for OpenQASM generation".to_string());
+    }
+    qsc::codegen::get_qasm3(sources, language_features, profile.into())
+        .map_err(interpret_errors_into_message)
+}
+
 // allows testing without wasm bindings.
 fn _get_qir(sources: SourceMap, language_features: LanguageFeatures) -> Result<String, String> {
     let core = compile::core();
@@ -138,10 +169,48 @@ pub fn get_estimates(
     )
     .map_err(|e| e[0].to_string())?;
 
-    estimate_entry(&mut interpreter, params).map_err(|e| match &e[0] {
+    let estimates = estimate_entry(&mut interpreter, params).map_err(|e| match &e[0] {
         re::Error::Interpreter(interpret::Error::Eval(e)) => e.to_string(),
         re::Error::Interpreter(_) => unreachable!("interpreter errors should be eval errors"),
+        re::Error::Qir(_) => unreachable!("QIR errors should not occur for Q# sources"),
         re::Error::Estimation(e) => e.to_string(),
+    })?;
+
+    // Batches are estimated into an object with the results and their Pareto
+    // frontier, but callers expect an array of results for all parameters.
+    if re::system::is_batch_specification(params) {
+        let mut batch: serde_json::Value =
+            serde_json::from_str(&estimates).expect("estimates should be valid JSON");
+        Ok(batch["results"].take().to_string())
+    } else {
+        Ok(estimates)
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_logical_trace(
+    sources: Vec<js_sys::Array>,
+    language_features: Vec<String>,
+) -> Result<String, String> {
+    let sources = get_source_map(sources, &None);
+
+    let language_features = LanguageFeatures::from_iter(language_features);
+
+    let mut interpreter = interpret::Interpreter::new(
+        true,
+        sources,
+        PackageType::Exe,
+        Profile::Unrestricted.into(),
+        language_features,
+    )
+    .map_err(|e| e[0].to_string())?;
+
+    trace_entry(&mut interpreter).map_err(|e| match &e[0] {
+        re::Error::Interpreter(interpret::Error::Eval(e)) => e.to_string(),
+        re::Error::Interpreter(_) => unreachable!("interpreter errors should be eval errors"),
+        re::Error::Qir(_) | re::Error::Estimation(_) => {
+            unreachable!("only interpreter errors should occur when tracing Q# sources")
+        }
     })
 }
 
@@ -152,6 +221,7 @@ pub fn get_circuit(
     language_features: Vec<String>,
     simulate: bool,
     operation: Option<IOperationInfo>,
+    operation_depth: Option<usize>,
 ) -> Result<JsValue, String> {
     let sources = get_source_map(sources, &None);
     let target_profile = Profile::from_str(targetProfile).expect("invalid target profile");
@@ -172,6 +242,7 @@ pub fn get_circuit(
         }
         None => CircuitEntryPoint::EntryPoint,
     };
+    interpreter.set_circuit_operation_depth(operation_depth.unwrap_or_default());
 
     let circuit = interpreter
         .circuit(entry_point, simulate)
@@ -186,6 +257,24 @@ fn interpret_errors_into_vs_diagnostics_json(errs: Vec<qsc::interpret::Error>) -
         .expect("serializing errors to json should succeed")
 }
 
+/// Formats errors as plain text, one per line, prefixed with the location they point to.
+#[allow(clippy::needless_pass_by_value)]
+fn interpret_errors_into_message(errs: Vec<qsc::interpret::Error>) -> String {
+    interpret_errors_into_vs_diagnostics(&errs)
+        .into_iter()
+        .map(|(doc, diag, _)| {
+            let start = diag.range.start;
+            format!(
+                "{doc}:{}:{}: {}",
+                start.line + 1,
+                start.character + 1,
+                diag.message
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[wasm_bindgen]
 #[must_use]
 pub fn get_library_source_content(name: &str) -> Option<String> {
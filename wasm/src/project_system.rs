@@ -252,6 +252,9 @@ impl ProjectLoader {
                     .map_or_else(
                         |_| js_sys::Array::new(),
                         |proj| {
+                            for error in &proj.errors {
+                                log::error!("failed to load project file: {error:?}");
+                            }
                             proj.sources
                                 .into_iter()
                                 .map(|(path, contents)| {